@@ -0,0 +1,5 @@
+fn main() {
+    // gRPC stubs are generated only with the grpc feature. protoc is required
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/nrev.proto").expect("Failed to compile proto/nrev.proto");
+}
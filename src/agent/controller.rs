@@ -0,0 +1,253 @@
+use super::{AgentEvent, SignedJob, SIGNATURE_HEADER};
+use crate::host::Host;
+use crate::output;
+use crate::serve::{JobRequest, JobStatus};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+/// Run of the job on one agent
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgentRun {
+    pub agent: String,
+    pub status: JobStatus,
+    pub started_at: DateTime<Local>,
+    pub finished_at: DateTime<Local>,
+    pub error: Option<String>,
+    /// Verified JSON result of the agent
+    pub result: Option<Value>,
+}
+
+/// Open port and the agents that saw it open
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VantagePort {
+    pub number: u16,
+    pub open_from: Vec<String>,
+}
+
+/// Host found by at least one agent
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VantageHost {
+    pub ip_addr: IpAddr,
+    pub seen_by: Vec<String>,
+    pub ports: Vec<VantagePort>,
+}
+
+/// Results of one job from all agents, merged by host
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ControllerReport {
+    pub job: JobRequest,
+    pub agents: Vec<AgentRun>,
+    /// Hosts and open ports of port/host scans by vantage point
+    pub hosts: Vec<VantageHost>,
+}
+
+/// host:port of the agent. Accepts http://host:port and host without port
+pub fn agent_addr(agent: &str) -> String {
+    let addr: &str = agent.trim_start_matches("http://").trim_end_matches('/');
+    let has_port: bool = match addr.rsplit_once(':') {
+        // IPv6 literal without brackets has no port
+        Some((host, port)) => {
+            port.parse::<u16>().is_ok() && (!host.contains(':') || host.ends_with(']'))
+        }
+        None => false,
+    };
+    if has_port {
+        addr.to_string()
+    } else if addr.contains(':') && !addr.starts_with('[') {
+        format!("[{}]:{}", addr, crate::config::DEFAULT_AGENT_PORT)
+    } else {
+        format!("{}:{}", addr, crate::config::DEFAULT_AGENT_PORT)
+    }
+}
+
+/// Send the signed job and read events until the result
+fn run_on_agent(agent: &str, key: &str, job: &JobRequest) -> Result<Value, String> {
+    let addr: String = agent_addr(agent);
+    let socket_addr: SocketAddr = match addr.to_socket_addrs() {
+        Ok(mut addrs) => match addrs.next() {
+            Some(socket_addr) => socket_addr,
+            None => return Err(format!("Failed to resolve {}", addr)),
+        },
+        Err(e) => return Err(format!("Failed to resolve {}: {}", addr, e)),
+    };
+    let mut stream: TcpStream = TcpStream::connect_timeout(
+        &socket_addr,
+        Duration::from_millis(crate::config::DEFAULT_AGENT_CONNECT_TIMEOUT_MILLIS),
+    )
+    .map_err(|e| format!("Failed to connect to {}: {}", socket_addr, e))?;
+    // Agents send a heartbeat while the job runs
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(
+        crate::config::DEFAULT_AGENT_HEARTBEAT_SECS * 4,
+    )));
+    let signed_job = SignedJob {
+        job: job.clone(),
+        issued_at: Local::now().timestamp(),
+        nonce: uuid::Uuid::new_v4().to_string(),
+    };
+    let body: String = serde_json::to_string(&signed_job).unwrap();
+    let request: String = format!(
+        "POST /jobs HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n{}: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        addr,
+        SIGNATURE_HEADER,
+        super::sign(key, body.as_bytes()),
+        body.len(),
+        body
+    );
+    stream
+        .write_all(request.as_bytes())
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("Failed to send job: {}", e))?;
+    let mut reader = BufReader::new(stream);
+    let mut status_line: String = String::new();
+    reader
+        .read_line(&mut status_line)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let status: u16 = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or(format!("Invalid status line: {}", status_line.trim()))?;
+    loop {
+        let mut line: String = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) if line.trim().is_empty() => break,
+            Ok(_) => {}
+            Err(e) => return Err(format!("Failed to read response: {}", e)),
+        }
+    }
+    if status != 200 {
+        // {"error": message}
+        let mut body: String = String::new();
+        let _ = reader.read_to_string(&mut body);
+        let value: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+        return Err(format!(
+            "HTTP {}: {}",
+            status,
+            value["error"].as_str().unwrap_or(body.trim())
+        ));
+    }
+    for line in reader.lines() {
+        let line: String = line.map_err(|e| format!("Lost connection: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: AgentEvent =
+            serde_json::from_str(&line).map_err(|e| format!("Invalid event from agent: {}", e))?;
+        match event {
+            AgentEvent::Accepted { agent: name } => {
+                output::log_with_time(&format!("[{}] Accepted by {}", agent, name), "INFO")
+            }
+            AgentEvent::Started => output::log_with_time(&format!("[{}] Started", agent), "INFO"),
+            AgentEvent::Heartbeat => {}
+            AgentEvent::Result { result, signature } => {
+                if !super::verify_result(key, &signed_job.nonce, &result, &signature) {
+                    return Err("Invalid result signature".to_string());
+                }
+                return Ok(result);
+            }
+            AgentEvent::Error { error } => return Err(error),
+        }
+    }
+    Err("Agent closed the connection before the result".to_string())
+}
+
+/// Hosts in a port or host scan result
+fn result_hosts(command: &str, result: &Value) -> Vec<Host> {
+    match command {
+        "port" => serde_json::from_value::<Host>(result["host"].clone())
+            .map(|host| vec![host])
+            .unwrap_or_default(),
+        "host" => serde_json::from_value::<Vec<Host>>(result["hosts"].clone()).unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn vantage_hosts(command: &str, runs: &[AgentRun]) -> Vec<VantageHost> {
+    let mut hosts: BTreeMap<IpAddr, (BTreeSet<String>, BTreeMap<u16, Vec<String>>)> =
+        BTreeMap::new();
+    for run in runs {
+        let result: &Value = match &run.result {
+            Some(result) => result,
+            None => continue,
+        };
+        for host in result_hosts(command, result) {
+            let entry = hosts.entry(host.ip_addr).or_default();
+            entry.0.insert(run.agent.clone());
+            for port in host.get_open_port_numbers() {
+                entry.1.entry(port).or_default().push(run.agent.clone());
+            }
+        }
+    }
+    hosts
+        .into_iter()
+        .map(|(ip_addr, (seen_by, ports))| VantageHost {
+            ip_addr: ip_addr,
+            seen_by: seen_by.into_iter().collect(),
+            ports: ports
+                .into_iter()
+                .map(|(number, open_from)| VantagePort {
+                    number: number,
+                    open_from: open_from,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Run the job on all agents at once and merge the results
+pub fn dispatch(agents: &[String], key: &str, job: &JobRequest) -> ControllerReport {
+    let handles: Vec<_> = agents
+        .iter()
+        .map(|agent| {
+            let agent: String = agent.clone();
+            let key: String = key.to_string();
+            let job: JobRequest = job.clone();
+            thread::spawn(move || {
+                let started_at: DateTime<Local> = Local::now();
+                let result: Result<Value, String> = run_on_agent(&agent, &key, &job);
+                match &result {
+                    Ok(_) => output::log_with_time(&format!("[{}] Done", agent), "INFO"),
+                    Err(e) => output::log_with_time(&format!("[{}] {}", agent, e), "ERROR"),
+                }
+                AgentRun {
+                    agent: agent,
+                    status: if result.is_ok() {
+                        JobStatus::Done
+                    } else {
+                        JobStatus::Failed
+                    },
+                    started_at: started_at,
+                    finished_at: Local::now(),
+                    error: result.as_ref().err().cloned(),
+                    result: result.ok(),
+                }
+            })
+        })
+        .collect();
+    let runs: Vec<AgentRun> = handles
+        .into_iter()
+        .zip(agents)
+        .map(|(handle, agent)| {
+            handle.join().unwrap_or_else(|_| AgentRun {
+                agent: agent.clone(),
+                status: JobStatus::Failed,
+                started_at: Local::now(),
+                finished_at: Local::now(),
+                error: Some("Controller thread panicked".to_string()),
+                result: None,
+            })
+        })
+        .collect();
+    ControllerReport {
+        job: job.clone(),
+        hosts: vantage_hosts(&job.command, &runs),
+        agents: runs,
+    }
+}
@@ -0,0 +1,297 @@
+pub mod controller;
+
+use crate::output;
+use crate::serve::http::{self, Request, Response};
+use crate::serve::JobRequest;
+use chrono::Local;
+use clap::ArgMatches;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Header carrying the hex HMAC-SHA256 of the request body
+pub const SIGNATURE_HEADER: &str = "X-Nrev-Signature";
+/// Environment variable of the shared key of agents and the controller
+pub const AGENT_KEY_ENV: &str = "NREV_AGENT_KEY";
+/// Jobs issued more than this many seconds before or after the agent clock are rejected
+const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+/// Body of POST /jobs to an agent. Signed with the shared key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedJob {
+    pub job: JobRequest,
+    /// Unix time the controller issued the job
+    pub issued_at: i64,
+    /// Random per job. A nonce is accepted once
+    pub nonce: String,
+}
+
+/// One line of the NDJSON stream an agent sends back while running a job
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum AgentEvent {
+    /// Job verified. Waiting for the running job to finish if any
+    Accepted {
+        agent: String,
+    },
+    Started,
+    /// Sent while waiting, so the controller can tell a slow scan from a dead agent
+    Heartbeat,
+    /// JSON result of the job. `signature` is result_signature() of the nonce and result
+    Result {
+        result: Value,
+        signature: String,
+    },
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct AgentSetting {
+    pub listen_addr: SocketAddr,
+    /// Name reported to the controller
+    pub name: String,
+    pub key: String,
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Hex HMAC-SHA256 of the data
+pub fn sign(key: &str, data: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes());
+    to_hex(hmac::sign(&key, data).as_ref())
+}
+
+/// Check the hex HMAC-SHA256 in constant time
+pub fn verify(key: &str, data: &[u8], signature: &str) -> bool {
+    let tag: Vec<u8> = match from_hex(signature.trim()) {
+        Some(tag) => tag,
+        None => return false,
+    };
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes());
+    hmac::verify(&key, data, &tag).is_ok()
+}
+
+/// Signature of a result. Bound to the job nonce, so results can not be replayed to another job
+pub fn result_signature(key: &str, nonce: &str, result: &Value) -> String {
+    sign(key, format!("{}\n{}", nonce, result).as_bytes())
+}
+
+pub fn verify_result(key: &str, nonce: &str, result: &Value, signature: &str) -> bool {
+    verify(key, format!("{}\n{}", nonce, result).as_bytes(), signature)
+}
+
+/// Nonces seen within the clock skew window
+type NonceStore = Arc<Mutex<HashMap<String, i64>>>;
+
+/// Verify the signature, age and nonce of the job
+fn verify_job(request: &Request, key: &str, nonces: &NonceStore) -> Result<SignedJob, Response> {
+    let signature: &str = match request.header(SIGNATURE_HEADER) {
+        Some(signature) => signature,
+        None => return Err(Response::error(401, "Missing signature")),
+    };
+    if !verify(key, &request.body, signature) {
+        return Err(Response::error(401, "Invalid signature"));
+    }
+    let signed_job: SignedJob = serde_json::from_slice(&request.body)
+        .map_err(|e| Response::error(400, &format!("Invalid job: {}", e)))?;
+    let now: i64 = Local::now().timestamp();
+    if (now - signed_job.issued_at).abs() > MAX_CLOCK_SKEW_SECS {
+        return Err(Response::error(
+            401,
+            "Job expired. Check the clocks of the agent and controller",
+        ));
+    }
+    let mut nonces = nonces
+        .lock()
+        .map_err(|_| Response::error(500, "Nonce store unavailable"))?;
+    nonces.retain(|_, issued_at| (now - *issued_at).abs() <= MAX_CLOCK_SKEW_SECS);
+    if nonces.contains_key(&signed_job.nonce) {
+        return Err(Response::error(409, "Replayed job"));
+    }
+    nonces.insert(signed_job.nonce.clone(), signed_job.issued_at);
+    Ok(signed_job)
+}
+
+fn write_event(stream: &mut TcpStream, event: &AgentEvent) -> bool {
+    let line: String = format!("{}\n", serde_json::to_string(event).unwrap());
+    stream
+        .write_all(line.as_bytes())
+        .and_then(|_| stream.flush())
+        .is_ok()
+}
+
+/// Progress of the job thread
+enum JobProgress {
+    Started,
+    Finished(bool),
+}
+
+/// Run the job and stream events. Jobs run one at a time
+fn run_signed_job(
+    mut stream: TcpStream,
+    setting: &AgentSetting,
+    signed_job: SignedJob,
+    job_lock: &Arc<Mutex<()>>,
+) {
+    let id: String = uuid::Uuid::new_v4().to_string();
+    let result_path: PathBuf = std::env::temp_dir().join(format!("nrev-agent-{}.json", id));
+    let matches: ArgMatches = match crate::serve::parse_job(&signed_job.job, &result_path) {
+        Ok(matches) => matches,
+        Err(e) => {
+            http::write_response(&mut stream, &Response::error(400, &e));
+            return;
+        }
+    };
+    if http::write_stream_head(&mut stream, 200, "application/x-ndjson").is_err() {
+        return;
+    }
+    write_event(
+        &mut stream,
+        &AgentEvent::Accepted {
+            agent: setting.name.clone(),
+        },
+    );
+    output::log_with_time(
+        &format!(
+            "Job {} accepted: {} {}",
+            id, signed_job.job.command, signed_job.job.target
+        ),
+        "INFO",
+    );
+    let (tx, rx): (Sender<JobProgress>, Receiver<JobProgress>) = channel();
+    let command: String = signed_job.job.command.clone();
+    let job_lock: Arc<Mutex<()>> = job_lock.clone();
+    // The job finishes even if the controller goes away
+    thread::spawn(move || {
+        let _guard = job_lock.lock();
+        let _ = tx.send(JobProgress::Started);
+        let completed: bool = crate::serve::run_job(&command, &matches);
+        let _ = tx.send(JobProgress::Finished(completed));
+    });
+    let heartbeat: Duration = Duration::from_secs(crate::config::DEFAULT_AGENT_HEARTBEAT_SECS);
+    let completed: bool = loop {
+        match rx.recv_timeout(heartbeat) {
+            Ok(JobProgress::Started) => {
+                write_event(&mut stream, &AgentEvent::Started);
+            }
+            Ok(JobProgress::Finished(completed)) => break completed,
+            Err(RecvTimeoutError::Timeout) => {
+                write_event(&mut stream, &AgentEvent::Heartbeat);
+            }
+            Err(RecvTimeoutError::Disconnected) => break false,
+        }
+    };
+    let result: Result<Value, String> = if !completed {
+        Err("Job panicked. See the agent log".to_string())
+    } else {
+        match std::fs::read_to_string(&result_path) {
+            Ok(text) => serde_json::from_str::<Value>(&text)
+                .map_err(|e| format!("Failed to parse result: {}", e)),
+            Err(_) => Err("Finished without a result. See the agent log".to_string()),
+        }
+    };
+    let _ = std::fs::remove_file(&result_path);
+    output::log_with_time(
+        &format!(
+            "Job {} {}",
+            id,
+            if result.is_ok() { "done" } else { "failed" }
+        ),
+        "INFO",
+    );
+    let event: AgentEvent = match result {
+        Ok(result) => AgentEvent::Result {
+            signature: result_signature(&setting.key, &signed_job.nonce, &result),
+            result: result,
+        },
+        Err(e) => AgentEvent::Error { error: e },
+    };
+    write_event(&mut stream, &event);
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    setting: &AgentSetting,
+    nonces: &NonceStore,
+    job_lock: &Arc<Mutex<()>>,
+) {
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(
+        crate::config::DEFAULT_SERVE_READ_TIMEOUT_MILLIS,
+    )));
+    let request: Request = match http::read_request(&stream) {
+        Ok(request) => request,
+        Err(response) => {
+            http::write_response(&mut stream, &response);
+            return;
+        }
+    };
+    let response: Response = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/version") => Response::json(
+            200,
+            serde_json::json!({ "name": crate::app::CRATE_BIN_NAME, "version": clap::crate_version!(), "agent": setting.name })
+                .to_string(),
+        ),
+        ("POST", "/jobs") => match verify_job(&request, &setting.key, nonces) {
+            Ok(signed_job) => {
+                if let Ok(peer_addr) = stream.peer_addr() {
+                    output::log_with_time(&format!("Job from {}", peer_addr), "INFO");
+                }
+                run_signed_job(stream, setting, signed_job, job_lock);
+                return;
+            }
+            Err(response) => {
+                if let Ok(peer_addr) = stream.peer_addr() {
+                    output::log_with_time(
+                        &format!("Rejected job from {}: {}", peer_addr, response.body),
+                        "WARN",
+                    );
+                }
+                response
+            }
+        },
+        (_, "/version") | (_, "/jobs") => Response::error(405, "Method not allowed"),
+        _ => Response::error(404, "Not found"),
+    };
+    http::write_response(&mut stream, &response);
+}
+
+/// Accept signed jobs until the process is stopped
+pub fn serve(setting: AgentSetting) -> Result<(), String> {
+    let listener: TcpListener = TcpListener::bind(setting.listen_addr)
+        .map_err(|e| format!("Failed to listen on {}: {}", setting.listen_addr, e))?;
+    let nonces: NonceStore = Arc::new(Mutex::new(HashMap::new()));
+    let job_lock: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
+    for stream in listener.incoming() {
+        let stream: TcpStream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let setting: AgentSetting = setting.clone();
+        let nonces: NonceStore = nonces.clone();
+        let job_lock: Arc<Mutex<()>> = job_lock.clone();
+        thread::spawn(move || handle_connection(stream, &setting, &nonces, &job_lock));
+    }
+    Ok(())
+}
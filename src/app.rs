@@ -1,6 +1,6 @@
 use crate::sys;
 use clap::{crate_description, crate_name, crate_version};
-use std::sync::{Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock};
 
 // APP information
 pub const CRATE_BIN_NAME: &str = "nrev";
@@ -32,13 +32,101 @@ pub fn set_quiet_mode(enabled: bool) -> Result<(), String> {
     }
 }
 
+/// Stop handle of the command handling Ctrl-C. None if Ctrl-C exits the process
+static STOP: Mutex<Option<Arc<Mutex<bool>>>> = Mutex::new(None);
+
+/// Stop handle registered for Ctrl-C while the guard is alive.
+/// Each guard has its own stop flag, so a stop does not carry over to the next run
+pub struct StopGuard {
+    stop_handle: Arc<Mutex<bool>>,
+}
+
+impl StopGuard {
+    /// Get stop handle
+    pub fn get_stop_handle(&self) -> Arc<Mutex<bool>> {
+        self.stop_handle.clone()
+    }
+    /// Check if stop was requested by Ctrl-C
+    pub fn is_stopped(&self) -> bool {
+        match self.stop_handle.lock() {
+            Ok(stop) => *stop,
+            Err(_) => false,
+        }
+    }
+}
+
+impl Drop for StopGuard {
+    fn drop(&mut self) {
+        if let Ok(mut current) = STOP.lock() {
+            if current.as_ref().map_or(false, |stop_handle| {
+                Arc::ptr_eq(stop_handle, &self.stop_handle)
+            }) {
+                *current = None;
+            }
+        }
+    }
+}
+
+/// Let the caller stop on Ctrl-C instead of exiting the process, until the guard is dropped
+pub fn handle_stop() -> StopGuard {
+    let stop_handle: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
+    if let Ok(mut current) = STOP.lock() {
+        *current = Some(stop_handle.clone());
+    }
+    StopGuard {
+        stop_handle: stop_handle,
+    }
+}
+
+/// Install the Ctrl-C handler. The process exits unless the running command
+/// handles the stop flag, or when Ctrl-C is pressed again.
+pub fn set_ctrlc_handler() -> Result<(), String> {
+    match ctrlc::set_handler(move || {
+        let stop_handle: Option<Arc<Mutex<bool>>> = match STOP.lock() {
+            Ok(current) => current.clone(),
+            Err(_) => None,
+        };
+        match stop_handle {
+            Some(stop_handle) => match stop_handle.lock() {
+                Ok(mut stop) if !*stop => *stop = true,
+                _ => std::process::exit(130),
+            },
+            None => std::process::exit(130),
+        }
+    }) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
 pub enum AppCommands {
     PortScan,
     HostScan,
     Ping,
     Trace,
     Subdomain,
+    Dns,
+    Rdns,
+    Mdns,
     Neighbor,
+    Wol,
+    MyIp,
+    Whois,
+    Rdap,
+    Geo,
+    Http,
+    Tls,
+    Snmp,
+    Ntp,
+    Capture,
+    Passive,
+    Serve,
+    Grpc,
+    Exporter,
+    Schedule,
+    Import,
+    Agent,
+    Controller,
     Interfaces,
     Interface,
     CheckDependencies,
@@ -52,7 +140,28 @@ impl AppCommands {
             "ping" => Some(AppCommands::Ping),
             "trace" => Some(AppCommands::Trace),
             "subdomain" => Some(AppCommands::Subdomain),
+            "dns" => Some(AppCommands::Dns),
+            "rdns" => Some(AppCommands::Rdns),
+            "mdns" => Some(AppCommands::Mdns),
             "nei" => Some(AppCommands::Neighbor),
+            "wol" => Some(AppCommands::Wol),
+            "myip" => Some(AppCommands::MyIp),
+            "whois" => Some(AppCommands::Whois),
+            "rdap" => Some(AppCommands::Rdap),
+            "geo" => Some(AppCommands::Geo),
+            "http" => Some(AppCommands::Http),
+            "tls" => Some(AppCommands::Tls),
+            "snmp" => Some(AppCommands::Snmp),
+            "ntp" => Some(AppCommands::Ntp),
+            "capture" => Some(AppCommands::Capture),
+            "passive" => Some(AppCommands::Passive),
+            "serve" => Some(AppCommands::Serve),
+            "grpc" => Some(AppCommands::Grpc),
+            "exporter" => Some(AppCommands::Exporter),
+            "schedule" => Some(AppCommands::Schedule),
+            "import" => Some(AppCommands::Import),
+            "agent" => Some(AppCommands::Agent),
+            "controller" => Some(AppCommands::Controller),
             "interfaces" => Some(AppCommands::Interfaces),
             "interface" => Some(AppCommands::Interface),
             "check" => Some(AppCommands::CheckDependencies),
@@ -3,8 +3,60 @@ pub const DEFAULT_LOCAL_UDP_PORT: u16 = 53445;
 pub const DEFAULT_BASE_TARGET_UDP_PORT: u16 = 33435;
 pub const DEFAULT_HOP_LIMIT: u8 = 64;
 pub const DEFAULT_PING_COUNT: u32 = 4;
+pub const MIN_PING_INTERVAL_MILLIS: u64 = 10;
+pub const MIN_UNPRIVILEGED_PING_INTERVAL_MILLIS: u64 = 200;
+pub const FLOOD_PING_INTERVAL_MILLIS: u64 = 10;
+pub const RTT_GRAPH_WIDTH: usize = 60;
+pub const MAX_TRACE_FLOWS: usize = 64;
+pub const MAX_TRACE_QUERIES: u8 = 10;
+pub const DEFAULT_TRACE_DNS_TIMEOUT_MILLIS: u64 = 500;
+pub const DEFAULT_RTT_DELTA_THRESHOLD_MILLIS: u64 = 20;
 pub const DEFAULT_HOSTS_CONCURRENCY: usize = 50;
 pub const DEFAULT_PORTS_CONCURRENCY: usize = 100;
+pub const DEFAULT_DNS_CONCURRENCY: usize = 10;
+pub const DEFAULT_RDNS_CONCURRENCY: usize = 50;
+pub const DEFAULT_RDNS_TIMEOUT_MILLIS: u64 = 2000;
+pub const DEFAULT_HTTP_PROBE_CONCURRENCY: usize = 50;
+pub const DEFAULT_HTTP_PROBE_TIMEOUT_MILLIS: u64 = 10000;
+pub const DEFAULT_HTTP_MAX_REDIRECTS: usize = 10;
+/// Max size of HTTP response read from a server, including headers
+pub const MAX_HTTP_RESPONSE_SIZE: usize = 16 * 1024 * 1024;
+pub const DEFAULT_SNMP_CONCURRENCY: usize = 50;
+/// Agents drop requests with an unknown community. Each one costs this long
+pub const DEFAULT_SNMP_TIMEOUT_MILLIS: u64 = 1000;
+pub const DEFAULT_SMB_TIMEOUT_MILLIS: u64 = 3000;
+pub const DEFAULT_SSH_TIMEOUT_MILLIS: u64 = 5000;
+pub const DEFAULT_NTP_CONCURRENCY: usize = 50;
+pub const DEFAULT_NTP_TIMEOUT_MILLIS: u64 = 2000;
+/// NVD API responses list every CVE of a product
+pub const DEFAULT_CVE_API_TIMEOUT_MILLIS: u64 = 30000;
+pub const DEFAULT_SERVE_LISTEN_ADDR: &str = "127.0.0.1:8080";
+pub const DEFAULT_GRPC_LISTEN_ADDR: &str = "127.0.0.1:50051";
+pub const DEFAULT_AGENT_LISTEN_ADDR: &str = "127.0.0.1:7878";
+pub const DEFAULT_AGENT_PORT: u16 = 7878;
+pub const DEFAULT_AGENT_CONNECT_TIMEOUT_MILLIS: u64 = 10000;
+/// Agents send a heartbeat at this interval while a job runs
+pub const DEFAULT_AGENT_HEARTBEAT_SECS: u64 = 15;
+pub const DEFAULT_EXPORTER_LISTEN_ADDR: &str = "127.0.0.1:9725";
+pub const DEFAULT_EXPORTER_INTERVAL_SECS: u64 = 60;
+pub const DEFAULT_EXPORTER_TIMEOUT_MILLIS: u64 = 1000;
+pub const DEFAULT_EXPORT_TIMEOUT_MILLIS: u64 = 30000;
+pub const DEFAULT_ES_INDEX_PREFIX: &str = "nrev";
+pub const DEFAULT_HEC_BATCH_SIZE: usize = 100;
+pub const DEFAULT_WEBHOOK_TIMEOUT_MILLIS: u64 = 10000;
+pub const DEFAULT_ENRICH_TIMEOUT_MILLIS: u64 = 10000;
+pub const DEFAULT_MQTT_TOPIC: &str = "nrev/results";
+pub const DEFAULT_MQTT_TIMEOUT_MILLIS: u64 = 10000;
+pub const DEFAULT_SERVE_READ_TIMEOUT_MILLIS: u64 = 10000;
+/// Ping jobs can not be interrupted. Count and deadline are always set and capped
+pub const DEFAULT_JOB_PING_COUNT: u32 = 4;
+pub const MAX_JOB_PING_COUNT: u32 = 1000;
+pub const MAX_JOB_PING_DEADLINE_SECS: u64 = 600;
+/// mDNS responders answer with random delays. Listen this long
+pub const DEFAULT_MDNS_TIMEOUT_MILLIS: u64 = 3000;
+pub const SMALL_SUBDOMAIN_WORDLIST_SIZE: usize = 100;
+/// Names looked up per batch. Progress is checkpointed at batch boundaries
+pub const SUBDOMAIN_BATCH_SIZE: usize = 1000;
 pub const PCAP_WAIT_TIME_MILLIS: u64 = 10;
 
 // Database
@@ -18,5 +70,6 @@ pub const OS_FAMILY_BIN: &[u8] = include_bytes!("../../resources/ndb-os-family.b
 pub const OUI_BIN: &[u8] = include_bytes!("../../resources/ndb-oui.bin");
 pub const OUI_VM_BIN: &[u8] = include_bytes!("../../resources/ndb-oui-vm.bin");
 pub const SUBDOMAIN_BIN: &[u8] = include_bytes!("../../resources/ndb-subdomain.bin");
+pub const SUBDOMAIN_LARGE_BIN: &[u8] = include_bytes!("../../resources/ndb-subdomain-large.bin");
 pub const TCP_SERVICE_BIN: &[u8] = include_bytes!("../../resources/ndb-tcp-service.bin");
 pub const WELLKNOWN_PORTS_BIN: &[u8] = include_bytes!("../../resources/ndb-wellknown-ports.bin");
@@ -0,0 +1,370 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const NVD_API_URL: &str = "https://services.nvd.nist.gov/rest/json/cves/2.0";
+/// Environment variable of the NVD API key. Raises the rate limit
+const NVD_API_KEY_ENV: &str = "NVD_API_KEY";
+
+/// Products recognized in service banners and Server headers.
+/// (token followed by the version, CPE vendor, CPE product)
+const PRODUCT_TOKENS: [(&str, &str, &str); 16] = [
+    ("OpenSSH_", "openbsd", "openssh"),
+    ("dropbear_", "dropbear_ssh_project", "dropbear_ssh"),
+    ("Apache/", "apache", "http_server"),
+    ("nginx/", "f5", "nginx"),
+    (
+        "Microsoft-IIS/",
+        "microsoft",
+        "internet_information_services",
+    ),
+    ("lighttpd/", "lighttpd", "lighttpd"),
+    ("OpenSSL/", "openssl", "openssl"),
+    ("PHP/", "php", "php"),
+    ("Jetty(", "eclipse", "jetty"),
+    ("squid/", "squid-cache", "squid"),
+    ("MiniServ/", "webmin", "webmin"),
+    ("Werkzeug/", "palletsprojects", "werkzeug"),
+    ("gunicorn/", "gunicorn", "gunicorn"),
+    ("vsFTPd ", "vsftpd_project", "vsftpd"),
+    ("ProFTPD ", "proftpd", "proftpd"),
+    ("Exim ", "exim", "exim"),
+];
+
+/// Known vulnerability of a detected product
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CveInfo {
+    /// e.g. CVE-2023-38408
+    pub id: String,
+    /// CPE of the detected product
+    pub cpe: String,
+    /// CVSS base score (v3.1, v3.0 or v2 in this order)
+    pub score: Option<f64>,
+    pub severity: String,
+    pub description: String,
+}
+
+impl CveInfo {
+    /// Label for display. e.g. CVE-2023-38408 9.8 CRITICAL
+    pub fn label(&self) -> String {
+        match self.score {
+            Some(score) => format!("{} {:.1} {}", self.id, score, self.severity),
+            None => self.id.clone(),
+        }
+    }
+}
+
+/// Vulnerable configuration of a CVE
+#[derive(Clone, Debug)]
+struct CpeMatch {
+    vendor: String,
+    product: String,
+    /// Exact version, or * with a range
+    version: String,
+    start_including: Option<String>,
+    start_excluding: Option<String>,
+    end_including: Option<String>,
+    end_excluding: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+struct CveEntry {
+    id: String,
+    score: Option<f64>,
+    severity: String,
+    description: String,
+    matches: Vec<CpeMatch>,
+}
+
+/// CPEs of the products in a service banner or Server header
+pub fn service_cpes(service_version: &str) -> Vec<String> {
+    let mut cpes: Vec<String> = Vec::new();
+    for (token, vendor, product) in PRODUCT_TOKENS {
+        let rest: &str = match service_version.find(token) {
+            Some(i) => &service_version[i + token.len()..],
+            None => continue,
+        };
+        let version: String = rest
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '.')
+            .collect();
+        // 8.9p1 is version 8.9, update p1
+        let split: usize = version
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+        let (number, update) = version.split_at(split);
+        let number: &str = number.trim_end_matches('.');
+        if number.is_empty() {
+            continue;
+        }
+        let update: &str = if update.is_empty() { "*" } else { update };
+        cpes.push(format!(
+            "cpe:2.3:a:{}:{}:{}:{}:*:*:*:*:*:*",
+            vendor, product, number, update
+        ));
+    }
+    cpes
+}
+
+/// Compare dotted versions numerically where possible (e.g. 2.4.9 < 2.4.10)
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let a_parts: Vec<&str> = a.split(|c| c == '.' || c == '-').collect();
+    let b_parts: Vec<&str> = b.split(|c| c == '.' || c == '-').collect();
+    for i in 0..std::cmp::max(a_parts.len(), b_parts.len()) {
+        let a_part: &str = a_parts.get(i).copied().unwrap_or("0");
+        let b_part: &str = b_parts.get(i).copied().unwrap_or("0");
+        let ordering: Ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+            (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+            _ => a_part.cmp(b_part),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+impl CpeMatch {
+    fn matches(&self, vendor: &str, product: &str, version: &str) -> bool {
+        if self.vendor != vendor || self.product != product {
+            return false;
+        }
+        if self.version != "*" && self.version != "-" {
+            return compare_versions(&self.version, version) == Ordering::Equal;
+        }
+        let bounds: [(&Option<String>, fn(Ordering) -> bool); 4] = [
+            (&self.start_including, |o| o != Ordering::Less),
+            (&self.start_excluding, |o| o == Ordering::Greater),
+            (&self.end_including, |o| o != Ordering::Greater),
+            (&self.end_excluding, |o| o == Ordering::Less),
+        ];
+        bounds.iter().all(|(bound, check)| match bound {
+            Some(bound) => check(compare_versions(version, bound)),
+            None => true,
+        })
+    }
+}
+
+fn parse_cpe_match(value: &Value) -> Option<CpeMatch> {
+    if value.get("vulnerable").and_then(|v| v.as_bool()) == Some(false) {
+        return None;
+    }
+    let criteria: &str = value.get("criteria")?.as_str()?;
+    let fields: Vec<&str> = criteria.split(':').collect();
+    if fields.len() < 6 {
+        return None;
+    }
+    let bound = |name: &str| -> Option<String> {
+        value
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+    };
+    Some(CpeMatch {
+        vendor: fields[3].to_string(),
+        product: fields[4].to_string(),
+        version: fields[5].to_string(),
+        start_including: bound("versionStartIncluding"),
+        start_excluding: bound("versionStartExcluding"),
+        end_including: bound("versionEndIncluding"),
+        end_excluding: bound("versionEndExcluding"),
+    })
+}
+
+/// Collect cpeMatch objects of configuration nodes, including nested children
+fn collect_matches(value: &Value, matches: &mut Vec<CpeMatch>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(cpe_matches)) = map.get("cpeMatch") {
+                matches.extend(cpe_matches.iter().filter_map(parse_cpe_match));
+            }
+            for (key, child) in map {
+                if key != "cpeMatch" {
+                    collect_matches(child, matches);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for child in values {
+                collect_matches(child, matches);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parse NVD CVE JSON 2.0 (feed file or API response)
+fn parse_nvd_json(json: &Value) -> Result<Vec<CveEntry>, String> {
+    let vulnerabilities: &Vec<Value> = json
+        .get("vulnerabilities")
+        .and_then(|v| v.as_array())
+        .ok_or("Not an NVD CVE JSON 2.0 document".to_string())?;
+    let mut entries: Vec<CveEntry> = Vec::new();
+    for vulnerability in vulnerabilities {
+        let cve: &Value = match vulnerability.get("cve") {
+            Some(cve) => cve,
+            None => continue,
+        };
+        let id: String = match cve.get("id").and_then(|v| v.as_str()) {
+            Some(id) => id.to_string(),
+            None => continue,
+        };
+        let description: String = cve
+            .get("descriptions")
+            .and_then(|v| v.as_array())
+            .and_then(|descriptions| {
+                descriptions
+                    .iter()
+                    .find(|d| d.get("lang").and_then(|v| v.as_str()) == Some("en"))
+            })
+            .and_then(|d| d.get("value"))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let mut score: Option<f64> = None;
+        let mut severity: String = String::new();
+        for metric in ["cvssMetricV31", "cvssMetricV30", "cvssMetricV2"] {
+            if let Some(metric) = cve.pointer(&format!("/metrics/{}/0", metric)) {
+                score = metric
+                    .pointer("/cvssData/baseScore")
+                    .and_then(|v| v.as_f64());
+                // v2 has the severity outside cvssData
+                severity = metric
+                    .pointer("/cvssData/baseSeverity")
+                    .or(metric.get("baseSeverity"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                break;
+            }
+        }
+        let mut matches: Vec<CpeMatch> = Vec::new();
+        if let Some(configurations) = cve.get("configurations") {
+            collect_matches(configurations, &mut matches);
+        }
+        entries.push(CveEntry {
+            id: id,
+            score: score,
+            severity: severity,
+            description: description,
+            matches: matches,
+        });
+    }
+    Ok(entries)
+}
+
+/// CVE lookup from local NVD feed files and/or the NVD API
+pub struct CveLookup {
+    entries: Vec<CveEntry>,
+    /// Query the NVD API with this timeout
+    api_timeout: Option<Duration>,
+    /// vendor:product already queried from the API
+    fetched: HashSet<String>,
+}
+
+impl CveLookup {
+    pub fn new() -> CveLookup {
+        CveLookup {
+            entries: Vec::new(),
+            api_timeout: None,
+            fetched: HashSet::new(),
+        }
+    }
+    /// Load NVD CVE JSON 2.0 feed files (e.g. nvdcve-2.0-2024.json)
+    pub fn load_files(&mut self, file_paths: &[PathBuf]) -> Result<(), String> {
+        for file_path in file_paths {
+            let text: String = std::fs::read_to_string(file_path).map_err(|e| {
+                format!(
+                    "Failed to read CVE feed {}: {}",
+                    file_path.to_string_lossy(),
+                    e
+                )
+            })?;
+            let json: Value = serde_json::from_str(&text).map_err(|e| {
+                format!(
+                    "Failed to parse CVE feed {}: {}",
+                    file_path.to_string_lossy(),
+                    e
+                )
+            })?;
+            self.entries.extend(parse_nvd_json(&json)?);
+        }
+        Ok(())
+    }
+    pub fn set_api_timeout(&mut self, timeout: Duration) {
+        self.api_timeout = Some(timeout);
+    }
+    /// Fetch all CVEs of the product once, and keep them with the feed entries
+    fn fetch_product(&mut self, vendor: &str, product: &str) -> Result<(), String> {
+        let timeout: Duration = match self.api_timeout {
+            Some(timeout) => timeout,
+            None => return Ok(()),
+        };
+        if !self.fetched.insert(format!("{}:{}", vendor, product)) {
+            return Ok(());
+        }
+        let url: String = format!(
+            "{}?virtualMatchString={}",
+            NVD_API_URL,
+            crate::http::encode_query(&format!("cpe:2.3:a:{}:{}", vendor, product))
+        );
+        let api_key: String = std::env::var(NVD_API_KEY_ENV).unwrap_or_default();
+        let mut headers: Vec<(&str, &str)> = vec![("Accept", "application/json")];
+        if !api_key.is_empty() {
+            headers.push(("apiKey", api_key.as_str()));
+        }
+        let response = crate::http::get(&url, &headers, timeout)?;
+        if !response.is_success() {
+            return Err(format!("NVD API returned {}", response.status));
+        }
+        let json: Value = serde_json::from_str(&response.text())
+            .map_err(|e| format!("Failed to parse NVD API response: {}", e))?;
+        let known: HashSet<String> = self.entries.iter().map(|e| e.id.clone()).collect();
+        self.entries.extend(
+            parse_nvd_json(&json)?
+                .into_iter()
+                .filter(|entry| !known.contains(&entry.id)),
+        );
+        Ok(())
+    }
+    /// Known CVEs of the CPE, highest score first
+    pub fn lookup(&mut self, cpe: &str) -> Result<Vec<CveInfo>, String> {
+        let fields: Vec<&str> = cpe.split(':').collect();
+        if fields.len() < 6 {
+            return Err(format!("Invalid CPE: {}", cpe));
+        }
+        let (vendor, product, version) = (fields[3], fields[4], fields[5]);
+        self.fetch_product(vendor, product)?;
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut cves: Vec<CveInfo> = Vec::new();
+        for entry in &self.entries {
+            if !entry
+                .matches
+                .iter()
+                .any(|m| m.matches(vendor, product, version))
+            {
+                continue;
+            }
+            if !seen.insert(entry.id.as_str()) {
+                continue;
+            }
+            cves.push(CveInfo {
+                id: entry.id.clone(),
+                cpe: cpe.to_string(),
+                score: entry.score,
+                severity: entry.severity.clone(),
+                description: entry.description.clone(),
+            });
+        }
+        cves.sort_by(|a, b| {
+            b.score
+                .unwrap_or(0.0)
+                .partial_cmp(&a.score.unwrap_or(0.0))
+                .unwrap_or(Ordering::Equal)
+        });
+        Ok(cves)
+    }
+}
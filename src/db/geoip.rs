@@ -0,0 +1,165 @@
+use maxminddb::geoip2;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+/// Language of the names looked up from the database
+const GEOIP_LANG: &str = "en";
+
+/// Geolocation of IP address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GeoInfo {
+    /// ISO 3166-1 country code
+    pub country_code: String,
+    /// Country name
+    pub country: String,
+    /// City name
+    pub city: String,
+    /// AS number (ASN database)
+    #[serde(default)]
+    pub asn: Option<u32>,
+    /// AS organization (ASN database)
+    #[serde(default)]
+    pub as_org: String,
+}
+
+impl GeoInfo {
+    /// Label for display. e.g. Mountain View, United States (US)
+    pub fn label(&self) -> String {
+        let mut label: String = if self.city.is_empty() {
+            self.country.clone()
+        } else if self.country.is_empty() {
+            self.city.clone()
+        } else {
+            format!("{}, {}", self.city, self.country)
+        };
+        if !self.country_code.is_empty() {
+            if label.is_empty() {
+                label = self.country_code.clone();
+            } else {
+                label = format!("{} ({})", label, self.country_code);
+            }
+        }
+        label
+    }
+    /// AS label for display. e.g. AS15169 GOOGLE
+    pub fn as_label(&self) -> Option<String> {
+        let asn: u32 = self.asn?;
+        if self.as_org.is_empty() {
+            Some(format!("AS{}", asn))
+        } else {
+            Some(format!("AS{} {}", asn, self.as_org))
+        }
+    }
+}
+
+/// Result of geo subcommand for one target
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GeoLookupResult {
+    pub target: String,
+    pub ip_addr: IpAddr,
+    /// None if no record
+    pub geo: Option<GeoInfo>,
+}
+
+/// Reader of MaxMind-format (MMDB) City, Country or ASN databases
+pub struct GeoIpReader {
+    readers: Vec<maxminddb::Reader<Vec<u8>>>,
+}
+
+fn open_reader(path: &Path) -> Result<maxminddb::Reader<Vec<u8>>, String> {
+    maxminddb::Reader::open_readfile(path).map_err(|e| {
+        format!(
+            "Failed to open GeoIP database {}: {}",
+            path.to_string_lossy(),
+            e
+        )
+    })
+}
+
+/// True if the database has AS records (e.g. GeoLite2-ASN, DBIP-ASN-Lite)
+fn is_asn_database(reader: &maxminddb::Reader<Vec<u8>>) -> bool {
+    reader.metadata.database_type.to_uppercase().contains("ASN")
+}
+
+impl GeoIpReader {
+    /// Open MMDB file
+    pub fn open(path: &Path) -> Result<GeoIpReader, String> {
+        Ok(GeoIpReader {
+            readers: vec![open_reader(path)?],
+        })
+    }
+    /// Open MMDB files. Records of each database are merged on lookup
+    pub fn open_files(paths: &[PathBuf]) -> Result<GeoIpReader, String> {
+        let mut readers = Vec::new();
+        for path in paths {
+            readers.push(open_reader(path)?);
+        }
+        Ok(GeoIpReader { readers: readers })
+    }
+    /// Database types of the opened files (e.g. GeoLite2-City)
+    pub fn database_types(&self) -> Vec<String> {
+        self.readers
+            .iter()
+            .map(|reader| reader.metadata.database_type.clone())
+            .collect()
+    }
+    /// Look up geolocation of the IP address. Returns None for non-global address or no record
+    pub fn lookup(&self, ip_addr: &IpAddr) -> Option<GeoInfo> {
+        if !crate::ip::is_global_addr(ip_addr) {
+            return None;
+        }
+        let mut info = GeoInfo {
+            country_code: String::new(),
+            country: String::new(),
+            city: String::new(),
+            asn: None,
+            as_org: String::new(),
+        };
+        for reader in &self.readers {
+            if is_asn_database(reader) {
+                if let Ok(record) = reader.lookup::<geoip2::Asn>(*ip_addr) {
+                    info.asn = info.asn.or(record.autonomous_system_number);
+                    if info.as_org.is_empty() {
+                        info.as_org = record
+                            .autonomous_system_organization
+                            .unwrap_or_default()
+                            .to_string();
+                    }
+                }
+                continue;
+            }
+            // Country database has the same layout without city
+            let record: geoip2::City = match reader.lookup(*ip_addr) {
+                Ok(record) => record,
+                Err(_) => continue,
+            };
+            if let Some(country) = record.country {
+                if info.country_code.is_empty() {
+                    info.country_code = country.iso_code.unwrap_or_default().to_string();
+                }
+                if let Some(names) = country.names {
+                    if info.country.is_empty() {
+                        info.country = names.get(GEOIP_LANG).unwrap_or(&"").to_string();
+                    }
+                }
+            }
+            if let Some(city) = record.city {
+                if let Some(names) = city.names {
+                    if info.city.is_empty() {
+                        info.city = names.get(GEOIP_LANG).unwrap_or(&"").to_string();
+                    }
+                }
+            }
+        }
+        if info.country_code.is_empty()
+            && info.country.is_empty()
+            && info.city.is_empty()
+            && info.asn.is_none()
+        {
+            None
+        } else {
+            Some(info)
+        }
+    }
+}
@@ -1,6 +1,8 @@
+pub mod geoip;
 pub mod model;
 pub mod tcp_service;
 use crate::packet::frame::PacketFrame;
+use netdev::mac::MacAddr;
 use nex::packet::ethernet::EthernetHeader;
 
 use crate::config;
@@ -17,6 +19,20 @@ pub fn get_oui_detail_map() -> HashMap<String, String> {
     oui_map
 }
 
+/// Lookup vendor name of the MAC address from the OUI map. Returns empty string if not found.
+pub fn get_vendor_name(oui_map: &HashMap<String, String>, mac_addr: &MacAddr) -> String {
+    if *mac_addr == MacAddr::zero() {
+        return String::new();
+    }
+    let mac: String = mac_addr.address();
+    if mac.len() > 16 {
+        let prefix8 = mac[0..8].to_uppercase();
+        oui_map.get(&prefix8).unwrap_or(&String::new()).to_string()
+    } else {
+        oui_map.get(&mac).unwrap_or(&String::new()).to_string()
+    }
+}
+
 pub fn get_vm_oui_map() -> HashMap<String, String> {
     let mut oui_map: HashMap<String, String> = HashMap::new();
     let ds_oui: Vec<model::Oui> = bincode::deserialize(config::OUI_VM_BIN).unwrap_or(vec![]);
@@ -66,6 +82,17 @@ pub fn get_os_ttl_map() -> HashMap<u8, String> {
     os_ttl_map
 }
 
+/// Guess probable OS family from the TTL (hop limit) of the reply. Returns empty string if unknown.
+pub fn get_os_hint_by_ttl(os_ttl_map: &HashMap<u8, String>, ttl: u8) -> String {
+    if ttl == 0 {
+        return String::new();
+    }
+    os_ttl_map
+        .get(&ip::guess_initial_ttl(ttl))
+        .unwrap_or(&String::new())
+        .to_string()
+}
+
 pub fn get_os_ttl_list() -> Vec<model::OsTtl> {
     let ds_os_ttl: Vec<model::OsTtl> = bincode::deserialize(config::OS_TTL_BIN).unwrap_or(vec![]);
     ds_os_ttl
@@ -76,6 +103,20 @@ pub fn get_subdomain() -> Vec<String> {
     subdomain
 }
 
+/// Most common subdomains. The embedded list is ordered by frequency
+pub fn get_subdomain_small() -> Vec<String> {
+    let mut subdomain: Vec<String> = get_subdomain();
+    subdomain.truncate(config::SMALL_SUBDOMAIN_WORDLIST_SIZE);
+    subdomain
+}
+
+/// Default subdomains plus environment, infrastructure and numbered variants
+pub fn get_subdomain_large() -> Vec<String> {
+    let subdomain: Vec<String> =
+        bincode::deserialize(config::SUBDOMAIN_LARGE_BIN).unwrap_or(vec![]);
+    subdomain
+}
+
 pub fn get_os_family_fingerprints() -> Vec<model::OsFamilyFingerprint> {
     let ds_os_fingerprints: Vec<model::OsFamilyFingerprint> =
         bincode::deserialize(config::OS_FAMILY_FINGERPRINT_BIN).unwrap_or(vec![]);
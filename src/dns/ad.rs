@@ -0,0 +1,181 @@
+use hickory_resolver::proto::rr::RecordType;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use super::query::ResourceRecord;
+use crate::host::Host;
+
+/// SRV records registered by Active Directory domain controllers, with the role they indicate.
+/// The domain name is appended
+pub const AD_SRV_RECORDS: [(&str, &str); 8] = [
+    ("_ldap._tcp.dc._msdcs", "dc"),
+    ("_ldap._tcp.pdc._msdcs", "pdc"),
+    ("_ldap._tcp.gc._msdcs", "gc"),
+    ("_gc._tcp", "gc"),
+    ("_ldap._tcp", "ldap"),
+    ("_kerberos._tcp", "kerberos"),
+    ("_kerberos._udp", "kerberos"),
+    ("_kpasswd._tcp", "kpasswd"),
+];
+
+/// Ports of the services a domain controller usually exposes
+pub const AD_PORTS: [u16; 13] = [
+    53, 88, 135, 139, 389, 445, 464, 636, 3268, 3269, 3389, 5985, 9389,
+];
+
+/// Target of an SRV record
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SrvTarget {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+/// Answers of one SRV query
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AdService {
+    /// Queried name (e.g. _ldap._tcp.dc._msdcs.corp.example.com)
+    pub name: String,
+    pub role: String,
+    /// Response code, or the error if no response
+    pub response_code: String,
+    pub targets: Vec<SrvTarget>,
+}
+
+/// Host found in the SRV targets
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DomainController {
+    pub host_name: String,
+    pub ips: Vec<IpAddr>,
+    /// Roles advertised for the host (e.g. dc, pdc, gc, kerberos)
+    pub roles: Vec<String>,
+}
+
+/// Result of Active Directory service discovery
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AdDiscoveryResult {
+    pub domain: String,
+    pub server: SocketAddr,
+    pub services: Vec<AdService>,
+    pub domain_controllers: Vec<DomainController>,
+    /// Port scan results of the domain controllers (--then-port)
+    #[serde(default)]
+    pub hosts: Vec<Host>,
+    pub scan_time: Duration,
+}
+
+fn trim_name(name: &str) -> String {
+    name.trim_end_matches('.').to_lowercase()
+}
+
+/// Parse SRV data in presentation format (priority weight port target)
+fn parse_srv(value: &str) -> Option<SrvTarget> {
+    let fields: Vec<&str> = value.split_whitespace().collect();
+    if fields.len() != 4 {
+        return None;
+    }
+    Some(SrvTarget {
+        priority: fields[0].parse().ok()?,
+        weight: fields[1].parse().ok()?,
+        port: fields[2].parse().ok()?,
+        target: trim_name(fields[3]),
+    })
+}
+
+fn addresses_of(records: &[ResourceRecord], host_name: &str) -> Vec<IpAddr> {
+    records
+        .iter()
+        .filter(|r| {
+            (r.record_type == "A" || r.record_type == "AAAA") && trim_name(&r.name) == host_name
+        })
+        .filter_map(|r| r.value.parse::<IpAddr>().ok())
+        .collect()
+}
+
+/// Query the AD SRV records of the domain at the server and list the domain controllers
+pub fn discover(
+    domain: &str,
+    server: SocketAddr,
+    use_tcp: bool,
+    timeout: Duration,
+) -> AdDiscoveryResult {
+    let start_time: Instant = Instant::now();
+    let mut services: Vec<AdService> = Vec::new();
+    let mut additionals: Vec<ResourceRecord> = Vec::new();
+    let mut domain_controllers: Vec<DomainController> = Vec::new();
+    for (prefix, role) in AD_SRV_RECORDS {
+        let name: String = format!("{}.{}", prefix, domain);
+        let mut service = AdService {
+            name: name.clone(),
+            role: role.to_string(),
+            response_code: String::new(),
+            targets: vec![],
+        };
+        match super::query::query(&name, RecordType::SRV, server, use_tcp, timeout) {
+            Ok(result) => {
+                service.response_code = result.response_code.clone();
+                service.targets = result
+                    .answers
+                    .iter()
+                    .filter(|r| r.record_type == "SRV")
+                    .filter_map(|r| parse_srv(&r.value))
+                    .collect();
+                additionals.extend(result.additionals);
+            }
+            Err(e) => service.response_code = e,
+        }
+        for target in &service.targets {
+            // "." means the service is not available
+            if target.target.is_empty() {
+                continue;
+            }
+            match domain_controllers
+                .iter_mut()
+                .find(|dc| dc.host_name == target.target)
+            {
+                Some(dc) => {
+                    if !dc.roles.contains(&service.role) {
+                        dc.roles.push(service.role.clone());
+                    }
+                }
+                None => domain_controllers.push(DomainController {
+                    host_name: target.target.clone(),
+                    ips: vec![],
+                    roles: vec![service.role.clone()],
+                }),
+            }
+        }
+        services.push(service);
+    }
+    // Addresses from the additional section, otherwise from the same server
+    for dc in domain_controllers.iter_mut() {
+        dc.ips = addresses_of(&additionals, &dc.host_name);
+        if dc.ips.is_empty() {
+            for record_type in [RecordType::A, RecordType::AAAA] {
+                if let Ok(result) =
+                    super::query::query(&dc.host_name, record_type, server, use_tcp, timeout)
+                {
+                    // Answers may follow a CNAME. Take every address
+                    dc.ips.extend(
+                        result
+                            .answers
+                            .iter()
+                            .filter_map(|r| r.value.parse::<IpAddr>().ok()),
+                    );
+                }
+            }
+        }
+        dc.ips.sort();
+        dc.ips.dedup();
+    }
+    AdDiscoveryResult {
+        domain: domain.to_string(),
+        server: server,
+        services: services,
+        domain_controllers: domain_controllers,
+        hosts: vec![],
+        scan_time: start_time.elapsed(),
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+#[cfg(not(any(unix, target_os = "windows")))]
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::Resolver;
+use serde::{Deserialize, Serialize};
+
+/// Team Cymru IP to ASN mapping zones
+const CYMRU_ORIGIN_ZONE: &str = "origin.asn.cymru.com";
+const CYMRU_ORIGIN6_ZONE: &str = "origin6.asn.cymru.com";
+const CYMRU_ASN_ZONE: &str = "asn.cymru.com";
+
+/// Origin AS information of IP address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AsnInfo {
+    /// AS number
+    pub asn: u32,
+    /// Announced prefix
+    pub prefix: String,
+    /// Country code
+    pub country_code: String,
+    /// Short AS name (e.g. GOOGLE)
+    pub as_name: String,
+    /// Full AS description
+    pub description: String,
+}
+
+impl AsnInfo {
+    /// Label for display. e.g. AS15169 GOOGLE
+    pub fn label(&self) -> String {
+        if self.as_name.is_empty() {
+            format!("AS{}", self.asn)
+        } else {
+            format!("AS{} {}", self.asn, self.as_name)
+        }
+    }
+}
+
+#[cfg(any(unix, target_os = "windows"))]
+fn get_resolver() -> Option<Resolver> {
+    Resolver::from_system_conf().ok()
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn get_resolver() -> Option<Resolver> {
+    Resolver::new(ResolverConfig::default(), ResolverOpts::default()).ok()
+}
+
+fn lookup_txt(resolver: &Resolver, name: &str) -> Option<String> {
+    match resolver.txt_lookup(name) {
+        Ok(txt_lookup) => {
+            for txt in txt_lookup.iter() {
+                let data: Vec<String> = txt
+                    .txt_data()
+                    .iter()
+                    .map(|d| String::from_utf8_lossy(d).to_string())
+                    .collect();
+                return Some(data.concat());
+            }
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+/// Build Team Cymru origin query name. Reversed octets (IPv4) or nibbles (IPv6)
+fn origin_query_name(ip_addr: &IpAddr) -> String {
+    match ip_addr {
+        IpAddr::V4(ipv4_addr) => {
+            let o = ipv4_addr.octets();
+            format!("{}.{}.{}.{}.{}", o[3], o[2], o[1], o[0], CYMRU_ORIGIN_ZONE)
+        }
+        IpAddr::V6(ipv6_addr) => {
+            let mut nibbles: Vec<String> = Vec::new();
+            for b in ipv6_addr.octets().iter().rev() {
+                nibbles.push(format!("{:x}", b & 0x0f));
+                nibbles.push(format!("{:x}", b >> 4));
+            }
+            format!("{}.{}", nibbles.join("."), CYMRU_ORIGIN6_ZONE)
+        }
+    }
+}
+
+/// Split Team Cymru TXT record. e.g. "15169 | 8.8.8.0/24 | US | arin | 2014-03-14"
+fn split_fields(txt: &str) -> Vec<String> {
+    txt.split('|').map(|f| f.trim().to_string()).collect()
+}
+
+fn lookup_asn_with(resolver: &Resolver, ip_addr: &IpAddr) -> Option<AsnInfo> {
+    if !crate::ip::is_global_addr(ip_addr) {
+        return None;
+    }
+    let origin_txt: String = lookup_txt(resolver, &origin_query_name(ip_addr))?;
+    let origin: Vec<String> = split_fields(&origin_txt);
+    // Multiple origin ASes are separated by space. Use the first one
+    let asn: u32 = origin
+        .get(0)?
+        .split_whitespace()
+        .next()?
+        .parse::<u32>()
+        .ok()?;
+    let mut info = AsnInfo {
+        asn: asn,
+        prefix: origin.get(1).cloned().unwrap_or_default(),
+        country_code: origin.get(2).cloned().unwrap_or_default(),
+        as_name: String::new(),
+        description: String::new(),
+    };
+    // e.g. "15169 | US | arin | 2000-03-30 | GOOGLE - Google LLC, US"
+    if let Some(as_txt) = lookup_txt(resolver, &format!("AS{}.{}", asn, CYMRU_ASN_ZONE)) {
+        if let Some(description) = split_fields(&as_txt).get(4) {
+            info.description = description.clone();
+            info.as_name = description
+                .split(" - ")
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+        }
+    }
+    Some(info)
+}
+
+/// Look up origin AS of the IP address using Team Cymru DNS
+pub fn lookup_asn(ip_addr: &IpAddr) -> Option<AsnInfo> {
+    let resolver: Resolver = get_resolver()?;
+    lookup_asn_with(&resolver, ip_addr)
+}
+
+/// Look up origin AS of multiple IP addresses. Addresses without AS are omitted
+pub fn lookup_asns(ips: &[IpAddr]) -> HashMap<IpAddr, AsnInfo> {
+    let mut asn_map: HashMap<IpAddr, AsnInfo> = HashMap::new();
+    let resolver: Resolver = match get_resolver() {
+        Some(resolver) => resolver,
+        None => return asn_map,
+    };
+    for ip_addr in ips {
+        if asn_map.contains_key(ip_addr) {
+            continue;
+        }
+        if let Some(info) = lookup_asn_with(&resolver, ip_addr) {
+            asn_map.insert(*ip_addr, info);
+        }
+    }
+    asn_map
+}
@@ -0,0 +1,196 @@
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::str::FromStr;
+use std::time::Duration;
+
+#[cfg(not(any(unix, target_os = "windows")))]
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::proto::op::{Message, MessageType, OpCode, Query, ResponseCode};
+use hickory_resolver::proto::rr::{Name, RData, Record, RecordType};
+use hickory_resolver::proto::serialize::binary::{BinDecodable, BinEncodable};
+use hickory_resolver::Resolver;
+use serde::{Deserialize, Serialize};
+
+use super::domain::{DnsRecord, Domain};
+
+/// Connect and read timeout for each transfer
+const AXFR_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Zone transfer attempt against one nameserver
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZoneTransfer {
+    /// Nameserver host name
+    pub nameserver: String,
+    pub ip_addr: IpAddr,
+    /// The nameserver returned the zone
+    pub allowed: bool,
+    /// Number of records transferred
+    pub record_count: usize,
+    /// Reason the transfer failed
+    pub error: Option<String>,
+}
+
+#[cfg(any(unix, target_os = "windows"))]
+fn get_resolver() -> Option<Resolver> {
+    Resolver::from_system_conf().ok()
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn get_resolver() -> Option<Resolver> {
+    Resolver::new(ResolverConfig::default(), ResolverOpts::default()).ok()
+}
+
+fn trim_name(name: &str) -> String {
+    name.trim_end_matches('.').to_lowercase()
+}
+
+/// Get authoritative nameservers of the domain with their addresses
+pub fn get_nameservers(domain: &str) -> Vec<(String, Vec<IpAddr>)> {
+    let mut nameservers: Vec<(String, Vec<IpAddr>)> = Vec::new();
+    let resolver: Resolver = match get_resolver() {
+        Some(resolver) => resolver,
+        None => return nameservers,
+    };
+    let lookup = match resolver.lookup(domain, RecordType::NS) {
+        Ok(lookup) => lookup,
+        Err(_) => return nameservers,
+    };
+    for rdata in lookup.iter() {
+        if let RData::NS(ns) = rdata {
+            let ns_name: String = trim_name(&ns.to_string());
+            let ips: Vec<IpAddr> = match resolver.lookup_ip(ns_name.as_str()) {
+                Ok(lookup_ip) => lookup_ip.iter().collect(),
+                Err(_) => vec![],
+            };
+            nameservers.push((ns_name, ips));
+        }
+    }
+    nameservers
+}
+
+/// Read a length-prefixed message from the TCP stream
+pub(crate) fn read_message(stream: &mut TcpStream) -> Result<Message, String> {
+    let mut len_buf: [u8; 2] = [0; 2];
+    stream
+        .read_exact(&mut len_buf)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let mut buf: Vec<u8> = vec![0; u16::from_be_bytes(len_buf) as usize];
+    stream
+        .read_exact(&mut buf)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    Message::from_bytes(&buf).map_err(|e| format!("Malformed response: {}", e))
+}
+
+/// Request a full zone transfer (AXFR) over TCP.
+/// The zone ends with the second SOA record
+pub fn transfer_zone(domain: &str, ip_addr: IpAddr) -> Result<Vec<Record>, String> {
+    let name: Name = Name::from_str(domain).map_err(|e| format!("Invalid domain: {}", e))?;
+    let mut query = Message::new();
+    query
+        .set_id(rand::random::<u16>())
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(false)
+        .add_query(Query::query(name, RecordType::AXFR));
+    let query_bytes: Vec<u8> = query
+        .to_bytes()
+        .map_err(|e| format!("Failed to build query: {}", e))?;
+    let socket_addr: SocketAddr = SocketAddr::new(ip_addr, 53);
+    let mut stream: TcpStream = TcpStream::connect_timeout(&socket_addr, AXFR_TIMEOUT)
+        .map_err(|e| format!("Failed to connect to {}: {}", socket_addr, e))?;
+    let _ = stream.set_read_timeout(Some(AXFR_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(AXFR_TIMEOUT));
+    let mut req: Vec<u8> = Vec::new();
+    req.extend_from_slice(&(query_bytes.len() as u16).to_be_bytes());
+    req.extend_from_slice(&query_bytes);
+    stream
+        .write_all(&req)
+        .map_err(|e| format!("Failed to send query: {}", e))?;
+    let mut records: Vec<Record> = Vec::new();
+    let mut soa_count: usize = 0;
+    while soa_count < 2 {
+        let message: Message = read_message(&mut stream)?;
+        if message.response_code() != ResponseCode::NoError {
+            return Err(format!("Refused ({})", message.response_code()));
+        }
+        if message.answers().is_empty() {
+            return Err(String::from("Empty response"));
+        }
+        for record in message.answers() {
+            if record.record_type() == RecordType::SOA {
+                soa_count += 1;
+                // Closing SOA repeats the first one
+                if soa_count == 2 {
+                    break;
+                }
+            }
+            records.push(record.clone());
+        }
+    }
+    Ok(records)
+}
+
+/// Try AXFR against every authoritative nameserver of the domain.
+/// Returns the attempts and the records of the first successful transfer
+pub fn check_zone_transfer(domain: &str) -> (Vec<ZoneTransfer>, Vec<Record>) {
+    let mut transfers: Vec<ZoneTransfer> = Vec::new();
+    let mut zone: Vec<Record> = Vec::new();
+    for (nameserver, ips) in get_nameservers(domain) {
+        for ip_addr in ips {
+            let mut transfer = ZoneTransfer {
+                nameserver: nameserver.clone(),
+                ip_addr: ip_addr,
+                allowed: false,
+                record_count: 0,
+                error: None,
+            };
+            match transfer_zone(domain, ip_addr) {
+                Ok(records) => {
+                    transfer.allowed = true;
+                    transfer.record_count = records.len();
+                    if zone.is_empty() {
+                        zone = records;
+                    }
+                }
+                Err(e) => transfer.error = Some(e),
+            }
+            transfers.push(transfer);
+        }
+    }
+    (transfers, zone)
+}
+
+/// Group zone records by owner name. A/AAAA go to ips, others to records
+pub fn zone_to_domains(zone: &[Record]) -> Vec<Domain> {
+    let mut domains: Vec<Domain> = Vec::new();
+    for record in zone {
+        let name: String = trim_name(&record.name().to_string());
+        let rdata: &RData = match record.data() {
+            Some(rdata) => rdata,
+            None => continue,
+        };
+        let index: usize = match domains.iter().position(|d| d.domain_name == name) {
+            Some(index) => index,
+            None => {
+                domains.push(Domain {
+                    unicode_name: super::idn::to_unicode(&name),
+                    domain_name: name,
+                    ips: vec![],
+                    records: vec![],
+                    tags: vec![],
+                });
+                domains.len() - 1
+            }
+        };
+        let domain: &mut Domain = &mut domains[index];
+        match rdata {
+            RData::A(a) => domain.ips.push(IpAddr::V4(a.0)),
+            RData::AAAA(aaaa) => domain.ips.push(IpAddr::V6(aaaa.0)),
+            _ => domain.records.push(DnsRecord {
+                record_type: record.record_type().to_string(),
+                value: rdata.to_string(),
+            }),
+        }
+    }
+    domains
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use super::asn::{self, AsnInfo};
+use super::domain::Domain;
+
+/// Origin ASNs of well-known CDN and cloud providers
+const PROVIDER_ASNS: [(u32, &str); 26] = [
+    (13335, "cloudflare"),
+    (209242, "cloudflare"),
+    (20940, "akamai"),
+    (16625, "akamai"),
+    (16702, "akamai"),
+    (21342, "akamai"),
+    (32787, "akamai"),
+    (54113, "fastly"),
+    (16509, "aws"),
+    (14618, "aws"),
+    (15169, "google"),
+    (396982, "google"),
+    (8075, "azure"),
+    (8068, "azure"),
+    (19551, "imperva"),
+    (20446, "stackpath"),
+    (33438, "stackpath"),
+    (15133, "edgio"),
+    (22822, "edgio"),
+    (30148, "sucuri"),
+    (60068, "cdn77"),
+    (45102, "alibaba"),
+    (132203, "tencent"),
+    (14061, "digitalocean"),
+    (16276, "ovh"),
+    (24940, "hetzner"),
+];
+
+/// Providers that front the origin. Other tags are hosting providers
+const CDN_PROVIDERS: [&str; 8] = [
+    "cloudflare",
+    "akamai",
+    "fastly",
+    "imperva",
+    "stackpath",
+    "edgio",
+    "sucuri",
+    "cdn77",
+];
+
+/// Provider tag of the AS (e.g. cloudflare)
+pub fn provider_by_asn(asn: u32) -> Option<&'static str> {
+    PROVIDER_ASNS
+        .iter()
+        .find(|(n, _)| *n == asn)
+        .map(|(_, tag)| *tag)
+}
+
+/// Whether the tag is a CDN, i.e. the address is not the origin server
+pub fn is_cdn(tag: &str) -> bool {
+    CDN_PROVIDERS.contains(&tag)
+}
+
+/// Tag each domain with the providers of its resolved addresses.
+/// Addresses of unknown providers are tagged with the AS label
+pub fn tag_domains(domains: &mut [Domain]) {
+    let mut ips: Vec<IpAddr> = Vec::new();
+    for domain in domains.iter() {
+        for ip in &domain.ips {
+            if !ips.contains(ip) {
+                ips.push(*ip);
+            }
+        }
+    }
+    let asn_map: HashMap<IpAddr, AsnInfo> = asn::lookup_asns(&ips);
+    for domain in domains.iter_mut() {
+        for ip in &domain.ips {
+            let tag: String = match asn_map.get(ip) {
+                Some(info) => match provider_by_asn(info.asn) {
+                    Some(tag) => tag.to_string(),
+                    None => info.label(),
+                },
+                None => continue,
+            };
+            if !domain.tags.contains(&tag) {
+                domain.tags.push(tag);
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::domain::Domain;
+
+/// Line of the in-progress output file (JSON Lines).
+/// The file is replaced by the final result when the scan completes
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointEntry {
+    /// Scan parameters used to validate --resume
+    Start { target: String, word_count: usize },
+    /// Resolved subdomain
+    Found(Domain),
+    /// Number of words from the start of the word-list that have been looked up
+    Position(usize),
+}
+
+/// Appends checkpoint entries to the output file
+pub struct CheckpointWriter {
+    file: File,
+}
+
+impl CheckpointWriter {
+    /// Create (truncate) the file and write the start entry
+    pub fn create(
+        file_path: &PathBuf,
+        target: &str,
+        word_count: usize,
+    ) -> Result<CheckpointWriter, String> {
+        let file: File = File::create(file_path)
+            .map_err(|e| format!("Failed to create {}: {}", file_path.to_string_lossy(), e))?;
+        let mut writer = CheckpointWriter { file: file };
+        writer.write(&CheckpointEntry::Start {
+            target: target.to_string(),
+            word_count: word_count,
+        })?;
+        Ok(writer)
+    }
+    /// Open the file to continue a resumed scan
+    pub fn append(file_path: &PathBuf) -> Result<CheckpointWriter, String> {
+        let file: File = OpenOptions::new()
+            .append(true)
+            .open(file_path)
+            .map_err(|e| format!("Failed to open {}: {}", file_path.to_string_lossy(), e))?;
+        Ok(CheckpointWriter { file: file })
+    }
+    /// Write the entry and flush so that it survives a kill
+    pub fn write(&mut self, entry: &CheckpointEntry) -> Result<(), String> {
+        let line: String = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        writeln!(self.file, "{}", line)
+            .and_then(|_| self.file.flush())
+            .map_err(|e| format!("Failed to write checkpoint: {}", e))
+    }
+}
+
+/// State restored from an interrupted scan
+#[derive(Clone, Debug)]
+pub struct ResumeState {
+    pub target: String,
+    pub word_count: usize,
+    /// Words before this position are already looked up
+    pub position: usize,
+    pub domains: Vec<Domain>,
+}
+
+/// Read the in-progress output file of an interrupted scan
+pub fn load(file_path: &PathBuf) -> Result<ResumeState, String> {
+    let contents: String = std::fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path.to_string_lossy(), e))?;
+    let mut state: Option<ResumeState> = None;
+    for line in contents.lines() {
+        let line: &str = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // Last line may be cut off by the kill
+        let entry: CheckpointEntry = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        match entry {
+            CheckpointEntry::Start { target, word_count } => {
+                state = Some(ResumeState {
+                    target: target,
+                    word_count: word_count,
+                    position: 0,
+                    domains: vec![],
+                });
+            }
+            CheckpointEntry::Found(domain) => {
+                if let Some(state) = &mut state {
+                    if !state
+                        .domains
+                        .iter()
+                        .any(|d| d.domain_name == domain.domain_name)
+                    {
+                        state.domains.push(domain);
+                    }
+                }
+            }
+            CheckpointEntry::Position(position) => {
+                if let Some(state) = &mut state {
+                    state.position = position;
+                }
+            }
+        }
+    }
+    state.ok_or(format!(
+        "{} is not an interrupted subdomain scan",
+        file_path.to_string_lossy()
+    ))
+}
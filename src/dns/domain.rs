@@ -14,8 +14,37 @@ pub struct CertEntry {
     pub entry_timestamp: String,
 }
 
+/// Resource record other than A/AAAA
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DnsRecord {
+    /// Record type (e.g. MX)
+    pub record_type: String,
+    /// Record data in presentation format
+    pub value: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Domain {
+    /// Name in ASCII. Internationalized names are in punycode
     pub domain_name: String,
+    /// Unicode form of an internationalized name (IDN)
+    #[serde(default)]
+    pub unicode_name: Option<String>,
     pub ips: Vec<IpAddr>,
+    /// Additional records (MX, TXT, NS, CNAME, SRV) if requested
+    #[serde(default)]
+    pub records: Vec<DnsRecord>,
+    /// Providers of the resolved addresses (e.g. cloudflare, aws)
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Domain {
+    /// Name for display. IDNs are shown in both forms
+    pub fn display_name(&self) -> String {
+        match &self.unicode_name {
+            Some(unicode_name) => format!("{} ({})", self.domain_name, unicode_name),
+            None => self.domain_name.clone(),
+        }
+    }
 }
@@ -0,0 +1,44 @@
+use hickory_resolver::proto::rr::Name;
+
+/// Convert a Unicode domain name to its ASCII (punycode) form.
+/// ASCII names and IP addresses are returned as-is
+pub fn to_ascii(name: &str) -> Result<String, String> {
+    if name.is_ascii() {
+        return Ok(name.to_string());
+    }
+    match Name::from_utf8(name) {
+        Ok(idn) => Ok(idn.to_ascii().trim_end_matches('.').to_string()),
+        Err(e) => Err(format!("Invalid domain name {}: {}", name, e)),
+    }
+}
+
+/// Unicode form of a name with punycode labels. None if the name has none
+pub fn to_unicode(name: &str) -> Option<String> {
+    if !name.to_lowercase().contains("xn--") {
+        return None;
+    }
+    match Name::from_ascii(name) {
+        Ok(idn) => {
+            let unicode: String = idn.to_utf8().trim_end_matches('.').to_string();
+            if unicode == name.trim_end_matches('.') {
+                None
+            } else {
+                Some(unicode)
+            }
+        }
+        Err(_) => None,
+    }
+}
+
+/// Name for display. IDNs are shown in both forms (e.g. xn--bcher-kva.example (bücher.example))
+pub fn display_name(name: &str) -> String {
+    match to_unicode(name) {
+        Some(unicode) => format!("{} ({})", name, unicode),
+        None => name.to_string(),
+    }
+}
+
+/// Value parser for target arguments. Unicode domains are converted to punycode
+pub fn parse_target(target: &str) -> Result<String, String> {
+    to_ascii(target)
+}
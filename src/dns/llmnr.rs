@@ -0,0 +1,50 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use hickory_resolver::proto::op::Message;
+use hickory_resolver::proto::rr::{Name, RData, RecordType};
+use hickory_resolver::proto::serialize::binary::BinDecodable;
+use tokio::net::UdpSocket;
+
+/// LLMNR port (RFC 4795)
+const LLMNR_PORT: u16 = 5355;
+
+/// Ask the host for its own name with a unicast LLMNR PTR query.
+/// Windows answers for its addresses even without a DNS record
+pub async fn reverse_lookup(ip_addr: IpAddr, timeout: Duration) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let (id, query) = match super::query::build_query(Name::from(ip_addr), RecordType::PTR, false) {
+        Ok(query) => query,
+        Err(_) => return names,
+    };
+    let bind_addr: SocketAddr = match ip_addr {
+        IpAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+        IpAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+    };
+    let socket: UdpSocket = match UdpSocket::bind(bind_addr).await {
+        Ok(socket) => socket,
+        Err(_) => return names,
+    };
+    if socket
+        .send_to(&query, SocketAddr::new(ip_addr, LLMNR_PORT))
+        .await
+        .is_err()
+    {
+        return names;
+    }
+    let mut buf: [u8; 1500] = [0; 1500];
+    let len: usize = match tokio::time::timeout(timeout, socket.recv(&mut buf)).await {
+        Ok(Ok(len)) => len,
+        _ => return names,
+    };
+    let message: Message = match Message::from_bytes(&buf[..len]) {
+        Ok(message) if message.id() == id => message,
+        _ => return names,
+    };
+    for record in message.answers() {
+        if let Some(RData::PTR(ptr)) = record.data() {
+            names.push(ptr.0.to_string().trim_end_matches('.').to_string());
+        }
+    }
+    names
+}
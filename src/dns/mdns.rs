@@ -0,0 +1,298 @@
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use hickory_resolver::proto::op::{Message, MessageType, OpCode, Query};
+use hickory_resolver::proto::rr::{Name, RData, Record, RecordType};
+use hickory_resolver::proto::serialize::binary::{BinDecodable, BinEncodable};
+use serde::{Deserialize, Serialize};
+
+/// mDNS multicast group and port
+const MDNS_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(224, 0, 0, 251)), 5353);
+
+/// DNS-SD meta-query that lists the service types on the link
+const SERVICES_META_QUERY: &str = "_services._dns-sd._udp.local.";
+
+/// Service types browsed in addition to those found by the meta-query.
+/// Some responders do not answer the meta-query
+pub const MDNS_SERVICE_TYPES: [&str; 25] = [
+    "_http._tcp",
+    "_https._tcp",
+    "_ipp._tcp",
+    "_ipps._tcp",
+    "_printer._tcp",
+    "_pdl-datastream._tcp",
+    "_scanner._tcp",
+    "_uscan._tcp",
+    "_airplay._tcp",
+    "_raop._tcp",
+    "_googlecast._tcp",
+    "_spotify-connect._tcp",
+    "_sonos._tcp",
+    "_hap._tcp",
+    "_homekit._tcp",
+    "_matter._tcp",
+    "_esphomelib._tcp",
+    "_mqtt._tcp",
+    "_smb._tcp",
+    "_afpovertcp._tcp",
+    "_ssh._tcp",
+    "_sftp-ssh._tcp",
+    "_workstation._tcp",
+    "_companion-link._tcp",
+    "_device-info._tcp",
+];
+
+/// Service instance advertised by a device
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MdnsService {
+    /// Instance name (e.g. Living Room TV)
+    pub instance: String,
+    /// Service type (e.g. _googlecast._tcp)
+    pub service_type: String,
+    pub port: u16,
+    /// TXT key=value pairs
+    pub txt: Vec<String>,
+}
+
+/// Device that answered, grouped by host name
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MdnsDevice {
+    /// Host name (e.g. chromecast.local). Responder address if not advertised
+    pub host_name: String,
+    pub ips: Vec<IpAddr>,
+    pub services: Vec<MdnsService>,
+}
+
+/// Result of mDNS/DNS-SD discovery
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MdnsDiscoveryResult {
+    /// Service types seen on the link
+    pub service_types: Vec<String>,
+    pub devices: Vec<MdnsDevice>,
+    pub scan_time: Duration,
+}
+
+/// Records collected from the responses
+#[derive(Default)]
+struct MdnsCache {
+    /// Instance full name -> (service type, instance label)
+    instances: HashMap<String, (String, String)>,
+    /// Instance full name -> (target host, port)
+    srv: HashMap<String, (String, u16)>,
+    /// Instance full name -> TXT strings
+    txt: HashMap<String, Vec<String>>,
+    /// Host name -> addresses
+    addrs: HashMap<String, Vec<IpAddr>>,
+    /// Instance full name -> address of the responder
+    responders: HashMap<String, IpAddr>,
+    service_types: Vec<String>,
+}
+
+fn trim_name(name: &str) -> String {
+    name.trim_end_matches('.').to_string()
+}
+
+/// Service type of a PTR owner name (e.g. _ipp._tcp.local. -> _ipp._tcp)
+fn service_type_of(name: &str) -> String {
+    trim_name(name).trim_end_matches(".local").to_string()
+}
+
+/// Key to avoid sending the same query twice
+fn query_key(name: &str, record_types: &[RecordType]) -> String {
+    format!("{} {:?}", name.to_lowercase(), record_types)
+}
+
+/// Send a one-shot (legacy unicast) query. Responders answer to the source port
+fn send_query(socket: &UdpSocket, name: &str, record_types: &[RecordType]) -> Result<(), String> {
+    let name: Name = Name::from_str(name).map_err(|e| format!("Invalid name {}: {}", name, e))?;
+    let mut query = Message::new();
+    query
+        .set_id(0)
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(false);
+    for record_type in record_types {
+        query.add_query(Query::query(name.clone(), *record_type));
+    }
+    let bytes: Vec<u8> = query
+        .to_bytes()
+        .map_err(|e| format!("Failed to build query: {}", e))?;
+    socket
+        .send_to(&bytes, MDNS_ADDR)
+        .map_err(|e| format!("Failed to send query: {}", e))?;
+    Ok(())
+}
+
+impl MdnsCache {
+    /// Add a record. Returns names that need a follow-up query
+    fn add_record(&mut self, record: &Record, responder: IpAddr) -> Vec<(String, Vec<RecordType>)> {
+        let mut queries: Vec<(String, Vec<RecordType>)> = Vec::new();
+        let owner: String = record.name().to_string();
+        let rdata: &RData = match record.data() {
+            Some(rdata) => rdata,
+            None => return queries,
+        };
+        match rdata {
+            RData::PTR(ptr) => {
+                let target: String = ptr.0.to_string();
+                if owner.eq_ignore_ascii_case(SERVICES_META_QUERY) {
+                    let service_type: String = service_type_of(&target);
+                    if !self.service_types.contains(&service_type) {
+                        self.service_types.push(service_type);
+                        queries.push((target, vec![RecordType::PTR]));
+                    }
+                } else if !owner.starts_with("_services.") {
+                    let service_type: String = service_type_of(&owner);
+                    if !self.service_types.contains(&service_type) {
+                        self.service_types.push(service_type.clone());
+                    }
+                    if !self.instances.contains_key(&target) {
+                        // First label is the instance name as-is (may contain spaces and dots)
+                        let label: String = match ptr.0.iter().next() {
+                            Some(label) => String::from_utf8_lossy(label).to_string(),
+                            None => trim_name(&target),
+                        };
+                        self.instances.insert(target.clone(), (service_type, label));
+                        self.responders.insert(target.clone(), responder);
+                        if !self.srv.contains_key(&target) {
+                            queries.push((target, vec![RecordType::SRV, RecordType::TXT]));
+                        }
+                    }
+                }
+            }
+            RData::SRV(srv) => {
+                let target: String = srv.target().to_string();
+                self.responders.entry(owner.clone()).or_insert(responder);
+                self.srv.insert(owner, (target.clone(), srv.port()));
+                if !self.addrs.contains_key(&target) {
+                    queries.push((target, vec![RecordType::A, RecordType::AAAA]));
+                }
+            }
+            RData::TXT(txt) => {
+                let strings: Vec<String> = txt
+                    .txt_data()
+                    .iter()
+                    .map(|data| String::from_utf8_lossy(data).to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                self.txt.insert(owner, strings);
+            }
+            RData::A(a) => self.add_addr(owner, IpAddr::V4(a.0)),
+            RData::AAAA(aaaa) => self.add_addr(owner, IpAddr::V6(aaaa.0)),
+            _ => {}
+        }
+        queries
+    }
+    fn add_addr(&mut self, host: String, ip_addr: IpAddr) {
+        let ips: &mut Vec<IpAddr> = self.addrs.entry(host).or_default();
+        if !ips.contains(&ip_addr) {
+            ips.push(ip_addr);
+        }
+    }
+    /// Group service instances by host
+    fn into_devices(self) -> Vec<MdnsDevice> {
+        let mut devices: Vec<MdnsDevice> = Vec::new();
+        for (instance, (service_type, label)) in &self.instances {
+            let responder: Option<IpAddr> = self.responders.get(instance).cloned();
+            let (host, port): (String, u16) = match self.srv.get(instance) {
+                Some((host, port)) => (host.clone(), *port),
+                None => match responder {
+                    Some(ip_addr) => (ip_addr.to_string(), 0),
+                    None => continue,
+                },
+            };
+            let mut ips: Vec<IpAddr> = self.addrs.get(&host).cloned().unwrap_or_default();
+            if ips.is_empty() {
+                if let Some(ip_addr) = responder {
+                    ips.push(ip_addr);
+                }
+            }
+            let service = MdnsService {
+                instance: label.clone(),
+                service_type: service_type.clone(),
+                port: port,
+                txt: self.txt.get(instance).cloned().unwrap_or_default(),
+            };
+            let host_name: String = trim_name(&host);
+            match devices.iter_mut().find(|d| d.host_name == host_name) {
+                Some(device) => {
+                    for ip_addr in ips {
+                        if !device.ips.contains(&ip_addr) {
+                            device.ips.push(ip_addr);
+                        }
+                    }
+                    device.services.push(service);
+                }
+                None => devices.push(MdnsDevice {
+                    host_name: host_name,
+                    ips: ips,
+                    services: vec![service],
+                }),
+            }
+        }
+        for device in devices.iter_mut() {
+            device.ips.sort();
+            device.services.sort_by(|a, b| {
+                (a.service_type.as_str(), a.instance.as_str())
+                    .cmp(&(b.service_type.as_str(), b.instance.as_str()))
+            });
+        }
+        devices.sort_by(|a, b| a.ips.first().cmp(&b.ips.first()));
+        devices
+    }
+}
+
+/// Browse DNS-SD services on the link of the source address.
+/// Listens for the whole timeout since responders answer with random delays
+pub fn discover(
+    src_ip: Ipv4Addr,
+    service_types: &[String],
+    timeout: Duration,
+) -> Result<MdnsDiscoveryResult, String> {
+    let start_time: Instant = Instant::now();
+    let socket: UdpSocket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(src_ip), 0))
+        .map_err(|e| format!("Failed to bind socket: {}", e))?;
+    let _ = socket.set_multicast_ttl_v4(255);
+    let _ = socket.set_read_timeout(Some(Duration::from_millis(100)));
+    let mut cache = MdnsCache::default();
+    let mut queried: HashSet<String> = HashSet::new();
+    send_query(&socket, SERVICES_META_QUERY, &[RecordType::PTR])?;
+    for service_type in service_types {
+        let name: String = format!("{}.local.", service_type.trim_end_matches('.'));
+        if queried.insert(query_key(&name, &[RecordType::PTR])) {
+            send_query(&socket, &name, &[RecordType::PTR])?;
+        }
+    }
+    let mut buf: [u8; 9000] = [0; 9000];
+    while start_time.elapsed() < timeout {
+        let (len, src) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(_) => continue,
+        };
+        let message: Message = match Message::from_bytes(&buf[..len]) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+        if message.message_type() != MessageType::Response {
+            continue;
+        }
+        let mut follow_ups: Vec<(String, Vec<RecordType>)> = Vec::new();
+        for record in message.answers().iter().chain(message.additionals()) {
+            follow_ups.extend(cache.add_record(record, src.ip()));
+        }
+        for (name, record_types) in follow_ups {
+            if queried.insert(query_key(&name, &record_types)) {
+                let _ = send_query(&socket, &name, &record_types);
+            }
+        }
+    }
+    let mut discovered_types: Vec<String> = cache.service_types.clone();
+    discovered_types.sort();
+    Ok(MdnsDiscoveryResult {
+        service_types: discovered_types,
+        devices: cache.into_devices(),
+        scan_time: start_time.elapsed(),
+    })
+}
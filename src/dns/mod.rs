@@ -1,4 +1,17 @@
+pub mod ad;
+pub mod asn;
+pub mod axfr;
+pub mod cdn;
+pub mod checkpoint;
 pub mod domain;
+pub mod idn;
+pub mod llmnr;
+pub mod mdns;
+pub mod netbios;
+pub mod passive;
+pub mod query;
+pub mod rdns;
+pub mod resolver;
 pub mod result;
 pub mod scanner;
 pub mod setting;
@@ -45,6 +58,13 @@ pub fn lookup_host_name(host_name: &str) -> Option<IpAddr> {
     }
 }
 
+/// Resolve host name to IPv6 address (AAAA)
+pub fn lookup_host_name_ipv6(host_name: &str) -> Option<IpAddr> {
+    resolve_domain(host_name)
+        .into_iter()
+        .find(|ip_addr| ip_addr.is_ipv6())
+}
+
 pub async fn lookup_host_name_async(host_name: String) -> Option<IpAddr> {
     let ip_vec: Vec<IpAddr> = resolve_domain_async(host_name).await;
     let mut ipv6_vec: Vec<IpAddr> = vec![];
@@ -74,6 +94,12 @@ pub fn lookup_ip_addr(ip_addr: &IpAddr) -> Option<String> {
     }
 }
 
+/// Reverse lookup with the specified timeout. Single attempt
+pub fn lookup_ip_addr_with_timeout(ip_addr: &IpAddr, timeout: Duration) -> Option<String> {
+    let names: Vec<String> = resolve_ip_with_timeout(ip_addr, timeout);
+    names.into_iter().next()
+}
+
 pub async fn lookup_ip_addr_async(ip_addr: String) -> String {
     let ips: Vec<String> = resolve_ip_async(ip_addr).await;
     if ips.len() > 0 {
@@ -146,6 +172,54 @@ fn resolve_ip(ip_addr: &IpAddr) -> Vec<String> {
     }
 }
 
+#[cfg(any(unix, target_os = "windows"))]
+fn resolve_ip_with_timeout(ip_addr: &IpAddr, timeout: Duration) -> Vec<String> {
+    let mut system_conf = match hickory_resolver::system_conf::read_system_conf() {
+        Ok(system_conf) => system_conf,
+        Err(_) => return vec![],
+    };
+    system_conf.1.timeout = timeout;
+    system_conf.1.attempts = 1;
+    match Resolver::new(system_conf.0, system_conf.1) {
+        Ok(resolver) => reverse_lookup_names(&resolver, ip_addr),
+        Err(_) => vec![],
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn resolve_ip_with_timeout(ip_addr: &IpAddr, timeout: Duration) -> Vec<String> {
+    let mut opts = ResolverOpts::default();
+    opts.timeout = timeout;
+    opts.attempts = 1;
+    match Resolver::new(ResolverConfig::default(), opts) {
+        Ok(resolver) => reverse_lookup_names(&resolver, ip_addr),
+        Err(_) => vec![],
+    }
+}
+
+fn reverse_lookup_names(resolver: &Resolver, ip_addr: &IpAddr) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    match resolver.reverse_lookup(*ip_addr) {
+        Ok(rlookup) => {
+            for record in rlookup.as_lookup().record_iter() {
+                match record.data() {
+                    Some(data) => {
+                        let name = data.to_string();
+                        if name.ends_with(".") {
+                            names.push(name[0..name.len() - 1].to_string());
+                        } else {
+                            names.push(name);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            names
+        }
+        Err(_) => names,
+    }
+}
+
 #[cfg(not(any(unix, target_os = "windows")))]
 fn resolve_ip(ip_addr: IpAddr) -> Vec<String> {
     let mut names: Vec<String> = vec![];
@@ -275,7 +349,7 @@ pub async fn lookup_ips_async(ips: Vec<IpAddr>) -> HashMap<IpAddr, String> {
             let names = resolve_ip_async(ip.to_string()).await;
             (ip, names)
         })
-        .buffer_unordered(10);
+        .buffer_unordered(crate::config::DEFAULT_DNS_CONCURRENCY);
     let mut results: HashMap<IpAddr, String> = HashMap::new();
     while let Some(result) = tasks.next().await {
         results.insert(
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+/// NetBIOS Name Service port
+const NBNS_PORT: u16 = 137;
+
+/// NBSTAT question type
+const NBSTAT_TYPE: u16 = 0x0021;
+
+/// Name of the NetBIOS name table
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetbiosName {
+    pub name: String,
+    /// Service suffix (e.g. 0x00 workstation, 0x20 file server)
+    pub suffix: u8,
+    /// Group (workgroup/domain) name
+    pub group: bool,
+}
+
+/// Node status (NBSTAT) response of a host
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetbiosNodeStatus {
+    /// Computer name
+    pub name: Option<String>,
+    /// Workgroup or domain
+    pub workgroup: Option<String>,
+    pub mac_addr: Option<String>,
+    pub names: Vec<NetbiosName>,
+}
+
+/// Node status request for the wildcard name "*"
+fn build_request(id: u16) -> Vec<u8> {
+    let mut packet: Vec<u8> = Vec::with_capacity(50);
+    packet.extend_from_slice(&id.to_be_bytes());
+    // Flags, QDCOUNT 1, ANCOUNT, NSCOUNT, ARCOUNT
+    packet.extend_from_slice(&[0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    // First-level encoding of "*" padded with NUL to 16 bytes
+    packet.push(0x20);
+    let mut name: [u8; 16] = [0; 16];
+    name[0] = b'*';
+    for b in name {
+        packet.push(b'A' + (b >> 4));
+        packet.push(b'A' + (b & 0x0f));
+    }
+    packet.push(0x00);
+    packet.extend_from_slice(&NBSTAT_TYPE.to_be_bytes());
+    packet.extend_from_slice(&[0x00, 0x01]);
+    packet
+}
+
+fn parse_response(id: u16, buf: &[u8]) -> Option<NetbiosNodeStatus> {
+    if buf.len() < 12 || u16::from_be_bytes([buf[0], buf[1]]) != id {
+        return None;
+    }
+    // Answer name. Either a compression pointer or labels
+    let mut offset: usize = 12;
+    loop {
+        let len: u8 = *buf.get(offset)?;
+        if len & 0xc0 == 0xc0 {
+            offset += 2;
+            break;
+        }
+        offset += 1 + len as usize;
+        if len == 0 {
+            break;
+        }
+    }
+    // Type, class, TTL and RDLENGTH
+    offset += 10;
+    let count: usize = *buf.get(offset)? as usize;
+    offset += 1;
+    let mut status = NetbiosNodeStatus {
+        name: None,
+        workgroup: None,
+        mac_addr: None,
+        names: Vec::new(),
+    };
+    for _ in 0..count {
+        let entry: &[u8] = buf.get(offset..offset + 18)?;
+        let name: String = String::from_utf8_lossy(&entry[..15]).trim_end().to_string();
+        let suffix: u8 = entry[15];
+        let group: bool = entry[16] & 0x80 != 0;
+        if suffix == 0x00 {
+            if group && status.workgroup.is_none() {
+                status.workgroup = Some(name.clone());
+            } else if !group && status.name.is_none() {
+                status.name = Some(name.clone());
+            }
+        }
+        status.names.push(NetbiosName {
+            name: name,
+            suffix: suffix,
+            group: group,
+        });
+        offset += 18;
+    }
+    if let Some(mac) = buf.get(offset..offset + 6) {
+        // Samba answers with zeros
+        if mac.iter().any(|b| *b != 0) {
+            let octets: Vec<String> = mac.iter().map(|b| format!("{:02x}", b)).collect();
+            status.mac_addr = Some(octets.join(":"));
+        }
+    }
+    Some(status)
+}
+
+/// Query the NetBIOS name table of the host (nbtstat -A)
+pub async fn node_status(ip_addr: IpAddr, timeout: Duration) -> Option<NetbiosNodeStatus> {
+    let bind_addr: SocketAddr = match ip_addr {
+        IpAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+        IpAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+    };
+    let socket: UdpSocket = UdpSocket::bind(bind_addr).await.ok()?;
+    let id: u16 = rand::random::<u16>();
+    socket
+        .send_to(&build_request(id), SocketAddr::new(ip_addr, NBNS_PORT))
+        .await
+        .ok()?;
+    let mut buf: [u8; 1024] = [0; 1024];
+    let len: usize = match tokio::time::timeout(timeout, socket.recv(&mut buf)).await {
+        Ok(Ok(len)) => len,
+        _ => return None,
+    };
+    parse_response(id, &buf[..len])
+}
@@ -0,0 +1,107 @@
+use super::domain::CertEntry;
+use super::setting::{URL_CRT, URL_HACKERTARGET_HOSTSEARCH};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Public source of subdomain names that can be queried without touching the target
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PassiveSource {
+    /// Certificate transparency logs via crt.sh
+    CrtSh,
+    /// HackerTarget host search
+    HackerTarget,
+}
+
+impl PassiveSource {
+    pub fn all() -> Vec<PassiveSource> {
+        vec![PassiveSource::CrtSh, PassiveSource::HackerTarget]
+    }
+    pub fn name(&self) -> String {
+        match *self {
+            PassiveSource::CrtSh => String::from("crt.sh"),
+            PassiveSource::HackerTarget => String::from("HackerTarget"),
+        }
+    }
+    /// Query the source and return subdomains of the base domain
+    pub fn find_subdomains(
+        &self,
+        base_domain: &str,
+        timeout: Duration,
+    ) -> Result<Vec<String>, String> {
+        match *self {
+            PassiveSource::CrtSh => find_crtsh(base_domain, timeout),
+            PassiveSource::HackerTarget => find_hackertarget(base_domain, timeout),
+        }
+    }
+}
+
+fn find_crtsh(base_domain: &str, timeout: Duration) -> Result<Vec<String>, String> {
+    // Wildcard query (%.example.com) covers every name under the domain
+    let url: String = format!(
+        "{}?q={}&output=json",
+        URL_CRT,
+        crate::http::encode_query(&format!("%.{}", base_domain))
+    );
+    let res = crate::http::get(&url, &[("Accept", "application/json")], timeout)?;
+    if !res.is_success() {
+        return Err(format!("crt.sh returned HTTP {}", res.status));
+    }
+    let certs: Vec<CertEntry> = match serde_json::from_slice(&res.body) {
+        Ok(certs) => certs,
+        Err(e) => return Err(format!("Failed to parse crt.sh response: {}", e)),
+    };
+    let mut names: Vec<String> = Vec::new();
+    for cert in certs {
+        for value in cert
+            .name_value
+            .lines()
+            .chain(std::iter::once(cert.common_name.as_str()))
+        {
+            push_subdomain(&mut names, value, base_domain);
+        }
+    }
+    Ok(names)
+}
+
+fn find_hackertarget(base_domain: &str, timeout: Duration) -> Result<Vec<String>, String> {
+    let url: String = format!(
+        "{}?q={}",
+        URL_HACKERTARGET_HOSTSEARCH,
+        crate::http::encode_query(base_domain)
+    );
+    let res = crate::http::get(&url, &[], timeout)?;
+    if !res.is_success() {
+        return Err(format!("HackerTarget returned HTTP {}", res.status));
+    }
+    let text: String = res.text();
+    // Errors (e.g. API count exceeded) are returned as plain text with 200
+    if text.starts_with("error") || text.starts_with("API count exceeded") {
+        return Err(format!("HackerTarget: {}", text.trim()));
+    }
+    let mut names: Vec<String> = Vec::new();
+    // Each line is "host,ip"
+    for line in text.lines() {
+        if let Some(host) = line.split(',').next() {
+            push_subdomain(&mut names, host, base_domain);
+        }
+    }
+    Ok(names)
+}
+
+/// Normalize the name and add it if it is a subdomain of the base domain
+fn push_subdomain(names: &mut Vec<String>, name: &str, base_domain: &str) {
+    let name: String = name
+        .trim()
+        .trim_start_matches("*.")
+        .trim_end_matches('.')
+        .to_lowercase();
+    if is_subdomain(&name, base_domain) && !names.contains(&name) {
+        names.push(name);
+    }
+}
+
+pub fn is_subdomain(domain: &str, apex_domain: &str) -> bool {
+    domain.len() > apex_domain.len()
+        && domain.ends_with(apex_domain)
+        && domain[..domain.len() - apex_domain.len()].ends_with('.')
+}
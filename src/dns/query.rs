@@ -0,0 +1,346 @@
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use hickory_resolver::config::ResolverConfig;
+use hickory_resolver::proto::op::{Edns, Message, MessageType, OpCode, Query};
+use hickory_resolver::proto::rr::{Name, Record, RecordType};
+use hickory_resolver::proto::serialize::binary::{BinDecodable, BinEncodable};
+use serde::{Deserialize, Serialize};
+
+/// Record types supported by the dns subcommand
+pub const QUERY_TYPES: [&str; 10] = [
+    "A", "AAAA", "MX", "TXT", "NS", "SOA", "CAA", "CNAME", "PTR", "SRV",
+];
+
+/// Popular names checked by cache snooping when no list is given
+pub const DEFAULT_SNOOP_NAMES: [&str; 20] = [
+    "google.com",
+    "youtube.com",
+    "facebook.com",
+    "instagram.com",
+    "twitter.com",
+    "x.com",
+    "wikipedia.org",
+    "amazon.com",
+    "apple.com",
+    "microsoft.com",
+    "office.com",
+    "live.com",
+    "linkedin.com",
+    "netflix.com",
+    "github.com",
+    "zoom.us",
+    "slack.com",
+    "dropbox.com",
+    "whatsapp.net",
+    "windowsupdate.com",
+];
+
+/// UDP payload size advertised with EDNS
+const EDNS_PAYLOAD_SIZE: u16 = 1232;
+
+/// Resource record in a response section
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResourceRecord {
+    pub name: String,
+    pub ttl: u32,
+    pub record_type: String,
+    /// Record data in presentation format
+    pub value: String,
+}
+
+impl ResourceRecord {
+    fn from_record(record: &Record) -> ResourceRecord {
+        ResourceRecord {
+            name: record.name().to_string(),
+            ttl: record.ttl(),
+            record_type: record.record_type().to_string(),
+            value: match record.data() {
+                Some(rdata) => rdata.to_string(),
+                None => String::new(),
+            },
+        }
+    }
+}
+
+/// Result of a single DNS query
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DnsQueryResult {
+    /// Query name
+    pub name: String,
+    pub record_type: String,
+    pub server: SocketAddr,
+    /// udp or tcp
+    pub transport: String,
+    /// Response code (e.g. NOERROR, NXDOMAIN)
+    pub response_code: String,
+    /// Header flags set in the response (e.g. qr, rd, ra)
+    pub flags: Vec<String>,
+    pub answers: Vec<ResourceRecord>,
+    pub authorities: Vec<ResourceRecord>,
+    pub additionals: Vec<ResourceRecord>,
+    pub response_time: Duration,
+}
+
+/// First nameserver of the system configuration
+#[cfg(any(unix, target_os = "windows"))]
+pub fn get_system_server() -> SocketAddr {
+    let config: ResolverConfig = match hickory_resolver::system_conf::read_system_conf() {
+        Ok((config, _)) => config,
+        Err(_) => ResolverConfig::default(),
+    };
+    match config.name_servers().first() {
+        Some(name_server) => name_server.socket_addr,
+        None => SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)), 53),
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+pub fn get_system_server() -> SocketAddr {
+    match ResolverConfig::default().name_servers().first() {
+        Some(name_server) => name_server.socket_addr,
+        None => SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)), 53),
+    }
+}
+
+/// Build query name. IP address is converted to its reverse name for PTR
+fn query_name(name: &str, record_type: RecordType) -> Result<Name, String> {
+    if record_type == RecordType::PTR {
+        if let Ok(ip_addr) = name.parse::<IpAddr>() {
+            return Ok(Name::from(ip_addr));
+        }
+    }
+    let mut fqdn: String = name.to_string();
+    if !fqdn.ends_with('.') {
+        fqdn.push('.');
+    }
+    Name::from_str(&fqdn).map_err(|e| format!("Invalid name {}: {}", name, e))
+}
+
+/// Build a query with EDNS. Returns the message ID and the wire format
+pub(crate) fn build_query(
+    name: Name,
+    record_type: RecordType,
+    recursion_desired: bool,
+) -> Result<(u16, Vec<u8>), String> {
+    let id: u16 = rand::random::<u16>();
+    let mut edns = Edns::new();
+    edns.set_max_payload(EDNS_PAYLOAD_SIZE);
+    let mut query = Message::new();
+    query
+        .set_id(id)
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(recursion_desired)
+        .add_query(Query::query(name, record_type))
+        .set_edns(edns);
+    let bytes: Vec<u8> = query
+        .to_bytes()
+        .map_err(|e| format!("Failed to build query: {}", e))?;
+    Ok((id, bytes))
+}
+
+fn exchange_udp(
+    server: SocketAddr,
+    id: u16,
+    query: &[u8],
+    timeout: Duration,
+) -> Result<Message, String> {
+    let bind_addr: SocketAddr = match server {
+        SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+        SocketAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+    };
+    let socket: UdpSocket =
+        UdpSocket::bind(bind_addr).map_err(|e| format!("Failed to bind socket: {}", e))?;
+    let _ = socket.set_read_timeout(Some(timeout));
+    socket
+        .send_to(query, server)
+        .map_err(|e| format!("Failed to send query: {}", e))?;
+    let start_time: Instant = Instant::now();
+    let mut buf: [u8; 4096] = [0; 4096];
+    loop {
+        let (len, src) = socket
+            .recv_from(&mut buf)
+            .map_err(|e| format!("No response from {}: {}", server, e))?;
+        if src == server {
+            if let Ok(message) = Message::from_bytes(&buf[..len]) {
+                if message.id() == id {
+                    return Ok(message);
+                }
+            }
+        }
+        if start_time.elapsed() > timeout {
+            return Err(format!("No response from {}", server));
+        }
+    }
+}
+
+fn exchange_tcp(server: SocketAddr, query: &[u8], timeout: Duration) -> Result<Message, String> {
+    let mut stream: TcpStream = TcpStream::connect_timeout(&server, timeout)
+        .map_err(|e| format!("Failed to connect to {}: {}", server, e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    let mut req: Vec<u8> = Vec::new();
+    req.extend_from_slice(&(query.len() as u16).to_be_bytes());
+    req.extend_from_slice(query);
+    stream
+        .write_all(&req)
+        .map_err(|e| format!("Failed to send query: {}", e))?;
+    super::axfr::read_message(&mut stream)
+}
+
+fn response_flags(message: &Message) -> Vec<String> {
+    let mut flags: Vec<String> = Vec::new();
+    let header_flags: [(bool, &str); 7] = [
+        (message.message_type() == MessageType::Response, "qr"),
+        (message.authoritative(), "aa"),
+        (message.truncated(), "tc"),
+        (message.recursion_desired(), "rd"),
+        (message.recursion_available(), "ra"),
+        (message.authentic_data(), "ad"),
+        (message.checking_disabled(), "cd"),
+    ];
+    for (set, flag) in header_flags {
+        if set {
+            flags.push(flag.to_string());
+        }
+    }
+    flags
+}
+
+/// Send a recursive query to the server. Truncated UDP responses are retried over TCP
+pub fn query(
+    name: &str,
+    record_type: RecordType,
+    server: SocketAddr,
+    use_tcp: bool,
+    timeout: Duration,
+) -> Result<DnsQueryResult, String> {
+    query_with_recursion(name, record_type, server, use_tcp, true, timeout)
+}
+
+fn query_with_recursion(
+    name: &str,
+    record_type: RecordType,
+    server: SocketAddr,
+    use_tcp: bool,
+    recursion_desired: bool,
+    timeout: Duration,
+) -> Result<DnsQueryResult, String> {
+    let (id, query_bytes) = build_query(
+        query_name(name, record_type)?,
+        record_type,
+        recursion_desired,
+    )?;
+    let start_time: Instant = Instant::now();
+    let mut transport: &str = if use_tcp { "tcp" } else { "udp" };
+    let mut message: Message = if use_tcp {
+        exchange_tcp(server, &query_bytes, timeout)?
+    } else {
+        exchange_udp(server, id, &query_bytes, timeout)?
+    };
+    if !use_tcp && message.truncated() {
+        message = exchange_tcp(server, &query_bytes, timeout)?;
+        transport = "tcp";
+    }
+    let response_time: Duration = start_time.elapsed();
+    Ok(DnsQueryResult {
+        name: name.to_string(),
+        record_type: record_type.to_string(),
+        server: server,
+        transport: transport.to_string(),
+        response_code: format!("{:?}", message.response_code()).to_uppercase(),
+        flags: response_flags(&message),
+        answers: message
+            .answers()
+            .iter()
+            .map(ResourceRecord::from_record)
+            .collect(),
+        authorities: message
+            .name_servers()
+            .iter()
+            .map(ResourceRecord::from_record)
+            .collect(),
+        additionals: message
+            .additionals()
+            .iter()
+            .map(ResourceRecord::from_record)
+            .collect(),
+        response_time: response_time,
+    })
+}
+
+/// Cache snooping result of a name
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheSnoopEntry {
+    pub name: String,
+    /// The resolver answered from its cache
+    pub cached: bool,
+    /// Response code, or the error if no response
+    pub response_code: String,
+    /// Remaining TTL of the cached answer
+    pub ttl: Option<u32>,
+    pub answers: Vec<String>,
+}
+
+/// Result of DNS cache snooping against a resolver
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheSnoopResult {
+    pub server: SocketAddr,
+    pub record_type: String,
+    pub entries: Vec<CacheSnoopEntry>,
+    pub scan_time: Duration,
+}
+
+impl CacheSnoopResult {
+    pub fn cached_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.cached).count()
+    }
+}
+
+/// Send non-recursive queries so that the resolver answers only from its cache
+pub fn snoop_cache(
+    names: &[String],
+    record_type: RecordType,
+    server: SocketAddr,
+    timeout: Duration,
+) -> CacheSnoopResult {
+    let start_time: Instant = Instant::now();
+    let mut entries: Vec<CacheSnoopEntry> = Vec::new();
+    for name in names {
+        let entry: CacheSnoopEntry =
+            match query_with_recursion(name, record_type, server, false, false, timeout) {
+                Ok(result) => {
+                    // Referrals and CNAME-only answers are not cache hits for the type
+                    let answers: Vec<&ResourceRecord> = result
+                        .answers
+                        .iter()
+                        .filter(|r| r.record_type == result.record_type)
+                        .collect();
+                    CacheSnoopEntry {
+                        name: name.clone(),
+                        cached: result.response_code == "NOERROR" && !answers.is_empty(),
+                        response_code: result.response_code.clone(),
+                        ttl: answers.iter().map(|r| r.ttl).min(),
+                        answers: answers.iter().map(|r| r.value.clone()).collect(),
+                    }
+                }
+                Err(e) => CacheSnoopEntry {
+                    name: name.clone(),
+                    cached: false,
+                    response_code: e,
+                    ttl: None,
+                    answers: vec![],
+                },
+            };
+        entries.push(entry);
+    }
+    CacheSnoopResult {
+        server: server,
+        record_type: record_type.to_string(),
+        entries: entries,
+        scan_time: start_time.elapsed(),
+    }
+}
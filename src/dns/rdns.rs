@@ -0,0 +1,123 @@
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+
+use super::netbios::NetbiosNodeStatus;
+use super::resolver::{ResolverPool, ScanResolver};
+
+/// Names of an address
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PtrRecord {
+    pub ip_addr: IpAddr,
+    /// PTR names from DNS
+    pub names: Vec<String>,
+    /// Names answered by the host over LLMNR
+    #[serde(default)]
+    pub llmnr_names: Vec<String>,
+    #[serde(default)]
+    pub netbios: Option<NetbiosNodeStatus>,
+}
+
+impl PtrRecord {
+    pub fn has_name(&self) -> bool {
+        !self.names.is_empty() || !self.llmnr_names.is_empty() || self.netbios.is_some()
+    }
+}
+
+/// Settings of reverse DNS sweep
+#[derive(Clone)]
+pub struct RdnsSetting {
+    /// Custom resolvers used in rotation instead of the system resolver
+    pub resolver_pool: Option<Arc<ResolverPool>>,
+    /// Max DNS queries per second. None for unlimited
+    pub qps: Option<u32>,
+    pub concurrency: usize,
+    /// Timeout of each lookup
+    pub resolve_timeout: Duration,
+    /// Also query NetBIOS-NS node status
+    pub netbios: bool,
+    /// Also query LLMNR
+    pub llmnr: bool,
+}
+
+/// Result of reverse DNS sweep
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RdnsSweepResult {
+    /// Target as specified (e.g. 10.0.0.0/24)
+    pub target: String,
+    /// Number of addresses looked up
+    pub total: usize,
+    /// Addresses with names, in address order
+    pub records: Vec<PtrRecord>,
+    pub scan_time: Duration,
+}
+
+/// Look up names of the addresses in parallel.
+/// Each looked-up address is sent to the progress channel
+pub async fn sweep(
+    target: String,
+    ips: Vec<IpAddr>,
+    setting: RdnsSetting,
+    ptx: Arc<Mutex<Sender<IpAddr>>>,
+) -> RdnsSweepResult {
+    let start_time: Instant = Instant::now();
+    let total: usize = ips.len();
+    let resolver: ScanResolver = ScanResolver::new(setting.resolver_pool.clone(), setting.qps);
+    let resolver: &ScanResolver = &resolver;
+    let setting: &RdnsSetting = &setting;
+    let ptx: &Arc<Mutex<Sender<IpAddr>>> = &ptx;
+    let mut records: Vec<PtrRecord> = stream::iter(ips)
+        .map(|ip_addr| async move {
+            let ptr = async {
+                resolver.wait_turn().await;
+                match timeout(setting.resolve_timeout, resolver.reverse_lookup(ip_addr)).await {
+                    Ok(names) => names,
+                    Err(_) => vec![],
+                }
+            };
+            // Hosts answer these directly. Not subject to the resolver rate limit
+            let llmnr = async {
+                if setting.llmnr {
+                    super::llmnr::reverse_lookup(ip_addr, setting.resolve_timeout).await
+                } else {
+                    vec![]
+                }
+            };
+            let netbios = async {
+                if setting.netbios && ip_addr.is_ipv4() {
+                    super::netbios::node_status(ip_addr, setting.resolve_timeout).await
+                } else {
+                    None
+                }
+            };
+            let (names, llmnr_names, netbios): (
+                Vec<String>,
+                Vec<String>,
+                Option<NetbiosNodeStatus>,
+            ) = futures::join!(ptr, llmnr, netbios);
+            if let Ok(lr) = ptx.lock() {
+                let _ = lr.send(ip_addr);
+            }
+            PtrRecord {
+                ip_addr: ip_addr,
+                names: names,
+                llmnr_names: llmnr_names,
+                netbios: netbios,
+            }
+        })
+        .buffer_unordered(setting.concurrency)
+        .filter(|record| futures::future::ready(record.has_name()))
+        .collect()
+        .await;
+    records.sort_by_key(|record| record.ip_addr);
+    RdnsSweepResult {
+        target: target,
+        total: total,
+        records: records,
+        scan_time: start_time.elapsed(),
+    }
+}
@@ -0,0 +1,298 @@
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+use hickory_resolver::proto::rr::{Name, RData, RecordType};
+use hickory_resolver::TokioAsyncResolver;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Consecutive failures after which a resolver is taken out of rotation
+const MAX_CONSECUTIVE_FAILURES: usize = 5;
+
+struct PoolEntry {
+    addr: SocketAddr,
+    resolver: TokioAsyncResolver,
+    failures: AtomicUsize,
+    healthy: AtomicBool,
+}
+
+impl PoolEntry {
+    /// Count the failure. Returns true if the error is a negative answer,
+    /// which is still a healthy response
+    fn handle_error(&self, e: &ResolveError) -> bool {
+        match e.kind() {
+            ResolveErrorKind::NoRecordsFound { .. } => {
+                self.failures.store(0, Ordering::Relaxed);
+                true
+            }
+            _ => {
+                let failures: usize = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+                if failures >= MAX_CONSECUTIVE_FAILURES {
+                    self.healthy.store(false, Ordering::Relaxed);
+                }
+                false
+            }
+        }
+    }
+}
+
+/// Set of DNS resolvers queried in rotation.
+///
+/// A resolver that times out repeatedly is skipped until every resolver is unhealthy,
+/// at which point all of them are put back into rotation.
+pub struct ResolverPool {
+    entries: Vec<PoolEntry>,
+    next: AtomicUsize,
+}
+
+impl ResolverPool {
+    pub fn new(addrs: &[SocketAddr], timeout: Duration) -> ResolverPool {
+        let mut entries: Vec<PoolEntry> = Vec::new();
+        for addr in addrs {
+            let group = NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true);
+            let config = ResolverConfig::from_parts(None, vec![], group);
+            let mut opts = ResolverOpts::default();
+            opts.timeout = timeout;
+            // Rotation handles retries
+            opts.attempts = 1;
+            entries.push(PoolEntry {
+                addr: *addr,
+                resolver: TokioAsyncResolver::tokio(config, opts),
+                failures: AtomicUsize::new(0),
+                healthy: AtomicBool::new(true),
+            });
+        }
+        ResolverPool {
+            entries: entries,
+            next: AtomicUsize::new(0),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn addrs(&self) -> Vec<SocketAddr> {
+        self.entries.iter().map(|e| e.addr).collect()
+    }
+    /// Query each resolver for the name and drop the ones that do not answer.
+    /// Returns the removed resolvers with the reason
+    pub async fn health_check(&mut self, name: &str) -> Vec<(SocketAddr, String)> {
+        let mut failed: Vec<(SocketAddr, String)> = Vec::new();
+        let mut alive: Vec<PoolEntry> = Vec::new();
+        for entry in self.entries.drain(..) {
+            match entry.resolver.lookup_ip(name).await {
+                Ok(_) => alive.push(entry),
+                Err(e) => match e.kind() {
+                    ResolveErrorKind::NoRecordsFound { .. } => alive.push(entry),
+                    _ => failed.push((entry.addr, e.to_string())),
+                },
+            }
+        }
+        self.entries = alive;
+        failed
+    }
+    fn next_entry(&self) -> Option<&PoolEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        if self
+            .entries
+            .iter()
+            .all(|e| !e.healthy.load(Ordering::Relaxed))
+        {
+            for entry in &self.entries {
+                entry.failures.store(0, Ordering::Relaxed);
+                entry.healthy.store(true, Ordering::Relaxed);
+            }
+        }
+        for _ in 0..self.entries.len() {
+            let index: usize = self.next.fetch_add(1, Ordering::Relaxed) % self.entries.len();
+            let entry: &PoolEntry = &self.entries[index];
+            if entry.healthy.load(Ordering::Relaxed) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+    /// Resolve the name, moving on to the next resolver on failure
+    pub async fn lookup_ip(&self, name: &str) -> Vec<IpAddr> {
+        for _ in 0..self.entries.len() {
+            let entry: &PoolEntry = match self.next_entry() {
+                Some(entry) => entry,
+                None => break,
+            };
+            match entry.resolver.lookup_ip(name).await {
+                Ok(lookup) => {
+                    entry.failures.store(0, Ordering::Relaxed);
+                    return lookup.iter().collect();
+                }
+                Err(e) => {
+                    if entry.handle_error(&e) {
+                        return vec![];
+                    }
+                }
+            }
+        }
+        vec![]
+    }
+    /// Look up records of the type, moving on to the next resolver on failure
+    pub async fn lookup(&self, name: &str, record_type: RecordType) -> Vec<RData> {
+        for _ in 0..self.entries.len() {
+            let entry: &PoolEntry = match self.next_entry() {
+                Some(entry) => entry,
+                None => break,
+            };
+            match entry.resolver.lookup(name, record_type).await {
+                Ok(lookup) => {
+                    entry.failures.store(0, Ordering::Relaxed);
+                    return lookup
+                        .record_iter()
+                        .filter(|r| r.record_type() == record_type)
+                        .filter_map(|r| r.data().cloned())
+                        .collect();
+                }
+                Err(e) => {
+                    if entry.handle_error(&e) {
+                        return vec![];
+                    }
+                }
+            }
+        }
+        vec![]
+    }
+}
+
+/// Resolver used for the lookups of a scan
+pub(crate) struct ScanResolver {
+    backend: ResolverBackend,
+    rate_limiter: Option<RateLimiter>,
+}
+
+enum ResolverBackend {
+    System(TokioAsyncResolver),
+    Pool(Arc<ResolverPool>),
+}
+
+impl ScanResolver {
+    /// Use the pool if given, otherwise the system resolver. qps limits the query rate
+    pub(crate) fn new(resolver_pool: Option<Arc<ResolverPool>>, qps: Option<u32>) -> ScanResolver {
+        ScanResolver {
+            backend: match resolver_pool {
+                Some(resolver_pool) => ResolverBackend::Pool(resolver_pool),
+                None => ResolverBackend::System(system_resolver()),
+            },
+            rate_limiter: qps.map(RateLimiter::new),
+        }
+    }
+    /// Wait for the next query slot. Call before starting the lookup timeout
+    pub(crate) async fn wait_turn(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.wait().await;
+        }
+    }
+    pub(crate) async fn lookup_ip(&self, name: &str) -> Vec<IpAddr> {
+        match &self.backend {
+            ResolverBackend::System(resolver) => match resolver.lookup_ip(name).await {
+                Ok(lookup) => lookup.iter().collect(),
+                Err(_) => vec![],
+            },
+            ResolverBackend::Pool(resolver_pool) => resolver_pool.lookup_ip(name).await,
+        }
+    }
+    pub(crate) async fn lookup(&self, name: &str, record_type: RecordType) -> Vec<RData> {
+        match &self.backend {
+            ResolverBackend::System(resolver) => match resolver.lookup(name, record_type).await {
+                Ok(lookup) => lookup
+                    .record_iter()
+                    .filter(|r| r.record_type() == record_type)
+                    .filter_map(|r| r.data().cloned())
+                    .collect(),
+                Err(_) => vec![],
+            },
+            ResolverBackend::Pool(resolver_pool) => resolver_pool.lookup(name, record_type).await,
+        }
+    }
+    /// Look up PTR names of the address. Trailing dots are removed
+    pub(crate) async fn reverse_lookup(&self, ip_addr: IpAddr) -> Vec<String> {
+        self.lookup(&Name::from(ip_addr).to_string(), RecordType::PTR)
+            .await
+            .iter()
+            .map(|rdata| rdata.to_string().trim_end_matches('.').to_string())
+            .collect()
+    }
+}
+
+/// Spaces queries evenly to stay under the rate
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(qps: u32) -> RateLimiter {
+        RateLimiter {
+            interval: Duration::from_secs(1) / qps.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+    pub(crate) async fn wait(&self) {
+        let slot: Instant = {
+            let mut next = self.next.lock().unwrap();
+            let slot: Instant = std::cmp::max(*next, Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(any(unix, target_os = "windows"))]
+pub(crate) fn system_resolver() -> TokioAsyncResolver {
+    match TokioAsyncResolver::tokio_from_system_conf() {
+        Ok(resolver) => resolver,
+        Err(_) => TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default()),
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+pub(crate) fn system_resolver() -> TokioAsyncResolver {
+    TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default())
+}
+
+/// Parse resolver address. Port 53 is used if omitted (e.g. 1.1.1.1 or 1.1.1.1:5353)
+pub fn parse_resolver_addr(s: &str) -> Result<SocketAddr, String> {
+    let s: &str = s.trim();
+    if let Ok(ip_addr) = s.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip_addr, 53));
+    }
+    s.parse::<SocketAddr>()
+        .map_err(|_| format!("Invalid resolver address: {}", s))
+}
+
+/// Read resolver addresses from file. One address per line. Lines starting with # are ignored
+pub fn read_resolvers_file(file_path: &PathBuf) -> Result<Vec<SocketAddr>, String> {
+    let contents: String = match std::fs::read_to_string(file_path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            return Err(format!(
+                "Failed to read {}: {}",
+                file_path.to_string_lossy(),
+                e
+            ))
+        }
+    };
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    for line in contents.lines() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        addrs.push(parse_resolver_addr(line)?);
+    }
+    Ok(addrs)
+}
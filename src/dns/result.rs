@@ -1,6 +1,8 @@
 use crate::scan::result::ScanStatus;
 
+use super::axfr::ZoneTransfer;
 use super::domain::Domain;
+use crate::host::Host;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -15,6 +17,15 @@ pub struct DomainScanResult {
     pub scan_time: Duration,
     /// Scan job status
     pub scan_status: ScanStatus,
+    /// Zone transfer attempts against the authoritative nameservers
+    #[serde(default)]
+    pub zone_transfers: Vec<ZoneTransfer>,
+    /// Port scan results of the resolved addresses (--then-port)
+    #[serde(default)]
+    pub hosts: Vec<Host>,
+    /// Time taken to port scan
+    #[serde(default)]
+    pub port_scan_time: Duration,
 }
 
 impl DomainScanResult {
@@ -23,6 +34,9 @@ impl DomainScanResult {
             domains: vec![],
             scan_time: Duration::from_millis(0),
             scan_status: ScanStatus::Error(String::from("Scan not started")),
+            zone_transfers: vec![],
+            hosts: vec![],
+            port_scan_time: Duration::from_millis(0),
         }
     }
 }
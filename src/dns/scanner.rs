@@ -1,24 +1,17 @@
-use super::domain::Domain;
+use super::domain::{DnsRecord, Domain};
 use super::result::DomainScanResult;
 use futures::{stream, StreamExt};
+use std::collections::HashSet;
 use std::net::IpAddr;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::time::timeout;
 
-#[cfg(not(any(unix, target_os = "windows")))]
-use hickory_resolver::config::{ResolverConfig, ResolverOpts};
-use hickory_resolver::AsyncResolver;
+use hickory_resolver::proto::rr::RecordType;
 
-use super::setting::DEFAULT_USER_AGENT_FIREFOX;
-#[cfg(feature = "passive")]
-use crate::config::URL_CRT;
-#[cfg(feature = "passive")]
-use crate::model::CertEntry;
+use super::resolver::{ResolverPool, ScanResolver};
 use crate::scan::result::ScanStatus;
-#[cfg(feature = "passive")]
-use reqwest::Url;
 
 /// Structure for domain scan  
 ///
@@ -37,14 +30,24 @@ pub struct DomainScanner {
     pub concurrent_limit: usize,
     /// Result of domain scan.  
     pub scan_result: DomainScanResult,
-    /// Sender for progress messaging
-    tx: Arc<Mutex<Sender<String>>>,
+    /// Sender for progress messaging. Every looked-up name is sent, with ips if resolved
+    tx: Arc<Mutex<Sender<Domain>>>,
     /// Receiver for progress messaging
-    rx: Arc<Mutex<Receiver<String>>>,
-    /// Run passive scan
-    pub passive: bool,
-    /// User-Agent for passive scan
-    user_agent: String,
+    rx: Arc<Mutex<Receiver<Domain>>>,
+    /// Fully qualified names resolved in addition to the word-list (e.g. from passive sources)
+    pub domain_list: Vec<String>,
+    /// Subdomains found by an earlier run (--resume). Included in the result and recursion
+    pub known_domains: Vec<Domain>,
+    /// Words before this index are skipped under the base domain (--resume)
+    pub start_position: usize,
+    /// Custom resolvers used in rotation instead of the system resolver
+    pub resolver_pool: Option<Arc<ResolverPool>>,
+    /// Max queries per second. None for unlimited
+    pub qps: Option<u32>,
+    /// Levels to brute-force under each discovered subdomain. 0 disables recursion
+    pub recursive_depth: usize,
+    /// Record types fetched for each discovered subdomain
+    pub record_types: Vec<RecordType>,
 }
 
 impl DomainScanner {
@@ -60,8 +63,13 @@ impl DomainScanner {
             scan_result: DomainScanResult::new(),
             tx: Arc::new(Mutex::new(tx)),
             rx: Arc::new(Mutex::new(rx)),
-            passive: false,
-            user_agent: DEFAULT_USER_AGENT_FIREFOX.to_string(),
+            domain_list: vec![],
+            known_domains: vec![],
+            start_position: 0,
+            resolver_pool: None,
+            qps: None,
+            recursive_depth: 0,
+            record_types: vec![],
         };
         Ok(domain_scanner)
     }
@@ -84,57 +92,107 @@ impl DomainScanner {
     pub fn set_timeout(&mut self, timeout: Duration) {
         self.timeout = timeout;
     }
-    /// Set active/passive scan (default is active)
-    pub fn set_passive(&mut self, passive: bool) {
-        self.passive = passive;
+    /// Use custom resolvers instead of the system resolver
+    pub fn set_resolver_pool(&mut self, resolver_pool: ResolverPool) {
+        self.resolver_pool = Some(Arc::new(resolver_pool));
     }
-    /// Set user-agent for passive scan
-    pub fn set_user_agent(&mut self, user_agent: String) {
-        self.user_agent = user_agent;
+    /// Set max number of in-flight lookups
+    pub fn set_concurrent_limit(&mut self, concurrent_limit: usize) {
+        self.concurrent_limit = concurrent_limit;
     }
-    async fn scan_domain(&self) -> Result<Vec<Domain>, ()> {
-        if self.passive {
-            #[cfg(feature = "passive")]
-            match timeout(
-                self.timeout,
-                scan_subdomain_passive(
-                    self.base_domain.clone(),
+    /// Limit queries per second
+    pub fn set_qps(&mut self, qps: u32) {
+        self.qps = Some(qps);
+    }
+    /// Set depth of recursive brute-force under discovered subdomains
+    pub fn set_recursive_depth(&mut self, depth: usize) {
+        self.recursive_depth = depth;
+    }
+    /// Set record types to fetch for each discovered subdomain
+    pub fn set_record_types(&mut self, record_types: Vec<RecordType>) {
+        self.record_types = record_types;
+    }
+    /// Continue an earlier run. Words before the position are skipped
+    pub fn set_resume_state(&mut self, position: usize, domains: Vec<Domain>) {
+        self.start_position = position;
+        self.known_domains = domains;
+    }
+    /// Add fully qualified name to resolve
+    pub fn add_domain(&mut self, domain: String) {
+        if !self.domain_list.contains(&domain) {
+            self.domain_list.push(domain);
+        }
+    }
+    /// Brute-force the base domain, then each newly found subdomain up to recursive_depth
+    async fn scan_recursive(&self) -> Vec<Domain> {
+        // Built once and shared by every lookup of this scan
+        let resolver: ScanResolver = ScanResolver::new(self.resolver_pool.clone(), self.qps);
+        let start: usize = std::cmp::min(self.start_position, self.word_list.len());
+        let mut domains: Vec<Domain> = scan_subdomain(
+            self.base_domain.clone(),
+            &self.word_list[start..],
+            &self.domain_list,
+            &resolver,
+            &self.tx,
+            self.resolve_timeout,
+            self.concurrent_limit,
+        )
+        .await;
+        let mut known: HashSet<String> = domains.iter().map(|d| d.domain_name.clone()).collect();
+        for domain in &self.known_domains {
+            if known.insert(domain.domain_name.clone()) {
+                domains.push(domain.clone());
+            }
+        }
+        let mut parents: Vec<String> = domains.iter().map(|d| d.domain_name.clone()).collect();
+        for _ in 0..self.recursive_depth {
+            let mut found: Vec<String> = Vec::new();
+            for parent in parents {
+                // Every name resolves under a wildcard record
+                if is_wildcard(&resolver, &parent, self.resolve_timeout).await {
+                    continue;
+                }
+                let children: Vec<Domain> = scan_subdomain(
+                    parent,
+                    &self.word_list,
+                    &[],
+                    &resolver,
                     &self.tx,
                     self.resolve_timeout,
                     self.concurrent_limit,
-                    self.user_agent.clone(),
-                ),
-            )
-            .await
-            {
-                Ok(domains) => {
-                    return Ok(domains);
-                }
-                Err(_) => {
-                    return Err(());
+                )
+                .await;
+                for child in children {
+                    if known.insert(child.domain_name.clone()) {
+                        found.push(child.domain_name.clone());
+                        domains.push(child);
+                    }
                 }
             }
-            #[cfg(not(feature = "passive"))]
-            return Err(());
-        } else {
-            match timeout(
-                self.timeout,
-                scan_subdomain(
-                    self.base_domain.clone(),
-                    self.word_list.clone(),
-                    &self.tx,
-                    self.resolve_timeout,
-                    self.concurrent_limit,
-                ),
+            if found.is_empty() {
+                break;
+            }
+            parents = found;
+        }
+        if !self.record_types.is_empty() {
+            domains = fetch_records(
+                domains,
+                &self.record_types,
+                &resolver,
+                self.resolve_timeout,
+                self.concurrent_limit,
             )
-            .await
-            {
-                Ok(domains) => {
-                    return Ok(domains);
-                }
-                Err(_) => {
-                    return Err(());
-                }
+            .await;
+        }
+        domains
+    }
+    async fn scan_domain(&self) -> Result<Vec<Domain>, ()> {
+        match timeout(self.timeout, self.scan_recursive()).await {
+            Ok(domains) => {
+                return Ok(domains);
+            }
+            Err(_) => {
+                return Err(());
             }
         }
     }
@@ -142,11 +200,6 @@ impl DomainScanner {
     ///
     /// Results are stored in DomainScanner::scan_result
     pub async fn run_scan(&mut self) {
-        if self.passive && cfg!(not(feature = "passive")) {
-            self.scan_result.scan_status =
-                ScanStatus::Error(String::from("Passive scan not supported"));
-            return;
-        }
         let start_time = Instant::now();
         let res = self.scan_domain().await;
         match res {
@@ -170,231 +223,117 @@ impl DomainScanner {
         self.scan_result.clone()
     }
     /// Get progress receiver
-    pub fn get_progress_receiver(&self) -> Arc<Mutex<Receiver<String>>> {
+    pub fn get_progress_receiver(&self) -> Arc<Mutex<Receiver<Domain>>> {
         self.rx.clone()
     }
 }
 
-#[cfg(any(unix, target_os = "windows"))]
-async fn resolve_domain(host_name: String) -> Vec<IpAddr> {
-    let mut ips: Vec<IpAddr> = vec![];
-    let resolver = AsyncResolver::tokio_from_system_conf().unwrap();
-    match resolver.lookup_ip(host_name).await {
-        Ok(lip) => {
-            for ip in lip.iter() {
-                ips.push(ip);
-            }
-        }
-        Err(_) => {}
-    }
-    ips
-}
-
-#[cfg(feature = "async")]
-#[cfg(not(any(unix, target_os = "windows")))]
-async fn resolve_domain(host_name: String) -> Vec<IpAddr> {
-    let mut ips: Vec<IpAddr> = vec![];
-    let resolver =
-        AsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default()).unwrap();
-    match resolver.lookup_ip(host_name).await {
-        Ok(lip) => {
-            for ip in lip.iter() {
-                ips.push(ip);
-            }
-        }
-        Err(_) => {}
-    }
-    ips
-}
-
-#[cfg(feature = "passive")]
-fn extract_domain(target: String) -> String {
-    let mut domain_name: String = target;
-    match domain_name.strip_prefix("*.") {
-        Some(d) => {
-            domain_name = d.to_string();
-        }
-        None => {}
-    }
-    domain_name
-}
-
-#[cfg(feature = "passive")]
-fn is_subdomain(domain: String, apex_domain: String) -> bool {
-    domain.contains(&apex_domain)
-        && domain.ends_with(&apex_domain)
-        && domain.len() > apex_domain.len()
-}
-
-async fn scan_subdomain(
-    base_domain: String,
-    word_list: Vec<String>,
-    ptx: &Arc<Mutex<Sender<String>>>,
+/// Fetch the requested record types for each domain
+async fn fetch_records(
+    domains: Vec<Domain>,
+    record_types: &[RecordType],
+    resolver: &ScanResolver,
     resolve_timeout: Duration,
     concurrent_limit: usize,
 ) -> Vec<Domain> {
-    let mut result: Vec<Domain> = vec![];
-    let scan_results: Arc<Mutex<Vec<Domain>>> = Arc::new(Mutex::new(vec![]));
-    let mut target_domains: Vec<String> = vec![];
-    for word in word_list {
-        target_domains.push(format!("{}.{}", word, base_domain));
-    }
-    let results = stream::iter(target_domains)
-        .map(|domain| async move {
-            let mut d: Domain = Domain {
-                domain_name: domain.clone(),
-                ips: vec![],
-            };
-            match timeout(resolve_timeout, resolve_domain(domain.clone())).await {
-                Ok(ips) => {
-                    d.ips = ips;
-                    match ptx.lock() {
-                        Ok(lr) => match lr.send(domain) {
-                            Ok(_) => {}
-                            Err(_) => {}
-                        },
-                        Err(_) => {}
+    stream::iter(domains)
+        .map(|mut domain| async move {
+            for record_type in record_types {
+                resolver.wait_turn().await;
+                match timeout(
+                    resolve_timeout,
+                    resolver.lookup(&domain.domain_name, *record_type),
+                )
+                .await
+                {
+                    Ok(rdata) => {
+                        for data in rdata {
+                            domain.records.push(DnsRecord {
+                                record_type: record_type.to_string(),
+                                value: data.to_string(),
+                            });
+                        }
                     }
-                }
-                Err(_) => match ptx.lock() {
-                    Ok(lr) => match lr.send(domain) {
-                        Ok(_) => {}
-                        Err(_) => {}
-                    },
                     Err(_) => {}
-                },
-            }
-            d
-        })
-        .buffer_unordered(concurrent_limit);
-    results
-        .for_each(|domain| async {
-            if domain.ips.len() > 0 {
-                scan_results.lock().unwrap().push(domain);
+                }
             }
+            domain
         })
-        .await;
-    for domain in scan_results.lock().unwrap().iter() {
-        result.push(domain.to_owned());
+        .buffer_unordered(concurrent_limit)
+        .collect()
+        .await
+}
+
+/// Check whether a random name under the domain resolves
+async fn is_wildcard(resolver: &ScanResolver, domain: &str, resolve_timeout: Duration) -> bool {
+    let name: String = format!("{}.{}", uuid::Uuid::new_v4().simple(), domain);
+    resolver.wait_turn().await;
+    match timeout(resolve_timeout, resolver.lookup_ip(&name)).await {
+        Ok(ips) => !ips.is_empty(),
+        Err(_) => false,
     }
-    result
 }
 
-#[cfg(feature = "passive")]
-async fn scan_subdomain_passive(
+async fn scan_subdomain(
     base_domain: String,
-    ptx: &Arc<Mutex<Sender<String>>>,
+    word_list: &[String],
+    domain_list: &[String],
+    resolver: &ScanResolver,
+    ptx: &Arc<Mutex<Sender<Domain>>>,
     resolve_timeout: Duration,
     concurrent_limit: usize,
-    user_agent: String,
 ) -> Vec<Domain> {
     let mut result: Vec<Domain> = vec![];
     let scan_results: Arc<Mutex<Vec<Domain>>> = Arc::new(Mutex::new(vec![]));
-    let mut certs: Vec<CertEntry> = vec![];
-    //"https://crt.sh/?dNSName=example.com&output=json"
-    let url = match Url::parse_with_params(
-        URL_CRT,
-        &[
-            ("dNSName", base_domain.clone().as_str()),
-            ("output", "json"),
-        ],
-    ) {
-        Ok(url) => url,
-        Err(e) => {
-            println!("{}", e);
-            return result;
-        }
-    };
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(60))
-        .build()
-        .expect("failed to build HTTP reqest client");
-    let res = client
-        .get(url)
-        .header(reqwest::header::USER_AGENT, user_agent)
-        .send()
-        .await;
-    match res {
-        Ok(r) => {
-            if r.status().is_success() {
-                match r.text().await {
-                    Ok(res_text) => {
-                        let certs_json: serde_json::Value = serde_json::from_str(res_text.as_str())
-                            .unwrap_or(serde_json::json!({}));
-                        if certs_json.is_array() {
-                            let cert_array = certs_json.as_array().unwrap();
-                            for cert in cert_array {
-                                match serde_json::to_string(cert) {
-                                    Ok(cert) => {
-                                        let cert: CertEntry =
-                                            match serde_json::from_str(cert.as_str()) {
-                                                Ok(cert) => cert,
-                                                Err(_) => continue,
-                                            };
-                                        certs.push(cert);
-                                    }
-                                    Err(_) => {}
-                                }
-                            }
-                        }
-                    }
-                    Err(_) => {}
-                };
-            }
-        }
-        Err(_) => {}
-    }
     let mut target_domains: Vec<String> = vec![];
-    for cert in certs {
-        let domain_name: String = extract_domain(cert.common_name);
-        if is_subdomain(domain_name.clone(), base_domain.clone())
-            && !target_domains.contains(&domain_name)
-        {
-            target_domains.push(domain_name);
+    let mut seen: HashSet<String> = HashSet::new();
+    for word in word_list {
+        let domain: String = format!("{}.{}", word, base_domain);
+        if seen.insert(domain.clone()) {
+            target_domains.push(domain);
         }
-        let name_values: Vec<&str> = cert.name_value.trim().split("\n").collect();
-        for value in name_values {
-            let name: String = extract_domain(value.to_string());
-            if is_subdomain(name.clone(), base_domain.clone()) && !target_domains.contains(&name) {
-                target_domains.push(name);
-            }
+    }
+    for domain in domain_list {
+        if seen.insert(domain.clone()) {
+            target_domains.push(domain.clone());
         }
     }
-    let results = stream::iter(target_domains)
-        .map(|domain| async move {
-            let mut d: Domain = Domain {
-                domain_name: domain.clone(),
-                ips: vec![],
-            };
-            match timeout(resolve_timeout, resolve_domain(domain.clone())).await {
-                Ok(ips) => {
-                    d.ips = ips;
-                    match ptx.lock() {
-                        Ok(lr) => match lr.send(domain) {
-                            Ok(_) => {}
-                            Err(_) => {}
-                        },
-                        Err(_) => {}
+    // Each batch completes before the next starts so that progress can be checkpointed
+    for batch in target_domains.chunks(crate::config::SUBDOMAIN_BATCH_SIZE) {
+        let results = stream::iter(batch.to_vec())
+            .map(|domain| async move {
+                let mut d: Domain = Domain {
+                    domain_name: domain.clone(),
+                    unicode_name: super::idn::to_unicode(&domain),
+                    ips: vec![],
+                    records: vec![],
+                    tags: vec![],
+                };
+                resolver.wait_turn().await;
+                match timeout(resolve_timeout, resolver.lookup_ip(&domain)).await {
+                    Ok(ips) => {
+                        d.ips = ips;
                     }
+                    Err(_) => {}
                 }
-                Err(_) => match ptx.lock() {
-                    Ok(lr) => match lr.send(domain) {
+                match ptx.lock() {
+                    Ok(lr) => match lr.send(d.clone()) {
                         Ok(_) => {}
                         Err(_) => {}
                     },
                     Err(_) => {}
-                },
-            }
-            d
-        })
-        .buffer_unordered(concurrent_limit);
-    results
-        .for_each(|domain| async {
-            if domain.ips.len() > 0 {
-                scan_results.lock().unwrap().push(domain);
-            }
-        })
-        .await;
+                }
+                d
+            })
+            .buffer_unordered(concurrent_limit);
+        results
+            .for_each(|domain| async {
+                if domain.ips.len() > 0 {
+                    scan_results.lock().unwrap().push(domain);
+                }
+            })
+            .await;
+    }
     for domain in scan_results.lock().unwrap().iter() {
         result.push(domain.to_owned());
     }
@@ -1,5 +1,5 @@
-#[cfg(feature = "passive")]
 pub(crate) const URL_CRT: &str = "https://crt.sh/";
+pub(crate) const URL_HACKERTARGET_HOSTSEARCH: &str = "https://api.hackertarget.com/hostsearch/";
 pub const DEFAULT_USER_AGENT_FIREFOX: &str =
     "Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/121.0";
 pub const DEFAULT_USER_AGENT_CHROME: &str =
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::IpAddr;
+use std::time::Duration;
+
+const SHODAN_API_URL: &str = "https://api.shodan.io/shodan/host";
+const CENSYS_API_URL: &str = "https://search.censys.io/api/v2/hosts";
+/// Environment variable of the Shodan API key
+pub const SHODAN_API_KEY_ENV: &str = "SHODAN_API_KEY";
+/// Environment variables of the Censys API ID and secret
+pub const CENSYS_API_ID_ENV: &str = "CENSYS_API_ID";
+pub const CENSYS_API_SECRET_ENV: &str = "CENSYS_API_SECRET";
+
+/// Internet scan data source of --enrich
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnrichSource {
+    Shodan,
+    Censys,
+}
+
+impl EnrichSource {
+    pub fn from_str(s: &str) -> Option<EnrichSource> {
+        match s.to_lowercase().as_str() {
+            "shodan" => Some(EnrichSource::Shodan),
+            "censys" => Some(EnrichSource::Censys),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &str {
+        match *self {
+            EnrichSource::Shodan => "Shodan",
+            EnrichSource::Censys => "Censys",
+        }
+    }
+}
+
+/// What an internet scan service observed on the host
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExternalInfo {
+    pub source: String,
+    /// Ports seen from the internet
+    pub ports: Vec<u16>,
+    /// e.g. cloud, vpn, self-signed (Shodan tags, Censys labels)
+    pub tags: Vec<String>,
+    /// Time of the last observation as reported by the service
+    pub last_update: String,
+    /// Seen from the internet, but not open in this scan
+    pub missing_locally: Vec<u16>,
+    /// Seen from the internet, and not in the scanned ports
+    pub not_scanned: Vec<u16>,
+    /// Open in this scan, but not seen from the internet
+    pub missing_externally: Vec<u16>,
+}
+
+impl ExternalInfo {
+    pub fn has_discrepancy(&self) -> bool {
+        !self.missing_locally.is_empty() || !self.missing_externally.is_empty()
+    }
+    /// Compare with the scanned and open ports of the scan
+    pub fn set_discrepancies(&mut self, scanned: &[u16], open: &[u16]) {
+        self.missing_locally = self
+            .ports
+            .iter()
+            .filter(|port| scanned.contains(port) && !open.contains(port))
+            .cloned()
+            .collect();
+        self.not_scanned = self
+            .ports
+            .iter()
+            .filter(|port| !scanned.contains(port))
+            .cloned()
+            .collect();
+        self.missing_externally = open
+            .iter()
+            .cloned()
+            .filter(|port| !self.ports.contains(port))
+            .collect();
+    }
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out: String = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b: [u8; 3] = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n: u32 = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_CHARS[((n >> (18 - i * 6)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn string_array(value: &Value) -> Vec<String> {
+    match value.as_array() {
+        Some(values) => values
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Looks up hosts in Shodan or Censys. Only global addresses are looked up
+pub struct Enricher {
+    pub source: EnrichSource,
+    /// Shodan API key, or Censys API ID and secret as id:secret
+    pub api_key: String,
+    pub timeout: Duration,
+}
+
+impl Enricher {
+    pub fn new(source: EnrichSource, api_key: &str) -> Enricher {
+        Enricher {
+            source: source,
+            api_key: api_key.to_string(),
+            timeout: Duration::from_millis(crate::config::DEFAULT_ENRICH_TIMEOUT_MILLIS),
+        }
+    }
+    /// API key from the environment. SHODAN_API_KEY, or CENSYS_API_ID and CENSYS_API_SECRET
+    pub fn api_key_from_env(source: EnrichSource) -> Option<String> {
+        match source {
+            EnrichSource::Shodan => std::env::var(SHODAN_API_KEY_ENV).ok(),
+            EnrichSource::Censys => match (
+                std::env::var(CENSYS_API_ID_ENV),
+                std::env::var(CENSYS_API_SECRET_ENV),
+            ) {
+                (Ok(id), Ok(secret)) => Some(format!("{}:{}", id, secret)),
+                _ => None,
+            },
+        }
+        .filter(|key| !key.is_empty())
+    }
+    /// Ports and tags of the host. None if the service has no data of the host
+    pub fn lookup(&self, ip_addr: &IpAddr) -> Result<Option<ExternalInfo>, String> {
+        let response = match self.source {
+            EnrichSource::Shodan => {
+                let url: String = format!(
+                    "{}/{}?minify=true&key={}",
+                    SHODAN_API_URL,
+                    ip_addr,
+                    crate::http::encode_query(&self.api_key)
+                );
+                crate::http::get(&url, &[("Accept", "application/json")], self.timeout)?
+            }
+            EnrichSource::Censys => {
+                let url: String = format!("{}/{}", CENSYS_API_URL, ip_addr);
+                let auth: String = format!("Basic {}", base64_encode(self.api_key.as_bytes()));
+                crate::http::get(
+                    &url,
+                    &[("Accept", "application/json"), ("Authorization", &auth)],
+                    self.timeout,
+                )?
+            }
+        };
+        if response.status == 404 {
+            return Ok(None);
+        }
+        if !response.is_success() {
+            return Err(format!(
+                "{} API returned {}",
+                self.source.name(),
+                response.status
+            ));
+        }
+        let json: Value = serde_json::from_str(&response.text())
+            .map_err(|e| format!("Failed to parse {} API response: {}", self.source.name(), e))?;
+        let (mut ports, tags, last_update): (Vec<u16>, Vec<String>, String) = match self.source {
+            EnrichSource::Shodan => (
+                json["ports"]
+                    .as_array()
+                    .map(|ports| {
+                        ports
+                            .iter()
+                            .filter_map(|p| p.as_u64().map(|p| p as u16))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                string_array(&json["tags"]),
+                json["last_update"].as_str().unwrap_or("").to_string(),
+            ),
+            EnrichSource::Censys => {
+                let result: &Value = &json["result"];
+                // Only TCP services. Scans here are TCP
+                let ports: Vec<u16> = result["services"]
+                    .as_array()
+                    .map(|services| {
+                        services
+                            .iter()
+                            .filter(|s| {
+                                s["transport_protocol"]
+                                    .as_str()
+                                    .map_or(true, |p| p.eq_ignore_ascii_case("tcp"))
+                            })
+                            .filter_map(|s| s["port"].as_u64().map(|p| p as u16))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (
+                    ports,
+                    string_array(&result["labels"]),
+                    result["last_updated_at"].as_str().unwrap_or("").to_string(),
+                )
+            }
+        };
+        ports.sort();
+        ports.dedup();
+        Ok(Some(ExternalInfo {
+            source: self.source.name().to_string(),
+            ports: ports,
+            tags: tags,
+            last_update: last_update,
+            missing_locally: Vec::new(),
+            not_scanned: Vec::new(),
+            missing_externally: Vec::new(),
+        }))
+    }
+}
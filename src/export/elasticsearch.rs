@@ -0,0 +1,176 @@
+use super::ScanDocuments;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Mappings of the host index. IP fields use the ip type for CIDR queries in Kibana
+fn host_mappings() -> Value {
+    json!({
+        "properties": {
+            "@timestamp": { "type": "date" },
+            "scan_id": { "type": "keyword" },
+            "ip": { "type": "ip" },
+            "hostname": { "type": "keyword" },
+            "mac_addr": { "type": "keyword" },
+            "vendor": { "type": "keyword" },
+            "os_family": { "type": "keyword" },
+            "ttl": { "type": "integer" },
+            "discovered_by": { "type": "keyword" },
+            "open_ports": { "type": "integer" },
+            "open_port_count": { "type": "integer" },
+            "geo": geo_mappings(),
+        }
+    })
+}
+
+fn port_mappings() -> Value {
+    json!({
+        "properties": {
+            "@timestamp": { "type": "date" },
+            "scan_id": { "type": "keyword" },
+            "ip": { "type": "ip" },
+            "hostname": { "type": "keyword" },
+            "port": { "type": "integer" },
+            "protocol": { "type": "keyword" },
+            "status": { "type": "keyword" },
+            "service_name": { "type": "keyword" },
+            "service_version": {
+                "type": "text",
+                "fields": { "keyword": { "type": "keyword", "ignore_above": 256 } }
+            },
+            "cpes": { "type": "keyword" },
+            "cves": { "type": "keyword" },
+            "max_cvss": { "type": "float" },
+            "geo": geo_mappings(),
+        }
+    })
+}
+
+fn geo_mappings() -> Value {
+    json!({
+        "properties": {
+            "country_code": { "type": "keyword" },
+            "country": { "type": "keyword" },
+            "city": { "type": "keyword" },
+            "asn": { "type": "long" },
+            "as_org": { "type": "keyword" },
+        }
+    })
+}
+
+/// Bulk exporter. Hosts go to `<prefix>-hosts` and ports to `<prefix>-ports`
+#[derive(Clone, Debug)]
+pub struct ElasticsearchExporter {
+    /// Base URL. e.g. https://localhost:9200
+    pub url: String,
+    pub index_prefix: String,
+    /// Sent as `Authorization: ApiKey <key>` if set
+    pub api_key: Option<String>,
+    pub timeout: Duration,
+}
+
+impl ElasticsearchExporter {
+    pub fn new(url: &str, index_prefix: &str) -> ElasticsearchExporter {
+        ElasticsearchExporter {
+            url: url.trim_end_matches('/').to_string(),
+            index_prefix: index_prefix.to_string(),
+            api_key: std::env::var("ES_API_KEY")
+                .ok()
+                .filter(|key| !key.is_empty()),
+            timeout: Duration::from_millis(crate::config::DEFAULT_EXPORT_TIMEOUT_MILLIS),
+        }
+    }
+    pub fn host_index(&self) -> String {
+        format!("{}-hosts", self.index_prefix)
+    }
+    pub fn port_index(&self) -> String {
+        format!("{}-ports", self.index_prefix)
+    }
+    fn request(
+        &self,
+        method: &str,
+        path: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<Value, String> {
+        let authorization: Option<String> =
+            self.api_key.as_ref().map(|key| format!("ApiKey {}", key));
+        let mut headers: Vec<(&str, &str)> = vec![("Content-Type", content_type)];
+        if let Some(authorization) = &authorization {
+            headers.push(("Authorization", authorization.as_str()));
+        }
+        let response = crate::http::request(
+            method,
+            &format!("{}{}", self.url, path),
+            &headers,
+            Some(body),
+            self.timeout,
+        )?;
+        let value: Value = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+        if response.is_success() {
+            Ok(value)
+        } else {
+            let reason: String = value["error"]["type"]
+                .as_str()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| response.text());
+            Err(format!("HTTP {}: {}", response.status, reason))
+        }
+    }
+    /// Create the index with mappings. An existing index is left as is
+    fn ensure_index(&self, index: &str, mappings: Value) -> Result<(), String> {
+        let body: String = json!({ "mappings": mappings }).to_string();
+        match self.request(
+            "PUT",
+            &format!("/{}", index),
+            "application/json",
+            body.as_bytes(),
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) if e.contains("resource_already_exists_exception") => Ok(()),
+            Err(e) => Err(format!("Failed to create index {}: {}", index, e)),
+        }
+    }
+    fn bulk(&self, index: &str, documents: &[Value]) -> Result<usize, String> {
+        if documents.is_empty() {
+            return Ok(0);
+        }
+        let action: String = json!({ "index": { "_index": index } }).to_string();
+        let mut body: String = String::new();
+        for document in documents {
+            body.push_str(&action);
+            body.push('\n');
+            body.push_str(&document.to_string());
+            body.push('\n');
+        }
+        let value: Value =
+            self.request("POST", "/_bulk", "application/x-ndjson", body.as_bytes())?;
+        if value["errors"].as_bool() == Some(true) {
+            let items: Vec<Value> = value["items"].as_array().cloned().unwrap_or_default();
+            let failed: Vec<&Value> = items
+                .iter()
+                .filter(|item| item["index"]["error"].is_object())
+                .collect();
+            let reason: String = failed
+                .first()
+                .and_then(|item| item["index"]["error"]["reason"].as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            return Err(format!(
+                "{} of {} documents rejected by {}: {}",
+                failed.len(),
+                documents.len(),
+                index,
+                reason
+            ));
+        }
+        Ok(documents.len())
+    }
+    /// Index all documents. Returns the number of documents indexed
+    pub fn export(&self, documents: &ScanDocuments) -> Result<usize, String> {
+        self.ensure_index(&self.host_index(), host_mappings())?;
+        self.ensure_index(&self.port_index(), port_mappings())?;
+        let hosts: usize = self.bulk(&self.host_index(), &documents.hosts)?;
+        let ports: usize = self.bulk(&self.port_index(), &documents.ports)?;
+        Ok(hosts + ports)
+    }
+}
@@ -0,0 +1,130 @@
+pub mod elasticsearch;
+pub mod splunk;
+
+use crate::host::{Host, PortStatus};
+use chrono::Local;
+use netdev::mac::MacAddr;
+use serde_json::{json, Map, Value};
+
+/// Destination of --export
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportKind {
+    Elasticsearch,
+    Splunk,
+}
+
+impl ExportKind {
+    pub fn from_str(s: &str) -> Option<ExportKind> {
+        match s.to_lowercase().as_str() {
+            "elasticsearch" | "es" => Some(ExportKind::Elasticsearch),
+            "splunk" => Some(ExportKind::Splunk),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &str {
+        match *self {
+            ExportKind::Elasticsearch => "Elasticsearch",
+            ExportKind::Splunk => "Splunk",
+        }
+    }
+}
+
+/// Flat documents of one scan. Every document has `@timestamp` and `scan_id`
+#[derive(Clone, Debug)]
+pub struct ScanDocuments {
+    pub hosts: Vec<Value>,
+    pub ports: Vec<Value>,
+}
+
+fn geo_value(host: &Host) -> Option<Value> {
+    host.geo.as_ref().map(|geo| {
+        json!({
+            "country_code": geo.country_code,
+            "country": geo.country,
+            "city": geo.city,
+            "asn": geo.asn,
+            "as_org": geo.as_org,
+        })
+    })
+}
+
+/// One document per host and one per open port
+pub fn scan_documents(hosts: &[Host]) -> ScanDocuments {
+    let timestamp: String = Local::now().to_rfc3339();
+    let scan_id: String = uuid::Uuid::new_v4().to_string();
+    let mut documents = ScanDocuments {
+        hosts: Vec::new(),
+        ports: Vec::new(),
+    };
+    for host in hosts {
+        let mut common: Map<String, Value> = Map::new();
+        common.insert("@timestamp".to_string(), json!(timestamp));
+        common.insert("scan_id".to_string(), json!(scan_id));
+        common.insert("ip".to_string(), json!(host.ip_addr.to_string()));
+        if !host.hostname.is_empty() && host.hostname != host.ip_addr.to_string() {
+            common.insert("hostname".to_string(), json!(host.hostname));
+        }
+        if let Some(geo) = geo_value(host) {
+            common.insert("geo".to_string(), geo);
+        }
+        let open_ports: Vec<u16> = host.get_open_port_numbers();
+        let mut host_doc: Map<String, Value> = common.clone();
+        if host.mac_addr != MacAddr::zero() {
+            host_doc.insert("mac_addr".to_string(), json!(host.mac_addr.to_string()));
+        }
+        if !host.vendor_name.is_empty() {
+            host_doc.insert("vendor".to_string(), json!(host.vendor_name));
+        }
+        if !host.os_family.is_empty() {
+            host_doc.insert("os_family".to_string(), json!(host.os_family));
+        }
+        if host.ttl > 0 {
+            host_doc.insert("ttl".to_string(), json!(host.ttl));
+        }
+        let discovered_by: Vec<String> = host
+            .discovered_by
+            .iter()
+            .map(|p| p.to_str().to_string())
+            .collect();
+        if !discovered_by.is_empty() {
+            host_doc.insert("discovered_by".to_string(), json!(discovered_by));
+        }
+        host_doc.insert("open_port_count".to_string(), json!(open_ports.len()));
+        host_doc.insert("open_ports".to_string(), json!(open_ports));
+        documents.hosts.push(Value::Object(host_doc));
+        for port in &host.ports {
+            if port.status != PortStatus::Open {
+                continue;
+            }
+            let mut port_doc: Map<String, Value> = common.clone();
+            port_doc.insert("port".to_string(), json!(port.number));
+            port_doc.insert("protocol".to_string(), json!("tcp"));
+            port_doc.insert("status".to_string(), json!(port.status.id()));
+            if !port.service_name.is_empty() {
+                port_doc.insert("service_name".to_string(), json!(port.service_name));
+            }
+            if !port.service_version.is_empty() {
+                port_doc.insert("service_version".to_string(), json!(port.service_version));
+            }
+            if !port.cpes.is_empty() {
+                port_doc.insert("cpes".to_string(), json!(port.cpes));
+            }
+            if !port.cves.is_empty() {
+                let cve_ids: Vec<&str> = port.cves.iter().map(|cve| cve.id.as_str()).collect();
+                port_doc.insert("cves".to_string(), json!(cve_ids));
+                let max_score: Option<f64> = port
+                    .cves
+                    .iter()
+                    .filter_map(|cve| cve.score)
+                    .fold(None, |max, score| {
+                        Some(max.map_or(score, |m: f64| m.max(score)))
+                    });
+                if let Some(max_score) = max_score {
+                    port_doc.insert("max_cvss".to_string(), json!(max_score));
+                }
+            }
+            documents.ports.push(Value::Object(port_doc));
+        }
+    }
+    documents
+}
@@ -0,0 +1,90 @@
+use crate::host::Host;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Splunk HTTP Event Collector exporter. Each host of the result is sent as one event
+#[derive(Clone, Debug)]
+pub struct SplunkHecExporter {
+    /// Base URL. e.g. https://splunk.example.com:8088
+    pub url: String,
+    pub token: String,
+    /// Target index. The default index of the token if None
+    pub index: Option<String>,
+    /// Events sent per request
+    pub batch_size: usize,
+    pub timeout: Duration,
+}
+
+impl SplunkHecExporter {
+    pub fn new(url: &str, token: &str) -> SplunkHecExporter {
+        SplunkHecExporter {
+            url: url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            index: None,
+            batch_size: crate::config::DEFAULT_HEC_BATCH_SIZE,
+            timeout: Duration::from_millis(crate::config::DEFAULT_EXPORT_TIMEOUT_MILLIS),
+        }
+    }
+    fn endpoint(&self) -> String {
+        if self.url.contains("/services/collector") {
+            self.url.clone()
+        } else {
+            format!("{}/services/collector/event", self.url)
+        }
+    }
+    /// Event body has the same schema as a host in the JSON result
+    fn event(&self, host: &Host, time: i64, scan_id: &str) -> Result<Value, String> {
+        let mut event: Value = json!({
+            "time": time,
+            "host": host.ip_addr.to_string(),
+            "source": crate::app::CRATE_BIN_NAME,
+            "sourcetype": "nrev:host",
+            "event": serde_json::to_value(host).map_err(|e| e.to_string())?,
+            "fields": { "scan_id": scan_id },
+        });
+        if let Some(index) = &self.index {
+            event["index"] = json!(index);
+        }
+        Ok(event)
+    }
+    fn send(&self, body: &str) -> Result<(), String> {
+        let authorization: String = format!("Splunk {}", self.token);
+        let response = crate::http::post(
+            &self.endpoint(),
+            &[
+                ("Content-Type", "application/json"),
+                ("Authorization", authorization.as_str()),
+            ],
+            body.as_bytes(),
+            self.timeout,
+        )?;
+        if response.is_success() {
+            return Ok(());
+        }
+        // e.g. {"text":"Invalid token","code":4}
+        let value: Value = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+        let reason: String = value["text"]
+            .as_str()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| response.text());
+        Err(format!("HTTP {}: {}", response.status, reason))
+    }
+    /// Send events in batches. Returns the number of events sent
+    pub fn export(&self, hosts: &[Host]) -> Result<usize, String> {
+        let time: i64 = chrono::Local::now().timestamp();
+        let scan_id: String = uuid::Uuid::new_v4().to_string();
+        let mut sent: usize = 0;
+        for chunk in hosts.chunks(self.batch_size.max(1)) {
+            // HEC accepts concatenated event objects in one request
+            let mut body: String = String::new();
+            for host in chunk {
+                body.push_str(&self.event(host, time, &scan_id)?.to_string());
+                body.push('\n');
+            }
+            self.send(&body)
+                .map_err(|e| format!("{} (after {} events)", e, sent))?;
+            sent += chunk.len();
+        }
+        Ok(sent)
+    }
+}
@@ -0,0 +1,304 @@
+use crate::output;
+use crate::ping::pinger::Pinger;
+use crate::ping::setting::PingSetting;
+use crate::serve::http::{self, Response};
+use chrono::{DateTime, Local};
+use netdev::Interface;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Target to check. `name` is the target as given on the command line
+#[derive(Clone, Debug)]
+pub struct ExporterTarget {
+    pub name: String,
+    pub ip_addr: IpAddr,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExporterSetting {
+    pub listen_addr: SocketAddr,
+    pub interface: Interface,
+    pub targets: Vec<ExporterTarget>,
+    /// TCP ports checked on every target
+    pub ports: Vec<u16>,
+    pub interval: Duration,
+    /// Timeout of each ping and TCP connect
+    pub timeout: Duration,
+}
+
+/// Result of the latest check of a target
+#[derive(Clone, Debug)]
+pub struct TargetMetrics {
+    pub target: ExporterTarget,
+    /// Replied to ICMP ping
+    pub up: bool,
+    pub rtt: Option<Duration>,
+    /// Port number and whether TCP connect succeeded
+    pub ports: BTreeMap<u16, bool>,
+}
+
+/// Metrics served on /metrics
+#[derive(Clone, Debug)]
+pub struct ExporterMetrics {
+    pub targets: Vec<TargetMetrics>,
+    pub checks: u64,
+    pub last_check: Option<DateTime<Local>>,
+    pub last_check_duration: Duration,
+}
+
+impl ExporterMetrics {
+    pub fn new() -> ExporterMetrics {
+        ExporterMetrics {
+            targets: Vec::new(),
+            checks: 0,
+            last_check: None,
+            last_check_duration: Duration::from_millis(0),
+        }
+    }
+}
+
+type SharedMetrics = Arc<Mutex<ExporterMetrics>>;
+
+fn ping_target(interface: &Interface, ip_addr: IpAddr, timeout: Duration) -> Option<Duration> {
+    let mut setting: PingSetting = PingSetting::icmp_ping(interface, ip_addr, 1).ok()?;
+    setting.receive_timeout = timeout;
+    setting.probe_timeout = timeout;
+    let ping_result = Pinger::new(setting).ok()?.ping().ok()?;
+    if ping_result.stat.received_count > 0 {
+        Some(ping_result.stat.min)
+    } else {
+        None
+    }
+}
+
+fn check_target(
+    interface: &Interface,
+    target: &ExporterTarget,
+    ports: &[u16],
+    timeout: Duration,
+) -> TargetMetrics {
+    let rtt: Option<Duration> = ping_target(interface, target.ip_addr, timeout);
+    let mut port_status: BTreeMap<u16, bool> = BTreeMap::new();
+    for port in ports {
+        let socket_addr: SocketAddr = SocketAddr::new(target.ip_addr, *port);
+        port_status.insert(
+            *port,
+            TcpStream::connect_timeout(&socket_addr, timeout).is_ok(),
+        );
+    }
+    TargetMetrics {
+        target: target.clone(),
+        up: rtt.is_some(),
+        rtt: rtt,
+        ports: port_status,
+    }
+}
+
+/// Check all targets concurrently
+fn check_targets(setting: &ExporterSetting) -> Vec<TargetMetrics> {
+    let mut results: Vec<TargetMetrics> = Vec::new();
+    for chunk in setting
+        .targets
+        .chunks(crate::config::DEFAULT_HOSTS_CONCURRENCY)
+    {
+        let mut handles = Vec::new();
+        for target in chunk {
+            let interface: Interface = setting.interface.clone();
+            let target: ExporterTarget = target.clone();
+            let ports: Vec<u16> = setting.ports.clone();
+            let timeout: Duration = setting.timeout;
+            handles.push(thread::spawn(move || {
+                check_target(&interface, &target, &ports, timeout)
+            }));
+        }
+        for handle in handles {
+            match handle.join() {
+                Ok(target_metrics) => results.push(target_metrics),
+                Err(e) => output::log_with_time(&format!("{:?}", e), "ERROR"),
+            }
+        }
+    }
+    results
+}
+
+fn run_checker(setting: ExporterSetting, metrics: SharedMetrics) {
+    loop {
+        let start_time: Instant = Instant::now();
+        let targets: Vec<TargetMetrics> = check_targets(&setting);
+        let up: usize = targets.iter().filter(|t| t.up).count();
+        if let Ok(mut metrics) = metrics.lock() {
+            metrics.targets = targets;
+            metrics.checks += 1;
+            metrics.last_check = Some(Local::now());
+            metrics.last_check_duration = start_time.elapsed();
+        }
+        output::log_with_time(
+            &format!(
+                "Checked {} target(s), {} up in {:?}",
+                setting.targets.len(),
+                up,
+                start_time.elapsed()
+            ),
+            "INFO",
+        );
+        thread::sleep(setting.interval.saturating_sub(start_time.elapsed()));
+    }
+}
+
+/// Escape a label value of the Prometheus text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn metric_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Render metrics in the Prometheus text exposition format
+pub fn render_metrics(metrics: &ExporterMetrics) -> String {
+    let mut out: String = String::new();
+    let labels = |target: &ExporterTarget| {
+        format!(
+            "target=\"{}\",ip=\"{}\"",
+            escape_label(&target.name),
+            target.ip_addr
+        )
+    };
+    metric_header(
+        &mut out,
+        "nrev_target_up",
+        "gauge",
+        "Whether the target replied to ICMP ping (1) or not (0)",
+    );
+    for t in &metrics.targets {
+        let _ = writeln!(
+            out,
+            "nrev_target_up{{{}}} {}",
+            labels(&t.target),
+            t.up as u8
+        );
+    }
+    metric_header(
+        &mut out,
+        "nrev_target_rtt_seconds",
+        "gauge",
+        "ICMP ping round trip time. Absent while the target is down",
+    );
+    for t in &metrics.targets {
+        if let Some(rtt) = t.rtt {
+            let _ = writeln!(
+                out,
+                "nrev_target_rtt_seconds{{{}}} {}",
+                labels(&t.target),
+                rtt.as_secs_f64()
+            );
+        }
+    }
+    metric_header(
+        &mut out,
+        "nrev_port_open",
+        "gauge",
+        "Whether TCP connect to the port succeeded (1) or not (0)",
+    );
+    for t in &metrics.targets {
+        for (port, open) in &t.ports {
+            let _ = writeln!(
+                out,
+                "nrev_port_open{{{},port=\"{}\"}} {}",
+                labels(&t.target),
+                port,
+                *open as u8
+            );
+        }
+    }
+    metric_header(
+        &mut out,
+        "nrev_open_ports",
+        "gauge",
+        "Number of open ports among the checked ports",
+    );
+    for t in &metrics.targets {
+        let _ = writeln!(
+            out,
+            "nrev_open_ports{{{}}} {}",
+            labels(&t.target),
+            t.ports.values().filter(|open| **open).count()
+        );
+    }
+    metric_header(
+        &mut out,
+        "nrev_checks_total",
+        "counter",
+        "Number of completed check rounds",
+    );
+    let _ = writeln!(out, "nrev_checks_total {}", metrics.checks);
+    if let Some(last_check) = metrics.last_check {
+        metric_header(
+            &mut out,
+            "nrev_last_check_timestamp_seconds",
+            "gauge",
+            "Unix time of the latest check round",
+        );
+        let _ = writeln!(
+            out,
+            "nrev_last_check_timestamp_seconds {}",
+            last_check.timestamp()
+        );
+        metric_header(
+            &mut out,
+            "nrev_last_check_duration_seconds",
+            "gauge",
+            "Time taken by the latest check round",
+        );
+        let _ = writeln!(
+            out,
+            "nrev_last_check_duration_seconds {}",
+            metrics.last_check_duration.as_secs_f64()
+        );
+    }
+    out
+}
+
+fn handle_connection(mut stream: TcpStream, metrics: &SharedMetrics) {
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(
+        crate::config::DEFAULT_SERVE_READ_TIMEOUT_MILLIS,
+    )));
+    let response: Response = match http::read_request(&stream) {
+        Ok(request) => match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/metrics") => match metrics.lock() {
+                Ok(metrics) => Response::text(200, render_metrics(&metrics)),
+                Err(_) => Response::error(500, "Metrics unavailable"),
+            },
+            (_, "/metrics") => Response::error(405, "Method not allowed"),
+            _ => Response::error(404, "Not found"),
+        },
+        Err(response) => response,
+    };
+    http::write_response(&mut stream, &response);
+}
+
+/// Check targets every interval and serve /metrics until the process is stopped
+pub fn serve(setting: ExporterSetting) -> Result<(), String> {
+    let listener: TcpListener = TcpListener::bind(setting.listen_addr)
+        .map_err(|e| format!("Failed to listen on {}: {}", setting.listen_addr, e))?;
+    let metrics: SharedMetrics = Arc::new(Mutex::new(ExporterMetrics::new()));
+    let checker_metrics: SharedMetrics = metrics.clone();
+    thread::spawn(move || run_checker(setting, checker_metrics));
+    for stream in listener.incoming() {
+        let stream: TcpStream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let metrics: SharedMetrics = metrics.clone();
+        thread::spawn(move || handle_connection(stream, &metrics));
+    }
+    Ok(())
+}
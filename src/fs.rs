@@ -4,3 +4,9 @@ use std::path::PathBuf;
 pub fn save_text(file_path: &PathBuf, contents_text: String) -> Result<(), std::io::Error> {
     fs::write(file_path, contents_text)
 }
+
+/// Per-user data directory (~/.nrev)
+pub fn app_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(format!(".{}", crate::app::CRATE_BIN_NAME)))
+}
@@ -0,0 +1,312 @@
+pub mod proto {
+    tonic::include_proto!("nrev");
+}
+
+use crate::host::Host;
+use crate::scan::result::ScanResult;
+use crate::scan::scanner::{HostScanner, PortScanner, ServiceDetector};
+use crate::scan::setting::{
+    HostScanSetting, HostScanType, PortScanSetting, PortScanType, ServiceProbeSetting,
+};
+use netdev::mac::MacAddr;
+use netdev::Interface;
+use proto::scanner_server::{Scanner, ScannerServer};
+use proto::{
+    host_scan_event, port_scan_event, HostFinding, HostScanEvent, HostScanRequest, PortFinding,
+    PortScanEvent, PortScanRequest, Progress, Summary,
+};
+use std::net::{IpAddr, SocketAddr};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+/// Events buffered per stream before the scan waits for the client
+const STREAM_BUFFER_SIZE: usize = 256;
+
+pub struct ScannerService {
+    interface: Interface,
+}
+
+type EventSender<T> = mpsc::Sender<Result<T, Status>>;
+
+fn duration_or(millis: u64, default_millis: u64) -> Duration {
+    Duration::from_millis(if millis == 0 { default_millis } else { millis })
+}
+
+fn port_event(event: port_scan_event::Event) -> PortScanEvent {
+    PortScanEvent { event: Some(event) }
+}
+
+fn host_event(event: host_scan_event::Event) -> HostScanEvent {
+    HostScanEvent { event: Some(event) }
+}
+
+/// Blocking port scan. Events are sent until the client goes away
+fn run_port_scan(
+    interface: Interface,
+    ip_addr: IpAddr,
+    hostname: String,
+    ports: Vec<u16>,
+    request: PortScanRequest,
+    tx: EventSender<PortScanEvent>,
+) {
+    let start_time: Instant = Instant::now();
+    let total: u64 = ports.len() as u64;
+    let scan_type: PortScanType = if request.connect {
+        PortScanType::TcpConnectScan
+    } else {
+        PortScanType::TcpSynScan
+    };
+    let mut scan_setting = PortScanSetting::default()
+        .set_if_index(interface.index)
+        .set_scan_type(scan_type)
+        .add_target(Host::new(ip_addr, hostname.clone()).with_ports(ports))
+        .set_timeout(duration_or(request.timeout_millis, 10000))
+        .set_wait_time(duration_or(request.wait_time_millis, 200))
+        .set_send_rate(Duration::from_millis(0));
+    scan_setting.randomize_ports();
+    let port_scanner = PortScanner::new(scan_setting);
+    let progress_rx = port_scanner.get_progress_receiver();
+    let handle = thread::spawn(move || port_scanner.scan());
+    let mut done: u64 = 0;
+    while let Ok(_socket_addr) = progress_rx.lock().unwrap().recv() {
+        done += 1;
+        let progress = port_event(port_scan_event::Event::Progress(Progress {
+            done: done,
+            total: total,
+        }));
+        if tx.blocking_send(Ok(progress)).is_err() {
+            break;
+        }
+    }
+    let mut scan_result: ScanResult = match handle.join() {
+        Ok(scan_result) => scan_result,
+        Err(_) => {
+            let _ = tx.blocking_send(Err(Status::internal("Port scan failed")));
+            return;
+        }
+    };
+    scan_result.sort_ports();
+    let open_ports: Vec<u16> = scan_result.get_open_port_numbers(ip_addr);
+    let finding = |port: u16, service_name: String, service_version: String| {
+        port_event(port_scan_event::Event::Port(PortFinding {
+            ip_addr: ip_addr.to_string(),
+            port: port as u32,
+            status: "open".to_string(),
+            service_name: service_name,
+            service_version: service_version,
+        }))
+    };
+    for port in &open_ports {
+        if tx
+            .blocking_send(Ok(finding(*port, String::new(), String::new())))
+            .is_err()
+        {
+            return;
+        }
+    }
+    if request.service_detection && !open_ports.is_empty() {
+        let probe_setting: ServiceProbeSetting =
+            ServiceProbeSetting::default(ip_addr, hostname, open_ports.clone());
+        let service_result = ServiceDetector::new(probe_setting).run();
+        for port in &open_ports {
+            if let Some(result) = service_result.get(port) {
+                let event = finding(
+                    *port,
+                    result.service_name.clone(),
+                    result.service_detail.clone().unwrap_or_default(),
+                );
+                if tx.blocking_send(Ok(event)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+    let _ = tx.blocking_send(Ok(port_event(port_scan_event::Event::Summary(Summary {
+        found: open_ports.len() as u64,
+        elapsed_millis: start_time.elapsed().as_millis() as u64,
+    }))));
+}
+
+/// Blocking host scan. Hosts are sent as soon as they reply
+fn run_host_scan(
+    interface: Interface,
+    targets: Vec<IpAddr>,
+    port: u16,
+    request: HostScanRequest,
+    tx: EventSender<HostScanEvent>,
+) {
+    let start_time: Instant = Instant::now();
+    let scan_type: HostScanType = if request.protocol.is_empty() {
+        HostScanType::IcmpPingScan
+    } else {
+        HostScanType::from_str(&request.protocol)
+    };
+    let total: u64 = targets.len() as u64;
+    let hosts: Vec<Host> = targets
+        .into_iter()
+        .map(|ip| Host::new(ip, String::new()).with_ports(vec![port]))
+        .collect();
+    let scan_setting = HostScanSetting::default()
+        .set_if_index(interface.index)
+        .set_protocol(scan_type.protocol())
+        .set_scan_type(scan_type)
+        .set_targets(hosts)
+        .set_timeout(duration_or(request.timeout_millis, 10000))
+        .set_wait_time(duration_or(request.wait_time_millis, 200))
+        .set_send_rate(crate::util::setting::calculate_send_rate(&interface));
+    let host_scanner = HostScanner::new(scan_setting);
+    let progress_rx = host_scanner.get_progress_receiver();
+    let found_rx = host_scanner.get_found_receiver();
+    let found_tx: EventSender<HostScanEvent> = tx.clone();
+    let found_handle = thread::spawn(move || {
+        let oui_map = crate::db::get_oui_detail_map();
+        let mut found: u64 = 0;
+        while let Ok(host) = found_rx.lock().unwrap().recv() {
+            found += 1;
+            let vendor_name: String = if host.mac_addr != MacAddr::zero() {
+                crate::db::get_vendor_name(&oui_map, &host.mac_addr)
+            } else {
+                String::new()
+            };
+            let event = host_event(host_scan_event::Event::Host(HostFinding {
+                ip_addr: host.ip_addr.to_string(),
+                hostname: host.hostname.clone(),
+                mac_addr: if host.mac_addr != MacAddr::zero() {
+                    host.mac_addr.to_string()
+                } else {
+                    String::new()
+                },
+                vendor_name: vendor_name,
+                ttl: host.ttl as u32,
+                discovered_by: host
+                    .discovered_by
+                    .iter()
+                    .map(|p| p.to_str().to_string())
+                    .collect(),
+            }));
+            if found_tx.blocking_send(Ok(event)).is_err() {
+                break;
+            }
+        }
+        found
+    });
+    let handle = thread::spawn(move || host_scanner.scan());
+    let mut done: u64 = 0;
+    while let Ok(_host) = progress_rx.lock().unwrap().recv() {
+        done += 1;
+        let progress = host_event(host_scan_event::Event::Progress(Progress {
+            done: done,
+            total: total,
+        }));
+        if tx.blocking_send(Ok(progress)).is_err() {
+            break;
+        }
+    }
+    if handle.join().is_err() {
+        let _ = tx.blocking_send(Err(Status::internal("Host scan failed")));
+        return;
+    }
+    let found: u64 = found_handle.join().unwrap_or(0);
+    let _ = tx.blocking_send(Ok(host_event(host_scan_event::Event::Summary(Summary {
+        found: found,
+        elapsed_millis: start_time.elapsed().as_millis() as u64,
+    }))));
+}
+
+#[tonic::async_trait]
+impl Scanner for ScannerService {
+    type ScanPortsStream = ReceiverStream<Result<PortScanEvent, Status>>;
+    type ScanHostsStream = ReceiverStream<Result<HostScanEvent, Status>>;
+
+    async fn scan_ports(
+        &self,
+        request: Request<PortScanRequest>,
+    ) -> Result<Response<Self::ScanPortsStream>, Status> {
+        let request: PortScanRequest = request.into_inner();
+        let (ip_addr, hostname): (IpAddr, String) =
+            if crate::host::is_valid_ip_addr(&request.target) {
+                let ip_addr: IpAddr = request
+                    .target
+                    .parse()
+                    .map_err(|_| Status::invalid_argument("Invalid target"))?;
+                (ip_addr, request.target.clone())
+            } else {
+                match crate::dns::lookup_host_name(&request.target) {
+                    Some(ip_addr) => (ip_addr, request.target.clone()),
+                    None => {
+                        return Err(Status::invalid_argument(format!(
+                            "Failed to resolve {}",
+                            request.target
+                        )))
+                    }
+                }
+            };
+        let ports: Vec<u16> = if request.ports.is_empty() {
+            crate::db::get_default_ports()
+        } else {
+            let mut ports: Vec<u16> = Vec::new();
+            for port in &request.ports {
+                match u16::try_from(*port) {
+                    Ok(port) => ports.push(port),
+                    Err(_) => {
+                        return Err(Status::invalid_argument(format!("Invalid port: {}", port)))
+                    }
+                }
+            }
+            ports
+        };
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER_SIZE);
+        let interface: Interface = self.interface.clone();
+        thread::spawn(move || run_port_scan(interface, ip_addr, hostname, ports, request, tx));
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn scan_hosts(
+        &self,
+        request: Request<HostScanRequest>,
+    ) -> Result<Response<Self::ScanHostsStream>, Status> {
+        let request: HostScanRequest = request.into_inner();
+        let mut targets: Vec<IpAddr> = Vec::new();
+        for target in &request.targets {
+            match crate::ip::expand_ip_target(target) {
+                Some(ips) => targets.extend(ips),
+                None => {
+                    return Err(Status::invalid_argument(format!(
+                        "Invalid target: {}",
+                        target
+                    )))
+                }
+            }
+        }
+        if targets.is_empty() {
+            return Err(Status::invalid_argument("No targets"));
+        }
+        let port: u16 = if request.port == 0 {
+            80
+        } else {
+            u16::try_from(request.port)
+                .map_err(|_| Status::invalid_argument(format!("Invalid port: {}", request.port)))?
+        };
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER_SIZE);
+        let interface: Interface = self.interface.clone();
+        thread::spawn(move || run_host_scan(interface, targets, port, request, tx));
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Serve the gRPC API until the process is stopped
+pub fn serve(listen_addr: SocketAddr, interface: Interface) -> Result<(), String> {
+    let service = ScannerService {
+        interface: interface,
+    };
+    let rt = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    rt.block_on(
+        tonic::transport::Server::builder()
+            .add_service(ScannerServer::new(service))
+            .serve(listen_addr),
+    )
+    .map_err(|e| format!("gRPC server error: {}", e))
+}
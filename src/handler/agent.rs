@@ -0,0 +1,189 @@
+use crate::agent::controller::ControllerReport;
+use crate::agent::AgentSetting;
+use crate::output;
+use crate::serve::{JobRequest, JobStatus};
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use termtree::Tree;
+
+/// Shared key from --key or NREV_AGENT_KEY
+fn get_key(sub_args: &ArgMatches) -> Option<String> {
+    sub_args
+        .get_one::<String>("key")
+        .cloned()
+        .or(std::env::var(crate::agent::AGENT_KEY_ENV).ok())
+        .filter(|key| !key.is_empty())
+}
+
+pub fn handle_agent(args: &ArgMatches) {
+    let agent_args = match args.subcommand_matches("agent") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let listen_addr: SocketAddr = match agent_args.get_one::<SocketAddr>("listen") {
+        Some(listen_addr) => *listen_addr,
+        None => crate::config::DEFAULT_AGENT_LISTEN_ADDR.parse().unwrap(),
+    };
+    let key: String = match get_key(agent_args) {
+        Some(key) => key,
+        None => {
+            output::log_with_time(
+                "Agent requires a shared key. Set --key or NREV_AGENT_KEY",
+                "ERROR",
+            );
+            return;
+        }
+    };
+    let name: String = match agent_args.get_one::<String>("name") {
+        Some(name) => name.clone(),
+        None => std::env::var("HOSTNAME")
+            .or(std::env::var("COMPUTERNAME"))
+            .unwrap_or(listen_addr.to_string()),
+    };
+    let setting = AgentSetting {
+        listen_addr: listen_addr,
+        name: name,
+        key: key,
+    };
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("Agent", None, None));
+        tree.push(node_label("Name", Some(&setting.name), None));
+        tree.push(node_label("Listen", Some(&listen_addr.to_string()), None));
+        tree.push(node_label("Jobs", Some("Signed with HMAC-SHA256"), None));
+        println!("{}", tree);
+    }
+    output::log_with_time(
+        "Jobs and results are signed, not encrypted. Use a VPN or SSH tunnel on untrusted networks",
+        "INFO",
+    );
+    output::log_with_time(&format!("Listening on {}", listen_addr), "INFO");
+    if let Err(e) = crate::agent::serve(setting) {
+        output::log_with_time(&e, "ERROR");
+    }
+}
+
+pub fn handle_controller(args: &ArgMatches) {
+    let controller_args = match args.subcommand_matches("controller") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let agents: Vec<String> = match controller_args.get_many::<String>("agent") {
+        Some(agents) => agents.cloned().collect(),
+        None => return,
+    };
+    let key: String = match get_key(controller_args) {
+        Some(key) => key,
+        None => {
+            output::log_with_time(
+                "Controller requires the shared key of the agents. Set --key or NREV_AGENT_KEY",
+                "ERROR",
+            );
+            return;
+        }
+    };
+    let job_line: &String = controller_args.get_one::<String>("job").unwrap();
+    let job: JobRequest = match JobRequest::from_line(job_line) {
+        Some(job) => job,
+        None => {
+            output::log_with_time(
+                "--job must be a command and target. e.g. \"port example.com --ports 22,80\"",
+                "ERROR",
+            );
+            return;
+        }
+    };
+    // Reject jobs the agents would reject, before contacting them
+    if let Err(e) = crate::serve::parse_job(&job, &std::env::temp_dir().join("nrev-validate.json"))
+    {
+        output::log_with_time(&e, "ERROR");
+        return;
+    }
+    output::log_with_time(
+        &format!("Sending job to {} agent(s): {}", agents.len(), job_line),
+        "INFO",
+    );
+    let report: ControllerReport = crate::agent::controller::dispatch(&agents, &key, &job);
+    if args.get_flag("json") {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        show_report(&report);
+    }
+    let failed: usize = report
+        .agents
+        .iter()
+        .filter(|run| run.status == JobStatus::Failed)
+        .count();
+    output::log_with_time(
+        &format!(
+            "{} of {} agent(s) completed",
+            report.agents.len() - failed,
+            report.agents.len()
+        ),
+        if failed == 0 { "INFO" } else { "WARN" },
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&report).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_report(report: &ControllerReport) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("Controller Report", None, None));
+    let mut agents_tree = Tree::new(node_label("Agents", None, None));
+    for run in &report.agents {
+        let elapsed = (run.finished_at - run.started_at)
+            .to_std()
+            .unwrap_or_default();
+        let status: String = match &run.error {
+            Some(e) => format!("Failed: {}", e),
+            None => format!("Done ({:?})", elapsed),
+        };
+        agents_tree.push(node_label(&run.agent, Some(&status), None));
+    }
+    tree.push(agents_tree);
+    let completed: usize = report
+        .agents
+        .iter()
+        .filter(|run| run.status == JobStatus::Done)
+        .count();
+    if !report.hosts.is_empty() {
+        let mut hosts_tree = Tree::new(node_label("Hosts", None, None));
+        for host in &report.hosts {
+            let mut host_tree = Tree::new(node_label(
+                &host.ip_addr.to_string(),
+                Some(&format!("seen by {}/{}", host.seen_by.len(), completed)),
+                None,
+            ));
+            for port in &host.ports {
+                // Ports not open from every vantage point are the interesting ones
+                let open_from: String = if port.open_from.len() == completed {
+                    "all agents".to_string()
+                } else {
+                    port.open_from.join(", ")
+                };
+                host_tree.push(node_label(&port.number.to_string(), Some(&open_from), None));
+            }
+            hosts_tree.push(host_tree);
+        }
+        tree.push(hosts_tree);
+    }
+    println!("{}", tree);
+}
@@ -0,0 +1,309 @@
+use crate::app::StopGuard;
+use crate::output;
+use crate::packet::frame::PacketFrame;
+use crate::pcap::file::{PcapWriter, LINKTYPE_ETHERNET, LINKTYPE_RAW};
+use crate::pcap::filter::PacketFilter;
+use crate::pcap::{PacketCaptureOptions, RawPacket};
+use crate::util::tree::node_label;
+use chrono::{DateTime, Local};
+use clap::ArgMatches;
+use netdev::Interface;
+use nex::packet::tcp::TcpFlags;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use termtree::Tree;
+
+/// Parse size with optional K/M/G suffix (e.g. 100M)
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value: String = value.trim().to_uppercase();
+    let (number, unit): (&str, u64) = match value.chars().last() {
+        Some('K') => (&value[..value.len() - 1], 1024),
+        Some('M') => (&value[..value.len() - 1], 1024 * 1024),
+        Some('G') => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value.as_str(), 1),
+    };
+    number
+        .parse::<u64>()
+        .map(|n| n * unit)
+        .map_err(|_| format!("Invalid size: {}", value))
+}
+
+/// TCP flags in tcpdump notation (S, F, R, P, U, E, W, and . for ACK)
+fn format_tcp_flags(flags: u8) -> String {
+    let names: [(u8, char); 8] = [
+        (TcpFlags::SYN, 'S'),
+        (TcpFlags::FIN, 'F'),
+        (TcpFlags::RST, 'R'),
+        (TcpFlags::PSH, 'P'),
+        (TcpFlags::URG, 'U'),
+        (TcpFlags::ECE, 'E'),
+        (TcpFlags::CWR, 'W'),
+        (TcpFlags::ACK, '.'),
+    ];
+    names
+        .iter()
+        .filter(|(flag, _)| flags & flag != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// One-line summary of the packet
+fn packet_summary(frame: &PacketFrame) -> String {
+    let addrs = crate::pcap::filter::addresses(frame);
+    if let (Some((src, dst)), Some(tcp_header)) = (addrs, &frame.tcp_header) {
+        return format!(
+            "TCP {} > {} [{}] payload {}",
+            SocketAddr::new(src, tcp_header.source),
+            SocketAddr::new(dst, tcp_header.destination),
+            format_tcp_flags(tcp_header.flags),
+            frame.payload.len()
+        );
+    }
+    if let (Some((src, dst)), Some(udp_header)) = (addrs, &frame.udp_header) {
+        return format!(
+            "UDP {} > {} payload {}",
+            SocketAddr::new(src, udp_header.source),
+            SocketAddr::new(dst, udp_header.destination),
+            frame.payload.len()
+        );
+    }
+    if let (Some((src, dst)), Some(icmp_header)) = (addrs, &frame.icmp_header) {
+        return format!("ICMP {} > {} {:?}", src, dst, icmp_header.icmp_type);
+    }
+    if let (Some((src, dst)), Some(icmpv6_header)) = (addrs, &frame.icmpv6_header) {
+        return format!("ICMPv6 {} > {} {:?}", src, dst, icmpv6_header.icmpv6_type);
+    }
+    if let Some(arp_header) = &frame.arp_header {
+        return format!(
+            "ARP {} > {} {:?}",
+            arp_header.sender_proto_addr, arp_header.target_proto_addr, arp_header.operation
+        );
+    }
+    if let Some(ipv4_header) = &frame.ipv4_header {
+        return format!(
+            "IPv4 {} > {} {:?}",
+            ipv4_header.source, ipv4_header.destination, ipv4_header.next_level_protocol
+        );
+    }
+    if let Some(ipv6_header) = &frame.ipv6_header {
+        return format!(
+            "IPv6 {} > {} {:?}",
+            ipv6_header.source, ipv6_header.destination, ipv6_header.next_header
+        );
+    }
+    match &frame.ethernet_header {
+        Some(ethernet_header) => format!(
+            "{} > {} {:?}",
+            ethernet_header.source, ethernet_header.destination, ethernet_header.ethertype
+        ),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn handle_capture(args: &ArgMatches) {
+    let capture_args = match args.subcommand_matches("capture") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let interface: Interface = match capture_args
+        .get_one::<String>("interface")
+        .or(args.get_one::<String>("interface"))
+    {
+        Some(if_name) => match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => {
+                output::log_with_time(&format!("Interface not found: {}", if_name), "ERROR");
+                return;
+            }
+        },
+        None => match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        },
+    };
+    let filter: Option<PacketFilter> = match capture_args.get_one::<String>("filter") {
+        Some(expr) => match PacketFilter::parse(expr) {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        },
+        None => None,
+    };
+    let max_size: Option<u64> = match capture_args.get_one::<String>("max_size") {
+        Some(size) => match parse_size(size) {
+            Ok(size) => Some(size),
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        },
+        None => None,
+    };
+    let count: Option<usize> = capture_args.get_one::<usize>("count").copied();
+    let duration: Option<Duration> = capture_args
+        .get_one::<u64>("duration")
+        .map(|secs| Duration::from_secs(*secs));
+    let output_path: Option<PathBuf> = capture_args.get_one::<PathBuf>("output").cloned();
+    let mut options: PacketCaptureOptions = PacketCaptureOptions::from_interface(&interface);
+    options.promiscuous = capture_args.get_flag("promisc");
+    if let Some(duration) = duration {
+        options.capture_timeout = duration;
+    }
+    let mut writer: Option<PcapWriter> = match &output_path {
+        Some(path) => {
+            let link_type: u32 = if options.tunnel {
+                LINKTYPE_RAW
+            } else {
+                LINKTYPE_ETHERNET
+            };
+            match PcapWriter::create(path, link_type) {
+                Ok(writer) => Some(writer),
+                Err(e) => {
+                    output::log_with_time(&e, "ERROR");
+                    return;
+                }
+            }
+        }
+        None => None,
+    };
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("Capture Config", None, None));
+        tree.push(node_label("Interface", Some(&interface.name), None));
+        if let Some(expr) = capture_args.get_one::<String>("filter") {
+            tree.push(node_label("Filter", Some(expr), None));
+        }
+        if let Some(count) = count {
+            tree.push(node_label("Count", Some(&count.to_string()), None));
+        }
+        if let Some(duration) = duration {
+            tree.push(node_label(
+                "Duration",
+                Some(&format!("{:?}", duration)),
+                None,
+            ));
+        }
+        if let Some(max_size) = max_size {
+            tree.push(node_label(
+                "Max Size",
+                Some(&format!("{} bytes", max_size)),
+                None,
+            ));
+        }
+        if let Some(path) = &output_path {
+            tree.push(node_label("Output", Some(&path.to_string_lossy()), None));
+        }
+        println!("{}", tree);
+    }
+    let config = nex::datalink::Config {
+        write_buffer_size: 4096,
+        read_buffer_size: 65536,
+        read_timeout: Some(options.read_timeout),
+        write_timeout: None,
+        channel_type: nex::datalink::ChannelType::Layer2,
+        bpf_fd_attempts: 1000,
+        linux_fanout: None,
+        promiscuous: options.promiscuous,
+    };
+    let mut rx = match nex::datalink::channel(&interface, config) {
+        Ok(nex::datalink::Channel::Ethernet(_, rx)) => rx,
+        Ok(_) => {
+            output::log_with_time("Unknown channel type", "ERROR");
+            return;
+        }
+        Err(e) => {
+            output::log_with_time(&format!("Failed to open channel: {}", e), "ERROR");
+            return;
+        }
+    };
+    let stop_guard: StopGuard = crate::app::handle_stop();
+    let stop: Arc<Mutex<bool>> = stop_guard.get_stop_handle();
+    output::log_with_time(
+        &format!("Capturing on {}. Press Ctrl-C to stop", interface.name),
+        "INFO",
+    );
+    let (tx, packet_rx): (Sender<RawPacket>, Receiver<RawPacket>) = channel();
+    let capture_stop: Arc<Mutex<bool>> = stop.clone();
+    let handle =
+        thread::spawn(move || crate::pcap::start_raw_capture(&mut rx, options, &capture_stop, tx));
+    let start_time: Instant = Instant::now();
+    let mut packets: usize = 0;
+    let mut bytes: usize = 0;
+    for packet in packet_rx.iter() {
+        if let Some(filter) = &filter {
+            if !filter.matches(&packet.frame) {
+                continue;
+            }
+        }
+        if let Some(writer) = &mut writer {
+            if let Some(max_size) = max_size {
+                if writer.size_with(packet.data.len()) > max_size {
+                    output::log_with_time("Size limit reached", "INFO");
+                    break;
+                }
+            }
+            if let Err(e) = writer.write_packet(packet.timestamp, &packet.data) {
+                output::log_with_time(&e, "ERROR");
+                break;
+            }
+        } else if let Some(max_size) = max_size {
+            if (bytes + packet.data.len()) as u64 > max_size {
+                output::log_with_time("Size limit reached", "INFO");
+                break;
+            }
+        }
+        packets += 1;
+        bytes += packet.data.len();
+        if !crate::app::is_quiet_mode() {
+            let time: DateTime<Local> = DateTime::from(packet.timestamp);
+            println!(
+                "{} {} len {}",
+                time.format("%H:%M:%S%.6f"),
+                packet_summary(&packet.frame),
+                packet.data.len()
+            );
+        }
+        if count.map_or(false, |count| packets >= count) {
+            break;
+        }
+    }
+    // Stop the capture thread if a limit ended the loop
+    if let Ok(mut stop) = stop.lock() {
+        *stop = true;
+    }
+    drop(packet_rx);
+    let _ = handle.join();
+    if let Some(writer) = &mut writer {
+        if let Err(e) = writer.flush() {
+            output::log_with_time(&e, "ERROR");
+        }
+    }
+    output::log_with_time(
+        &format!(
+            "{} packets ({} bytes) captured in {:?}",
+            packets,
+            bytes,
+            start_time.elapsed()
+        ),
+        "INFO",
+    );
+    if let (Some(path), Some(writer)) = (&output_path, &writer) {
+        output::log_with_time(
+            &format!(
+                "Saved to {} ({} bytes)",
+                path.to_string_lossy(),
+                writer.size()
+            ),
+            "INFO",
+        );
+    }
+}
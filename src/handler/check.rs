@@ -1,15 +1,81 @@
-use crate::dep;
+use crate::output;
+use crate::sys::capability::{CapabilityReport, ScanSupport};
+use crate::util::tree::node_label;
 use clap::ArgMatches;
+use std::path::PathBuf;
+use termtree::Tree;
 
-pub fn check_dependencies(_arg: &ArgMatches) {
-    match dep::check_dependencies() {
-        Ok(_) => {
-            println!("All dependencies are installed.");
-            std::process::exit(0);
-        }
-        Err(e) => {
-            println!("Error: {}", e);
-            std::process::exit(1);
+pub fn check_dependencies(arg: &ArgMatches) {
+    output::log_with_time("Checking capabilities...", "INFO");
+    let report: CapabilityReport = crate::sys::capability::run_self_test();
+    if arg.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&report).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_capability_report(&report);
+    }
+    match arg.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&report).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
         }
+        None => {}
+    }
+    // Non-zero if any scan type would fail
+    if report.scans.iter().all(|scan| scan.supported) {
+        std::process::exit(0);
+    } else {
+        std::process::exit(1);
+    }
+}
+
+fn scan_status(scan: &ScanSupport) -> String {
+    if scan.supported {
+        "OK".to_string()
+    } else {
+        let missing: Vec<&str> = scan.missing.iter().map(|c| c.name()).collect();
+        format!("Unavailable (no {})", missing.join(", "))
+    }
+}
+
+fn show_capability_report(report: &CapabilityReport) {
+    let mut tree = Tree::new(node_label("Capabilities", None, None));
+    tree.push(node_label("OS", Some(&report.os_type), None));
+    tree.push(node_label(
+        "Privileged",
+        Some(&report.privileged.to_string()),
+        None,
+    ));
+    if let Some(interface) = &report.interface {
+        tree.push(node_label("Default Interface", Some(interface), None));
+    }
+    let mut checks_tree = Tree::new(node_label("Checks", None, None));
+    for check in &report.checks {
+        let status: &str = if check.available { "OK" } else { "NG" };
+        let mut check_tree = Tree::new(node_label(check.capability.name(), Some(status), None));
+        check_tree.push(node_label("Detail", Some(&check.detail), None));
+        checks_tree.push(check_tree);
+    }
+    tree.push(checks_tree);
+    let mut scans_tree = Tree::new(node_label("Scan Types", None, None));
+    for scan in &report.scans {
+        scans_tree.push(node_label(&scan.scan_type, Some(&scan_status(scan)), None));
+    }
+    tree.push(scans_tree);
+    println!("{}", tree);
+    if !report.privileged && report.scans.iter().any(|scan| !scan.supported) {
+        output::log_with_time(
+            "Some scan types need administrator privileges. Run with sudo (or as Administrator)",
+            "WARN",
+        );
     }
 }
@@ -1,10 +1,25 @@
 use crate::db;
+use crate::dns::ad::AdDiscoveryResult;
+use crate::dns::axfr::{self, ZoneTransfer};
+use crate::dns::checkpoint::{self, CheckpointEntry, CheckpointWriter, ResumeState};
 use crate::dns::domain::Domain;
+use crate::dns::mdns::MdnsDiscoveryResult;
+use crate::dns::passive::PassiveSource;
+use crate::dns::query::{CacheSnoopResult, DnsQueryResult, ResourceRecord};
+use crate::dns::rdns::{RdnsSetting, RdnsSweepResult};
+use crate::dns::resolver::ResolverPool;
 use crate::dns::{result::DomainScanResult, scanner::DomainScanner};
+use crate::host::Host;
+use crate::scan::result::{ScanResult, ScanStatus};
+use crate::scan::setting::{PortScanSetting, PortScanType};
 use crate::util::tree::node_label;
 use clap::ArgMatches;
+use hickory_resolver::proto::rr::RecordType;
 use indicatif::{ProgressBar, ProgressDrawTarget};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::{thread, time::Duration};
 use termtree::Tree;
 use tokio::runtime::Runtime;
@@ -21,6 +36,15 @@ pub fn handle_subdomain_scan(args: &ArgMatches) {
         Some(target) => target.to_owned(),
         None => return,
     };
+    let timeout = match host_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_secs(30),
+    };
+
+    if host_args.get_flag("passive_only") {
+        handle_passive_subdomain_scan(args, target, timeout);
+        return;
+    }
 
     let domain_ips: Vec<std::net::IpAddr> = crate::dns::lookup_host(&target);
     if domain_ips.is_empty() {
@@ -28,16 +52,14 @@ pub fn handle_subdomain_scan(args: &ArgMatches) {
         return;
     }
     let target_domain: Domain = crate::dns::domain::Domain {
+        unicode_name: crate::dns::idn::to_unicode(&target),
         domain_name: target,
         ips: domain_ips,
+        records: vec![],
+        tags: vec![],
     };
 
-    let timeout = match host_args.get_one::<u64>("timeout") {
-        Some(timeout) => Duration::from_millis(*timeout),
-        None => Duration::from_secs(30),
-    };
-
-    let word_list: Vec<String> = match host_args.get_one::<PathBuf>("wordlist") {
+    let mut word_list: Vec<String> = match host_args.get_one::<PathBuf>("wordlist") {
         Some(file_path) => match std::fs::read_to_string(&file_path) {
             Ok(contents) => {
                 let mut word_list: Vec<String> = Vec::new();
@@ -46,14 +68,83 @@ pub fn handle_subdomain_scan(args: &ArgMatches) {
                     if word.is_empty() {
                         continue;
                     }
-                    word_list.push(word.to_owned());
+                    // Unicode labels are looked up in punycode
+                    match crate::dns::idn::to_ascii(word) {
+                        Ok(word) => word_list.push(word),
+                        Err(e) => output::log_with_time(&e, "WARN"),
+                    }
                 }
                 word_list
             }
             Err(_) => vec![],
         },
-        None => db::get_subdomain(),
+        None => match host_args
+            .get_one::<String>("wordlist_size")
+            .map(|s| s.as_str())
+        {
+            Some("small") => db::get_subdomain_small(),
+            Some("large") => db::get_subdomain_large(),
+            _ => db::get_subdomain(),
+        },
     };
+    if word_list.is_empty() {
+        output::log_with_time("Wordlist is empty or could not be read", "ERROR");
+        return;
+    }
+    // Words are looked up in order. Duplicates would shift the resume position
+    let mut seen_words: HashSet<String> = HashSet::new();
+    word_list.retain(|word| seen_words.insert(word.clone()));
+
+    // Findings are appended to the output file while scanning
+    let mut resume_state: Option<ResumeState> = None;
+    let mut checkpoint: Option<CheckpointWriter> = None;
+    if let Some(file_path) = args.get_one::<PathBuf>("save") {
+        let writer = if host_args.get_flag("resume") {
+            let state: ResumeState = match checkpoint::load(file_path) {
+                Ok(state) => state,
+                Err(e) => {
+                    output::log_with_time(&e, "ERROR");
+                    return;
+                }
+            };
+            if state.target != target_domain.domain_name || state.word_count != word_list.len() {
+                output::log_with_time(
+                    &format!(
+                        "{} was started with a different target or word-list",
+                        file_path.to_string_lossy()
+                    ),
+                    "ERROR",
+                );
+                return;
+            }
+            output::log_with_time(
+                &format!(
+                    "Resuming from word {} of {} ({} subdomains found)",
+                    state.position,
+                    state.word_count,
+                    state.domains.len()
+                ),
+                "INFO",
+            );
+            resume_state = Some(state);
+            CheckpointWriter::append(file_path)
+        } else {
+            CheckpointWriter::create(file_path, &target_domain.domain_name, word_list.len())
+        };
+        match writer {
+            Ok(writer) => checkpoint = Some(writer),
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        }
+    } else if host_args.get_flag("resume") {
+        output::log_with_time(
+            "--resume requires the output file (-o <file_path>)",
+            "ERROR",
+        );
+        return;
+    }
 
     let mut domain_scanner = match DomainScanner::new() {
         Ok(scanner) => scanner,
@@ -62,14 +153,105 @@ pub fn handle_subdomain_scan(args: &ArgMatches) {
     domain_scanner.set_base_domain(target_domain.domain_name.clone());
     domain_scanner.word_list = word_list;
     domain_scanner.set_timeout(timeout);
+    let resume_position: usize = match resume_state {
+        Some(state) => {
+            domain_scanner.set_resume_state(state.position, state.domains);
+            state.position
+        }
+        None => 0,
+    };
+    if host_args.get_flag("passive") {
+        for domain in collect_passive_subdomains(&target_domain.domain_name, timeout) {
+            domain_scanner.add_domain(domain);
+        }
+    }
+    match get_qps(host_args) {
+        Ok(Some(qps)) => {
+            // Rough number of queries: words, passive names and wildcard/record checks are ignored
+            let queries: usize = domain_scanner.word_list.len() + domain_scanner.domain_list.len();
+            if Duration::from_secs((queries as u64) / (qps as u64)) > timeout {
+                output::log_with_time(
+                    &format!(
+                        "{} queries at {} qps exceed the timeout {:?}. Set --timeout to scan the whole list",
+                        queries, qps, timeout
+                    ),
+                    "WARN",
+                );
+            }
+            domain_scanner.set_qps(qps);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    }
+    if let Some(concurrency) = host_args.get_one::<usize>("concurrency") {
+        if *concurrency == 0 {
+            output::log_with_time("Concurrency must be at least 1", "ERROR");
+            return;
+        }
+        domain_scanner.set_concurrent_limit(*concurrency);
+    }
+    if let Some(record_types) = host_args.get_many::<String>("records") {
+        domain_scanner.set_record_types(record_types.map(|t| parse_record_type(t)).collect());
+    }
+    if host_args.get_flag("recursive") {
+        domain_scanner.set_recursive_depth(*host_args.get_one::<usize>("depth").unwrap_or(&1));
+    }
+    let rt = Runtime::new().unwrap();
+    let resolver_addrs: Vec<SocketAddr> = match get_resolver_addrs(host_args) {
+        Ok(addrs) => addrs,
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    };
+    if !resolver_addrs.is_empty() {
+        let mut resolver_pool: ResolverPool =
+            ResolverPool::new(&resolver_addrs, domain_scanner.resolve_timeout);
+        // Drop resolvers that cannot answer for the target domain
+        for (addr, e) in rt.block_on(resolver_pool.health_check(&target_domain.domain_name)) {
+            output::log_with_time(&format!("Resolver {} removed: {}", addr, e), "WARN");
+        }
+        if resolver_pool.is_empty() {
+            output::log_with_time("No healthy resolver available", "ERROR");
+            return;
+        }
+        domain_scanner.set_resolver_pool(resolver_pool);
+    }
 
     print_option(&domain_scanner);
 
+    let mut zone_transfers: Vec<ZoneTransfer> = vec![];
+    let mut zone_domains: Vec<Domain> = vec![];
+    if host_args.get_flag("axfr") {
+        output::log_with_time("Attempting zone transfer...", "INFO");
+        let (transfers, zone) = axfr::check_zone_transfer(&target_domain.domain_name);
+        if transfers.is_empty() {
+            output::log_with_time("No authoritative nameserver found", "WARN");
+        }
+        for transfer in &transfers {
+            if transfer.allowed {
+                output::log_with_time(
+                    &format!(
+                        "MISCONFIGURATION: {} ({}) allows zone transfer. {} records exposed",
+                        transfer.nameserver, transfer.ip_addr, transfer.record_count
+                    ),
+                    "WARN",
+                );
+            }
+        }
+        zone_transfers = transfers;
+        zone_domains = axfr::zone_to_domains(&zone);
+    }
+
     // Display progress with indicatif
     if !crate::app::is_quiet_mode() {
         println!("[Progress]");
     }
-    let bar = ProgressBar::new(domain_scanner.word_list.len() as u64);
+    let remaining_words: usize = domain_scanner.word_list.len() - resume_position;
+    let bar = ProgressBar::new((remaining_words + domain_scanner.domain_list.len()) as u64);
     if crate::app::is_quiet_mode() {
         bar.set_draw_target(ProgressDrawTarget::hidden());
     }
@@ -79,15 +261,99 @@ pub fn handle_subdomain_scan(args: &ArgMatches) {
     bar.set_message("SubdomainScan");
 
     let rx = domain_scanner.get_progress_receiver();
-    let rt = Runtime::new().unwrap();
+    let word_count: usize = domain_scanner.word_list.len();
     // Run scan
     let handle = thread::spawn(move || rt.block_on(async { domain_scanner.scan().await }));
-    // Print progress
-    while let Ok(_domain) = rx.lock().unwrap().recv() {
+    // Print progress. Subdomains are printed as they resolve
+    let mut found_count: usize = 0;
+    let mut lookup_count: usize = 0;
+    let mut saved_position: usize = resume_position;
+    while let Ok(domain) = rx.lock().unwrap().recv() {
+        // Recursion adds a word-list pass for each discovered subdomain
+        if bar.position() >= bar.length() {
+            bar.inc_length(word_count as u64);
+        }
         bar.inc(1);
+        lookup_count += 1;
+        if let Some(writer) = &mut checkpoint {
+            // Names are looked up in batches. Every word of a completed batch is done
+            let batch_size: usize = crate::config::SUBDOMAIN_BATCH_SIZE;
+            let position: usize = resume_position
+                + std::cmp::min(lookup_count / batch_size * batch_size, remaining_words);
+            let mut entries: Vec<CheckpointEntry> = Vec::new();
+            if domain.ips.len() > 0 {
+                entries.push(CheckpointEntry::Found(domain.clone()));
+            }
+            if position > saved_position {
+                entries.push(CheckpointEntry::Position(position));
+                saved_position = position;
+            }
+            for entry in entries {
+                if let Err(e) = writer.write(&entry) {
+                    bar.println(format!(
+                        "[{}] [WARN] {}",
+                        crate::sys::time::get_systime(),
+                        e
+                    ));
+                    checkpoint = None;
+                    break;
+                }
+            }
+        }
+        if domain.ips.len() > 0 {
+            found_count += 1;
+            let ips: Vec<String> = domain.ips.iter().map(|ip| ip.to_string()).collect();
+            bar.println(format!(
+                "[{}] [INFO] Found {} ({})",
+                crate::sys::time::get_systime(),
+                domain.display_name(),
+                ips.join(", ")
+            ));
+            bar.set_message(format!("SubdomainScan - {} found", found_count));
+        }
+    }
+    bar.finish_with_message(format!("SubdomainScan - {} found", found_count));
+    let mut result: DomainScanResult = handle.join().unwrap();
+    result.zone_transfers = zone_transfers;
+    // Merge the transferred zone into the brute-force results
+    for zone_domain in zone_domains {
+        match result
+            .domains
+            .iter_mut()
+            .find(|d| d.domain_name == zone_domain.domain_name)
+        {
+            Some(domain) => {
+                for ip in zone_domain.ips {
+                    if !domain.ips.contains(&ip) {
+                        domain.ips.push(ip);
+                    }
+                }
+                for record in zone_domain.records {
+                    if !domain.records.contains(&record) {
+                        domain.records.push(record);
+                    }
+                }
+            }
+            None => result.domains.push(zone_domain),
+        }
+    }
+    if host_args.get_flag("cdn") {
+        output::log_with_time("Looking up providers of resolved addresses...", "INFO");
+        crate::dns::cdn::tag_domains(&mut result.domains);
+    }
+    if host_args.get_flag("then_port") {
+        let ports: Vec<u16> = match host_args.get_many::<u16>("port") {
+            Some(ports) => ports.cloned().collect(),
+            None => crate::db::get_default_ports(),
+        };
+        match portscan_domains(args, &result.domains, ports) {
+            Ok((hosts, port_scan_time)) => {
+                result.hosts = hosts;
+                result.port_scan_time = port_scan_time;
+            }
+            Err(e) => output::log_with_time(&e, "ERROR"),
+        }
     }
-    bar.finish_with_message("SubdomainScan");
-    let result: DomainScanResult = handle.join().unwrap();
     // Print results
     if args.get_flag("json") {
         let json_result = serde_json::to_string_pretty(&result).unwrap();
@@ -96,6 +362,178 @@ pub fn handle_subdomain_scan(args: &ArgMatches) {
         show_domainscan_result(&result, target_domain);
     }
     output::log_with_time(&format!("Scan completed in {:?}", result.scan_time), "INFO");
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) if checkpoint.is_some() && result.scan_status == ScanStatus::Timeout => {
+            output::log_with_time(
+                &format!(
+                    "Scan timed out. Progress is kept in {}. Continue with --resume",
+                    file_path.to_string_lossy()
+                ),
+                "WARN",
+            );
+        }
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn get_qps(sub_args: &ArgMatches) -> Result<Option<u32>, String> {
+    match sub_args.get_one::<u32>("qps") {
+        Some(0) => Err(String::from("QPS must be at least 1")),
+        Some(qps) => Ok(Some(*qps)),
+        None => Ok(None),
+    }
+}
+
+/// Port scan the resolved addresses of the domains. Each address is scanned once
+fn portscan_domains(
+    args: &ArgMatches,
+    domains: &[Domain],
+    ports: Vec<u16>,
+) -> Result<(Vec<Host>, Duration), String> {
+    let interface: netdev::Interface = match args.get_one::<String>("interface") {
+        Some(if_name) => match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => return Err(format!("Interface {} not found", if_name)),
+        },
+        None => match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(e) => return Err(format!("Failed to get default interface: {}", e)),
+        },
+    };
+    let mut targets: Vec<Host> = Vec::new();
+    for domain in domains {
+        for ip_addr in &domain.ips {
+            // Skip IPv6 addresses if the interface has no IPv6 address
+            if ip_addr.is_ipv6() && interface.ipv6.is_empty() {
+                continue;
+            }
+            if !targets.iter().any(|host| host.ip_addr == *ip_addr) {
+                targets.push(
+                    Host::new(*ip_addr, domain.domain_name.clone()).with_ports(ports.clone()),
+                );
+            }
+        }
+    }
+    if targets.is_empty() {
+        return Err(String::from("No addresses to port scan"));
+    }
+    output::log_with_time(
+        &format!(
+            "Port scanning {} unique address(es) on {} port(s)...",
+            targets.len(),
+            ports.len()
+        ),
+        "INFO",
+    );
+    let mut port_setting = PortScanSetting::default()
+        .set_if_index(interface.index)
+        .set_scan_type(PortScanType::TcpSynScan)
+        .set_targets(targets.clone())
+        .set_timeout(Duration::from_millis(10000))
+        .set_wait_time(Duration::from_millis(200))
+        .set_send_rate(Duration::from_millis(0));
+    port_setting.randomize_ports();
+    port_setting.randomize_hosts();
+    let portscan_result: ScanResult = crate::handler::host::run_portscan(port_setting);
+    let tcp_map: HashMap<u16, String> = crate::db::get_tcp_map();
+    for host in &mut targets {
+        host.ports = match portscan_result.get_host(host.ip_addr) {
+            Some(h) => h.get_open_ports(),
+            None => Vec::new(),
+        };
+        host.ports.sort_by_key(|port| port.number);
+        for port in &mut host.ports {
+            port.service_name = tcp_map.get(&port.number).cloned().unwrap_or_default();
+        }
+    }
+    Ok((targets, portscan_result.scan_time))
+}
+
+/// Resolvers from --resolvers and --resolvers-file
+fn get_resolver_addrs(sub_args: &ArgMatches) -> Result<Vec<SocketAddr>, String> {
+    let mut resolver_addrs: Vec<SocketAddr> = match sub_args.get_many::<SocketAddr>("resolvers") {
+        Some(addrs) => addrs.cloned().collect(),
+        None => vec![],
+    };
+    if let Some(file_path) = sub_args.get_one::<PathBuf>("resolvers_file") {
+        resolver_addrs.extend(crate::dns::resolver::read_resolvers_file(file_path)?);
+    }
+    Ok(resolver_addrs)
+}
+
+/// Query every passive source and merge the names found
+fn collect_passive_subdomains(base_domain: &str, timeout: Duration) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for source in PassiveSource::all() {
+        output::log_with_time(&format!("Querying {}...", source.name()), "INFO");
+        match source.find_subdomains(base_domain, timeout) {
+            Ok(found) => {
+                output::log_with_time(&format!("{}: {} names", source.name(), found.len()), "INFO");
+                for name in found {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+            Err(e) => output::log_with_time(&e, "WARN"),
+        }
+    }
+    names
+}
+
+/// Subdomain enumeration from public sources only. Names are not resolved
+fn handle_passive_subdomain_scan(args: &ArgMatches, target: String, timeout: Duration) {
+    let start_time = std::time::Instant::now();
+    let names: Vec<String> = collect_passive_subdomains(&target, timeout);
+    let mut result: DomainScanResult = DomainScanResult::new();
+    result.domains = names
+        .into_iter()
+        .map(|name| Domain {
+            unicode_name: crate::dns::idn::to_unicode(&name),
+            domain_name: name,
+            ips: vec![],
+            records: vec![],
+            tags: vec![],
+        })
+        .collect();
+    result.scan_time = start_time.elapsed();
+    result.scan_status = ScanStatus::Done;
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_domainscan_result(
+            &result,
+            Domain {
+                unicode_name: crate::dns::idn::to_unicode(&target),
+                domain_name: target,
+                ips: vec![],
+                records: vec![],
+                tags: vec![],
+            },
+        );
+    }
+    output::log_with_time(
+        &format!(
+            "Found {} subdomains in {:?}",
+            result.domains.len(),
+            result.scan_time
+        ),
+        "INFO",
+    );
     match args.get_one::<PathBuf>("save") {
         Some(file_path) => {
             match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
@@ -114,6 +552,16 @@ pub fn handle_subdomain_scan(args: &ArgMatches) {
     }
 }
 
+fn parse_record_type(name: &str) -> RecordType {
+    match name {
+        "mx" => RecordType::MX,
+        "txt" => RecordType::TXT,
+        "ns" => RecordType::NS,
+        "cname" => RecordType::CNAME,
+        _ => RecordType::SRV,
+    }
+}
+
 fn print_option(setting: &DomainScanner) {
     if crate::app::is_quiet_mode() {
         return;
@@ -126,6 +574,36 @@ fn print_option(setting: &DomainScanner) {
         Some(&setting.word_list.len().to_string()),
         None,
     ));
+    if let Some(qps) = setting.qps {
+        setting_tree.push(node_label("Max QPS", Some(&qps.to_string()), None));
+    }
+    if setting.recursive_depth > 0 {
+        setting_tree.push(node_label(
+            "Recursive Depth",
+            Some(&setting.recursive_depth.to_string()),
+            None,
+        ));
+    }
+    if !setting.record_types.is_empty() {
+        let record_types: Vec<String> =
+            setting.record_types.iter().map(|t| t.to_string()).collect();
+        setting_tree.push(node_label("Records", Some(&record_types.join(", ")), None));
+    }
+    if let Some(resolver_pool) = &setting.resolver_pool {
+        let addrs: Vec<String> = resolver_pool
+            .addrs()
+            .iter()
+            .map(|addr| addr.to_string())
+            .collect();
+        setting_tree.push(node_label("Resolvers", Some(&addrs.join(", ")), None));
+    }
+    if setting.domain_list.len() > 0 {
+        setting_tree.push(node_label(
+            "Passive Names",
+            Some(&setting.domain_list.len().to_string()),
+            None,
+        ));
+    }
     setting_tree.push(node_label(
         "Timeout",
         Some(&format!("{:?}", setting.timeout)),
@@ -143,7 +621,11 @@ fn print_option(setting: &DomainScanner) {
     ));
     tree.push(setting_tree);
     let mut target_tree = Tree::new(node_label("Target", None, None));
-    target_tree.push(node_label("Domain Name", Some(&setting.base_domain), None));
+    target_tree.push(node_label(
+        "Domain Name",
+        Some(&crate::dns::idn::display_name(&setting.base_domain)),
+        None,
+    ));
     tree.push(target_tree);
     println!("{}", tree);
 }
@@ -153,11 +635,11 @@ fn show_domainscan_result(scan_result: &DomainScanResult, target_domain: Domain)
         println!();
     }
     let mut tree = Tree::new(node_label(
-        &format!("SubdomainScan Result - {}", target_domain.domain_name),
+        &format!("SubdomainScan Result - {}", target_domain.display_name()),
         None,
         None,
     ));
-    let mut domain_tree = Tree::new(node_label(&target_domain.domain_name, None, None));
+    let mut domain_tree = Tree::new(node_label(&target_domain.display_name(), None, None));
     let mut ipv4_tree = Tree::new(node_label("IPv4 Addresses", None, None));
     let mut ipv6_tree = Tree::new(node_label("IPv6 Addresses", None, None));
     for ip in &target_domain.ips {
@@ -171,7 +653,23 @@ fn show_domainscan_result(scan_result: &DomainScanResult, target_domain: Domain)
     domain_tree.push(ipv6_tree);
     let mut subdomains_tree = Tree::new(node_label("Subdomains", None, None));
     for domain in &scan_result.domains {
-        let mut subdomain_tree = Tree::new(node_label(&domain.domain_name, None, None));
+        let tags: String = domain
+            .tags
+            .iter()
+            .map(|tag| {
+                if crate::dns::cdn::is_cdn(tag) {
+                    format!("{} (cdn)", tag)
+                } else {
+                    tag.clone()
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        let mut subdomain_tree = Tree::new(node_label(
+            &domain.display_name(),
+            if tags.is_empty() { None } else { Some(&tags) },
+            None,
+        ));
         let mut ipv4_tree = Tree::new(node_label("IPv4 Addresses", None, None));
         let mut ipv6_tree = Tree::new(node_label("IPv6 Addresses", None, None));
         for ip in &domain.ips {
@@ -181,11 +679,766 @@ fn show_domainscan_result(scan_result: &DomainScanResult, target_domain: Domain)
                 ipv6_tree.push(node_label(&ip.to_string(), None, None));
             }
         }
-        subdomain_tree.push(ipv4_tree);
-        subdomain_tree.push(ipv6_tree);
+        if domain.ips.len() > 0 {
+            subdomain_tree.push(ipv4_tree);
+            subdomain_tree.push(ipv6_tree);
+        }
+        if domain.records.len() > 0 {
+            let mut records_tree = Tree::new(node_label("Records", None, None));
+            for record in &domain.records {
+                records_tree.push(node_label(&record.record_type, Some(&record.value), None));
+            }
+            subdomain_tree.push(records_tree);
+        }
         subdomains_tree.push(subdomain_tree);
     }
     domain_tree.push(subdomains_tree);
     tree.push(domain_tree);
+    if scan_result.hosts.len() > 0 {
+        let mut ports_tree = Tree::new(node_label("Open Ports", None, None));
+        for host in &scan_result.hosts {
+            let names: Vec<&str> = scan_result
+                .domains
+                .iter()
+                .filter(|d| d.ips.contains(&host.ip_addr))
+                .map(|d| d.domain_name.as_str())
+                .collect();
+            let mut host_tree = Tree::new(node_label(
+                &host.ip_addr.to_string(),
+                Some(&names.join(", ")),
+                None,
+            ));
+            for port in &host.ports {
+                host_tree.push(node_label(
+                    &port.number.to_string(),
+                    Some(&port.service_name),
+                    None,
+                ));
+            }
+            ports_tree.push(host_tree);
+        }
+        tree.push(ports_tree);
+    }
+    if scan_result.zone_transfers.len() > 0 {
+        let mut axfr_tree = Tree::new(node_label("Zone Transfer", None, None));
+        for transfer in &scan_result.zone_transfers {
+            let status: String = if transfer.allowed {
+                format!(
+                    "ALLOWED - {} records (misconfiguration)",
+                    transfer.record_count
+                )
+            } else {
+                format!("Denied - {}", transfer.error.clone().unwrap_or_default())
+            };
+            axfr_tree.push(node_label(
+                &format!("{} ({})", transfer.nameserver, transfer.ip_addr),
+                Some(&status),
+                None,
+            ));
+        }
+        tree.push(axfr_tree);
+    }
+    println!("{}", tree);
+}
+
+/// dig-like single query
+pub fn handle_dns_query(args: &ArgMatches) {
+    let query_args = match args.subcommand_matches("dns") {
+        Some(matches) => matches,
+        None => return,
+    };
+    if query_args.get_flag("snoop") {
+        handle_cache_snoop(args, query_args);
+        return;
+    }
+    if query_args.get_flag("ad") {
+        handle_ad_discovery(args, query_args);
+        return;
+    }
+    let name: String = match query_args.get_one::<String>("target") {
+        Some(name) => name.to_owned(),
+        None => return,
+    };
+    let record_type: RecordType = match query_args.get_one::<String>("type") {
+        Some(record_type) => match RecordType::from_str(record_type) {
+            Ok(record_type) => record_type,
+            Err(_) => {
+                output::log_with_time(&format!("Invalid record type: {}", record_type), "ERROR");
+                return;
+            }
+        },
+        None => {
+            if name.parse::<IpAddr>().is_ok() {
+                RecordType::PTR
+            } else {
+                RecordType::A
+            }
+        }
+    };
+    let server: SocketAddr = match query_args.get_one::<SocketAddr>("server") {
+        Some(server) => *server,
+        None => crate::dns::query::get_system_server(),
+    };
+    let timeout: Duration = match query_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_secs(5),
+    };
+    output::log_with_time(
+        &format!("Querying {} {} at {}...", name, record_type, server),
+        "INFO",
+    );
+    let result: DnsQueryResult = match crate::dns::query::query(
+        &name,
+        record_type,
+        server,
+        query_args.get_flag("tcp"),
+        timeout,
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    };
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_dns_query_result(&result);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+/// Active Directory service discovery with SRV records
+fn handle_ad_discovery(args: &ArgMatches, query_args: &ArgMatches) {
+    let domain: String = match query_args.get_one::<String>("target") {
+        Some(domain) => domain.trim_end_matches('.').to_string(),
+        None => return,
+    };
+    let server: SocketAddr = match query_args.get_one::<SocketAddr>("server") {
+        Some(server) => *server,
+        None => crate::dns::query::get_system_server(),
+    };
+    let timeout: Duration = match query_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_secs(5),
+    };
+    output::log_with_time(
+        &format!("Querying AD SRV records of {} at {}...", domain, server),
+        "INFO",
+    );
+    let mut result: AdDiscoveryResult =
+        crate::dns::ad::discover(&domain, server, query_args.get_flag("tcp"), timeout);
+    if result.domain_controllers.is_empty() {
+        output::log_with_time(
+            &format!("No domain controllers found for {}", domain),
+            "WARN",
+        );
+    } else if query_args.get_flag("then_port") {
+        let ports: Vec<u16> = match query_args.get_many::<u16>("port") {
+            Some(ports) => ports.cloned().collect(),
+            None => crate::dns::ad::AD_PORTS.to_vec(),
+        };
+        let domains: Vec<Domain> = result
+            .domain_controllers
+            .iter()
+            .map(|dc| Domain {
+                domain_name: dc.host_name.clone(),
+                unicode_name: None,
+                ips: dc.ips.clone(),
+                records: vec![],
+                tags: vec![],
+            })
+            .collect();
+        match portscan_domains(args, &domains, ports) {
+            Ok((hosts, _)) => result.hosts = hosts,
+            Err(e) => output::log_with_time(&e, "ERROR"),
+        }
+    }
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_ad_result(&result);
+    }
+    output::log_with_time(
+        &format!(
+            "Found {} domain controllers in {:?}",
+            result.domain_controllers.len(),
+            result.scan_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_ad_result(result: &AdDiscoveryResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label(
+        &format!("Active Directory - {}", result.domain),
+        None,
+        None,
+    ));
+    tree.push(node_label("Server", Some(&result.server.to_string()), None));
+    let mut dc_tree = Tree::new(node_label("Domain Controllers", None, None));
+    for dc in &result.domain_controllers {
+        let mut host_tree = Tree::new(node_label(&dc.host_name, Some(&dc.roles.join(", ")), None));
+        for ip_addr in &dc.ips {
+            let mut ip_tree = Tree::new(node_label(&ip_addr.to_string(), None, None));
+            if let Some(host) = result.hosts.iter().find(|h| h.ip_addr == *ip_addr) {
+                for port in &host.ports {
+                    ip_tree.push(node_label(
+                        &port.number.to_string(),
+                        Some(&port.service_name),
+                        None,
+                    ));
+                }
+            }
+            host_tree.push(ip_tree);
+        }
+        dc_tree.push(host_tree);
+    }
+    tree.push(dc_tree);
+    let mut srv_tree = Tree::new(node_label("SRV Records", None, None));
+    for service in &result.services {
+        let mut service_tree = Tree::new(node_label(
+            &service.name,
+            Some(&service.response_code),
+            None,
+        ));
+        for target in &service.targets {
+            service_tree.push(node_label(
+                &target.target,
+                Some(&format!(
+                    "port {} priority {} weight {}",
+                    target.port, target.priority, target.weight
+                )),
+                None,
+            ));
+        }
+        srv_tree.push(service_tree);
+    }
+    tree.push(srv_tree);
+    println!("{}", tree);
+}
+
+fn record_section(label: &str, records: &[ResourceRecord]) -> Tree<String> {
+    let mut section_tree = Tree::new(node_label(label, None, None));
+    for record in records {
+        section_tree.push(node_label(
+            &record.name,
+            Some(&format!(
+                "{} {} {}",
+                record.ttl, record.record_type, record.value
+            )),
+            None,
+        ));
+    }
+    section_tree
+}
+
+fn show_dns_query_result(result: &DnsQueryResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label(
+        &format!(
+            "DNS Query - {} {}",
+            crate::dns::idn::display_name(&result.name),
+            result.record_type
+        ),
+        None,
+        None,
+    ));
+    tree.push(node_label(
+        "Server",
+        Some(&format!("{} ({})", result.server, result.transport)),
+        None,
+    ));
+    tree.push(node_label("Status", Some(&result.response_code), None));
+    tree.push(node_label("Flags", Some(&result.flags.join(" ")), None));
+    tree.push(node_label(
+        "Response Time",
+        Some(&format!("{:?}", result.response_time)),
+        None,
+    ));
+    tree.push(record_section("Answer", &result.answers));
+    if result.authorities.len() > 0 {
+        tree.push(record_section("Authority", &result.authorities));
+    }
+    if result.additionals.len() > 0 {
+        tree.push(record_section("Additional", &result.additionals));
+    }
+    println!("{}", tree);
+}
+
+/// Reverse DNS (PTR) sweep over a network
+pub fn handle_rdns_sweep(args: &ArgMatches) {
+    output::log_with_time("Initiating reverse DNS sweep...", "INFO");
+    let rdns_args = match args.subcommand_matches("rdns") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let target: String = match rdns_args.get_one::<String>("target") {
+        Some(target) => target.to_owned(),
+        None => return,
+    };
+    let ips: Vec<IpAddr> = match crate::ip::expand_ip_target(&target) {
+        Some(ips) => ips,
+        None => {
+            output::log_with_time(&format!("Invalid target: {}", target), "ERROR");
+            return;
+        }
+    };
+    let concurrency: usize = match rdns_args.get_one::<usize>("concurrency") {
+        Some(concurrency) => *concurrency,
+        None => crate::config::DEFAULT_RDNS_CONCURRENCY,
+    };
+    if concurrency == 0 {
+        output::log_with_time("Concurrency must be at least 1", "ERROR");
+        return;
+    }
+    let resolve_timeout: Duration = match rdns_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(crate::config::DEFAULT_RDNS_TIMEOUT_MILLIS),
+    };
+    let resolver_addrs: Vec<SocketAddr> = match get_resolver_addrs(rdns_args) {
+        Ok(addrs) => addrs,
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    };
+    let qps: Option<u32> = match get_qps(rdns_args) {
+        Ok(qps) => qps,
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    };
+    let resolver_pool: Option<ResolverPool> = if resolver_addrs.is_empty() {
+        None
+    } else {
+        Some(ResolverPool::new(&resolver_addrs, resolve_timeout))
+    };
+    let netbios: bool = rdns_args.get_flag("netbios");
+    let llmnr: bool = rdns_args.get_flag("llmnr");
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("Reverse DNS Sweep Config", None, None));
+        tree.push(node_label("Target", Some(&target), None));
+        tree.push(node_label("Addresses", Some(&ips.len().to_string()), None));
+        if let Some(resolver_pool) = &resolver_pool {
+            let addrs: Vec<String> = resolver_pool
+                .addrs()
+                .iter()
+                .map(|addr| addr.to_string())
+                .collect();
+            tree.push(node_label("Resolvers", Some(&addrs.join(", ")), None));
+        }
+        tree.push(node_label(
+            "Concurrent limit",
+            Some(&concurrency.to_string()),
+            None,
+        ));
+        if let Some(qps) = qps {
+            tree.push(node_label("Max QPS", Some(&qps.to_string()), None));
+        }
+        if netbios || llmnr {
+            let mut methods: Vec<&str> = vec!["DNS"];
+            if netbios {
+                methods.push("NetBIOS-NS");
+            }
+            if llmnr {
+                methods.push("LLMNR");
+            }
+            tree.push(node_label("Methods", Some(&methods.join(", ")), None));
+        }
+        tree.push(node_label(
+            "Resolve timeout",
+            Some(&format!("{:?}", resolve_timeout)),
+            None,
+        ));
+        println!("{}", tree);
+        println!("[Progress]");
+    }
+    let bar = ProgressBar::new(ips.len() as u64);
+    if crate::app::is_quiet_mode() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar.enable_steady_tick(120);
+    bar.set_style(output::get_progress_style());
+    bar.set_position(0);
+    bar.set_message("ReverseDNS");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let ptx = std::sync::Arc::new(std::sync::Mutex::new(tx));
+    let setting = RdnsSetting {
+        resolver_pool: resolver_pool.map(std::sync::Arc::new),
+        qps: qps,
+        concurrency: concurrency,
+        resolve_timeout: resolve_timeout,
+        netbios: netbios,
+        llmnr: llmnr,
+    };
+    let rt = Runtime::new().unwrap();
+    let handle =
+        thread::spawn(move || rt.block_on(crate::dns::rdns::sweep(target, ips, setting, ptx)));
+    while let Ok(_ip_addr) = rx.recv() {
+        bar.inc(1);
+    }
+    bar.finish_with_message("ReverseDNS");
+    let result: RdnsSweepResult = handle.join().unwrap();
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_rdns_result(&result);
+    }
+    output::log_with_time(
+        &format!(
+            "Found {} names for {} addresses in {:?}",
+            result.records.len(),
+            result.total,
+            result.scan_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+/// mDNS/DNS-SD discovery on the link of the interface
+pub fn handle_mdns_discovery(args: &ArgMatches) {
+    let mdns_args = match args.subcommand_matches("mdns") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let interface: netdev::Interface = if let Some(if_name) = args.get_one::<String>("interface") {
+        match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => {
+                output::log_with_time(&format!("Interface not found: {}", if_name), "ERROR");
+                return;
+            }
+        }
+    } else {
+        match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        }
+    };
+    let src_ip: Ipv4Addr = match interface.ipv4.first() {
+        Some(ipv4) => ipv4.addr,
+        None => {
+            output::log_with_time(&format!("{} has no IPv4 address", interface.name), "ERROR");
+            return;
+        }
+    };
+    let mut service_types: Vec<String> = crate::dns::mdns::MDNS_SERVICE_TYPES
+        .iter()
+        .map(|service_type| service_type.to_string())
+        .collect();
+    if let Some(services) = mdns_args.get_many::<String>("service") {
+        for service_type in services {
+            if !service_types.contains(service_type) {
+                service_types.push(service_type.to_owned());
+            }
+        }
+    }
+    let timeout: Duration = match mdns_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(crate::config::DEFAULT_MDNS_TIMEOUT_MILLIS),
+    };
+    output::log_with_time(
+        &format!(
+            "Browsing mDNS services on {} for {:?}...",
+            interface.name, timeout
+        ),
+        "INFO",
+    );
+    let result: MdnsDiscoveryResult =
+        match crate::dns::mdns::discover(src_ip, &service_types, timeout) {
+            Ok(result) => result,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        };
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_mdns_result(&result);
+    }
+    output::log_with_time(
+        &format!(
+            "Found {} devices with {} service types in {:?}",
+            result.devices.len(),
+            result.service_types.len(),
+            result.scan_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_mdns_result(result: &MdnsDiscoveryResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("mDNS Devices", None, None));
+    for device in &result.devices {
+        let ips: Vec<String> = device.ips.iter().map(|ip| ip.to_string()).collect();
+        let mut device_tree = Tree::new(node_label(&device.host_name, Some(&ips.join(", ")), None));
+        for service in &device.services {
+            let mut service_tree = Tree::new(node_label(
+                &service.instance,
+                Some(&format!("{} port {}", service.service_type, service.port)),
+                None,
+            ));
+            for txt in &service.txt {
+                service_tree.push(node_label(txt, None, None));
+            }
+            device_tree.push(service_tree);
+        }
+        tree.push(device_tree);
+    }
+    println!("{}", tree);
+}
+
+fn show_rdns_result(result: &RdnsSweepResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label(
+        &format!("Reverse DNS - {}", result.target),
+        None,
+        None,
+    ));
+    for record in &result.records {
+        if record.llmnr_names.is_empty() && record.netbios.is_none() {
+            tree.push(node_label(
+                &record.ip_addr.to_string(),
+                Some(&record.names.join(", ")),
+                None,
+            ));
+            continue;
+        }
+        let mut ip_tree = Tree::new(node_label(&record.ip_addr.to_string(), None, None));
+        if !record.names.is_empty() {
+            ip_tree.push(node_label("DNS", Some(&record.names.join(", ")), None));
+        }
+        if !record.llmnr_names.is_empty() {
+            ip_tree.push(node_label(
+                "LLMNR",
+                Some(&record.llmnr_names.join(", ")),
+                None,
+            ));
+        }
+        if let Some(netbios) = &record.netbios {
+            let mut netbios_tree = Tree::new(node_label(
+                "NetBIOS",
+                Some(netbios.name.as_deref().unwrap_or("")),
+                None,
+            ));
+            if let Some(workgroup) = &netbios.workgroup {
+                netbios_tree.push(node_label("Workgroup", Some(workgroup), None));
+            }
+            if let Some(mac_addr) = &netbios.mac_addr {
+                netbios_tree.push(node_label("MAC Address", Some(mac_addr), None));
+            }
+            ip_tree.push(netbios_tree);
+        }
+        tree.push(ip_tree);
+    }
+    println!("{}", tree);
+}
+
+/// DNS cache snooping with non-recursive queries
+fn handle_cache_snoop(args: &ArgMatches, query_args: &ArgMatches) {
+    let server: SocketAddr = match query_args.get_one::<SocketAddr>("server") {
+        Some(server) => *server,
+        None => return,
+    };
+    let record_type: RecordType = match query_args.get_one::<String>("type") {
+        Some(record_type) => match RecordType::from_str(record_type) {
+            Ok(record_type) => record_type,
+            Err(_) => {
+                output::log_with_time(&format!("Invalid record type: {}", record_type), "ERROR");
+                return;
+            }
+        },
+        None => RecordType::A,
+    };
+    let timeout: Duration = match query_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_secs(5),
+    };
+    let names: Vec<String> = match query_args.get_one::<PathBuf>("wordlist") {
+        Some(file_path) => match std::fs::read_to_string(file_path) {
+            Ok(contents) => contents
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| line.to_string())
+                .collect(),
+            Err(e) => {
+                output::log_with_time(
+                    &format!("Failed to read {}: {}", file_path.to_string_lossy(), e),
+                    "ERROR",
+                );
+                return;
+            }
+        },
+        None => crate::dns::query::DEFAULT_SNOOP_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    };
+    output::log_with_time(
+        &format!("Snooping cache of {} for {} names...", server, names.len()),
+        "INFO",
+    );
+    let result: CacheSnoopResult =
+        crate::dns::query::snoop_cache(&names, record_type, server, timeout);
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_cache_snoop_result(&result);
+    }
+    if result.cached_count() > 0 {
+        output::log_with_time(
+            &format!(
+                "{} answers non-recursive queries from its cache. Visited names are exposed",
+                server
+            ),
+            "WARN",
+        );
+    } else if result.entries.iter().all(|e| e.response_code == "REFUSED") {
+        output::log_with_time(&format!("{} refuses non-recursive queries", server), "INFO");
+    }
+    output::log_with_time(
+        &format!(
+            "{} of {} names cached ({:?})",
+            result.cached_count(),
+            result.entries.len(),
+            result.scan_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_cache_snoop_result(result: &CacheSnoopResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label(
+        &format!(
+            "DNS Cache Snooping - {} ({})",
+            result.server, result.record_type
+        ),
+        None,
+        None,
+    ));
+    let mut cached_tree = Tree::new(node_label("Cached", None, None));
+    let mut not_cached_tree = Tree::new(node_label("Not Cached", None, None));
+    for entry in &result.entries {
+        if entry.cached {
+            let mut entry_tree = Tree::new(node_label(
+                &entry.name,
+                Some(&format!("TTL {}", entry.ttl.unwrap_or_default())),
+                None,
+            ));
+            for answer in &entry.answers {
+                entry_tree.push(node_label(answer, None, None));
+            }
+            cached_tree.push(entry_tree);
+        } else {
+            not_cached_tree.push(node_label(&entry.name, Some(&entry.response_code), None));
+        }
+    }
+    tree.push(cached_tree);
+    tree.push(not_cached_tree);
     println!("{}", tree);
 }
@@ -0,0 +1,131 @@
+use crate::exporter::{ExporterSetting, ExporterTarget};
+use crate::output;
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use netdev::Interface;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+use termtree::Tree;
+
+pub fn handle_exporter(args: &ArgMatches) {
+    let exporter_args = match args.subcommand_matches("exporter") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let mut names: Vec<String> = match exporter_args.get_many::<String>("target") {
+        Some(targets) => targets.cloned().collect(),
+        None => Vec::new(),
+    };
+    if let Some(file_path) = exporter_args.get_one::<PathBuf>("input_file") {
+        match super::ping::read_target_list(file_path) {
+            Ok(file_targets) => names.extend(file_targets),
+            Err(e) => {
+                output::log_with_time(
+                    &format!("Failed to read {}: {}", file_path.to_string_lossy(), e),
+                    "ERROR",
+                );
+                return;
+            }
+        }
+    }
+    let interface: Interface = match args.get_one::<String>("interface") {
+        Some(if_name) => match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => {
+                output::log_with_time(&format!("Interface not found: {}", if_name), "ERROR");
+                return;
+            }
+        },
+        None => match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        },
+    };
+    // Resolved once at startup. Duplicate addresses are checked once
+    let mut targets: Vec<ExporterTarget> = Vec::new();
+    let mut known: HashSet<IpAddr> = HashSet::new();
+    for name in names {
+        let ip_addr: IpAddr = match name.parse::<IpAddr>() {
+            Ok(ip_addr) => ip_addr,
+            Err(_) => match crate::dns::lookup_host_name(&name) {
+                Some(ip_addr) => ip_addr,
+                None => {
+                    output::log_with_time(&format!("Failed to resolve {}", name), "ERROR");
+                    return;
+                }
+            },
+        };
+        if known.insert(ip_addr) {
+            targets.push(ExporterTarget {
+                name: name,
+                ip_addr: ip_addr,
+            });
+        }
+    }
+    if targets.is_empty() {
+        output::log_with_time("No target specified", "ERROR");
+        return;
+    }
+    let ports: Vec<u16> = match exporter_args.get_many::<u16>("ports") {
+        Some(ports) => ports.cloned().collect(),
+        None => Vec::new(),
+    };
+    let setting = ExporterSetting {
+        listen_addr: match exporter_args.get_one::<SocketAddr>("listen") {
+            Some(listen_addr) => *listen_addr,
+            None => crate::config::DEFAULT_EXPORTER_LISTEN_ADDR.parse().unwrap(),
+        },
+        interface: interface,
+        targets: targets,
+        ports: ports,
+        interval: Duration::from_secs(
+            *exporter_args
+                .get_one::<u64>("interval")
+                .unwrap_or(&crate::config::DEFAULT_EXPORTER_INTERVAL_SECS),
+        ),
+        timeout: Duration::from_millis(
+            *exporter_args
+                .get_one::<u64>("timeout")
+                .unwrap_or(&crate::config::DEFAULT_EXPORTER_TIMEOUT_MILLIS),
+        ),
+    };
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("Prometheus Exporter", None, None));
+        tree.push(node_label(
+            "Metrics",
+            Some(&format!("http://{}/metrics", setting.listen_addr)),
+            None,
+        ));
+        tree.push(node_label("Interface", Some(&setting.interface.name), None));
+        tree.push(node_label(
+            "Targets",
+            Some(&setting.targets.len().to_string()),
+            None,
+        ));
+        if !setting.ports.is_empty() {
+            let ports: Vec<String> = setting.ports.iter().map(|p| p.to_string()).collect();
+            tree.push(node_label("Ports", Some(&ports.join(",")), None));
+        }
+        tree.push(node_label(
+            "Interval",
+            Some(&format!("{:?}", setting.interval)),
+            None,
+        ));
+        tree.push(node_label(
+            "Timeout",
+            Some(&format!("{:?}", setting.timeout)),
+            None,
+        ));
+        println!("{}", tree);
+    }
+    output::log_with_time(&format!("Listening on {}", setting.listen_addr), "INFO");
+    if let Err(e) = crate::exporter::serve(setting) {
+        output::log_with_time(&e, "ERROR");
+    }
+}
@@ -0,0 +1,111 @@
+use crate::db::geoip::{GeoIpReader, GeoLookupResult};
+use crate::output;
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use termtree::Tree;
+
+pub fn handle_geo(args: &ArgMatches) {
+    let geo_args = match args.subcommand_matches("geo") {
+        Some(matches) => matches,
+        None => return,
+    };
+    if args.get_one::<PathBuf>("geoip").is_none() {
+        output::log_with_time(
+            "Specify MMDB database with --geoip - Example: --geoip GeoLite2-City.mmdb,GeoLite2-ASN.mmdb",
+            "ERROR",
+        );
+        return;
+    }
+    let reader: GeoIpReader = match super::get_geoip_reader(args) {
+        Some(reader) => reader,
+        None => return,
+    };
+    output::log_with_time(
+        &format!("Using {}", reader.database_types().join(", ")),
+        "INFO",
+    );
+    let targets: Vec<String> = match geo_args.get_many::<String>("target") {
+        Some(targets) => targets.cloned().collect(),
+        None => return,
+    };
+    let mut results: Vec<GeoLookupResult> = Vec::new();
+    for target in targets {
+        let ip_addr: IpAddr = match target.parse::<IpAddr>() {
+            Ok(ip_addr) => ip_addr,
+            Err(_) => match crate::dns::lookup_host_name(&target) {
+                Some(ip_addr) => ip_addr,
+                None => {
+                    output::log_with_time(&format!("Failed to resolve {}", target), "WARN");
+                    continue;
+                }
+            },
+        };
+        if !crate::ip::is_global_addr(&ip_addr) {
+            output::log_with_time(&format!("{} is not a global address", ip_addr), "WARN");
+        }
+        results.push(GeoLookupResult {
+            geo: reader.lookup(&ip_addr),
+            target: target,
+            ip_addr: ip_addr,
+        });
+    }
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&results).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_geo_results(&results);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&results).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_geo_results(results: &[GeoLookupResult]) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("GeoIP", None, None));
+    for result in results {
+        let label: String = if result.target == result.ip_addr.to_string() {
+            result.target.clone()
+        } else {
+            format!("{} ({})", result.target, result.ip_addr)
+        };
+        let mut target_tree = Tree::new(node_label(&label, None, None));
+        match &result.geo {
+            Some(geo) => {
+                let values: [(&str, &String); 3] = [
+                    ("Country", &geo.country),
+                    ("Country Code", &geo.country_code),
+                    ("City", &geo.city),
+                ];
+                for (label, value) in values {
+                    if !value.is_empty() {
+                        target_tree.push(node_label(label, Some(value), None));
+                    }
+                }
+                if let Some(as_label) = geo.as_label() {
+                    target_tree.push(node_label("ASN", Some(&as_label), None));
+                }
+            }
+            None => target_tree.push(node_label("No record", None, None)),
+        }
+        tree.push(target_tree);
+    }
+    println!("{}", tree);
+}
@@ -0,0 +1,60 @@
+use crate::output;
+use clap::ArgMatches;
+use netdev::Interface;
+use std::net::SocketAddr;
+
+pub fn handle_grpc(args: &ArgMatches) {
+    let grpc_args = match args.subcommand_matches("grpc") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let listen_addr: SocketAddr = match grpc_args.get_one::<SocketAddr>("listen") {
+        Some(listen_addr) => *listen_addr,
+        None => crate::config::DEFAULT_GRPC_LISTEN_ADDR.parse().unwrap(),
+    };
+    let interface: Interface = match args.get_one::<String>("interface") {
+        Some(if_name) => match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => {
+                output::log_with_time(&format!("Interface not found: {}", if_name), "ERROR");
+                return;
+            }
+        },
+        None => match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        },
+    };
+    serve(listen_addr, interface);
+}
+
+#[cfg(feature = "grpc")]
+fn serve(listen_addr: SocketAddr, interface: Interface) {
+    if !listen_addr.ip().is_loopback() {
+        output::log_with_time(
+            "Listening on a non-loopback address. Anyone who can reach it can run scans",
+            "WARN",
+        );
+    }
+    output::log_with_time(
+        &format!(
+            "Serving gRPC on {} (interface {})",
+            listen_addr, interface.name
+        ),
+        "INFO",
+    );
+    if let Err(e) = crate::grpc::serve(listen_addr, interface) {
+        output::log_with_time(&e, "ERROR");
+    }
+}
+
+#[cfg(not(feature = "grpc"))]
+fn serve(_listen_addr: SocketAddr, _interface: Interface) {
+    output::log_with_time(
+        "Built without gRPC support. Rebuild with: cargo build --release --features grpc",
+        "ERROR",
+    );
+}
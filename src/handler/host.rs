@@ -1,17 +1,18 @@
 use crate::host::Host;
-use crate::json::host::HostScanResult;
+use crate::json::host::{HostScanResult, NetworkSummary};
 use crate::scan::result::ScanResult;
-use crate::scan::scanner::HostScanner;
-use crate::scan::setting::{HostScanSetting, HostScanType};
+use crate::scan::scanner::{HostScanner, PortScanner};
+use crate::scan::setting::{HostScanSetting, HostScanType, PortScanSetting, PortScanType};
 use crate::util::tree::node_label;
 use clap::ArgMatches;
 use indicatif::{ProgressBar, ProgressDrawTarget};
-use ipnet::Ipv4Net;
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
 use netdev::Interface;
-use std::collections::HashMap;
-use std::net::{IpAddr, Ipv4Addr};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 use termtree::Tree;
@@ -19,14 +20,65 @@ use termtree::Tree;
 use crate::output;
 
 pub fn handle_hostscan(args: &ArgMatches) {
+    if let Some(host_args) = args.subcommand_matches("host") {
+        if host_args.contains_id("watch") {
+            super::watch::run_watch(args, "host", host_args, || {
+                scan_hosts(args).map(|result| result.hosts)
+            });
+            return;
+        }
+    }
+    let result: HostScanResult = match scan_hosts(args) {
+        Some(result) => result,
+        None => return,
+    };
+    // Print results
+    if !super::print_masscan(args, &result.hosts) {
+        if args.get_flag("json") {
+            let json_result = serde_json::to_string_pretty(&result).unwrap();
+            println!("{}", json_result);
+        } else {
+            show_hostscan_result(&result);
+        }
+    }
+    for conflict in &result.conflicts {
+        output::log_alert(&conflict.message());
+    }
+    output::log_with_time("Scan completed", "INFO");
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+    super::export_hosts(args, &result.hosts);
+}
+
+/// Run host scan and the optional port scan of the hosts found up
+pub fn scan_hosts(args: &ArgMatches) -> Option<HostScanResult> {
     output::log_with_time("Initiating host scan...", "INFO");
     let host_args = match args.subcommand_matches("host") {
         Some(matches) => matches,
-        None => return,
+        None => return None,
     };
-    let target: String = match host_args.get_one::<String>("target") {
-        Some(target) => target.to_owned(),
-        None => return,
+    let target_args: Vec<String> = match host_args.get_many::<String>("target") {
+        Some(targets) => targets.cloned().collect(),
+        None => Vec::new(),
+    };
+    let input_file: Option<PathBuf> = host_args.get_one::<PathBuf>("input_file").cloned();
+    let auto_mode: bool = match host_args.get_one::<String>("protocol") {
+        Some(protocol) => protocol.eq_ignore_ascii_case("auto"),
+        None => false,
     };
     let scan_type: HostScanType = match host_args.get_one::<String>("protocol") {
         Some(protocol) => HostScanType::from_str(protocol),
@@ -36,89 +88,217 @@ pub fn handle_hostscan(args: &ArgMatches) {
         Some(timeout) => Duration::from_millis(*timeout),
         None => Duration::from_millis(10000),
     };
-    let port: u16 = match host_args.get_one::<u16>("port") {
-        Some(port) => *port,
-        None => 80 as u16,
+    let ports: Vec<u16> = match host_args.get_many::<u16>("port") {
+        Some(ports) => ports.cloned().collect(),
+        None => vec![80],
+    };
+    // Ports to scan on the hosts found up. Default 1000 ports unless specified
+    let then_ports: Vec<u16> = if host_args.get_flag("then_port") {
+        match host_args.get_many::<u16>("port") {
+            Some(ports) => ports.cloned().collect(),
+            None => crate::db::get_default_ports(),
+        }
+    } else {
+        Vec::new()
     };
     let default_waittime: Duration = Duration::from_millis(200);
     let wait_time = match host_args.get_one::<u64>("waittime") {
         Some(wait_time) => Duration::from_millis(*wait_time),
         None => default_waittime,
     };
-    let send_rate = match host_args.get_one::<u64>("rate") {
-        Some(send_rate) => Duration::from_millis(*send_rate),
-        None => Duration::from_millis(0),
+    let retries: u32 = match host_args.get_one::<u32>("retries") {
+        Some(retries) => *retries,
+        None => 0,
     };
-    let target_ips: Vec<IpAddr> = match Ipv4Net::from_str(&target) {
-        Ok(ipv4net) => {
-            // convert hosts to Vec<IpAddr>
-            ipv4net.hosts().map(|x| IpAddr::V4(x)).collect()
-        }
-        Err(_) => {
-            match Ipv4Addr::from_str(&target) {
-                Ok(ip_addr) => Ipv4Net::new(ip_addr, 24)
-                    .unwrap()
-                    .hosts()
-                    .map(|x| IpAddr::V4(x))
-                    .collect(),
-                Err(_) => {
-                    // Check if target is host-list file
-                    match std::fs::read_to_string(&target) {
-                        Ok(hosts) => {
-                            let mut ips: Vec<IpAddr> = Vec::new();
-                            for host in hosts.lines() {
-                                let host = host.trim();
-                                if host.is_empty() {
-                                    continue;
-                                }
-                                match IpAddr::from_str(host) {
-                                    Ok(ip) => ips.push(ip),
-                                    Err(_) => continue,
-                                }
-                            }
-                            ips
-                        }
-                        Err(_) => vec![],
-                    }
+    let mut target_groups: Vec<TargetGroup> = Vec::new();
+    for target in &target_args {
+        // Single IPv4 address is expanded to /24 network unless multiple targets are given
+        match parse_target(target, target_args.len() == 1) {
+            Some(group) => target_groups.push(group),
+            None => {
+                output::log_with_time(
+                    &format!(
+                        "Invalid target: {}. Networks and ranges are limited to {} addresses",
+                        target,
+                        crate::ip::MAX_EXPAND_ADDRS
+                    ),
+                    "ERROR",
+                );
+                return None;
+            }
+        }
+    }
+    if let Some(file_path) = &input_file {
+        match read_host_list(file_path) {
+            Ok(ips) => target_groups.push(TargetGroup {
+                kind: "InputFile",
+                label: file_path.to_string_lossy().to_string(),
+                ips: ips,
+                discovery_net: None,
+            }),
+            Err(e) => {
+                output::log_with_time(
+                    &format!("Failed to read {}: {}", file_path.to_string_lossy(), e),
+                    "ERROR",
+                );
+                return None;
+            }
+        }
+    }
+    // IPv6 networks too large to enumerate. On-link hosts are discovered via all-nodes multicast.
+    let discovery_nets: Vec<Ipv6Net> = target_groups
+        .iter()
+        .filter_map(|group| group.discovery_net)
+        .collect();
+    let mut target_ips: Vec<IpAddr> = Vec::new();
+    let mut known: HashSet<IpAddr> = HashSet::new();
+    for group in &target_groups {
+        for ip in &group.ips {
+            if known.insert(*ip) {
+                target_ips.push(*ip);
+            }
+        }
+    }
+    if discovery_nets.len() > 0 && known.insert(IpAddr::V6(crate::ip::IPV6_ALL_NODES)) {
+        target_ips.push(IpAddr::V6(crate::ip::IPV6_ALL_NODES));
+    }
+    // Remove excluded addresses and networks
+    let mut exclude_nets: Vec<IpNet> = Vec::new();
+    if let Some(excludes) = host_args.get_many::<String>("exclude") {
+        for exclude in excludes {
+            match crate::ip::parse_ip_net(exclude) {
+                Some(ipnet) => exclude_nets.push(ipnet),
+                None => {
+                    output::log_with_time(&format!("Invalid exclude: {}", exclude), "ERROR");
+                    return None;
                 }
             }
         }
-    };
+    }
+    let target_ips: Vec<IpAddr> = target_ips
+        .into_iter()
+        .filter(|ip| !crate::ip::is_in_networks(ip, &exclude_nets))
+        .collect();
+    let target_set: HashSet<IpAddr> = target_ips.iter().cloned().collect();
     // Add scan target
     let mut targets: Vec<Host> = Vec::new();
     for ip in target_ips {
-        let host: Host = Host::new(ip, String::new()).with_ports(vec![port]);
+        let host: Host = Host::new(ip, String::new()).with_ports(ports.clone());
         targets.push(host);
     }
     let interface: Interface = if let Some(if_name) = args.get_one::<String>("interface") {
         match crate::interface::get_interface_by_name(if_name.to_string()) {
             Some(iface) => iface,
-            None => return,
+            None => return None,
         }
     } else {
         match netdev::get_default_interface() {
             Ok(iface) => iface,
-            Err(_) => return,
+            Err(_) => return None,
+        }
+    };
+    let send_rate = match host_args.get_one::<u64>("rate") {
+        Some(send_rate) => Duration::from_millis(*send_rate),
+        None => crate::util::setting::calculate_send_rate(&interface),
+    };
+    // Auto mode combines ARP/NDP (on-link), ICMP echo and TCP SYN
+    let mut scan_types: Vec<HostScanType> = if auto_mode {
+        let mut scan_types: Vec<HostScanType> = Vec::new();
+        if !interface.is_tun() && !interface.is_loopback() {
+            scan_types.push(HostScanType::ArpScan);
         }
+        scan_types.push(HostScanType::IcmpPingScan);
+        scan_types.push(HostScanType::TcpPingScan);
+        scan_types
+    } else {
+        vec![scan_type]
     };
+    if discovery_nets.len() > 0 {
+        if interface.is_tun() || interface.is_loopback() {
+            output::log_with_time(
+                "IPv6 multicast discovery is not supported on tun or loopback interface",
+                "ERROR",
+            );
+            return None;
+        }
+        // Only ICMPv6 echo is answered by all-nodes multicast
+        if auto_mode || !matches!(scan_types[0], HostScanType::IcmpPingScan) {
+            let nets: Vec<String> = discovery_nets.iter().map(|net| net.to_string()).collect();
+            output::log_with_time(
+                &format!(
+                    "{} is too large to scan. Using ICMPv6 multicast discovery instead",
+                    nets.join(",")
+                ),
+                "INFO",
+            );
+            scan_types = vec![HostScanType::IcmpPingScan];
+        }
+    }
+    if !auto_mode {
+        if let HostScanType::ArpScan = scan_types[0] {
+            if interface.is_tun() || interface.is_loopback() {
+                output::log_with_time(
+                    "ARP/NDP scan is not supported on tun or loopback interface",
+                    "ERROR",
+                );
+                return None;
+            }
+        }
+    }
+    if targets.is_empty() {
+        output::log_with_time("No targets to scan", "ERROR");
+        return None;
+    }
     let mut scan_setting = HostScanSetting::default()
         .set_if_index(interface.index)
-        .set_scan_type(scan_type)
+        .set_protocol(scan_types[0].protocol())
+        .set_scan_type(scan_types[0].clone())
         .set_targets(targets)
         .set_timeout(timeout)
         .set_wait_time(wait_time)
         .set_send_rate(send_rate);
     // Print options
-    print_option(&target, &scan_setting, &interface);
+    print_option(
+        &target_groups,
+        &exclude_nets,
+        &scan_types,
+        &scan_setting,
+        retries,
+        &then_ports,
+        &interface,
+    );
     if !host_args.get_flag("random") {
         scan_setting.randomize_ports();
         scan_setting.randomize_hosts();
     }
+    let mut scan_settings: Vec<HostScanSetting> = Vec::new();
+    for scan_type in &scan_types {
+        let mut setting = scan_setting
+            .clone()
+            .set_protocol(scan_type.protocol())
+            .set_scan_type(scan_type.clone());
+        if auto_mode {
+            if let HostScanType::ArpScan = scan_type {
+                // ARP/NDP reaches on-link hosts only
+                setting
+                    .targets
+                    .retain(|host| crate::interface::is_on_link(&interface, &host.ip_addr));
+            }
+        }
+        if !setting.targets.is_empty() {
+            scan_settings.push(setting);
+        }
+    }
     if !crate::app::is_quiet_mode() {
         println!("[Progress]");
     }
     // Display progress with indicatif
-    let bar = ProgressBar::new(scan_setting.targets.len() as u64);
+    let bar = ProgressBar::new(
+        scan_settings
+            .iter()
+            .map(|setting| setting.targets.len() as u64)
+            .sum(),
+    );
     if crate::app::is_quiet_mode() {
         bar.set_draw_target(ProgressDrawTarget::hidden());
     }
@@ -126,74 +306,388 @@ pub fn handle_hostscan(args: &ArgMatches) {
     bar.set_style(output::get_progress_style());
     bar.set_position(0);
     bar.set_message("HostScan");
-    let host_scanner = HostScanner::new(scan_setting);
-    let rx = host_scanner.get_progress_receiver();
-    // Run scan
-    let handle = thread::spawn(move || host_scanner.scan());
-    // Print progress
-    while let Ok(_host) = rx.lock().unwrap().recv() {
-        bar.inc(1);
+    let mut hostscan_result: ScanResult = ScanResult::new();
+    // Hosts confirmed up so far (across all scan types)
+    let found: Arc<Mutex<HashSet<IpAddr>>> = Arc::new(Mutex::new(HashSet::new()));
+    for setting in scan_settings {
+        let label: String = if auto_mode {
+            format!("HostScan {}", setting.scan_type.to_str())
+        } else {
+            String::from("HostScan")
+        };
+        let mut result: ScanResult = run_hostscan(setting.clone(), &bar, &label, &found);
+        // Re-probe hosts that did not answer
+        for retry in 1..=retries {
+            let answered: HashSet<IpAddr> = result.hosts.iter().map(|host| host.ip_addr).collect();
+            let remaining: Vec<Host> = setting
+                .targets
+                .iter()
+                .filter(|target| {
+                    !answered.contains(&target.ip_addr) && !target.ip_addr.is_multicast()
+                })
+                .cloned()
+                .collect();
+            if remaining.is_empty() {
+                break;
+            }
+            bar.inc_length(remaining.len() as u64);
+            let retry_setting = setting.clone().set_targets(remaining);
+            let retry_label = format!("{} (retry {}/{})", label, retry, retries);
+            result.merge(run_hostscan(retry_setting, &bar, &retry_label, &found));
+        }
+        hostscan_result.merge(result);
     }
-    let mut hostscan_result: ScanResult = handle.join().unwrap();
     bar.finish_with_message(format!("HostScan ({:?})", hostscan_result.scan_time));
     if hostscan_result.hosts.len() == 0 {
         output::log_with_time("No results found", "INFO");
-        return;
+        return None;
+    }
+    if discovery_nets.len() > 0 {
+        // Multicast responders may be outside the target networks
+        hostscan_result.hosts.retain(|host| {
+            if target_set.contains(&host.ip_addr) {
+                return true;
+            }
+            match host.ip_addr {
+                IpAddr::V6(ipv6_addr) => {
+                    discovery_nets.iter().any(|net| net.contains(&ipv6_addr))
+                        && !crate::ip::is_in_networks(&host.ip_addr, &exclude_nets)
+                }
+                IpAddr::V4(_) => false,
+            }
+        });
+        if hostscan_result.hosts.len() == 0 {
+            output::log_with_time("No results found", "INFO");
+            return None;
+        }
+    }
+    if host_args.get_flag("resolve") {
+        output::log_with_time("Resolving host names...", "INFO");
+        // Only detected hosts are resolved, with a bounded number of concurrent lookups
+        let ips: Vec<IpAddr> = hostscan_result
+            .hosts
+            .iter()
+            .filter(|host| host.hostname.is_empty())
+            .map(|host| host.ip_addr)
+            .collect();
+        let dns_map: HashMap<IpAddr, String> = crate::dns::lookup_ips(ips);
+        for host in &mut hostscan_result.hosts {
+            if let Some(hostname) = dns_map.get(&host.ip_addr) {
+                if !hostname.is_empty() {
+                    host.hostname = hostname.clone();
+                }
+            }
+        }
     }
     hostscan_result.sort_ports();
     hostscan_result.sort_hosts();
     let os_family_map: HashMap<IpAddr, String> =
         crate::db::get_fingerprint_map(&hostscan_result.fingerprints);
+    let os_ttl_map: HashMap<u8, String> = crate::db::get_os_ttl_map();
     for host in &mut hostscan_result.hosts {
         host.os_family = os_family_map
             .get(&host.ip_addr)
             .unwrap_or(&String::new())
             .to_string();
+        host.os_hint = crate::db::get_os_hint_by_ttl(&os_ttl_map, host.ttl);
     }
-    let result: HostScanResult = HostScanResult::from_scan_result(&hostscan_result);
-    // Print results
-    if args.get_flag("json") {
-        let json_result = serde_json::to_string_pretty(&result).unwrap();
-        println!("{}", json_result);
-    } else {
-        show_hostscan_result(&result);
+    // Set vendor name from the bundled OUI database
+    let oui_map: HashMap<String, String> = crate::db::get_oui_detail_map();
+    for host in &mut hostscan_result.hosts {
+        if !crate::ip::is_global_addr(&host.ip_addr) {
+            host.vendor_name = crate::db::get_vendor_name(&oui_map, &host.mac_addr);
+        }
     }
-    output::log_with_time("Scan completed", "INFO");
-    match args.get_one::<PathBuf>("save") {
-        Some(file_path) => {
-            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
-                Ok(_) => {
-                    output::log_with_time(
-                        &format!("Saved to {}", file_path.to_string_lossy()),
-                        "INFO",
-                    );
+    // Set geolocation from the GeoIP database
+    if let Some(geoip_reader) = super::get_geoip_reader(args) {
+        for host in &mut hostscan_result.hosts {
+            host.geo = geoip_reader.lookup(&host.ip_addr);
+        }
+    }
+    // Port scan the hosts found up
+    let mut port_scan_time: Duration = Duration::from_millis(0);
+    if then_ports.len() > 0 {
+        let port_targets: Vec<Host> = hostscan_result
+            .hosts
+            .iter()
+            .map(|host| {
+                Host::new(host.ip_addr, host.hostname.clone()).with_ports(then_ports.clone())
+            })
+            .collect();
+        let mut port_setting = PortScanSetting::default()
+            .set_if_index(interface.index)
+            .set_scan_type(PortScanType::TcpSynScan)
+            .set_targets(port_targets)
+            .set_timeout(timeout)
+            .set_wait_time(wait_time)
+            .set_send_rate(send_rate);
+        if !host_args.get_flag("random") {
+            port_setting.randomize_ports();
+            port_setting.randomize_hosts();
+        }
+        let portscan_result: ScanResult = run_portscan(port_setting);
+        port_scan_time = portscan_result.scan_time;
+        let tcp_map: HashMap<u16, String> = crate::db::get_tcp_map();
+        for host in &mut hostscan_result.hosts {
+            host.ports = match portscan_result.get_host(host.ip_addr) {
+                Some(h) => h.get_open_ports(),
+                None => Vec::new(),
+            };
+            host.ports.sort_by_key(|port| port.number);
+            for port in &mut host.ports {
+                port.service_name = tcp_map.get(&port.number).cloned().unwrap_or_default();
+            }
+        }
+    }
+    // Ports and tags seen from the internet
+    super::enrich_hosts(args, &mut hostscan_result.hosts, Some(&then_ports));
+    let mut result: HostScanResult = HostScanResult::from_scan_result(&hostscan_result);
+    result.port_scan_time = port_scan_time;
+    // Per-network summary
+    for group in &target_groups {
+        let ips: HashSet<IpAddr> = group
+            .ips
+            .iter()
+            .filter(|ip| !crate::ip::is_in_networks(ip, &exclude_nets))
+            .cloned()
+            .collect();
+        let hosts_up: usize = hostscan_result
+            .hosts
+            .iter()
+            .filter(|host| {
+                ips.contains(&host.ip_addr)
+                    || match (&group.discovery_net, host.ip_addr) {
+                        (Some(net), IpAddr::V6(ipv6_addr)) => net.contains(&ipv6_addr),
+                        _ => false,
+                    }
+            })
+            .count();
+        result.networks.push(NetworkSummary {
+            network: group.label.clone(),
+            targets: ips.len(),
+            hosts_up: hosts_up,
+        });
+    }
+    Some(result)
+}
+
+/// Run host scan with progress. Hosts are printed as they are confirmed.
+pub(crate) fn run_hostscan(
+    setting: HostScanSetting,
+    bar: &ProgressBar,
+    label: &str,
+    found: &Arc<Mutex<HashSet<IpAddr>>>,
+) -> ScanResult {
+    bar.set_message(format!("{} - {} found", label, found.lock().unwrap().len()));
+    let host_scanner = HostScanner::new(setting);
+    let rx = host_scanner.get_progress_receiver();
+    let found_rx = host_scanner.get_found_receiver();
+    // Print hosts as they are confirmed
+    let found_bar = bar.clone();
+    let found_hosts = Arc::clone(found);
+    let found_label: String = label.to_string();
+    let found_handle = thread::spawn(move || {
+        while let Ok(host) = found_rx.lock().unwrap().recv() {
+            let mut found_hosts = found_hosts.lock().unwrap();
+            if !found_hosts.insert(host.ip_addr) {
+                continue;
+            }
+            let protocols: Vec<&str> = host.discovered_by.iter().map(|p| p.to_str()).collect();
+            found_bar.println(format!(
+                "[{}] [INFO] Found {} ({})",
+                crate::sys::time::get_systime(),
+                host.ip_addr,
+                protocols.join(", ")
+            ));
+            found_bar.set_message(format!("{} - {} found", found_label, found_hosts.len()));
+        }
+    });
+    // Run scan
+    let handle = thread::spawn(move || host_scanner.scan());
+    // Print progress
+    while let Ok(_host) = rx.lock().unwrap().recv() {
+        bar.inc(1);
+    }
+    let result: ScanResult = handle.join().unwrap();
+    let _ = found_handle.join();
+    result
+}
+
+/// Run port scan on the hosts found up with progress
+pub(crate) fn run_portscan(setting: PortScanSetting) -> ScanResult {
+    let bar = ProgressBar::new(
+        setting
+            .targets
+            .iter()
+            .map(|target| target.ports.len() as u64)
+            .sum(),
+    );
+    if crate::app::is_quiet_mode() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar.set_style(output::get_progress_style());
+    bar.set_position(0);
+    bar.set_message(format!("PortScan - {} host(s)", setting.targets.len()));
+    let port_scanner = PortScanner::new(setting);
+    let rx = port_scanner.get_progress_receiver();
+    // Run port scan
+    let handle = thread::spawn(move || port_scanner.scan());
+    // Print port scan progress
+    while let Ok(_socket_addr) = rx.lock().unwrap().recv() {
+        bar.inc(1);
+    }
+    let result: ScanResult = handle.join().unwrap();
+    bar.finish_with_message(format!("PortScan ({:?})", result.scan_time));
+    result
+}
+
+/// Target network or address list given on the command line
+struct TargetGroup {
+    /// Kind of the target for display. Network, Host, Range, List or InputFile
+    kind: &'static str,
+    label: String,
+    ips: Vec<IpAddr>,
+    /// IPv6 network to be discovered via multicast instead of enumerating addresses
+    discovery_net: Option<Ipv6Net>,
+}
+
+/// Parse target. Network (CIDR), IP address, range or host-list file.
+fn parse_target(target: &str, expand_single: bool) -> Option<TargetGroup> {
+    let target: &str = target.trim();
+    match IpNet::from_str(target) {
+        Ok(IpNet::V4(ipv4net)) => Some(TargetGroup {
+            kind: "Network",
+            label: ipv4net.to_string(),
+            ips: crate::ip::expand_ip_net(&IpNet::V4(ipv4net))?,
+            discovery_net: None,
+        }),
+        Ok(IpNet::V6(ipv6net)) => {
+            if ipv6net.prefix_len() < 112 {
+                Some(TargetGroup {
+                    kind: "Network",
+                    label: ipv6net.to_string(),
+                    ips: Vec::new(),
+                    discovery_net: Some(ipv6net),
+                })
+            } else {
+                Some(TargetGroup {
+                    kind: "Network",
+                    label: ipv6net.to_string(),
+                    ips: ipv6net.hosts().map(|x| IpAddr::V6(x)).collect(),
+                    discovery_net: None,
+                })
+            }
+        }
+        Err(_) => match IpAddr::from_str(target) {
+            Ok(IpAddr::V4(ip_addr)) if expand_single => {
+                let ipv4net = Ipv4Net::new(ip_addr, 24).unwrap().trunc();
+                Some(TargetGroup {
+                    kind: "Network",
+                    label: ipv4net.to_string(),
+                    ips: ipv4net.hosts().map(|x| IpAddr::V4(x)).collect(),
+                    discovery_net: None,
+                })
+            }
+            Ok(ip_addr) => Some(TargetGroup {
+                kind: "Host",
+                label: ip_addr.to_string(),
+                ips: vec![ip_addr],
+                discovery_net: None,
+            }),
+            Err(_) => match crate::ip::parse_ip_range(target) {
+                Some(ips) => Some(TargetGroup {
+                    kind: "Range",
+                    label: target.to_string(),
+                    ips: ips,
+                    discovery_net: None,
+                }),
+                None => {
+                    // Check if target is host-list file
+                    match read_host_list(&PathBuf::from(target)) {
+                        Ok(ips) => Some(TargetGroup {
+                            kind: "List",
+                            label: target.to_string(),
+                            ips: ips,
+                            discovery_net: None,
+                        }),
+                        Err(_) => None,
+                    }
                 }
-                Err(e) => {
-                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+            },
+        },
+    }
+}
+
+/// Read host list file.
+///
+/// Each line can be an IP address, network (CIDR) or range. Lines starting with `#` are ignored.
+fn read_host_list(file_path: &PathBuf) -> Result<Vec<IpAddr>, String> {
+    let contents: String = match std::fs::read_to_string(file_path) {
+        Ok(contents) => contents,
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut ips: Vec<IpAddr> = Vec::new();
+    let mut ip_set: HashSet<IpAddr> = HashSet::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match crate::ip::expand_ip_target(line) {
+            Some(expanded) => {
+                for ip in expanded {
+                    if ip_set.insert(ip) {
+                        ips.push(ip);
+                    }
                 }
             }
+            None => {
+                output::log_with_time(&format!("Skipped invalid entry: {}", line), "WARN");
+            }
         }
-        None => {}
     }
+    Ok(ips)
 }
 
-fn print_option(target: &str, setting: &HostScanSetting, interface: &Interface) {
+fn print_option(
+    target_groups: &Vec<TargetGroup>,
+    exclude_nets: &Vec<IpNet>,
+    scan_types: &Vec<HostScanType>,
+    setting: &HostScanSetting,
+    retries: u32,
+    then_ports: &Vec<u16>,
+    interface: &Interface,
+) {
     if crate::app::is_quiet_mode() {
         return;
     }
     println!();
     let mut tree = Tree::new(node_label("HostScan Config", None, None));
     let mut setting_tree = Tree::new(node_label("Settings", None, None));
-    setting_tree.push(node_label(
-        "Protocol",
-        Some(setting.protocol.to_str()),
-        None,
-    ));
+    if scan_types.len() > 1 {
+        setting_tree.push(node_label("Protocol", Some("AUTO"), None));
+    } else {
+        setting_tree.push(node_label(
+            "Protocol",
+            Some(setting.protocol.to_str()),
+            None,
+        ));
+    }
+    let scan_type_names: Vec<&str> = scan_types.iter().map(|t| t.to_str()).collect();
     setting_tree.push(node_label(
         "ScanType",
-        Some(setting.scan_type.to_str()),
+        Some(&scan_type_names.join(",")),
         None,
     ));
+    if scan_types
+        .iter()
+        .any(|t| matches!(t, HostScanType::TcpPingScan))
+    {
+        if let Some(target) = setting.targets.first() {
+            let ports: Vec<String> = target.get_ports().iter().map(|p| p.to_string()).collect();
+            setting_tree.push(node_label("Ports", Some(&ports.join(",")), None));
+        }
+    }
     setting_tree.push(node_label("InterfaceName", Some(&interface.name), None));
     setting_tree.push(node_label(
         "Timeout",
@@ -210,21 +704,47 @@ fn print_option(target: &str, setting: &HostScanSetting, interface: &Interface)
         Some(&format!("{:?}", setting.send_rate)),
         None,
     ));
+    if retries > 0 {
+        setting_tree.push(node_label("Retries", Some(&retries.to_string()), None));
+    }
+    if then_ports.len() > 10 {
+        setting_tree.push(node_label(
+            "Then PortScan",
+            Some(&format!("{} port(s)", then_ports.len())),
+            None,
+        ));
+    } else if then_ports.len() > 0 {
+        let ports: Vec<String> = then_ports.iter().map(|p| p.to_string()).collect();
+        setting_tree.push(node_label("Then PortScan", Some(&ports.join(",")), None));
+    }
     tree.push(setting_tree);
     let mut target_tree = Tree::new(node_label("Target", None, None));
-    match Ipv4Net::from_str(&target) {
-        Ok(ipv4net) => {
-            target_tree.push(node_label("Network", Some(&ipv4net.to_string()), None));
-        }
-        Err(_) => match Ipv4Addr::from_str(&target) {
-            Ok(ip_addr) => {
-                let net = Ipv4Net::new(ip_addr, 24).unwrap();
-                target_tree.push(node_label("Network", Some(&net.to_string()), None));
-            }
-            Err(_) => {
-                target_tree.push(node_label("List", Some(target), None));
-            }
-        },
+    for group in target_groups {
+        target_tree.push(node_label(group.kind, Some(&group.label), None));
+    }
+    if exclude_nets.len() > 0 {
+        let mut exclude_tree = Tree::new(node_label("Exclude", None, None));
+        for net in exclude_nets {
+            exclude_tree.push(node_label(&net.to_string(), None, None));
+        }
+        target_tree.push(exclude_tree);
+    }
+    if setting
+        .targets
+        .iter()
+        .any(|target| target.ip_addr.is_multicast())
+    {
+        target_tree.push(node_label(
+            "Discovery",
+            Some(&format!("ICMPv6 multicast ({})", crate::ip::IPV6_ALL_NODES)),
+            None,
+        ));
+    } else {
+        target_tree.push(node_label(
+            "Hosts",
+            Some(&setting.targets.len().to_string()),
+            None,
+        ));
     }
     tree.push(target_tree);
     println!("{}", tree);
@@ -234,33 +754,87 @@ fn show_hostscan_result(hostscan_result: &HostScanResult) {
     if !crate::app::is_quiet_mode() {
         println!();
     }
-    let oui_map: HashMap<String, String> = crate::db::get_oui_detail_map();
     let mut tree = Tree::new(node_label("HostScan Result", None, None));
     let mut hosts_tree = Tree::new(node_label("Hosts", None, None));
-    for host in &hostscan_result.hosts {
-        let mut host_tree = Tree::new(node_label(&host.ip_addr.to_string(), None, None));
-        host_tree.push(node_label("Host Name", Some(&host.hostname), None));
-        host_tree.push(node_label("TTL", Some(&host.ttl.to_string()), None));
-        host_tree.push(node_label("OS Family", Some(&host.os_family), None));
-        if !crate::ip::is_global_addr(&host.ip_addr) {
-            let vendor_name = if host.mac_addr.address().len() > 16 {
-                let prefix8 = host.mac_addr.address()[0..8].to_uppercase();
-                oui_map.get(&prefix8).unwrap_or(&String::new()).to_string()
+    let (ipv4_hosts, ipv6_hosts): (Vec<&Host>, Vec<&Host>) = hostscan_result
+        .hosts
+        .iter()
+        .partition(|host| host.ip_addr.is_ipv4());
+    // Group by IP version only when both are present
+    if ipv4_hosts.len() > 0 && ipv6_hosts.len() > 0 {
+        let mut ipv4_tree = Tree::new(node_label("IPv4", None, None));
+        for host in ipv4_hosts {
+            ipv4_tree.push(host_tree(host));
+        }
+        hosts_tree.push(ipv4_tree);
+        let mut ipv6_tree = Tree::new(node_label("IPv6", None, None));
+        for host in ipv6_hosts {
+            ipv6_tree.push(host_tree(host));
+        }
+        hosts_tree.push(ipv6_tree);
+    } else {
+        for host in &hostscan_result.hosts {
+            hosts_tree.push(host_tree(host));
+        }
+    }
+    tree.push(hosts_tree);
+    if hostscan_result.networks.len() > 1 {
+        let mut networks_tree = Tree::new(node_label("Networks", None, None));
+        for network in &hostscan_result.networks {
+            let up: String = if network.targets > 0 {
+                format!("{}/{} up", network.hosts_up, network.targets)
             } else {
-                oui_map
-                    .get(&host.mac_addr.address())
-                    .unwrap_or(&String::new())
-                    .to_string()
+                format!("{} up", network.hosts_up)
             };
-            host_tree.push(node_label(
-                "MAC Address",
-                Some(&host.mac_addr.to_string()),
+            networks_tree.push(node_label(&network.network, Some(&up), None));
+        }
+        tree.push(networks_tree);
+    }
+    println!("{}", tree);
+}
+
+fn host_tree(host: &Host) -> Tree<String> {
+    let mut host_tree = Tree::new(node_label(&host.ip_addr.to_string(), None, None));
+    host_tree.push(node_label("Host Name", Some(&host.hostname), None));
+    host_tree.push(node_label("TTL", Some(&host.ttl.to_string()), None));
+    host_tree.push(node_label("OS Family", Some(&host.os_family), None));
+    host_tree.push(node_label("OS Hint (TTL)", Some(&host.os_hint), None));
+    let protocols: Vec<&str> = host.discovered_by.iter().map(|p| p.to_str()).collect();
+    host_tree.push(node_label(
+        "Discovered By",
+        Some(&protocols.join(", ")),
+        None,
+    ));
+    if !crate::ip::is_global_addr(&host.ip_addr) {
+        host_tree.push(node_label(
+            "MAC Address",
+            Some(&host.mac_addr.to_string()),
+            None,
+        ));
+        host_tree.push(node_label("Vendor Name", Some(&host.vendor_name), None));
+    }
+    if let Some(geo) = &host.geo {
+        let location: String = geo.label();
+        if !location.is_empty() {
+            host_tree.push(node_label("Location", Some(&location), None));
+        }
+        if let Some(as_label) = geo.as_label() {
+            host_tree.push(node_label("ASN", Some(&as_label), None));
+        }
+    }
+    if let Some(external) = &host.external {
+        host_tree.push(super::port::external_tree(external));
+    }
+    if host.ports.len() > 0 {
+        let mut port_tree = Tree::new(node_label("Open Ports", None, None));
+        for port in &host.ports {
+            port_tree.push(node_label(
+                &port.number.to_string(),
+                Some(&port.service_name),
                 None,
             ));
-            host_tree.push(node_label("Vendor Name", Some(&vendor_name), None));
         }
-        hosts_tree.push(host_tree);
+        host_tree.push(port_tree);
     }
-    tree.push(hosts_tree);
-    println!("{}", tree);
+    host_tree
 }
@@ -0,0 +1,205 @@
+use crate::http::probe::{HttpProbeResult, HttpProbeScanResult, HttpProbeSetting};
+use crate::output;
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use termtree::Tree;
+use tokio::runtime::Runtime;
+
+/// Targets from the arguments. An existing file is read as a list, one target per line
+fn read_targets(args: Vec<String>) -> Result<Vec<String>, String> {
+    let mut targets: Vec<String> = Vec::new();
+    for arg in args {
+        if Path::new(&arg).is_file() {
+            let text: String = std::fs::read_to_string(&arg)
+                .map_err(|e| format!("Failed to read {}: {}", arg, e))?;
+            targets.extend(
+                text.lines()
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.to_string()),
+            );
+        } else {
+            targets.push(arg);
+        }
+    }
+    targets.dedup();
+    Ok(targets)
+}
+
+pub fn handle_http_probe(args: &ArgMatches) {
+    let http_args = match args.subcommand_matches("http") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let targets: Vec<String> = match http_args.get_many::<String>("target") {
+        Some(targets) => match read_targets(targets.cloned().collect()) {
+            Ok(targets) => targets,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        },
+        None => return,
+    };
+    if targets.is_empty() {
+        output::log_with_time("No target specified", "ERROR");
+        return;
+    }
+    let concurrency: usize = match http_args.get_one::<usize>("concurrency") {
+        Some(concurrency) => *concurrency,
+        None => crate::config::DEFAULT_HTTP_PROBE_CONCURRENCY,
+    };
+    if concurrency == 0 {
+        output::log_with_time("Concurrency must be at least 1", "ERROR");
+        return;
+    }
+    let timeout: Duration = match http_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(crate::config::DEFAULT_HTTP_PROBE_TIMEOUT_MILLIS),
+    };
+    let setting = HttpProbeSetting {
+        concurrency: concurrency,
+        timeout: timeout,
+        max_redirects: if http_args.get_flag("no_redirect") {
+            0
+        } else {
+            crate::config::DEFAULT_HTTP_MAX_REDIRECTS
+        },
+    };
+    output::log_with_time("Initiating HTTP probe...", "INFO");
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("HTTP Probe Config", None, None));
+        tree.push(node_label(
+            "Targets",
+            Some(&targets.len().to_string()),
+            None,
+        ));
+        tree.push(node_label(
+            "Concurrent limit",
+            Some(&concurrency.to_string()),
+            None,
+        ));
+        tree.push(node_label(
+            "Max redirects",
+            Some(&setting.max_redirects.to_string()),
+            None,
+        ));
+        tree.push(node_label("Timeout", Some(&format!("{:?}", timeout)), None));
+        println!("{}", tree);
+        println!("[Progress]");
+    }
+    let bar = ProgressBar::new(targets.len() as u64);
+    if crate::app::is_quiet_mode() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar.enable_steady_tick(120);
+    bar.set_style(output::get_progress_style());
+    bar.set_position(0);
+    bar.set_message("HttpProbe");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let ptx = std::sync::Arc::new(std::sync::Mutex::new(tx));
+    let rt = Runtime::new().unwrap();
+    let handle = thread::spawn(move || {
+        rt.block_on(crate::http::probe::probe_targets(targets, setting, ptx))
+    });
+    while let Ok(_target) = rx.recv() {
+        bar.inc(1);
+    }
+    bar.finish_with_message("HttpProbe");
+    let result: HttpProbeScanResult = handle.join().unwrap();
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_http_probe_result(&result);
+    }
+    let responded: usize = result.results.iter().filter(|r| r.status.is_some()).count();
+    output::log_with_time(
+        &format!(
+            "{} of {} targets responded in {:?}",
+            responded,
+            result.results.len(),
+            result.scan_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn probe_tree(result: &HttpProbeResult) -> Tree<String> {
+    let mut tree = Tree::new(node_label(&result.url, None, None));
+    let status: u16 = match result.status {
+        Some(status) => status,
+        None => {
+            tree.push(node_label(
+                "Error",
+                Some(result.error.as_deref().unwrap_or_default()),
+                None,
+            ));
+            return tree;
+        }
+    };
+    tree.push(node_label("Status", Some(&status.to_string()), None));
+    if !result.redirects.is_empty() {
+        let mut redirect_tree = Tree::new(node_label("Redirects", None, None));
+        for redirect in &result.redirects {
+            redirect_tree.push(node_label(
+                &redirect.url,
+                Some(&redirect.status.to_string()),
+                Some(" ->"),
+            ));
+        }
+        tree.push(redirect_tree);
+    }
+    if !result.title.is_empty() {
+        tree.push(node_label("Title", Some(&result.title), None));
+    }
+    if !result.server.is_empty() {
+        tree.push(node_label("Server", Some(&result.server), None));
+    }
+    if !result.content_type.is_empty() {
+        tree.push(node_label("Content-Type", Some(&result.content_type), None));
+    }
+    tree.push(node_label(
+        "Content-Length",
+        Some(&result.content_length.to_string()),
+        None,
+    ));
+    tree.push(node_label(
+        "Response Time",
+        Some(&format!("{:?}", result.response_time)),
+        None,
+    ));
+    tree
+}
+
+fn show_http_probe_result(result: &HttpProbeScanResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("HTTP Probe Result", None, None));
+    for probe_result in &result.results {
+        tree.push(probe_tree(probe_result));
+    }
+    println!("{}", tree);
+}
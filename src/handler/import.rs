@@ -0,0 +1,142 @@
+use crate::host::Host;
+use crate::json::host::HostScanResult;
+use crate::nmap::NmapRun;
+use crate::output;
+use crate::scan::result::ScanStatus;
+use crate::schedule::HistoryRecord;
+use crate::serve::JobStatus;
+use chrono::{DateTime, Local, TimeZone};
+use clap::ArgMatches;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub fn handle_import(args: &ArgMatches) {
+    let import_args = match args.subcommand_matches("import") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let file_path: &PathBuf = import_args.get_one::<PathBuf>("file").unwrap();
+    let name: &String = import_args.get_one::<String>("name").unwrap();
+    if let Err(e) = crate::schedule::validate_name(name) {
+        output::log_with_time(&e, "ERROR");
+        return;
+    }
+    let history_dir: PathBuf = match import_args
+        .get_one::<PathBuf>("history")
+        .cloned()
+        .or(crate::schedule::default_history_dir())
+    {
+        Some(history_dir) => history_dir,
+        None => {
+            output::log_with_time("Home directory not found. Specify --history", "ERROR");
+            return;
+        }
+    };
+    let run: NmapRun = match std::fs::read_to_string(file_path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| crate::nmap::parse(&text))
+    {
+        Ok(run) => run,
+        Err(e) => {
+            output::log_with_time(
+                &format!("Failed to read {}: {}", file_path.to_string_lossy(), e),
+                "ERROR",
+            );
+            return;
+        }
+    };
+    output::log_with_time(
+        &format!(
+            "Loaded {} hosts and {} ports from {}",
+            run.hosts.len(),
+            run.hosts.iter().map(|h| h.ports.len()).sum::<usize>(),
+            file_path.to_string_lossy()
+        ),
+        "INFO",
+    );
+    // Keep the time of the nmap scan, so imports sort with scheduled runs
+    let started_at: DateTime<Local> = run
+        .start_time
+        .and_then(|secs| Local.timestamp_opt(secs, 0).single())
+        .unwrap_or(Local::now());
+    let finished_at: DateTime<Local> = run
+        .end_time
+        .and_then(|secs| Local.timestamp_opt(secs, 0).single())
+        .unwrap_or(started_at);
+    let mut hosts: Vec<Host> = run.hosts;
+    if let Some(geoip_reader) = super::get_geoip_reader(args) {
+        for host in &mut hosts {
+            host.geo = geoip_reader.lookup(&host.ip_addr);
+        }
+    }
+    if let Some(mut cve_lookup) = super::get_cve_lookup(args) {
+        for host in &mut hosts {
+            super::set_cves(&mut cve_lookup, &mut host.ports);
+        }
+    }
+    super::enrich_hosts(args, &mut hosts, None);
+    let mut result: HostScanResult = HostScanResult::new();
+    result.hosts = hosts;
+    result.scan_time = run
+        .elapsed
+        .map(|secs| Duration::from_secs_f64(secs.max(0.0)))
+        .unwrap_or_default();
+    result.scan_status = ScanStatus::Done;
+    let result_path: PathBuf =
+        crate::schedule::history_result_path(&history_dir, name, &started_at);
+    let saved: Result<(), String> = std::fs::create_dir_all(history_dir.join(name))
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            crate::fs::save_text(&result_path, serde_json::to_string_pretty(&result).unwrap())
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = saved {
+        output::log_with_time(
+            &format!("Failed to save {}: {}", result_path.display(), e),
+            "ERROR",
+        );
+        return;
+    }
+    let record = HistoryRecord {
+        schedule: name.clone(),
+        command: "import".to_string(),
+        target: file_path.to_string_lossy().to_string(),
+        started_at: started_at,
+        finished_at: finished_at,
+        status: JobStatus::Done,
+        result_file: Some(result_path.clone()),
+        error: None,
+    };
+    if let Err(e) = crate::schedule::append_history(&history_dir, &record) {
+        output::log_with_time(&format!("Failed to write history: {}", e), "ERROR");
+        return;
+    }
+    output::log_with_time(&format!("Imported to {}", result_path.display()), "INFO");
+    if !super::print_masscan(args, &result.hosts) {
+        if args.get_flag("json") {
+            let json_result = serde_json::to_string_pretty(&result).unwrap();
+            println!("{}", json_result);
+        } else {
+            for host in &result.hosts {
+                super::port::show_portscan_result(host);
+            }
+        }
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+    super::export_hosts(args, &result.hosts);
+}
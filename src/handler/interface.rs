@@ -1,29 +1,96 @@
+use std::io::Write;
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::app::StopGuard;
+use crate::interface::stats::{format_bytes, InterfaceStats, InterfaceTraffic};
+use crate::json::interface::{GatewayCheck, InterfaceInfo};
 use crate::output;
+use crate::protocol::Protocol;
 use crate::util::tree::node_label;
 use clap::ArgMatches;
 use netdev::mac::MacAddr;
 use netdev::Interface;
 use termtree::Tree;
 
+/// Find interface by name, friendly name, index or assigned IP address
+fn find_interface(key: &str) -> Option<Interface> {
+    let interfaces: Vec<Interface> = netdev::get_interfaces();
+    if let Some(iface) = interfaces
+        .iter()
+        .find(|iface| iface.name == key || iface.friendly_name.as_deref() == Some(key))
+    {
+        return Some(iface.clone());
+    }
+    if let Ok(index) = key.parse::<u32>() {
+        return interfaces.into_iter().find(|iface| iface.index == index);
+    }
+    if let Ok(ip_addr) = key.parse::<IpAddr>() {
+        return interfaces.into_iter().find(|iface| {
+            iface
+                .ipv4
+                .iter()
+                .any(|ipv4| IpAddr::V4(ipv4.addr) == ip_addr)
+                || iface
+                    .ipv6
+                    .iter()
+                    .any(|ipv6| IpAddr::V6(ipv6.addr) == ip_addr)
+        });
+    }
+    None
+}
+
 pub fn show_default_interface(args: &ArgMatches) {
-    let iface: Interface = match netdev::get_default_interface() {
-        Ok(interface) => interface,
-        Err(_) => {
-            println!("Failed to get default interface");
-            return;
-        }
+    let if_args = args.subcommand_matches("interface");
+    let name: Option<&String> = if_args.and_then(|matches| matches.get_one::<String>("name"));
+    let iface: Interface = match name {
+        Some(name) => match find_interface(name) {
+            Some(interface) => interface,
+            None => {
+                println!("Interface not found: {}", name);
+                return;
+            }
+        },
+        None => match netdev::get_default_interface() {
+            Ok(interface) => interface,
+            Err(_) => {
+                println!("Failed to get default interface");
+                return;
+            }
+        },
     };
-    if args.get_flag("json") {
-        let json_result = serde_json::to_string_pretty(&iface).unwrap();
+    let default_index: Option<u32> = netdev::get_default_interface()
+        .ok()
+        .map(|iface| iface.index);
+    let mut info: InterfaceInfo = InterfaceInfo::from_interface(&iface, default_index);
+    let only: Option<&String> = if_args.and_then(|matches| matches.get_one::<String>("only"));
+    let brief: bool = if_args.map_or(false, |matches| matches.get_flag("brief"));
+    let no_probe: bool = if_args.map_or(false, |matches| matches.get_flag("no_probe"));
+    // Quick LAN check on the default interface
+    if name.is_none() && only.is_none() && !brief && !no_probe {
+        info.gateway_check = check_gateway(&iface);
+    }
+    if let Some(field) = only {
+        // Plain values for scripts
+        for value in field_values(&info, field) {
+            println!("{}", value);
+        }
+    } else if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&info).unwrap();
         println!("{}", json_result);
+    } else if brief {
+        println!("{}", brief_line(&info));
     } else {
         show_interface_tree(&iface);
+        if let Some(gateway_check) = &info.gateway_check {
+            show_gateway_check(gateway_check);
+        }
     }
     match args.get_one::<PathBuf>("save") {
         Some(file_path) => {
-            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&iface).unwrap()) {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&info).unwrap()) {
                 Ok(_) => {
                     output::log_with_time(
                         &format!("Saved to {}", file_path.to_string_lossy()),
@@ -40,19 +107,36 @@ pub fn show_default_interface(args: &ArgMatches) {
 }
 
 pub fn show_interfaces(args: &ArgMatches) {
+    if let Some(if_args) = args.subcommand_matches("interfaces") {
+        if if_args.get_flag("stats") {
+            show_interface_stats(args, if_args);
+            return;
+        }
+    }
     let interfaces: Vec<Interface> = netdev::get_interfaces();
+    let default_index: Option<u32> = netdev::get_default_interface()
+        .ok()
+        .map(|iface| iface.index);
+    let infos: Vec<InterfaceInfo> = interfaces
+        .iter()
+        .map(|iface| InterfaceInfo::from_interface(iface, default_index))
+        .collect();
+    let brief: bool = args
+        .subcommand_matches("interfaces")
+        .map_or(false, |matches| matches.get_flag("brief"));
     if args.get_flag("json") {
-        let json_result = serde_json::to_string_pretty(&interfaces).unwrap();
+        let json_result = serde_json::to_string_pretty(&infos).unwrap();
         println!("{}", json_result);
+    } else if brief {
+        for info in &infos {
+            println!("{}", brief_line(info));
+        }
     } else {
         show_interfaces_tree(&interfaces);
     }
     match args.get_one::<PathBuf>("save") {
         Some(file_path) => {
-            match crate::fs::save_text(
-                file_path,
-                serde_json::to_string_pretty(&interfaces).unwrap(),
-            ) {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&infos).unwrap()) {
                 Ok(_) => {
                     output::log_with_time(
                         &format!("Saved to {}", file_path.to_string_lossy()),
@@ -68,6 +152,263 @@ pub fn show_interfaces(args: &ArgMatches) {
     }
 }
 
+/// Sample counters twice over the interval and show rates.
+/// With --watch, keep sampling and redraw the table in place
+fn show_interface_stats(args: &ArgMatches, if_args: &ArgMatches) {
+    let interval: Duration = match if_args.get_one::<u64>("interval") {
+        Some(interval) => Duration::from_secs((*interval).max(1)),
+        None => Duration::from_secs(1),
+    };
+    let watch: bool = if_args.get_flag("watch");
+    // Interface given by -i only
+    let if_name: Option<&String> = args.get_one::<String>("interface");
+    let sample = || -> Result<Vec<InterfaceStats>, String> {
+        let mut stats_list: Vec<InterfaceStats> = crate::interface::stats::get_interface_stats()?;
+        if let Some(if_name) = if_name {
+            stats_list.retain(|stats| &stats.name == if_name);
+        }
+        Ok(stats_list)
+    };
+    let stop_guard: Option<StopGuard> = if watch {
+        Some(crate::app::handle_stop())
+    } else {
+        None
+    };
+    let mut prev: Vec<InterfaceStats> = match sample() {
+        Ok(stats_list) => stats_list,
+        Err(e) => {
+            output::log_with_time(&format!("Failed to get interface stats: {}", e), "ERROR");
+            return;
+        }
+    };
+    let mut prev_time: Instant = Instant::now();
+    loop {
+        thread::sleep(interval);
+        let curr: Vec<InterfaceStats> = match sample() {
+            Ok(stats_list) => stats_list,
+            Err(e) => {
+                output::log_with_time(&format!("Failed to get interface stats: {}", e), "ERROR");
+                return;
+            }
+        };
+        let elapsed: Duration = prev_time.elapsed();
+        prev_time = Instant::now();
+        let traffic: Vec<InterfaceTraffic> = curr
+            .iter()
+            .map(|stats| {
+                let prev_stats: InterfaceStats = prev
+                    .iter()
+                    .find(|prev_stats| prev_stats.name == stats.name)
+                    .cloned()
+                    .unwrap_or(stats.clone());
+                InterfaceTraffic::from_samples(&prev_stats, stats, elapsed)
+            })
+            .collect();
+        prev = curr;
+        if !watch {
+            if args.get_flag("json") {
+                let json_result = serde_json::to_string_pretty(&traffic).unwrap();
+                println!("{}", json_result);
+            } else {
+                show_traffic_table(&traffic);
+            }
+            match args.get_one::<PathBuf>("save") {
+                Some(file_path) => {
+                    match crate::fs::save_text(
+                        file_path,
+                        serde_json::to_string_pretty(&traffic).unwrap(),
+                    ) {
+                        Ok(_) => {
+                            output::log_with_time(
+                                &format!("Saved to {}", file_path.to_string_lossy()),
+                                "INFO",
+                            );
+                        }
+                        Err(e) => {
+                            output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                        }
+                    }
+                }
+                None => {}
+            }
+            return;
+        }
+        if args.get_flag("json") {
+            // One sample per line
+            println!("{}", serde_json::to_string(&traffic).unwrap());
+        } else {
+            // Clear screen and move the cursor home
+            print!("\x1b[2J\x1b[H");
+            println!(
+                "[{}] Interface traffic every {:?}. Press Ctrl-C to stop",
+                crate::sys::time::get_systime(),
+                interval
+            );
+            show_traffic_table(&traffic);
+        }
+        let _ = std::io::stdout().flush();
+        if stop_guard
+            .as_ref()
+            .map_or(false, |guard| guard.is_stopped())
+        {
+            break;
+        }
+    }
+}
+
+fn show_traffic_table(traffic: &[InterfaceTraffic]) {
+    let name_width: usize = traffic
+        .iter()
+        .map(|t| t.stats.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Interface".len());
+    println!(
+        "{:<name_width$}  {:>11}  {:>11}  {:>8}  {:>8}  {:>10}  {:>10}  {:>11}  {:>11}",
+        "Interface",
+        "RX/s",
+        "TX/s",
+        "RX Pkt/s",
+        "TX Pkt/s",
+        "RX Total",
+        "TX Total",
+        "Err RX/TX",
+        "Drop RX/TX",
+        name_width = name_width
+    );
+    for t in traffic {
+        println!(
+            "{:<name_width$}  {:>11}  {:>11}  {:>8.0}  {:>8.0}  {:>10}  {:>10}  {:>11}  {:>11}",
+            t.stats.name,
+            format!("{}/s", format_bytes(t.rx_rate)),
+            format!("{}/s", format_bytes(t.tx_rate)),
+            t.rx_packet_rate,
+            t.tx_packet_rate,
+            format_bytes(t.stats.rx_bytes as f64),
+            format_bytes(t.stats.tx_bytes as f64),
+            format!("{}/{}", t.stats.rx_errors, t.stats.tx_errors),
+            format!("{}/{}", t.stats.rx_dropped, t.stats.tx_dropped),
+            name_width = name_width
+        );
+    }
+}
+
+/// Resolve the gateway by ARP/NDP and ping it once
+fn check_gateway(iface: &Interface) -> Option<GatewayCheck> {
+    let gateway = iface.gateway.as_ref()?;
+    let ip_addr: IpAddr = match (gateway.ipv4.first(), gateway.ipv6.first()) {
+        (Some(ipv4), _) => IpAddr::V4(*ipv4),
+        (None, Some(ipv6)) => IpAddr::V6(*ipv6),
+        (None, None) => return None,
+    };
+    if !crate::sys::os::is_privileged() {
+        output::log_with_time(
+            "Gateway check requires administrator privileges. Skipped",
+            "WARN",
+        );
+        return None;
+    }
+    output::log_with_time(&format!("Checking gateway {}...", ip_addr), "INFO");
+    let mac_addr: Option<MacAddr> = if iface.is_tun() || iface.is_loopback() {
+        None
+    } else {
+        crate::neighbor::resolve_mac_addr(iface, ip_addr)
+    };
+    let vendor_name: String = match &mac_addr {
+        Some(mac_addr) => crate::db::get_vendor_name(&crate::db::get_oui_detail_map(), mac_addr),
+        None => String::new(),
+    };
+    let rtt: Option<Duration> =
+        match super::ping::oneshot_ping(iface.index, ip_addr, Protocol::ICMP, None) {
+            Ok(ping_result) => ping_result.stat.responses.first().map(|r| r.rtt),
+            Err(_) => None,
+        };
+    Some(GatewayCheck {
+        ip_addr: ip_addr,
+        mac_addr: mac_addr,
+        vendor_name: vendor_name,
+        rtt: rtt,
+    })
+}
+
+fn show_gateway_check(gateway_check: &GatewayCheck) {
+    let mut tree = Tree::new(node_label("Gateway Check", None, None));
+    tree.push(node_label(
+        "IP Address",
+        Some(&gateway_check.ip_addr.to_string()),
+        None,
+    ));
+    let mac: String = match &gateway_check.mac_addr {
+        Some(mac_addr) => mac_addr.address(),
+        None => "No ARP/NDP reply".to_string(),
+    };
+    tree.push(node_label("MAC", Some(&mac), None));
+    if !gateway_check.vendor_name.is_empty() {
+        tree.push(node_label("Vendor", Some(&gateway_check.vendor_name), None));
+    }
+    let rtt: String = match gateway_check.rtt {
+        Some(rtt) => format!("{:?}", rtt),
+        None => "No ICMP reply".to_string(),
+    };
+    tree.push(node_label("RTT", Some(&rtt), None));
+    let status: &str = if gateway_check.is_ok() {
+        "OK"
+    } else if gateway_check.mac_addr.is_some() {
+        // Some gateways drop ICMP echo
+        "Reachable (no ICMP reply)"
+    } else {
+        "Unreachable"
+    };
+    tree.push(node_label("Status", Some(status), None));
+    println!("{}", tree);
+}
+
+/// Values of the field for --only
+fn field_values(info: &InterfaceInfo, field: &str) -> Vec<String> {
+    match field {
+        "name" => vec![info.name.clone()],
+        "index" => vec![info.index.to_string()],
+        "mac" => info.mac_addr.iter().map(|mac| mac.address()).collect(),
+        "ipv4" => info.ipv4.clone(),
+        "ipv6" => info.ipv6.clone(),
+        "mtu" => info.mtu.iter().map(|mtu| mtu.to_string()).collect(),
+        "gateway" => match &info.gateway {
+            Some(gateway) => gateway
+                .ipv4
+                .iter()
+                .map(|ip| ip.to_string())
+                .chain(gateway.ipv6.iter().map(|ip| ip.to_string()))
+                .collect(),
+            None => vec![],
+        },
+        "dns" => info.dns_servers.iter().map(|ip| ip.to_string()).collect(),
+        "flags" => info.flags.clone(),
+        _ => vec![],
+    }
+}
+
+/// name index MAC IPv4,... IPv6,...
+fn brief_line(info: &InterfaceInfo) -> String {
+    let join_or_dash = |values: &Vec<String>| -> String {
+        if values.is_empty() {
+            "-".to_string()
+        } else {
+            values.join(",")
+        }
+    };
+    format!(
+        "{} {} {} {} {}{}",
+        info.name,
+        info.index,
+        info.mac_addr
+            .map(|mac| mac.address())
+            .unwrap_or("-".to_string()),
+        join_or_dash(&info.ipv4),
+        join_or_dash(&info.ipv6),
+        if info.default { " default" } else { "" }
+    )
+}
+
 pub fn show_interface_tree(iface: &Interface) {
     let mut tree = Tree::new(node_label("Interface", None, None));
     tree.push(node_label("Index", Some(&iface.index.to_string()), None));
@@ -84,6 +425,14 @@ pub fn show_interface_tree(iface: &Interface) {
         Some(&iface.mac_addr.unwrap_or(MacAddr::zero()).to_string()),
         None,
     ));
+    if let Some(mtu) = iface.mtu {
+        tree.push(node_label("MTU", Some(&mtu.to_string()), None));
+    }
+    tree.push(node_label(
+        "Flags",
+        Some(&crate::json::interface::interface_flags(iface).join(" ")),
+        None,
+    ));
     let mut ipv4_tree = Tree::new(node_label("IPv4 Addresses", None, None));
     for ipv4 in &iface.ipv4 {
         ipv4_tree.push(node_label(&ipv4.addr.to_string(), None, None));
@@ -140,6 +489,14 @@ pub fn show_interfaces_tree(interfaces: &Vec<Interface>) {
             Some(&iface.mac_addr.unwrap_or(MacAddr::zero()).to_string()),
             None,
         ));
+        if let Some(mtu) = iface.mtu {
+            iface_tree.push(node_label("MTU", Some(&mtu.to_string()), None));
+        }
+        iface_tree.push(node_label(
+            "Flags",
+            Some(&crate::json::interface::interface_flags(iface).join(" ")),
+            None,
+        ));
         let mut ipv4_tree = Tree::new(node_label("IPv4 Addresses", None, None));
         for ipv4 in &iface.ipv4 {
             ipv4_tree.push(node_label(&ipv4.addr.to_string(), None, None));
@@ -1,15 +1,39 @@
+pub mod agent;
+pub mod capture;
 pub mod check;
 pub mod dns;
+pub mod exporter;
+pub mod geo;
+pub mod grpc;
 pub mod host;
+pub mod http;
+pub mod import;
 pub mod interface;
+pub mod myip;
 pub mod neighbor;
+pub mod ntp;
+pub mod passive;
 pub mod ping;
 pub mod port;
+pub mod schedule;
+pub mod serve;
+pub mod snmp;
+pub mod tls;
 pub mod trace;
+pub mod watch;
+pub mod whois;
+pub mod wol;
 
+use crate::cve::CveLookup;
+use crate::db::geoip::GeoIpReader;
 use crate::db::model::OsFamilyFingerprint;
-use crate::host::Host;
+use crate::enrich::{EnrichSource, Enricher, ExternalInfo};
+use crate::export::elasticsearch::ElasticsearchExporter;
+use crate::export::splunk::SplunkHecExporter;
+use crate::export::ExportKind;
+use crate::host::{Host, Port};
 use crate::json::port::PortScanResult;
+use crate::mqtt::MqttPublisher;
 use crate::scan::result::ScanResult;
 use crate::scan::scanner::{PortScanner, ServiceDetector};
 use crate::scan::setting::{PortScanSetting, PortScanType, ServiceProbeSetting};
@@ -158,6 +182,7 @@ pub fn default_probe(target_host: &str, args: &ArgMatches) {
         if let Some(result) = service_result.get(&port.number) {
             port.service_name = result.service_name.clone();
             port.service_version = result.service_detail.clone().unwrap_or(String::new());
+            port.cpes = crate::cve::service_cpes(&port.service_version);
         }
     }
     // OS detection
@@ -175,31 +200,43 @@ pub fn default_probe(target_host: &str, args: &ArgMatches) {
         if let Some(h) = portscan_result.get_host(result.host.ip_addr) {
             if h.mac_addr != MacAddr::zero() {
                 let oui_map: HashMap<String, String> = crate::db::get_oui_detail_map();
-                let vendor_name = if h.mac_addr.address().len() > 16 {
-                    let prefix8 = h.mac_addr.address()[0..8].to_uppercase();
-                    oui_map.get(&prefix8).unwrap_or(&String::new()).to_string()
-                } else {
-                    oui_map
-                        .get(&h.mac_addr.address())
-                        .unwrap_or(&String::new())
-                        .to_string()
-                };
+                let vendor_name = crate::db::get_vendor_name(&oui_map, &h.mac_addr);
                 result.host.mac_addr = h.mac_addr;
                 result.host.vendor_name = vendor_name;
             }
         }
     }
+    // Set geolocation from the GeoIP database
+    if let Some(geoip_reader) = get_geoip_reader(args) {
+        result.host.geo = geoip_reader.lookup(&result.host.ip_addr);
+    }
+    // SMB host information
+    if args.get_flag("smb") {
+        result.host.smb = crate::smb::enumerate_host(
+            result.host.ip_addr,
+            &result.host.get_open_port_numbers(),
+            Duration::from_millis(crate::config::DEFAULT_SMB_TIMEOUT_MILLIS),
+        );
+    }
+    // Known CVEs of the detected CPEs
+    if let Some(mut cve_lookup) = get_cve_lookup(args) {
+        set_cves(&mut cve_lookup, &mut result.host.ports);
+    }
+    // Ports and tags seen from the internet
+    enrich_hosts(args, std::slice::from_mut(&mut result.host), None);
     result.host.ttl = portscan_result.hosts[0].ttl;
     result.port_scan_time = portscan_result.scan_time;
     result.service_detection_time = sd_elapsed_time;
     result.total_scan_time = portscan_result.scan_time + sd_elapsed_time;
     result.scan_status = portscan_result.scan_status;
     // Print results
-    if args.get_flag("json") {
-        let json_result = serde_json::to_string_pretty(&result).unwrap();
-        println!("{}", json_result);
-    } else {
-        port::show_portscan_result(&result.host);
+    if !print_masscan(args, std::slice::from_ref(&result.host)) {
+        if args.get_flag("json") {
+            let json_result = serde_json::to_string_pretty(&result).unwrap();
+            println!("{}", json_result);
+        } else {
+            port::show_portscan_result(&result.host);
+        }
     }
 
     output::log_with_time(
@@ -223,4 +260,275 @@ pub fn default_probe(target_host: &str, args: &ArgMatches) {
         }
         None => {}
     }
+    export_hosts(args, std::slice::from_ref(&result.host));
+}
+
+/// Parse TOS byte in decimal or hex (0x prefix)
+pub fn parse_tos(value: &str) -> Result<u8, String> {
+    let result = match value.strip_prefix("0x").or(value.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => value.parse::<u8>(),
+    };
+    result.map_err(|_| format!("Invalid TOS value: {}. Must be 0-255 or 0x00-0xff", value))
+}
+
+/// Get TOS byte from --tos or --dscp. Returns 0 if neither is specified
+pub fn get_tos(sub_args: &ArgMatches) -> u8 {
+    if let Some(tos) = sub_args.get_one::<u8>("tos") {
+        *tos
+    } else if let Some(dscp) = sub_args.get_one::<u8>("dscp") {
+        *dscp << 2
+    } else {
+        0
+    }
+}
+
+/// Format TOS byte with its DSCP and ECN
+pub fn format_tos(tos: u8) -> String {
+    format!("0x{:02x} (DSCP {}, ECN {})", tos, tos >> 2, tos & 0x03)
+}
+
+/// Open GeoIP database specified by --geoip. Returns None if not specified or failed to open
+pub fn get_geoip_reader(args: &ArgMatches) -> Option<GeoIpReader> {
+    let file_paths: Vec<PathBuf> = args.get_many::<PathBuf>("geoip")?.cloned().collect();
+    match GeoIpReader::open_files(&file_paths) {
+        Ok(reader) => Some(reader),
+        Err(e) => {
+            output::log_with_time(&e, "WARN");
+            None
+        }
+    }
+}
+
+pub fn get_cve_lookup(args: &ArgMatches) -> Option<CveLookup> {
+    let file_paths: Vec<PathBuf> = match args.get_many::<PathBuf>("cve_db") {
+        Some(file_paths) => file_paths.cloned().collect(),
+        None => Vec::new(),
+    };
+    let use_api: bool = args.get_flag("cve_api");
+    if file_paths.is_empty() && !use_api {
+        return None;
+    }
+    let mut cve_lookup: CveLookup = CveLookup::new();
+    if let Err(e) = cve_lookup.load_files(&file_paths) {
+        output::log_with_time(&e, "WARN");
+        if !use_api {
+            return None;
+        }
+    }
+    if use_api {
+        cve_lookup.set_api_timeout(Duration::from_millis(
+            crate::config::DEFAULT_CVE_API_TIMEOUT_MILLIS,
+        ));
+    }
+    Some(cve_lookup)
+}
+
+/// Base topic of --mqtt
+pub fn mqtt_topic(args: &ArgMatches) -> String {
+    args.get_one::<String>("mqtt_topic")
+        .map(|topic| topic.trim_end_matches('/').to_string())
+        .unwrap_or(crate::config::DEFAULT_MQTT_TOPIC.to_string())
+}
+
+/// Publish (topic, payload) pairs to the --mqtt broker
+pub fn send_mqtt(args: &ArgMatches, messages: &[(String, String)]) {
+    let url: &String = match args.get_one::<String>("mqtt") {
+        Some(url) => url,
+        None => return,
+    };
+    if messages.is_empty() {
+        return;
+    }
+    match MqttPublisher::new(url).and_then(|publisher| publisher.publish_all(messages)) {
+        Ok(sent) => output::log_with_time(
+            &format!("Published {} message(s) to {}", sent, mqtt_topic(args)),
+            "INFO",
+        ),
+        Err(e) => output::log_with_time(&format!("MQTT publish failed: {}", e), "ERROR"),
+    }
+}
+
+/// Publish one message per host and per open port if --mqtt is set
+fn publish_mqtt(args: &ArgMatches, hosts: &[Host]) {
+    if !args.contains_id("mqtt") {
+        return;
+    }
+    let topic: String = mqtt_topic(args);
+    let documents = crate::export::scan_documents(hosts);
+    let mut messages: Vec<(String, String)> = Vec::new();
+    for host in &documents.hosts {
+        messages.push((format!("{}/host", topic), host.to_string()));
+    }
+    for port in &documents.ports {
+        messages.push((format!("{}/port", topic), port.to_string()));
+    }
+    send_mqtt(args, &messages);
+}
+
+/// Set what Shodan or Censys observed on public hosts if --enrich is set.
+/// Differences from the scan are logged as alerts. `scanned_ports` defaults to the ports of each host
+pub fn enrich_hosts(args: &ArgMatches, hosts: &mut [Host], scanned_ports: Option<&[u16]>) {
+    let source: EnrichSource = match args.get_one::<String>("enrich") {
+        Some(source) => match EnrichSource::from_str(source) {
+            Some(source) => source,
+            None => {
+                output::log_with_time(&format!("Unsupported enrichment: {}", source), "ERROR");
+                return;
+            }
+        },
+        None => return,
+    };
+    let api_key: String = match args
+        .get_one::<String>("enrich_key")
+        .cloned()
+        .or(Enricher::api_key_from_env(source))
+    {
+        Some(api_key) => api_key,
+        None => {
+            output::log_with_time(
+                &format!(
+                    "--enrich {} requires an API key. Set --enrich-key",
+                    source.name().to_lowercase()
+                ),
+                "ERROR",
+            );
+            return;
+        }
+    };
+    let enricher: Enricher = Enricher::new(source, &api_key);
+    for host in hosts.iter_mut() {
+        if !crate::ip::is_global_addr(&host.ip_addr) {
+            continue;
+        }
+        let mut external: ExternalInfo = match enricher.lookup(&host.ip_addr) {
+            Ok(Some(external)) => external,
+            Ok(None) => continue,
+            Err(e) => {
+                output::log_with_time(
+                    &format!("{} lookup of {} failed: {}", source.name(), host.ip_addr, e),
+                    "WARN",
+                );
+                continue;
+            }
+        };
+        let scanned: Vec<u16> = match scanned_ports {
+            Some(ports) => ports.to_vec(),
+            None => host.get_ports(),
+        };
+        external.set_discrepancies(&scanned, &host.get_open_port_numbers());
+        if !external.missing_locally.is_empty() {
+            output::log_alert(&format!(
+                "{}: {} sees ports {:?} open, not open in this scan",
+                host.ip_addr,
+                source.name(),
+                external.missing_locally
+            ));
+        }
+        if !external.missing_externally.is_empty() {
+            output::log_alert(&format!(
+                "{}: ports {:?} open in this scan, not seen by {}",
+                host.ip_addr,
+                external.missing_externally,
+                source.name()
+            ));
+        }
+        host.external = Some(external);
+    }
+}
+
+/// Print hosts in masscan format if --masscan-output is set. Returns true if printed
+pub fn print_masscan(args: &ArgMatches, hosts: &[Host]) -> bool {
+    let timestamp: i64 = chrono::Local::now().timestamp();
+    match args.get_one::<String>("masscan_output").map(|s| s.as_str()) {
+        Some("list") => println!("{}", crate::masscan::to_list(hosts, timestamp)),
+        Some("json") => println!("{}", crate::masscan::to_json(hosts, timestamp)),
+        _ => return false,
+    }
+    true
+}
+
+/// Send scan results to the --mqtt broker and the --export destination
+pub fn export_hosts(args: &ArgMatches, hosts: &[Host]) {
+    publish_mqtt(args, hosts);
+    let kind: ExportKind = match args.get_one::<String>("export") {
+        Some(kind) => match ExportKind::from_str(kind) {
+            Some(kind) => kind,
+            None => {
+                output::log_with_time(&format!("Unsupported export: {}", kind), "ERROR");
+                return;
+            }
+        },
+        None => return,
+    };
+    let result: Result<usize, String> = match kind {
+        ExportKind::Elasticsearch => {
+            let url: &String = match args.get_one::<String>("es_url") {
+                Some(url) => url,
+                None => {
+                    output::log_with_time("--export elasticsearch requires --es-url", "ERROR");
+                    return;
+                }
+            };
+            let index_prefix: &str = match args.get_one::<String>("es_index") {
+                Some(index_prefix) => index_prefix,
+                None => crate::config::DEFAULT_ES_INDEX_PREFIX,
+            };
+            ElasticsearchExporter::new(url, index_prefix)
+                .export(&crate::export::scan_documents(hosts))
+        }
+        ExportKind::Splunk => {
+            let url: &String = match args.get_one::<String>("hec_url") {
+                Some(url) => url,
+                None => {
+                    output::log_with_time("--export splunk requires --hec-url", "ERROR");
+                    return;
+                }
+            };
+            let token: String = match args
+                .get_one::<String>("hec_token")
+                .cloned()
+                .or(std::env::var("SPLUNK_HEC_TOKEN").ok())
+            {
+                Some(token) => token,
+                None => {
+                    output::log_with_time(
+                        "--export splunk requires --hec-token or SPLUNK_HEC_TOKEN",
+                        "ERROR",
+                    );
+                    return;
+                }
+            };
+            let mut exporter: SplunkHecExporter = SplunkHecExporter::new(url, &token);
+            exporter.index = args.get_one::<String>("hec_index").cloned();
+            if let Some(batch_size) = args.get_one::<usize>("hec_batch") {
+                exporter.batch_size = *batch_size;
+            }
+            exporter.export(hosts)
+        }
+    };
+    match result {
+        Ok(count) => output::log_with_time(
+            &format!("Exported {} documents to {}", count, kind.name()),
+            "INFO",
+        ),
+        Err(e) => output::log_with_time(
+            &format!("Failed to export to {}: {}", kind.name(), e),
+            "ERROR",
+        ),
+    }
+}
+
+/// Set known CVEs of the CPEs detected on each port
+pub fn set_cves(cve_lookup: &mut CveLookup, ports: &mut Vec<Port>) {
+    for port in ports {
+        for cpe in &port.cpes {
+            match cve_lookup.lookup(cpe) {
+                Ok(cves) => port.cves.extend(cves),
+                Err(e) => {
+                    output::log_with_time(&format!("CVE lookup of {} failed: {}", cpe, e), "WARN")
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,138 @@
+use crate::ip::external::{ExternalAddr, ExternalIpMethod, ExternalIpResult, DEFAULT_STUN_SERVERS};
+use crate::output;
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use termtree::Tree;
+
+pub fn handle_myip(args: &ArgMatches) {
+    let myip_args = match args.subcommand_matches("myip") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let methods: Vec<ExternalIpMethod> = match myip_args.get_one::<String>("method") {
+        Some(method) => match ExternalIpMethod::from_str(method) {
+            Some(method) => vec![method],
+            None => return,
+        },
+        // Fall back in order
+        None => vec![
+            ExternalIpMethod::Stun,
+            ExternalIpMethod::Dns,
+            ExternalIpMethod::Http,
+        ],
+    };
+    let stun_servers: Vec<String> = match myip_args.get_many::<String>("stun") {
+        Some(servers) => servers.cloned().collect(),
+        None => DEFAULT_STUN_SERVERS.iter().map(|s| s.to_string()).collect(),
+    };
+    let timeout: Duration = match myip_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(3000),
+    };
+    output::log_with_time("Looking up external addresses...", "INFO");
+    // IPv4 and IPv6 in parallel
+    let v4_methods: Vec<ExternalIpMethod> = methods.clone();
+    let v4_servers: Vec<String> = stun_servers.clone();
+    let v4_handle = thread::spawn(move || {
+        crate::ip::external::lookup(&v4_methods, &v4_servers, false, timeout)
+    });
+    let v6_result = crate::ip::external::lookup(&methods, &stun_servers, true, timeout);
+    let v4_result = v4_handle
+        .join()
+        .unwrap_or(Err(vec!["Lookup thread panicked".to_string()]));
+    let mut local_ips: Vec<IpAddr> = Vec::new();
+    for iface in netdev::get_interfaces() {
+        if iface.is_loopback() {
+            continue;
+        }
+        local_ips.extend(iface.ipv4.iter().map(|ipv4| IpAddr::V4(ipv4.addr)));
+        local_ips.extend(iface.ipv6.iter().map(|ipv6| IpAddr::V6(ipv6.addr)));
+    }
+    let is_nat = |addr: &ExternalAddr| !local_ips.contains(&addr.ip_addr);
+    for (label, result) in [("IPv4", &v4_result), ("IPv6", &v6_result)] {
+        if let Err(errors) = result {
+            output::log_with_time(
+                &format!("No external {} address. {}", label, errors.join(", ")),
+                "WARN",
+            );
+        }
+    }
+    let result = ExternalIpResult {
+        ipv4_nat: v4_result.as_ref().ok().map(is_nat),
+        ipv6_nat: v6_result.as_ref().ok().map(is_nat),
+        ipv4: v4_result.ok(),
+        ipv6: v6_result.ok(),
+        local_ips: local_ips.clone(),
+    };
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_myip_result(&result);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn external_addr_tree(label: &str, addr: &ExternalAddr, nat: Option<bool>) -> Tree<String> {
+    let mut tree = Tree::new(node_label(label, Some(&addr.ip_addr.to_string()), None));
+    tree.push(node_label(
+        "Method",
+        Some(&format!("{:?} ({})", addr.method, addr.server)),
+        None,
+    ));
+    if let (Some(local_addr), Some(mapped_port)) = (addr.local_addr, addr.mapped_port) {
+        // Port changed by NAT
+        let port: String = if local_addr.port() == mapped_port {
+            format!("{} (preserved)", mapped_port)
+        } else {
+            format!("{} -> {}", local_addr.port(), mapped_port)
+        };
+        tree.push(node_label("Mapped Port", Some(&port), None));
+    }
+    match nat {
+        Some(true) => tree.push(node_label("NAT", Some("Yes"), None)),
+        Some(false) => tree.push(node_label("NAT", Some("No (address is local)"), None)),
+        None => {}
+    };
+    tree
+}
+
+fn show_myip_result(result: &ExternalIpResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("External Address", None, None));
+    match &result.ipv4 {
+        Some(addr) => tree.push(external_addr_tree("IPv4", addr, result.ipv4_nat)),
+        None => tree.push(node_label("IPv4", Some("Not available"), None)),
+    };
+    match &result.ipv6 {
+        Some(addr) => tree.push(external_addr_tree("IPv6", addr, result.ipv6_nat)),
+        None => tree.push(node_label("IPv6", Some("Not available"), None)),
+    };
+    let mut local_tree = Tree::new(node_label("Local Addresses", None, None));
+    for ip_addr in &result.local_ips {
+        local_tree.push(node_label(&ip_addr.to_string(), None, None));
+    }
+    tree.push(local_tree);
+    println!("{}", tree);
+}
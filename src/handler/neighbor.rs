@@ -1,15 +1,27 @@
+use crate::app::StopGuard;
+use crate::host::Host;
+use crate::neighbor::conflict::detect_conflicts;
+use crate::neighbor::presence::{PresenceEvent, PresenceTracker};
 use crate::neighbor::resolver::DeviceResolver;
-use crate::neighbor::result::DeviceResolveResult;
+use crate::neighbor::result::{DeviceResolveResult, Neighbor, NeighborSweepResult};
 use crate::neighbor::setting::AddressResolveSetting;
+use crate::neighbor::table::NeighborEntry;
 use crate::output;
+use crate::protocol::Protocol;
+use crate::scan::result::ScanResult;
+use crate::scan::setting::{HostScanSetting, HostScanType};
 use crate::util::tree::node_label;
 use clap::ArgMatches;
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use netdev::mac::MacAddr;
 use netdev::Interface;
-use std::net::IpAddr;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
-use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use termtree::Tree;
 
 pub fn handle_neighbor_discovery(args: &ArgMatches) {
@@ -17,13 +29,29 @@ pub fn handle_neighbor_discovery(args: &ArgMatches) {
         Some(matches) => matches,
         None => return,
     };
+    if nei_args.get_flag("table") {
+        handle_neighbor_table(args);
+        return;
+    }
     let target: String = match nei_args.get_one::<String>("target") {
         Some(target) => target.to_owned(),
         None => return,
     };
-    let dst_ip: IpAddr = match IpAddr::from_str(&target) {
-        Ok(ip_addr) => ip_addr,
-        Err(_) => {
+    if nei_args.get_flag("announce") {
+        handle_announce(args, nei_args, &target);
+        return;
+    }
+    // Network or range. Resolve every address. Tracking a single address also sweeps
+    if crate::ip::parse_ip_with_zone(&target).is_none() || nei_args.get_flag("track") {
+        if let Some(ips) = crate::ip::expand_ip_target(&target) {
+            handle_neighbor_sweep(args, nei_args, target, ips);
+            return;
+        }
+    }
+    // Zone ID of IPv6 address (e.g. fe80::1%eth0)
+    let (dst_ip, zone): (IpAddr, Option<String>) = match crate::ip::parse_ip_with_zone(&target) {
+        Some(parsed) => parsed,
+        None => {
             output::log_with_time("Invalid IP Address", "ERROR");
             return;
         }
@@ -36,7 +64,20 @@ pub fn handle_neighbor_discovery(args: &ArgMatches) {
             output::log_with_time("Initiating NDP...", "INFO");
         }
     }
-    let interface: netdev::Interface = if let Some(if_name) = args.get_one::<String>("interface") {
+    // Zone ID takes precedence over --interface
+    let interface: netdev::Interface = if let Some(zone) = &zone {
+        let iface = match zone.parse::<u32>() {
+            Ok(if_index) => crate::interface::get_interface_by_index(if_index),
+            Err(_) => crate::interface::get_interface_by_name(zone.to_string()),
+        };
+        match iface {
+            Some(iface) => iface,
+            None => {
+                output::log_with_time(&format!("Invalid zone ID: {}", zone), "ERROR");
+                return;
+            }
+        }
+    } else if let Some(if_name) = args.get_one::<String>("interface") {
         match crate::interface::get_interface_by_name(if_name.to_string()) {
             Some(iface) => iface,
             None => return,
@@ -47,6 +88,15 @@ pub fn handle_neighbor_discovery(args: &ArgMatches) {
             Err(_) => return,
         }
     };
+    if !crate::interface::is_on_link(&interface, &dst_ip) {
+        output::log_with_time(
+            &format!(
+                "{} is not on-link of {}. Only neighbors on the same link answer",
+                dst_ip, interface.name
+            ),
+            "WARN",
+        );
+    }
     let count: u32 = match nei_args.get_one::<u32>("count") {
         Some(count) => *count,
         None => 1,
@@ -92,7 +142,15 @@ pub fn handle_neighbor_discovery(args: &ArgMatches) {
     }
     match handle.join() {
         Ok(resolve_result) => match resolve_result {
-            Ok(r) => {
+            Ok(mut r) => {
+                // Replies from more than one MAC address
+                let bindings: Vec<(IpAddr, MacAddr)> = r
+                    .results
+                    .iter()
+                    .filter(|probe| probe.probe_status.kind == crate::probe::ProbeStatusKind::Done)
+                    .map(|probe| (probe.ip_addr, probe.mac_addr))
+                    .collect();
+                r.conflicts = detect_conflicts(&bindings);
                 // Print results
                 if args.get_flag("json") {
                     let json_result = serde_json::to_string_pretty(&r).unwrap();
@@ -100,6 +158,9 @@ pub fn handle_neighbor_discovery(args: &ArgMatches) {
                 } else {
                     show_resolve_result(&r);
                 }
+                for conflict in &r.conflicts {
+                    output::log_alert(&conflict.message());
+                }
                 match args.get_one::<PathBuf>("save") {
                     Some(file_path) => {
                         match crate::fs::save_text(
@@ -138,6 +199,408 @@ pub fn handle_neighbor_discovery(args: &ArgMatches) {
     }
 }
 
+/// Resolve every on-link address of the network concurrently (ARP/NDP scan)
+fn handle_neighbor_sweep(
+    args: &ArgMatches,
+    nei_args: &ArgMatches,
+    target: String,
+    ips: Vec<IpAddr>,
+) {
+    let interface: Interface = if let Some(if_name) = args.get_one::<String>("interface") {
+        match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => return,
+        }
+    } else {
+        match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(_) => return,
+        }
+    };
+    if interface.is_tun() || interface.is_loopback() {
+        output::log_with_time(
+            "ARP/NDP is not supported on tun or loopback interface",
+            "ERROR",
+        );
+        return;
+    }
+    // ARP/NDP reaches on-link hosts only
+    let targets: Vec<Host> = ips
+        .into_iter()
+        .filter(|ip_addr| crate::interface::is_on_link(&interface, ip_addr))
+        .map(|ip_addr| Host::new(ip_addr, String::new()))
+        .collect();
+    if targets.is_empty() {
+        output::log_with_time(
+            &format!("{} is not on-link of {}", target, interface.name),
+            "ERROR",
+        );
+        return;
+    }
+    let timeout = match nei_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_secs(30),
+    };
+    let wait_time = match nei_args.get_one::<u64>("waittime") {
+        Some(wait_time) => Duration::from_millis(*wait_time),
+        None => Duration::from_secs(1),
+    };
+    let send_rate = match nei_args.get_one::<u64>("rate") {
+        Some(send_rate) => Duration::from_millis(*send_rate),
+        None => crate::util::setting::calculate_send_rate(&interface),
+    };
+    let total: usize = targets.len();
+    let mut scan_setting = HostScanSetting::default()
+        .set_if_index(interface.index)
+        .set_protocol(HostScanType::ArpScan.protocol())
+        .set_scan_type(HostScanType::ArpScan)
+        .set_targets(targets)
+        .set_timeout(timeout)
+        .set_wait_time(wait_time)
+        .set_send_rate(send_rate);
+    scan_setting.randomize_hosts();
+    if nei_args.get_flag("track") {
+        handle_presence_tracking(args, nei_args, target, &interface, scan_setting);
+        return;
+    }
+    output::log_with_time(
+        &format!("Resolving {} addresses on {}...", total, interface.name),
+        "INFO",
+    );
+    if !crate::app::is_quiet_mode() {
+        println!("[Progress]");
+    }
+    let result: NeighborSweepResult = run_sweep(target, &interface, scan_setting, true);
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_sweep_result(&result);
+    }
+    for conflict in &result.conflicts {
+        output::log_alert(&conflict.message());
+    }
+    output::log_with_time(
+        &format!(
+            "{} of {} addresses resolved in {:?}",
+            result.neighbors.len(),
+            result.total,
+            result.elapsed_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+/// Send gratuitous ARP for a local address after confirmation
+fn handle_announce(args: &ArgMatches, nei_args: &ArgMatches, target: &str) {
+    let ipv4_addr: Ipv4Addr = match target.parse::<Ipv4Addr>() {
+        Ok(ipv4_addr) => ipv4_addr,
+        Err(_) => {
+            output::log_with_time("Gratuitous ARP requires a local IPv4 address", "ERROR");
+            return;
+        }
+    };
+    // The interface that owns the address, unless specified
+    let interface: Interface = match args.get_one::<String>("interface") {
+        Some(if_name) => match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => {
+                output::log_with_time(&format!("Interface not found: {}", if_name), "ERROR");
+                return;
+            }
+        },
+        None => match netdev::get_interfaces()
+            .into_iter()
+            .find(|iface| iface.ipv4.iter().any(|ipv4| ipv4.addr == ipv4_addr))
+        {
+            Some(iface) => iface,
+            None => {
+                output::log_with_time(
+                    &format!("{} is not assigned to any interface", ipv4_addr),
+                    "ERROR",
+                );
+                return;
+            }
+        },
+    };
+    if !interface.ipv4.iter().any(|ipv4| ipv4.addr == ipv4_addr) {
+        output::log_with_time(
+            &format!("{} is not assigned to {}", ipv4_addr, interface.name),
+            "ERROR",
+        );
+        return;
+    }
+    let count: u32 = match nei_args.get_one::<u32>("count") {
+        Some(count) => *count,
+        None => 3,
+    };
+    let send_rate = match nei_args.get_one::<u64>("rate") {
+        Some(send_rate) => Duration::from_millis(*send_rate),
+        None => Duration::from_secs(1),
+    };
+    // Neighbors overwrite their caches. Confirm unless --yes
+    if !nei_args.get_flag("yes") {
+        let message: String = format!(
+            "Send {} gratuitous ARP for {} ({}) on {}?",
+            count,
+            ipv4_addr,
+            interface.mac_addr.unwrap_or(MacAddr::zero()).address(),
+            interface.name
+        );
+        match inquire::Confirm::new(&message).with_default(false).prompt() {
+            Ok(true) => {}
+            Ok(false) => {
+                output::log_with_time("Cancelled", "INFO");
+                return;
+            }
+            Err(e) => {
+                output::log_with_time(&format!("Cancelled: {}", e), "ERROR");
+                return;
+            }
+        }
+    }
+    output::log_with_time(
+        &format!("Announcing {} on {}...", ipv4_addr, interface.name),
+        "INFO",
+    );
+    match crate::neighbor::announce(&interface, ipv4_addr, count, send_rate) {
+        Ok(sent) => {
+            output::log_with_time(&format!("Sent {} gratuitous ARP", sent), "INFO");
+        }
+        Err(e) => {
+            output::log_with_time(&format!("Announce Failed: {}", e), "ERROR");
+        }
+    }
+}
+
+/// ARP/NDP scan of the targets in the setting
+fn run_sweep(
+    target: String,
+    interface: &Interface,
+    scan_setting: HostScanSetting,
+    show_progress: bool,
+) -> NeighborSweepResult {
+    let total: usize = scan_setting.targets.len();
+    let bar = ProgressBar::new(total as u64);
+    if crate::app::is_quiet_mode() || !show_progress {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar.set_style(output::get_progress_style());
+    bar.set_position(0);
+    let found: Arc<Mutex<HashSet<IpAddr>>> = Arc::new(Mutex::new(HashSet::new()));
+    let scan_result: ScanResult =
+        crate::handler::host::run_hostscan(scan_setting, &bar, "NeighborSweep", &found);
+    bar.finish_with_message(format!("NeighborSweep ({:?})", scan_result.scan_time));
+    let oui_map: HashMap<String, String> = crate::db::get_oui_detail_map();
+    let mut neighbors: Vec<Neighbor> = scan_result
+        .hosts
+        .iter()
+        .map(|host| Neighbor {
+            ip_addr: host.ip_addr,
+            mac_addr: host.mac_addr,
+            vendor_name: crate::db::get_vendor_name(&oui_map, &host.mac_addr),
+            protocol: if host.ip_addr.is_ipv4() {
+                Protocol::ARP
+            } else {
+                Protocol::NDP
+            },
+        })
+        .collect();
+    neighbors.sort_by_key(|neighbor| neighbor.ip_addr);
+    NeighborSweepResult {
+        target: target,
+        interface: interface.name.clone(),
+        total: total,
+        neighbors: neighbors,
+        conflicts: detect_conflicts(&scan_result.address_bindings),
+        elapsed_time: scan_result.scan_time,
+    }
+}
+
+/// Re-sweep periodically and print join/leave events until interrupted
+fn handle_presence_tracking(
+    args: &ArgMatches,
+    nei_args: &ArgMatches,
+    target: String,
+    interface: &Interface,
+    scan_setting: HostScanSetting,
+) {
+    let interval: Duration = match nei_args.get_one::<u64>("interval") {
+        Some(interval) => Duration::from_secs(*interval),
+        None => Duration::from_secs(60),
+    };
+    let miss_limit: u32 = match nei_args.get_one::<u32>("miss") {
+        Some(miss) => *miss,
+        None => 2,
+    };
+    let json: bool = args.get_flag("json");
+    let stop_guard: StopGuard = crate::app::handle_stop();
+    output::log_with_time(
+        &format!(
+            "Tracking {} on {} every {:?}. Press Ctrl-C to stop",
+            target, interface.name, interval
+        ),
+        "INFO",
+    );
+    let mut tracker = PresenceTracker::new(miss_limit);
+    let mut first: bool = true;
+    while !stop_guard.is_stopped() {
+        let sweep_start: Instant = Instant::now();
+        let result: NeighborSweepResult =
+            run_sweep(target.clone(), interface, scan_setting.clone(), false);
+        let events: Vec<PresenceEvent> = tracker.update(&result.neighbors);
+        if first {
+            // Devices present at start are the baseline, not events
+            output::log_with_time(
+                &format!("{} neighbors present", tracker.present_count()),
+                "INFO",
+            );
+            first = false;
+        } else {
+            for event in &events {
+                if json {
+                    println!("{}", serde_json::to_string(event).unwrap());
+                } else {
+                    output::log_with_time(&event.message(), event.kind.level());
+                }
+            }
+        }
+        for conflict in &result.conflicts {
+            output::log_alert(&conflict.message());
+        }
+        let _ = std::io::stdout().flush();
+        // Sleep in short steps to stop promptly
+        while !stop_guard.is_stopped() && sweep_start.elapsed() < interval {
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+    output::log_with_time("Tracking stopped", "INFO");
+}
+
+/// Show the ARP/NDP cache of the OS
+fn handle_neighbor_table(args: &ArgMatches) {
+    let mut entries: Vec<NeighborEntry> = match crate::neighbor::table::get_neighbor_table() {
+        Ok(entries) => entries,
+        Err(e) => {
+            output::log_with_time(&format!("Failed to read neighbor table: {}", e), "ERROR");
+            return;
+        }
+    };
+    // Entries of the specified interface only
+    if let Some(if_name) = args.get_one::<String>("interface") {
+        entries.retain(|entry| &entry.interface == if_name);
+    }
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&entries).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_neighbor_table(&entries);
+    }
+    output::log_with_time(&format!("{} entries", entries.len()), "INFO");
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&entries).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_neighbor_table(entries: &[NeighborEntry]) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let ip_width: usize = entries
+        .iter()
+        .map(|entry| entry.ip_addr.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("IP Address".len());
+    let if_width: usize = entries
+        .iter()
+        .map(|entry| entry.interface.len())
+        .max()
+        .unwrap_or(0)
+        .max("Interface".len());
+    println!(
+        "{:<ip_width$}  {:<17}  {:<if_width$}  {:<10}  {}",
+        "IP Address",
+        "MAC Address",
+        "Interface",
+        "State",
+        "Vendor",
+        ip_width = ip_width,
+        if_width = if_width
+    );
+    for entry in entries {
+        println!(
+            "{:<ip_width$}  {:<17}  {:<if_width$}  {:<10}  {}",
+            entry.ip_addr.to_string(),
+            entry.mac_addr.address(),
+            entry.interface,
+            entry.state,
+            entry.vendor_name,
+            ip_width = ip_width,
+            if_width = if_width
+        );
+    }
+}
+
+/// IP -> MAC -> vendor table
+fn show_sweep_result(result: &NeighborSweepResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let ip_width: usize = result
+        .neighbors
+        .iter()
+        .map(|neighbor| neighbor.ip_addr.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("IP Address".len());
+    println!(
+        "{:<ip_width$}  {:<17}  {}",
+        "IP Address",
+        "MAC Address",
+        "Vendor",
+        ip_width = ip_width
+    );
+    for neighbor in &result.neighbors {
+        println!(
+            "{:<ip_width$}  {:<17}  {}",
+            neighbor.ip_addr.to_string(),
+            neighbor.mac_addr.address(),
+            neighbor.vendor_name,
+            ip_width = ip_width
+        );
+    }
+}
+
 fn print_option(setting: &AddressResolveSetting, interface: &Interface) {
     if crate::app::is_quiet_mode() {
         return;
@@ -188,6 +651,7 @@ fn show_resolve_result(resolve_result: &DeviceResolveResult) {
     if !crate::app::is_quiet_mode() {
         println!();
     }
+    let oui_map: HashMap<String, String> = crate::db::get_oui_detail_map();
     let mut tree = Tree::new(node_label("NeighborResolve Result", None, None));
     // Responses
     let mut responses_tree = Tree::new(node_label("Responses", None, None));
@@ -209,6 +673,11 @@ fn show_resolve_result(resolve_result: &DeviceResolveResult) {
             Some(&response.mac_addr.address()),
             None,
         ));
+        response_tree.push(node_label(
+            "Vendor Name",
+            Some(&crate::db::get_vendor_name(&oui_map, &response.mac_addr)),
+            None,
+        ));
         response_tree.push(node_label("IP Address", Some(&source_ip_addr), None));
         response_tree.push(node_label(
             "Protocol",
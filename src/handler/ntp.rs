@@ -0,0 +1,188 @@
+use crate::ntp::{NtpScanResult, NtpServerInfo, NtpSetting};
+use crate::output;
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use termtree::Tree;
+use tokio::runtime::Runtime;
+
+/// System variables shown in the tree, with labels. All are in the JSON output
+const SHOWN_VARIABLES: [(&str, &str); 3] = [
+    ("version", "Daemon"),
+    ("system", "System"),
+    ("processor", "Processor"),
+];
+
+pub fn handle_ntp_probe(args: &ArgMatches) {
+    let ntp_args = match args.subcommand_matches("ntp") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let target: String = match ntp_args.get_one::<String>("target") {
+        Some(target) => target.to_owned(),
+        None => return,
+    };
+    let ips: Vec<IpAddr> = match crate::ip::expand_ip_target(&target) {
+        Some(ips) => ips,
+        None => match crate::dns::lookup_host_name(&target) {
+            Some(ip_addr) => vec![ip_addr],
+            None => {
+                output::log_with_time(&format!("Invalid target: {}", target), "ERROR");
+                return;
+            }
+        },
+    };
+    let concurrency: usize = match ntp_args.get_one::<usize>("concurrency") {
+        Some(concurrency) => *concurrency,
+        None => crate::config::DEFAULT_NTP_CONCURRENCY,
+    };
+    if concurrency == 0 {
+        output::log_with_time("Concurrency must be at least 1", "ERROR");
+        return;
+    }
+    let timeout: Duration = match ntp_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(crate::config::DEFAULT_NTP_TIMEOUT_MILLIS),
+    };
+    let setting = NtpSetting {
+        timeout: timeout,
+        concurrency: concurrency,
+        monlist: !ntp_args.get_flag("no_monlist"),
+    };
+    output::log_with_time("Initiating NTP probe...", "INFO");
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("NTP Probe Config", None, None));
+        tree.push(node_label("Target", Some(&target), None));
+        tree.push(node_label("Hosts", Some(&ips.len().to_string()), None));
+        tree.push(node_label(
+            "monlist",
+            Some(if setting.monlist { "Check" } else { "Skip" }),
+            None,
+        ));
+        tree.push(node_label(
+            "Concurrent limit",
+            Some(&concurrency.to_string()),
+            None,
+        ));
+        tree.push(node_label("Timeout", Some(&format!("{:?}", timeout)), None));
+        println!("{}", tree);
+        println!("[Progress]");
+    }
+    let bar = ProgressBar::new(ips.len() as u64);
+    if crate::app::is_quiet_mode() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar.enable_steady_tick(120);
+    bar.set_style(output::get_progress_style());
+    bar.set_position(0);
+    bar.set_message("NtpProbe");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let ptx = std::sync::Arc::new(std::sync::Mutex::new(tx));
+    let rt = Runtime::new().unwrap();
+    let handle = thread::spawn(move || rt.block_on(crate::ntp::probe_targets(ips, setting, ptx)));
+    while let Ok(_ip_addr) = rx.recv() {
+        bar.inc(1);
+    }
+    bar.finish_with_message("NtpProbe");
+    let result: NtpScanResult = handle.join().unwrap();
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_ntp_result(&result);
+    }
+    let exposed: usize = result
+        .servers
+        .iter()
+        .filter(|server| server.monlist.is_some())
+        .count();
+    output::log_with_time(
+        &format!(
+            "{} servers found, {} with monlist enabled in {:?}",
+            result.servers.len(),
+            exposed,
+            result.scan_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn server_tree(server: &NtpServerInfo) -> Tree<String> {
+    let mut tree = Tree::new(node_label(&server.ip_addr.to_string(), None, None));
+    tree.push(node_label(
+        "Version",
+        Some(&server.version.to_string()),
+        None,
+    ));
+    tree.push(node_label(
+        "Stratum",
+        Some(&server.stratum.to_string()),
+        None,
+    ));
+    if !server.reference_id.is_empty() {
+        tree.push(node_label("Reference ID", Some(&server.reference_id), None));
+    }
+    if let Some(reference_time) = &server.reference_time {
+        tree.push(node_label(
+            "Reference Time",
+            Some(&reference_time.to_rfc3339()),
+            None,
+        ));
+    }
+    tree.push(node_label(
+        "Offset",
+        Some(&format!("{:.3} ms", server.offset_millis)),
+        None,
+    ));
+    tree.push(node_label(
+        "Root Delay",
+        Some(&format!("{:?}", server.root_delay)),
+        None,
+    ));
+    for (name, label) in SHOWN_VARIABLES {
+        if let Some(value) = server.variables.get(name) {
+            tree.push(node_label(label, Some(value), None));
+        }
+    }
+    let issues: Vec<String> = server.issues();
+    if !issues.is_empty() {
+        let mut issues_tree = Tree::new(node_label("Issues", None, None));
+        for issue in &issues {
+            issues_tree.push(node_label(issue, None, None));
+        }
+        tree.push(issues_tree);
+    }
+    tree
+}
+
+fn show_ntp_result(result: &NtpScanResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("NTP Probe Result", None, None));
+    for server in &result.servers {
+        tree.push(server_tree(server));
+    }
+    println!("{}", tree);
+}
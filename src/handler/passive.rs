@@ -0,0 +1,197 @@
+use crate::app::StopGuard;
+use crate::output;
+use crate::passive::{PassiveHost, PassiveInventory};
+use crate::pcap::{PacketCaptureOptions, RawPacket};
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use netdev::mac::MacAddr;
+use netdev::Interface;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use termtree::Tree;
+
+pub fn handle_passive(args: &ArgMatches) {
+    let passive_args = match args.subcommand_matches("passive") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let interface: Interface = match passive_args
+        .get_one::<String>("interface")
+        .or(args.get_one::<String>("interface"))
+    {
+        Some(if_name) => match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => {
+                output::log_with_time(&format!("Interface not found: {}", if_name), "ERROR");
+                return;
+            }
+        },
+        None => match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        },
+    };
+    let duration: Option<Duration> = passive_args
+        .get_one::<u64>("duration")
+        .map(|secs| Duration::from_secs(*secs));
+    let mut options: PacketCaptureOptions = PacketCaptureOptions::from_interface(&interface);
+    options.promiscuous = passive_args.get_flag("promisc");
+    if let Some(duration) = duration {
+        options.capture_timeout = duration;
+    }
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("Passive Inventory Config", None, None));
+        tree.push(node_label("Interface", Some(&interface.name), None));
+        if let Some(duration) = duration {
+            tree.push(node_label(
+                "Duration",
+                Some(&format!("{:?}", duration)),
+                None,
+            ));
+        }
+        tree.push(node_label(
+            "Promiscuous",
+            Some(&options.promiscuous.to_string()),
+            None,
+        ));
+        println!("{}", tree);
+    }
+    let config = nex::datalink::Config {
+        write_buffer_size: 4096,
+        read_buffer_size: 65536,
+        read_timeout: Some(options.read_timeout),
+        write_timeout: None,
+        channel_type: nex::datalink::ChannelType::Layer2,
+        bpf_fd_attempts: 1000,
+        linux_fanout: None,
+        promiscuous: options.promiscuous,
+    };
+    // Receive only. Nothing is sent on the interface
+    let mut rx = match nex::datalink::channel(&interface, config) {
+        Ok(nex::datalink::Channel::Ethernet(_, rx)) => rx,
+        Ok(_) => {
+            output::log_with_time("Unknown channel type", "ERROR");
+            return;
+        }
+        Err(e) => {
+            output::log_with_time(&format!("Failed to open channel: {}", e), "ERROR");
+            return;
+        }
+    };
+    let stop_guard: StopGuard = crate::app::handle_stop();
+    let stop: Arc<Mutex<bool>> = stop_guard.get_stop_handle();
+    output::log_with_time(
+        &format!(
+            "Listening for ARP/DHCP/mDNS/TCP SYN on {}. Press Ctrl-C to stop",
+            interface.name
+        ),
+        "INFO",
+    );
+    let (tx, packet_rx): (Sender<RawPacket>, Receiver<RawPacket>) = channel();
+    let capture_stop: Arc<Mutex<bool>> = stop.clone();
+    let handle =
+        thread::spawn(move || crate::pcap::start_raw_capture(&mut rx, options, &capture_stop, tx));
+    let start_time: Instant = Instant::now();
+    let mut inventory: PassiveInventory = PassiveInventory::new(&interface);
+    for packet in packet_rx.iter() {
+        for event in inventory.update(&packet.frame) {
+            output::log_with_time(&event, "INFO");
+        }
+    }
+    let _ = handle.join();
+    inventory.capture_time = start_time.elapsed();
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&inventory).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_inventory(&inventory);
+    }
+    output::log_with_time(
+        &format!(
+            "{} hosts found from {} packets in {:?}",
+            inventory.hosts.len(),
+            inventory.packets,
+            inventory.capture_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&inventory).unwrap())
+            {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn host_tree(host: &PassiveHost) -> Tree<String> {
+    let mut tree = Tree::new(node_label(&host.ip_addr.to_string(), None, None));
+    if host.mac_addr != MacAddr::zero() {
+        tree.push(node_label(
+            "MAC Address",
+            Some(&host.mac_addr.to_string()),
+            None,
+        ));
+    }
+    if !host.vendor_name.is_empty() {
+        tree.push(node_label("Vendor", Some(&host.vendor_name), None));
+    }
+    if !host.hostnames.is_empty() {
+        let hostnames: Vec<String> = host.hostnames.iter().cloned().collect();
+        tree.push(node_label("Host Name", Some(&hostnames.join(", ")), None));
+    }
+    if !host.vendor_class.is_empty() {
+        tree.push(node_label(
+            "DHCP Vendor Class",
+            Some(&host.vendor_class),
+            None,
+        ));
+    }
+    if !host.ports.is_empty() {
+        let ports: Vec<String> = host.ports.iter().map(|port| port.to_string()).collect();
+        tree.push(node_label("TCP Ports", Some(&ports.join(", ")), None));
+    }
+    if !host.services.is_empty() {
+        let mut services_tree = Tree::new(node_label("mDNS Services", None, None));
+        for service in &host.services {
+            services_tree.push(node_label(service, None, None));
+        }
+        tree.push(services_tree);
+    }
+    let sources: Vec<String> = host.sources.iter().cloned().collect();
+    tree.push(node_label("Seen In", Some(&sources.join(", ")), None));
+    tree.push(node_label(
+        "Last Seen",
+        Some(&host.last_seen.format("%Y-%m-%d %H:%M:%S").to_string()),
+        None,
+    ));
+    tree
+}
+
+fn show_inventory(inventory: &PassiveInventory) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("Passive Inventory", None, None));
+    for host in inventory.hosts.values() {
+        tree.push(host_tree(host));
+    }
+    println!("{}", tree);
+}
@@ -1,12 +1,21 @@
+use crate::app::StopGuard;
 use crate::output;
-use crate::ping::{pinger::Pinger, result::PingResult, setting::PingSetting};
+use crate::packet::ipv4::{Ipv4OptionData, Ipv4OptionType};
+use crate::ping::{
+    pinger::Pinger,
+    result::{HostPingResult, MultiPingResult, PingResult, PingStat, PmtuResult},
+    setting::PingSetting,
+};
 use crate::protocol::Protocol;
 use crate::util::tree::node_label;
 use clap::ArgMatches;
 use netdev::Interface;
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 use termtree::Tree;
@@ -164,24 +173,34 @@ pub fn handle_ping(args: &ArgMatches) {
         Some(matches) => matches,
         None => return,
     };
-    let interface: netdev::Interface = if let Some(if_name) = args.get_one::<String>("interface") {
-        match crate::interface::get_interface_by_name(if_name.to_string()) {
-            Some(iface) => iface,
-            None => return,
-        }
-    } else {
-        match netdev::get_default_interface() {
-            Ok(iface) => iface,
-            Err(_) => return,
-        }
+    let mut targets: Vec<String> = match ping_args.get_many::<String>("target") {
+        Some(targets) => targets.cloned().collect(),
+        None => Vec::new(),
     };
-    let target: String = match ping_args.get_one::<String>("target") {
+    let input_file: Option<&PathBuf> = ping_args.get_one::<PathBuf>("input_file");
+    if let Some(file_path) = input_file {
+        match read_target_list(file_path) {
+            Ok(file_targets) => targets.extend(file_targets),
+            Err(e) => {
+                output::log_with_time(
+                    &format!("Failed to read {}: {}", file_path.to_string_lossy(), e),
+                    "ERROR",
+                );
+                return;
+            }
+        }
+    }
+    let target: String = match targets.first() {
         Some(target) => target.to_owned(),
-        None => return,
+        None => {
+            output::log_with_time("No target specified", "ERROR");
+            return;
+        }
     };
+    // Ping until interrupted unless count is specified
     let count: u32 = match ping_args.get_one::<u32>("count") {
         Some(count) => *count,
-        None => 4,
+        None => 0,
     };
     let maxhop: u8 = match ping_args.get_one::<u8>("maxhop") {
         Some(maxhop) => *maxhop,
@@ -201,14 +220,32 @@ pub fn handle_ping(args: &ArgMatches) {
         Some(port) => *port,
         None => 80,
     };
-    let dst_ip: IpAddr = match IpAddr::from_str(&target) {
-        Ok(ip_addr) => ip_addr,
-        Err(_) => match SocketAddr::from_str(&target) {
+    if targets.len() > 1 || input_file.is_some() {
+        // Multi-target ping always stops after count
+        let count: u32 = if count == 0 {
+            crate::config::DEFAULT_PING_COUNT
+        } else {
+            count
+        };
+        handle_multi_ping(args, targets, protocol, port, count, maxhop);
+        return;
+    }
+    let mut dst_hostname: String = target.clone();
+    // Zone ID of IPv6 address (e.g. fe80::1%eth0)
+    let mut zone: Option<String> = None;
+    let dst_ip: IpAddr = match crate::ip::parse_ip_with_zone(&target) {
+        Some((ip_addr, zone_id)) => {
+            dst_hostname = ip_addr.to_string();
+            zone = zone_id;
+            ip_addr
+        }
+        None => match SocketAddr::from_str(&target) {
             Ok(socket_addr) => {
                 port = socket_addr.port();
                 if protocol == Protocol::ICMP {
                     protocol = Protocol::TCP;
                 }
+                dst_hostname = socket_addr.ip().to_string();
                 socket_addr.ip()
             }
             Err(_) => match crate::dns::lookup_host_name(&target) {
@@ -220,6 +257,13 @@ pub fn handle_ping(args: &ArgMatches) {
             },
         },
     };
+    let interface: netdev::Interface = match get_ping_interface(args, &zone) {
+        Ok(iface) => iface,
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    };
     let timeout = match ping_args.get_one::<u64>("timeout") {
         Some(timeout) => Duration::from_millis(*timeout),
         None => Duration::from_secs(30),
@@ -232,6 +276,13 @@ pub fn handle_ping(args: &ArgMatches) {
         Some(send_rate) => Duration::from_millis(*send_rate),
         None => Duration::from_secs(1),
     };
+    let interval: Option<Duration> = match get_interval(ping_args) {
+        Ok(interval) => interval,
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    };
     let mut setting: PingSetting = match protocol {
         Protocol::ICMP => PingSetting::icmp_ping(&interface, dst_ip, count).unwrap(),
         Protocol::TCP => PingSetting::tcp_ping(&interface, dst_ip, port, count).unwrap(),
@@ -241,16 +292,49 @@ pub fn handle_ping(args: &ArgMatches) {
             return;
         }
     };
-    setting.dst_hostname = target
-        .split(":")
-        .collect::<Vec<&str>>()
-        .get(0)
-        .unwrap()
-        .to_string();
+    setting.dst_hostname = dst_hostname;
+    if let Err(e) = set_link_local_mac(&mut setting, &interface) {
+        output::log_with_time(&e, "ERROR");
+        return;
+    }
     setting.hop_limit = maxhop;
     setting.receive_timeout = wait_time;
     setting.probe_timeout = timeout;
     setting.send_rate = send_rate;
+    setting.interval = interval;
+    set_probe_options(&mut setting, ping_args);
+    if ping_args.get_flag("record_route") {
+        setting.ipv4_option = Some(Ipv4OptionType::RecordRoute);
+    } else if ping_args.get_flag("timestamp") {
+        setting.ipv4_option = Some(Ipv4OptionType::Timestamp);
+    }
+    if setting.ipv4_option.is_some()
+        && (setting.protocol != Protocol::ICMP || !setting.dst_ip.is_ipv4())
+    {
+        output::log_with_time(
+            "Record route and timestamp options are supported only for ICMP over IPv4",
+            "ERROR",
+        );
+        return;
+    }
+    if let Err(e) = check_payload_size(&setting) {
+        output::log_with_time(&e, "ERROR");
+        return;
+    }
+    let flood: bool = ping_args.get_flag("flood");
+    if flood {
+        if !crate::sys::os::is_privileged() {
+            output::log_with_time("Flood ping requires administrator privileges", "ERROR");
+            return;
+        }
+        if setting.protocol != Protocol::ICMP {
+            output::log_with_time("Flood ping supports ICMP only", "ERROR");
+            return;
+        }
+        // Send every 10ms without waiting for replies
+        setting.flood = true;
+        setting.interval = None;
+    }
 
     let target_addr: String =
         if setting.dst_ip.to_string() != setting.dst_hostname && !setting.dst_hostname.is_empty() {
@@ -259,28 +343,53 @@ pub fn handle_ping(args: &ArgMatches) {
             setting.dst_ip.to_string()
         };
 
+    if ping_args.get_flag("pmtu") {
+        if setting.protocol != Protocol::ICMP {
+            output::log_with_time("Path MTU discovery supports ICMP only", "ERROR");
+            return;
+        }
+        handle_pmtu(args, setting, &interface, target_addr);
+        return;
+    }
+
     print_option(&setting, &interface);
 
-    let pinger: Pinger = Pinger::new(setting).unwrap();
+    let mut pinger: Pinger = Pinger::new(setting).unwrap();
     let rx = pinger.get_progress_receiver();
+    // Stop on Ctrl-C and print the summary
+    let stop_guard: StopGuard = crate::app::handle_stop();
+    pinger.set_stop_handle(stop_guard.get_stop_handle());
     let handle = thread::spawn(move || pinger.ping());
     for r in rx.lock().unwrap().iter() {
+        // Flood mode prints a dot per request and a backspace per reply
+        if flood {
+            if r.probe_status.kind == crate::probe::ProbeStatusKind::Done {
+                print!(".\x08");
+            } else {
+                print!(".");
+            }
+            let _ = std::io::stdout().flush();
+            continue;
+        }
         let source: String = if r.ip_addr.to_string() != r.host_name && !r.host_name.is_empty() {
             format!("{}({})", r.host_name, r.ip_addr)
         } else {
             r.ip_addr.to_string()
         };
+        // IPv6 reports hop limit instead of TTL
+        let ttl_label: &str = if r.ip_addr.is_ipv6() { "HLIM" } else { "TTL" };
         if r.probe_status.kind == crate::probe::ProbeStatusKind::Done {
             if let Some(port) = r.port_number {
                 output::log_with_time(
                     &format!(
-                        "{} [{:?}] {} Bytes from {}:{}, HOP:{}, TTL:{}, RTT:{:?}",
+                        "{} [{:?}] {} Bytes from {}:{}, HOP:{}, {}:{}, RTT:{:?}",
                         r.seq,
                         r.protocol,
                         r.received_packet_size,
                         source,
                         port,
                         r.hop,
+                        ttl_label,
                         r.ttl,
                         r.rtt
                     ),
@@ -289,12 +398,22 @@ pub fn handle_ping(args: &ArgMatches) {
             } else {
                 output::log_with_time(
                     &format!(
-                        "{} [{:?}] {} Bytes from {}, HOP:{}, TTL:{}, RTT:{:?}",
-                        r.seq, r.protocol, r.received_packet_size, source, r.hop, r.ttl, r.rtt
+                        "{} [{:?}] {} Bytes from {}, HOP:{}, {}:{}, RTT:{:?}",
+                        r.seq,
+                        r.protocol,
+                        r.received_packet_size,
+                        source,
+                        r.hop,
+                        ttl_label,
+                        r.ttl,
+                        r.rtt
                     ),
                     "INFO",
                 );
             }
+            if let Some(ip_options) = &r.ip_options {
+                log_ip_options(ip_options);
+            }
         } else {
             if let Some(port) = r.port_number {
                 output::log_with_time(
@@ -315,16 +434,29 @@ pub fn handle_ping(args: &ArgMatches) {
             }
         }
     }
+    if flood {
+        println!();
+    }
+    // No reply counts as 100% loss
+    let mut loss: f64 = 100.0;
     match handle.join() {
         Ok(ping_result) => match ping_result {
             Ok(ping_result) => {
                 if ping_result.probe_status.kind == crate::probe::ProbeStatusKind::Done {
+                    loss = loss_percent(&ping_result.stat);
                     // Print results
                     if args.get_flag("json") {
                         let json_result = serde_json::to_string_pretty(&ping_result).unwrap();
                         println!("{}", json_result);
                     } else {
-                        show_ping_result(&ping_result, target_addr);
+                        if flood {
+                            show_flood_result(&ping_result, target_addr);
+                        } else {
+                            show_ping_result(&ping_result, target_addr);
+                        }
+                        if ping_args.get_flag("graph") {
+                            show_rtt_graph(&ping_result);
+                        }
                     }
                     match args.get_one::<PathBuf>("save") {
                         Some(file_path) => {
@@ -359,6 +491,454 @@ pub fn handle_ping(args: &ArgMatches) {
         },
         Err(e) => println!("{:?}", e),
     }
+    check_loss_threshold(ping_args, loss);
+}
+
+fn loss_percent(stat: &PingStat) -> f64 {
+    if stat.transmitted_count == 0 {
+        return 100.0;
+    }
+    100.0 - (stat.received_count as f64 / stat.transmitted_count as f64) * 100.0
+}
+
+/// Exit with non-zero status if loss exceeds --fail-on-loss threshold
+fn check_loss_threshold(ping_args: &ArgMatches, loss: f64) {
+    if let Some(threshold) = ping_args.get_one::<f64>("fail_on_loss") {
+        if loss > *threshold {
+            output::log_with_time(
+                &format!("Packet loss {:.1}% exceeds threshold {}%", loss, threshold),
+                "ERROR",
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Interface selected by the zone ID of the target, --interface or the default interface.
+/// Zone ID takes precedence over --interface
+fn get_ping_interface(args: &ArgMatches, zone: &Option<String>) -> Result<Interface, String> {
+    if let Some(zone) = zone {
+        let iface = match zone.parse::<u32>() {
+            Ok(if_index) => crate::interface::get_interface_by_index(if_index),
+            Err(_) => crate::interface::get_interface_by_name(zone.to_string()),
+        };
+        return iface.ok_or(format!("Invalid zone ID: {}", zone));
+    }
+    if let Some(if_name) = args.get_one::<String>("interface") {
+        return crate::interface::get_interface_by_name(if_name.to_string())
+            .ok_or(format!("Interface not found: {}", if_name));
+    }
+    netdev::get_default_interface().map_err(|e| e.to_string())
+}
+
+/// Interval between the start of each probe from --interval
+fn get_interval(ping_args: &ArgMatches) -> Result<Option<Duration>, String> {
+    match ping_args.get_one::<u64>("interval") {
+        Some(interval) => {
+            if *interval < crate::config::MIN_PING_INTERVAL_MILLIS {
+                return Err(format!(
+                    "Interval must be at least {}ms",
+                    crate::config::MIN_PING_INTERVAL_MILLIS
+                ));
+            }
+            if *interval < crate::config::MIN_UNPRIVILEGED_PING_INTERVAL_MILLIS
+                && !crate::sys::os::is_privileged()
+            {
+                return Err(format!(
+                    "Interval less than {}ms requires administrator privileges",
+                    crate::config::MIN_UNPRIVILEGED_PING_INTERVAL_MILLIS
+                ));
+            }
+            Ok(Some(Duration::from_millis(*interval)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Set deadline, payload size, DF bit and TOS of probes
+fn set_probe_options(setting: &mut PingSetting, ping_args: &ArgMatches) {
+    setting.deadline = match ping_args.get_one::<u64>("deadline") {
+        Some(deadline) => Some(Duration::from_secs(*deadline)),
+        None => None,
+    };
+    if let Some(size) = ping_args.get_one::<usize>("size") {
+        setting.payload_size = *size;
+    }
+    setting.dont_fragment = ping_args.get_flag("df");
+    setting.tos = super::get_tos(ping_args);
+}
+
+/// ICMP echo payload must fit in the IP packet along with headers and options
+fn check_payload_size(setting: &PingSetting) -> Result<(), String> {
+    if setting.protocol != Protocol::ICMP {
+        return Ok(());
+    }
+    let max_size: usize =
+        crate::packet::icmp::max_echo_payload_len(&setting.dst_ip, setting.ipv4_option);
+    if setting.payload_size > max_size {
+        return Err(format!(
+            "Payload size {} exceeds the maximum of {} bytes",
+            setting.payload_size, max_size
+        ));
+    }
+    Ok(())
+}
+
+/// Link-local destination is not reachable via gateway. Resolve its MAC address
+fn set_link_local_mac(setting: &mut PingSetting, interface: &Interface) -> Result<(), String> {
+    if let IpAddr::V6(ipv6_addr) = setting.dst_ip {
+        if crate::ip::is_link_local_ipv6(&ipv6_addr)
+            && !interface.is_tun()
+            && !interface.is_loopback()
+        {
+            match crate::neighbor::resolve_mac_addr(interface, setting.dst_ip) {
+                Some(mac_addr) => setting.dst_mac = Some(mac_addr),
+                None => {
+                    return Err(format!(
+                        "Failed to resolve MAC address of {}",
+                        setting.dst_ip
+                    ))
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Options that apply to a single target only
+const SINGLE_TARGET_OPTIONS: [(&str, &str); 5] = [
+    ("flood", "--flood"),
+    ("pmtu", "--pmtu"),
+    ("record_route", "--record-route"),
+    ("timestamp", "--timestamp"),
+    ("graph", "--graph"),
+];
+
+/// Ping multiple targets and print alive/unreachable summary.
+/// Targets on the same interface share one capture channel
+fn handle_multi_ping(
+    args: &ArgMatches,
+    targets: Vec<String>,
+    protocol: Protocol,
+    port: u16,
+    count: u32,
+    maxhop: u8,
+) {
+    let ping_args = match args.subcommand_matches("ping") {
+        Some(matches) => matches,
+        None => return,
+    };
+    for (id, name) in SINGLE_TARGET_OPTIONS {
+        if ping_args.get_flag(id) {
+            output::log_with_time(
+                &format!("{} is not supported with multiple targets", name),
+                "ERROR",
+            );
+            return;
+        }
+    }
+    let timeout = match ping_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_secs(30),
+    };
+    let wait_time = match ping_args.get_one::<u64>("waittime") {
+        Some(wait_time) => Duration::from_millis(*wait_time),
+        None => Duration::from_secs(1),
+    };
+    let send_rate = match ping_args.get_one::<u64>("rate") {
+        Some(send_rate) => Duration::from_millis(*send_rate),
+        None => Duration::from_secs(1),
+    };
+    let interval: Option<Duration> = match get_interval(ping_args) {
+        Ok(interval) => interval,
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    };
+    // Resolve targets. Duplicate addresses are pinged once
+    let mut result: MultiPingResult = MultiPingResult::new();
+    // Settings grouped by interface index
+    let mut settings: BTreeMap<u32, Vec<PingSetting>> = BTreeMap::new();
+    let mut known: HashSet<IpAddr> = HashSet::new();
+    for target in &targets {
+        let (dst_ip, dst_port, zone): (IpAddr, u16, Option<String>) =
+            match crate::ip::parse_ip_with_zone(target) {
+                Some((ip_addr, zone)) => (ip_addr, port, zone),
+                None => match SocketAddr::from_str(target) {
+                    Ok(socket_addr) => (socket_addr.ip(), socket_addr.port(), None),
+                    Err(_) => match crate::dns::lookup_host_name(target) {
+                        Some(ip_addr) => (ip_addr, port, None),
+                        None => {
+                            let mut host_result = HostPingResult::new(target.clone(), None);
+                            host_result.message = "Failed to resolve".to_string();
+                            result.hosts.push(host_result);
+                            continue;
+                        }
+                    },
+                },
+            };
+        if !known.insert(dst_ip) {
+            continue;
+        }
+        let interface: Interface = match get_ping_interface(args, &zone) {
+            Ok(iface) => iface,
+            Err(e) => {
+                let mut host_result = HostPingResult::new(target.clone(), Some(dst_ip));
+                host_result.message = e;
+                result.hosts.push(host_result);
+                continue;
+            }
+        };
+        let setting = match protocol {
+            Protocol::ICMP => PingSetting::icmp_ping(&interface, dst_ip, count),
+            Protocol::TCP => PingSetting::tcp_ping(&interface, dst_ip, dst_port, count),
+            Protocol::UDP => PingSetting::udp_ping(&interface, dst_ip, count),
+            _ => {
+                output::log_with_time("Unsupported protocol", "ERROR");
+                return;
+            }
+        };
+        let mut setting: PingSetting = match setting {
+            Ok(setting) => setting,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        };
+        setting.dst_hostname = target.clone();
+        setting.hop_limit = maxhop;
+        setting.receive_timeout = wait_time;
+        setting.probe_timeout = timeout;
+        setting.send_rate = send_rate;
+        setting.interval = interval;
+        set_probe_options(&mut setting, ping_args);
+        if let Err(e) = check_payload_size(&setting) {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+        if let Err(e) = set_link_local_mac(&mut setting, &interface) {
+            let mut host_result = HostPingResult::new(target.clone(), Some(dst_ip));
+            host_result.message = e;
+            result.hosts.push(host_result);
+            continue;
+        }
+        settings
+            .entry(interface.index)
+            .or_insert(Vec::new())
+            .push(setting);
+    }
+    output::log_with_time(
+        &format!(
+            "Pinging {} target(s), count {}...",
+            settings.values().map(|group| group.len()).sum::<usize>(),
+            count
+        ),
+        "INFO",
+    );
+    // Stop on Ctrl-C and print the summary
+    let stop_guard: StopGuard = crate::app::handle_stop();
+    let start_time = std::time::Instant::now();
+    let mut handles = Vec::new();
+    for (_, group) in settings {
+        let stop_handle: Arc<Mutex<bool>> = stop_guard.get_stop_handle();
+        handles.push(thread::spawn(move || {
+            let ping_results = crate::ping::multi::multi_ping(group.clone(), &stop_handle);
+            let mut host_results: Vec<HostPingResult> = Vec::new();
+            for (i, setting) in group.into_iter().enumerate() {
+                let mut host_result =
+                    HostPingResult::new(setting.dst_hostname.clone(), Some(setting.dst_ip));
+                match &ping_results {
+                    Ok(ping_results) => {
+                        if let Some(ping_result) = ping_results.get(i) {
+                            host_result.alive = ping_result.probe_status.kind
+                                == crate::probe::ProbeStatusKind::Done;
+                            host_result.message = ping_result.probe_status.message.clone();
+                            host_result.stat = ping_result.stat.clone();
+                        }
+                    }
+                    Err(e) => host_result.message = e.clone(),
+                }
+                host_results.push(host_result);
+            }
+            host_results
+        }));
+    }
+    for handle in handles {
+        match handle.join() {
+            Ok(host_results) => result.hosts.extend(host_results),
+            Err(e) => output::log_with_time(&format!("{:?}", e), "ERROR"),
+        }
+    }
+    result.elapsed_time = start_time.elapsed();
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_multi_ping_result(&result);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+    // Worst loss among targets
+    let max_loss: f64 = result
+        .hosts
+        .iter()
+        .map(|host| loss_percent(&host.stat))
+        .fold(0.0, f64::max);
+    check_loss_threshold(ping_args, max_loss);
+}
+
+/// Read target list file. One IP address or host name per line. Lines starting with `#` are ignored.
+pub fn read_target_list(file_path: &PathBuf) -> Result<Vec<String>, String> {
+    let contents: String = match std::fs::read_to_string(file_path) {
+        Ok(contents) => contents,
+        Err(e) => return Err(e.to_string()),
+    };
+    Ok(contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect())
+}
+
+fn show_multi_ping_result(result: &MultiPingResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("Ping Result", None, None));
+    let mut alive_tree = Tree::new(node_label("Alive", None, None));
+    let mut unreachable_tree = Tree::new(node_label("Unreachable", None, None));
+    for host in &result.hosts {
+        let target: String = match host.ip_addr {
+            Some(ip_addr) if ip_addr.to_string() != host.target => {
+                format!("{}({})", host.target, ip_addr)
+            }
+            _ => host.target.clone(),
+        };
+        if host.alive {
+            let loss: f64 = loss_percent(&host.stat);
+            alive_tree.push(node_label(
+                &target,
+                Some(&format!(
+                    "{}/{} received, {:.1}% loss, min/avg/max {:?}/{:?}/{:?}",
+                    host.stat.received_count,
+                    host.stat.transmitted_count,
+                    loss,
+                    host.stat.min,
+                    host.stat.avg,
+                    host.stat.max
+                )),
+                None,
+            ));
+        } else {
+            unreachable_tree.push(node_label(&target, Some(&host.message), None));
+        }
+    }
+    tree.push(alive_tree);
+    tree.push(unreachable_tree);
+    let alive_count: usize = result.hosts.iter().filter(|host| host.alive).count();
+    tree.push(node_label(
+        "Summary",
+        Some(&format!(
+            "{} alive, {} unreachable ({:?})",
+            alive_count,
+            result.hosts.len() - alive_count,
+            result.elapsed_time
+        )),
+        None,
+    ));
+    println!("{}", tree);
+}
+
+fn handle_pmtu(
+    args: &ArgMatches,
+    setting: PingSetting,
+    interface: &Interface,
+    target_addr: String,
+) {
+    let max_mtu: usize = interface.mtu.unwrap_or(1500) as usize;
+    output::log_with_time(
+        &format!(
+            "Discovering path MTU to {} (interface MTU {})...",
+            target_addr, max_mtu
+        ),
+        "INFO",
+    );
+    let pmtu_result: PmtuResult = match crate::ping::pmtu::discover_pmtu(&setting, max_mtu) {
+        Ok(pmtu_result) => pmtu_result,
+        Err(e) => {
+            output::log_with_time(&format!("Failed to discover path MTU: {}", e), "ERROR");
+            return;
+        }
+    };
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&pmtu_result).unwrap();
+        println!("{}", json_result);
+    } else {
+        if !crate::app::is_quiet_mode() {
+            println!();
+        }
+        let mut tree = Tree::new(node_label(
+            &format!("Path MTU Result - {}", target_addr),
+            None,
+            None,
+        ));
+        tree.push(node_label(
+            "Path MTU",
+            Some(&pmtu_result.mtu.to_string()),
+            None,
+        ));
+        tree.push(node_label(
+            "Max Payload Size",
+            Some(&pmtu_result.payload_size.to_string()),
+            None,
+        ));
+        tree.push(node_label(
+            "Probes",
+            Some(&pmtu_result.probes.to_string()),
+            None,
+        ));
+        tree.push(node_label(
+            "Elapsed Time",
+            Some(&format!("{:?}", pmtu_result.elapsed_time)),
+            None,
+        ));
+        println!("{}", tree);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(
+                file_path,
+                serde_json::to_string_pretty(&pmtu_result).unwrap(),
+            ) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
 }
 
 fn print_option(setting: &PingSetting, interface: &Interface) {
@@ -375,11 +955,15 @@ fn print_option(setting: &PingSetting, interface: &Interface) {
         Some(format!("{:?}", setting.protocol).as_str()),
         None,
     ));
-    setting_tree.push(node_label(
-        "Count",
-        Some(setting.count.to_string().as_str()),
-        None,
-    ));
+    if setting.count == 0 {
+        setting_tree.push(node_label("Count", Some("Until interrupted"), None));
+    } else {
+        setting_tree.push(node_label(
+            "Count",
+            Some(setting.count.to_string().as_str()),
+            None,
+        ));
+    }
     setting_tree.push(node_label(
         "Hop Limit",
         Some(setting.hop_limit.to_string().as_str()),
@@ -395,11 +979,49 @@ fn print_option(setting: &PingSetting, interface: &Interface) {
         Some(format!("{:?}", setting.receive_timeout).as_str()),
         None,
     ));
-    setting_tree.push(node_label(
-        "Send Rate",
-        Some(format!("{:?}", setting.send_rate).as_str()),
-        None,
-    ));
+    if let Some(deadline) = setting.deadline {
+        setting_tree.push(node_label(
+            "Deadline",
+            Some(format!("{:?}", deadline).as_str()),
+            None,
+        ));
+    }
+    match setting.interval {
+        Some(interval) => {
+            setting_tree.push(node_label(
+                "Interval",
+                Some(format!("{:?}", interval).as_str()),
+                None,
+            ));
+        }
+        None => {
+            setting_tree.push(node_label(
+                "Send Rate",
+                Some(format!("{:?}", setting.send_rate).as_str()),
+                None,
+            ));
+        }
+    }
+    if setting.payload_size > 0 {
+        setting_tree.push(node_label(
+            "Payload Size",
+            Some(setting.payload_size.to_string().as_str()),
+            None,
+        ));
+    }
+    if setting.dont_fragment {
+        setting_tree.push(node_label("Don't Fragment", Some("true"), None));
+    }
+    if setting.tos != 0 {
+        setting_tree.push(node_label(
+            "TOS",
+            Some(&super::format_tos(setting.tos)),
+            None,
+        ));
+    }
+    if let Some(ipv4_option) = setting.ipv4_option {
+        setting_tree.push(node_label("IP Option", Some(ipv4_option.to_str()), None));
+    }
     tree.push(setting_tree);
     // Target
     let mut target_tree = Tree::new(node_label("Target", None, None));
@@ -418,6 +1040,38 @@ fn print_option(setting: &PingSetting, interface: &Interface) {
     println!("{}", tree);
 }
 
+/// Print decoded record route / timestamp options of the reply
+fn log_ip_options(ip_options: &Ipv4OptionData) {
+    if !ip_options.route.is_empty() {
+        let route: Vec<String> = ip_options.route.iter().map(|a| a.to_string()).collect();
+        output::log_with_time(&format!("  RR: {}", route.join(" -> ")), "INFO");
+    }
+    if !ip_options.timestamps.is_empty() {
+        let timestamps: Vec<String> = ip_options
+            .timestamps
+            .iter()
+            .map(|t| format_ip_timestamp(*t))
+            .collect();
+        output::log_with_time(&format!("  TS: {}", timestamps.join(", ")), "INFO");
+    }
+}
+
+/// Format IP timestamp (milliseconds since midnight UT)
+fn format_ip_timestamp(ts: u32) -> String {
+    // High-order bit set means non-standard time value
+    if ts & 0x8000_0000 != 0 {
+        return format!("{} (non-standard)", ts & 0x7fff_ffff);
+    }
+    let secs: u32 = ts / 1000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03} UT",
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60,
+        ts % 1000
+    )
+}
+
 fn show_ping_result(ping_result: &PingResult, target_addr: String) {
     if !crate::app::is_quiet_mode() {
         println!();
@@ -456,7 +1110,11 @@ fn show_ping_result(ping_result: &PingResult, target_addr: String) {
             None,
         ));
         response_tree.push(node_label(
-            "TTL",
+            if response.ip_addr.is_ipv6() {
+                "Hop Limit"
+            } else {
+                "TTL"
+            },
             Some(response.ttl.to_string().as_str()),
             None,
         ));
@@ -465,53 +1123,156 @@ fn show_ping_result(ping_result: &PingResult, target_addr: String) {
             Some(format!("{:?}", response.rtt).as_str()),
             None,
         ));
+        if let Some(ip_options) = &response.ip_options {
+            if !ip_options.route.is_empty() {
+                let mut route_tree = Tree::new(node_label("Record Route", None, None));
+                for addr in &ip_options.route {
+                    route_tree.push(node_label(&addr.to_string(), None, None));
+                }
+                response_tree.push(route_tree);
+            }
+            if !ip_options.timestamps.is_empty() {
+                let mut ts_tree = Tree::new(node_label("Timestamps", None, None));
+                for ts in &ip_options.timestamps {
+                    ts_tree.push(node_label(&format_ip_timestamp(*ts), None, None));
+                }
+                response_tree.push(ts_tree);
+            }
+        }
 
         responses_tree.push(response_tree);
     }
-    tree.push(responses_tree);
+    // Quiet mode shows statistics only
+    if !crate::app::is_quiet_mode() {
+        tree.push(responses_tree);
+    }
+    tree.push(stat_tree(&ping_result.stat));
+
+    println!("{}", tree);
+}
 
-    // Statistics
+fn show_rtt_graph(ping_result: &PingResult) {
+    let mut tree = Tree::new(node_label("RTT Graph", None, None));
+    tree.push(node_label(
+        &crate::util::sparkline::sparkline(
+            &ping_result.stat.rtt_series,
+            crate::config::RTT_GRAPH_WIDTH,
+        ),
+        None,
+        None,
+    ));
+    tree.push(node_label(
+        "Scale",
+        Some(&format!(
+            "{:?} - {:?} (· lost)",
+            ping_result.stat.min, ping_result.stat.max
+        )),
+        None,
+    ));
+    println!("{}", tree);
+}
+
+fn show_flood_result(ping_result: &PingResult, target_addr: String) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label(
+        &format!("Flood Ping Result - {}", target_addr),
+        None,
+        None,
+    ));
+    let probe_secs: f64 = ping_result.stat.probe_time.as_secs_f64();
+    let mut throughput_tree = Tree::new(node_label("Throughput", None, None));
+    throughput_tree.push(node_label(
+        "Elapsed Time",
+        Some(format!("{:?}", ping_result.stat.probe_time).as_str()),
+        None,
+    ));
+    if probe_secs > 0.0 {
+        throughput_tree.push(node_label(
+            "Sent",
+            Some(
+                format!(
+                    "{:.1} packets/s",
+                    ping_result.stat.transmitted_count as f64 / probe_secs
+                )
+                .as_str(),
+            ),
+            None,
+        ));
+        throughput_tree.push(node_label(
+            "Received",
+            Some(
+                format!(
+                    "{:.1} packets/s",
+                    ping_result.stat.received_count as f64 / probe_secs
+                )
+                .as_str(),
+            ),
+            None,
+        ));
+    }
+    tree.push(throughput_tree);
+    tree.push(stat_tree(&ping_result.stat));
+    println!("{}", tree);
+}
+
+fn stat_tree(stat: &PingStat) -> Tree<String> {
     let mut stat_tree = Tree::new(node_label("Statistics", None, None));
     stat_tree.push(node_label(
         "Transmitted",
-        Some(format!("{}", ping_result.stat.transmitted_count).as_str()),
+        Some(format!("{}", stat.transmitted_count).as_str()),
         None,
     ));
     stat_tree.push(node_label(
         "Received",
-        Some(format!("{}", ping_result.stat.received_count).as_str()),
+        Some(format!("{}", stat.received_count).as_str()),
         None,
     ));
     stat_tree.push(node_label(
         "Loss",
-        Some(
-            format!(
-                "{}%",
-                100.0
-                    - (ping_result.stat.received_count as f64
-                        / ping_result.stat.transmitted_count as f64)
-                        * 100.0
-            )
-            .as_str(),
-        ),
+        Some(format!("{}%", loss_percent(stat)).as_str()),
         None,
     ));
     stat_tree.push(node_label(
         "Min",
-        Some(format!("{:?}", ping_result.stat.min).as_str()),
+        Some(format!("{:?}", stat.min).as_str()),
         None,
     ));
     stat_tree.push(node_label(
         "Max",
-        Some(format!("{:?}", ping_result.stat.max).as_str()),
+        Some(format!("{:?}", stat.max).as_str()),
         None,
     ));
     stat_tree.push(node_label(
         "Avg",
-        Some(format!("{:?}", ping_result.stat.avg).as_str()),
+        Some(format!("{:?}", stat.avg).as_str()),
         None,
     ));
-    tree.push(stat_tree);
-
-    println!("{}", tree);
+    stat_tree.push(node_label(
+        "Mdev",
+        Some(format!("{:?}", stat.mdev).as_str()),
+        None,
+    ));
+    stat_tree.push(node_label(
+        "Jitter",
+        Some(format!("{:?}", stat.jitter).as_str()),
+        None,
+    ));
+    stat_tree.push(node_label(
+        "P50",
+        Some(format!("{:?}", stat.p50).as_str()),
+        None,
+    ));
+    stat_tree.push(node_label(
+        "P95",
+        Some(format!("{:?}", stat.p95).as_str()),
+        None,
+    ));
+    stat_tree.push(node_label(
+        "P99",
+        Some(format!("{:?}", stat.p99).as_str()),
+        None,
+    ));
+    stat_tree
 }
@@ -1,10 +1,14 @@
 use crate::db::model::OsFamilyFingerprint;
+use crate::enrich::ExternalInfo;
 use crate::host::{Host, PortStatus};
+use crate::json::host::HostScanResult;
 use crate::json::port::PortScanResult;
 use crate::output;
-use crate::scan::result::ScanResult;
+use crate::scan::result::{ScanResult, ScanStatus};
 use crate::scan::scanner::{PortScanner, ServiceDetector};
 use crate::scan::setting::{PortScanSetting, PortScanType, ServiceProbeSetting};
+use crate::smb::SmbInfo;
+use crate::ssh::SshAuditResult;
 use crate::util::tree::node_label;
 use clap::ArgMatches;
 use indicatif::{ProgressBar, ProgressDrawTarget};
@@ -18,14 +22,168 @@ use std::time::Duration;
 use termtree::Tree;
 
 pub fn handle_portscan(args: &ArgMatches) {
+    if let Some(port_args) = args.subcommand_matches("port") {
+        if let Some(file_path) = port_args.get_one::<PathBuf>("masscan_input") {
+            handle_masscan_input(args, file_path);
+            return;
+        }
+        if port_args.contains_id("watch") {
+            super::watch::run_watch(args, "port", port_args, || {
+                scan_ports(args).map(|result| vec![result.host])
+            });
+            return;
+        }
+    }
+    let result: PortScanResult = match scan_ports(args) {
+        Some(result) => result,
+        None => return,
+    };
+    // Print results
+    if !super::print_masscan(args, std::slice::from_ref(&result.host)) {
+        if args.get_flag("json") {
+            let json_result = serde_json::to_string_pretty(&result).unwrap();
+            println!("{}", json_result);
+        } else {
+            show_portscan_result(&result.host);
+        }
+    }
+
+    output::log_with_time(
+        &format!("Total elapsed time {:?} ", result.total_scan_time),
+        "INFO",
+    );
+
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+    super::export_hosts(args, std::slice::from_ref(&result.host));
+}
+
+/// Run service detection on the open ports listed in masscan output. Ports are not re-scanned
+fn handle_masscan_input(args: &ArgMatches, file_path: &PathBuf) {
+    let records = match std::fs::read_to_string(file_path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| crate::masscan::parse(&text))
+    {
+        Ok(records) => records,
+        Err(e) => {
+            output::log_with_time(
+                &format!("Failed to read {}: {}", file_path.to_string_lossy(), e),
+                "ERROR",
+            );
+            return;
+        }
+    };
+    let mut hosts: Vec<Host> = crate::masscan::records_to_hosts(&records);
+    if hosts.is_empty() {
+        output::log_with_time(
+            &format!("No open TCP ports in {}", file_path.to_string_lossy()),
+            "ERROR",
+        );
+        return;
+    }
+    output::log_with_time(
+        &format!(
+            "Loaded {} open ports on {} hosts from {}",
+            hosts.iter().map(|h| h.ports.len()).sum::<usize>(),
+            hosts.len(),
+            file_path.to_string_lossy()
+        ),
+        "INFO",
+    );
+    let start_time = std::time::Instant::now();
+    let bar = ProgressBar::new(hosts.len() as u64);
+    if crate::app::is_quiet_mode() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar.enable_steady_tick(120);
+    bar.set_style(output::get_progress_style());
+    bar.set_position(0);
+    bar.set_message("ServiceDetection");
+    for host in &mut hosts {
+        let probe_setting: ServiceProbeSetting = ServiceProbeSetting::default(
+            host.ip_addr,
+            host.hostname.clone(),
+            host.get_open_port_numbers(),
+        );
+        let service_result = ServiceDetector::new(probe_setting).run();
+        for port in &mut host.ports {
+            if let Some(result) = service_result.get(&port.number) {
+                port.service_name = result.service_name.clone();
+                port.service_version = result.service_detail.clone().unwrap_or(String::new());
+                port.cpes = crate::cve::service_cpes(&port.service_version);
+            }
+        }
+        bar.inc(1);
+    }
+    bar.finish_with_message(format!("ServiceDetection ({:?})", start_time.elapsed()));
+    if let Some(geoip_reader) = super::get_geoip_reader(args) {
+        for host in &mut hosts {
+            host.geo = geoip_reader.lookup(&host.ip_addr);
+        }
+    }
+    if let Some(mut cve_lookup) = super::get_cve_lookup(args) {
+        for host in &mut hosts {
+            super::set_cves(&mut cve_lookup, &mut host.ports);
+        }
+    }
+    super::enrich_hosts(args, &mut hosts, None);
+    let mut result: HostScanResult = HostScanResult::new();
+    result.hosts = hosts;
+    result.scan_time = start_time.elapsed();
+    result.scan_status = ScanStatus::Done;
+    if !super::print_masscan(args, &result.hosts) {
+        if args.get_flag("json") {
+            let json_result = serde_json::to_string_pretty(&result).unwrap();
+            println!("{}", json_result);
+        } else {
+            for host in &result.hosts {
+                show_portscan_result(host);
+            }
+        }
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+    super::export_hosts(args, &result.hosts);
+}
+
+/// Run port scan and service detection of the target
+pub fn scan_ports(args: &ArgMatches) -> Option<PortScanResult> {
     output::log_with_time("Initiating port scan...", "INFO");
     let port_args = match args.subcommand_matches("port") {
         Some(matches) => matches,
-        None => return,
+        None => return None,
     };
     let target: String = match port_args.get_one::<String>("target") {
         Some(target) => target.to_owned(),
-        None => return,
+        None => return None,
     };
     let target_host_name: String;
     let target_ip_addr: IpAddr;
@@ -37,7 +195,7 @@ pub fn handle_portscan(args: &ArgMatches) {
         target_host_name = target.clone();
         target_ip_addr = match crate::dns::lookup_host_name(&target) {
             Some(ip) => ip,
-            None => return,
+            None => return None,
         };
     }
     if port_args.contains_id("ports") {
@@ -71,12 +229,12 @@ pub fn handle_portscan(args: &ArgMatches) {
     let interface: netdev::Interface = if let Some(if_name) = args.get_one::<String>("interface") {
         match crate::interface::get_interface_by_name(if_name.to_string()) {
             Some(iface) => iface,
-            None => return,
+            None => return None,
         }
     } else {
         match netdev::get_default_interface() {
             Ok(iface) => iface,
-            Err(_) => return,
+            Err(_) => return None,
         }
     };
     // Check reachability by ping (one-shot)
@@ -97,7 +255,7 @@ pub fn handle_portscan(args: &ArgMatches) {
                     &format!("{} You can disable this initial ping by --noping", e),
                     "ERROR",
                 );
-                return;
+                return None;
             }
         }
     }
@@ -161,7 +319,7 @@ pub fn handle_portscan(args: &ArgMatches) {
 
     if portscan_result.hosts.len() == 0 {
         output::log_with_time("No results found", "INFO");
-        return;
+        return None;
     }
 
     portscan_result.sort_ports();
@@ -200,6 +358,7 @@ pub fn handle_portscan(args: &ArgMatches) {
         if let Some(result) = service_result.get(&port.number) {
             port.service_name = result.service_name.clone();
             port.service_version = result.service_detail.clone().unwrap_or(String::new());
+            port.cpes = crate::cve::service_cpes(&port.service_version);
         }
     }
     // OS detection
@@ -217,54 +376,58 @@ pub fn handle_portscan(args: &ArgMatches) {
         if let Some(h) = portscan_result.get_host(result.host.ip_addr) {
             if h.mac_addr != MacAddr::zero() {
                 let oui_map: HashMap<String, String> = crate::db::get_oui_detail_map();
-                let vendor_name = if h.mac_addr.address().len() > 16 {
-                    let prefix8 = h.mac_addr.address()[0..8].to_uppercase();
-                    oui_map.get(&prefix8).unwrap_or(&String::new()).to_string()
-                } else {
-                    oui_map
-                        .get(&h.mac_addr.address())
-                        .unwrap_or(&String::new())
-                        .to_string()
-                };
+                let vendor_name = crate::db::get_vendor_name(&oui_map, &h.mac_addr);
                 result.host.mac_addr = h.mac_addr;
                 result.host.vendor_name = vendor_name;
             }
         }
     }
+    // Set geolocation from the GeoIP database
+    if let Some(geoip_reader) = super::get_geoip_reader(args) {
+        result.host.geo = geoip_reader.lookup(&result.host.ip_addr);
+    }
+    // SMB host information
+    if port_args.get_flag("smb") {
+        result.host.smb = crate::smb::enumerate_host(
+            result.host.ip_addr,
+            &result.host.get_open_port_numbers(),
+            Duration::from_millis(crate::config::DEFAULT_SMB_TIMEOUT_MILLIS),
+        );
+    }
+    // SSH algorithm audit
+    if port_args.get_flag("ssh_audit") {
+        let ip_addr: IpAddr = result.host.ip_addr;
+        for port in &mut result.host.ports {
+            if port.status != PortStatus::Open
+                || (port.number != crate::ssh::SSH_PORT && port.service_name != "ssh")
+            {
+                continue;
+            }
+            match crate::ssh::audit(
+                ip_addr,
+                port.number,
+                Duration::from_millis(crate::config::DEFAULT_SSH_TIMEOUT_MILLIS),
+            ) {
+                Ok(ssh) => port.ssh = Some(ssh),
+                Err(e) => output::log_with_time(
+                    &format!("SSH audit of port {} failed: {}", port.number, e),
+                    "WARN",
+                ),
+            }
+        }
+    }
+    // Known CVEs of the detected CPEs
+    if let Some(mut cve_lookup) = super::get_cve_lookup(args) {
+        super::set_cves(&mut cve_lookup, &mut result.host.ports);
+    }
+    // Ports and tags seen from the internet
+    super::enrich_hosts(args, std::slice::from_mut(&mut result.host), None);
     result.host.ttl = portscan_result.hosts[0].ttl;
     result.port_scan_time = portscan_result.scan_time;
     result.service_detection_time = sd_elapsed_time;
     result.total_scan_time = portscan_result.scan_time + sd_elapsed_time;
     result.scan_status = portscan_result.scan_status;
-    // Print results
-    if args.get_flag("json") {
-        let json_result = serde_json::to_string_pretty(&result).unwrap();
-        println!("{}", json_result);
-    } else {
-        show_portscan_result(&result.host);
-    }
-
-    output::log_with_time(
-        &format!("Total elapsed time {:?} ", result.total_scan_time),
-        "INFO",
-    );
-
-    match args.get_one::<PathBuf>("save") {
-        Some(file_path) => {
-            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
-                Ok(_) => {
-                    output::log_with_time(
-                        &format!("Saved to {}", file_path.to_string_lossy()),
-                        "INFO",
-                    );
-                }
-                Err(e) => {
-                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
-                }
-            }
-        }
-        None => {}
-    }
+    Some(result)
 }
 
 pub fn print_option(setting: &PortScanSetting, interface: &Interface) {
@@ -330,6 +493,117 @@ pub fn print_option(setting: &PortScanSetting, interface: &Interface) {
     println!("{}", tree);
 }
 
+/// Ports and tags seen by Shodan or Censys, with the differences from the scan
+pub fn external_tree(external: &ExternalInfo) -> Tree<String> {
+    let join = |ports: &Vec<u16>| {
+        ports
+            .iter()
+            .map(|port| port.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    let mut tree = Tree::new(node_label(
+        &format!("External ({})", external.source),
+        None,
+        None,
+    ));
+    tree.push(node_label("Ports", Some(&join(&external.ports)), None));
+    if !external.tags.is_empty() {
+        tree.push(node_label("Tags", Some(&external.tags.join(", ")), None));
+    }
+    if !external.last_update.is_empty() {
+        tree.push(node_label("Last Update", Some(&external.last_update), None));
+    }
+    if !external.missing_locally.is_empty() {
+        tree.push(node_label(
+            "Not Open In Scan",
+            Some(&join(&external.missing_locally)),
+            None,
+        ));
+    }
+    if !external.not_scanned.is_empty() {
+        tree.push(node_label(
+            "Not Scanned",
+            Some(&join(&external.not_scanned)),
+            None,
+        ));
+    }
+    if !external.missing_externally.is_empty() {
+        tree.push(node_label(
+            "Not Seen Externally",
+            Some(&join(&external.missing_externally)),
+            None,
+        ));
+    }
+    tree
+}
+
+fn smb_tree(smb: &SmbInfo) -> Tree<String> {
+    let mut tree = Tree::new(node_label("SMB", None, None));
+    tree.push(node_label("Port", Some(&smb.port.to_string()), None));
+    tree.push(node_label("Dialect", Some(&smb.dialect), None));
+    tree.push(node_label("Signing", Some(smb.signing_label()), None));
+    if !smb.os_version.is_empty() {
+        tree.push(node_label("OS Version", Some(&smb.os_version), None));
+    }
+    if !smb.netbios_name.is_empty() {
+        tree.push(node_label("NetBIOS Name", Some(&smb.netbios_name), None));
+    }
+    if !smb.netbios_domain.is_empty() {
+        tree.push(node_label(
+            "Domain/Workgroup",
+            Some(&smb.netbios_domain),
+            None,
+        ));
+    }
+    if !smb.dns_name.is_empty() {
+        tree.push(node_label("DNS Name", Some(&smb.dns_name), None));
+    }
+    if !smb.dns_domain.is_empty() {
+        tree.push(node_label("DNS Domain", Some(&smb.dns_domain), None));
+    }
+    if !smb.dns_forest.is_empty() && smb.dns_forest != smb.dns_domain {
+        tree.push(node_label("DNS Forest", Some(&smb.dns_forest), None));
+    }
+    if let Some(system_time) = &smb.system_time {
+        tree.push(node_label(
+            "System Time",
+            Some(&system_time.to_rfc3339()),
+            None,
+        ));
+    }
+    tree
+}
+
+fn ssh_tree(ssh: &SshAuditResult) -> Tree<String> {
+    let mut tree = Tree::new(node_label("SSH", None, None));
+    tree.push(node_label("Banner", Some(&ssh.banner), None));
+    let lists: [(&str, &Vec<String>); 4] = [
+        ("Key Exchange", &ssh.kex_algorithms),
+        ("Host Key", &ssh.host_key_algorithms),
+        ("Cipher", &ssh.encryption_algorithms),
+        ("MAC", &ssh.mac_algorithms),
+    ];
+    for (label, algorithms) in lists {
+        if algorithms.is_empty() {
+            continue;
+        }
+        let mut list_tree = Tree::new(node_label(label, None, None));
+        for algorithm in algorithms {
+            list_tree.push(node_label(algorithm, None, None));
+        }
+        tree.push(list_tree);
+    }
+    if !ssh.issues.is_empty() {
+        let mut issues_tree = Tree::new(node_label("Issues", None, None));
+        for issue in &ssh.issues {
+            issues_tree.push(node_label(issue, None, None));
+        }
+        tree.push(issues_tree);
+    }
+    tree
+}
+
 pub fn show_portscan_result(host: &Host) {
     if !crate::app::is_quiet_mode() {
         println!();
@@ -365,6 +639,21 @@ pub fn show_portscan_result(host: &Host) {
     if !host.os_family.is_empty() {
         host_tree.push(node_label("OS Family", Some(&host.os_family), None));
     }
+    if let Some(geo) = &host.geo {
+        let location: String = geo.label();
+        if !location.is_empty() {
+            host_tree.push(node_label("Location", Some(&location), None));
+        }
+        if let Some(as_label) = geo.as_label() {
+            host_tree.push(node_label("ASN", Some(&as_label), None));
+        }
+    }
+    if let Some(smb) = &host.smb {
+        host_tree.push(smb_tree(smb));
+    }
+    if let Some(external) = &host.external {
+        host_tree.push(external_tree(external));
+    }
     let mut port_info_tree = Tree::new(node_label("Port Info", None, None));
     for port in &host.ports {
         if port.status == PortStatus::Open {
@@ -376,6 +665,19 @@ pub fn show_portscan_result(host: &Host) {
                 Some(&port.service_version),
                 None,
             ));
+            if let Some(ssh) = &port.ssh {
+                port_tree.push(ssh_tree(ssh));
+            }
+            if !port.cpes.is_empty() {
+                port_tree.push(node_label("CPE", Some(&port.cpes.join(", ")), None));
+            }
+            if !port.cves.is_empty() {
+                let mut cves_tree = Tree::new(node_label("CVEs", None, None));
+                for cve in &port.cves {
+                    cves_tree.push(node_label(&cve.label(), None, None));
+                }
+                port_tree.push(cves_tree);
+            }
             port_info_tree.push(port_tree);
         }
     }
@@ -0,0 +1,147 @@
+use crate::output;
+use crate::schedule::cron::CronSchedule;
+use crate::schedule::ScheduleEntry;
+use crate::serve::JobRequest;
+use crate::util::tree::node_label;
+use chrono::Local;
+use clap::ArgMatches;
+use std::path::PathBuf;
+use termtree::Tree;
+
+pub fn handle_schedule(args: &ArgMatches) {
+    let schedule_args = match args.subcommand_matches("schedule") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let app_dir: Option<PathBuf> = crate::fs::app_dir();
+    let config_path: PathBuf = match schedule_args
+        .get_one::<PathBuf>("config")
+        .cloned()
+        .or(app_dir.as_ref().map(|dir| dir.join("schedules.json")))
+    {
+        Some(config_path) => config_path,
+        None => {
+            output::log_with_time("Home directory not found. Specify --config", "ERROR");
+            return;
+        }
+    };
+    let history_dir: PathBuf = match schedule_args
+        .get_one::<PathBuf>("history")
+        .cloned()
+        .or(crate::schedule::default_history_dir())
+    {
+        Some(history_dir) => history_dir,
+        None => {
+            output::log_with_time("Home directory not found. Specify --history", "ERROR");
+            return;
+        }
+    };
+    let mut entries: Vec<ScheduleEntry> = match crate::schedule::load_schedules(&config_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+    };
+    if let Some(name) = schedule_args.get_one::<String>("add") {
+        let cron: &String = schedule_args.get_one::<String>("cron").unwrap();
+        let job_line: &String = schedule_args.get_one::<String>("job").unwrap();
+        let job: JobRequest = match JobRequest::from_line(job_line) {
+            Some(job) => job,
+            None => {
+                output::log_with_time(
+                    "--job must be a command and target. e.g. \"host 192.168.1.0/24\"",
+                    "ERROR",
+                );
+                return;
+            }
+        };
+        let entry = ScheduleEntry {
+            name: name.to_string(),
+            cron: cron.to_string(),
+            job: job,
+        };
+        if entries.iter().any(|e| e.name == entry.name) {
+            output::log_with_time(&format!("Schedule {} already exists", entry.name), "ERROR");
+            return;
+        }
+        if let Err(e) = crate::schedule::validate(&entry) {
+            output::log_with_time(&e, "ERROR");
+            return;
+        }
+        entries.push(entry);
+        match crate::schedule::save_schedules(&config_path, &entries) {
+            Ok(_) => output::log_with_time(
+                &format!("Added schedule {} to {}", name, config_path.display()),
+                "INFO",
+            ),
+            Err(e) => output::log_with_time(&e, "ERROR"),
+        }
+        return;
+    }
+    if let Some(name) = schedule_args.get_one::<String>("remove") {
+        let count: usize = entries.len();
+        entries.retain(|e| e.name != *name);
+        if entries.len() == count {
+            output::log_with_time(&format!("Schedule {} not found", name), "ERROR");
+            return;
+        }
+        match crate::schedule::save_schedules(&config_path, &entries) {
+            Ok(_) => output::log_with_time(&format!("Removed schedule {}", name), "INFO"),
+            Err(e) => output::log_with_time(&e, "ERROR"),
+        }
+        return;
+    }
+    if schedule_args.get_flag("list") {
+        if args.get_flag("json") {
+            println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+        } else {
+            show_schedules(&entries);
+        }
+        return;
+    }
+    if entries.is_empty() {
+        output::log_with_time(
+            &format!(
+                "No schedules in {}. Add one with: nrev schedule --add <name> --cron <expr> --job <command line>",
+                config_path.display()
+            ),
+            "ERROR",
+        );
+        return;
+    }
+    if !crate::app::is_quiet_mode() {
+        show_schedules(&entries);
+    }
+    output::log_with_time(
+        &format!(
+            "Running {} schedule(s). History: {}",
+            entries.len(),
+            history_dir.display()
+        ),
+        "INFO",
+    );
+    if let Err(e) = crate::schedule::run_scheduler(entries, history_dir) {
+        output::log_with_time(&e, "ERROR");
+    }
+}
+
+fn show_schedules(entries: &[ScheduleEntry]) {
+    println!();
+    let mut tree = Tree::new(node_label("Schedules", None, None));
+    for entry in entries {
+        let mut entry_tree = Tree::new(node_label(&entry.name, None, None));
+        entry_tree.push(node_label("Cron", Some(&entry.cron), None));
+        entry_tree.push(node_label("Job", Some(&entry.job_line()), None));
+        let next_run: String = match CronSchedule::parse(&entry.cron) {
+            Ok(cron) => match cron.next_after(&Local::now()) {
+                Some(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+                None => "Never".to_string(),
+            },
+            Err(e) => e,
+        };
+        entry_tree.push(node_label("Next Run", Some(&next_run), None));
+        tree.push(entry_tree);
+    }
+    println!("{}", tree);
+}
@@ -0,0 +1,57 @@
+use crate::output;
+use crate::serve::ServeSetting;
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use std::net::SocketAddr;
+use termtree::Tree;
+
+pub fn handle_serve(args: &ArgMatches) {
+    let serve_args = match args.subcommand_matches("serve") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let listen_addr: SocketAddr = match serve_args.get_one::<SocketAddr>("listen") {
+        Some(listen_addr) => *listen_addr,
+        None => crate::config::DEFAULT_SERVE_LISTEN_ADDR.parse().unwrap(),
+    };
+    let setting = ServeSetting {
+        listen_addr: listen_addr,
+        token: serve_args.get_one::<String>("token").cloned(),
+    };
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("REST API", None, None));
+        tree.push(node_label("Listen", Some(&listen_addr.to_string()), None));
+        tree.push(node_label(
+            "Token",
+            Some(if setting.token.is_some() {
+                "Required"
+            } else {
+                "None"
+            }),
+            None,
+        ));
+        let mut endpoints_tree = Tree::new(node_label("Endpoints", None, None));
+        for endpoint in [
+            "POST /jobs",
+            "GET /jobs",
+            "GET /jobs/{id}",
+            "GET /jobs/{id}/result",
+            "GET /version",
+        ] {
+            endpoints_tree.push(node_label(endpoint, None, None));
+        }
+        tree.push(endpoints_tree);
+        println!("{}", tree);
+    }
+    if !listen_addr.ip().is_loopback() && setting.token.is_none() {
+        output::log_with_time(
+            "Listening on a non-loopback address without --token. Anyone who can reach it can run scans",
+            "WARN",
+        );
+    }
+    output::log_with_time(&format!("Listening on {}", listen_addr), "INFO");
+    if let Err(e) = crate::serve::serve(setting) {
+        output::log_with_time(&e, "ERROR");
+    }
+}
@@ -0,0 +1,179 @@
+use crate::output;
+use crate::snmp::{SnmpDevice, SnmpSetting, SnmpSweepResult, SnmpVersion};
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use termtree::Tree;
+use tokio::runtime::Runtime;
+
+/// Uptime as days, hours, minutes and seconds
+fn format_uptime(uptime: Duration) -> String {
+    let secs: u64 = uptime.as_secs();
+    format!(
+        "{}d {:02}h {:02}m {:02}s",
+        secs / 86400,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+pub fn handle_snmp_sweep(args: &ArgMatches) {
+    let snmp_args = match args.subcommand_matches("snmp") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let target: String = match snmp_args.get_one::<String>("target") {
+        Some(target) => target.to_owned(),
+        None => return,
+    };
+    let ips: Vec<IpAddr> = match crate::ip::expand_ip_target(&target) {
+        Some(ips) => ips,
+        None => {
+            output::log_with_time(&format!("Invalid target: {}", target), "ERROR");
+            return;
+        }
+    };
+    let communities: Vec<String> = match snmp_args.get_many::<String>("community") {
+        Some(communities) => communities.cloned().collect(),
+        None => crate::snmp::DEFAULT_COMMUNITIES
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+    };
+    let version: SnmpVersion = match snmp_args.get_one::<String>("snmp_version") {
+        Some(version) => match SnmpVersion::from_str(version) {
+            Some(version) => version,
+            None => {
+                output::log_with_time(&format!("Invalid SNMP version: {}", version), "ERROR");
+                return;
+            }
+        },
+        None => SnmpVersion::V2c,
+    };
+    let concurrency: usize = match snmp_args.get_one::<usize>("concurrency") {
+        Some(concurrency) => *concurrency,
+        None => crate::config::DEFAULT_SNMP_CONCURRENCY,
+    };
+    if concurrency == 0 {
+        output::log_with_time("Concurrency must be at least 1", "ERROR");
+        return;
+    }
+    let timeout: Duration = match snmp_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(crate::config::DEFAULT_SNMP_TIMEOUT_MILLIS),
+    };
+    let setting = SnmpSetting {
+        communities: communities,
+        version: version,
+        timeout: timeout,
+        concurrency: concurrency,
+    };
+    output::log_with_time("Initiating SNMP sweep...", "INFO");
+    if !crate::app::is_quiet_mode() {
+        println!();
+        let mut tree = Tree::new(node_label("SNMP Sweep Config", None, None));
+        tree.push(node_label("Target", Some(&target), None));
+        tree.push(node_label("Hosts", Some(&ips.len().to_string()), None));
+        tree.push(node_label("Version", Some(version.name()), None));
+        tree.push(node_label(
+            "Communities",
+            Some(&setting.communities.join(", ")),
+            None,
+        ));
+        tree.push(node_label(
+            "Concurrent limit",
+            Some(&concurrency.to_string()),
+            None,
+        ));
+        tree.push(node_label("Timeout", Some(&format!("{:?}", timeout)), None));
+        println!("{}", tree);
+        println!("[Progress]");
+    }
+    let bar = ProgressBar::new(ips.len() as u64);
+    if crate::app::is_quiet_mode() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar.enable_steady_tick(120);
+    bar.set_style(output::get_progress_style());
+    bar.set_position(0);
+    bar.set_message("SnmpSweep");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let ptx = std::sync::Arc::new(std::sync::Mutex::new(tx));
+    let rt = Runtime::new().unwrap();
+    let handle = thread::spawn(move || rt.block_on(crate::snmp::sweep(ips, setting, ptx)));
+    while let Ok(_ip_addr) = rx.recv() {
+        bar.inc(1);
+    }
+    bar.finish_with_message("SnmpSweep");
+    let result: SnmpSweepResult = handle.join().unwrap();
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_snmp_result(&result);
+    }
+    output::log_with_time(
+        &format!(
+            "{} devices found in {:?}",
+            result.devices.len(),
+            result.scan_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn device_tree(device: &SnmpDevice) -> Tree<String> {
+    let mut tree = Tree::new(node_label(&device.ip_addr.to_string(), None, None));
+    tree.push(node_label("Community", Some(&device.community), None));
+    if !device.sys_name.is_empty() {
+        tree.push(node_label("Name", Some(&device.sys_name), None));
+    }
+    if !device.sys_descr.is_empty() {
+        tree.push(node_label("Description", Some(&device.sys_descr), None));
+    }
+    if !device.sys_object_id.is_empty() {
+        tree.push(node_label("Object ID", Some(&device.sys_object_id), None));
+    }
+    if let Some(uptime) = device.sys_uptime {
+        tree.push(node_label("Uptime", Some(&format_uptime(uptime)), None));
+    }
+    if !device.sys_contact.is_empty() {
+        tree.push(node_label("Contact", Some(&device.sys_contact), None));
+    }
+    if !device.sys_location.is_empty() {
+        tree.push(node_label("Location", Some(&device.sys_location), None));
+    }
+    tree
+}
+
+fn show_snmp_result(result: &SnmpSweepResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("SNMP Sweep Result", None, None));
+    for device in &result.devices {
+        tree.push(device_tree(device));
+    }
+    println!("{}", tree);
+}
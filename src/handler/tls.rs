@@ -0,0 +1,173 @@
+use crate::output;
+use crate::tls::TlsAuditResult;
+use crate::util::tree::node_label;
+use clap::ArgMatches;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use termtree::Tree;
+
+/// Split host[:port]. IPv6 address with port is enclosed in brackets
+fn parse_host_port(target: &str, default_port: u16) -> Option<(String, u16)> {
+    if let Ok(ip_addr) = target.parse::<IpAddr>() {
+        return Some((ip_addr.to_string(), default_port));
+    }
+    if let Some(rest) = target.strip_prefix('[') {
+        let (host, port) = rest.split_once(']')?;
+        let port: u16 = match port.strip_prefix(':') {
+            Some(port) => port.parse().ok()?,
+            None => default_port,
+        };
+        return Some((host.to_string(), port));
+    }
+    match target.rsplit_once(':') {
+        Some((host, port)) => Some((host.to_string(), port.parse().ok()?)),
+        None => Some((target.to_string(), default_port)),
+    }
+}
+
+pub fn handle_tls_audit(args: &ArgMatches) {
+    let tls_args = match args.subcommand_matches("tls") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let target: &String = match tls_args.get_one::<String>("target") {
+        Some(target) => target,
+        None => return,
+    };
+    let (host, port) = match parse_host_port(target, 443) {
+        Some(host_port) => host_port,
+        None => {
+            output::log_with_time(&format!("Invalid target: {}", target), "ERROR");
+            return;
+        }
+    };
+    let ip_addr: IpAddr = match host.parse::<IpAddr>() {
+        Ok(ip_addr) => ip_addr,
+        Err(_) => match crate::dns::lookup_host_name(&host) {
+            Some(ip_addr) => ip_addr,
+            None => {
+                output::log_with_time(&format!("Failed to resolve {}", host), "ERROR");
+                return;
+            }
+        },
+    };
+    let server_name: String = match tls_args.get_one::<String>("sni") {
+        Some(sni) => sni.to_string(),
+        None => host.clone(),
+    };
+    let timeout: Duration = match tls_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(5000),
+    };
+    let enum_ciphers: bool = !tls_args.get_flag("no_ciphers");
+    output::log_with_time(
+        &format!("Auditing TLS of {}:{} ({})...", host, port, ip_addr),
+        "INFO",
+    );
+    let result: TlsAuditResult =
+        crate::tls::audit(&host, ip_addr, port, &server_name, enum_ciphers, timeout);
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_tls_audit_result(&result);
+    }
+    output::log_with_time(
+        &format!(
+            "{} issues found in {:?}",
+            result.issues.len(),
+            result.scan_time
+        ),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_tls_audit_result(result: &TlsAuditResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label(
+        "TLS Audit",
+        Some(&format!("{}:{}", result.host, result.port)),
+        None,
+    ));
+    tree.push(node_label(
+        "IP Address",
+        Some(&result.ip_addr.to_string()),
+        None,
+    ));
+    tree.push(node_label("Server Name", Some(&result.server_name), None));
+    let mut versions_tree = Tree::new(node_label("Protocols", None, None));
+    for version in &result.versions {
+        let status: &str = if version.supported { "Yes" } else { "No" };
+        let mut version_tree = Tree::new(node_label(&version.version, Some(status), None));
+        for cipher_suite in &version.cipher_suites {
+            let label: String = if cipher_suite.weak {
+                format!("{} (weak)", cipher_suite.name)
+            } else {
+                cipher_suite.name.clone()
+            };
+            version_tree.push(node_label(&label, None, None));
+        }
+        versions_tree.push(version_tree);
+    }
+    tree.push(versions_tree);
+    let chain_status: String = match &result.chain_error {
+        Some(e) => format!("Invalid ({})", e),
+        None => "Valid".to_string(),
+    };
+    tree.push(node_label("Chain", Some(&chain_status), None));
+    if !result.certificates.is_empty() {
+        let mut certs_tree = Tree::new(node_label("Certificates", None, None));
+        for cert in &result.certificates {
+            let mut cert_tree = Tree::new(node_label(&cert.subject, None, None));
+            cert_tree.push(node_label("Issuer", Some(&cert.issuer), None));
+            if let Some(not_before) = cert.not_before {
+                cert_tree.push(node_label(
+                    "Not Before",
+                    Some(&not_before.to_string()),
+                    None,
+                ));
+            }
+            if let Some(not_after) = cert.not_after {
+                let days: i64 = cert.days_remaining().unwrap_or_default();
+                cert_tree.push(node_label(
+                    "Not After",
+                    Some(&format!("{} ({} days)", not_after, days)),
+                    None,
+                ));
+            }
+            if !cert.sans.is_empty() {
+                cert_tree.push(node_label("SAN", Some(&cert.sans.join(", ")), None));
+            }
+            cert_tree.push(node_label("Serial", Some(&cert.serial), None));
+            certs_tree.push(cert_tree);
+        }
+        tree.push(certs_tree);
+    }
+    if !result.issues.is_empty() {
+        let mut issues_tree = Tree::new(node_label("Issues", None, None));
+        for issue in &result.issues {
+            issues_tree.push(node_label(issue, None, None));
+        }
+        tree.push(issues_tree);
+    }
+    println!("{}", tree);
+}
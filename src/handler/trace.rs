@@ -1,17 +1,27 @@
-use crate::config::DEFAULT_BASE_TARGET_UDP_PORT;
+use crate::config::{
+    DEFAULT_BASE_TARGET_UDP_PORT, DEFAULT_RTT_DELTA_THRESHOLD_MILLIS, MAX_TRACE_FLOWS,
+    MAX_TRACE_QUERIES,
+};
+use crate::db::geoip::{GeoInfo, GeoIpReader};
+use crate::dns::asn::AsnInfo;
 use crate::output;
-use crate::ping::result::TracerouteResult;
+use crate::ping::result::{
+    FlowTraceResult, MultiFlowTraceResult, MultiTargetTraceResult, TargetTraceResult, TraceMap,
+    TracerouteResult,
+};
 use crate::probe::ProbeStatusKind;
+use crate::trace::baseline::{HopChangeKind, HopDiff, PathDiff};
 use crate::trace::setting::TraceSetting;
 use crate::trace::tracer::Tracer;
 use crate::util::tree::node_label;
 use clap::ArgMatches;
 use netdev::Interface;
+use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use termtree::Tree;
 
 pub fn handle_traceroute(args: &ArgMatches) {
@@ -20,69 +30,22 @@ pub fn handle_traceroute(args: &ArgMatches) {
         Some(matches) => matches,
         None => return,
     };
-    let interface: Interface = if let Some(if_name) = args.get_one::<String>("interface") {
-        match crate::interface::get_interface_by_name(if_name.to_string()) {
-            Some(iface) => iface,
-            None => return,
-        }
-    } else {
-        match netdev::get_default_interface() {
-            Ok(iface) => iface,
-            Err(_) => return,
-        }
-    };
-    let target: String = match trace_args.get_one::<String>("target") {
-        Some(target) => target.to_owned(),
+    let targets: Vec<String> = match trace_args.get_many::<String>("target") {
+        Some(targets) => targets.cloned().collect(),
         None => return,
     };
-    let mut port: u16 = match trace_args.get_one::<u16>("port") {
-        Some(port) => *port,
-        None => DEFAULT_BASE_TARGET_UDP_PORT,
-    };
-    let maxhop: u8 = match trace_args.get_one::<u8>("maxhop") {
-        Some(maxhop) => *maxhop,
-        None => 64,
-    };
-    let dst_ip: IpAddr = match IpAddr::from_str(&target) {
-        Ok(ip_addr) => ip_addr,
-        Err(_) => match SocketAddr::from_str(&target) {
-            Ok(socket_addr) => {
-                port = socket_addr.port();
-                socket_addr.ip()
+    if targets.len() > 1 {
+        handle_multi_target_trace(args, trace_args, targets);
+        return;
+    }
+    let (setting, interface): (TraceSetting, Interface) =
+        match get_trace_setting(args, trace_args, &targets[0]) {
+            Ok(setting) => setting,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
             }
-            Err(_) => match crate::dns::lookup_host_name(&target) {
-                Some(ip_addr) => ip_addr,
-                None => {
-                    output::log_with_time("Failed to resolve domain", "ERROR");
-                    return;
-                }
-            },
-        },
-    };
-    let timeout = match trace_args.get_one::<u64>("timeout") {
-        Some(timeout) => Duration::from_millis(*timeout),
-        None => Duration::from_secs(30),
-    };
-    let wait_time = match trace_args.get_one::<u64>("waittime") {
-        Some(wait_time) => Duration::from_millis(*wait_time),
-        None => Duration::from_secs(1),
-    };
-    let send_rate = match trace_args.get_one::<u64>("rate") {
-        Some(send_rate) => Duration::from_millis(*send_rate),
-        None => Duration::from_secs(1),
-    };
-    let mut setting: TraceSetting = TraceSetting::udp_trace(&interface, dst_ip).unwrap();
-    setting.dst_hostname = target
-        .split(":")
-        .collect::<Vec<&str>>()
-        .get(0)
-        .unwrap()
-        .to_string();
-    setting.dst_port = port;
-    setting.hop_limit = maxhop;
-    setting.receive_timeout = wait_time;
-    setting.probe_timeout = timeout;
-    setting.send_rate = send_rate;
+        };
 
     let target_addr: String =
         if setting.dst_ip.to_string() != setting.dst_hostname && !setting.dst_hostname.is_empty() {
@@ -91,44 +54,162 @@ pub fn handle_traceroute(args: &ArgMatches) {
             setting.dst_ip.to_string()
         };
 
+    let flows: usize = match trace_args.get_one::<usize>("flows") {
+        Some(flows) => *flows,
+        None => 1,
+    };
+    if flows == 0 || flows > MAX_TRACE_FLOWS {
+        output::log_with_time(
+            &format!("Number of flows must be 1 to {}", MAX_TRACE_FLOWS),
+            "ERROR",
+        );
+        return;
+    }
+
+    // Load baseline before probing so that a bad file fails fast
+    let baseline: Option<TracerouteResult> = match trace_args.get_one::<PathBuf>("baseline") {
+        Some(file_path) => match crate::trace::baseline::load_baseline(file_path) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        },
+        None => None,
+    };
+    let rtt_threshold: Duration = match trace_args.get_one::<u64>("rtt-threshold") {
+        Some(threshold) => Duration::from_millis(*threshold),
+        None => Duration::from_millis(DEFAULT_RTT_DELTA_THRESHOLD_MILLIS),
+    };
+    if baseline.is_some() && flows > 1 {
+        output::log_with_time("--baseline cannot be used with --flows", "ERROR");
+        return;
+    }
+    if trace_args.contains_id("to_map") && flows > 1 {
+        output::log_with_time("--to-map cannot be used with --flows", "ERROR");
+        return;
+    }
+
     print_option(&setting, &interface);
 
+    let asn: bool = trace_args.get_flag("asn");
+    let geoip_reader: Option<GeoIpReader> = super::get_geoip_reader(args);
+
+    if flows > 1 {
+        handle_multi_flow_trace(args, setting, flows, asn, geoip_reader, target_addr);
+        return;
+    }
+
+    let resolve: bool = setting.resolve;
+    let dns_timeout: Duration = setting.dns_timeout;
+    let dst_ip: IpAddr = setting.dst_ip;
     let tracer: Tracer = Tracer::new(setting).unwrap();
     let rx = tracer.get_progress_receiver();
     let handle = thread::spawn(move || tracer.trace());
+    let mut dns_map: HashMap<IpAddr, String> = HashMap::new();
+    let mut asn_map: HashMap<IpAddr, AsnInfo> = HashMap::new();
+    let mut geo_map: HashMap<IpAddr, GeoInfo> = HashMap::new();
     for r in rx.lock().unwrap().iter() {
         if r.probe_status.kind == crate::probe::ProbeStatusKind::Done {
+            if resolve && !dns_map.contains_key(&r.ip_addr) {
+                if let Some(host_name) =
+                    crate::dns::lookup_ip_addr_with_timeout(&r.ip_addr, dns_timeout)
+                {
+                    dns_map.insert(r.ip_addr, host_name);
+                }
+            }
+            if asn && !asn_map.contains_key(&r.ip_addr) {
+                if let Some(info) = crate::dns::asn::lookup_asn(&r.ip_addr) {
+                    asn_map.insert(r.ip_addr, info);
+                }
+            }
+            if let Some(geoip_reader) = &geoip_reader {
+                if let Some(geo) = geoip_reader.lookup(&r.ip_addr) {
+                    geo_map.insert(r.ip_addr, geo);
+                }
+            }
+            let source: String = hop_label(&r.ip_addr, dns_map.get(&r.ip_addr), &asn_map, &geo_map);
+            // IPv6 reports hop limit instead of TTL
+            let ttl_label: &str = if r.ip_addr.is_ipv6() { "HLIM" } else { "TTL" };
             output::log_with_time(
                 &format!(
-                    "{} {} Bytes from {}, HOP:{}, TTL:{}, RTT:{:?}, NodeType: {}",
+                    "{} {} Bytes from {}, HOP:{}, {}:{}, RTT:{:?}, NodeType: {}",
                     r.seq,
                     r.received_packet_size,
-                    r.ip_addr,
+                    source,
                     r.hop,
+                    ttl_label,
                     r.ttl,
                     r.rtt,
                     r.node_type.name()
                 ),
                 "INFO",
             );
+            if r.mpls_labels.len() > 0 {
+                let labels: Vec<String> = r.mpls_labels.iter().map(|l| l.to_string()).collect();
+                output::log_with_time(&format!("  MPLS: {}", labels.join(" | ")), "INFO");
+            }
         } else {
             output::log_with_time(&format!("{} {}", r.seq, r.probe_status.message), "ERROR");
         }
     }
     match handle.join() {
         Ok(trace_result) => match trace_result {
-            Ok(trace_result) => {
+            Ok(mut trace_result) => {
+                for node in trace_result.nodes.iter_mut() {
+                    if let Some(host_name) = dns_map.get(&node.ip_addr) {
+                        node.host_name = host_name.clone();
+                    }
+                }
+                trace_result.asns = asn_map;
+                trace_result.geo = geo_map;
+                let path_diff: Option<PathDiff> = baseline.as_ref().map(|baseline| {
+                    crate::trace::baseline::compare_paths(baseline, &trace_result, rtt_threshold)
+                });
                 // Print results
                 if args.get_flag("json") {
-                    let json_result = serde_json::to_string_pretty(&trace_result).unwrap();
+                    let json_result = match &path_diff {
+                        Some(path_diff) => serde_json::to_string_pretty(&serde_json::json!({
+                            "trace": trace_result,
+                            "path_diff": path_diff,
+                        }))
+                        .unwrap(),
+                        None => serde_json::to_string_pretty(&trace_result).unwrap(),
+                    };
                     println!("{}", json_result);
                 } else {
                     show_trace_result(&trace_result, target_addr);
+                    if let Some(path_diff) = &path_diff {
+                        show_path_diff(path_diff, &trace_result, rtt_threshold);
+                    }
+                }
+                if let Some(path_diff) = &path_diff {
+                    if path_diff.path_changed {
+                        output::log_with_time("Path changed from baseline", "WARN");
+                    }
+                    if path_diff.rtt_alerts > 0 {
+                        output::log_with_time(
+                            &format!(
+                                "{} hop(s) with RTT delta over {:?}",
+                                path_diff.rtt_alerts, rtt_threshold
+                            ),
+                            "WARN",
+                        );
+                    }
                 }
                 output::log_with_time(
                     &format!("Traceroute completed in: {:?}", trace_result.elapsed_time),
                     "INFO",
                 );
+                if let Some(map_name) = trace_args.get_one::<String>("to_map") {
+                    let mut topology = MultiTargetTraceResult::new();
+                    topology.add_target(TargetTraceResult {
+                        target: targets[0].clone(),
+                        ip_addr: dst_ip,
+                        result: trace_result.clone(),
+                    });
+                    save_trace_map(map_name, &topology);
+                }
                 match args.get_one::<PathBuf>("save") {
                     Some(file_path) => {
                         match crate::fs::save_text(
@@ -155,6 +236,655 @@ pub fn handle_traceroute(args: &ArgMatches) {
     }
 }
 
+/// Trace each flow with a different source port to enumerate ECMP paths
+fn handle_multi_flow_trace(
+    args: &ArgMatches,
+    setting: TraceSetting,
+    flows: usize,
+    asn: bool,
+    geoip_reader: Option<GeoIpReader>,
+    target_addr: String,
+) {
+    let start_time = Instant::now();
+    let mut multi_result = MultiFlowTraceResult::new();
+    for flow_id in 1..=flows {
+        let mut flow_setting: TraceSetting = setting.clone();
+        flow_setting.src_port = setting.src_port.wrapping_add((flow_id - 1) as u16);
+        output::log_with_time(
+            &format!(
+                "Tracing flow {}/{} (source port {})",
+                flow_id, flows, flow_setting.src_port
+            ),
+            "INFO",
+        );
+        let src_port: u16 = flow_setting.src_port;
+        let tracer: Tracer = match Tracer::new(flow_setting) {
+            Ok(tracer) => tracer,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        };
+        match tracer.trace() {
+            Ok(trace_result) => {
+                multi_result.add_flow(FlowTraceResult {
+                    flow_id: flow_id,
+                    src_port: src_port,
+                    result: trace_result,
+                });
+            }
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        }
+    }
+    if setting.resolve {
+        let mut dns_map: HashMap<IpAddr, String> = HashMap::new();
+        for flow in multi_result.flows.iter_mut() {
+            for node in flow.result.nodes.iter_mut() {
+                if node.probe_status.kind != ProbeStatusKind::Done {
+                    continue;
+                }
+                if !dns_map.contains_key(&node.ip_addr) {
+                    let host_name: String =
+                        crate::dns::lookup_ip_addr_with_timeout(&node.ip_addr, setting.dns_timeout)
+                            .unwrap_or(node.ip_addr.to_string());
+                    dns_map.insert(node.ip_addr, host_name);
+                }
+                node.host_name = dns_map.get(&node.ip_addr).cloned().unwrap_or_default();
+            }
+        }
+    }
+    if asn {
+        let mut ips: Vec<IpAddr> = Vec::new();
+        for flow in &multi_result.flows {
+            for node in &flow.result.nodes {
+                if node.probe_status.kind == ProbeStatusKind::Done && !ips.contains(&node.ip_addr) {
+                    ips.push(node.ip_addr);
+                }
+            }
+        }
+        let asn_map: HashMap<IpAddr, AsnInfo> = crate::dns::asn::lookup_asns(&ips);
+        for flow in multi_result.flows.iter_mut() {
+            for node in &flow.result.nodes {
+                if let Some(info) = asn_map.get(&node.ip_addr) {
+                    flow.result.asns.insert(node.ip_addr, info.clone());
+                }
+            }
+        }
+    }
+    if let Some(geoip_reader) = &geoip_reader {
+        for flow in multi_result.flows.iter_mut() {
+            for node in &flow.result.nodes {
+                if let Some(geo) = geoip_reader.lookup(&node.ip_addr) {
+                    flow.result.geo.insert(node.ip_addr, geo);
+                }
+            }
+        }
+    }
+    multi_result.elapsed_time = start_time.elapsed();
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&multi_result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_multi_flow_result(&multi_result, target_addr);
+    }
+    output::log_with_time(
+        &format!("Traceroute completed in: {:?}", multi_result.elapsed_time),
+        "INFO",
+    );
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(
+                file_path,
+                serde_json::to_string_pretty(&multi_result).unwrap(),
+            ) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+/// Trace each target in turn and merge the paths into one topology
+fn handle_multi_target_trace(args: &ArgMatches, trace_args: &ArgMatches, targets: Vec<String>) {
+    if trace_args.contains_id("flows") || trace_args.contains_id("baseline") {
+        output::log_with_time(
+            "--flows and --baseline cannot be used with multiple targets",
+            "ERROR",
+        );
+        return;
+    }
+    let asn: bool = trace_args.get_flag("asn");
+    let geoip_reader: Option<GeoIpReader> = super::get_geoip_reader(args);
+    let start_time = Instant::now();
+    let mut multi_result = MultiTargetTraceResult::new();
+    for (i, target) in targets.iter().enumerate() {
+        let (setting, _interface): (TraceSetting, Interface) =
+            match get_trace_setting(args, trace_args, target) {
+                Ok(setting) => setting,
+                Err(e) => {
+                    output::log_with_time(&format!("{}: {}", target, e), "ERROR");
+                    continue;
+                }
+            };
+        output::log_with_time(
+            &format!(
+                "Tracing target {}/{} ({})",
+                i + 1,
+                targets.len(),
+                setting.dst_ip
+            ),
+            "INFO",
+        );
+        let dst_ip: IpAddr = setting.dst_ip;
+        let resolve: bool = setting.resolve;
+        let dns_timeout: Duration = setting.dns_timeout;
+        let tracer: Tracer = match Tracer::new(setting) {
+            Ok(tracer) => tracer,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                continue;
+            }
+        };
+        let mut trace_result: TracerouteResult = match tracer.trace() {
+            Ok(trace_result) => trace_result,
+            Err(e) => {
+                output::log_with_time(&format!("{}: {}", target, e), "ERROR");
+                continue;
+            }
+        };
+        let ips: Vec<IpAddr> = trace_result
+            .hops
+            .iter()
+            .flat_map(|hop| hop.ip_addrs.clone())
+            .collect();
+        if resolve {
+            let mut dns_map: HashMap<IpAddr, String> = HashMap::new();
+            for ip_addr in &ips {
+                if let Some(host_name) =
+                    crate::dns::lookup_ip_addr_with_timeout(ip_addr, dns_timeout)
+                {
+                    dns_map.insert(*ip_addr, host_name);
+                }
+            }
+            for node in trace_result.nodes.iter_mut() {
+                if let Some(host_name) = dns_map.get(&node.ip_addr) {
+                    node.host_name = host_name.clone();
+                }
+            }
+        }
+        if asn {
+            trace_result.asns = crate::dns::asn::lookup_asns(&ips);
+        }
+        if let Some(geoip_reader) = &geoip_reader {
+            for ip_addr in &ips {
+                if let Some(geo) = geoip_reader.lookup(ip_addr) {
+                    trace_result.geo.insert(*ip_addr, geo);
+                }
+            }
+        }
+        multi_result.add_target(TargetTraceResult {
+            target: target.clone(),
+            ip_addr: dst_ip,
+            result: trace_result,
+        });
+    }
+    multi_result.elapsed_time = start_time.elapsed();
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&multi_result).unwrap();
+        println!("{}", json_result);
+    } else {
+        show_topology(&multi_result);
+    }
+    output::log_with_time(
+        &format!("Traceroute completed in: {:?}", multi_result.elapsed_time),
+        "INFO",
+    );
+    if let Some(map_name) = trace_args.get_one::<String>("to_map") {
+        save_trace_map(map_name, &multi_result);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(
+                file_path,
+                serde_json::to_string_pretty(&multi_result).unwrap(),
+            ) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+/// Merge the topology into the named map file (<name>.map.json), creating it if missing
+fn save_trace_map(map_name: &str, topology: &MultiTargetTraceResult) {
+    let file_path: PathBuf = PathBuf::from(format!("{}.map.json", map_name));
+    let mut trace_map: TraceMap = match std::fs::read_to_string(&file_path) {
+        Ok(text) => match serde_json::from_str::<TraceMap>(&text) {
+            Ok(trace_map) => trace_map,
+            Err(e) => {
+                output::log_with_time(
+                    &format!("Failed to parse {}: {}", file_path.to_string_lossy(), e),
+                    "ERROR",
+                );
+                return;
+            }
+        },
+        Err(_) => TraceMap::new(map_name),
+    };
+    trace_map.merge(topology);
+    match crate::fs::save_text(
+        &file_path,
+        serde_json::to_string_pretty(&trace_map).unwrap(),
+    ) {
+        Ok(_) => {
+            output::log_with_time(
+                &format!(
+                    "Updated map {} ({} nodes, {} edges) in {}",
+                    trace_map.name,
+                    trace_map.nodes.len(),
+                    trace_map.edges.len(),
+                    file_path.to_string_lossy()
+                ),
+                "INFO",
+            );
+        }
+        Err(e) => {
+            output::log_with_time(&format!("Failed to save map: {}", e), "ERROR");
+        }
+    }
+}
+
+/// Build subtree of the topology from the node. `visited` guards against routing loops
+fn topology_tree(
+    multi_result: &MultiTargetTraceResult,
+    node_id: &str,
+    visited: &mut Vec<String>,
+) -> Tree<String> {
+    let label: String = match multi_result.nodes.iter().find(|node| node.id == node_id) {
+        Some(node) => {
+            let mut label: String = match node.ip_addr {
+                Some(ip_addr) => {
+                    let result: Option<&TracerouteResult> = multi_result
+                        .targets
+                        .iter()
+                        .map(|t| &t.result)
+                        .find(|r| r.hops.iter().any(|h| h.ip_addrs.contains(&ip_addr)));
+                    match result {
+                        Some(result) => {
+                            hop_label(&ip_addr, Some(&node.host_name), &result.asns, &result.geo)
+                        }
+                        None => ip_addr.to_string(),
+                    }
+                }
+                None => node.host_name.clone(),
+            };
+            // Mark the node where the path to a target ends
+            let reached: Vec<&str> = multi_result
+                .targets
+                .iter()
+                .filter(|t| Some(t.ip_addr) == node.ip_addr)
+                .map(|t| t.target.as_str())
+                .collect();
+            if !reached.is_empty() {
+                label = format!("{} <- {}", label, reached.join(", "));
+            }
+            label
+        }
+        None => node_id.to_string(),
+    };
+    let mut tree = Tree::new(label);
+    visited.push(node_id.to_string());
+    for edge in multi_result.edges.iter().filter(|edge| edge.src == node_id) {
+        if visited.contains(&edge.dst) {
+            continue;
+        }
+        tree.push(topology_tree(multi_result, &edge.dst, visited));
+    }
+    visited.pop();
+    tree
+}
+
+fn show_topology(multi_result: &MultiTargetTraceResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("Traceroute Topology", None, None));
+    let mut visited: Vec<String> = Vec::new();
+    tree.push(topology_tree(
+        multi_result,
+        MultiTargetTraceResult::LOCAL_NODE_ID,
+        &mut visited,
+    ));
+    let mut targets_tree = Tree::new(node_label("Targets", None, None));
+    for target in &multi_result.targets {
+        targets_tree.push(node_label(
+            &target.target,
+            Some(&format!(
+                "{} ({} hops, {})",
+                target.ip_addr,
+                target.result.hops.len(),
+                target.result.probe_status.kind.name()
+            )),
+            None,
+        ));
+    }
+    tree.push(targets_tree);
+    tree.push(node_label(
+        "Nodes",
+        Some(&multi_result.nodes.len().to_string()),
+        None,
+    ));
+    tree.push(node_label(
+        "Edges",
+        Some(&multi_result.edges.len().to_string()),
+        None,
+    ));
+    println!("{}", tree);
+}
+
+/// Build trace setting for the target from the common trace options
+fn get_trace_setting(
+    args: &ArgMatches,
+    trace_args: &ArgMatches,
+    target: &str,
+) -> Result<(TraceSetting, Interface), String> {
+    let mut port: u16 = match trace_args.get_one::<u16>("port") {
+        Some(port) => *port,
+        None => DEFAULT_BASE_TARGET_UDP_PORT,
+    };
+    let maxhop: u8 = match trace_args.get_one::<u8>("maxhop") {
+        Some(maxhop) => *maxhop,
+        None => 64,
+    };
+    let mut dst_hostname: String = target.to_string();
+    // Zone ID of IPv6 address (e.g. fe80::1%eth0)
+    let mut zone: Option<String> = None;
+    let dst_ip: IpAddr = match crate::ip::parse_ip_with_zone(target) {
+        Some((ip_addr, zone_id)) => {
+            dst_hostname = ip_addr.to_string();
+            zone = zone_id;
+            ip_addr
+        }
+        None => match SocketAddr::from_str(target) {
+            Ok(socket_addr) => {
+                port = socket_addr.port();
+                dst_hostname = socket_addr.ip().to_string();
+                socket_addr.ip()
+            }
+            Err(_) => {
+                let resolved: Option<IpAddr> = if trace_args.get_flag("ipv6") {
+                    crate::dns::lookup_host_name_ipv6(target)
+                } else {
+                    crate::dns::lookup_host_name(target)
+                };
+                match resolved {
+                    Some(ip_addr) => ip_addr,
+                    None => {
+                        return Err(String::from("Failed to resolve domain"));
+                    }
+                }
+            }
+        },
+    };
+    let src_ip: Option<IpAddr> = trace_args.get_one::<IpAddr>("source_ip").cloned();
+    if let Some(src_ip) = src_ip {
+        if src_ip.is_ipv4() != dst_ip.is_ipv4() {
+            return Err(format!(
+                "Source IP {} and target {} are of different address families",
+                src_ip, dst_ip
+            ));
+        }
+    }
+    // Zone ID takes precedence over --interface, and --interface over --source-ip
+    let interface: Interface = if let Some(zone) = &zone {
+        let iface = match zone.parse::<u32>() {
+            Ok(if_index) => crate::interface::get_interface_by_index(if_index),
+            Err(_) => crate::interface::get_interface_by_name(zone.to_string()),
+        };
+        match iface {
+            Some(iface) => iface,
+            None => {
+                return Err(format!("Invalid zone ID: {}", zone));
+            }
+        }
+    } else if let Some(if_name) = args.get_one::<String>("interface") {
+        match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => return Err(format!("Interface not found: {}", if_name)),
+        }
+    } else if let Some(src_ip) = src_ip {
+        match crate::interface::get_interface_by_ip(src_ip) {
+            Some(iface) => iface,
+            None => return Err(format!("No interface has the source IP {}", src_ip)),
+        }
+    } else {
+        match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(e) => return Err(format!("Failed to get default interface: {}", e)),
+        }
+    };
+    if let Some(src_ip) = src_ip {
+        if !crate::interface::get_interface_local_ips(&interface).contains(&src_ip) {
+            return Err(format!(
+                "Source IP {} is not assigned to {}",
+                src_ip, interface.name
+            ));
+        }
+    } else if let IpAddr::V6(ipv6_addr) = dst_ip {
+        // Global source for global target, link-local source otherwise
+        let src_ip: Option<IpAddr> = if nex::net::ip::is_global_ipv6(&ipv6_addr) {
+            crate::interface::get_interface_global_ipv6(&interface)
+        } else {
+            crate::interface::get_interface_local_ipv6(&interface)
+        };
+        if src_ip.is_none() {
+            return Err(format!(
+                "No suitable IPv6 source address on {}",
+                interface.name
+            ));
+        }
+    }
+    let timeout = match trace_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_secs(30),
+    };
+    let wait_time = match trace_args.get_one::<u64>("waittime") {
+        Some(wait_time) => Duration::from_millis(*wait_time),
+        None => Duration::from_secs(1),
+    };
+    let send_rate = match trace_args.get_one::<u64>("rate") {
+        Some(send_rate) => Duration::from_millis(*send_rate),
+        None => Duration::from_secs(1),
+    };
+    let mut setting: TraceSetting = TraceSetting::udp_trace(&interface, dst_ip).unwrap();
+    setting.dst_hostname = dst_hostname;
+    setting.src_ip = src_ip;
+    // Link-local destination is not reachable via gateway
+    if let IpAddr::V6(ipv6_addr) = dst_ip {
+        if crate::ip::is_link_local_ipv6(&ipv6_addr)
+            && !interface.is_tun()
+            && !interface.is_loopback()
+        {
+            match crate::neighbor::resolve_mac_addr(&interface, dst_ip) {
+                Some(mac_addr) => setting.dst_mac = Some(mac_addr),
+                None => {
+                    return Err(format!("Failed to resolve MAC address of {}", dst_ip));
+                }
+            }
+        }
+    }
+    setting.dst_port = port;
+    setting.hop_limit = maxhop;
+    if let Some(first_hop) = trace_args.get_one::<u8>("firsthop") {
+        if *first_hop == 0 || *first_hop >= maxhop {
+            return Err(format!(
+                "First hop must be 1 to {}",
+                maxhop.saturating_sub(1)
+            ));
+        }
+        setting.first_hop = *first_hop;
+    }
+    setting.tos = super::get_tos(trace_args);
+    setting.receive_timeout = wait_time;
+    setting.hop_timeout = match trace_args.get_one::<u64>("hop_timeout") {
+        Some(hop_timeout) => Duration::from_millis(*hop_timeout),
+        None => wait_time,
+    };
+    if setting.hop_timeout > timeout {
+        return Err(String::from(
+            "Hop timeout must not exceed the trace timeout",
+        ));
+    }
+    setting.probe_timeout = timeout;
+    setting.send_rate = send_rate;
+    if let Some(parallel) = trace_args.get_one::<u8>("parallel") {
+        if *parallel == 0 {
+            return Err(String::from("Number of parallel probes must be at least 1"));
+        }
+        setting.parallel = *parallel;
+    }
+    if let Some(queries) = trace_args.get_one::<u8>("queries") {
+        if *queries == 0 || *queries > MAX_TRACE_QUERIES {
+            return Err(format!(
+                "Number of queries must be 1 to {}",
+                MAX_TRACE_QUERIES
+            ));
+        }
+        setting.queries = *queries;
+    }
+    setting.resolve = !trace_args.get_flag("no_resolve");
+    if let Some(dns_timeout) = trace_args.get_one::<u64>("dns_timeout") {
+        setting.dns_timeout = Duration::from_millis(*dns_timeout);
+    }
+    Ok((setting, interface))
+}
+
+/// Hop address with AS and location annotations
+fn hop_label(
+    ip_addr: &IpAddr,
+    host_name: Option<&String>,
+    asn_map: &HashMap<IpAddr, AsnInfo>,
+    geo_map: &HashMap<IpAddr, GeoInfo>,
+) -> String {
+    let mut annotations: Vec<String> = Vec::new();
+    if let Some(info) = asn_map.get(ip_addr) {
+        annotations.push(info.label());
+    }
+    if let Some(geo) = geo_map.get(ip_addr) {
+        let location: String = geo.label();
+        if !location.is_empty() {
+            annotations.push(location);
+        }
+        // ASN database, unless --asn already annotated it
+        if !asn_map.contains_key(ip_addr) {
+            annotations.extend(geo.as_label());
+        }
+    }
+    let addr: String = match host_name {
+        Some(host_name) if !host_name.is_empty() && *host_name != ip_addr.to_string() => {
+            format!("{}({})", host_name, ip_addr)
+        }
+        _ => ip_addr.to_string(),
+    };
+    if annotations.is_empty() {
+        addr
+    } else {
+        format!("{} [{}]", addr, annotations.join(", "))
+    }
+}
+
+fn show_multi_flow_result(result: &MultiFlowTraceResult, target_addr: String) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label(
+        &format!("Traceroute Flows - {}", target_addr),
+        None,
+        None,
+    ));
+    let mut flows_tree = Tree::new(node_label("Flows", None, None));
+    for flow in &result.flows {
+        let mut flow_tree = Tree::new(node_label(
+            &format!("Flow {}", flow.flow_id),
+            Some(&format!("Source Port {}", flow.src_port)),
+            None,
+        ));
+        for hop in &flow.result.hops {
+            let addrs: Vec<String> = hop
+                .ip_addrs
+                .iter()
+                .map(|ip_addr| {
+                    let host_name: Option<&String> = flow
+                        .result
+                        .nodes
+                        .iter()
+                        .find(|node| node.ip_addr == *ip_addr)
+                        .map(|node| &node.host_name);
+                    hop_label(ip_addr, host_name, &flow.result.asns, &flow.result.geo)
+                })
+                .collect();
+            let label: String = if addrs.is_empty() {
+                "*".to_string()
+            } else {
+                addrs.join(", ")
+            };
+            flow_tree.push(node_label(&hop.ttl.to_string(), Some(&label), None));
+        }
+        flows_tree.push(flow_tree);
+    }
+    tree.push(flows_tree);
+    // Hops answered by more than one router are load-balanced
+    let mut lb_tree = Tree::new(node_label("Load-balanced Hops", None, None));
+    let max_len: usize = result.paths.iter().map(|p| p.len()).max().unwrap_or(0);
+    for i in 0..max_len {
+        let mut addrs: Vec<IpAddr> = Vec::new();
+        for path in &result.paths {
+            if let Some(Some(ip_addr)) = path.get(i) {
+                if !addrs.contains(ip_addr) {
+                    addrs.push(*ip_addr);
+                }
+            }
+        }
+        if addrs.len() > 1 {
+            let addrs: Vec<String> = addrs.iter().map(|a| a.to_string()).collect();
+            // Paths are indexed from the first hop
+            let ttl: u8 = result
+                .flows
+                .iter()
+                .find_map(|flow| flow.result.hops.get(i))
+                .map(|hop| hop.ttl)
+                .unwrap_or((i + 1) as u8);
+            lb_tree.push(node_label(&ttl.to_string(), Some(&addrs.join(", ")), None));
+        }
+    }
+    tree.push(lb_tree);
+    tree.push(node_label(
+        "Distinct Paths",
+        Some(&result.paths.len().to_string()),
+        None,
+    ));
+    println!("{}", tree);
+}
+
 fn print_option(setting: &TraceSetting, interface: &Interface) {
     if crate::app::is_quiet_mode() {
         return;
@@ -164,6 +894,9 @@ fn print_option(setting: &TraceSetting, interface: &Interface) {
     let mut tree = Tree::new(node_label("Traceroute Config", None, None));
     let mut setting_tree = Tree::new(node_label("Settings", None, None));
     setting_tree.push(node_label("Interface", Some(interface.name.as_str()), None));
+    if let Some(src_ip) = setting.src_ip {
+        setting_tree.push(node_label("Source IP", Some(&src_ip.to_string()), None));
+    }
     setting_tree.push(node_label(
         "Protocol",
         Some(format!("{:?}", setting.protocol).as_str()),
@@ -174,6 +907,34 @@ fn print_option(setting: &TraceSetting, interface: &Interface) {
         Some(setting.hop_limit.to_string().as_str()),
         None,
     ));
+    if setting.queries > 1 {
+        setting_tree.push(node_label(
+            "Queries per Hop",
+            Some(setting.queries.to_string().as_str()),
+            None,
+        ));
+    }
+    if setting.parallel > 1 {
+        setting_tree.push(node_label(
+            "Parallel Probes",
+            Some(setting.parallel.to_string().as_str()),
+            None,
+        ));
+    }
+    if setting.first_hop > 1 {
+        setting_tree.push(node_label(
+            "First Hop",
+            Some(setting.first_hop.to_string().as_str()),
+            None,
+        ));
+    }
+    if setting.tos != 0 {
+        setting_tree.push(node_label(
+            "TOS",
+            Some(&super::format_tos(setting.tos)),
+            None,
+        ));
+    }
     setting_tree.push(node_label(
         "Timeout",
         Some(format!("{:?}", setting.probe_timeout).as_str()),
@@ -184,11 +945,33 @@ fn print_option(setting: &TraceSetting, interface: &Interface) {
         Some(format!("{:?}", setting.receive_timeout).as_str()),
         None,
     ));
+    setting_tree.push(node_label(
+        "Hop Timeout",
+        Some(format!("{:?}", setting.hop_timeout).as_str()),
+        None,
+    ));
     setting_tree.push(node_label(
         "Send Rate",
         Some(format!("{:?}", setting.send_rate).as_str()),
         None,
     ));
+    setting_tree.push(node_label(
+        "Reverse DNS",
+        Some(
+            if setting.resolve {
+                format!("On (Timeout: {:?})", setting.dns_timeout)
+            } else {
+                "Off".to_string()
+            }
+            .as_str(),
+        ),
+        None,
+    ));
+    setting_tree.push(node_label(
+        "Source Port",
+        Some(setting.src_port.to_string().as_str()),
+        None,
+    ));
     tree.push(setting_tree);
     // Target
     let mut target_tree = Tree::new(node_label("Target", None, None));
@@ -238,6 +1021,23 @@ fn show_trace_result(trace_result: &TracerouteResult, target_addr: String) {
                     Some(&response.ip_addr.to_string()),
                     None,
                 ));
+                if response.host_name != response.ip_addr.to_string() {
+                    response_tree.push(node_label("Host Name", Some(&response.host_name), None));
+                }
+                if let Some(info) = trace_result.asns.get(&response.ip_addr) {
+                    response_tree.push(node_label("ASN", Some(&info.label()), None));
+                }
+                if let Some(geo) = trace_result.geo.get(&response.ip_addr) {
+                    let location: String = geo.label();
+                    if !location.is_empty() {
+                        response_tree.push(node_label("Location", Some(&location), None));
+                    }
+                    if !trace_result.asns.contains_key(&response.ip_addr) {
+                        if let Some(as_label) = geo.as_label() {
+                            response_tree.push(node_label("ASN", Some(&as_label), None));
+                        }
+                    }
+                }
                 response_tree.push(node_label(
                     "Protocol",
                     Some(format!("{:?}", response.protocol).as_str()),
@@ -254,7 +1054,11 @@ fn show_trace_result(trace_result: &TracerouteResult, target_addr: String) {
                     None,
                 ));
                 response_tree.push(node_label(
-                    "TTL",
+                    if response.ip_addr.is_ipv6() {
+                        "Hop Limit"
+                    } else {
+                        "TTL"
+                    },
                     Some(response.ttl.to_string().as_str()),
                     None,
                 ));
@@ -268,6 +1072,13 @@ fn show_trace_result(trace_result: &TracerouteResult, target_addr: String) {
                     Some(&response.node_type.name()),
                     None,
                 ));
+                if response.mpls_labels.len() > 0 {
+                    let mut mpls_tree = Tree::new(node_label("MPLS Labels", None, None));
+                    for label in &response.mpls_labels {
+                        mpls_tree.push(node_label(&label.to_string(), None, None));
+                    }
+                    response_tree.push(mpls_tree);
+                }
                 responses_tree.push(response_tree);
             }
             _ => {
@@ -291,6 +1102,56 @@ fn show_trace_result(trace_result: &TracerouteResult, target_addr: String) {
         }
     }
     tree.push(responses_tree);
+    // Per-hop statistics when multiple queries were sent per TTL
+    if trace_result.hops.iter().any(|hop| hop.sent > 1) {
+        let mut hops_tree = Tree::new(node_label("Hops", None, None));
+        for hop in &trace_result.hops {
+            let addrs: Vec<String> = hop
+                .ip_addrs
+                .iter()
+                .map(|ip_addr| {
+                    let host_name: Option<&String> = trace_result
+                        .nodes
+                        .iter()
+                        .find(|node| node.ip_addr == *ip_addr)
+                        .map(|node| &node.host_name);
+                    hop_label(ip_addr, host_name, &trace_result.asns, &trace_result.geo)
+                })
+                .collect();
+            let mut hop_tree = Tree::new(node_label(
+                &hop.ttl.to_string(),
+                Some(
+                    if addrs.is_empty() {
+                        "*".to_string()
+                    } else {
+                        addrs.join(", ")
+                    }
+                    .as_str(),
+                ),
+                None,
+            ));
+            hop_tree.push(node_label(
+                "Loss",
+                Some(&format!(
+                    "{:.1}% ({}/{})",
+                    hop.loss_percent, hop.received, hop.sent
+                )),
+                None,
+            ));
+            if hop.received > 0 {
+                hop_tree.push(node_label(
+                    "RTT min/avg/max",
+                    Some(&format!(
+                        "{:?} / {:?} / {:?}",
+                        hop.min_rtt, hop.avg_rtt, hop.max_rtt
+                    )),
+                    None,
+                ));
+            }
+            hops_tree.push(hop_tree);
+        }
+        tree.push(hops_tree);
+    }
     tree.push(node_label(
         "Status",
         Some(&trace_result.probe_status.kind.name()),
@@ -298,3 +1159,88 @@ fn show_trace_result(trace_result: &TracerouteResult, target_addr: String) {
     ));
     println!("{}", tree);
 }
+
+fn hop_diff_label(
+    ip_addr: &Option<IpAddr>,
+    ttl: Option<u8>,
+    trace_result: &TracerouteResult,
+) -> String {
+    match ip_addr {
+        Some(ip_addr) => {
+            let host_name: Option<&String> = trace_result
+                .nodes
+                .iter()
+                .find(|node| node.ip_addr == *ip_addr && !node.host_name.is_empty())
+                .map(|node| &node.host_name);
+            format!(
+                "{} {}",
+                ttl.unwrap_or(0),
+                hop_label(ip_addr, host_name, &trace_result.asns, &trace_result.geo)
+            )
+        }
+        None => format!("{} *", ttl.unwrap_or(0)),
+    }
+}
+
+fn show_path_diff(path_diff: &PathDiff, trace_result: &TracerouteResult, rtt_threshold: Duration) {
+    let mut tree = Tree::new(node_label("Path Changes", None, None));
+    for hop in &path_diff.hops {
+        let label: String = match hop.kind {
+            HopChangeKind::Unchanged => {
+                format!("  {}", hop_diff_label(&hop.ip_addr, hop.ttl, trace_result))
+            }
+            HopChangeKind::Inserted => {
+                format!("+ {}", hop_diff_label(&hop.ip_addr, hop.ttl, trace_result))
+            }
+            HopChangeKind::Removed => format!(
+                "- {}",
+                hop_diff_label(&hop.baseline_ip_addr, hop.baseline_ttl, trace_result)
+            ),
+            HopChangeKind::Changed => format!(
+                "~ {} (was {})",
+                hop_diff_label(&hop.ip_addr, hop.ttl, trace_result),
+                match hop.baseline_ip_addr {
+                    Some(ip_addr) => ip_addr.to_string(),
+                    None => String::from("*"),
+                }
+            ),
+        };
+        let mut hop_tree = Tree::new(node_label(&label, None, None));
+        if let Some(delta) = hop.rtt_delta_ms {
+            hop_tree.push(node_label(
+                "RTT Delta",
+                Some(&rtt_delta_label(hop, delta)),
+                None,
+            ));
+        }
+        tree.push(hop_tree);
+    }
+    tree.push(node_label(
+        "Path Changed",
+        Some(&path_diff.path_changed.to_string()),
+        None,
+    ));
+    tree.push(node_label(
+        "RTT Alerts",
+        Some(&format!(
+            "{} (threshold {:?})",
+            path_diff.rtt_alerts, rtt_threshold
+        )),
+        None,
+    ));
+    println!("{}", tree);
+}
+
+fn rtt_delta_label(hop: &HopDiff, delta: f64) -> String {
+    let label: String = format!(
+        "{:+.3}ms ({:?} -> {:?})",
+        delta,
+        hop.baseline_rtt.unwrap_or_default(),
+        hop.rtt.unwrap_or_default()
+    );
+    if hop.rtt_alert {
+        format!("{} !", label)
+    } else {
+        label
+    }
+}
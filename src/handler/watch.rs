@@ -0,0 +1,116 @@
+use crate::host::Host;
+use crate::output;
+use crate::util::tree::node_label;
+use crate::watch::WatchChange;
+use clap::ArgMatches;
+use std::thread;
+use std::time::Duration;
+use termtree::Tree;
+
+/// Re-run the scan every --watch seconds and report only the changes from the previous round
+pub fn run_watch(
+    args: &ArgMatches,
+    command: &str,
+    sub_args: &ArgMatches,
+    scan: impl Fn() -> Option<Vec<Host>>,
+) {
+    let interval: Duration = match sub_args.get_one::<u64>("watch") {
+        Some(secs) => Duration::from_secs(*secs),
+        None => return,
+    };
+    let webhook: Option<&String> = sub_args.get_one::<String>("webhook");
+    output::log_with_time(
+        &format!(
+            "Watching every {:?}. Only changes are reported. Press Ctrl-C to stop",
+            interval
+        ),
+        "INFO",
+    );
+    let mut previous: Option<Vec<Host>> = None;
+    loop {
+        match scan() {
+            Some(hosts) => {
+                match &previous {
+                    None => {
+                        if args.get_flag("json") {
+                            println!("{}", serde_json::to_string_pretty(&hosts).unwrap());
+                        } else {
+                            show_baseline(&hosts);
+                        }
+                    }
+                    Some(previous) => {
+                        let changes: Vec<WatchChange> = crate::watch::diff_hosts(previous, &hosts);
+                        report_changes(args, command, &changes, webhook);
+                    }
+                }
+                previous = Some(hosts);
+            }
+            None => {
+                output::log_with_time("No result in this round. Keeping the previous one", "WARN")
+            }
+        }
+        thread::sleep(interval);
+    }
+}
+
+fn report_changes(
+    args: &ArgMatches,
+    command: &str,
+    changes: &[WatchChange],
+    webhook: Option<&String>,
+) {
+    if changes.is_empty() {
+        output::log_with_time("No changes", "INFO");
+        return;
+    }
+    for change in changes {
+        if args.get_flag("json") {
+            println!("{}", serde_json::to_string(change).unwrap());
+        } else if crate::app::is_quiet_mode() {
+            println!("{}", change.message());
+        } else {
+            output::log_alert(&change.message());
+        }
+    }
+    if let Some(url) = webhook {
+        match crate::watch::send_webhook(
+            url,
+            command,
+            changes,
+            Duration::from_millis(crate::config::DEFAULT_WEBHOOK_TIMEOUT_MILLIS),
+        ) {
+            Ok(_) => output::log_with_time(
+                &format!("Sent {} change(s) to webhook", changes.len()),
+                "INFO",
+            ),
+            Err(e) => output::log_with_time(&format!("Webhook failed: {}", e), "ERROR"),
+        }
+    }
+    let topic: String = format!("{}/change", super::mqtt_topic(args));
+    let messages: Vec<(String, String)> = changes
+        .iter()
+        .map(|change| (topic.clone(), serde_json::to_string(change).unwrap()))
+        .collect();
+    super::send_mqtt(args, &messages);
+}
+
+fn show_baseline(hosts: &[Host]) {
+    println!();
+    let mut tree = Tree::new(node_label("Baseline", None, None));
+    for host in hosts {
+        let ports: Vec<String> = host
+            .get_open_port_numbers()
+            .iter()
+            .map(|port| port.to_string())
+            .collect();
+        let mut host_tree = Tree::new(node_label(&host.ip_addr.to_string(), None, None));
+        if !host.hostname.is_empty() && host.hostname != host.ip_addr.to_string() {
+            host_tree.push(node_label("Host Name", Some(&host.hostname), None));
+        }
+        if !ports.is_empty() {
+            host_tree.push(node_label("Open Ports", Some(&ports.join(", ")), None));
+        }
+        tree.push(host_tree);
+    }
+    println!("{}", tree);
+}
@@ -0,0 +1,226 @@
+use crate::output;
+use crate::util::tree::node_label;
+use crate::whois::rdap::{RdapQuery, RdapResult};
+use crate::whois::WhoisResult;
+use clap::ArgMatches;
+use std::path::PathBuf;
+use std::time::Duration;
+use termtree::Tree;
+
+pub fn handle_whois(args: &ArgMatches) {
+    let whois_args = match args.subcommand_matches("whois") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let query: String = match whois_args.get_one::<String>("query") {
+        Some(query) => query.trim().trim_end_matches('.').to_lowercase(),
+        None => return,
+    };
+    let server: Option<&String> = whois_args.get_one::<String>("server");
+    let timeout: Duration = match whois_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(10000),
+    };
+    output::log_with_time(&format!("Looking up whois of {}...", query), "INFO");
+    let result: WhoisResult =
+        match crate::whois::lookup(&query, server.map(|s| s.as_str()), timeout) {
+            Ok(result) => result,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        };
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else if whois_args.get_flag("raw") {
+        for response in &result.responses {
+            println!("% {}", response.server);
+            println!("{}", response.text.trim_end());
+            println!();
+        }
+    } else {
+        show_whois_result(&result);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_whois_result(result: &WhoisResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let fields = &result.fields;
+    let mut tree = Tree::new(node_label("Whois", Some(&result.query), None));
+    let values: [(&str, &Option<String>); 9] = [
+        ("Registrar", &fields.registrar),
+        ("Created", &fields.creation_date),
+        ("Updated", &fields.updated_date),
+        ("Expires", &fields.expiry_date),
+        ("NetName", &fields.netname),
+        ("Network", &fields.net_range),
+        ("Organization", &fields.organization),
+        ("Country", &fields.country),
+        ("Abuse Contact", &fields.abuse_contact),
+    ];
+    for (label, value) in values {
+        if let Some(value) = value {
+            tree.push(node_label(label, Some(value), None));
+        }
+    }
+    if !fields.status.is_empty() {
+        let mut status_tree = Tree::new(node_label("Status", None, None));
+        for status in &fields.status {
+            status_tree.push(node_label(status, None, None));
+        }
+        tree.push(status_tree);
+    }
+    if !fields.name_servers.is_empty() {
+        let mut ns_tree = Tree::new(node_label("Name Servers", None, None));
+        for ns in &fields.name_servers {
+            ns_tree.push(node_label(ns, None, None));
+        }
+        tree.push(ns_tree);
+    }
+    let servers: Vec<&str> = result.responses.iter().map(|r| r.server.as_str()).collect();
+    tree.push(node_label("Servers", Some(&servers.join(" -> ")), None));
+    tree.push(node_label(
+        "Lookup Time",
+        Some(&format!("{:?}", result.lookup_time)),
+        None,
+    ));
+    println!("{}", tree);
+}
+
+pub fn handle_rdap(args: &ArgMatches) {
+    let rdap_args = match args.subcommand_matches("rdap") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let query: RdapQuery = match rdap_args
+        .get_one::<String>("query")
+        .and_then(|query| RdapQuery::parse(query))
+    {
+        Some(query) => query,
+        None => {
+            output::log_with_time("Invalid query. Specify IP address, ASN or domain", "ERROR");
+            return;
+        }
+    };
+    let server: Option<&String> = rdap_args.get_one::<String>("server");
+    let timeout: Duration = match rdap_args.get_one::<u64>("timeout") {
+        Some(timeout) => Duration::from_millis(*timeout),
+        None => Duration::from_millis(10000),
+    };
+    output::log_with_time(&format!("Looking up RDAP of {:?}...", query), "INFO");
+    let result: RdapResult =
+        match crate::whois::rdap::lookup(query, server.map(|s| s.as_str()), timeout) {
+            Ok(result) => result,
+            Err(e) => {
+                output::log_with_time(&e, "ERROR");
+                return;
+            }
+        };
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    } else if rdap_args.get_flag("raw") {
+        println!("{}", serde_json::to_string_pretty(&result.raw).unwrap());
+    } else {
+        show_rdap_result(&result);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn show_rdap_result(result: &RdapResult) {
+    if !crate::app::is_quiet_mode() {
+        println!();
+    }
+    let mut tree = Tree::new(node_label("RDAP", Some(&result.object_class), None));
+    let values: [(&str, &String); 4] = [
+        ("Handle", &result.handle),
+        ("Name", &result.name),
+        ("Range", &result.range),
+        ("Country", &result.country),
+    ];
+    for (label, value) in values {
+        if !value.is_empty() {
+            tree.push(node_label(label, Some(value), None));
+        }
+    }
+    if !result.status.is_empty() {
+        tree.push(node_label("Status", Some(&result.status.join(", ")), None));
+    }
+    if !result.events.is_empty() {
+        let mut events_tree = Tree::new(node_label("Events", None, None));
+        for event in &result.events {
+            events_tree.push(node_label(&event.action, Some(&event.date), None));
+        }
+        tree.push(events_tree);
+    }
+    if !result.entities.is_empty() {
+        let mut entities_tree = Tree::new(node_label("Entities", None, None));
+        for entity in &result.entities {
+            let label: String = if entity.name.is_empty() {
+                entity.handle.clone()
+            } else {
+                entity.name.clone()
+            };
+            let mut entity_tree = Tree::new(node_label(&label, None, None));
+            entity_tree.push(node_label("Roles", Some(&entity.roles.join(", ")), None));
+            if !entity.email.is_empty() {
+                entity_tree.push(node_label("Email", Some(&entity.email), None));
+            }
+            entities_tree.push(entity_tree);
+        }
+        tree.push(entities_tree);
+    }
+    if !result.name_servers.is_empty() {
+        let mut ns_tree = Tree::new(node_label("Name Servers", None, None));
+        for ns in &result.name_servers {
+            ns_tree.push(node_label(ns, None, None));
+        }
+        tree.push(ns_tree);
+    }
+    if let Some(abuse) = result.contact_of("abuse") {
+        if !abuse.email.is_empty() {
+            tree.push(node_label("Abuse Contact", Some(&abuse.email), None));
+        }
+    }
+    tree.push(node_label("Server", Some(&result.url), None));
+    tree.push(node_label(
+        "Lookup Time",
+        Some(&format!("{:?}", result.lookup_time)),
+        None,
+    ));
+    println!("{}", tree);
+}
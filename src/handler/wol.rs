@@ -0,0 +1,162 @@
+use crate::neighbor::wol::{WolResult, DEFAULT_WOL_PORT};
+use crate::output;
+use crate::ping::pinger::Pinger;
+use crate::ping::setting::PingSetting;
+use clap::ArgMatches;
+use netdev::mac::MacAddr;
+use netdev::Interface;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub fn handle_wol(args: &ArgMatches) {
+    let wol_args = match args.subcommand_matches("wol") {
+        Some(matches) => matches,
+        None => return,
+    };
+    let mac_addr: MacAddr = match wol_args
+        .get_one::<String>("mac")
+        .and_then(|mac| crate::neighbor::parse_mac_addr(mac))
+    {
+        Some(mac_addr) => mac_addr,
+        None => {
+            output::log_with_time("Invalid MAC address", "ERROR");
+            return;
+        }
+    };
+    let interface: Interface = if let Some(if_name) = args.get_one::<String>("interface") {
+        match crate::interface::get_interface_by_name(if_name.to_string()) {
+            Some(iface) => iface,
+            None => return,
+        }
+    } else {
+        match netdev::get_default_interface() {
+            Ok(iface) => iface,
+            Err(_) => return,
+        }
+    };
+    let port: u16 = match wol_args.get_one::<u16>("port") {
+        Some(port) => *port,
+        None => DEFAULT_WOL_PORT,
+    };
+    // Directed broadcast of the interface unless specified
+    let (broadcast, src_ip): (Ipv4Addr, Option<Ipv4Addr>) =
+        match wol_args.get_one::<Ipv4Addr>("broadcast") {
+            Some(broadcast) => (*broadcast, None),
+            None => match interface.ipv4.first() {
+                Some(ipv4) => match ipnet::Ipv4Net::new(ipv4.addr, ipv4.prefix_len) {
+                    Ok(net) => (net.broadcast(), Some(ipv4.addr)),
+                    Err(_) => (Ipv4Addr::BROADCAST, Some(ipv4.addr)),
+                },
+                None => (Ipv4Addr::BROADCAST, None),
+            },
+        };
+    let count: u32 = match wol_args.get_one::<u32>("count") {
+        Some(count) => *count,
+        None => 3,
+    };
+    let dst_addr: SocketAddr = SocketAddr::new(IpAddr::V4(broadcast), port);
+    output::log_with_time(
+        &format!(
+            "Sending magic packet for {} to {}...",
+            mac_addr.address(),
+            dst_addr
+        ),
+        "INFO",
+    );
+    let start_time: Instant = Instant::now();
+    let sent: u32 = match crate::neighbor::wol::send_magic_packet(
+        &mac_addr,
+        dst_addr,
+        src_ip,
+        count,
+        Duration::from_millis(100),
+    ) {
+        Ok(sent) => sent,
+        Err(e) => {
+            output::log_with_time(&format!("Wake-on-LAN Failed: {}", e), "ERROR");
+            return;
+        }
+    };
+    output::log_with_time(&format!("Sent {} magic packets", sent), "INFO");
+    let mut result = WolResult {
+        mac_addr: mac_addr,
+        dst_addr: dst_addr,
+        sent: sent,
+        verify_ip: None,
+        up: false,
+        wake_time: None,
+    };
+    // Ping until the host answers or the wait time elapses
+    if let Some(verify_ip) = wol_args.get_one::<IpAddr>("verify") {
+        let wait: u64 = match wol_args.get_one::<u64>("wait") {
+            Some(wait) => *wait,
+            None => 60,
+        };
+        result.verify_ip = Some(*verify_ip);
+        output::log_with_time(
+            &format!("Waiting up to {}s for {} to come up...", wait, verify_ip),
+            "INFO",
+        );
+        if let Some(rtt) = wait_for_host(&interface, *verify_ip, wait as u32) {
+            result.up = true;
+            result.wake_time = Some(start_time.elapsed());
+            output::log_with_time(
+                &format!(
+                    "{} is up after {:?}. RTT:{:?}",
+                    verify_ip,
+                    start_time.elapsed(),
+                    rtt
+                ),
+                "INFO",
+            );
+        } else {
+            output::log_with_time(
+                &format!("{} did not answer within {}s", verify_ip, wait),
+                "WARN",
+            );
+        }
+    }
+    if args.get_flag("json") {
+        let json_result = serde_json::to_string_pretty(&result).unwrap();
+        println!("{}", json_result);
+    }
+    match args.get_one::<PathBuf>("save") {
+        Some(file_path) => {
+            match crate::fs::save_text(file_path, serde_json::to_string_pretty(&result).unwrap()) {
+                Ok(_) => {
+                    output::log_with_time(
+                        &format!("Saved to {}", file_path.to_string_lossy()),
+                        "INFO",
+                    );
+                }
+                Err(e) => {
+                    output::log_with_time(&format!("Failed to save: {}", e), "ERROR");
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+/// ICMP ping once a second. Returns RTT of the first reply
+fn wait_for_host(interface: &Interface, ip_addr: IpAddr, count: u32) -> Option<Duration> {
+    let setting: PingSetting = PingSetting::icmp_ping(interface, ip_addr, count.max(1)).ok()?;
+    let pinger: Pinger = Pinger::new(setting).ok()?;
+    let rx = pinger.get_progress_receiver();
+    let stop_handle = pinger.get_stop_handle();
+    let handle = thread::spawn(move || pinger.ping());
+    let mut rtt: Option<Duration> = None;
+    for r in rx.lock().unwrap().iter() {
+        if r.probe_status.kind == crate::probe::ProbeStatusKind::Done {
+            rtt = Some(r.rtt);
+            if let Ok(mut stop) = stop_handle.lock() {
+                *stop = true;
+            }
+            break;
+        }
+    }
+    let _ = handle.join();
+    rtt
+}
@@ -2,7 +2,13 @@ use netdev::mac::MacAddr;
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 
+use crate::cve::CveInfo;
+use crate::db::geoip::GeoInfo;
 use crate::dns;
+use crate::enrich::ExternalInfo;
+use crate::protocol::Protocol;
+use crate::smb::SmbInfo;
+use crate::ssh::SshAuditResult;
 
 /// Status of the scanned port
 #[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
@@ -43,6 +49,12 @@ pub struct Port {
     pub service_name: String,
     /// Service version
     pub service_version: String,
+    /// SSH banner and algorithms. Set by --ssh-audit
+    pub ssh: Option<SshAuditResult>,
+    /// CPEs of the products in the service version
+    pub cpes: Vec<String>,
+    /// Known CVEs of the CPEs. Set by --cve-db or --cve-api
+    pub cves: Vec<CveInfo>,
 }
 
 impl Port {
@@ -52,6 +64,9 @@ impl Port {
             status: PortStatus::Unknown,
             service_name: String::new(),
             service_version: String::new(),
+            ssh: None,
+            cpes: Vec::new(),
+            cves: Vec::new(),
         }
     }
 }
@@ -71,8 +86,18 @@ pub struct Host {
     pub vendor_name: String,
     /// OS Family
     pub os_family: String,
+    /// Probable OS family guessed from the initial TTL
+    pub os_hint: String,
+    /// Protocols that confirmed the host is up
+    pub discovered_by: Vec<Protocol>,
     /// TTL
     pub ttl: u8,
+    /// Geolocation from GeoIP database
+    pub geo: Option<GeoInfo>,
+    /// SMB negotiation and NTLM host information (445/139)
+    pub smb: Option<SmbInfo>,
+    /// Ports and tags seen by Shodan or Censys. Set by --enrich
+    pub external: Option<ExternalInfo>,
 }
 
 impl Host {
@@ -84,7 +109,12 @@ impl Host {
             mac_addr: MacAddr::zero(),
             vendor_name: String::new(),
             os_family: String::new(),
+            os_hint: String::new(),
+            discovered_by: Vec::new(),
             ttl: 0,
+            geo: None,
+            smb: None,
+            external: None,
         }
     }
     pub fn with_port_range(mut self, start: u16, end: u16) -> Self {
@@ -0,0 +1,332 @@
+pub mod probe;
+
+use nex::socket::tls::rustls;
+use nex::socket::tls::TlsClient;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::config::MAX_HTTP_RESPONSE_SIZE;
+use crate::dns::setting::DEFAULT_USER_AGENT_FIREFOX;
+
+/// Parsed http/https URL
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpUrl {
+    pub tls: bool,
+    pub host: String,
+    pub port: u16,
+    /// Path with query string
+    pub path: String,
+}
+
+impl HttpUrl {
+    pub fn parse(url: &str) -> Result<HttpUrl, String> {
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(format!("Unsupported URL: {}", url));
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let default_port: u16 = if tls { 443 } else { 80 };
+        // IPv6 literal is enclosed in brackets (e.g. [::1]:8080)
+        let (host, port_str): (&str, Option<&str>) = if authority.starts_with('[') {
+            match authority.find(']') {
+                Some(i) => (&authority[1..i], authority[i + 1..].strip_prefix(':')),
+                None => return Err(format!("Invalid URL: {}", url)),
+            }
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            }
+        };
+        let port: u16 = match port_str {
+            Some(port) => port
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid port in URL: {}", url))?,
+            None => default_port,
+        };
+        if host.is_empty() {
+            return Err(format!("Invalid URL: {}", url));
+        }
+        Ok(HttpUrl {
+            tls: tls,
+            host: host.to_string(),
+            port: port,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// HTTP response
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Header fields in received order. Names are lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name: String = name.to_lowercase();
+        self.headers
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+    }
+    pub fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+    /// 3xx with Location header
+    pub fn is_redirect(&self) -> bool {
+        self.status >= 300 && self.status < 400 && self.header("location").is_some()
+    }
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+}
+
+/// Resolve Location header against the request URL
+pub fn join_url(base: &HttpUrl, location: &str) -> String {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        return location.to_string();
+    }
+    let scheme: &str = if base.tls { "https" } else { "http" };
+    let default_port: u16 = if base.tls { 443 } else { 80 };
+    let host: String = if base.host.contains(':') {
+        format!("[{}]", base.host)
+    } else {
+        base.host.clone()
+    };
+    let authority: String = if base.port == default_port {
+        host
+    } else {
+        format!("{}:{}", host, base.port)
+    };
+    if let Some(rest) = location.strip_prefix("//") {
+        format!("{}://{}", scheme, rest)
+    } else if location.starts_with('/') {
+        format!("{}://{}{}", scheme, authority, location)
+    } else {
+        // Relative to the directory of the path
+        let path: &str = base.path.split('?').next().unwrap_or("/");
+        let dir: &str = &path[..path.rfind('/').map(|i| i + 1).unwrap_or(0)];
+        format!("{}://{}{}{}", scheme, authority, dir, location)
+    }
+}
+
+/// Send GET request, following redirects up to max_redirects
+pub fn get_following(
+    url: &str,
+    headers: &[(&str, &str)],
+    max_redirects: usize,
+    timeout: Duration,
+) -> Result<(String, HttpResponse), String> {
+    let mut url: String = url.to_string();
+    for _ in 0..=max_redirects {
+        let response: HttpResponse = get(&url, headers, timeout)?;
+        if !response.is_redirect() {
+            return Ok((url, response));
+        }
+        let location: &str = response.header("location").unwrap_or_default();
+        url = join_url(&HttpUrl::parse(&url)?, location);
+    }
+    Err(format!("Too many redirects: {}", url))
+}
+
+/// Percent-encode a query component
+pub fn encode_query(value: &str) -> String {
+    let mut encoded: String = String::new();
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// Send GET request
+pub fn get(url: &str, headers: &[(&str, &str)], timeout: Duration) -> Result<HttpResponse, String> {
+    request("GET", url, headers, None, timeout)
+}
+
+/// Send POST request with body
+pub fn post(
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    timeout: Duration,
+) -> Result<HttpResponse, String> {
+    request("POST", url, headers, Some(body), timeout)
+}
+
+/// Send HTTP/1.1 request over a new connection (http or https)
+pub fn request(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&[u8]>,
+    timeout: Duration,
+) -> Result<HttpResponse, String> {
+    let url: HttpUrl = HttpUrl::parse(url)?;
+    let socket_addr: SocketAddr = match (url.host.as_str(), url.port).to_socket_addrs() {
+        Ok(mut addrs) => match addrs.next() {
+            Some(addr) => addr,
+            None => return Err(format!("Failed to resolve {}", url.host)),
+        },
+        Err(e) => return Err(format!("Failed to resolve {}: {}", url.host, e)),
+    };
+    let stream: TcpStream = match TcpStream::connect_timeout(&socket_addr, timeout) {
+        Ok(stream) => stream,
+        Err(e) => return Err(format!("Failed to connect to {}: {}", socket_addr, e)),
+    };
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    let mut req: Vec<u8> = Vec::new();
+    req.extend_from_slice(format!("{} {} HTTP/1.1\r\n", method, url.path).as_bytes());
+    let host_header: String = if url.port == 80 || url.port == 443 {
+        url.host.clone()
+    } else {
+        format!("{}:{}", url.host, url.port)
+    };
+    req.extend_from_slice(format!("Host: {}\r\n", host_header).as_bytes());
+    if !headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("user-agent"))
+    {
+        req.extend_from_slice(format!("User-Agent: {}\r\n", DEFAULT_USER_AGENT_FIREFOX).as_bytes());
+    }
+    for (name, value) in headers {
+        req.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+    }
+    if let Some(body) = body {
+        req.extend_from_slice(format!("Content-Length: {}\r\n", body.len()).as_bytes());
+    }
+    req.extend_from_slice(b"Connection: close\r\n\r\n");
+    if let Some(body) = body {
+        req.extend_from_slice(body);
+    }
+    let mut raw: Vec<u8> = Vec::new();
+    if url.tls {
+        let native_certs = match nex::socket::tls::certs::get_native_certs() {
+            Ok(certs) => certs,
+            Err(e) => return Err(format!("Failed to load root certificates: {}", e)),
+        };
+        let config = rustls::ClientConfig::builder()
+            .with_root_certificates(native_certs)
+            .with_no_client_auth();
+        let mut tls_client = match TlsClient::new(url.host.clone(), stream, config) {
+            Ok(tls_client) => tls_client,
+            Err(e) => return Err(format!("TLS handshake with {} failed: {}", url.host, e)),
+        };
+        tls_client
+            .write_all(&req)
+            .and_then(|_| tls_client.flush())
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+        read_response(&mut tls_client, &mut raw)?;
+    } else {
+        let mut stream = stream;
+        stream
+            .write_all(&req)
+            .and_then(|_| stream.flush())
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+        read_response(&mut stream, &mut raw)?;
+    }
+    parse_response(&raw)
+}
+
+/// Read until the server closes the connection or MAX_HTTP_RESPONSE_SIZE is reached.
+/// Data read before a timeout or unclean TLS close is kept.
+fn read_response<R: Read>(reader: &mut R, raw: &mut Vec<u8>) -> Result<(), String> {
+    let mut buf: [u8; 8192] = [0; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                if raw.len() + n > MAX_HTTP_RESPONSE_SIZE {
+                    return Err(format!("Response exceeds {} bytes", MAX_HTTP_RESPONSE_SIZE));
+                }
+                raw.extend_from_slice(&buf[..n]);
+            }
+            Err(e) => {
+                if raw.is_empty() {
+                    return Err(format!("Failed to read response: {}", e));
+                }
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn parse_response(raw: &[u8]) -> Result<HttpResponse, String> {
+    let header_end: usize = match raw.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(i) => i,
+        None => return Err(String::from("Malformed HTTP response")),
+    };
+    let head: String = String::from_utf8_lossy(&raw[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let status_line: &str = lines.next().unwrap_or_default();
+    let status: u16 = match status_line.split_whitespace().nth(1) {
+        Some(code) => code
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid status line: {}", status_line))?,
+        None => return Err(format!("Invalid status line: {}", status_line)),
+    };
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    let mut response = HttpResponse {
+        status: status,
+        headers: headers,
+        body: raw[header_end + 4..].to_vec(),
+    };
+    if response
+        .header("transfer-encoding")
+        .map(|v| v.to_lowercase().contains("chunked"))
+        .unwrap_or(false)
+    {
+        response.body = decode_chunked(&response.body);
+    }
+    Ok(response)
+}
+
+fn decode_chunked(data: &[u8]) -> Vec<u8> {
+    let mut body: Vec<u8> = Vec::new();
+    let mut pos: usize = 0;
+    while pos < data.len() {
+        let line_end: usize = match data[pos..].windows(2).position(|w| w == b"\r\n") {
+            Some(i) => pos + i,
+            None => break,
+        };
+        let size_str: String = String::from_utf8_lossy(&data[pos..line_end]).to_string();
+        let size: usize =
+            match usize::from_str_radix(size_str.split(';').next().unwrap_or("").trim(), 16) {
+                Ok(size) => size,
+                Err(_) => break,
+            };
+        if size == 0 {
+            break;
+        }
+        let start: usize = line_end + 2;
+        // Chunk size comes from the server
+        let end: usize = match start.checked_add(size) {
+            Some(end) => std::cmp::min(end, data.len()),
+            None => break,
+        };
+        body.extend_from_slice(&data[start..end]);
+        pos = end + 2;
+    }
+    body
+}
@@ -0,0 +1,204 @@
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{HttpResponse, HttpUrl};
+
+/// Settings of HTTP probing
+#[derive(Clone, Debug)]
+pub struct HttpProbeSetting {
+    pub concurrency: usize,
+    /// Timeout of each request
+    pub timeout: Duration,
+    /// Max number of redirects to follow. 0 to not follow
+    pub max_redirects: usize,
+}
+
+/// Hop of the redirect chain
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HttpRedirect {
+    pub url: String,
+    pub status: u16,
+}
+
+/// Result of probing one target
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HttpProbeResult {
+    /// Target as specified
+    pub target: String,
+    /// Final URL after redirects
+    pub url: String,
+    /// Status of the final response. None if no response
+    pub status: Option<u16>,
+    pub redirects: Vec<HttpRedirect>,
+    pub server: String,
+    pub title: String,
+    pub content_type: String,
+    pub content_length: usize,
+    /// Time until the final response, including redirects
+    pub response_time: Duration,
+    pub error: Option<String>,
+}
+
+/// Result of HTTP probing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HttpProbeScanResult {
+    /// Results in the order of the targets
+    pub results: Vec<HttpProbeResult>,
+    pub scan_time: Duration,
+}
+
+/// URLs to try for the target. Scheme-less targets are tried with https, then http
+pub fn candidate_urls(target: &str) -> Vec<String> {
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return vec![target.to_string()];
+    }
+    vec![format!("https://{}", target), format!("http://{}", target)]
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Text of the title element, whitespace collapsed
+pub fn extract_title(body: &str) -> String {
+    let lower: String = body.to_lowercase();
+    let start: usize = match lower.find("<title") {
+        Some(i) => match lower[i..].find('>') {
+            Some(j) => i + j + 1,
+            None => return String::new(),
+        },
+        None => return String::new(),
+    };
+    let end: usize = match lower[start..].find("</title") {
+        Some(i) => start + i,
+        None => return String::new(),
+    };
+    // Lowercasing may change byte offsets of non-ASCII text
+    let title: &str = body.get(start..end).unwrap_or_default();
+    decode_entities(&title.split_whitespace().collect::<Vec<&str>>().join(" "))
+}
+
+/// Request the URL, following redirects
+fn probe_url(
+    url: &str,
+    setting: &HttpProbeSetting,
+) -> Result<(String, Vec<HttpRedirect>, HttpResponse), String> {
+    let mut url: String = url.to_string();
+    let mut redirects: Vec<HttpRedirect> = Vec::new();
+    loop {
+        let response: HttpResponse = super::get(&url, &[("Accept", "*/*")], setting.timeout)?;
+        if !response.is_redirect() || redirects.len() >= setting.max_redirects {
+            return Ok((url, redirects, response));
+        }
+        let location: &str = response.header("location").unwrap_or_default();
+        let next: String = super::join_url(&HttpUrl::parse(&url)?, location);
+        redirects.push(HttpRedirect {
+            url: url,
+            status: response.status,
+        });
+        // Loop or unsupported scheme
+        if redirects.iter().any(|r| r.url == next) || HttpUrl::parse(&next).is_err() {
+            return Err(format!("Redirect loop or unsupported location: {}", next));
+        }
+        url = next;
+    }
+}
+
+/// Probe the target. For scheme-less targets the first URL that answers is used
+pub fn probe(target: &str, setting: &HttpProbeSetting) -> HttpProbeResult {
+    let start_time: Instant = Instant::now();
+    let mut result = HttpProbeResult {
+        target: target.to_string(),
+        url: String::new(),
+        status: None,
+        redirects: vec![],
+        server: String::new(),
+        title: String::new(),
+        content_type: String::new(),
+        content_length: 0,
+        response_time: Duration::ZERO,
+        error: None,
+    };
+    let mut errors: Vec<String> = Vec::new();
+    for url in candidate_urls(target) {
+        match probe_url(&url, setting) {
+            Ok((url, redirects, response)) => {
+                result.url = url;
+                result.status = Some(response.status);
+                result.redirects = redirects;
+                result.server = response.header("server").unwrap_or_default().to_string();
+                result.content_type = response
+                    .header("content-type")
+                    .unwrap_or_default()
+                    .to_string();
+                result.content_length = response.body.len();
+                result.title = extract_title(&response.text());
+                result.response_time = start_time.elapsed();
+                return result;
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    result.url = candidate_urls(target).remove(0);
+    result.response_time = start_time.elapsed();
+    result.error = Some(errors.join(", "));
+    result
+}
+
+/// Probe the targets in parallel.
+/// Each probed target is sent to the progress channel
+pub async fn probe_targets(
+    targets: Vec<String>,
+    setting: HttpProbeSetting,
+    ptx: Arc<Mutex<Sender<String>>>,
+) -> HttpProbeScanResult {
+    let start_time: Instant = Instant::now();
+    let setting: &HttpProbeSetting = &setting;
+    let ptx: &Arc<Mutex<Sender<String>>> = &ptx;
+    let mut results: Vec<(usize, HttpProbeResult)> = stream::iter(targets.into_iter().enumerate())
+        .map(|(i, target)| async move {
+            let probe_setting: HttpProbeSetting = setting.clone();
+            // Requests are blocking. Run each on the blocking pool
+            let result: HttpProbeResult = match tokio::task::spawn_blocking({
+                let target: String = target.clone();
+                move || probe(&target, &probe_setting)
+            })
+            .await
+            {
+                Ok(result) => result,
+                Err(e) => HttpProbeResult {
+                    target: target.clone(),
+                    url: String::new(),
+                    status: None,
+                    redirects: vec![],
+                    server: String::new(),
+                    title: String::new(),
+                    content_type: String::new(),
+                    content_length: 0,
+                    response_time: Duration::ZERO,
+                    error: Some(e.to_string()),
+                },
+            };
+            if let Ok(lr) = ptx.lock() {
+                let _ = lr.send(target);
+            }
+            (i, result)
+        })
+        .buffer_unordered(setting.concurrency)
+        .collect()
+        .await;
+    results.sort_by_key(|(i, _)| *i);
+    HttpProbeScanResult {
+        results: results.into_iter().map(|(_, result)| result).collect(),
+        scan_time: start_time.elapsed(),
+    }
+}
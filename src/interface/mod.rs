@@ -1,3 +1,5 @@
+pub mod stats;
+
 use netdev::interface::Interface;
 use netdev::mac::MacAddr;
 use std::{
@@ -64,6 +66,28 @@ pub fn get_interface_local_ipv6(iface: &Interface) -> Option<IpAddr> {
     return None;
 }
 
+/// Check if the IP address is in one of the networks directly connected to the interface
+pub fn is_on_link(iface: &Interface, ip_addr: &IpAddr) -> bool {
+    match ip_addr {
+        IpAddr::V4(ipv4_addr) => {
+            iface.ipv4.iter().any(
+                |ipv4| match ipnet::Ipv4Net::new(ipv4.addr, ipv4.prefix_len) {
+                    Ok(net) => net.contains(ipv4_addr),
+                    Err(_) => false,
+                },
+            )
+        }
+        IpAddr::V6(ipv6_addr) => {
+            iface.ipv6.iter().any(
+                |ipv6| match ipnet::Ipv6Net::new(ipv6.addr, ipv6.prefix_len) {
+                    Ok(net) => net.contains(ipv6_addr),
+                    Err(_) => false,
+                },
+            )
+        }
+    }
+}
+
 pub fn get_interface_ips(iface: &Interface) -> Vec<String> {
     let mut ips: Vec<String> = Vec::new();
     for ip in iface.ipv4.clone() {
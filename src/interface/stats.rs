@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Traffic counters of an interface
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InterfaceStats {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+    pub tx_dropped: u64,
+}
+
+/// Counters with rates over the sampling interval
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InterfaceTraffic {
+    pub stats: InterfaceStats,
+    /// Received bytes per second
+    pub rx_rate: f64,
+    /// Sent bytes per second
+    pub tx_rate: f64,
+    pub rx_packet_rate: f64,
+    pub tx_packet_rate: f64,
+}
+
+impl InterfaceTraffic {
+    /// Rates from two samples of the same interface
+    pub fn from_samples(
+        prev: &InterfaceStats,
+        curr: &InterfaceStats,
+        elapsed: Duration,
+    ) -> InterfaceTraffic {
+        let secs: f64 = elapsed.as_secs_f64().max(0.001);
+        // Counters may be reset or wrap
+        let rate = |prev: u64, curr: u64| curr.saturating_sub(prev) as f64 / secs;
+        InterfaceTraffic {
+            stats: curr.clone(),
+            rx_rate: rate(prev.rx_bytes, curr.rx_bytes),
+            tx_rate: rate(prev.tx_bytes, curr.tx_bytes),
+            rx_packet_rate: rate(prev.rx_packets, curr.rx_packets),
+            tx_packet_rate: rate(prev.tx_packets, curr.tx_packets),
+        }
+    }
+}
+
+/// /sys/class/net/<name>/statistics
+#[cfg(target_os = "linux")]
+pub fn get_interface_stats() -> Result<Vec<InterfaceStats>, String> {
+    let dir = std::fs::read_dir("/sys/class/net")
+        .map_err(|e| format!("Failed to read /sys/class/net: {}", e))?;
+    let mut stats_list: Vec<InterfaceStats> = Vec::new();
+    for entry in dir.flatten() {
+        let name: String = entry.file_name().to_string_lossy().to_string();
+        let counter = |counter: &str| -> u64 {
+            std::fs::read_to_string(entry.path().join("statistics").join(counter))
+                .ok()
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        stats_list.push(InterfaceStats {
+            rx_bytes: counter("rx_bytes"),
+            rx_packets: counter("rx_packets"),
+            rx_errors: counter("rx_errors"),
+            rx_dropped: counter("rx_dropped"),
+            tx_bytes: counter("tx_bytes"),
+            tx_packets: counter("tx_packets"),
+            tx_errors: counter("tx_errors"),
+            tx_dropped: counter("tx_dropped"),
+            name: name,
+        });
+    }
+    stats_list.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(stats_list)
+}
+
+/// netstat -ibn
+/// Name Mtu Network Address Ipkts Ierrs Ibytes Opkts Oerrs Obytes Coll [Drop]
+#[cfg(target_os = "macos")]
+pub fn get_interface_stats() -> Result<Vec<InterfaceStats>, String> {
+    let output = std::process::Command::new("netstat")
+        .args(["-ibnd"])
+        .output()
+        .map_err(|e| format!("Failed to run netstat: {}", e))?;
+    let text: String = String::from_utf8_lossy(&output.stdout).to_string();
+    let mut lines = text.lines();
+    let header: Vec<&str> = match lines.next() {
+        Some(header) => header.split_whitespace().collect(),
+        None => return Err("No output from netstat".to_string()),
+    };
+    let column = |name: &str| header.iter().position(|h| *h == name);
+    let mut stats_list: Vec<InterfaceStats> = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // One row per address. The link row carries the counters
+        if fields.len() < 3 || !fields[2].starts_with("<Link#") {
+            continue;
+        }
+        // The link row has no address column if the interface has no MAC address
+        let offset: usize = header.len().saturating_sub(fields.len());
+        let value = |name: &str| -> u64 {
+            column(name)
+                .and_then(|i| i.checked_sub(offset))
+                .and_then(|i| fields.get(i))
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        stats_list.push(InterfaceStats {
+            name: fields[0].trim_end_matches('*').to_string(),
+            rx_bytes: value("Ibytes"),
+            rx_packets: value("Ipkts"),
+            rx_errors: value("Ierrs"),
+            rx_dropped: 0,
+            tx_bytes: value("Obytes"),
+            tx_packets: value("Opkts"),
+            tx_errors: value("Oerrs"),
+            tx_dropped: value("Drop"),
+        });
+    }
+    stats_list.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(stats_list)
+}
+
+/// Get-NetAdapterStatistics in CSV
+#[cfg(target_os = "windows")]
+pub fn get_interface_stats() -> Result<Vec<InterfaceStats>, String> {
+    let command: &str = "Get-NetAdapterStatistics | Select-Object Name,ReceivedBytes,ReceivedUnicastPackets,ReceivedPacketErrors,ReceivedDiscardedPackets,SentBytes,SentUnicastPackets,OutboundPacketErrors,OutboundDiscardedPackets | ConvertTo-Csv -NoTypeInformation";
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-Command", command])
+        .output()
+        .map_err(|e| format!("Failed to run powershell: {}", e))?;
+    let text: String = String::from_utf8_lossy(&output.stdout).to_string();
+    let mut stats_list: Vec<InterfaceStats> = Vec::new();
+    for line in text.lines().skip(1) {
+        let fields: Vec<String> = line
+            .split("\",\"")
+            .map(|field| field.trim_matches('"').to_string())
+            .collect();
+        if fields.len() < 9 {
+            continue;
+        }
+        let value = |i: usize| fields[i].parse::<u64>().unwrap_or(0);
+        stats_list.push(InterfaceStats {
+            name: fields[0].clone(),
+            rx_bytes: value(1),
+            rx_packets: value(2),
+            rx_errors: value(3),
+            rx_dropped: value(4),
+            tx_bytes: value(5),
+            tx_packets: value(6),
+            tx_errors: value(7),
+            tx_dropped: value(8),
+        });
+    }
+    stats_list.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(stats_list)
+}
+
+/// Human-readable bytes (e.g. 1.5 MB)
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value: f64 = bytes;
+    let mut unit: usize = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", value as u64, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
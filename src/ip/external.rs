@@ -0,0 +1,266 @@
+use hickory_resolver::proto::rr::RecordType;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+/// STUN servers tried in order
+pub const DEFAULT_STUN_SERVERS: [&str; 2] = ["stun.l.google.com:19302", "stun.cloudflare.com:3478"];
+
+/// OpenDNS answers myip.opendns.com with the address of the client
+const OPENDNS_MYIP_NAME: &str = "myip.opendns.com";
+const OPENDNS_RESOLVER_V4: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(208, 67, 222, 222)), 53);
+const OPENDNS_RESOLVER_V6: SocketAddr = SocketAddr::new(
+    IpAddr::V6(Ipv6Addr::new(0x2620, 0x119, 0x35, 0, 0, 0, 0, 0x35)),
+    53,
+);
+
+/// HTTP endpoints that return the client address as text
+const HTTP_ENDPOINT_V4: &str = "https://ipv4.icanhazip.com/";
+const HTTP_ENDPOINT_V6: &str = "https://ipv6.icanhazip.com/";
+
+const STUN_MAGIC_COOKIE: u32 = 0x2112A442;
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_RESPONSE: u16 = 0x0101;
+const STUN_ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const STUN_ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+/// Method to find the external address
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExternalIpMethod {
+    Stun,
+    Dns,
+    Http,
+}
+
+impl ExternalIpMethod {
+    pub fn from_str(s: &str) -> Option<ExternalIpMethod> {
+        match s {
+            "stun" => Some(ExternalIpMethod::Stun),
+            "dns" => Some(ExternalIpMethod::Dns),
+            "http" => Some(ExternalIpMethod::Http),
+            _ => None,
+        }
+    }
+}
+
+/// External address of one address family
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExternalAddr {
+    pub ip_addr: IpAddr,
+    pub method: ExternalIpMethod,
+    /// Server that answered
+    pub server: String,
+    /// Local address and port of the request (STUN only)
+    pub local_addr: Option<SocketAddr>,
+    /// Port seen by the server (STUN only)
+    pub mapped_port: Option<u16>,
+}
+
+/// Result of external address detection
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExternalIpResult {
+    pub ipv4: Option<ExternalAddr>,
+    pub ipv6: Option<ExternalAddr>,
+    /// Addresses of the local interfaces
+    pub local_ips: Vec<IpAddr>,
+    /// True if the external IPv4 address is not a local address. None if unknown
+    pub ipv4_nat: Option<bool>,
+    /// True if the external IPv6 address is not a local address (NPTv6/NAT66). None if unknown
+    pub ipv6_nat: Option<bool>,
+}
+
+fn build_stun_request(transaction_id: &[u8; 12]) -> Vec<u8> {
+    let mut packet: Vec<u8> = Vec::with_capacity(20);
+    packet.extend_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.extend_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    packet.extend_from_slice(transaction_id);
+    packet
+}
+
+/// Mapped address from the binding response. XOR-MAPPED-ADDRESS is preferred
+fn parse_stun_response(buf: &[u8], transaction_id: &[u8; 12]) -> Option<SocketAddr> {
+    if buf.len() < 20
+        || u16::from_be_bytes([buf[0], buf[1]]) != STUN_BINDING_RESPONSE
+        || &buf[8..20] != transaction_id
+    {
+        return None;
+    }
+    let len: usize = u16::from_be_bytes([buf[2], buf[3]]) as usize;
+    let attrs: &[u8] = buf.get(20..20 + len)?;
+    let cookie: [u8; 4] = STUN_MAGIC_COOKIE.to_be_bytes();
+    let mut mapped: Option<SocketAddr> = None;
+    let mut offset: usize = 0;
+    while offset + 4 <= attrs.len() {
+        let attr_type: u16 = u16::from_be_bytes([attrs[offset], attrs[offset + 1]]);
+        let attr_len: usize = u16::from_be_bytes([attrs[offset + 2], attrs[offset + 3]]) as usize;
+        let value: &[u8] = attrs.get(offset + 4..offset + 4 + attr_len)?;
+        if value.len() >= 8 {
+            let xor: bool = attr_type == STUN_ATTR_XOR_MAPPED_ADDRESS;
+            if xor || attr_type == STUN_ATTR_MAPPED_ADDRESS {
+                let mut port: u16 = u16::from_be_bytes([value[2], value[3]]);
+                if xor {
+                    port ^= (STUN_MAGIC_COOKIE >> 16) as u16;
+                }
+                let ip_addr: Option<IpAddr> = match value[1] {
+                    0x01 => {
+                        let mut octets: [u8; 4] = [value[4], value[5], value[6], value[7]];
+                        if xor {
+                            for i in 0..4 {
+                                octets[i] ^= cookie[i];
+                            }
+                        }
+                        Some(IpAddr::V4(Ipv4Addr::from(octets)))
+                    }
+                    0x02 if value.len() >= 20 => {
+                        let mut octets: [u8; 16] = [0; 16];
+                        octets.copy_from_slice(&value[4..20]);
+                        if xor {
+                            // Magic cookie followed by the transaction ID
+                            for i in 0..16 {
+                                octets[i] ^= if i < 4 {
+                                    cookie[i]
+                                } else {
+                                    transaction_id[i - 4]
+                                };
+                            }
+                        }
+                        Some(IpAddr::V6(Ipv6Addr::from(octets)))
+                    }
+                    _ => None,
+                };
+                if let Some(ip_addr) = ip_addr {
+                    let addr: SocketAddr = SocketAddr::new(ip_addr, port);
+                    if xor {
+                        return Some(addr);
+                    }
+                    mapped = Some(addr);
+                }
+            }
+        }
+        // Attributes are padded to 4 bytes
+        offset += 4 + (attr_len + 3) / 4 * 4;
+    }
+    mapped
+}
+
+/// Send STUN binding request to the server over the address family
+pub fn stun_lookup(server: &str, ipv6: bool, timeout: Duration) -> Result<ExternalAddr, String> {
+    let server_addr: SocketAddr = server
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", server, e))?
+        .find(|addr| addr.is_ipv6() == ipv6)
+        .ok_or(format!("No address of {} for the address family", server))?;
+    let bind_addr: SocketAddr = if ipv6 {
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0)
+    } else {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)
+    };
+    let socket: UdpSocket =
+        UdpSocket::bind(bind_addr).map_err(|e| format!("Failed to bind socket: {}", e))?;
+    socket
+        .connect(server_addr)
+        .map_err(|e| format!("Failed to connect to {}: {}", server_addr, e))?;
+    let _ = socket.set_read_timeout(Some(timeout));
+    let transaction_id: [u8; 12] = rand::random::<[u8; 12]>();
+    socket
+        .send(&build_stun_request(&transaction_id))
+        .map_err(|e| format!("Failed to send: {}", e))?;
+    let mut buf: [u8; 1024] = [0; 1024];
+    let len: usize = socket
+        .recv(&mut buf)
+        .map_err(|e| format!("No response from {}: {}", server_addr, e))?;
+    let mapped: SocketAddr = parse_stun_response(&buf[..len], &transaction_id)
+        .ok_or(format!("Invalid response from {}", server_addr))?;
+    Ok(ExternalAddr {
+        ip_addr: mapped.ip(),
+        method: ExternalIpMethod::Stun,
+        server: server.to_string(),
+        local_addr: socket.local_addr().ok(),
+        mapped_port: Some(mapped.port()),
+    })
+}
+
+/// Query myip.opendns.com at OpenDNS
+pub fn dns_lookup(ipv6: bool, timeout: Duration) -> Result<ExternalAddr, String> {
+    let (server, record_type): (SocketAddr, RecordType) = if ipv6 {
+        (OPENDNS_RESOLVER_V6, RecordType::AAAA)
+    } else {
+        (OPENDNS_RESOLVER_V4, RecordType::A)
+    };
+    let result = crate::dns::query::query(OPENDNS_MYIP_NAME, record_type, server, false, timeout)?;
+    let ip_addr: IpAddr = result
+        .answers
+        .iter()
+        .filter_map(|r| r.value.parse::<IpAddr>().ok())
+        .next()
+        .ok_or(format!("No answer from {}", server))?;
+    Ok(ExternalAddr {
+        ip_addr: ip_addr,
+        method: ExternalIpMethod::Dns,
+        server: server.to_string(),
+        local_addr: None,
+        mapped_port: None,
+    })
+}
+
+/// GET the address from an HTTP endpoint of the address family
+pub fn http_lookup(ipv6: bool, timeout: Duration) -> Result<ExternalAddr, String> {
+    let url: &str = if ipv6 {
+        HTTP_ENDPOINT_V6
+    } else {
+        HTTP_ENDPOINT_V4
+    };
+    let response = crate::http::get(url, &[], timeout)?;
+    if !response.is_success() {
+        return Err(format!("{} returned {}", url, response.status));
+    }
+    let ip_addr: IpAddr = response
+        .text()
+        .trim()
+        .parse::<IpAddr>()
+        .map_err(|_| format!("Invalid response from {}", url))?;
+    if ip_addr.is_ipv6() != ipv6 {
+        return Err(format!("{} answered over the other address family", url));
+    }
+    Ok(ExternalAddr {
+        ip_addr: ip_addr,
+        method: ExternalIpMethod::Http,
+        server: url.to_string(),
+        local_addr: None,
+        mapped_port: None,
+    })
+}
+
+/// Find the external address of the address family with the methods in order.
+/// Returns errors of each method if all failed
+pub fn lookup(
+    methods: &[ExternalIpMethod],
+    stun_servers: &[String],
+    ipv6: bool,
+    timeout: Duration,
+) -> Result<ExternalAddr, Vec<String>> {
+    let mut errors: Vec<String> = Vec::new();
+    for method in methods {
+        let result: Result<ExternalAddr, String> = match method {
+            ExternalIpMethod::Stun => {
+                let mut result: Result<ExternalAddr, String> = Err("No STUN server".to_string());
+                for server in stun_servers {
+                    result = stun_lookup(server, ipv6, timeout);
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+                result
+            }
+            ExternalIpMethod::Dns => dns_lookup(ipv6, timeout),
+            ExternalIpMethod::Http => http_lookup(ipv6, timeout),
+        };
+        match result {
+            Ok(addr) => return Ok(addr),
+            Err(e) => errors.push(format!("{:?}: {}", method, e)),
+        }
+    }
+    Err(errors)
+}
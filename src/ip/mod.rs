@@ -1,4 +1,15 @@
-use std::net::IpAddr;
+pub mod external;
+
+use ipnet::{IpNet, Ipv4AddrRange, Ipv6AddrRange};
+use netdev::mac::MacAddr;
+use std::net::{IpAddr, Ipv6Addr};
+use std::str::FromStr;
+
+/// Max number of addresses expanded from a network or range. /12 for IPv4, /108 for IPv6
+pub const MAX_EXPAND_ADDRS: u128 = 1 << 20;
+
+/// IPv6 link-local all-nodes multicast address
+pub const IPV6_ALL_NODES: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
 
 pub fn is_global_addr(ip_addr: &IpAddr) -> bool {
     match ip_addr {
@@ -16,3 +27,121 @@ pub fn guess_initial_ttl(ttl: u8) -> u8 {
         255
     }
 }
+
+/// Parse IP address or network (CIDR) string.
+///
+/// Single IP address is converted to host network (/32 or /128).
+pub fn parse_ip_net(s: &str) -> Option<IpNet> {
+    let s = s.trim();
+    match IpNet::from_str(s) {
+        Ok(ipnet) => Some(ipnet),
+        Err(_) => match IpAddr::from_str(s) {
+            Ok(ip_addr) => Some(IpNet::from(ip_addr)),
+            Err(_) => None,
+        },
+    }
+}
+
+/// Check if the IP address is included in any of the networks
+pub fn is_in_networks(ip_addr: &IpAddr, networks: &Vec<IpNet>) -> bool {
+    networks.iter().any(|net| net.contains(ip_addr))
+}
+
+/// Number of addresses in the network
+pub fn net_size(ipnet: &IpNet) -> u128 {
+    let host_bits: u8 = ipnet.max_prefix_len() - ipnet.prefix_len();
+    1u128.checked_shl(host_bits as u32).unwrap_or(u128::MAX)
+}
+
+/// Expand the network into host addresses. None above MAX_EXPAND_ADDRS
+pub fn expand_ip_net(ipnet: &IpNet) -> Option<Vec<IpAddr>> {
+    if net_size(ipnet) > MAX_EXPAND_ADDRS {
+        return None;
+    }
+    Some(ipnet.hosts().collect())
+}
+
+/// Parse IP address range string. Example: 192.168.1.10-192.168.1.20 or 192.168.1.10-20.
+/// None above MAX_EXPAND_ADDRS
+pub fn parse_ip_range(s: &str) -> Option<Vec<IpAddr>> {
+    let (start, end) = s.trim().split_once('-')?;
+    let start: IpAddr = IpAddr::from_str(start.trim()).ok()?;
+    let end: &str = end.trim();
+    match start {
+        IpAddr::V4(start_v4) => {
+            let end_v4 = match std::net::Ipv4Addr::from_str(end) {
+                Ok(end_v4) => end_v4,
+                Err(_) => {
+                    // Last octet only
+                    let last: u8 = end.parse().ok()?;
+                    let o = start_v4.octets();
+                    std::net::Ipv4Addr::new(o[0], o[1], o[2], last)
+                }
+            };
+            let size: u128 =
+                (u32::from(end_v4) as u128 + 1).saturating_sub(u32::from(start_v4) as u128);
+            if size > MAX_EXPAND_ADDRS {
+                return None;
+            }
+            Some(
+                Ipv4AddrRange::new(start_v4, end_v4)
+                    .map(|ip| IpAddr::V4(ip))
+                    .collect(),
+            )
+        }
+        IpAddr::V6(start_v6) => {
+            let end_v6 = std::net::Ipv6Addr::from_str(end).ok()?;
+            let size: u128 = u128::from(end_v6)
+                .saturating_sub(u128::from(start_v6))
+                .saturating_add(1);
+            if end_v6 >= start_v6 && size > MAX_EXPAND_ADDRS {
+                return None;
+            }
+            Some(
+                Ipv6AddrRange::new(start_v6, end_v6)
+                    .map(|ip| IpAddr::V6(ip))
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// Expand IP address, network (CIDR) or range string into IP addresses
+pub fn expand_ip_target(s: &str) -> Option<Vec<IpAddr>> {
+    let s = s.trim();
+    if let Ok(ip_addr) = IpAddr::from_str(s) {
+        return Some(vec![ip_addr]);
+    }
+    if let Ok(ipnet) = IpNet::from_str(s) {
+        return expand_ip_net(&ipnet);
+    }
+    parse_ip_range(s)
+}
+
+/// Check if the address is IPv6 unicast link-local (fe80::/10)
+pub fn is_link_local_ipv6(ipv6_addr: &Ipv6Addr) -> bool {
+    (ipv6_addr.segments()[0] & 0xffc0) == 0xfe80
+}
+
+/// Get ethernet multicast address for the IPv6 multicast address (33:33 + last 32 bits)
+pub fn ipv6_multicast_mac(ipv6_addr: &Ipv6Addr) -> MacAddr {
+    let o = ipv6_addr.octets();
+    MacAddr::new(0x33, 0x33, o[12], o[13], o[14], o[15])
+}
+
+/// Parse IP address with optional zone ID (e.g. `fe80::1%eth0`). Brackets are allowed.
+pub fn parse_ip_with_zone(s: &str) -> Option<(IpAddr, Option<String>)> {
+    let s = s.trim().trim_start_matches('[').trim_end_matches(']');
+    match s.split_once('%') {
+        Some((addr, zone)) => match Ipv6Addr::from_str(addr) {
+            Ok(ipv6_addr) if !zone.is_empty() => {
+                Some((IpAddr::V6(ipv6_addr), Some(zone.to_string())))
+            }
+            _ => None,
+        },
+        None => match IpAddr::from_str(s) {
+            Ok(ip_addr) => Some((ip_addr, None)),
+            Err(_) => None,
+        },
+    }
+}
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     host::Host,
+    neighbor::conflict::AddressConflict,
     scan::result::{ScanResult, ScanStatus},
 };
 
@@ -16,6 +17,24 @@ pub struct HostScanResult {
     pub scan_time: Duration,
     /// Status of the scan task
     pub scan_status: ScanStatus,
+    /// Summary of each target network
+    pub networks: Vec<NetworkSummary>,
+    /// Time taken to port scan the hosts found up. Zero if not performed
+    pub port_scan_time: Duration,
+    /// Duplicate IP or shared MAC addresses seen in ARP/NDP replies
+    #[serde(default)]
+    pub conflicts: Vec<AddressConflict>,
+}
+
+/// Summary of hosts found in a target network
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkSummary {
+    /// Target network, address or list
+    pub network: String,
+    /// Number of scanned addresses. 0 for networks discovered via multicast
+    pub targets: usize,
+    /// Number of hosts up
+    pub hosts_up: usize,
 }
 
 impl HostScanResult {
@@ -25,6 +44,9 @@ impl HostScanResult {
             hosts: vec![],
             scan_time: Duration::from_millis(0),
             scan_status: ScanStatus::Error("Scan not started".to_string()),
+            networks: vec![],
+            port_scan_time: Duration::from_millis(0),
+            conflicts: vec![],
         }
     }
     pub fn from_scan_result(scan_result: &ScanResult) -> HostScanResult {
@@ -32,6 +54,9 @@ impl HostScanResult {
             hosts: scan_result.hosts.clone(),
             scan_time: scan_result.scan_time.clone(),
             scan_status: scan_result.scan_status.clone(),
+            networks: vec![],
+            port_scan_time: Duration::from_millis(0),
+            conflicts: crate::neighbor::conflict::detect_conflicts(&scan_result.address_bindings),
         }
     }
 }
@@ -0,0 +1,116 @@
+use netdev::mac::MacAddr;
+use netdev::Interface;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+/// Gateway of an interface
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GatewayInfo {
+    pub mac_addr: MacAddr,
+    pub ipv4: Vec<Ipv4Addr>,
+    pub ipv6: Vec<Ipv6Addr>,
+}
+
+/// Result of probing the gateway
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GatewayCheck {
+    pub ip_addr: IpAddr,
+    /// MAC address resolved by ARP/NDP. None if no reply
+    pub mac_addr: Option<MacAddr>,
+    pub vendor_name: String,
+    /// RTT of ICMP echo. None if no reply
+    pub rtt: Option<Duration>,
+}
+
+impl GatewayCheck {
+    pub fn is_ok(&self) -> bool {
+        self.mac_addr.is_some() && self.rtt.is_some()
+    }
+}
+
+/// Network interface for JSON output
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InterfaceInfo {
+    pub index: u32,
+    pub name: String,
+    pub friendly_name: Option<String>,
+    pub description: Option<String>,
+    /// Interface type (e.g. Ethernet, Wireless80211, Loopback)
+    pub if_type: String,
+    pub mac_addr: Option<MacAddr>,
+    /// IPv4 addresses in CIDR notation
+    pub ipv4: Vec<String>,
+    /// IPv6 addresses in CIDR notation
+    pub ipv6: Vec<String>,
+    pub mtu: Option<u32>,
+    pub gateway: Option<GatewayInfo>,
+    pub dns_servers: Vec<IpAddr>,
+    /// Flags set on the interface (e.g. UP, BROADCAST, MULTICAST)
+    pub flags: Vec<String>,
+    /// True if this is the default interface
+    pub default: bool,
+    /// Result of probing the gateway. Default interface only
+    #[serde(default)]
+    pub gateway_check: Option<GatewayCheck>,
+}
+
+impl InterfaceInfo {
+    pub fn from_interface(iface: &Interface, default_index: Option<u32>) -> InterfaceInfo {
+        InterfaceInfo {
+            index: iface.index,
+            name: iface.name.clone(),
+            friendly_name: iface.friendly_name.clone(),
+            description: iface.description.clone(),
+            if_type: iface.if_type.name(),
+            mac_addr: iface.mac_addr,
+            ipv4: iface
+                .ipv4
+                .iter()
+                .map(|ipv4| format!("{}/{}", ipv4.addr, ipv4.prefix_len))
+                .collect(),
+            ipv6: iface
+                .ipv6
+                .iter()
+                .map(|ipv6| format!("{}/{}", ipv6.addr, ipv6.prefix_len))
+                .collect(),
+            mtu: iface.mtu,
+            gateway: iface.gateway.as_ref().map(|gateway| GatewayInfo {
+                mac_addr: gateway.mac_addr,
+                ipv4: gateway.ipv4.clone(),
+                ipv6: gateway.ipv6.clone(),
+            }),
+            dns_servers: iface.dns_servers.clone(),
+            flags: interface_flags(iface),
+            default: default_index == Some(iface.index),
+            gateway_check: None,
+        }
+    }
+}
+
+/// Names of the flags set on the interface
+pub fn interface_flags(iface: &Interface) -> Vec<String> {
+    let mut flags: Vec<String> = Vec::new();
+    if iface.is_up() {
+        flags.push("UP".to_string());
+    }
+    if iface.is_running() {
+        flags.push("RUNNING".to_string());
+    }
+    if iface.is_loopback() {
+        flags.push("LOOPBACK".to_string());
+    }
+    if iface.is_point_to_point() {
+        flags.push("POINTTOPOINT".to_string());
+    }
+    if iface.is_broadcast() {
+        flags.push("BROADCAST".to_string());
+    }
+    if iface.is_multicast() {
+        flags.push("MULTICAST".to_string());
+    }
+    if iface.is_tun() {
+        flags.push("TUN".to_string());
+    }
+    flags
+}
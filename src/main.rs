@@ -1,24 +1,45 @@
 // Core
+pub mod agent;
 pub mod config;
+pub mod cve;
 pub mod db;
 pub mod dep;
 pub mod dns;
+pub mod enrich;
+pub mod export;
+pub mod exporter;
 pub mod fp;
 pub mod fs;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod host;
+pub mod http;
 pub mod interface;
 pub mod ip;
 pub mod json;
+pub mod masscan;
+pub mod mqtt;
 pub mod neighbor;
+pub mod nmap;
+pub mod ntp;
 pub mod packet;
+pub mod passive;
 pub mod pcap;
 pub mod ping;
 pub mod probe;
 pub mod protocol;
 pub mod scan;
+pub mod schedule;
+pub mod serve;
+pub mod smb;
+pub mod snmp;
+pub mod ssh;
 pub mod sys;
+pub mod tls;
 pub mod trace;
 pub mod util;
+pub mod watch;
+pub mod whois;
 // CLI
 pub mod app;
 pub mod handler;
@@ -28,6 +49,7 @@ use app::{AppCommands, CRATE_REPOSITORY};
 use clap::{crate_description, crate_name, crate_version, value_parser};
 use clap::{Arg, ArgMatches, Command};
 use std::env;
+use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 
 fn main() {
@@ -44,10 +66,16 @@ fn main() {
             std::process::exit(1);
         }
     }
+    if let Err(e) = app::set_ctrlc_handler() {
+        output::log_with_time(&format!("Failed to set Ctrl-C handler: {}", e), "WARN");
+    }
     let subcommand_name = arg_matches.subcommand_name().unwrap_or("");
     let app_command = AppCommands::from_str(subcommand_name);
     app::show_banner_with_starttime();
-    check_deps();
+    // check reports missing dependencies by itself
+    if !matches!(app_command, Some(AppCommands::CheckDependencies)) {
+        check_deps();
+    }
     match app_command {
         Some(AppCommands::PortScan) => {
             handler::port::handle_portscan(&arg_matches);
@@ -64,15 +92,78 @@ fn main() {
         Some(AppCommands::Subdomain) => {
             handler::dns::handle_subdomain_scan(&arg_matches);
         }
+        Some(AppCommands::Dns) => {
+            handler::dns::handle_dns_query(&arg_matches);
+        }
+        Some(AppCommands::Rdns) => {
+            handler::dns::handle_rdns_sweep(&arg_matches);
+        }
+        Some(AppCommands::Mdns) => {
+            handler::dns::handle_mdns_discovery(&arg_matches);
+        }
         Some(AppCommands::Neighbor) => {
             handler::neighbor::handle_neighbor_discovery(&arg_matches);
         }
+        Some(AppCommands::Wol) => {
+            handler::wol::handle_wol(&arg_matches);
+        }
+        Some(AppCommands::MyIp) => {
+            handler::myip::handle_myip(&arg_matches);
+        }
         Some(AppCommands::Interfaces) => {
             handler::interface::show_interfaces(&arg_matches);
         }
         Some(AppCommands::Interface) => {
             handler::interface::show_default_interface(&arg_matches);
         }
+        Some(AppCommands::Whois) => {
+            handler::whois::handle_whois(&arg_matches);
+        }
+        Some(AppCommands::Rdap) => {
+            handler::whois::handle_rdap(&arg_matches);
+        }
+        Some(AppCommands::Geo) => {
+            handler::geo::handle_geo(&arg_matches);
+        }
+        Some(AppCommands::Http) => {
+            handler::http::handle_http_probe(&arg_matches);
+        }
+        Some(AppCommands::Tls) => {
+            handler::tls::handle_tls_audit(&arg_matches);
+        }
+        Some(AppCommands::Snmp) => {
+            handler::snmp::handle_snmp_sweep(&arg_matches);
+        }
+        Some(AppCommands::Ntp) => {
+            handler::ntp::handle_ntp_probe(&arg_matches);
+        }
+        Some(AppCommands::Capture) => {
+            handler::capture::handle_capture(&arg_matches);
+        }
+        Some(AppCommands::Passive) => {
+            handler::passive::handle_passive(&arg_matches);
+        }
+        Some(AppCommands::Serve) => {
+            handler::serve::handle_serve(&arg_matches);
+        }
+        Some(AppCommands::Grpc) => {
+            handler::grpc::handle_grpc(&arg_matches);
+        }
+        Some(AppCommands::Exporter) => {
+            handler::exporter::handle_exporter(&arg_matches);
+        }
+        Some(AppCommands::Schedule) => {
+            handler::schedule::handle_schedule(&arg_matches);
+        }
+        Some(AppCommands::Import) => {
+            handler::import::handle_import(&arg_matches);
+        }
+        Some(AppCommands::Agent) => {
+            handler::agent::handle_agent(&arg_matches);
+        }
+        Some(AppCommands::Controller) => {
+            handler::agent::handle_controller(&arg_matches);
+        }
         Some(AppCommands::CheckDependencies) => {
             handler::check::check_dependencies(&arg_matches);
         }
@@ -92,6 +183,11 @@ fn main() {
 }
 
 fn parse_args() -> ArgMatches {
+    get_app().get_matches()
+}
+
+/// Command line definition. Also used to parse the options of server jobs
+fn get_app() -> Command {
     let app_description: &str = crate_description!();
     let app: Command = Command::new(crate_name!())
         .version(crate_version!())
@@ -103,7 +199,7 @@ fn parse_args() -> ArgMatches {
             .long("target")
             .value_name("target")
             .display_order(1)
-            .value_parser(value_parser!(String))
+            .value_parser(crate::dns::idn::parse_target)
         )
         .arg(Arg::new("interface")
             .help("Specify the network interface")
@@ -124,6 +220,11 @@ fn parse_args() -> ArgMatches {
             .long("full")
             .num_args(0)
         )
+        .arg(Arg::new("smb")
+            .help("Negotiate SMB without credentials on open 445/139 to show the dialect, signing, OS version and names")
+            .long("smb")
+            .num_args(0)
+        )
         .arg(Arg::new("json")
             .help("Displays results in JSON format.")
             .short('j')
@@ -137,19 +238,126 @@ fn parse_args() -> ArgMatches {
             .value_name("file_path")
             .value_parser(value_parser!(PathBuf))
         )
+        .arg(Arg::new("geoip")
+            .help("Annotate hosts and hops with country/city/ASN using MaxMind-format (MMDB) databases - Example: --geoip GeoLite2-City.mmdb,GeoLite2-ASN.mmdb")
+            .long("geoip")
+            .value_name("file_path")
+            .value_parser(value_parser!(PathBuf))
+            .value_delimiter(',')
+            .global(true)
+        )
+        .arg(Arg::new("cve_db")
+            .help("List known CVEs of detected services using NVD CVE JSON 2.0 feed files - Example: --cve-db nvdcve-2.0-2024.json,nvdcve-2.0-2023.json")
+            .long("cve-db")
+            .value_name("file_path")
+            .value_parser(value_parser!(PathBuf))
+            .value_delimiter(',')
+            .global(true)
+        )
+        .arg(Arg::new("cve_api")
+            .help("List known CVEs of detected services by querying the NVD API. Set NVD_API_KEY for a higher rate limit")
+            .long("cve-api")
+            .num_args(0)
+            .global(true)
+        )
+        .arg(Arg::new("mqtt")
+            .help("Publish each host and open port of port/host scans as JSON to an MQTT broker. Use mqtts:// for TLS - Example: --mqtt tcp://broker:1883")
+            .long("mqtt")
+            .value_name("url")
+            .global(true)
+        )
+        .arg(Arg::new("mqtt_topic")
+            .help("Base topic of --mqtt. Messages go to <topic>/host, <topic>/port and <topic>/change. Default: nrev/results")
+            .long("topic")
+            .value_name("topic")
+            .requires("mqtt")
+            .global(true)
+        )
+        .arg(Arg::new("enrich")
+            .help("Add ports and tags seen by Shodan or Censys to public hosts, and alert on differences from the scan - Example: --enrich shodan")
+            .long("enrich")
+            .value_name("source")
+            .value_parser(["shodan", "censys"])
+            .global(true)
+        )
+        .arg(Arg::new("enrich_key")
+            .help("API key of --enrich. Censys takes id:secret. Default: SHODAN_API_KEY, or CENSYS_API_ID and CENSYS_API_SECRET")
+            .long("enrich-key")
+            .value_name("key")
+            .global(true)
+        )
+        .arg(Arg::new("masscan_output")
+            .help("Print port/host scan results in masscan list or JSON format - Example: --masscan-output list")
+            .long("masscan-output")
+            .value_name("format")
+            .value_parser(["list", "json"])
+            .global(true)
+        )
+        .arg(Arg::new("export")
+            .help("Send port/host scan results to a data store - Example: --export elasticsearch --es-url http://localhost:9200")
+            .long("export")
+            .value_name("destination")
+            .value_parser(["elasticsearch", "splunk"])
+            .global(true)
+        )
+        .arg(Arg::new("es_url")
+            .help("Elasticsearch URL for --export elasticsearch. Set ES_API_KEY for API key authentication")
+            .long("es-url")
+            .value_name("url")
+            .global(true)
+        )
+        .arg(Arg::new("es_index")
+            .help("Elasticsearch index prefix. Documents go to <prefix>-hosts and <prefix>-ports. Default: nrev")
+            .long("es-index")
+            .value_name("prefix")
+            .global(true)
+        )
+        .arg(Arg::new("hec_url")
+            .help("Splunk HTTP Event Collector URL for --export splunk - Example: --hec-url https://splunk.example.com:8088")
+            .long("hec-url")
+            .value_name("url")
+            .global(true)
+        )
+        .arg(Arg::new("hec_token")
+            .help("Splunk HEC token. SPLUNK_HEC_TOKEN is used if not set")
+            .long("hec-token")
+            .value_name("token")
+            .global(true)
+        )
+        .arg(Arg::new("hec_index")
+            .help("Splunk index. Default: the default index of the token")
+            .long("hec-index")
+            .value_name("index")
+            .global(true)
+        )
+        .arg(Arg::new("hec_batch")
+            .help("Events sent per HEC request. Default: 100")
+            .long("hec-batch")
+            .value_name("count")
+            .value_parser(value_parser!(usize))
+            .global(true)
+        )
         .arg(Arg::new("quiet")
             .help("Quiet mode. Suppress output. Only show final results.")
             .short('q')
             .long("quiet")
             .num_args(0)
+            .global(true)
         )
         .subcommand(Command::new("port")
             .about("Scan port. nrev port --help for more information")
             .arg(Arg::new("target")
                 .help("Specify the target. IP address or Hostname")
                 .value_name("target")
-                .value_parser(value_parser!(String))
-                .required(true)
+                .value_parser(crate::dns::idn::parse_target)
+                .required_unless_present("masscan_input")
+            )
+            .arg(Arg::new("masscan_input")
+                .help("Run service detection on the open ports in masscan list (-oL) or JSON (-oJ) output instead of scanning - Example: --masscan-input masscan.txt")
+                .long("masscan-input")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with_all(["target", "watch"])
             )
             .arg(Arg::new("ports")
                 .help("Specify the ports. Example: 80,443,8080")
@@ -180,6 +388,16 @@ fn parse_args() -> ArgMatches {
                 .long("service")
                 .num_args(0)
             )
+            .arg(Arg::new("ssh_audit")
+                .help("Record the banner and algorithms of open SSH ports and flag deprecated ones")
+                .long("ssh-audit")
+                .num_args(0)
+            )
+            .arg(Arg::new("smb")
+                .help("Negotiate SMB without credentials on open 445/139 to show the dialect, signing, OS version and names")
+                .long("smb")
+                .num_args(0)
+            )
             .arg(Arg::new("random")
                 .help("Don't randomize targets. By default, nrev randomizes the order of targets.")
                 .short('R')
@@ -222,26 +440,46 @@ fn parse_args() -> ArgMatches {
                 .value_name("duration")
                 .value_parser(value_parser!(u64))
             )
+            .arg(Arg::new("watch")
+                .help("Re-run the scan every specified seconds and report only new/gone hosts and opened/closed ports - Example: --watch 300")
+                .long("watch")
+                .value_name("secs")
+                .value_parser(value_parser!(u64).range(1..))
+            )
+            .arg(Arg::new("webhook")
+                .help("POST changes found by --watch as JSON to this URL. Slack-compatible - Example: --webhook https://hooks.example.com/xxx")
+                .long("webhook")
+                .value_name("url")
+                .requires("watch")
+            )
         )
         .subcommand(Command::new("host")
             .about("Scan host in specified network or host-list. nrev host --help for more information")
             .arg(Arg::new("target")
-                .help("Specify the target network")
+                .help("Specify the target network(s). Example: 192.168.1.0/24,192.168.2.0/24. Large IPv6 networks (e.g. /64) are discovered via all-nodes multicast")
                 .value_name("target")
-                .required(true)
+                .value_delimiter(',')
+                .required_unless_present("input_file")
+            )
+            .arg(Arg::new("input_file")
+                .help("Read targets from file. Each line can be an IP address, CIDR or range (e.g. 10.0.0.1-10.0.0.20)")
+                .long("input-file")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
             )
             .arg(Arg::new("protocol")
-                .help("Specify the protocol")
+                .help("Specify the protocol (icmp, tcp, udp, arp, auto). arp uses NDP for IPv6 targets. auto combines arp, icmp and tcp")
                 .short('P')
                 .long("protocol")
                 .value_name("protocol_name")
                 .value_parser(value_parser!(String))
             )
             .arg(Arg::new("port")
-                .help("Specify the port. Example: --port 80")
+                .help("Specify the port(s) for TCP host scan and --then-port. Example: --port 22,80,443")
                 .short('p')
                 .long("port")
                 .value_name("port")
+                .value_delimiter(',')
                 .value_parser(value_parser!(u16))
             )
             .arg(Arg::new("random")
@@ -250,6 +488,18 @@ fn parse_args() -> ArgMatches {
                 .long("random")
                 .num_args(0)
             )
+            .arg(Arg::new("resolve")
+                .help("Resolve host names of detected hosts (reverse DNS lookup)")
+                .long("resolve")
+                .num_args(0)
+            )
+            .arg(Arg::new("exclude")
+                .help("Exclude IP addresses or networks from the targets. Example: --exclude 10.0.5.0/24,10.0.0.1")
+                .long("exclude")
+                .value_name("exclude")
+                .value_delimiter(',')
+                .value_parser(value_parser!(String))
+            )
             .arg(Arg::new("timeout")
                 .help("Set timeout in ms - Example: --timeout 10000")
                 .long("timeout")
@@ -264,21 +514,52 @@ fn parse_args() -> ArgMatches {
                 .value_parser(value_parser!(u64))
             )
             .arg(Arg::new("rate")
-                .help("Set send-rate in ms - Example: --rate 1")
+                .help("Set send-rate in ms - Example: --rate 1. By default, chosen from the interface type and link speed")
                 .long("rate")
                 .value_name("duration")
                 .value_parser(value_parser!(u64))
             )
+            .arg(Arg::new("retries")
+                .help("Re-probe hosts that did not answer, up to the specified number of times - Example: --retries 2")
+                .long("retries")
+                .value_name("retries")
+                .value_parser(value_parser!(u32))
+            )
+            .arg(Arg::new("then_port")
+                .help("Port scan the hosts found up. Scans --port if specified, otherwise default 1000 ports")
+                .long("then-port")
+                .num_args(0)
+            )
+            .arg(Arg::new("watch")
+                .help("Re-run the scan every specified seconds and report only new/gone hosts and opened/closed ports - Example: --watch 300")
+                .long("watch")
+                .value_name("secs")
+                .value_parser(value_parser!(u64).range(1..))
+            )
+            .arg(Arg::new("webhook")
+                .help("POST changes found by --watch as JSON to this URL. Slack-compatible - Example: --webhook https://hooks.example.com/xxx")
+                .long("webhook")
+                .value_name("url")
+                .requires("watch")
+            )
         )
         .subcommand(Command::new("ping")
             .about("Ping to specified host. nrev ping --help for more information")
             .arg(Arg::new("target")
-                .help("Specify the target. IP address or Hostname")
+                .help("Specify the target(s). IP address or Hostname. Multiple targets are pinged concurrently - Example: 1.1.1.1,8.8.8.8. IPv6 link-local address requires zone ID (e.g. fe80::1%eth0)")
                 .value_name("target")
-                .required(true)
+                .value_delimiter(',')
+                .value_parser(crate::dns::idn::parse_target)
+                .required_unless_present("input_file")
+            )
+            .arg(Arg::new("input_file")
+                .help("Read targets from file. One IP address or host name per line")
+                .long("input-file")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
             )
             .arg(Arg::new("count")
-                .help("Set number of requests or pings to be sent")
+                .help("Set number of requests or pings to be sent. By default, ping until interrupted (Ctrl-C)")
                 .short('c')
                 .long("count")
                 .value_name("count")
@@ -324,14 +605,93 @@ fn parse_args() -> ArgMatches {
                 .value_name("duration")
                 .value_parser(value_parser!(u64))
             )
+            .arg(Arg::new("interval")
+                .help("Set interval between the start of each ping in ms (min 10ms, less than 200ms requires administrator privileges) - Example: --interval 50")
+                .long("interval")
+                .value_name("interval")
+                .value_parser(value_parser!(u64))
+                .conflicts_with("rate")
+            )
+            .arg(Arg::new("deadline")
+                .help("Stop after the specified seconds regardless of count - Example: --deadline 10")
+                .long("deadline")
+                .value_name("secs")
+                .value_parser(value_parser!(u64))
+            )
+            .arg(Arg::new("size")
+                .help("Set ICMP echo payload size in bytes - Example: --size 1472")
+                .short('s')
+                .long("size")
+                .value_name("size")
+                .value_parser(value_parser!(usize))
+            )
+            .arg(Arg::new("tos")
+                .help("Set TOS (IPv4) or Traffic Class (IPv6) byte of probes - Example: --tos 0xb8")
+                .long("tos")
+                .value_name("value")
+                .value_parser(crate::handler::parse_tos)
+                .conflicts_with("dscp")
+            )
+            .arg(Arg::new("dscp")
+                .help("Set DSCP (0-63) of probes - Example: --dscp 46")
+                .long("dscp")
+                .value_name("value")
+                .value_parser(value_parser!(u8).range(0..64))
+            )
+            .arg(Arg::new("df")
+                .help("Set Don't Fragment bit (IPv4)")
+                .long("df")
+                .num_args(0)
+            )
+            .arg(Arg::new("record_route")
+                .help("Add Record Route IP option and show the recorded route (ICMP, IPv4)")
+                .long("record-route")
+                .num_args(0)
+                .conflicts_with("timestamp")
+            )
+            .arg(Arg::new("timestamp")
+                .help("Add Timestamp IP option and show the recorded timestamps (ICMP, IPv4)")
+                .long("timestamp")
+                .num_args(0)
+            )
+            .arg(Arg::new("fail_on_loss")
+                .help("Exit with non-zero status if packet loss exceeds the specified percentage - Example: --fail-on-loss 20")
+                .long("fail-on-loss")
+                .value_name("pct")
+                .value_parser(value_parser!(f64))
+            )
+            .arg(Arg::new("graph")
+                .help("Show RTT history graph (sparkline) after ping")
+                .long("graph")
+                .num_args(0)
+            )
+            .arg(Arg::new("flood")
+                .help("Flood ping. Send ICMP echo requests 100 times per second without waiting for replies. Requires administrator privileges")
+                .long("flood")
+                .num_args(0)
+                .conflicts_with_all(["interval", "rate"])
+            )
+            .arg(Arg::new("pmtu")
+                .help("Discover path MTU by searching the largest unfragmented ICMP echo")
+                .long("pmtu")
+                .num_args(0)
+            )
         )
         .subcommand(Command::new("trace")
             .about("Traceroute to specified host. nrev trace --help for more information")
             .arg(Arg::new("target")
-                .help("Specify the target. IP address or Hostname")
+                .help("Specify the target(s). IP address or Hostname. Multiple targets are traced in turn and merged into one topology - Example: 1.1.1.1,8.8.8.8")
                 .value_name("target")
+                .value_delimiter(',')
+                .value_parser(crate::dns::idn::parse_target)
                 .required(true)
             )
+            .arg(Arg::new("source_ip")
+                .help("Set source IP address. The interface is chosen by the address unless specified with -i - Example: --source-ip 192.168.2.10")
+                .long("source-ip")
+                .value_name("ip_addr")
+                .value_parser(value_parser!(IpAddr))
+            )
             .arg(Arg::new("port")
                 .help("Specify the port. Example: 33435")
                 .short('p')
@@ -346,31 +706,122 @@ fn parse_args() -> ArgMatches {
                 .value_name("maxhop")
                 .value_parser(value_parser!(u8))
             )
+            .arg(Arg::new("ipv6")
+                .help("Use IPv6 address when resolving the target host name")
+                .short('6')
+                .long("ipv6")
+                .num_args(0)
+            )
+            .arg(Arg::new("firsthop")
+                .help("Set the TTL of the first probe (default: 1) - Example: --firsthop 3")
+                .long("firsthop")
+                .value_name("ttl")
+                .value_parser(value_parser!(u8))
+            )
             .arg(Arg::new("timeout")
-                .help("Set timeout in ms - Example: --timeout 10000")
+                .help("Set timeout of the whole trace in ms (default: 30000) - Example: --timeout 10000")
                 .long("timeout")
                 .value_name("timeout")
                 .value_parser(value_parser!(u64))
             )
             .arg(Arg::new("waittime")
-                .help("Set wait-time in ms (default:100ms) - Example: -w 200")
+                .help("Set wait-time in ms (default: 1000). Also the per-hop timeout unless --hop-timeout is set - Example: -w 200")
                 .short('w')
                 .long("waittime")
                 .value_name("waittime")
                 .value_parser(value_parser!(u64))
             )
+            .arg(Arg::new("hop_timeout")
+                .help("Set time in ms to wait for the reply of each hop before moving on - Example: --hop-timeout 3000")
+                .long("hop-timeout")
+                .value_name("duration")
+                .value_parser(value_parser!(u64))
+            )
             .arg(Arg::new("rate")
                 .help("Set send-rate in ms - Example: --rate 100")
                 .long("rate")
                 .value_name("duration")
                 .value_parser(value_parser!(u64))
             )
+            .arg(Arg::new("queries")
+                .help("Set number of probes per hop and show per-hop loss and RTT statistics - Example: --queries 3")
+                .long("queries")
+                .value_name("count")
+                .value_parser(value_parser!(u8))
+            )
+            .arg(Arg::new("tos")
+                .help("Set TOS (IPv4) or Traffic Class (IPv6) byte of probes - Example: --tos 0xb8")
+                .long("tos")
+                .value_name("value")
+                .value_parser(crate::handler::parse_tos)
+                .conflicts_with("dscp")
+            )
+            .arg(Arg::new("dscp")
+                .help("Set DSCP (0-63) of probes - Example: --dscp 46")
+                .long("dscp")
+                .value_name("value")
+                .value_parser(value_parser!(u8).range(0..64))
+            )
+            .arg(Arg::new("parallel")
+                .help("Probe multiple TTLs concurrently for faster trace - Example: --parallel 16")
+                .long("parallel")
+                .value_name("count")
+                .value_parser(value_parser!(u8))
+            )
+            .arg(Arg::new("resolve")
+                .help("Resolve host names of hops (default)")
+                .long("resolve")
+                .num_args(0)
+                .conflicts_with("no_resolve")
+            )
+            .arg(Arg::new("no_resolve")
+                .help("Do not resolve host names of hops")
+                .short('n')
+                .long("no-resolve")
+                .num_args(0)
+            )
+            .arg(Arg::new("dns_timeout")
+                .help("Set timeout of each reverse DNS lookup in ms (default: 500ms) - Example: --dns-timeout 200")
+                .long("dns-timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+            .arg(Arg::new("asn")
+                .help("Look up origin AS of each hop (Team Cymru IP to ASN mapping via DNS)")
+                .long("asn")
+                .num_args(0)
+            )
+            .arg(Arg::new("flows")
+                .help("Trace multiple flows (source ports) to enumerate load-balanced (ECMP) paths - Example: --flows 8")
+                .long("flows")
+                .value_name("count")
+                .value_parser(value_parser!(usize))
+            )
+            .arg(Arg::new("baseline")
+                .help("Compare the path with a trace saved by --save and highlight changes - Example: --baseline old.json")
+                .long("baseline")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+            )
+            .arg(Arg::new("rtt-threshold")
+                .help("Set RTT delta in ms to flag against the baseline (default: 20) - Example: --rtt-threshold 50")
+                .long("rtt-threshold")
+                .value_name("duration")
+                .value_parser(value_parser!(u64))
+                .requires("baseline")
+            )
+            .arg(Arg::new("to_map")
+                .help("Add the traced topology to the named network map (<name>.map.json). Nodes and edges are merged into the existing map - Example: --to-map office")
+                .long("to-map")
+                .value_name("name")
+            )
         )
         .subcommand(Command::new("subdomain")
             .about("Find subdomains. nrev subdomain --help for more information")
             .arg(Arg::new("target")
-                .help("Specify the target apex-domain")
+                .help("Specify the target apex-domain. Unicode (IDN) names are accepted")
                 .value_name("target")
+                .value_parser(crate::dns::idn::parse_target)
                 .required(true)
             )
             .arg(Arg::new("wordlist")
@@ -380,6 +831,102 @@ fn parse_args() -> ArgMatches {
                 .value_name("file_path")
                 .value_parser(value_parser!(PathBuf))
             )
+            .arg(Arg::new("wordlist_size")
+                .help("Use the built-in wordlist of the given size (default: medium) - Example: --wordlist-size large")
+                .long("wordlist-size")
+                .value_name("size")
+                .value_parser(["small", "medium", "large"])
+                .conflicts_with("wordlist")
+            )
+            .arg(Arg::new("recursive")
+                .help("Brute-force under each discovered subdomain (e.g. bar.foo.example.com). Wildcard subdomains are skipped")
+                .long("recursive")
+                .num_args(0)
+            )
+            .arg(Arg::new("depth")
+                .help("Set max depth of recursive brute-force (default: 1) - Example: --depth 2")
+                .long("depth")
+                .value_name("depth")
+                .value_parser(value_parser!(usize))
+                .requires("recursive")
+            )
+            .arg(Arg::new("records")
+                .help("Fetch additional records for each discovered subdomain. All types if no value is given - Example: --records mx,txt")
+                .long("records")
+                .value_name("types")
+                .num_args(0..=1)
+                .value_delimiter(',')
+                .value_parser(["mx", "txt", "ns", "cname", "srv"])
+                .default_missing_values(["mx", "txt", "ns", "cname", "srv"])
+            )
+            .arg(Arg::new("resume")
+                .help("Resume an interrupted scan from the output file (-o). Findings are appended to it while scanning")
+                .long("resume")
+                .num_args(0)
+                .conflicts_with("passive_only")
+            )
+            .arg(Arg::new("then_port")
+                .help("Port scan the resolved addresses (deduplicated). Scans --port if specified, otherwise default 1000 ports")
+                .long("then-port")
+                .num_args(0)
+                .conflicts_with("passive_only")
+            )
+            .arg(Arg::new("port")
+                .help("Specify the port(s) for --then-port. Example: --port 80,443")
+                .short('p')
+                .long("port")
+                .value_name("port")
+                .value_delimiter(',')
+                .value_parser(value_parser!(u16))
+                .requires("then_port")
+            )
+            .arg(Arg::new("axfr")
+                .help("Attempt zone transfer (AXFR) against the authoritative nameservers and import the zone if allowed")
+                .long("axfr")
+                .num_args(0)
+                .conflicts_with("passive_only")
+            )
+            .arg(Arg::new("cdn")
+                .help("Tag resolved addresses with their CDN or cloud provider (e.g. cloudflare, aws) by origin ASN")
+                .long("cdn")
+                .num_args(0)
+            )
+            .arg(Arg::new("qps")
+                .help("Limit DNS queries per second - Example: --qps 50")
+                .long("qps")
+                .value_name("count")
+                .value_parser(value_parser!(u32))
+            )
+            .arg(Arg::new("concurrency")
+                .help("Set max number of concurrent lookups (default: 100) - Example: --concurrency 500")
+                .long("concurrency")
+                .value_name("count")
+                .value_parser(value_parser!(usize))
+            )
+            .arg(Arg::new("resolvers")
+                .help("Use these DNS resolvers in rotation instead of the system resolver - Example: --resolvers 1.1.1.1,8.8.8.8:53")
+                .long("resolvers")
+                .value_name("ip_addr")
+                .value_delimiter(',')
+                .value_parser(crate::dns::resolver::parse_resolver_addr)
+            )
+            .arg(Arg::new("resolvers_file")
+                .help("Read DNS resolvers from file (one per line) and use them in rotation")
+                .long("resolvers-file")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+            )
+            .arg(Arg::new("passive")
+                .help("Also collect subdomains from public sources (certificate transparency, etc.) and merge with brute-force results")
+                .long("passive")
+                .num_args(0)
+            )
+            .arg(Arg::new("passive_only")
+                .help("Collect subdomains from public sources only. No DNS query or packet is sent toward the target")
+                .long("passive-only")
+                .num_args(0)
+                .conflicts_with_all(["passive", "wordlist", "wordlist_size"])
+            )
             .arg(Arg::new("timeout")
                 .help("Set timeout in ms - Example: --timeout 10000")
                 .long("timeout")
@@ -387,12 +934,185 @@ fn parse_args() -> ArgMatches {
                 .value_parser(value_parser!(u64))
             )
         )
+        .subcommand(Command::new("dns")
+            .about("Query DNS records. nrev dns --help for more information")
+            .arg(Arg::new("target")
+                .help("Specify the name to query. IP address is looked up as PTR. Domain name for --ad")
+                .value_name("name")
+                .value_parser(crate::dns::idn::parse_target)
+                .required_unless_present("snoop")
+            )
+            .arg(Arg::new("snoop")
+                .help("Cache snooping. Send non-recursive queries to the server and report names found in its cache")
+                .long("snoop")
+                .num_args(0)
+                .requires("server")
+                .conflicts_with("target")
+            )
+            .arg(Arg::new("ad")
+                .help("Active Directory discovery. Query the _ldap/_kerberos/_gc SRV records of the domain and list the domain controllers")
+                .long("ad")
+                .num_args(0)
+                .requires("target")
+                .conflicts_with_all(["snoop", "type"])
+            )
+            .arg(Arg::new("then_port")
+                .help("Port scan the domain controllers found by --ad. Scans --port if specified, otherwise common AD ports")
+                .long("then-port")
+                .num_args(0)
+                .requires("ad")
+            )
+            .arg(Arg::new("port")
+                .help("Specify the port(s) for --then-port. Example: --port 389,636")
+                .short('p')
+                .long("port")
+                .value_name("port")
+                .value_delimiter(',')
+                .value_parser(value_parser!(u16))
+                .requires("then_port")
+            )
+            .arg(Arg::new("wordlist")
+                .help("Names checked by --snoop, one per line (default: built-in list of popular names)")
+                .short('w')
+                .long("wordlist")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+                .requires("snoop")
+            )
+            .arg(Arg::new("type")
+                .help("Set record type (default: A, or PTR for IP address) - Example: --type MX")
+                .long("type")
+                .value_name("type")
+                .value_parser(crate::dns::query::QUERY_TYPES)
+                .ignore_case(true)
+            )
+            .arg(Arg::new("server")
+                .help("Query this DNS server instead of the system resolver - Example: --server 1.1.1.1")
+                .long("server")
+                .value_name("ip_addr")
+                .value_parser(crate::dns::resolver::parse_resolver_addr)
+            )
+            .arg(Arg::new("tcp")
+                .help("Query over TCP")
+                .long("tcp")
+                .num_args(0)
+            )
+            .arg(Arg::new("timeout")
+                .help("Set timeout in ms (default: 5000) - Example: --timeout 10000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("rdns")
+            .about("Reverse DNS (PTR) sweep. nrev rdns --help for more information")
+            .arg(Arg::new("target")
+                .help("Specify the target network, range or IP address - Example: 10.0.0.0/24")
+                .value_name("target")
+                .required(true)
+            )
+            .arg(Arg::new("resolvers")
+                .help("Use these DNS resolvers in rotation instead of the system resolver - Example: --resolvers 10.0.0.53")
+                .long("resolvers")
+                .value_name("ip_addr")
+                .value_delimiter(',')
+                .value_parser(crate::dns::resolver::parse_resolver_addr)
+            )
+            .arg(Arg::new("resolvers_file")
+                .help("Read DNS resolvers from file. One address per line")
+                .long("resolvers-file")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+            )
+            .arg(Arg::new("qps")
+                .help("Limit DNS queries per second - Example: --qps 50")
+                .long("qps")
+                .value_name("count")
+                .value_parser(value_parser!(u32))
+            )
+            .arg(Arg::new("netbios")
+                .help("Also query NetBIOS-NS node status of each address to recover Windows host names")
+                .long("netbios")
+                .num_args(0)
+            )
+            .arg(Arg::new("llmnr")
+                .help("Also ask each address for its name over LLMNR")
+                .long("llmnr")
+                .num_args(0)
+            )
+            .arg(Arg::new("concurrency")
+                .help("Set max number of concurrent lookups (default: 50) - Example: --concurrency 100")
+                .long("concurrency")
+                .value_name("count")
+                .value_parser(value_parser!(usize))
+            )
+            .arg(Arg::new("timeout")
+                .help("Set timeout of each lookup in ms (default: 2000) - Example: --timeout 1000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("mdns")
+            .about("Discover devices and services on the local network with mDNS/DNS-SD. nrev mdns --help for more information")
+            .arg(Arg::new("service")
+                .help("Browse these service types in addition to the common ones - Example: --service _octoprint._tcp")
+                .long("service")
+                .value_name("service_type")
+                .value_delimiter(',')
+            )
+            .arg(Arg::new("timeout")
+                .help("Set listen time in ms (default: 3000) - Example: --timeout 5000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
         .subcommand(Command::new("nei")
             .about("Resolve IP address to MAC address")
             .arg(Arg::new("target")
-                .help("Specify the target IP address")
+                .help("Specify the target IP address, or network/range to resolve every address - Example: 192.168.1.0/24. IPv6 link-local address requires zone ID (e.g. fe80::1%eth0)")
                 .value_name("target")
-                .required(true)
+                .required_unless_present("table")
+            )
+            .arg(Arg::new("table")
+                .help("Show the ARP/NDP cache of the OS instead of sending requests")
+                .long("table")
+                .num_args(0)
+                .conflicts_with("target")
+            )
+            .arg(Arg::new("announce")
+                .help("Send gratuitous ARP for the local IPv4 address to update neighbor caches (e.g. after failover). Asks for confirmation")
+                .long("announce")
+                .num_args(0)
+                .requires("target")
+            )
+            .arg(Arg::new("yes")
+                .help("Skip the confirmation of --announce")
+                .long("yes")
+                .num_args(0)
+                .requires("announce")
+            )
+            .arg(Arg::new("track")
+                .help("Re-sweep the network periodically and print join/leave events until Ctrl-C. With --json, one event per line")
+                .long("track")
+                .num_args(0)
+                .requires("target")
+                .conflicts_with("announce")
+            )
+            .arg(Arg::new("interval")
+                .help("Set interval of --track sweeps in seconds (default: 60) - Example: --interval 30")
+                .long("interval")
+                .value_name("seconds")
+                .value_parser(value_parser!(u64))
+                .requires("track")
+            )
+            .arg(Arg::new("miss")
+                .help("Set number of sweeps without answer before a leave event (default: 2)")
+                .long("miss")
+                .value_name("count")
+                .value_parser(value_parser!(u32))
+                .requires("track")
             )
             .arg(Arg::new("count")
                 .help("Set number of requests or pings to be sent")
@@ -421,17 +1141,525 @@ fn parse_args() -> ArgMatches {
                 .value_parser(value_parser!(u64))
             )
         )
+        .subcommand(Command::new("wol")
+            .about("Wake up a host by Wake-on-LAN magic packet")
+            .arg(Arg::new("mac")
+                .help("Specify the MAC address of the host - Example: 00:11:22:33:44:55")
+                .value_name("mac")
+                .required(true)
+            )
+            .arg(Arg::new("broadcast")
+                .help("Set the destination (broadcast) address. Directed broadcast of the interface by default - Example: --broadcast 192.168.1.255")
+                .long("broadcast")
+                .value_name("addr")
+                .value_parser(value_parser!(std::net::Ipv4Addr))
+            )
+            .arg(Arg::new("port")
+                .help("Set the destination UDP port (default: 9) - Example: --port 7")
+                .short('p')
+                .long("port")
+                .value_name("port")
+                .value_parser(value_parser!(u16))
+            )
+            .arg(Arg::new("count")
+                .help("Set number of magic packets to be sent (default: 3)")
+                .short('c')
+                .long("count")
+                .value_name("count")
+                .value_parser(value_parser!(u32))
+            )
+            .arg(Arg::new("verify")
+                .help("Ping the IP address after sending to verify the host comes up - Example: --verify 192.168.1.20")
+                .long("verify")
+                .value_name("ip_addr")
+                .value_parser(value_parser!(IpAddr))
+            )
+            .arg(Arg::new("wait")
+                .help("Set max time to wait for the host in seconds (default: 60)")
+                .long("wait")
+                .value_name("seconds")
+                .value_parser(value_parser!(u64))
+                .requires("verify")
+            )
+        )
+        .subcommand(Command::new("myip")
+            .about("Show external IPv4/IPv6 addresses and NAT presence")
+            .arg(Arg::new("method")
+                .help("Use only the method. By default STUN, DNS and HTTP are tried in order")
+                .long("method")
+                .value_name("method")
+                .value_parser(["stun", "dns", "http"])
+            )
+            .arg(Arg::new("stun")
+                .help("Specify STUN servers - Example: --stun stun.example.com:3478")
+                .long("stun")
+                .value_name("host:port")
+                .num_args(1..)
+                .value_delimiter(',')
+            )
+            .arg(Arg::new("timeout")
+                .help("Set timeout of each lookup in ms (default: 3000) - Example: --timeout 5000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("whois")
+            .about("Look up whois of a domain or IP address, following referrals")
+            .arg(Arg::new("query")
+                .help("Specify the domain name or IP address - Example: example.com")
+                .value_name("query")
+                .required(true)
+            )
+            .arg(Arg::new("server")
+                .help("Set the first whois server to query (default: whois.iana.org) - Example: --server whois.arin.net")
+                .long("server")
+                .value_name("server")
+            )
+            .arg(Arg::new("raw")
+                .help("Print raw responses of each server")
+                .long("raw")
+                .num_args(0)
+            )
+            .arg(Arg::new("timeout")
+                .help("Set timeout of each server in ms (default: 10000) - Example: --timeout 5000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("rdap")
+            .about("Look up registration data of an IP address, ASN or domain by RDAP")
+            .arg(Arg::new("query")
+                .help("Specify the IP address, ASN or domain name - Example: AS15169")
+                .value_name("query")
+                .required(true)
+            )
+            .arg(Arg::new("server")
+                .help("Set the base URL of the RDAP server. Selected from the IANA bootstrap registry by default - Example: --server https://rdap.arin.net/registry")
+                .long("server")
+                .value_name("url")
+            )
+            .arg(Arg::new("raw")
+                .help("Print the raw RDAP response")
+                .long("raw")
+                .num_args(0)
+            )
+            .arg(Arg::new("timeout")
+                .help("Set timeout of each request in ms (default: 10000) - Example: --timeout 5000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("geo")
+            .about("Look up country/city/ASN of IP addresses in MMDB databases given by --geoip")
+            .arg(Arg::new("target")
+                .help("Specify IP addresses or host names - Example: 8.8.8.8 1.1.1.1")
+                .value_name("target")
+                .num_args(1..)
+                .required(true)
+            )
+        )
+        .subcommand(Command::new("http")
+            .about("Probe hosts/URLs for status code, redirect chain, server header, title and response time")
+            .arg(Arg::new("target")
+                .help("Specify hosts, URLs or files with one target per line - Example: example.com https://example.net/login")
+                .value_name("target")
+                .num_args(1..)
+                .required(true)
+            )
+            .arg(Arg::new("no_redirect")
+                .help("Do not follow redirects")
+                .long("no-redirect")
+                .num_args(0)
+            )
+            .arg(Arg::new("concurrency")
+                .help("Set max number of concurrent requests (default: 50) - Example: --concurrency 100")
+                .long("concurrency")
+                .value_name("count")
+                .value_parser(value_parser!(usize))
+            )
+            .arg(Arg::new("timeout")
+                .help("Set timeout of each request in ms (default: 10000) - Example: --timeout 5000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("tls")
+            .about("Audit TLS of a server. Supported protocols and cipher suites, certificate chain, expiry and name mismatch")
+            .arg(Arg::new("target")
+                .help("Specify the host and port (default: 443) - Example: example.com:8443")
+                .value_name("host:port")
+                .required(true)
+            )
+            .arg(Arg::new("sni")
+                .help("Set the server name for SNI and certificate check. The host by default")
+                .long("sni")
+                .value_name("name")
+            )
+            .arg(Arg::new("no_ciphers")
+                .help("Only check protocol versions. Skip cipher suite enumeration")
+                .long("no-ciphers")
+                .num_args(0)
+            )
+            .arg(Arg::new("timeout")
+                .help("Set timeout of each handshake in ms (default: 5000) - Example: --timeout 3000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("snmp")
+            .about("Find SNMP devices by common community strings and show sysDescr/sysName/uptime")
+            .arg(Arg::new("target")
+                .help("Specify IP address, network (CIDR) or range - Example: 192.168.1.0/24")
+                .value_name("target")
+                .required(true)
+            )
+            .arg(Arg::new("community")
+                .help("Set community strings to try, in order (default: public,private,community,manager,cisco,snmp) - Example: --community public,netops")
+                .long("community")
+                .value_name("community")
+                .value_delimiter(',')
+            )
+            .arg(Arg::new("snmp_version")
+                .help("Set SNMP version (default: 2c)")
+                .long("snmp-version")
+                .value_name("version")
+                .value_parser(["1", "2c"])
+            )
+            .arg(Arg::new("concurrency")
+                .help("Set max number of hosts probed concurrently (default: 50) - Example: --concurrency 100")
+                .long("concurrency")
+                .value_name("count")
+                .value_parser(value_parser!(usize))
+            )
+            .arg(Arg::new("timeout")
+                .help("Set wait time for each community in ms (default: 1000) - Example: --timeout 2000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("ntp")
+            .about("Query NTP servers for version, stratum and system variables, and check monlist for amplification exposure")
+            .arg(Arg::new("target")
+                .help("Specify host name, IP address, network (CIDR) or range - Example: 192.168.1.0/24")
+                .value_name("target")
+                .required(true)
+            )
+            .arg(Arg::new("no_monlist")
+                .help("Do not send the mode 7 monlist request")
+                .long("no-monlist")
+                .num_args(0)
+            )
+            .arg(Arg::new("concurrency")
+                .help("Set max number of hosts probed concurrently (default: 50) - Example: --concurrency 100")
+                .long("concurrency")
+                .value_name("count")
+                .value_parser(value_parser!(usize))
+            )
+            .arg(Arg::new("timeout")
+                .help("Set wait time for each query in ms (default: 2000) - Example: --timeout 3000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+        )
+        .subcommand(Command::new("capture")
+            .about("Capture packets with a live one-line-per-packet display, and save them to a pcap file")
+            .arg(Arg::new("interface")
+                .help("Specify the network interface. The default interface if not specified")
+                .short('i')
+                .long("interface")
+                .value_name("interface_name")
+            )
+            .arg(Arg::new("filter")
+                .help("Set the capture filter. Protocols (tcp, udp, icmp, icmp6, arp, ip, ip6), [src|dst] host/net/port, and/or/not - Example: --filter \"tcp port 443\"")
+                .short('f')
+                .long("filter")
+                .value_name("expression")
+            )
+            .arg(Arg::new("output")
+                .help("Save packets to a pcap file - Example: -o out.pcap")
+                .short('o')
+                .long("output")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+            )
+            .arg(Arg::new("count")
+                .help("Stop after capturing this many packets - Example: --count 1000")
+                .short('c')
+                .long("count")
+                .value_name("count")
+                .value_parser(value_parser!(usize))
+            )
+            .arg(Arg::new("duration")
+                .help("Stop after this many seconds - Example: --duration 60")
+                .long("duration")
+                .value_name("seconds")
+                .value_parser(value_parser!(u64))
+            )
+            .arg(Arg::new("max_size")
+                .help("Stop before the capture exceeds this size. K/M/G suffix allowed - Example: --max-size 100M")
+                .long("max-size")
+                .value_name("size")
+            )
+            .arg(Arg::new("promisc")
+                .help("Capture in promiscuous mode")
+                .long("promisc")
+                .num_args(0)
+            )
+        )
+        .subcommand(Command::new("passive")
+            .about("Build a host inventory from ARP/DHCP/mDNS/TCP SYN traffic without sending any packet")
+            .arg(Arg::new("interface")
+                .help("Specify the network interface. The default interface if not specified")
+                .short('i')
+                .long("interface")
+                .value_name("interface_name")
+            )
+            .arg(Arg::new("duration")
+                .help("Stop after this many seconds. Until Ctrl-C if not specified - Example: --duration 600")
+                .long("duration")
+                .value_name("seconds")
+                .value_parser(value_parser!(u64))
+            )
+            .arg(Arg::new("promisc")
+                .help("Listen in promiscuous mode to see TCP connections between other hosts")
+                .long("promisc")
+                .num_args(0)
+            )
+        )
+        .subcommand(Command::new("serve")
+            .about("Serve a REST API to launch port/host/ping/trace jobs, poll their status and fetch JSON results")
+            .arg(Arg::new("listen")
+                .help("Listen address - Example: --listen 127.0.0.1:8080")
+                .long("listen")
+                .value_name("socket_addr")
+                .value_parser(value_parser!(SocketAddr))
+            )
+            .arg(Arg::new("token")
+                .help("Require this bearer token in the Authorization header")
+                .long("token")
+                .value_name("token")
+            )
+        )
+        .subcommand(Command::new("grpc")
+            .about("Serve a gRPC API that streams port/host scan findings in real time. Requires the grpc feature")
+            .arg(Arg::new("listen")
+                .help("Listen address - Example: --listen 127.0.0.1:50051")
+                .long("listen")
+                .value_name("socket_addr")
+                .value_parser(value_parser!(SocketAddr))
+            )
+        )
+        .subcommand(Command::new("exporter")
+            .about("Ping/port-check targets periodically and expose the results on /metrics for Prometheus")
+            .arg(Arg::new("target")
+                .help("Specify the target(s). IP address or Hostname - Example: 192.168.1.1,example.com")
+                .value_name("target")
+                .value_delimiter(',')
+                .value_parser(crate::dns::idn::parse_target)
+                .required_unless_present("input_file")
+            )
+            .arg(Arg::new("input_file")
+                .help("Read targets from file. One IP address or host name per line")
+                .long("input-file")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+            )
+            .arg(Arg::new("ports")
+                .help("TCP ports to check on each target - Example: 22,80,443")
+                .short('p')
+                .long("ports")
+                .value_name("ports")
+                .value_delimiter(',')
+                .value_parser(value_parser!(u16))
+            )
+            .arg(Arg::new("interval")
+                .help("Seconds between check rounds. Default: 60")
+                .long("interval")
+                .value_name("secs")
+                .value_parser(value_parser!(u64))
+            )
+            .arg(Arg::new("timeout")
+                .help("Timeout of each ping and TCP connect in milliseconds. Default: 1000")
+                .long("timeout")
+                .value_name("timeout")
+                .value_parser(value_parser!(u64))
+            )
+            .arg(Arg::new("listen")
+                .help("Listen address - Example: --listen 0.0.0.0:9725")
+                .long("listen")
+                .value_name("socket_addr")
+                .value_parser(value_parser!(SocketAddr))
+            )
+        )
+        .subcommand(Command::new("schedule")
+            .about("Run recurring port/host/ping/trace scans on cron expressions and keep their results in the history directory")
+            .arg(Arg::new("add")
+                .help("Add a schedule with this name. Requires --cron and --job")
+                .long("add")
+                .value_name("name")
+                .requires_all(["cron", "job"])
+                .conflicts_with_all(["remove", "list"])
+            )
+            .arg(Arg::new("cron")
+                .help("Cron expression (minute hour day month weekday) - Example: --cron \"0 */6 * * *\"")
+                .long("cron")
+                .value_name("expr")
+                .requires("add")
+            )
+            .arg(Arg::new("job")
+                .help("Command line of the scan - Example: --job \"host 192.168.1.0/24 --ports 22,80\"")
+                .long("job")
+                .value_name("command_line")
+                .requires("add")
+            )
+            .arg(Arg::new("remove")
+                .help("Remove the schedule with this name")
+                .long("remove")
+                .value_name("name")
+                .conflicts_with("list")
+            )
+            .arg(Arg::new("list")
+                .help("List schedules and their next run")
+                .long("list")
+                .num_args(0)
+            )
+            .arg(Arg::new("config")
+                .help("Schedule file. Default: ~/.nrev/schedules.json")
+                .long("config")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+            )
+            .arg(Arg::new("history")
+                .help("Directory for results and history.jsonl. Default: ~/.nrev/history")
+                .long("history")
+                .value_name("dir_path")
+                .value_parser(value_parser!(PathBuf))
+            )
+        )
+        .subcommand(Command::new("import")
+            .about("Import nmap XML results into the scan history")
+            .arg(Arg::new("file")
+                .help("nmap XML output (-oX) - Example: nrev import results.xml")
+                .value_name("file_path")
+                .value_parser(value_parser!(PathBuf))
+                .required(true)
+            )
+            .arg(Arg::new("name")
+                .help("Name of the imported results in the history")
+                .long("name")
+                .value_name("name")
+                .default_value("nmap")
+            )
+            .arg(Arg::new("history")
+                .help("Directory for results and history.jsonl. Default: ~/.nrev/history")
+                .long("history")
+                .value_name("dir_path")
+                .value_parser(value_parser!(PathBuf))
+            )
+        )
+        .subcommand(Command::new("agent")
+            .about("Run as an agent accepting signed port/host/ping/trace jobs from a controller")
+            .arg(Arg::new("listen")
+                .help("Listen address - Example: --listen 0.0.0.0:7878")
+                .long("listen")
+                .value_name("socket_addr")
+                .value_parser(value_parser!(SocketAddr))
+            )
+            .arg(Arg::new("key")
+                .help("Shared key to verify jobs and sign results. Default: NREV_AGENT_KEY")
+                .long("key")
+                .value_name("key")
+            )
+            .arg(Arg::new("name")
+                .help("Name of this vantage point reported to the controller. Default: host name")
+                .long("name")
+                .value_name("name")
+            )
+        )
+        .subcommand(Command::new("controller")
+            .about("Run a scan from multiple agents and merge their results into one report")
+            .arg(Arg::new("agent")
+                .help("Agent addresses - Example: --agent 10.0.0.5:7878,203.0.113.10:7878")
+                .long("agent")
+                .value_name("host:port")
+                .value_delimiter(',')
+                .required(true)
+            )
+            .arg(Arg::new("job")
+                .help("Command line of the scan - Example: --job \"port example.com --ports 22,80,443\"")
+                .long("job")
+                .value_name("command_line")
+                .required(true)
+            )
+            .arg(Arg::new("key")
+                .help("Shared key of the agents. Default: NREV_AGENT_KEY")
+                .long("key")
+                .value_name("key")
+            )
+        )
         .subcommand(Command::new("interfaces")
             .about("Show network interfaces")
+            .arg(Arg::new("brief")
+                .help("Show one line per interface (name, index, MAC, addresses)")
+                .long("brief")
+                .num_args(0)
+            )
+            .arg(Arg::new("stats")
+                .help("Show RX/TX bytes, packets, errors and rates per interface")
+                .long("stats")
+                .num_args(0)
+                .conflicts_with("brief")
+            )
+            .arg(Arg::new("watch")
+                .help("Refresh --stats in place until Ctrl-C")
+                .long("watch")
+                .num_args(0)
+                .requires("stats")
+            )
+            .arg(Arg::new("interval")
+                .help("Set sampling interval of --stats in seconds (default: 1)")
+                .long("interval")
+                .value_name("seconds")
+                .value_parser(value_parser!(u64))
+                .requires("stats")
+            )
         )
         .subcommand(Command::new("interface")
-            .about("Show default network interface")
+            .about("Show network interface. Default interface if not specified")
+            .arg(Arg::new("name")
+                .help("Specify the interface by name, friendly name, index or IP address - Example: eth0")
+                .value_name("name")
+            )
+            .arg(Arg::new("only")
+                .help("Print only the values of the field, one per line")
+                .long("only")
+                .value_name("field")
+                .value_parser(["name", "index", "mac", "ipv4", "ipv6", "mtu", "gateway", "dns", "flags"])
+                .conflicts_with("brief")
+            )
+            .arg(Arg::new("brief")
+                .help("Show in one line (name, index, MAC, addresses)")
+                .long("brief")
+                .num_args(0)
+            )
+            .arg(Arg::new("no_probe")
+                .help("Do not probe the gateway of the default interface (ARP/NDP and ICMP)")
+                .long("no-probe")
+                .num_args(0)
+            )
         )
         .subcommand(Command::new("check")
-            .about("Check dependencies (Windows only)")
+            .about("Check dependencies and capabilities (raw socket, packet capture, ICMP socket, IPv6) and show which scan types will work")
         )
         ;
-    app.get_matches()
+    app
 }
 
 fn check_deps() {
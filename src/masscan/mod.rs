@@ -0,0 +1,198 @@
+use crate::host::{Host, Port, PortStatus};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// One line of masscan output. e.g. `open tcp 80 192.168.1.1 1609459200`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MasscanRecord {
+    pub ip_addr: IpAddr,
+    pub port: u16,
+    /// tcp, udp or sctp
+    pub proto: String,
+    /// open or closed
+    pub status: String,
+    pub ttl: Option<u8>,
+    pub timestamp: i64,
+}
+
+/// Parse list output (-oL)
+fn parse_list(text: &str) -> Result<Vec<MasscanRecord>, String> {
+    let mut records: Vec<MasscanRecord> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // Banner lines are skipped. Only port status is used
+        if fields[0] == "banner" {
+            continue;
+        }
+        if fields.len() < 4 {
+            return Err(format!("Line {}: unexpected format: {}", i + 1, line));
+        }
+        records.push(MasscanRecord {
+            status: fields[0].to_string(),
+            proto: fields[1].to_string(),
+            port: fields[2]
+                .parse()
+                .map_err(|_| format!("Line {}: invalid port: {}", i + 1, fields[2]))?,
+            ip_addr: fields[3]
+                .parse()
+                .map_err(|_| format!("Line {}: invalid address: {}", i + 1, fields[3]))?,
+            ttl: None,
+            timestamp: fields.get(4).and_then(|ts| ts.parse().ok()).unwrap_or(0),
+        });
+    }
+    Ok(records)
+}
+
+fn parse_json_object(value: &Value, records: &mut Vec<MasscanRecord>) {
+    let ip_addr: IpAddr = match value["ip"].as_str().and_then(|ip| ip.parse().ok()) {
+        Some(ip_addr) => ip_addr,
+        None => return,
+    };
+    // timestamp is a string in masscan output
+    let timestamp: i64 = match &value["timestamp"] {
+        Value::String(ts) => ts.parse().unwrap_or(0),
+        ts => ts.as_i64().unwrap_or(0),
+    };
+    for port in value["ports"].as_array().into_iter().flatten() {
+        // Banner entries have `service` instead of `status`
+        let (status, port_number) = match (port["status"].as_str(), port["port"].as_u64()) {
+            (Some(status), Some(port_number)) => (status, port_number),
+            _ => continue,
+        };
+        records.push(MasscanRecord {
+            ip_addr: ip_addr,
+            port: port_number as u16,
+            proto: port["proto"].as_str().unwrap_or("tcp").to_string(),
+            status: status.to_string(),
+            ttl: port["ttl"].as_u64().map(|ttl| ttl as u8),
+            timestamp: timestamp,
+        });
+    }
+}
+
+/// Parse JSON output (-oJ). Older masscan versions write one object per line
+/// with trailing commas and a `{finished: 1}` line, so objects are read line by line if needed
+fn parse_json(text: &str) -> Result<Vec<MasscanRecord>, String> {
+    let mut records: Vec<MasscanRecord> = Vec::new();
+    if let Ok(Value::Array(values)) = serde_json::from_str::<Value>(text) {
+        for value in &values {
+            parse_json_object(value, &mut records);
+        }
+        return Ok(records);
+    }
+    for (i, line) in text.lines().enumerate() {
+        let line: &str = line.trim().trim_end_matches(',');
+        if !line.starts_with('{') || line.starts_with("{finished") {
+            continue;
+        }
+        let value: Value = serde_json::from_str(line)
+            .map_err(|e| format!("Line {}: invalid JSON: {}", i + 1, e))?;
+        parse_json_object(&value, &mut records);
+    }
+    Ok(records)
+}
+
+/// Parse masscan list or JSON output. The format is detected from the content
+pub fn parse(text: &str) -> Result<Vec<MasscanRecord>, String> {
+    let trimmed: &str = text.trim_start();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        parse_json(text)
+    } else {
+        parse_list(text)
+    }
+}
+
+/// Hosts with the open TCP ports of the records
+pub fn records_to_hosts(records: &[MasscanRecord]) -> Vec<Host> {
+    let mut hosts: BTreeMap<IpAddr, Host> = BTreeMap::new();
+    for record in records {
+        if record.status != "open" || record.proto != "tcp" {
+            continue;
+        }
+        let host: &mut Host = hosts
+            .entry(record.ip_addr)
+            .or_insert_with(|| Host::new(record.ip_addr, record.ip_addr.to_string()));
+        if let Some(ttl) = record.ttl {
+            host.ttl = ttl;
+        }
+        if host.ports.iter().any(|port| port.number == record.port) {
+            continue;
+        }
+        let mut port: Port = Port::new(record.port);
+        port.status = PortStatus::Open;
+        host.ports.push(port);
+    }
+    let mut hosts: Vec<Host> = hosts.into_values().collect();
+    for host in &mut hosts {
+        host.ports.sort_by_key(|port| port.number);
+    }
+    hosts
+}
+
+/// Open ports in list format. Detected service versions are written as banner lines
+pub fn to_list(hosts: &[Host], timestamp: i64) -> String {
+    let mut lines: Vec<String> = vec!["#masscan".to_string()];
+    for host in hosts {
+        for port in host.get_open_ports() {
+            lines.push(format!(
+                "open tcp {} {} {}",
+                port.number, host.ip_addr, timestamp
+            ));
+            if !port.service_version.is_empty() {
+                lines.push(format!(
+                    "banner tcp {} {} {} {} {}",
+                    port.number,
+                    host.ip_addr,
+                    timestamp,
+                    if port.service_name.is_empty() {
+                        "unknown"
+                    } else {
+                        &port.service_name
+                    },
+                    port.service_version.replace('\n', " ")
+                ));
+            }
+        }
+    }
+    lines.push("# end".to_string());
+    lines.join("\n")
+}
+
+/// Open ports in JSON format. One object per port as masscan writes
+pub fn to_json(hosts: &[Host], timestamp: i64) -> String {
+    let mut objects: Vec<Value> = Vec::new();
+    for host in hosts {
+        for port in host.get_open_ports() {
+            objects.push(json!({
+                "ip": host.ip_addr.to_string(),
+                "timestamp": timestamp.to_string(),
+                "ports": [{
+                    "port": port.number,
+                    "proto": "tcp",
+                    "status": "open",
+                    "ttl": host.ttl,
+                }],
+            }));
+            if !port.service_version.is_empty() {
+                objects.push(json!({
+                    "ip": host.ip_addr.to_string(),
+                    "timestamp": timestamp.to_string(),
+                    "ports": [{
+                        "port": port.number,
+                        "proto": "tcp",
+                        "service": {
+                            "name": port.service_name,
+                            "banner": port.service_version,
+                        },
+                    }],
+                }));
+            }
+        }
+    }
+    serde_json::to_string_pretty(&objects).unwrap()
+}
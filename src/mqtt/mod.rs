@@ -0,0 +1,285 @@
+use nex::socket::tls::rustls;
+use nex::socket::tls::TlsClient;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Keep alive sent in CONNECT. The connection lasts only for one publish
+const KEEP_ALIVE_SECS: u16 = 60;
+
+/// Parsed broker URL. tcp://[user:pass@]host[:1883] or mqtts://[user:pass@]host[:8883]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MqttUrl {
+    pub tls: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl MqttUrl {
+    pub fn parse(url: &str) -> Result<MqttUrl, String> {
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("tcp://") {
+            (false, rest)
+        } else if let Some(rest) = url.strip_prefix("mqtt://") {
+            (false, rest)
+        } else if let Some(rest) = url.strip_prefix("mqtts://").or(url.strip_prefix("ssl://")) {
+            (true, rest)
+        } else {
+            return Err(format!("Unsupported MQTT URL: {}", url));
+        };
+        let authority: &str = rest.trim_end_matches('/');
+        let (userinfo, authority): (Option<&str>, &str) = match authority.rsplit_once('@') {
+            Some((userinfo, authority)) => (Some(userinfo), authority),
+            None => (None, authority),
+        };
+        let (username, password): (Option<String>, Option<String>) = match userinfo {
+            Some(userinfo) => match userinfo.split_once(':') {
+                Some((username, password)) => {
+                    (Some(username.to_string()), Some(password.to_string()))
+                }
+                None => (Some(userinfo.to_string()), None),
+            },
+            None => (None, None),
+        };
+        let default_port: u16 = if tls { 8883 } else { 1883 };
+        // IPv6 literal is enclosed in brackets (e.g. [::1]:1883)
+        let (host, port_str): (&str, Option<&str>) = if authority.starts_with('[') {
+            match authority.find(']') {
+                Some(i) => (&authority[1..i], authority[i + 1..].strip_prefix(':')),
+                None => return Err(format!("Invalid MQTT URL: {}", url)),
+            }
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            }
+        };
+        let port: u16 = match port_str {
+            Some(port) => port
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid port in MQTT URL: {}", url))?,
+            None => default_port,
+        };
+        if host.is_empty() {
+            return Err(format!("Invalid MQTT URL: {}", url));
+        }
+        Ok(MqttUrl {
+            tls: tls,
+            host: host.to_string(),
+            port: port,
+            username: username,
+            password: password,
+        })
+    }
+}
+
+/// Remaining length. Variable byte integer, up to 4 bytes
+fn encode_length(mut len: usize, buf: &mut Vec<u8>) {
+    loop {
+        let mut byte: u8 = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+/// UTF-8 string or binary data with a 2 byte length prefix
+fn encode_bytes(data: &[u8], buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    buf.extend_from_slice(data);
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut buf: Vec<u8> = vec![header];
+    encode_length(body.len(), &mut buf);
+    buf.extend_from_slice(body);
+    buf
+}
+
+fn connect_packet(client_id: &str, username: Option<&str>, password: Option<&str>) -> Vec<u8> {
+    let mut body: Vec<u8> = Vec::new();
+    encode_bytes(b"MQTT", &mut body);
+    // Protocol level 4 (MQTT 3.1.1)
+    body.push(4);
+    // Clean session
+    let mut flags: u8 = 0x02;
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    body.extend_from_slice(&KEEP_ALIVE_SECS.to_be_bytes());
+    encode_bytes(client_id.as_bytes(), &mut body);
+    if let Some(username) = username {
+        encode_bytes(username.as_bytes(), &mut body);
+    }
+    if let Some(password) = password {
+        encode_bytes(password.as_bytes(), &mut body);
+    }
+    packet(0x10, &body)
+}
+
+/// PUBLISH with QoS 1. Retain is off so only live subscribers get scan events
+fn publish_packet(topic: &str, packet_id: u16, payload: &[u8]) -> Vec<u8> {
+    let mut body: Vec<u8> = Vec::new();
+    encode_bytes(topic.as_bytes(), &mut body);
+    body.extend_from_slice(&packet_id.to_be_bytes());
+    body.extend_from_slice(payload);
+    packet(0x32, &body)
+}
+
+fn connack_error(code: u8) -> String {
+    match code {
+        1 => "unacceptable protocol version".to_string(),
+        2 => "client identifier rejected".to_string(),
+        3 => "server unavailable".to_string(),
+        4 => "bad user name or password".to_string(),
+        5 => "not authorized".to_string(),
+        _ => format!("return code {}", code),
+    }
+}
+
+/// Read one packet. Returns the first byte of the fixed header and the body
+fn read_packet<S: Read>(stream: &mut S) -> Result<(u8, Vec<u8>), String> {
+    let mut header: [u8; 1] = [0; 1];
+    stream
+        .read_exact(&mut header)
+        .map_err(|e| format!("Failed to read from broker: {}", e))?;
+    let mut len: usize = 0;
+    let mut multiplier: usize = 1;
+    for _ in 0..4 {
+        let mut byte: [u8; 1] = [0; 1];
+        stream
+            .read_exact(&mut byte)
+            .map_err(|e| format!("Failed to read from broker: {}", e))?;
+        len += (byte[0] & 0x7f) as usize * multiplier;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        multiplier *= 128;
+    }
+    let mut body: Vec<u8> = vec![0; len];
+    stream
+        .read_exact(&mut body)
+        .map_err(|e| format!("Failed to read from broker: {}", e))?;
+    Ok((header[0], body))
+}
+
+/// Publishes messages to an MQTT 3.1.1 broker. One connection per publish_all
+#[derive(Clone, Debug)]
+pub struct MqttPublisher {
+    pub url: MqttUrl,
+    pub client_id: String,
+    pub timeout: Duration,
+}
+
+impl MqttPublisher {
+    pub fn new(url: &str) -> Result<MqttPublisher, String> {
+        Ok(MqttPublisher {
+            url: MqttUrl::parse(url)?,
+            client_id: format!(
+                "{}-{}",
+                crate::app::CRATE_BIN_NAME,
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            timeout: Duration::from_millis(crate::config::DEFAULT_MQTT_TIMEOUT_MILLIS),
+        })
+    }
+    fn session<S: Read + Write>(
+        &self,
+        stream: &mut S,
+        messages: &[(String, String)],
+    ) -> Result<usize, String> {
+        let write = |stream: &mut S, data: &[u8]| {
+            stream
+                .write_all(data)
+                .and_then(|_| stream.flush())
+                .map_err(|e| format!("Failed to send to broker: {}", e))
+        };
+        write(
+            stream,
+            &connect_packet(
+                &self.client_id,
+                self.url.username.as_deref(),
+                self.url.password.as_deref(),
+            ),
+        )?;
+        match read_packet(stream)? {
+            (0x20, body) if body.len() == 2 => {
+                if body[1] != 0 {
+                    return Err(format!("Connection refused: {}", connack_error(body[1])));
+                }
+            }
+            _ => return Err("Unexpected response to CONNECT".to_string()),
+        }
+        let mut sent: usize = 0;
+        for (i, (topic, payload)) in messages.iter().enumerate() {
+            // Packet identifier must be non-zero
+            let packet_id: u16 = (i % u16::MAX as usize) as u16 + 1;
+            write(
+                stream,
+                &publish_packet(topic, packet_id, payload.as_bytes()),
+            )
+            .map_err(|e| format!("{} (after {} messages)", e, sent))?;
+            // Wait for PUBACK, so the broker has the message before we disconnect
+            loop {
+                let (header, body) =
+                    read_packet(stream).map_err(|e| format!("{} (after {} messages)", e, sent))?;
+                if header >> 4 == 4 && body.len() >= 2 && body[..2] == packet_id.to_be_bytes() {
+                    break;
+                }
+            }
+            sent += 1;
+        }
+        // DISCONNECT
+        let _ = write(stream, &[0xe0, 0x00]);
+        Ok(sent)
+    }
+    /// Publish (topic, payload) pairs in order with QoS 1. Returns the number of messages sent
+    pub fn publish_all(&self, messages: &[(String, String)]) -> Result<usize, String> {
+        let socket_addr: SocketAddr =
+            match (self.url.host.as_str(), self.url.port).to_socket_addrs() {
+                Ok(mut addrs) => match addrs.next() {
+                    Some(addr) => addr,
+                    None => return Err(format!("Failed to resolve {}", self.url.host)),
+                },
+                Err(e) => return Err(format!("Failed to resolve {}: {}", self.url.host, e)),
+            };
+        let stream: TcpStream = match TcpStream::connect_timeout(&socket_addr, self.timeout) {
+            Ok(stream) => stream,
+            Err(e) => return Err(format!("Failed to connect to {}: {}", socket_addr, e)),
+        };
+        let _ = stream.set_read_timeout(Some(self.timeout));
+        let _ = stream.set_write_timeout(Some(self.timeout));
+        if self.url.tls {
+            let native_certs = match nex::socket::tls::certs::get_native_certs() {
+                Ok(certs) => certs,
+                Err(e) => return Err(format!("Failed to load root certificates: {}", e)),
+            };
+            let config = rustls::ClientConfig::builder()
+                .with_root_certificates(native_certs)
+                .with_no_client_auth();
+            let mut tls_client = match TlsClient::new(self.url.host.clone(), stream, config) {
+                Ok(tls_client) => tls_client,
+                Err(e) => {
+                    return Err(format!(
+                        "TLS handshake with {} failed: {}",
+                        self.url.host, e
+                    ))
+                }
+            };
+            self.session(&mut tls_client, messages)
+        } else {
+            let mut stream = stream;
+            self.session(&mut stream, messages)
+        }
+    }
+}
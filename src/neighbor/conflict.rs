@@ -0,0 +1,88 @@
+use netdev::mac::MacAddr;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// Kind of address conflict
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AddressConflictKind {
+    /// One IP address answered from multiple MAC addresses
+    DuplicateIp,
+    /// One MAC address claimed multiple IPv4 addresses
+    SharedMac,
+}
+
+/// IP/MAC binding that is not one-to-one.
+/// A sign of duplicate addresses or ARP/NDP spoofing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AddressConflict {
+    pub kind: AddressConflictKind,
+    pub ip_addrs: Vec<IpAddr>,
+    pub mac_addrs: Vec<MacAddr>,
+}
+
+impl AddressConflict {
+    pub fn message(&self) -> String {
+        let ips: Vec<String> = self.ip_addrs.iter().map(|ip| ip.to_string()).collect();
+        let macs: Vec<String> = self.mac_addrs.iter().map(|mac| mac.address()).collect();
+        match self.kind {
+            AddressConflictKind::DuplicateIp => format!(
+                "Duplicate IP: {} is claimed by {} MAC addresses ({}). Possible address conflict or ARP spoofing",
+                ips.join(", "),
+                macs.len(),
+                macs.join(", ")
+            ),
+            AddressConflictKind::SharedMac => format!(
+                "Shared MAC: {} claims {} IP addresses ({}). Possible ARP spoofing, proxy ARP or multi-homed host",
+                macs.join(", "),
+                ips.len(),
+                ips.join(", ")
+            ),
+        }
+    }
+}
+
+/// Find IP addresses with multiple MAC addresses and MAC addresses with multiple IPv4 addresses.
+/// IPv6 is excluded from the latter since hosts usually have several IPv6 addresses
+pub fn detect_conflicts(bindings: &[(IpAddr, MacAddr)]) -> Vec<AddressConflict> {
+    let mut ip_map: BTreeMap<IpAddr, Vec<MacAddr>> = BTreeMap::new();
+    let mut mac_map: BTreeMap<String, (MacAddr, Vec<IpAddr>)> = BTreeMap::new();
+    for (ip_addr, mac_addr) in bindings {
+        if *mac_addr == MacAddr::zero() {
+            continue;
+        }
+        let macs: &mut Vec<MacAddr> = ip_map.entry(*ip_addr).or_default();
+        if !macs.contains(mac_addr) {
+            macs.push(*mac_addr);
+        }
+        if ip_addr.is_ipv4() {
+            let (_, ips) = mac_map
+                .entry(mac_addr.address())
+                .or_insert((*mac_addr, Vec::new()));
+            if !ips.contains(ip_addr) {
+                ips.push(*ip_addr);
+            }
+        }
+    }
+    let mut conflicts: Vec<AddressConflict> = Vec::new();
+    for (ip_addr, macs) in ip_map {
+        if macs.len() > 1 {
+            conflicts.push(AddressConflict {
+                kind: AddressConflictKind::DuplicateIp,
+                ip_addrs: vec![ip_addr],
+                mac_addrs: macs,
+            });
+        }
+    }
+    for (_, (mac_addr, mut ips)) in mac_map {
+        if ips.len() > 1 {
+            ips.sort();
+            conflicts.push(AddressConflict {
+                kind: AddressConflictKind::SharedMac,
+                ip_addrs: ips,
+                mac_addrs: vec![mac_addr],
+            });
+        }
+    }
+    conflicts
+}
@@ -1,3 +1,103 @@
+pub mod conflict;
+pub mod presence;
 pub mod resolver;
 pub mod result;
 pub mod setting;
+pub mod table;
+pub mod wol;
+
+use netdev::mac::MacAddr;
+use netdev::Interface;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+
+use crate::probe::ProbeStatusKind;
+use resolver::DeviceResolver;
+use setting::AddressResolveSetting;
+
+/// Parse MAC address in any of 00:11:22:33:44:55, 0:11:22:33:44:55, 00-11-22-33-44-55,
+/// 0011.2233.4455 or 001122334455
+pub fn parse_mac_addr(s: &str) -> Option<MacAddr> {
+    let s: &str = s.trim();
+    let octets: Vec<u8> = if s.contains(':') || s.contains('-') {
+        s.split(|c| c == ':' || c == '-')
+            .map(|part| {
+                if part.is_empty() || part.len() > 2 {
+                    return None;
+                }
+                u8::from_str_radix(part, 16).ok()
+            })
+            .collect::<Option<Vec<u8>>>()?
+    } else {
+        let hex: String = s.replace('.', "");
+        if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        (0..6)
+            .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?
+    };
+    if octets.len() != 6 {
+        return None;
+    }
+    Some(MacAddr::new(
+        octets[0], octets[1], octets[2], octets[3], octets[4], octets[5],
+    ))
+}
+
+/// Resolve MAC address of on-link neighbor by ARP (IPv4) or NDP (IPv6)
+pub fn resolve_mac_addr(interface: &Interface, ip_addr: IpAddr) -> Option<MacAddr> {
+    let setting: AddressResolveSetting = match ip_addr {
+        IpAddr::V4(ipv4_addr) => AddressResolveSetting::arp(interface, ipv4_addr, 1).ok()?,
+        IpAddr::V6(ipv6_addr) => AddressResolveSetting::ndp(interface, ipv6_addr, 1).ok()?,
+    };
+    let resolver: DeviceResolver = DeviceResolver::new(setting).ok()?;
+    let result = resolver.resolve().ok()?;
+    result
+        .results
+        .iter()
+        .find(|r| r.probe_status.kind == ProbeStatusKind::Done)
+        .map(|r| r.mac_addr)
+}
+
+/// Send gratuitous ARP for the local address so that neighbors update their caches.
+/// Returns the number of packets sent
+pub fn announce(
+    interface: &Interface,
+    ipv4_addr: Ipv4Addr,
+    count: u32,
+    interval: Duration,
+) -> Result<u32, String> {
+    let src_mac: MacAddr = match interface.mac_addr {
+        Some(mac_addr) => mac_addr,
+        None => return Err(format!("{} has no MAC address", interface.name)),
+    };
+    let config = nex::datalink::Config {
+        write_buffer_size: 4096,
+        read_buffer_size: 4096,
+        read_timeout: None,
+        write_timeout: None,
+        channel_type: nex::datalink::ChannelType::Layer2,
+        bpf_fd_attempts: 1000,
+        linux_fanout: None,
+        promiscuous: false,
+    };
+    let mut tx = match nex::datalink::channel(interface, config) {
+        Ok(nex::datalink::Channel::Ethernet(tx, _rx)) => tx,
+        Ok(_) => return Err("Unable to create channel".to_string()),
+        Err(e) => return Err(format!("Unable to create channel: {}", e)),
+    };
+    let packet: Vec<u8> = crate::packet::arp::build_gratuitous_arp_packet(src_mac, ipv4_addr);
+    let mut sent: u32 = 0;
+    for seq in 1..count + 1 {
+        match tx.send(&packet) {
+            Some(Ok(_)) => sent += 1,
+            Some(Err(e)) => return Err(format!("Failed to send: {}", e)),
+            None => return Err("Failed to send".to_string()),
+        }
+        if seq < count {
+            std::thread::sleep(interval);
+        }
+    }
+    Ok(sent)
+}
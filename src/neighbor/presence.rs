@@ -0,0 +1,146 @@
+use netdev::mac::MacAddr;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use super::result::Neighbor;
+
+/// Kind of presence change
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PresenceEventKind {
+    /// New device appeared
+    Join,
+    /// Device did not answer for the configured number of sweeps
+    Leave,
+    /// Address answered from a different MAC address
+    MacChange,
+}
+
+impl PresenceEventKind {
+    /// Log level of the event
+    pub fn level(&self) -> &str {
+        match self {
+            PresenceEventKind::Join => "JOIN",
+            PresenceEventKind::Leave => "LEAVE",
+            PresenceEventKind::MacChange => "CHANGE",
+        }
+    }
+}
+
+/// Presence change of a neighbor
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresenceEvent {
+    /// Time in RFC 3339 and ISO 8601 date and time string
+    pub time: String,
+    pub kind: PresenceEventKind,
+    pub ip_addr: IpAddr,
+    pub mac_addr: MacAddr,
+    pub vendor_name: String,
+    /// Previous MAC address on MacChange
+    pub previous_mac_addr: Option<MacAddr>,
+}
+
+impl PresenceEvent {
+    pub fn message(&self) -> String {
+        match &self.previous_mac_addr {
+            Some(previous) => format!(
+                "{} {} -> {} {}",
+                self.ip_addr,
+                previous.address(),
+                self.mac_addr.address(),
+                self.vendor_name
+            ),
+            None => format!(
+                "{} {} {}",
+                self.ip_addr,
+                self.mac_addr.address(),
+                self.vendor_name
+            ),
+        }
+    }
+}
+
+struct TrackedNeighbor {
+    neighbor: Neighbor,
+    /// Consecutive sweeps without answer
+    missed: u32,
+}
+
+/// Keeps neighbors seen by previous sweeps and reports changes
+pub struct PresenceTracker {
+    neighbors: HashMap<IpAddr, TrackedNeighbor>,
+    /// Sweeps without answer before a leave event
+    miss_limit: u32,
+}
+
+impl PresenceTracker {
+    pub fn new(miss_limit: u32) -> PresenceTracker {
+        PresenceTracker {
+            neighbors: HashMap::new(),
+            miss_limit: miss_limit.max(1),
+        }
+    }
+    /// Number of neighbors considered present
+    pub fn present_count(&self) -> usize {
+        self.neighbors.len()
+    }
+    /// Apply the result of a sweep. Returns events in address order
+    pub fn update(&mut self, neighbors: &[Neighbor]) -> Vec<PresenceEvent> {
+        let time: String = crate::sys::time::get_sysdate();
+        let mut events: Vec<PresenceEvent> = Vec::new();
+        let new_event =
+            |kind: PresenceEventKind, neighbor: &Neighbor, previous: Option<MacAddr>| {
+                PresenceEvent {
+                    time: time.clone(),
+                    kind: kind,
+                    ip_addr: neighbor.ip_addr,
+                    mac_addr: neighbor.mac_addr,
+                    vendor_name: neighbor.vendor_name.clone(),
+                    previous_mac_addr: previous,
+                }
+            };
+        let mut answered: Vec<IpAddr> = Vec::new();
+        for neighbor in neighbors {
+            answered.push(neighbor.ip_addr);
+            match self.neighbors.get_mut(&neighbor.ip_addr) {
+                Some(tracked) => {
+                    tracked.missed = 0;
+                    if tracked.neighbor.mac_addr != neighbor.mac_addr {
+                        events.push(new_event(
+                            PresenceEventKind::MacChange,
+                            neighbor,
+                            Some(tracked.neighbor.mac_addr),
+                        ));
+                        tracked.neighbor = neighbor.clone();
+                    }
+                }
+                None => {
+                    events.push(new_event(PresenceEventKind::Join, neighbor, None));
+                    self.neighbors.insert(
+                        neighbor.ip_addr,
+                        TrackedNeighbor {
+                            neighbor: neighbor.clone(),
+                            missed: 0,
+                        },
+                    );
+                }
+            }
+        }
+        let mut left: Vec<IpAddr> = Vec::new();
+        for (ip_addr, tracked) in self.neighbors.iter_mut() {
+            if answered.contains(ip_addr) {
+                continue;
+            }
+            tracked.missed += 1;
+            if tracked.missed >= self.miss_limit {
+                events.push(new_event(PresenceEventKind::Leave, &tracked.neighbor, None));
+                left.push(*ip_addr);
+            }
+        }
+        for ip_addr in left {
+            self.neighbors.remove(&ip_addr);
+        }
+        events.sort_by_key(|event| event.ip_addr);
+        events
+    }
+}
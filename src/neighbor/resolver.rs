@@ -158,6 +158,8 @@ pub(crate) fn run_arp(
                                         node_type: NodeType::Destination,
                                         sent_packet_size: arp_packet.len(),
                                         received_packet_size: packet.len(),
+                                        mpls_labels: Vec::new(),
+                                        ip_options: None,
                                     };
                                     responses.push(probe_result.clone());
                                     match msg_tx.lock() {
@@ -292,6 +294,8 @@ pub(crate) fn run_ndp(
                                                 node_type: NodeType::Destination,
                                                 sent_packet_size: ndp_packet.len(),
                                                 received_packet_size: packet.len(),
+                                                mpls_labels: Vec::new(),
+                                                ip_options: None,
                                             };
                                             responses.push(probe_result.clone());
                                             match msg_tx.lock() {
@@ -1,8 +1,11 @@
+use super::conflict::AddressConflict;
 use crate::{
     probe::{ProbeResult, ProbeStatus},
     protocol::Protocol,
 };
+use netdev::mac::MacAddr;
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use std::time::Duration;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -16,6 +19,9 @@ pub struct DeviceResolveResult {
     /// Elapsed time
     pub elapsed_time: Duration,
     pub protocol: Protocol,
+    /// Set when replies came from more than one MAC address
+    #[serde(default)]
+    pub conflicts: Vec<AddressConflict>,
 }
 
 impl DeviceResolveResult {
@@ -27,6 +33,33 @@ impl DeviceResolveResult {
             end_time: String::new(),
             elapsed_time: Duration::from_millis(0),
             protocol: Protocol::ARP,
+            conflicts: Vec::new(),
         }
     }
 }
+
+/// Neighbor found by a subnet sweep
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Neighbor {
+    pub ip_addr: IpAddr,
+    pub mac_addr: MacAddr,
+    pub vendor_name: String,
+    /// ARP or NDP
+    pub protocol: Protocol,
+}
+
+/// Result of a subnet-wide ARP/NDP sweep
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NeighborSweepResult {
+    /// Target as specified (e.g. 192.168.1.0/24)
+    pub target: String,
+    pub interface: String,
+    /// Number of addresses probed
+    pub total: usize,
+    /// Neighbors that answered, in address order
+    pub neighbors: Vec<Neighbor>,
+    /// Duplicate IP or shared MAC addresses seen in the replies
+    #[serde(default)]
+    pub conflicts: Vec<AddressConflict>,
+    pub elapsed_time: Duration,
+}
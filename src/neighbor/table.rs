@@ -0,0 +1,258 @@
+use netdev::mac::MacAddr;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::process::Command;
+
+/// Entry of the OS neighbor cache (ARP/NDP table)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NeighborEntry {
+    pub ip_addr: IpAddr,
+    pub mac_addr: MacAddr,
+    pub interface: String,
+    /// State as reported by the OS (e.g. reachable, stale, permanent)
+    pub state: String,
+    pub vendor_name: String,
+}
+
+/// Parse MAC address of an entry. Zero (unresolved) is None
+fn parse_mac(s: &str) -> Option<MacAddr> {
+    let mac_addr: MacAddr = super::parse_mac_addr(s)?;
+    if mac_addr == MacAddr::zero() {
+        return None;
+    }
+    Some(mac_addr)
+}
+
+/// Parse IP address with optional zone ID (e.g. fe80::1%en0)
+fn parse_ip(s: &str) -> Option<IpAddr> {
+    s.split('%').next()?.parse::<IpAddr>().ok()
+}
+
+fn run_command(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn new_entry(ip_addr: IpAddr, mac_addr: MacAddr, interface: &str, state: &str) -> NeighborEntry {
+    NeighborEntry {
+        ip_addr: ip_addr,
+        mac_addr: mac_addr,
+        interface: interface.to_string(),
+        state: state.to_lowercase(),
+        vendor_name: String::new(),
+    }
+}
+
+/// /proc/net/arp
+/// IP address  HW type  Flags  HW address  Mask  Device
+#[cfg(target_os = "linux")]
+fn read_arp_table() -> Result<Vec<NeighborEntry>, String> {
+    let text: String = std::fs::read_to_string("/proc/net/arp")
+        .map_err(|e| format!("Failed to read /proc/net/arp: {}", e))?;
+    let mut entries: Vec<NeighborEntry> = Vec::new();
+    for line in text.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            continue;
+        }
+        let (ip_addr, mac_addr) = match (parse_ip(fields[0]), parse_mac(fields[3])) {
+            (Some(ip_addr), Some(mac_addr)) => (ip_addr, mac_addr),
+            _ => continue,
+        };
+        // ATF_PERM
+        let flags: u32 = u32::from_str_radix(fields[2].trim_start_matches("0x"), 16).unwrap_or(0);
+        let state: &str = if flags & 0x04 != 0 {
+            "permanent"
+        } else {
+            "dynamic"
+        };
+        entries.push(new_entry(ip_addr, mac_addr, fields[5], state));
+    }
+    Ok(entries)
+}
+
+/// ip -6 neigh show
+/// fe80::1 dev eth0 lladdr 00:11:22:33:44:55 router REACHABLE
+#[cfg(target_os = "linux")]
+fn read_ndp_table() -> Result<Vec<NeighborEntry>, String> {
+    let text: String = run_command("ip", &["-6", "neigh", "show"])?;
+    let mut entries: Vec<NeighborEntry> = Vec::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let ip_addr: IpAddr = match fields.first().and_then(|s| parse_ip(s)) {
+            Some(ip_addr) => ip_addr,
+            None => continue,
+        };
+        let value_of = |key: &str| -> Option<&str> {
+            fields
+                .iter()
+                .position(|field| *field == key)
+                .and_then(|i| fields.get(i + 1).copied())
+        };
+        let mac_addr: MacAddr = match value_of("lladdr").and_then(parse_mac) {
+            Some(mac_addr) => mac_addr,
+            None => continue,
+        };
+        let interface: &str = value_of("dev").unwrap_or("");
+        let state: &str = fields.last().copied().unwrap_or("");
+        entries.push(new_entry(ip_addr, mac_addr, interface, state));
+    }
+    Ok(entries)
+}
+
+/// arp -an
+/// ? (192.168.1.1) at 0:11:22:33:44:55 on en0 ifscope [ethernet]
+#[cfg(target_os = "macos")]
+fn read_arp_table() -> Result<Vec<NeighborEntry>, String> {
+    let text: String = run_command("arp", &["-an"])?;
+    let mut entries: Vec<NeighborEntry> = Vec::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 || fields[2] != "at" || fields[4] != "on" {
+            continue;
+        }
+        let ip_addr: IpAddr = match parse_ip(fields[1].trim_matches(|c| c == '(' || c == ')')) {
+            Some(ip_addr) => ip_addr,
+            None => continue,
+        };
+        let mac_addr: MacAddr = match parse_mac(fields[3]) {
+            Some(mac_addr) => mac_addr,
+            None => continue,
+        };
+        let state: &str = if fields.contains(&"permanent") {
+            "permanent"
+        } else {
+            "dynamic"
+        };
+        entries.push(new_entry(ip_addr, mac_addr, fields[5], state));
+    }
+    Ok(entries)
+}
+
+/// ndp -an
+/// Neighbor  Linklayer Address  Netif  Expire  St  Flgs  Prbs
+#[cfg(target_os = "macos")]
+fn read_ndp_table() -> Result<Vec<NeighborEntry>, String> {
+    let text: String = run_command("ndp", &["-an"])?;
+    let mut entries: Vec<NeighborEntry> = Vec::new();
+    for line in text.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 5 {
+            continue;
+        }
+        let (ip_addr, mac_addr) = match (parse_ip(fields[0]), parse_mac(fields[1])) {
+            (Some(ip_addr), Some(mac_addr)) => (ip_addr, mac_addr),
+            _ => continue,
+        };
+        let state: &str = match fields[4] {
+            "R" => "reachable",
+            "S" => "stale",
+            "D" => "delay",
+            "P" => "probe",
+            "I" => "incomplete",
+            "N" => "nostate",
+            "W" => "waitdelete",
+            _ => fields[4],
+        };
+        entries.push(new_entry(ip_addr, mac_addr, fields[2], state));
+    }
+    Ok(entries)
+}
+
+/// Interface name from the index in arp/netsh output
+#[cfg(target_os = "windows")]
+fn interface_name(if_index: u32) -> String {
+    match crate::interface::get_interface_by_index(if_index) {
+        Some(iface) => iface.friendly_name.unwrap_or(iface.name),
+        None => if_index.to_string(),
+    }
+}
+
+/// arp -a
+/// Interface: 192.168.1.10 --- 0xb
+///   Internet Address      Physical Address      Type
+///   192.168.1.1           00-11-22-33-44-55     dynamic
+#[cfg(target_os = "windows")]
+fn read_arp_table() -> Result<Vec<NeighborEntry>, String> {
+    let text: String = run_command("arp", &["-a"])?;
+    let mut entries: Vec<NeighborEntry> = Vec::new();
+    let mut interface: String = String::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() >= 4 && fields[2] == "---" {
+            interface = u32::from_str_radix(fields[3].trim_start_matches("0x"), 16)
+                .map(interface_name)
+                .unwrap_or_default();
+            continue;
+        }
+        if fields.len() < 3 {
+            continue;
+        }
+        let (ip_addr, mac_addr) = match (parse_ip(fields[0]), parse_mac(fields[1])) {
+            (Some(ip_addr), Some(mac_addr)) => (ip_addr, mac_addr),
+            _ => continue,
+        };
+        // Broadcast and multicast entries are static mappings
+        if mac_addr.octets()[0] & 0x01 != 0 {
+            continue;
+        }
+        entries.push(new_entry(ip_addr, mac_addr, &interface, fields[2]));
+    }
+    Ok(entries)
+}
+
+/// netsh interface ipv6 show neighbors
+/// Interface 11: Ethernet
+/// fe80::1          00-11-22-33-44-55     Reachable (Router)
+#[cfg(target_os = "windows")]
+fn read_ndp_table() -> Result<Vec<NeighborEntry>, String> {
+    let text: String = run_command("netsh", &["interface", "ipv6", "show", "neighbors"])?;
+    let mut entries: Vec<NeighborEntry> = Vec::new();
+    let mut interface: String = String::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() >= 2 && fields[0] == "Interface" && fields[1].ends_with(':') {
+            interface = fields[1]
+                .trim_end_matches(':')
+                .parse::<u32>()
+                .map(interface_name)
+                .unwrap_or_default();
+            continue;
+        }
+        if fields.len() < 3 {
+            continue;
+        }
+        let (ip_addr, mac_addr) = match (parse_ip(fields[0]), parse_mac(fields[1])) {
+            (Some(ip_addr), Some(mac_addr)) => (ip_addr, mac_addr),
+            _ => continue,
+        };
+        if mac_addr.octets()[0] & 0x01 != 0 {
+            continue;
+        }
+        entries.push(new_entry(ip_addr, mac_addr, &interface, fields[2]));
+    }
+    Ok(entries)
+}
+
+/// Read the ARP and NDP cache of the OS.
+/// Entries without a resolved MAC address (incomplete, failed) are omitted
+pub fn get_neighbor_table() -> Result<Vec<NeighborEntry>, String> {
+    let mut entries: Vec<NeighborEntry> = read_arp_table()?;
+    // IPv6 may be disabled or the tool missing. ARP entries are still shown
+    if let Ok(ndp_entries) = read_ndp_table() {
+        entries.extend(ndp_entries);
+    }
+    let oui_map = crate::db::get_oui_detail_map();
+    for entry in entries.iter_mut() {
+        entry.vendor_name = crate::db::get_vendor_name(&oui_map, &entry.mac_addr);
+    }
+    entries
+        .sort_by(|a, b| (a.interface.as_str(), a.ip_addr).cmp(&(b.interface.as_str(), b.ip_addr)));
+    Ok(entries)
+}
@@ -0,0 +1,66 @@
+use netdev::mac::MacAddr;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::Duration;
+
+/// Default port of Wake-on-LAN magic packet (discard)
+pub const DEFAULT_WOL_PORT: u16 = 9;
+
+/// Result of sending magic packets
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WolResult {
+    pub mac_addr: MacAddr,
+    pub dst_addr: SocketAddr,
+    /// Number of magic packets sent
+    pub sent: u32,
+    /// Host used for the follow-up ping. None if not verified
+    pub verify_ip: Option<IpAddr>,
+    /// True if the host answered the follow-up ping
+    pub up: bool,
+    /// Time from the first magic packet until the host answered
+    pub wake_time: Option<Duration>,
+}
+
+/// 6 bytes of 0xFF followed by 16 repetitions of the MAC address
+pub fn build_magic_packet(mac_addr: &MacAddr) -> Vec<u8> {
+    let mut packet: Vec<u8> = Vec::with_capacity(102);
+    packet.extend_from_slice(&[0xff; 6]);
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac_addr.octets());
+    }
+    packet
+}
+
+/// Send magic packets over UDP to the (broadcast) address.
+/// Returns the number of packets sent
+pub fn send_magic_packet(
+    mac_addr: &MacAddr,
+    dst_addr: SocketAddr,
+    src_ip: Option<Ipv4Addr>,
+    count: u32,
+    interval: Duration,
+) -> Result<u32, String> {
+    let bind_addr: SocketAddr = match dst_addr {
+        SocketAddr::V4(_) => {
+            SocketAddr::new(IpAddr::V4(src_ip.unwrap_or(Ipv4Addr::UNSPECIFIED)), 0)
+        }
+        SocketAddr::V6(_) => return Err("Magic packet requires an IPv4 address".to_string()),
+    };
+    let socket: UdpSocket =
+        UdpSocket::bind(bind_addr).map_err(|e| format!("Failed to bind socket: {}", e))?;
+    socket
+        .set_broadcast(true)
+        .map_err(|e| format!("Failed to enable broadcast: {}", e))?;
+    let packet: Vec<u8> = build_magic_packet(mac_addr);
+    let mut sent: u32 = 0;
+    for seq in 1..count + 1 {
+        socket
+            .send_to(&packet, dst_addr)
+            .map_err(|e| format!("Failed to send: {}", e))?;
+        sent += 1;
+        if seq < count {
+            std::thread::sleep(interval);
+        }
+    }
+    Ok(sent)
+}
@@ -0,0 +1,181 @@
+pub mod xml;
+
+use crate::host::{Host, Port, PortStatus};
+use std::net::{IpAddr, Ipv4Addr};
+use xml::XmlToken;
+
+/// Hosts and scan information of an nmap XML file (-oX)
+#[derive(Clone, Debug)]
+pub struct NmapRun {
+    /// Command line of the nmap scan
+    pub args: String,
+    /// Unix time the scan started
+    pub start_time: Option<i64>,
+    /// Unix time the scan finished
+    pub end_time: Option<i64>,
+    /// Seconds taken by the scan
+    pub elapsed: Option<f64>,
+    /// Hosts that were up. Only TCP ports are kept
+    pub hosts: Vec<Host>,
+}
+
+/// Convert a CPE 2.2 URI (cpe:/a:openbsd:openssh:8.9p1) to CPE 2.3 formatted string
+pub fn cpe22_to_23(cpe: &str) -> Option<String> {
+    let uri: &str = cpe.strip_prefix("cpe:/")?;
+    let mut parts: Vec<&str> = uri.split(':').collect();
+    if parts.is_empty() || parts.len() > 11 {
+        return None;
+    }
+    parts.resize(11, "*");
+    let parts: Vec<&str> = parts
+        .into_iter()
+        .map(|p| if p.is_empty() { "*" } else { p })
+        .collect();
+    Some(format!("cpe:2.3:{}", parts.join(":")))
+}
+
+fn port_status(state: &str) -> PortStatus {
+    match state {
+        "open" => PortStatus::Open,
+        "closed" => PortStatus::Closed,
+        "filtered" | "open|filtered" | "closed|filtered" => PortStatus::Filtered,
+        _ => PortStatus::Unknown,
+    }
+}
+
+/// Host being read. The address is set from the <address> element
+struct HostState {
+    host: Host,
+    ip_set: bool,
+    up: bool,
+}
+
+/// Parse nmap XML output
+pub fn parse(text: &str) -> Result<NmapRun, String> {
+    let tokens: Vec<XmlToken> = xml::tokenize(text)?;
+    let mut run = NmapRun {
+        args: String::new(),
+        start_time: None,
+        end_time: None,
+        elapsed: None,
+        hosts: Vec::new(),
+    };
+    let mut found_nmaprun: bool = false;
+    let mut current: Option<HostState> = None;
+    let mut current_port: Option<(Port, String)> = None;
+    let mut in_port_cpe: bool = false;
+    for token in &tokens {
+        match token {
+            XmlToken::Start { name, attrs, .. } => {
+                let attr = |key: &str| attrs.get(key).map(|v| v.as_str()).unwrap_or("");
+                match name.as_str() {
+                    "nmaprun" => {
+                        found_nmaprun = true;
+                        run.args = attr("args").to_string();
+                        run.start_time = attr("start").parse().ok();
+                    }
+                    "finished" => {
+                        run.end_time = attr("time").parse().ok();
+                        run.elapsed = attr("elapsed").parse().ok();
+                    }
+                    "host" => {
+                        current = Some(HostState {
+                            host: Host::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), String::new()),
+                            ip_set: false,
+                            up: false,
+                        });
+                    }
+                    _ => {}
+                }
+                let state: &mut HostState = match current.as_mut() {
+                    Some(state) => state,
+                    None => continue,
+                };
+                match name.as_str() {
+                    "status" => state.up = attr("state") == "up",
+                    "address" => match attr("addrtype") {
+                        "ipv4" | "ipv6" => {
+                            if let Ok(ip_addr) = attr("addr").parse::<IpAddr>() {
+                                state.host.ip_addr = ip_addr;
+                                state.ip_set = true;
+                            }
+                        }
+                        "mac" => {
+                            if let Some(mac_addr) = crate::neighbor::parse_mac_addr(attr("addr")) {
+                                state.host.mac_addr = mac_addr;
+                            }
+                            state.host.vendor_name = attr("vendor").to_string();
+                        }
+                        _ => {}
+                    },
+                    "hostname" => {
+                        if state.host.hostname.is_empty() {
+                            state.host.hostname = attr("name").to_string();
+                        }
+                    }
+                    "port" => {
+                        if let Ok(number) = attr("portid").parse::<u16>() {
+                            current_port = Some((Port::new(number), attr("protocol").to_string()));
+                        }
+                    }
+                    "state" => {
+                        if let Some((port, _)) = current_port.as_mut() {
+                            port.status = port_status(attr("state"));
+                        }
+                    }
+                    "service" => {
+                        if let Some((port, _)) = current_port.as_mut() {
+                            port.service_name = attr("name").to_string();
+                            let version: Vec<&str> =
+                                [attr("product"), attr("version"), attr("extrainfo")]
+                                    .into_iter()
+                                    .filter(|s| !s.is_empty())
+                                    .collect();
+                            port.service_version = version.join(" ");
+                        }
+                    }
+                    "cpe" => in_port_cpe = current_port.is_some(),
+                    "osclass" => {
+                        if state.host.os_family.is_empty() {
+                            state.host.os_family = attr("osfamily").to_string();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            XmlToken::Text(text) => {
+                if in_port_cpe {
+                    if let Some((port, _)) = current_port.as_mut() {
+                        if let Some(cpe) = cpe22_to_23(text) {
+                            port.cpes.push(cpe);
+                        }
+                    }
+                }
+            }
+            XmlToken::End(name) => match name.as_str() {
+                "cpe" => in_port_cpe = false,
+                "port" => {
+                    if let (Some(state), Some((port, protocol))) =
+                        (current.as_mut(), current_port.take())
+                    {
+                        if protocol == "tcp" {
+                            state.host.ports.push(port);
+                        }
+                    }
+                }
+                "host" => {
+                    if let Some(state) = current.take() {
+                        if state.up && state.ip_set {
+                            run.hosts.push(state.host);
+                        }
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    if !found_nmaprun {
+        return Err("Not an nmap XML file (no <nmaprun> element)".to_string());
+    }
+    Ok(run)
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+/// Token of a minimal XML reader. Enough for nmap output: no namespaces, no CDATA
+#[derive(Clone, Debug, PartialEq)]
+pub enum XmlToken {
+    Start {
+        name: String,
+        attrs: HashMap<String, String>,
+        /// Self-closing tag. No End token follows
+        empty: bool,
+    },
+    End(String),
+    Text(String),
+}
+
+/// Decode predefined and numeric character references
+pub fn decode_entities(s: &str) -> String {
+    let mut out: String = String::with_capacity(s.len());
+    let mut rest: &str = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let end: usize = match rest.find(';') {
+            Some(end) if end <= 10 => end,
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let entity: &str = &rest[1..end];
+        let decoded: Option<char> = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn parse_attrs(s: &str) -> HashMap<String, String> {
+    let mut attrs: HashMap<String, String> = HashMap::new();
+    let mut rest: &str = s.trim();
+    while !rest.is_empty() {
+        let eq: usize = match rest.find('=') {
+            Some(eq) => eq,
+            None => break,
+        };
+        let name: &str = rest[..eq].trim();
+        rest = rest[eq + 1..].trim_start();
+        let quote: char = match rest.chars().next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => break,
+        };
+        let end: usize = match rest[1..].find(quote) {
+            Some(end) => end + 1,
+            None => break,
+        };
+        attrs.insert(name.to_string(), decode_entities(&rest[1..end]));
+        rest = rest[end + 1..].trim_start();
+    }
+    attrs
+}
+
+/// Index of the `>` closing the tag at the start of `s`. Quoted `>` is skipped
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(i),
+            None => {}
+        }
+    }
+    None
+}
+
+pub fn tokenize(text: &str) -> Result<Vec<XmlToken>, String> {
+    let mut tokens: Vec<XmlToken> = Vec::new();
+    let mut rest: &str = text;
+    while let Some(lt) = rest.find('<') {
+        let content: &str = rest[..lt].trim();
+        if !content.is_empty() {
+            tokens.push(XmlToken::Text(decode_entities(content)));
+        }
+        rest = &rest[lt..];
+        // Comment, declaration, DOCTYPE or processing instruction (xml-stylesheet)
+        if rest.starts_with("<!--") {
+            match rest.find("-->") {
+                Some(end) => rest = &rest[end + 3..],
+                None => return Err("Unterminated comment".to_string()),
+            }
+            continue;
+        }
+        let end: usize = match tag_end(rest) {
+            Some(end) => end,
+            None => return Err("Unterminated tag".to_string()),
+        };
+        let inner: &str = &rest[1..end];
+        rest = &rest[end + 1..];
+        if inner.starts_with('?') || inner.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = inner.strip_prefix('/') {
+            tokens.push(XmlToken::End(name.trim().to_string()));
+            continue;
+        }
+        let empty: bool = inner.ends_with('/');
+        let inner: &str = inner.trim_end_matches('/');
+        let (name, attrs) = match inner.find(char::is_whitespace) {
+            Some(i) => (&inner[..i], parse_attrs(&inner[i..])),
+            None => (inner, HashMap::new()),
+        };
+        tokens.push(XmlToken::Start {
+            name: name.to_string(),
+            attrs: attrs,
+            empty: empty,
+        });
+    }
+    Ok(tokens)
+}
@@ -0,0 +1,357 @@
+use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const NTP_PORT: u16 = 123;
+/// Seconds between 1900-01-01 and 1970-01-01
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+const NTP_PACKET_SIZE: usize = 48;
+/// LI 0, VN 4, mode 3 (client)
+const CLIENT_REQUEST: u8 = 0x23;
+/// LI 0, VN 2, mode 6 (control)
+const CONTROL_REQUEST: u8 = 0x16;
+const CONTROL_OP_READVAR: u8 = 0x02;
+const CONTROL_RESPONSE: u8 = 0x80;
+const CONTROL_ERROR: u8 = 0x40;
+const CONTROL_MORE: u8 = 0x20;
+const CONTROL_HEADER_SIZE: usize = 12;
+/// VN 2, mode 7 (private), implementation XNTPD, request MON_GETLIST_1
+const MONLIST_REQUEST: [u8; 4] = [0x17, 0x00, 0x03, 0x2a];
+
+/// Settings of NTP probing
+#[derive(Clone, Debug)]
+pub struct NtpSetting {
+    /// Wait time for each query
+    pub timeout: Duration,
+    pub concurrency: usize,
+    /// Send mode 7 monlist request
+    pub monlist: bool,
+}
+
+/// Reply of a mode 6 or mode 7 query that counts toward amplification
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NtpAmplification {
+    pub request_bytes: usize,
+    pub response_packets: usize,
+    pub response_bytes: usize,
+}
+
+impl NtpAmplification {
+    pub fn factor(&self) -> f64 {
+        self.response_bytes as f64 / self.request_bytes as f64
+    }
+}
+
+/// Server information of a host answering on 123/udp
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NtpServerInfo {
+    pub ip_addr: IpAddr,
+    pub version: u8,
+    pub stratum: u8,
+    /// Reference ID. Source name at stratum 1, upstream address otherwise
+    pub reference_id: String,
+    pub precision: i8,
+    pub root_delay: Duration,
+    pub root_dispersion: Duration,
+    pub reference_time: Option<DateTime<Utc>>,
+    /// Clock offset from the local clock in ms
+    pub offset_millis: f64,
+    /// System variables of mode 6 READVAR (version, system, processor...)
+    pub variables: BTreeMap<String, String>,
+    /// Mode 6 READVAR reply. None if not answered
+    pub readvar: Option<NtpAmplification>,
+    /// Mode 7 monlist reply. None if not answered
+    pub monlist: Option<NtpAmplification>,
+}
+
+impl NtpServerInfo {
+    /// Findings for amplification exposure
+    pub fn issues(&self) -> Vec<String> {
+        let mut issues: Vec<String> = Vec::new();
+        if let Some(monlist) = &self.monlist {
+            issues.push(format!(
+                "monlist is enabled ({} packets, {:.1}x amplification)",
+                monlist.response_packets,
+                monlist.factor()
+            ));
+        }
+        if let Some(readvar) = &self.readvar {
+            issues.push(format!(
+                "Mode 6 queries are answered ({:.1}x amplification)",
+                readvar.factor()
+            ));
+        }
+        issues
+    }
+}
+
+/// Result of NTP probing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NtpScanResult {
+    /// Responding servers in the order of the targets
+    pub servers: Vec<NtpServerInfo>,
+    pub scan_time: Duration,
+}
+
+fn now_ntp() -> f64 {
+    let since_epoch: Duration = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    since_epoch.as_secs_f64() + NTP_UNIX_OFFSET as f64
+}
+
+/// 64-bit timestamp as seconds since 1900
+fn read_timestamp(data: &[u8]) -> f64 {
+    let secs: u32 = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    let frac: u32 = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+    secs as f64 + frac as f64 / 4_294_967_296.0
+}
+
+/// 32-bit short format (16.16) as duration
+fn read_short(data: &[u8]) -> Duration {
+    let value: u32 = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    Duration::from_secs_f64(value as f64 / 65536.0)
+}
+
+fn open_socket(ip_addr: IpAddr, timeout: Duration) -> Option<UdpSocket> {
+    let bind_addr: SocketAddr = match ip_addr {
+        IpAddr::V4(_) => "0.0.0.0:0".parse().unwrap(),
+        IpAddr::V6(_) => "[::]:0".parse().unwrap(),
+    };
+    let socket: UdpSocket = UdpSocket::bind(bind_addr).ok()?;
+    socket.connect(SocketAddr::new(ip_addr, NTP_PORT)).ok()?;
+    let _ = socket.set_read_timeout(Some(timeout));
+    Some(socket)
+}
+
+/// Mode 3 client query
+fn query_time(ip_addr: IpAddr, timeout: Duration) -> Option<NtpServerInfo> {
+    let socket: UdpSocket = open_socket(ip_addr, timeout)?;
+    let mut request: [u8; NTP_PACKET_SIZE] = [0; NTP_PACKET_SIZE];
+    request[0] = CLIENT_REQUEST;
+    let t1: f64 = now_ntp();
+    let secs: u32 = t1 as u32;
+    let frac: u32 = ((t1 - secs as f64) * 4_294_967_296.0) as u32;
+    request[40..44].copy_from_slice(&secs.to_be_bytes());
+    request[44..48].copy_from_slice(&frac.to_be_bytes());
+    socket.send(&request).ok()?;
+    let mut buf: [u8; 1024] = [0; 1024];
+    let len: usize = socket.recv(&mut buf).ok()?;
+    let t4: f64 = now_ntp();
+    // Mode 4 (server)
+    if len < NTP_PACKET_SIZE || buf[0] & 0x07 != 4 {
+        return None;
+    }
+    let stratum: u8 = buf[1];
+    let reference_id: String = if stratum <= 1 {
+        String::from_utf8_lossy(&buf[12..16])
+            .trim_end_matches('\0')
+            .to_string()
+    } else {
+        match ip_addr {
+            IpAddr::V4(_) => Ipv4Addr::new(buf[12], buf[13], buf[14], buf[15]).to_string(),
+            // IPv6 references are the first 4 bytes of the MD5 of the address
+            IpAddr::V6(_) => format!(
+                "{:02x}{:02x}{:02x}{:02x}",
+                buf[12], buf[13], buf[14], buf[15]
+            ),
+        }
+    };
+    let reference_time: f64 = read_timestamp(&buf[16..24]);
+    let t2: f64 = read_timestamp(&buf[32..40]);
+    let t3: f64 = read_timestamp(&buf[40..48]);
+    Some(NtpServerInfo {
+        ip_addr: ip_addr,
+        version: (buf[0] >> 3) & 0x07,
+        stratum: stratum,
+        reference_id: reference_id,
+        precision: buf[3] as i8,
+        root_delay: read_short(&buf[4..8]),
+        root_dispersion: read_short(&buf[8..12]),
+        reference_time: if reference_time >= NTP_UNIX_OFFSET as f64 {
+            DateTime::<Utc>::from_timestamp((reference_time as u64 - NTP_UNIX_OFFSET) as i64, 0)
+        } else {
+            None
+        },
+        offset_millis: ((t2 - t1) + (t3 - t4)) / 2.0 * 1000.0,
+        variables: BTreeMap::new(),
+        readvar: None,
+        monlist: None,
+    })
+}
+
+/// Split name=value list. Quoted values may contain commas
+fn parse_variables(text: &str) -> BTreeMap<String, String> {
+    let mut variables: BTreeMap<String, String> = BTreeMap::new();
+    let mut items: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    let mut quoted: bool = false;
+    for c in text.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+    for item in items {
+        let item: &str = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+        match item.split_once('=') {
+            Some((name, value)) => {
+                variables.insert(name.trim().to_string(), value.trim().to_string())
+            }
+            None => variables.insert(item.to_string(), String::new()),
+        };
+    }
+    variables
+}
+
+/// Mode 6 READVAR of the system variables. Reassembles fragments by offset
+fn query_readvar(
+    ip_addr: IpAddr,
+    timeout: Duration,
+) -> Option<(BTreeMap<String, String>, NtpAmplification)> {
+    let socket: UdpSocket = open_socket(ip_addr, timeout)?;
+    let sequence: u16 = rand::random::<u16>();
+    let mut request: [u8; CONTROL_HEADER_SIZE] = [0; CONTROL_HEADER_SIZE];
+    request[0] = CONTROL_REQUEST;
+    request[1] = CONTROL_OP_READVAR;
+    request[2..4].copy_from_slice(&sequence.to_be_bytes());
+    socket.send(&request).ok()?;
+    let mut amplification = NtpAmplification {
+        request_bytes: request.len(),
+        response_packets: 0,
+        response_bytes: 0,
+    };
+    let mut fragments: Vec<(u16, Vec<u8>)> = Vec::new();
+    let mut last_seen: bool = false;
+    let mut buf: [u8; 2048] = [0; 2048];
+    while let Ok(len) = socket.recv(&mut buf) {
+        if len < CONTROL_HEADER_SIZE
+            || buf[0] & 0x07 != 6
+            || buf[1] & CONTROL_RESPONSE == 0
+            || u16::from_be_bytes([buf[2], buf[3]]) != sequence
+        {
+            continue;
+        }
+        amplification.response_packets += 1;
+        amplification.response_bytes += len;
+        if buf[1] & CONTROL_ERROR != 0 {
+            return None;
+        }
+        let offset: u16 = u16::from_be_bytes([buf[8], buf[9]]);
+        let count: usize = u16::from_be_bytes([buf[10], buf[11]]) as usize;
+        let data: &[u8] =
+            &buf[CONTROL_HEADER_SIZE..std::cmp::min(len, CONTROL_HEADER_SIZE + count)];
+        fragments.push((offset, data.to_vec()));
+        if buf[1] & CONTROL_MORE == 0 {
+            last_seen = true;
+        }
+        if last_seen {
+            // Done when the fragments are contiguous from 0
+            fragments.sort_by_key(|(offset, _)| *offset);
+            let mut end: usize = 0;
+            let contiguous: bool = fragments.iter().all(|(offset, data)| {
+                let ok: bool = *offset as usize == end;
+                end += data.len();
+                ok
+            });
+            if contiguous {
+                break;
+            }
+        }
+    }
+    if fragments.is_empty() {
+        return None;
+    }
+    let text: Vec<u8> = fragments.into_iter().flat_map(|(_, data)| data).collect();
+    Some((
+        parse_variables(&String::from_utf8_lossy(&text)),
+        amplification,
+    ))
+}
+
+/// Mode 7 monlist. Any reply means the command is enabled
+fn query_monlist(ip_addr: IpAddr, timeout: Duration) -> Option<NtpAmplification> {
+    let socket: UdpSocket = open_socket(ip_addr, timeout)?;
+    let mut request: [u8; NTP_PACKET_SIZE] = [0; NTP_PACKET_SIZE];
+    request[..MONLIST_REQUEST.len()].copy_from_slice(&MONLIST_REQUEST);
+    socket.send(&request).ok()?;
+    let mut amplification = NtpAmplification {
+        request_bytes: request.len(),
+        response_packets: 0,
+        response_bytes: 0,
+    };
+    let mut buf: [u8; 2048] = [0; 2048];
+    // Replies are spread over up to 100 packets. Read until the wait time passes
+    while let Ok(len) = socket.recv(&mut buf) {
+        if len < 8 || buf[0] & 0x07 != 7 || buf[3] != MONLIST_REQUEST[3] {
+            continue;
+        }
+        // Error code in the upper 4 bits (e.g. 4 no data, 1 incompatible implementation)
+        if buf[4] >> 4 != 0 {
+            break;
+        }
+        amplification.response_packets += 1;
+        amplification.response_bytes += len;
+    }
+    if amplification.response_packets == 0 {
+        None
+    } else {
+        Some(amplification)
+    }
+}
+
+/// Query time, system variables and monlist of the host
+pub fn probe(ip_addr: IpAddr, setting: &NtpSetting) -> Option<NtpServerInfo> {
+    let mut info: NtpServerInfo = query_time(ip_addr, setting.timeout)?;
+    if let Some((variables, amplification)) = query_readvar(ip_addr, setting.timeout) {
+        info.variables = variables;
+        info.readvar = Some(amplification);
+    }
+    if setting.monlist {
+        info.monlist = query_monlist(ip_addr, setting.timeout);
+    }
+    Some(info)
+}
+
+/// Probe the targets concurrently. Sends each target to ptx when done
+pub async fn probe_targets(
+    targets: Vec<IpAddr>,
+    setting: NtpSetting,
+    ptx: Arc<Mutex<Sender<IpAddr>>>,
+) -> NtpScanResult {
+    let start_time: Instant = Instant::now();
+    let setting: &NtpSetting = &setting;
+    let ptx: &Arc<Mutex<Sender<IpAddr>>> = &ptx;
+    let mut results: Vec<(usize, Option<NtpServerInfo>)> =
+        stream::iter(targets.into_iter().enumerate())
+            .map(|(i, ip_addr)| async move {
+                let probe_setting: NtpSetting = setting.clone();
+                // Sockets are blocking. Run each on the blocking pool
+                let info: Option<NtpServerInfo> =
+                    tokio::task::spawn_blocking(move || probe(ip_addr, &probe_setting))
+                        .await
+                        .unwrap_or(None);
+                if let Ok(lr) = ptx.lock() {
+                    let _ = lr.send(ip_addr);
+                }
+                (i, info)
+            })
+            .buffer_unordered(setting.concurrency)
+            .collect()
+            .await;
+    results.sort_by_key(|(i, _)| *i);
+    NtpScanResult {
+        servers: results.into_iter().filter_map(|(_, info)| info).collect(),
+        scan_time: start_time.elapsed(),
+    }
+}
@@ -1,4 +1,5 @@
 use indicatif::ProgressStyle;
+use std::io::IsTerminal;
 
 pub const SECTION_DIVIDER: &str = "────────────────────────────────────────";
 
@@ -17,6 +18,19 @@ pub fn log_with_time(message: &str, level: &str) {
     println!("[{}] [{}] {}", now, level, message);
 }
 
+/// Highlighted warning. Bold red on terminal
+pub fn log_alert(message: &str) {
+    if crate::app::is_quiet_mode() {
+        return;
+    }
+    let now: String = crate::sys::time::get_systime();
+    if std::io::stdout().is_terminal() {
+        println!("\x1b[1;31m[{}] [ALERT] {}\x1b[0m", now, message);
+    } else {
+        println!("[{}] [ALERT] {}", now, message);
+    }
+}
+
 pub fn log_with_datetime(message: &str, level: &str) {
     if crate::app::is_quiet_mode() {
         return;
@@ -4,7 +4,7 @@ use nex::packet::ethernet::EtherType;
 use nex::util::packet_builder::arp::ArpPacketBuilder;
 use nex::util::packet_builder::builder::PacketBuilder;
 use nex::util::packet_builder::ethernet::EthernetPacketBuilder;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr};
 
 /// Build ARP packet
 pub fn build_arp_packet(setting: PacketBuildSetting) -> Vec<u8> {
@@ -36,3 +36,23 @@ pub fn build_arp_packet(setting: PacketBuildSetting) -> Vec<u8> {
     }
     packet_builder.packet()
 }
+
+/// Build gratuitous ARP request announcing the address of the sender.
+/// Sender and target IP are both the announced address
+pub fn build_gratuitous_arp_packet(src_mac: MacAddr, ipv4_addr: Ipv4Addr) -> Vec<u8> {
+    let mut packet_builder = PacketBuilder::new();
+    let ethernet_packet_builder = EthernetPacketBuilder {
+        src_mac: src_mac,
+        dst_mac: MacAddr::broadcast(),
+        ether_type: EtherType::Arp,
+    };
+    packet_builder.set_ethernet(ethernet_packet_builder);
+    let arp_packet = ArpPacketBuilder {
+        src_mac: src_mac,
+        dst_mac: MacAddr::zero(),
+        src_ip: ipv4_addr,
+        dst_ip: ipv4_addr,
+    };
+    packet_builder.set_arp(arp_packet);
+    packet_builder.packet()
+}
@@ -1,18 +1,91 @@
 use nex::packet::ethernet::EtherType;
-use nex::packet::icmp::IcmpType;
-use nex::packet::icmpv6::Icmpv6Type;
+use nex::packet::icmp::{IcmpPacket, IcmpType};
+use nex::packet::icmpv6::{Icmpv6Packet, Icmpv6Type};
 use nex::packet::ip::IpNextLevelProtocol;
+use nex::packet::ipv4::Ipv4Flags;
 use nex::util::packet_builder::builder::PacketBuilder;
 use nex::util::packet_builder::ethernet::EthernetPacketBuilder;
 use nex::util::packet_builder::icmp::IcmpPacketBuilder;
 use nex::util::packet_builder::icmpv6::Icmpv6PacketBuilder;
 use nex::util::packet_builder::ipv4::Ipv4PacketBuilder;
 use nex::util::packet_builder::ipv6::Ipv6PacketBuilder;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv6Addr};
 
 use crate::fp::setting::FingerprintType;
+use crate::packet::ip;
+use crate::packet::ipv4;
 use crate::packet::setting::PacketBuildSetting;
 
+const ETHERNET_HEADER_LEN: usize = 14;
+const IPV4_HEADER_LEN: usize = 20;
+const ICMP_HEADER_LEN: usize = 8;
+const ICMPV6_HEADER_LEN: usize = 8;
+const IPV6_HEADER_LEN: usize = 40;
+
+/// Maximum echo payload size that fits in the IP packet, including IPv4 options if any
+pub fn max_echo_payload_len(dst_ip: &IpAddr, ipv4_option: Option<ipv4::Ipv4OptionType>) -> usize {
+    match dst_ip {
+        IpAddr::V4(_) => {
+            let option_len: usize = match ipv4_option {
+                Some(option_type) => ipv4::build_ipv4_option(option_type).len(),
+                None => 0,
+            };
+            u16::MAX as usize - IPV4_HEADER_LEN - option_len - ICMP_HEADER_LEN
+        }
+        IpAddr::V6(_) => u16::MAX as usize - ICMPV6_HEADER_LEN,
+    }
+}
+
+/// Offset of ICMP header in the IP packet starting at ip_offset
+fn icmp_offset(packet: &[u8], ip_offset: usize) -> Option<usize> {
+    match packet.get(ip_offset)? >> 4 {
+        4 => Some(ip_offset + ((packet[ip_offset] & 0x0f) as usize) * 4),
+        6 => Some(ip_offset + IPV6_HEADER_LEN),
+        _ => None,
+    }
+}
+
+/// Set identifier and sequence number of the ICMP echo request and update the checksum
+pub fn set_echo_id_seq(packet: &mut [u8], ip_offset: usize, id: u16, seq: u16) {
+    let offset: usize = match icmp_offset(packet, ip_offset) {
+        Some(offset) if packet.len() >= offset + ICMP_HEADER_LEN => offset,
+        _ => return,
+    };
+    packet[offset + 4..offset + 6].copy_from_slice(&id.to_be_bytes());
+    packet[offset + 6..offset + 8].copy_from_slice(&seq.to_be_bytes());
+    packet[offset + 2..offset + 4].copy_from_slice(&[0, 0]);
+    let checksum: u16 = if packet[ip_offset] >> 4 == 4 {
+        match IcmpPacket::new(&packet[offset..]) {
+            Some(icmp_packet) => nex::packet::icmp::checksum(&icmp_packet),
+            None => return,
+        }
+    } else {
+        let mut src: [u8; 16] = [0; 16];
+        let mut dst: [u8; 16] = [0; 16];
+        src.copy_from_slice(&packet[ip_offset + 8..ip_offset + 24]);
+        dst.copy_from_slice(&packet[ip_offset + 24..ip_offset + 40]);
+        match Icmpv6Packet::new(&packet[offset..]) {
+            Some(icmpv6_packet) => nex::packet::icmpv6::checksum(
+                &icmpv6_packet,
+                &Ipv6Addr::from(src),
+                &Ipv6Addr::from(dst),
+            ),
+            None => return,
+        }
+    };
+    packet[offset + 2..offset + 4].copy_from_slice(&checksum.to_be_bytes());
+}
+
+/// Get identifier and sequence number of the ICMP echo message in the packet
+pub fn get_echo_id_seq(packet: &[u8], ip_offset: usize) -> Option<(u16, u16)> {
+    let offset: usize = icmp_offset(packet, ip_offset)?;
+    let header: &[u8] = packet.get(offset..offset + ICMP_HEADER_LEN)?;
+    Some((
+        u16::from_be_bytes([header[4], header[5]]),
+        u16::from_be_bytes([header[6], header[7]]),
+    ))
+}
+
 /// Build ICMP packet. Supports both ICMPv4 and ICMPv6
 pub fn build_icmp_packet(setting: PacketBuildSetting) -> Vec<u8> {
     let mut packet_builder = PacketBuilder::new();
@@ -35,6 +108,14 @@ pub fn build_icmp_packet(setting: PacketBuildSetting) -> Vec<u8> {
                 let mut ipv4_packet_builder =
                     Ipv4PacketBuilder::new(src_ipv4, dst_ipv4, IpNextLevelProtocol::Icmp);
                 ipv4_packet_builder.ttl = Some(setting.hop_limit);
+                ipv4_packet_builder.identification = setting.ip_id;
+                if setting.dont_fragment {
+                    ipv4_packet_builder.flags = Some(Ipv4Flags::DontFragment);
+                }
+                if setting.payload.len() > 0 {
+                    ipv4_packet_builder.total_length =
+                        Some((IPV4_HEADER_LEN + ICMP_HEADER_LEN + setting.payload.len()) as u16);
+                }
                 packet_builder.set_ipv4(ipv4_packet_builder);
             }
             IpAddr::V6(_) => {}
@@ -45,6 +126,10 @@ pub fn build_icmp_packet(setting: PacketBuildSetting) -> Vec<u8> {
                 let mut ipv6_packet_builder =
                     Ipv6PacketBuilder::new(src_ipv4, dst_ipv6, IpNextLevelProtocol::Icmpv6);
                 ipv6_packet_builder.hop_limit = Some(setting.hop_limit);
+                if setting.payload.len() > 0 {
+                    ipv6_packet_builder.payload_length =
+                        Some((ICMPV6_HEADER_LEN + setting.payload.len()) as u16);
+                }
                 packet_builder.set_ipv6(ipv6_packet_builder);
             }
         },
@@ -55,6 +140,7 @@ pub fn build_icmp_packet(setting: PacketBuildSetting) -> Vec<u8> {
             IpAddr::V4(src_ipv4) => {
                 let mut icmp_packet_builder = IcmpPacketBuilder::new(src_ipv4, dst_ipv4);
                 icmp_packet_builder.icmp_type = IcmpType::EchoRequest;
+                icmp_packet_builder.payload = setting.payload.clone();
                 packet_builder.set_icmp(icmp_packet_builder);
             }
             IpAddr::V6(_) => {}
@@ -64,15 +150,41 @@ pub fn build_icmp_packet(setting: PacketBuildSetting) -> Vec<u8> {
             IpAddr::V6(src_ipv6) => {
                 let mut icmpv6_packet_builder = Icmpv6PacketBuilder::new(src_ipv6, dst_ipv6);
                 icmpv6_packet_builder.icmpv6_type = Icmpv6Type::EchoRequest;
+                icmpv6_packet_builder.payload = setting.payload.clone();
                 packet_builder.set_icmpv6(icmpv6_packet_builder);
             }
         },
     }
-    if setting.ip_packet {
+    let mut packet = if setting.ip_packet {
         packet_builder.ip_packet()
     } else {
         packet_builder.packet()
+    };
+    if let (Some(option_type), IpAddr::V4(_)) = (setting.ipv4_option, setting.dst_ip) {
+        let ip_offset = if setting.ip_packet {
+            0
+        } else {
+            ETHERNET_HEADER_LEN
+        };
+        if ipv4::insert_ipv4_option(
+            &mut packet,
+            ip_offset,
+            &ipv4::build_ipv4_option(option_type),
+        )
+        .is_err()
+        {
+            return Vec::new();
+        }
+    }
+    if setting.tos != 0 {
+        let ip_offset = if setting.ip_packet {
+            0
+        } else {
+            ETHERNET_HEADER_LEN
+        };
+        ip::set_traffic_class(&mut packet, ip_offset, setting.tos);
     }
+    packet
 }
 
 /// Build ICMP probe packet. Supports both ICMPv4 and ICMPv6
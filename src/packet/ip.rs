@@ -0,0 +1,39 @@
+use crate::packet::ipv4;
+
+const IPV4_HEADER_LEN: usize = 20;
+const IPV6_HEADER_LEN: usize = 40;
+
+/// Set TOS (IPv4) or Traffic Class (IPv6) of the IP header of the packet.
+///
+/// `ip_offset` is the offset of the IP header (e.g. 14 for Ethernet frame).
+/// The IPv4 header checksum is recalculated.
+pub fn set_traffic_class(packet: &mut [u8], ip_offset: usize, tos: u8) {
+    if packet.len() <= ip_offset {
+        return;
+    }
+    match packet[ip_offset] >> 4 {
+        4 => {
+            if packet.len() < ip_offset + IPV4_HEADER_LEN {
+                return;
+            }
+            let header_len: usize = ((packet[ip_offset] & 0x0f) as usize) * 4;
+            if header_len < IPV4_HEADER_LEN || packet.len() < ip_offset + header_len {
+                return;
+            }
+            packet[ip_offset + 1] = tos;
+            packet[ip_offset + 10] = 0;
+            packet[ip_offset + 11] = 0;
+            let checksum: u16 = ipv4::ipv4_checksum(&packet[ip_offset..ip_offset + header_len]);
+            packet[ip_offset + 10..ip_offset + 12].copy_from_slice(&checksum.to_be_bytes());
+        }
+        6 => {
+            if packet.len() < ip_offset + IPV6_HEADER_LEN {
+                return;
+            }
+            // Traffic Class spans the low 4 bits of byte 0 and the high 4 bits of byte 1
+            packet[ip_offset] = (packet[ip_offset] & 0xf0) | (tos >> 4);
+            packet[ip_offset + 1] = (packet[ip_offset + 1] & 0x0f) | (tos << 4);
+        }
+        _ => {}
+    }
+}
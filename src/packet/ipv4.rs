@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+
+pub const IPV4_HEADER_LEN: usize = 20;
+/// Maximum length of IPv4 options
+pub const IPV4_MAX_OPTION_LEN: usize = 40;
+
+const IPOPT_EOL: u8 = 0;
+const IPOPT_NOP: u8 = 1;
+const IPOPT_RR: u8 = 7;
+const IPOPT_TS: u8 = 68;
+/// Timestamp option flag: timestamps only
+const IPOPT_TS_TSONLY: u8 = 0;
+
+/// IPv4 option added to probe packet
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Ipv4OptionType {
+    RecordRoute,
+    Timestamp,
+}
+
+impl Ipv4OptionType {
+    pub fn to_str(&self) -> &str {
+        match self {
+            Ipv4OptionType::RecordRoute => "Record Route",
+            Ipv4OptionType::Timestamp => "Timestamp",
+        }
+    }
+}
+
+/// Decoded IPv4 options of received packet
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Ipv4OptionData {
+    /// Recorded route
+    pub route: Vec<Ipv4Addr>,
+    /// Timestamps in milliseconds since midnight UT
+    pub timestamps: Vec<u32>,
+}
+
+/// Build IPv4 option bytes. Uses the maximum option space
+pub fn build_ipv4_option(option_type: Ipv4OptionType) -> Vec<u8> {
+    let mut option: Vec<u8> = Vec::with_capacity(IPV4_MAX_OPTION_LEN);
+    match option_type {
+        Ipv4OptionType::RecordRoute => {
+            // type, length, pointer and 9 addresses
+            option.push(IPOPT_RR);
+            option.push(39);
+            option.push(4);
+            option.resize(39, 0);
+            option.push(IPOPT_EOL);
+        }
+        Ipv4OptionType::Timestamp => {
+            // type, length, pointer, overflow/flag and 9 timestamps
+            option.push(IPOPT_TS);
+            option.push(40);
+            option.push(5);
+            option.push(IPOPT_TS_TSONLY);
+            option.resize(40, 0);
+        }
+    }
+    option
+}
+
+/// Insert options into IPv4 header of the packet, updating header length, total length and checksum.
+///
+/// `ip_offset` is the offset of the IPv4 header (e.g. 14 for Ethernet frame).
+/// The packet is left unchanged if the options do not fit in the header or total length.
+pub fn insert_ipv4_option(
+    packet: &mut Vec<u8>,
+    ip_offset: usize,
+    option: &[u8],
+) -> Result<(), String> {
+    if option.is_empty() || option.len() % 4 != 0 || packet.len() < ip_offset + IPV4_HEADER_LEN {
+        return Err("Invalid IPv4 option or packet".to_string());
+    }
+    let header_len: usize = ((packet[ip_offset] & 0x0f) as usize) * 4;
+    if header_len + option.len() > IPV4_HEADER_LEN + IPV4_MAX_OPTION_LEN {
+        return Err("IPv4 options exceed maximum header length".to_string());
+    }
+    let total_len: u16 = match u16::from_be_bytes([packet[ip_offset + 2], packet[ip_offset + 3]])
+        .checked_add(option.len() as u16)
+    {
+        Some(total_len) => total_len,
+        None => return Err("IPv4 options exceed maximum total length".to_string()),
+    };
+    let insert_at: usize = ip_offset + header_len;
+    packet.splice(insert_at..insert_at, option.iter().cloned());
+    let new_header_len: usize = header_len + option.len();
+    packet[ip_offset] = (packet[ip_offset] & 0xf0) | (new_header_len / 4) as u8;
+    packet[ip_offset + 2..ip_offset + 4].copy_from_slice(&total_len.to_be_bytes());
+    packet[ip_offset + 10] = 0;
+    packet[ip_offset + 11] = 0;
+    let checksum: u16 = ipv4_checksum(&packet[ip_offset..ip_offset + new_header_len]);
+    packet[ip_offset + 10..ip_offset + 12].copy_from_slice(&checksum.to_be_bytes());
+    Ok(())
+}
+
+/// Parse Record Route and Timestamp options of IPv4 header
+pub fn parse_ipv4_options(packet: &[u8], ip_offset: usize) -> Option<Ipv4OptionData> {
+    if packet.len() < ip_offset + IPV4_HEADER_LEN {
+        return None;
+    }
+    let header_len: usize = ((packet[ip_offset] & 0x0f) as usize) * 4;
+    if header_len <= IPV4_HEADER_LEN || packet.len() < ip_offset + header_len {
+        return None;
+    }
+    let options: &[u8] = &packet[ip_offset + IPV4_HEADER_LEN..ip_offset + header_len];
+    let mut data = Ipv4OptionData {
+        route: Vec::new(),
+        timestamps: Vec::new(),
+    };
+    let mut i: usize = 0;
+    while i < options.len() {
+        match options[i] {
+            IPOPT_EOL => break,
+            IPOPT_NOP => {
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        if i + 2 > options.len() {
+            break;
+        }
+        let len: usize = options[i + 1] as usize;
+        if len < 3 || i + len > options.len() {
+            break;
+        }
+        let option: &[u8] = &options[i..i + len];
+        // Pointer is 1-origin offset of the next free slot
+        let used_end: usize = std::cmp::min((option[2] as usize).saturating_sub(1), len);
+        match option[0] {
+            IPOPT_RR => {
+                for addr in option[3..std::cmp::max(used_end, 3)].chunks_exact(4) {
+                    data.route
+                        .push(Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]));
+                }
+            }
+            IPOPT_TS if len >= 4 => {
+                for ts in option[4..std::cmp::max(used_end, 4)].chunks_exact(4) {
+                    data.timestamps
+                        .push(u32::from_be_bytes([ts[0], ts[1], ts[2], ts[3]]));
+                }
+            }
+            _ => {}
+        }
+        i += len;
+    }
+    if data.route.is_empty() && data.timestamps.is_empty() {
+        None
+    } else {
+        Some(data)
+    }
+}
+
+pub(crate) fn ipv4_checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = 0;
+    for word in header.chunks(2) {
+        let value: u16 = if word.len() == 2 {
+            u16::from_be_bytes([word[0], word[1]])
+        } else {
+            u16::from_be_bytes([word[0], 0])
+        };
+        sum += value as u32;
+    }
+    while (sum >> 16) != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
@@ -1,6 +1,9 @@
 pub mod arp;
 pub mod frame;
 pub mod icmp;
+pub mod ip;
+pub mod ipv4;
+pub mod mpls;
 pub mod ndp;
 pub mod setting;
 pub mod tcp;
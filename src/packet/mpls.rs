@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+const ICMP_HEADER_LEN: usize = 8;
+const IPV6_HEADER_LEN: usize = 40;
+/// Original datagram length assumed by non-compliant implementations (RFC 4884)
+const ICMP_EXT_DEFAULT_OFFSET: usize = 128;
+const ICMP_EXT_VERSION: u8 = 2;
+const ICMP_EXT_HEADER_LEN: usize = 4;
+const ICMP_EXT_OBJECT_HEADER_LEN: usize = 4;
+/// MPLS Label Stack Class (RFC 4950)
+const MPLS_CLASS_NUM: u8 = 1;
+const MPLS_C_TYPE_INCOMING: u8 = 1;
+const MPLS_ENTRY_LEN: usize = 4;
+
+/// MPLS label stack entry
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MplsLabel {
+    /// Label value
+    pub label: u32,
+    /// Experimental bits (Traffic Class)
+    pub exp: u8,
+    /// Bottom of stack
+    pub bottom_of_stack: bool,
+    /// TTL
+    pub ttl: u8,
+}
+
+impl std::fmt::Display for MplsLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "L={} E={} S={} TTL={}",
+            self.label, self.exp, self.bottom_of_stack as u8, self.ttl
+        )
+    }
+}
+
+/// Parse MPLS label stack from ICMP extensions (RFC 4884, RFC 4950) of ICMP/ICMPv6 error message.
+///
+/// `ip_offset` is the offset of the IP header in the packet.
+pub fn parse_mpls_labels(packet: &[u8], ip_offset: usize) -> Vec<MplsLabel> {
+    let mut labels: Vec<MplsLabel> = Vec::new();
+    let version: u8 = match packet.get(ip_offset) {
+        Some(b) => b >> 4,
+        None => return labels,
+    };
+    // Offset of ICMP header and length of original datagram
+    let (icmp_offset, orig_len): (usize, usize) = match version {
+        4 => {
+            let ihl: usize = ((packet[ip_offset] & 0x0f) as usize) * 4;
+            let icmp_offset: usize = ip_offset + ihl;
+            // Length in 32-bit words
+            let len: usize = *packet.get(icmp_offset + 5).unwrap_or(&0) as usize * 4;
+            (icmp_offset, len)
+        }
+        6 => {
+            let icmp_offset: usize = ip_offset + IPV6_HEADER_LEN;
+            // Length in 64-bit words
+            let len: usize = *packet.get(icmp_offset + 4).unwrap_or(&0) as usize * 8;
+            (icmp_offset, len)
+        }
+        _ => return labels,
+    };
+    let orig_len: usize = if orig_len == 0 {
+        ICMP_EXT_DEFAULT_OFFSET
+    } else {
+        orig_len
+    };
+    let ext_offset: usize = icmp_offset + ICMP_HEADER_LEN + orig_len;
+    if packet.len() < ext_offset + ICMP_EXT_HEADER_LEN
+        || packet[ext_offset] >> 4 != ICMP_EXT_VERSION
+    {
+        return labels;
+    }
+    let mut offset: usize = ext_offset + ICMP_EXT_HEADER_LEN;
+    while offset + ICMP_EXT_OBJECT_HEADER_LEN <= packet.len() {
+        let obj_len: usize = u16::from_be_bytes([packet[offset], packet[offset + 1]]) as usize;
+        if obj_len < ICMP_EXT_OBJECT_HEADER_LEN || offset + obj_len > packet.len() {
+            break;
+        }
+        let class_num: u8 = packet[offset + 2];
+        let c_type: u8 = packet[offset + 3];
+        if class_num == MPLS_CLASS_NUM && c_type == MPLS_C_TYPE_INCOMING {
+            let entries: &[u8] = &packet[offset + ICMP_EXT_OBJECT_HEADER_LEN..offset + obj_len];
+            for entry in entries.chunks_exact(MPLS_ENTRY_LEN) {
+                let value: u32 = u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]);
+                labels.push(MplsLabel {
+                    label: value >> 12,
+                    exp: ((value >> 9) & 0x07) as u8,
+                    bottom_of_stack: (value >> 8) & 0x01 == 1,
+                    ttl: (value & 0xff) as u8,
+                });
+            }
+        }
+        offset += obj_len;
+    }
+    labels
+}
@@ -1,45 +1,60 @@
 use crate::packet::setting::PacketBuildSetting;
-use nex::net::mac::MacAddr;
-use nex::packet::ethernet::EtherType;
-use nex::packet::ethernet::MAC_ADDR_LEN;
-use nex::packet::icmpv6::ndp::{NDP_OPT_PACKET_LEN, NDP_SOL_PACKET_LEN};
-use nex::packet::ip::IpNextLevelProtocol;
-use nex::util::packet_builder::builder::PacketBuilder;
-use nex::util::packet_builder::ethernet::EthernetPacketBuilder;
-use nex::util::packet_builder::ipv6::Ipv6PacketBuilder;
-use nex::util::packet_builder::ndp::NdpPacketBuilder;
-use std::net::IpAddr;
+use nex::packet::icmpv6::Icmpv6Packet;
+use std::net::{IpAddr, Ipv6Addr};
 
-/// Build NDP packet
+/// ICMPv6 type of Neighbor Solicitation
+const NEIGHBOR_SOLICITATION: u8 = 135;
+/// Source link-layer address option
+const OPT_SOURCE_LINK_ADDR: u8 = 1;
+/// IPv6 next header value of ICMPv6
+const NEXT_HEADER_ICMPV6: u8 = 58;
+
+/// Solicited-node multicast address of the target (ff02::1:ff + last 24 bits)
+pub fn solicited_node_addr(target: &Ipv6Addr) -> Ipv6Addr {
+    let o = target.octets();
+    Ipv6Addr::new(
+        0xff02,
+        0,
+        0,
+        0,
+        0,
+        1,
+        0xff00 | o[13] as u16,
+        ((o[14] as u16) << 8) | o[15] as u16,
+    )
+}
+
+/// Build NDP Neighbor Solicitation for the destination address.
+/// Sent to its solicited-node multicast address as hosts do (RFC 4861)
 pub fn build_ndp_packet(setting: PacketBuildSetting) -> Vec<u8> {
-    let mut packet_builder = PacketBuilder::new();
-    let ethernet_packet_builder = EthernetPacketBuilder {
-        src_mac: setting.src_mac,
-        dst_mac: MacAddr::broadcast(),
-        ether_type: EtherType::Ipv6,
+    let (src_ipv6, target) = match (setting.src_ip, setting.dst_ip) {
+        (IpAddr::V6(src_ipv6), IpAddr::V6(target)) => (src_ipv6, target),
+        _ => return Vec::new(),
     };
-    packet_builder.set_ethernet(ethernet_packet_builder);
-
-    match setting.src_ip {
-        IpAddr::V4(_) => {}
-        IpAddr::V6(src_ipv6) => {
-            match setting.dst_ip {
-                IpAddr::V4(_) => {}
-                IpAddr::V6(dst_ipv6) => {
-                    // IPv6 Header
-                    let mut ipv6_packet_builder =
-                        Ipv6PacketBuilder::new(src_ipv6, dst_ipv6, IpNextLevelProtocol::Icmpv6);
-                    ipv6_packet_builder.payload_length =
-                        Some((NDP_SOL_PACKET_LEN + NDP_OPT_PACKET_LEN + MAC_ADDR_LEN) as u16);
-                    ipv6_packet_builder.hop_limit = Some(u8::MAX);
-                    packet_builder.set_ipv6(ipv6_packet_builder);
-                    // NDP Header
-                    let ndp_packet_builder =
-                        NdpPacketBuilder::new(setting.src_mac, src_ipv6, dst_ipv6);
-                    packet_builder.set_ndp(ndp_packet_builder);
-                }
-            }
-        }
-    }
-    packet_builder.packet()
+    let dst_ipv6: Ipv6Addr = solicited_node_addr(&target);
+    // ICMPv6 Neighbor Solicitation with the source link-layer address option
+    let mut icmpv6: Vec<u8> = Vec::with_capacity(32);
+    icmpv6.extend_from_slice(&[NEIGHBOR_SOLICITATION, 0, 0, 0, 0, 0, 0, 0]);
+    icmpv6.extend_from_slice(&target.octets());
+    icmpv6.extend_from_slice(&[OPT_SOURCE_LINK_ADDR, 1]);
+    icmpv6.extend_from_slice(&setting.src_mac.octets());
+    // Checksum over the pseudo-header with the solicited-node destination
+    let checksum: u16 = match Icmpv6Packet::new(&icmpv6) {
+        Some(icmpv6_packet) => nex::packet::icmpv6::checksum(&icmpv6_packet, &src_ipv6, &dst_ipv6),
+        None => return Vec::new(),
+    };
+    icmpv6[2..4].copy_from_slice(&checksum.to_be_bytes());
+    let mut packet: Vec<u8> = Vec::with_capacity(14 + 40 + icmpv6.len());
+    // Ethernet header
+    packet.extend_from_slice(&crate::ip::ipv6_multicast_mac(&dst_ipv6).octets());
+    packet.extend_from_slice(&setting.src_mac.octets());
+    packet.extend_from_slice(&[0x86, 0xdd]);
+    // IPv6 header. Hop limit must be 255
+    packet.extend_from_slice(&[0x60, 0, 0, 0]);
+    packet.extend_from_slice(&(icmpv6.len() as u16).to_be_bytes());
+    packet.extend_from_slice(&[NEXT_HEADER_ICMPV6, u8::MAX]);
+    packet.extend_from_slice(&src_ipv6.octets());
+    packet.extend_from_slice(&dst_ipv6.octets());
+    packet.extend_from_slice(&icmpv6);
+    packet
 }
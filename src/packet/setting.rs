@@ -1,6 +1,6 @@
 use crate::{
     config::DEFAULT_LOCAL_UDP_PORT, neighbor::setting::AddressResolveSetting,
-    ping::setting::PingSetting, trace::setting::TraceSetting,
+    packet::ipv4::Ipv4OptionType, ping::setting::PingSetting, trace::setting::TraceSetting,
 };
 use netdev::mac::MacAddr;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
@@ -14,6 +14,14 @@ pub struct PacketBuildSetting {
     pub src_port: u16,
     pub dst_port: u16,
     pub hop_limit: u8,
+    /// IPv4 identification. Chosen by the packet builder if None
+    pub ip_id: Option<u16>,
+    /// Set IPv4 Don't Fragment bit
+    pub dont_fragment: bool,
+    /// IPv4 option added to IPv4 header
+    pub ipv4_option: Option<Ipv4OptionType>,
+    /// TOS (IPv4) or Traffic Class (IPv6). DSCP is the upper 6 bits
+    pub tos: u8,
     pub payload: Vec<u8>,
     pub ip_packet: bool,
 }
@@ -28,6 +36,10 @@ impl PacketBuildSetting {
             src_port: 0,
             dst_port: 0,
             hop_limit: 64,
+            ip_id: None,
+            dont_fragment: false,
+            ipv4_option: None,
+            tos: 0,
             payload: Vec::new(),
             ip_packet: false,
         }
@@ -35,9 +47,10 @@ impl PacketBuildSetting {
     pub fn from_ping_setting(ping_setting: &PingSetting) -> Self {
         match crate::interface::get_interface_by_index(ping_setting.if_index) {
             Some(interface) => {
-                let dst_mac = match &interface.gateway {
-                    Some(gateway) => gateway.mac_addr,
-                    None => MacAddr::zero(),
+                let dst_mac = match (ping_setting.dst_mac, &interface.gateway) {
+                    (Some(dst_mac), _) => dst_mac,
+                    (None, Some(gateway)) => gateway.mac_addr,
+                    (None, None) => MacAddr::zero(),
                 };
                 let src_ip = match ping_setting.dst_ip {
                     IpAddr::V4(_) => crate::interface::get_interface_ipv4(&interface)
@@ -60,7 +73,11 @@ impl PacketBuildSetting {
                     src_port: DEFAULT_LOCAL_UDP_PORT,
                     dst_port: ping_setting.dst_port.unwrap_or(0),
                     hop_limit: ping_setting.hop_limit,
-                    payload: Vec::new(),
+                    ip_id: None,
+                    dont_fragment: ping_setting.dont_fragment,
+                    ipv4_option: ping_setting.ipv4_option,
+                    tos: ping_setting.tos,
+                    payload: vec![0; ping_setting.payload_size],
                     ip_packet: interface.is_tun() || interface.is_loopback(),
                 }
             }
@@ -72,6 +89,10 @@ impl PacketBuildSetting {
                 src_port: 0,
                 dst_port: 0,
                 hop_limit: ping_setting.hop_limit,
+                ip_id: None,
+                dont_fragment: false,
+                ipv4_option: None,
+                tos: 0,
                 payload: Vec::new(),
                 ip_packet: false,
             },
@@ -80,14 +101,16 @@ impl PacketBuildSetting {
     pub fn from_trace_setting(ping_setting: &TraceSetting, seq_ttl: u8) -> Self {
         match crate::interface::get_interface_by_index(ping_setting.if_index) {
             Some(interface) => {
-                let dst_mac = match &interface.gateway {
-                    Some(gateway) => gateway.mac_addr,
-                    None => MacAddr::zero(),
+                let dst_mac = match (ping_setting.dst_mac, &interface.gateway) {
+                    (Some(dst_mac), _) => dst_mac,
+                    (None, Some(gateway)) => gateway.mac_addr,
+                    (None, None) => MacAddr::zero(),
                 };
-                let src_ip = match ping_setting.dst_ip {
-                    IpAddr::V4(_) => crate::interface::get_interface_ipv4(&interface)
+                let src_ip = match (ping_setting.src_ip, ping_setting.dst_ip) {
+                    (Some(src_ip), _) => src_ip,
+                    (None, IpAddr::V4(_)) => crate::interface::get_interface_ipv4(&interface)
                         .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)),
-                    IpAddr::V6(ipv6_addr) => {
+                    (None, IpAddr::V6(ipv6_addr)) => {
                         if nex::net::ip::is_global_ipv6(&ipv6_addr) {
                             crate::interface::get_interface_global_ipv6(&interface)
                                 .unwrap_or(IpAddr::V6(Ipv6Addr::LOCALHOST))
@@ -102,9 +125,13 @@ impl PacketBuildSetting {
                     dst_mac: dst_mac,
                     src_ip: src_ip,
                     dst_ip: ping_setting.dst_ip,
-                    src_port: DEFAULT_LOCAL_UDP_PORT,
+                    src_port: ping_setting.src_port,
                     dst_port: ping_setting.dst_port,
                     hop_limit: seq_ttl,
+                    ip_id: None,
+                    dont_fragment: false,
+                    ipv4_option: None,
+                    tos: ping_setting.tos,
                     payload: Vec::new(),
                     ip_packet: interface.is_tun() || interface.is_loopback(),
                 }
@@ -117,6 +144,10 @@ impl PacketBuildSetting {
                 src_port: 0,
                 dst_port: 0,
                 hop_limit: seq_ttl,
+                ip_id: None,
+                dont_fragment: false,
+                ipv4_option: None,
+                tos: 0,
                 payload: Vec::new(),
                 ip_packet: false,
             },
@@ -150,6 +181,10 @@ impl PacketBuildSetting {
                     src_port: DEFAULT_LOCAL_UDP_PORT,
                     dst_port: 0,
                     hop_limit: 64,
+                    ip_id: None,
+                    dont_fragment: false,
+                    ipv4_option: None,
+                    tos: 0,
                     payload: Vec::new(),
                     ip_packet: interface.is_tun() || interface.is_loopback(),
                 }
@@ -162,6 +197,10 @@ impl PacketBuildSetting {
                 src_port: 0,
                 dst_port: 0,
                 hop_limit: 64,
+                ip_id: None,
+                dont_fragment: false,
+                ipv4_option: None,
+                tos: 0,
                 payload: Vec::new(),
                 ip_packet: false,
             },
@@ -1,3 +1,4 @@
+use crate::packet::ip;
 use crate::packet::setting::PacketBuildSetting;
 use nex::packet::ethernet::EtherType;
 use nex::packet::ip::IpNextLevelProtocol;
@@ -8,6 +9,8 @@ use nex::util::packet_builder::{
 };
 use std::net::{IpAddr, SocketAddr};
 
+const ETHERNET_HEADER_LEN: usize = 14;
+
 /// Build TCP SYN packet with default options
 pub fn build_tcp_syn_packet(setting: PacketBuildSetting) -> Vec<u8> {
     let mut packet_builder = PacketBuilder::new();
@@ -27,6 +30,7 @@ pub fn build_tcp_syn_packet(setting: PacketBuildSetting) -> Vec<u8> {
                     Ipv4PacketBuilder::new(src_ipv4, dst_ipv4, IpNextLevelProtocol::Tcp);
                 ipv4_packet_builder.total_length = Some(64);
                 ipv4_packet_builder.ttl = Some(setting.hop_limit);
+                ipv4_packet_builder.identification = setting.ip_id;
                 packet_builder.set_ipv4(ipv4_packet_builder);
             }
             IpAddr::V6(_) => {}
@@ -59,11 +63,20 @@ pub fn build_tcp_syn_packet(setting: PacketBuildSetting) -> Vec<u8> {
     ];
     packet_builder.set_tcp(tcp_packet_builder);
 
-    if setting.ip_packet {
+    let mut packet = if setting.ip_packet {
         packet_builder.ip_packet()
     } else {
         packet_builder.packet()
+    };
+    if setting.tos != 0 {
+        let ip_offset = if setting.ip_packet {
+            0
+        } else {
+            ETHERNET_HEADER_LEN
+        };
+        ip::set_traffic_class(&mut packet, ip_offset, setting.tos);
     }
+    packet
 }
 
 /// Build TCP SYN packet with minimum options
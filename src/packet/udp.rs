@@ -1,3 +1,4 @@
+use crate::packet::ip;
 use crate::packet::setting::PacketBuildSetting;
 use nex::packet::ethernet::EtherType;
 use nex::packet::ip::IpNextLevelProtocol;
@@ -7,6 +8,10 @@ use nex::util::packet_builder::{
 };
 use std::net::{IpAddr, SocketAddr};
 
+const ETHERNET_HEADER_LEN: usize = 14;
+const IPV4_HEADER_LEN: usize = 20;
+const UDP_HEADER_LEN: usize = 8;
+
 /// Build UDP packet
 pub fn build_udp_packet(setting: PacketBuildSetting) -> Vec<u8> {
     let mut packet_builder = PacketBuilder::new();
@@ -29,6 +34,11 @@ pub fn build_udp_packet(setting: PacketBuildSetting) -> Vec<u8> {
                 let mut ipv4_packet_builder =
                     Ipv4PacketBuilder::new(src_ipv4, dst_ipv4, IpNextLevelProtocol::Udp);
                 ipv4_packet_builder.ttl = Some(setting.hop_limit);
+                ipv4_packet_builder.identification = setting.ip_id;
+                if setting.payload.len() > 0 {
+                    ipv4_packet_builder.total_length =
+                        Some((IPV4_HEADER_LEN + UDP_HEADER_LEN + setting.payload.len()) as u16);
+                }
                 packet_builder.set_ipv4(ipv4_packet_builder);
             }
             IpAddr::V6(_) => {}
@@ -39,6 +49,10 @@ pub fn build_udp_packet(setting: PacketBuildSetting) -> Vec<u8> {
                 let mut ipv6_packet_builder =
                     Ipv6PacketBuilder::new(src_ipv4, dst_ipv6, IpNextLevelProtocol::Udp);
                 ipv6_packet_builder.hop_limit = Some(setting.hop_limit);
+                if setting.payload.len() > 0 {
+                    ipv6_packet_builder.payload_length =
+                        Some((UDP_HEADER_LEN + setting.payload.len()) as u16);
+                }
                 packet_builder.set_ipv6(ipv6_packet_builder);
             }
         },
@@ -47,10 +61,11 @@ pub fn build_udp_packet(setting: PacketBuildSetting) -> Vec<u8> {
     match setting.dst_ip {
         IpAddr::V4(dst_ipv4) => match setting.src_ip {
             IpAddr::V4(src_ipv4) => {
-                let udp_packet_builder = UdpPacketBuilder::new(
+                let mut udp_packet_builder = UdpPacketBuilder::new(
                     SocketAddr::new(IpAddr::V4(src_ipv4), setting.src_port),
                     SocketAddr::new(IpAddr::V4(dst_ipv4), setting.dst_port),
                 );
+                udp_packet_builder.payload = setting.payload.clone();
                 packet_builder.set_udp(udp_packet_builder);
             }
             IpAddr::V6(_) => {}
@@ -58,19 +73,29 @@ pub fn build_udp_packet(setting: PacketBuildSetting) -> Vec<u8> {
         IpAddr::V6(dst_ipv6) => match setting.src_ip {
             IpAddr::V4(_) => {}
             IpAddr::V6(src_ipv6) => {
-                let udp_packet_builder = UdpPacketBuilder::new(
+                let mut udp_packet_builder = UdpPacketBuilder::new(
                     SocketAddr::new(IpAddr::V6(src_ipv6), setting.src_port),
                     SocketAddr::new(IpAddr::V6(dst_ipv6), setting.dst_port),
                 );
+                udp_packet_builder.payload = setting.payload.clone();
                 packet_builder.set_udp(udp_packet_builder);
             }
         },
     }
-    if setting.ip_packet {
+    let mut packet = if setting.ip_packet {
         packet_builder.ip_packet()
     } else {
         packet_builder.packet()
+    };
+    if setting.tos != 0 {
+        let ip_offset = if setting.ip_packet {
+            0
+        } else {
+            ETHERNET_HEADER_LEN
+        };
+        ip::set_traffic_class(&mut packet, ip_offset, setting.tos);
     }
+    packet
 }
 
 pub fn build_ip_next_udp_packet(setting: PacketBuildSetting) -> Vec<u8> {
@@ -0,0 +1,89 @@
+use netdev::mac::MacAddr;
+use std::net::Ipv4Addr;
+
+pub const DHCP_SERVER_PORT: u16 = 67;
+pub const DHCP_CLIENT_PORT: u16 = 68;
+const BOOTP_HEADER_SIZE: usize = 236;
+const MAGIC_COOKIE: [u8; 4] = [0x63, 0x82, 0x53, 0x63];
+const BOOTREPLY: u8 = 2;
+const OPTION_PAD: u8 = 0;
+const OPTION_END: u8 = 255;
+const OPTION_HOSTNAME: u8 = 12;
+const OPTION_REQUESTED_IP: u8 = 50;
+const OPTION_MESSAGE_TYPE: u8 = 53;
+const OPTION_VENDOR_CLASS: u8 = 60;
+
+/// Client information in a DHCP message
+#[derive(Clone, Debug, PartialEq)]
+pub struct DhcpInfo {
+    /// Client hardware address
+    pub mac_addr: MacAddr,
+    /// Assigned, current or requested address
+    pub ip_addr: Option<Ipv4Addr>,
+    pub hostname: Option<String>,
+    /// Vendor class identifier (e.g. MSFT 5.0, android-dhcp-14)
+    pub vendor_class: Option<String>,
+    pub message_type: u8,
+}
+
+fn read_ipv4(payload: &[u8], offset: usize) -> Option<Ipv4Addr> {
+    let octets: [u8; 4] = payload.get(offset..offset + 4)?.try_into().ok()?;
+    let ip_addr: Ipv4Addr = Ipv4Addr::from(octets);
+    if ip_addr.is_unspecified() {
+        None
+    } else {
+        Some(ip_addr)
+    }
+}
+
+/// Parse a DHCP (BOOTP) message from the UDP payload
+pub fn parse(payload: &[u8]) -> Option<DhcpInfo> {
+    if payload.len() < BOOTP_HEADER_SIZE + MAGIC_COOKIE.len()
+        || payload[BOOTP_HEADER_SIZE..BOOTP_HEADER_SIZE + 4] != MAGIC_COOKIE
+    {
+        return None;
+    }
+    let chaddr: &[u8] = &payload[28..34];
+    let mut info = DhcpInfo {
+        mac_addr: MacAddr::new(
+            chaddr[0], chaddr[1], chaddr[2], chaddr[3], chaddr[4], chaddr[5],
+        ),
+        ip_addr: None,
+        hostname: None,
+        vendor_class: None,
+        message_type: 0,
+    };
+    let mut requested_ip: Option<Ipv4Addr> = None;
+    let mut pos: usize = BOOTP_HEADER_SIZE + MAGIC_COOKIE.len();
+    while pos < payload.len() {
+        let code: u8 = payload[pos];
+        if code == OPTION_PAD {
+            pos += 1;
+            continue;
+        }
+        if code == OPTION_END {
+            break;
+        }
+        let len: usize = *payload.get(pos + 1)? as usize;
+        let value: &[u8] = payload.get(pos + 2..pos + 2 + len)?;
+        match code {
+            OPTION_HOSTNAME => {
+                info.hostname = Some(String::from_utf8_lossy(value).trim().to_string())
+            }
+            OPTION_REQUESTED_IP => requested_ip = read_ipv4(value, 0),
+            OPTION_MESSAGE_TYPE => info.message_type = value.first().copied().unwrap_or(0),
+            OPTION_VENDOR_CLASS => {
+                info.vendor_class = Some(String::from_utf8_lossy(value).trim().to_string())
+            }
+            _ => {}
+        }
+        pos += 2 + len;
+    }
+    // yiaddr in replies, ciaddr when renewing, otherwise the requested address
+    info.ip_addr = if payload[0] == BOOTREPLY {
+        read_ipv4(payload, 16)
+    } else {
+        read_ipv4(payload, 12).or(requested_ip)
+    };
+    Some(info)
+}
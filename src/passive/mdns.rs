@@ -0,0 +1,104 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub const MDNS_PORT: u16 = 5353;
+const DNS_HEADER_SIZE: usize = 12;
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_AAAA: u16 = 28;
+/// Limit of compression pointers followed per name
+const MAX_POINTERS: usize = 16;
+
+/// Records announced in an mDNS response
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MdnsAnnouncement {
+    /// (host name, address) from A/AAAA records
+    pub addresses: Vec<(String, IpAddr)>,
+    /// Service types from PTR records (e.g. _ipp._tcp.local)
+    pub services: Vec<String>,
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+/// Read a possibly compressed name. Returns the name and the position after it
+fn read_name(data: &[u8], start: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut pos: usize = start;
+    let mut end: Option<usize> = None;
+    let mut pointers: usize = 0;
+    loop {
+        let len: u8 = *data.get(pos)?;
+        if len == 0 {
+            pos += 1;
+            break;
+        }
+        if len & 0xC0 == 0xC0 {
+            pointers += 1;
+            if pointers > MAX_POINTERS {
+                return None;
+            }
+            let offset: usize = (read_u16(data, pos)? & 0x3FFF) as usize;
+            if end.is_none() {
+                end = Some(pos + 2);
+            }
+            pos = offset;
+            continue;
+        }
+        let label: &[u8] = data.get(pos + 1..pos + 1 + len as usize)?;
+        labels.push(String::from_utf8_lossy(label).to_string());
+        pos += 1 + len as usize;
+    }
+    Some((labels.join("."), end.unwrap_or(pos)))
+}
+
+/// Parse the resource records of an mDNS message from the UDP payload
+pub fn parse(payload: &[u8]) -> Option<MdnsAnnouncement> {
+    if payload.len() < DNS_HEADER_SIZE {
+        return None;
+    }
+    let question_count: u16 = read_u16(payload, 4)?;
+    let record_count: usize = read_u16(payload, 6)? as usize
+        + read_u16(payload, 8)? as usize
+        + read_u16(payload, 10)? as usize;
+    let mut pos: usize = DNS_HEADER_SIZE;
+    for _ in 0..question_count {
+        let (_, next) = read_name(payload, pos)?;
+        // Type and class
+        pos = next + 4;
+    }
+    let mut announcement = MdnsAnnouncement::default();
+    for _ in 0..record_count {
+        let (name, next) = read_name(payload, pos)?;
+        let record_type: u16 = read_u16(payload, next)?;
+        // Class and TTL
+        let data_len: usize = read_u16(payload, next + 8)? as usize;
+        let data_pos: usize = next + 10;
+        let data: &[u8] = payload.get(data_pos..data_pos + data_len)?;
+        match record_type {
+            TYPE_A if data.len() == 4 => {
+                let octets: [u8; 4] = data.try_into().ok()?;
+                announcement
+                    .addresses
+                    .push((name, IpAddr::V4(Ipv4Addr::from(octets))));
+            }
+            TYPE_AAAA if data.len() == 16 => {
+                let octets: [u8; 16] = data.try_into().ok()?;
+                announcement
+                    .addresses
+                    .push((name, IpAddr::V6(Ipv6Addr::from(octets))));
+            }
+            TYPE_PTR => {
+                // Service type enumeration and instance pointers
+                if name.starts_with('_') && !name.starts_with("_services.") {
+                    if !announcement.services.contains(&name) {
+                        announcement.services.push(name);
+                    }
+                }
+            }
+            _ => {}
+        }
+        pos = data_pos + data_len;
+    }
+    Some(announcement)
+}
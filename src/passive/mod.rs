@@ -0,0 +1,213 @@
+pub mod dhcp;
+pub mod mdns;
+
+use crate::packet::frame::PacketFrame;
+use chrono::{DateTime, Local};
+use ipnet::IpNet;
+use netdev::mac::MacAddr;
+use netdev::Interface;
+use nex::packet::tcp::TcpFlags;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Host observed from its own traffic
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PassiveHost {
+    pub ip_addr: IpAddr,
+    pub mac_addr: MacAddr,
+    pub vendor_name: String,
+    /// Names from DHCP and mDNS
+    pub hostnames: BTreeSet<String>,
+    /// Ports seen accepting (SYN-ACK) or being asked for (SYN) TCP connections
+    pub ports: BTreeSet<u16>,
+    /// Service types announced over mDNS
+    pub services: BTreeSet<String>,
+    /// DHCP vendor class identifier
+    pub vendor_class: String,
+    /// Traffic the host was learned from (ARP, DHCP, mDNS, TCP)
+    pub sources: BTreeSet<String>,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+}
+
+impl PassiveHost {
+    pub fn new(ip_addr: IpAddr) -> PassiveHost {
+        let now: DateTime<Local> = Local::now();
+        PassiveHost {
+            ip_addr: ip_addr,
+            mac_addr: MacAddr::zero(),
+            vendor_name: String::new(),
+            hostnames: BTreeSet::new(),
+            ports: BTreeSet::new(),
+            services: BTreeSet::new(),
+            vendor_class: String::new(),
+            sources: BTreeSet::new(),
+            first_seen: now,
+            last_seen: now,
+        }
+    }
+}
+
+/// Host inventory built without sending any packet
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PassiveInventory {
+    pub hosts: BTreeMap<IpAddr, PassiveHost>,
+    /// Packets inspected
+    pub packets: usize,
+    pub capture_time: Duration,
+    /// Networks of the capture interface. TCP endpoints outside them are not recorded
+    #[serde(skip)]
+    local_nets: Vec<IpNet>,
+    #[serde(skip)]
+    oui_map: HashMap<String, String>,
+}
+
+impl PassiveInventory {
+    pub fn new(iface: &Interface) -> PassiveInventory {
+        let mut local_nets: Vec<IpNet> = Vec::new();
+        for ip in &iface.ipv4 {
+            if let Ok(net) = IpNet::new(IpAddr::V4(ip.addr), ip.prefix_len) {
+                local_nets.push(net.trunc());
+            }
+        }
+        for ip in &iface.ipv6 {
+            if let Ok(net) = IpNet::new(IpAddr::V6(ip.addr), ip.prefix_len) {
+                local_nets.push(net.trunc());
+            }
+        }
+        PassiveInventory {
+            hosts: BTreeMap::new(),
+            packets: 0,
+            capture_time: Duration::from_millis(0),
+            local_nets: local_nets,
+            oui_map: crate::db::get_oui_detail_map(),
+        }
+    }
+    fn is_local(&self, ip_addr: &IpAddr) -> bool {
+        self.local_nets.iter().any(|net| net.contains(ip_addr))
+    }
+    /// Record what the packet tells about a host. Returns a description of each new fact
+    fn observe(
+        &mut self,
+        ip_addr: IpAddr,
+        source: &str,
+        f: impl FnOnce(&mut PassiveHost, &mut Vec<String>),
+    ) -> Vec<String> {
+        let mut events: Vec<String> = Vec::new();
+        if ip_addr.is_unspecified() || ip_addr.is_multicast() {
+            return events;
+        }
+        let host: &mut PassiveHost = self.hosts.entry(ip_addr).or_insert_with(|| {
+            events.push(format!("New host {} ({})", ip_addr, source));
+            PassiveHost::new(ip_addr)
+        });
+        host.last_seen = Local::now();
+        host.sources.insert(source.to_string());
+        f(host, &mut events);
+        events
+    }
+    fn set_mac_addr(&mut self, ip_addr: IpAddr, mac_addr: MacAddr) -> Vec<String> {
+        if mac_addr == MacAddr::zero() || mac_addr == MacAddr::broadcast() {
+            return Vec::new();
+        }
+        let vendor_name: String = crate::db::get_vendor_name(&self.oui_map, &mac_addr);
+        match self.hosts.get_mut(&ip_addr) {
+            Some(host) if host.mac_addr != mac_addr => {
+                host.mac_addr = mac_addr;
+                host.vendor_name = vendor_name;
+                vec![format!("{} is at {}", ip_addr, mac_addr)]
+            }
+            _ => Vec::new(),
+        }
+    }
+    /// Update the inventory from a captured packet. Returns new facts for the live display
+    pub fn update(&mut self, frame: &PacketFrame) -> Vec<String> {
+        self.packets += 1;
+        let mut events: Vec<String> = Vec::new();
+        if let Some(arp_header) = &frame.arp_header {
+            let ip_addr: IpAddr = IpAddr::V4(arp_header.sender_proto_addr);
+            events.extend(self.observe(ip_addr, "ARP", |_, _| {}));
+            if self.hosts.contains_key(&ip_addr) {
+                events.extend(self.set_mac_addr(ip_addr, arp_header.sender_hw_addr));
+            }
+            return events;
+        }
+        if let Some(udp_header) = &frame.udp_header {
+            let ports: [u16; 2] = [udp_header.source, udp_header.destination];
+            if ports.contains(&dhcp::DHCP_CLIENT_PORT) || ports.contains(&dhcp::DHCP_SERVER_PORT) {
+                if let Some(info) = dhcp::parse(&frame.payload) {
+                    if let Some(ipv4_addr) = info.ip_addr {
+                        let ip_addr: IpAddr = IpAddr::V4(ipv4_addr);
+                        events.extend(self.observe(ip_addr, "DHCP", |host, events| {
+                            if let Some(hostname) = &info.hostname {
+                                if !hostname.is_empty() && host.hostnames.insert(hostname.clone()) {
+                                    events.push(format!("{} is {}", ip_addr, hostname));
+                                }
+                            }
+                            if let Some(vendor_class) = &info.vendor_class {
+                                host.vendor_class = vendor_class.clone();
+                            }
+                        }));
+                        events.extend(self.set_mac_addr(ip_addr, info.mac_addr));
+                    }
+                }
+                return events;
+            }
+            if udp_header.source == mdns::MDNS_PORT {
+                if let Some(announcement) = mdns::parse(&frame.payload) {
+                    for (name, ip_addr) in &announcement.addresses {
+                        events.extend(self.observe(*ip_addr, "mDNS", |host, events| {
+                            if host.hostnames.insert(name.clone()) {
+                                events.push(format!("{} is {}", ip_addr, name));
+                            }
+                        }));
+                    }
+                    if let Some((src, _)) = crate::pcap::filter::addresses(frame) {
+                        if !announcement.services.is_empty() {
+                            events.extend(self.observe(src, "mDNS", |host, events| {
+                                for service in &announcement.services {
+                                    if host.services.insert(service.clone()) {
+                                        events.push(format!("{} offers {}", src, service));
+                                    }
+                                }
+                            }));
+                        }
+                    }
+                }
+                return events;
+            }
+        }
+        if let (Some(tcp_header), Some((src, dst))) =
+            (&frame.tcp_header, crate::pcap::filter::addresses(frame))
+        {
+            if tcp_header.flags & TcpFlags::SYN == 0 {
+                return events;
+            }
+            // SYN-ACK from the server, or SYN to it
+            let (server, port): (IpAddr, u16) = if tcp_header.flags & TcpFlags::ACK != 0 {
+                (src, tcp_header.source)
+            } else {
+                (dst, tcp_header.destination)
+            };
+            for ip_addr in [src, dst] {
+                if !self.is_local(&ip_addr) {
+                    continue;
+                }
+                events.extend(self.observe(ip_addr, "TCP", |host, events| {
+                    if ip_addr == server && host.ports.insert(port) {
+                        events.push(format!("{} port {}/tcp", ip_addr, port));
+                    }
+                }));
+            }
+            // The frame's source MAC belongs to the sender only on the local segment
+            if let Some(ethernet_header) = &frame.ethernet_header {
+                if self.is_local(&src) {
+                    events.extend(self.set_mac_addr(src, ethernet_header.source));
+                }
+            }
+        }
+        events
+    }
+}
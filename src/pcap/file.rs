@@ -0,0 +1,74 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Microsecond-resolution pcap (libpcap classic format)
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_VERSION_MAJOR: u16 = 2;
+const PCAP_VERSION_MINOR: u16 = 4;
+pub const PCAP_SNAPLEN: u32 = 65535;
+pub const LINKTYPE_ETHERNET: u32 = 1;
+/// Raw IPv4/IPv6 without link-layer header (TUN)
+pub const LINKTYPE_RAW: u32 = 101;
+const FILE_HEADER_SIZE: u64 = 24;
+const RECORD_HEADER_SIZE: u64 = 16;
+
+/// Writes packets to a pcap file
+pub struct PcapWriter {
+    writer: BufWriter<File>,
+    /// Bytes written including headers
+    size: u64,
+}
+
+impl PcapWriter {
+    pub fn create(path: &Path, link_type: u32) -> Result<PcapWriter, String> {
+        let file: File = File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.to_string_lossy(), e))?;
+        let mut writer = PcapWriter {
+            writer: BufWriter::new(file),
+            size: 0,
+        };
+        let mut header: Vec<u8> = Vec::with_capacity(FILE_HEADER_SIZE as usize);
+        header.extend_from_slice(&PCAP_MAGIC.to_le_bytes());
+        header.extend_from_slice(&PCAP_VERSION_MAJOR.to_le_bytes());
+        header.extend_from_slice(&PCAP_VERSION_MINOR.to_le_bytes());
+        // thiszone, sigfigs
+        header.extend_from_slice(&[0; 8]);
+        header.extend_from_slice(&PCAP_SNAPLEN.to_le_bytes());
+        header.extend_from_slice(&link_type.to_le_bytes());
+        writer.write_bytes(&header)?;
+        Ok(writer)
+    }
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), String> {
+        self.writer
+            .write_all(data)
+            .map_err(|e| format!("Failed to write pcap: {}", e))?;
+        self.size += data.len() as u64;
+        Ok(())
+    }
+    /// Append a packet. Truncated to the snap length
+    pub fn write_packet(&mut self, timestamp: SystemTime, data: &[u8]) -> Result<(), String> {
+        let since_epoch = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let captured: &[u8] = &data[..std::cmp::min(data.len(), PCAP_SNAPLEN as usize)];
+        let mut header: Vec<u8> = Vec::with_capacity(RECORD_HEADER_SIZE as usize);
+        header.extend_from_slice(&(since_epoch.as_secs() as u32).to_le_bytes());
+        header.extend_from_slice(&since_epoch.subsec_micros().to_le_bytes());
+        header.extend_from_slice(&(captured.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        self.write_bytes(&header)?;
+        self.write_bytes(captured)
+    }
+    /// Size of the file if the packet is written
+    pub fn size_with(&self, packet_len: usize) -> u64 {
+        self.size + RECORD_HEADER_SIZE + std::cmp::min(packet_len, PCAP_SNAPLEN as usize) as u64
+    }
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("Failed to write pcap: {}", e))
+    }
+}
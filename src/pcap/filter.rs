@@ -0,0 +1,210 @@
+use crate::packet::frame::PacketFrame;
+use ipnet::IpNet;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Direction qualifier of host/net/port primitives
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Any,
+    Src,
+    Dst,
+}
+
+/// Capture filter in a subset of the pcap-filter syntax.
+/// Protocols (tcp, udp, icmp, icmp6, arp, ip, ip6), [src|dst] host/net/port, and/or/not, parentheses
+#[derive(Clone, Debug, PartialEq)]
+pub enum PacketFilter {
+    Protocol(String),
+    Host(Direction, IpAddr),
+    Net(Direction, IpNet),
+    Port(Direction, u16),
+    Not(Box<PacketFilter>),
+    And(Box<PacketFilter>, Box<PacketFilter>),
+    Or(Box<PacketFilter>, Box<PacketFilter>),
+}
+
+const PROTOCOLS: [&str; 7] = ["tcp", "udp", "icmp", "icmp6", "arp", "ip", "ip6"];
+
+fn tokenize(expr: &str) -> Vec<String> {
+    expr.replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|token| token.as_str())
+    }
+    fn next(&mut self) -> Result<String, String> {
+        let token: String = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or("Unexpected end of filter".to_string())?;
+        self.pos += 1;
+        Ok(token)
+    }
+    fn parse_or(&mut self) -> Result<PacketFilter, String> {
+        let mut filter: PacketFilter = self.parse_and()?;
+        while matches!(self.peek(), Some("or") | Some("||")) {
+            self.pos += 1;
+            filter = PacketFilter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+    fn parse_and(&mut self) -> Result<PacketFilter, String> {
+        let mut filter: PacketFilter = self.parse_unary()?;
+        while matches!(self.peek(), Some("and") | Some("&&")) {
+            self.pos += 1;
+            filter = PacketFilter::And(Box::new(filter), Box::new(self.parse_unary()?));
+        }
+        Ok(filter)
+    }
+    fn parse_unary(&mut self) -> Result<PacketFilter, String> {
+        match self.peek() {
+            Some("not") | Some("!") => {
+                self.pos += 1;
+                Ok(PacketFilter::Not(Box::new(self.parse_unary()?)))
+            }
+            Some("(") => {
+                self.pos += 1;
+                let filter: PacketFilter = self.parse_or()?;
+                if self.next()? != ")" {
+                    return Err("Missing )".to_string());
+                }
+                Ok(filter)
+            }
+            _ => self.parse_primitive(),
+        }
+    }
+    fn parse_primitive(&mut self) -> Result<PacketFilter, String> {
+        let token: String = self.next()?;
+        if PROTOCOLS.contains(&token.as_str()) {
+            let protocol = PacketFilter::Protocol(token);
+            // "tcp port 443" is tcp and port 443
+            return match self.peek() {
+                Some("port") | Some("src") | Some("dst") | Some("host") | Some("net") => Ok(
+                    PacketFilter::And(Box::new(protocol), Box::new(self.parse_primitive()?)),
+                ),
+                _ => Ok(protocol),
+            };
+        }
+        let (direction, kind): (Direction, String) = match token.as_str() {
+            "src" => (Direction::Src, self.next()?),
+            "dst" => (Direction::Dst, self.next()?),
+            _ => (Direction::Any, token),
+        };
+        let value: String = self.next()?;
+        match kind.as_str() {
+            "host" => IpAddr::from_str(&value)
+                .map(|ip_addr| PacketFilter::Host(direction, ip_addr))
+                .map_err(|_| format!("Invalid host: {}", value)),
+            "net" => IpNet::from_str(&value)
+                .map(|net| PacketFilter::Net(direction, net))
+                .map_err(|_| format!("Invalid net: {}", value)),
+            "port" => value
+                .parse::<u16>()
+                .map(|port| PacketFilter::Port(direction, port))
+                .map_err(|_| format!("Invalid port: {}", value)),
+            _ => Err(format!("Unknown filter primitive: {}", kind)),
+        }
+    }
+}
+
+impl PacketFilter {
+    /// Parse filter expression (e.g. "tcp port 443 and not host 10.0.0.1")
+    pub fn parse(expr: &str) -> Result<PacketFilter, String> {
+        let mut parser = Parser {
+            tokens: tokenize(expr),
+            pos: 0,
+        };
+        let filter: PacketFilter = parser.parse_or()?;
+        match parser.peek() {
+            Some(token) => Err(format!("Unexpected token in filter: {}", token)),
+            None => Ok(filter),
+        }
+    }
+    pub fn matches(&self, frame: &PacketFrame) -> bool {
+        match self {
+            PacketFilter::Protocol(protocol) => match protocol.as_str() {
+                "tcp" => frame.tcp_header.is_some(),
+                "udp" => frame.udp_header.is_some(),
+                "icmp" => frame.icmp_header.is_some(),
+                "icmp6" => frame.icmpv6_header.is_some(),
+                "arp" => frame.arp_header.is_some(),
+                "ip" => frame.ipv4_header.is_some(),
+                "ip6" => frame.ipv6_header.is_some(),
+                _ => false,
+            },
+            PacketFilter::Host(direction, ip_addr) => {
+                match_direction(*direction, addresses(frame), |addr| addr == *ip_addr)
+            }
+            PacketFilter::Net(direction, net) => {
+                match_direction(*direction, addresses(frame), |addr| net.contains(&addr))
+            }
+            PacketFilter::Port(direction, port) => {
+                match_direction(*direction, ports(frame), |p| p == *port)
+            }
+            PacketFilter::Not(filter) => !filter.matches(frame),
+            PacketFilter::And(a, b) => a.matches(frame) && b.matches(frame),
+            PacketFilter::Or(a, b) => a.matches(frame) || b.matches(frame),
+        }
+    }
+}
+
+fn match_direction<T: Copy>(
+    direction: Direction,
+    endpoints: Option<(T, T)>,
+    f: impl Fn(T) -> bool,
+) -> bool {
+    match endpoints {
+        Some((src, dst)) => match direction {
+            Direction::Any => f(src) || f(dst),
+            Direction::Src => f(src),
+            Direction::Dst => f(dst),
+        },
+        None => false,
+    }
+}
+
+/// (source, destination) addresses of IP or ARP
+pub fn addresses(frame: &PacketFrame) -> Option<(IpAddr, IpAddr)> {
+    if let Some(ipv4_header) = &frame.ipv4_header {
+        return Some((
+            IpAddr::V4(ipv4_header.source),
+            IpAddr::V4(ipv4_header.destination),
+        ));
+    }
+    if let Some(ipv6_header) = &frame.ipv6_header {
+        return Some((
+            IpAddr::V6(ipv6_header.source),
+            IpAddr::V6(ipv6_header.destination),
+        ));
+    }
+    if let Some(arp_header) = &frame.arp_header {
+        return Some((
+            IpAddr::V4(arp_header.sender_proto_addr),
+            IpAddr::V4(arp_header.target_proto_addr),
+        ));
+    }
+    None
+}
+
+/// (source, destination) ports of TCP or UDP
+pub fn ports(frame: &PacketFrame) -> Option<(u16, u16)> {
+    if let Some(tcp_header) = &frame.tcp_header {
+        return Some((tcp_header.source, tcp_header.destination));
+    }
+    if let Some(udp_header) = &frame.udp_header {
+        return Some((udp_header.source, udp_header.destination));
+    }
+    None
+}
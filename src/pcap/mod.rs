@@ -1,6 +1,6 @@
+pub mod file;
+pub mod filter;
 pub mod setting;
-use std::net::IpAddr;
-//use std::sync::mpsc::Sender;
 use crate::interface;
 use crate::packet::frame::PacketFrame;
 use nex::datalink::RawReceiver;
@@ -10,9 +10,12 @@ use nex::packet::frame::ParseOption;
 use nex::packet::{ethernet::EtherType, ip::IpNextLevelProtocol};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::time::Instant;
+use std::time::SystemTime;
 
 /// Packet capture message
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -157,6 +160,88 @@ pub fn start_capture(
     rx: &mut Box<dyn RawReceiver>,
     capture_options: PacketCaptureOptions,
     stop: &Arc<Mutex<bool>>,
+) -> Vec<PacketFrame> {
+    capture(rx, capture_options, stop, None)
+}
+
+/// Start packet capture. Each captured frame is also sent to `msg_tx` as soon as it is received
+pub fn start_capture_with_sender(
+    rx: &mut Box<dyn RawReceiver>,
+    capture_options: PacketCaptureOptions,
+    stop: &Arc<Mutex<bool>>,
+    msg_tx: Sender<PacketFrame>,
+) -> Vec<PacketFrame> {
+    capture(rx, capture_options, stop, Some(&msg_tx))
+}
+
+/// Captured packet with its raw bytes
+#[derive(Debug, Clone)]
+pub struct RawPacket {
+    pub timestamp: SystemTime,
+    pub data: Vec<u8>,
+    pub frame: PacketFrame,
+}
+
+/// Start packet capture keeping the raw bytes. Each packet is sent to `msg_tx` instead of being collected.
+/// Returns when `stop` is set, the capture timeout passes or the receiver is dropped
+pub fn start_raw_capture(
+    rx: &mut Box<dyn RawReceiver>,
+    capture_options: PacketCaptureOptions,
+    stop: &Arc<Mutex<bool>>,
+    msg_tx: Sender<RawPacket>,
+) -> CaptureReport {
+    let mut report: CaptureReport = CaptureReport::new();
+    report.start_time = crate::sys::time::get_sysdate();
+    let start_time = Instant::now();
+    loop {
+        match rx.next() {
+            Ok(packet) => {
+                let mut parse_option: ParseOption = ParseOption::default();
+                if capture_options.tunnel
+                    || (cfg!(any(target_os = "macos", target_os = "ios"))
+                        && capture_options.loopback)
+                {
+                    parse_option.from_ip_packet = true;
+                    parse_option.offset = if capture_options.loopback { 14 } else { 0 };
+                }
+                let frame: Frame = Frame::from_bytes(&packet, parse_option);
+                if filter_packet(&frame, &capture_options) {
+                    let raw_packet = RawPacket {
+                        timestamp: SystemTime::now(),
+                        data: packet.to_vec(),
+                        frame: PacketFrame::from_nex_frame(&frame),
+                    };
+                    report.packets += 1;
+                    report.bytes += packet.len();
+                    if msg_tx.send(raw_packet).is_err() {
+                        break;
+                    }
+                }
+            }
+            Err(_) => {}
+        }
+        match stop.lock() {
+            Ok(stop) => {
+                if *stop {
+                    break;
+                }
+            }
+            Err(_) => {}
+        }
+        if Instant::now().duration_since(start_time) > capture_options.capture_timeout {
+            break;
+        }
+    }
+    report.end_time = crate::sys::time::get_sysdate();
+    report.duration = start_time.elapsed();
+    report
+}
+
+fn capture(
+    rx: &mut Box<dyn RawReceiver>,
+    capture_options: PacketCaptureOptions,
+    stop: &Arc<Mutex<bool>>,
+    msg_tx: Option<&Sender<PacketFrame>>,
 ) -> Vec<PacketFrame> {
     let mut frames = Vec::new();
     let start_time = Instant::now();
@@ -180,11 +265,13 @@ pub fn start_capture(
                 let frame: Frame = Frame::from_bytes(&packet, parse_option);
                 if filter_packet(&frame, &capture_options) {
                     let packet_frame = PacketFrame::from_nex_frame(&frame);
+                    if let Some(msg_tx) = msg_tx {
+                        match msg_tx.send(packet_frame.clone()) {
+                            Ok(_) => {}
+                            Err(_) => {}
+                        }
+                    }
                     frames.push(packet_frame);
-                    /* match msg_tx.send(packet_frame) {
-                        Ok(_) => {}
-                        Err(_) => {}
-                    } */
                 }
             }
             Err(_) => {}
@@ -1,3 +1,5 @@
+pub mod multi;
 pub mod pinger;
+pub mod pmtu;
 pub mod result;
 pub mod setting;
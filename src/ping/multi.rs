@@ -0,0 +1,265 @@
+use super::pinger::{is_deadline_exceeded, is_stopped, next_wait_time, wait_next};
+use super::result::{PingResult, PingStat};
+use super::setting::PingSetting;
+use crate::host::{NodeType, PortStatus};
+use crate::packet::setting::PacketBuildSetting;
+use crate::probe::{ProbeResult, ProbeStatus, ProbeStatusKind};
+use crate::protocol::Protocol;
+use netdev::Interface;
+use nex::net::mac::MacAddr;
+use nex::packet::frame::{Frame, ParseOption};
+use nex::packet::icmp::IcmpType;
+use nex::packet::icmpv6::Icmpv6Type;
+use nex::packet::tcp::TcpFlags;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Probe state of a target
+struct TargetState {
+    setting: PingSetting,
+    src_ip: IpAddr,
+    packet: Vec<u8>,
+    replied: bool,
+    responses: Vec<ProbeResult>,
+}
+
+/// Ping targets over one datalink channel. Each round sends a probe to every target
+/// and replies are dispatched to the target by source address.
+///
+/// All settings must share the interface, protocol and timing. Those of the first one are used.
+pub fn multi_ping(
+    settings: Vec<PingSetting>,
+    stop_handle: &Arc<Mutex<bool>>,
+) -> Result<Vec<PingResult>, String> {
+    let base: PingSetting = match settings.first() {
+        Some(setting) => setting.clone(),
+        None => return Ok(Vec::new()),
+    };
+    let interface: Interface = match crate::interface::get_interface_by_index(base.if_index) {
+        Some(interface) => interface,
+        None => {
+            return Err(format!(
+                "multi_ping: unable to get interface by index {}",
+                base.if_index
+            ))
+        }
+    };
+    let config = nex::datalink::Config {
+        write_buffer_size: 4096,
+        read_buffer_size: 4096,
+        read_timeout: Some(base.receive_timeout),
+        write_timeout: None,
+        channel_type: nex::datalink::ChannelType::Layer2,
+        bpf_fd_attempts: 1000,
+        linux_fanout: None,
+        promiscuous: false,
+    };
+    let (mut tx, mut rx) = match nex::datalink::channel(&interface, config) {
+        Ok(nex::datalink::Channel::Ethernet(tx, rx)) => (tx, rx),
+        Ok(_) => return Err("multi_ping: unable to create channel".to_string()),
+        Err(e) => return Err(format!("multi_ping: unable to create channel: {}", e)),
+    };
+    let mut parse_option: ParseOption = ParseOption::default();
+    if base.tunnel {
+        let payload_offset = if base.loopback { 14 } else { 0 };
+        parse_option.from_ip_packet = true;
+        parse_option.offset = payload_offset;
+    }
+    // Offset of IP header in received packet
+    let ip_offset: usize = if base.tunnel { parse_option.offset } else { 14 };
+    let echo_id: u16 = std::process::id() as u16;
+    let mut send_ip_offset: usize = 14;
+    let mut states: Vec<TargetState> = Vec::new();
+    // Source address of reply -> index of target
+    let mut target_map: HashMap<IpAddr, usize> = HashMap::new();
+    for setting in settings {
+        let packet_setting: PacketBuildSetting = PacketBuildSetting::from_ping_setting(&setting);
+        if packet_setting.ip_packet {
+            send_ip_offset = 0;
+        }
+        let packet: Vec<u8> = match setting.protocol {
+            Protocol::ICMP => crate::packet::icmp::build_icmp_packet(packet_setting.clone()),
+            Protocol::TCP => crate::packet::tcp::build_tcp_syn_packet(packet_setting.clone()),
+            Protocol::UDP => crate::packet::udp::build_udp_packet(packet_setting.clone()),
+            _ => return Err("multi_ping: unsupported protocol".to_string()),
+        };
+        target_map.insert(setting.dst_ip, states.len());
+        states.push(TargetState {
+            setting: setting,
+            src_ip: packet_setting.src_ip,
+            packet: packet,
+            replied: false,
+            responses: Vec::new(),
+        });
+    }
+    let start_date = crate::sys::time::get_sysdate();
+    let start_time = Instant::now();
+    let mut seq: u32 = 0;
+    while base.count == 0 || seq < base.count {
+        if is_stopped(stop_handle) || is_deadline_exceeded(&base, start_time) {
+            break;
+        }
+        seq += 1;
+        for state in &mut states {
+            if state.setting.protocol == Protocol::ICMP {
+                crate::packet::icmp::set_echo_id_seq(
+                    &mut state.packet,
+                    send_ip_offset,
+                    echo_id,
+                    seq as u16,
+                );
+            }
+            state.replied = false;
+            match tx.send(&state.packet) {
+                Some(_) => {}
+                None => {}
+            }
+        }
+        let send_time: Instant = Instant::now();
+        let mut pending: usize = states.len();
+        while pending > 0 && send_time.elapsed() < base.receive_timeout {
+            let packet = match rx.next() {
+                Ok(packet) => packet,
+                Err(_) => continue,
+            };
+            let frame: Frame = Frame::from_bytes(&packet, parse_option.clone());
+            let ip_layer = match &frame.ip {
+                Some(ip_layer) => ip_layer,
+                None => continue,
+            };
+            let (source, destination, ttl): (IpAddr, IpAddr, u8) =
+                match (&ip_layer.ipv4, &ip_layer.ipv6) {
+                    (Some(ipv4_header), _) => (
+                        IpAddr::V4(ipv4_header.source),
+                        IpAddr::V4(ipv4_header.destination),
+                        ipv4_header.ttl,
+                    ),
+                    (_, Some(ipv6_header)) => (
+                        IpAddr::V6(ipv6_header.source),
+                        IpAddr::V6(ipv6_header.destination),
+                        ipv6_header.hop_limit,
+                    ),
+                    _ => continue,
+                };
+            let state: &mut TargetState = match target_map.get(&source) {
+                Some(index) => &mut states[*index],
+                None => continue,
+            };
+            if state.replied || destination != state.src_ip {
+                continue;
+            }
+            let port_status: Option<PortStatus> = match state.setting.protocol {
+                Protocol::ICMP => {
+                    let echo_reply: bool = ip_layer
+                        .icmp
+                        .as_ref()
+                        .map_or(false, |icmp| icmp.icmp_type == IcmpType::EchoReply)
+                        || ip_layer
+                            .icmpv6
+                            .as_ref()
+                            .map_or(false, |icmpv6| icmpv6.icmpv6_type == Icmpv6Type::EchoReply);
+                    if !echo_reply
+                        || crate::packet::icmp::get_echo_id_seq(&packet, ip_offset)
+                            != Some((echo_id, seq as u16))
+                    {
+                        continue;
+                    }
+                    None
+                }
+                Protocol::TCP => {
+                    let tcp_header = match frame.transport.as_ref().and_then(|t| t.tcp.as_ref()) {
+                        Some(tcp_header) => tcp_header,
+                        None => continue,
+                    };
+                    if Some(tcp_header.source) != state.setting.dst_port {
+                        continue;
+                    }
+                    if tcp_header.flags == TcpFlags::SYN | TcpFlags::ACK {
+                        Some(PortStatus::Open)
+                    } else if tcp_header.flags == TcpFlags::RST | TcpFlags::ACK {
+                        Some(PortStatus::Closed)
+                    } else {
+                        continue;
+                    }
+                }
+                _ => {
+                    let unreachable: bool =
+                        ip_layer.icmp.as_ref().map_or(false, |icmp| {
+                            icmp.icmp_type == IcmpType::DestinationUnreachable
+                        }) || ip_layer.icmpv6.as_ref().map_or(false, |icmpv6| {
+                            icmpv6.icmpv6_type == Icmpv6Type::DestinationUnreachable
+                        });
+                    if !unreachable {
+                        continue;
+                    }
+                    Some(PortStatus::Closed)
+                }
+            };
+            let mut mac_addr: MacAddr = MacAddr::zero();
+            if let Some(datalink_layer) = &frame.datalink {
+                if let Some(ethernet_header) = &datalink_layer.ethernet {
+                    mac_addr = ethernet_header.source;
+                }
+            }
+            state.responses.push(ProbeResult {
+                seq: seq,
+                mac_addr: mac_addr,
+                ip_addr: state.setting.dst_ip,
+                host_name: state.setting.dst_hostname.clone(),
+                port_number: state.setting.dst_port,
+                port_status: port_status,
+                ttl: ttl,
+                hop: crate::ip::guess_initial_ttl(ttl) - ttl,
+                rtt: send_time.elapsed(),
+                probe_status: ProbeStatus::new(),
+                protocol: state.setting.protocol.clone(),
+                node_type: NodeType::Destination,
+                sent_packet_size: state.packet.len(),
+                received_packet_size: packet.len(),
+                mpls_labels: Vec::new(),
+                ip_options: None,
+            });
+            state.replied = true;
+            pending -= 1;
+        }
+        for state in &mut states {
+            if !state.replied {
+                state.responses.push(ProbeResult::timeout(
+                    seq,
+                    state.setting.dst_ip,
+                    state.setting.dst_hostname.clone(),
+                    state.setting.protocol.clone(),
+                    state.packet.len(),
+                ));
+            }
+        }
+        if base.count == 0 || seq < base.count {
+            wait_next(next_wait_time(&base, send_time, start_time), stop_handle);
+        }
+    }
+    let probe_time = Instant::now().duration_since(start_time);
+    let end_date = crate::sys::time::get_sysdate();
+    let mut results: Vec<PingResult> = Vec::new();
+    for state in states {
+        let mut result = PingResult::new();
+        result.protocol = state.setting.protocol;
+        result.start_time = start_date.clone();
+        result.end_time = end_date.clone();
+        result.elapsed_time = probe_time;
+        let received_count: usize = state
+            .responses
+            .iter()
+            .filter(|r| r.probe_status.kind == ProbeStatusKind::Done)
+            .count();
+        if received_count == 0 {
+            result.probe_status = ProbeStatus::with_error_message("No response".to_string());
+        } else {
+            result.stat = PingStat::from_responses(state.responses, probe_time);
+            result.probe_status = ProbeStatus::new();
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
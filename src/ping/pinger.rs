@@ -1,6 +1,7 @@
 use super::result::{PingResult, PingStat};
 use super::setting::PingSetting;
 use crate::host::{NodeType, PortStatus};
+use crate::packet::ipv4::parse_ipv4_options;
 use crate::packet::setting::PacketBuildSetting;
 use crate::probe::{ProbeResult, ProbeStatus, ProbeStatusKind};
 use crate::protocol::Protocol;
@@ -11,6 +12,7 @@ use nex::packet::frame::{Frame, ParseOption};
 use nex::packet::icmp::IcmpType;
 use nex::packet::icmpv6::Icmpv6Type;
 use nex::packet::tcp::TcpFlags;
+use std::collections::HashMap;
 use std::net::IpAddr;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
@@ -27,6 +29,8 @@ pub struct Pinger {
     tx: Arc<Mutex<Sender<ProbeResult>>>,
     /// Receiver for progress messaging
     rx: Arc<Mutex<Receiver<ProbeResult>>>,
+    /// Stop handle. Set true to stop ping after the current probe
+    stop_handle: Arc<Mutex<bool>>,
 }
 
 impl Pinger {
@@ -44,22 +48,32 @@ impl Pinger {
             ping_setting: setting,
             tx: Arc::new(Mutex::new(tx)),
             rx: Arc::new(Mutex::new(rx)),
+            stop_handle: Arc::new(Mutex::new(false)),
         };
         return Ok(pinger);
     }
     /// Run ping
     pub fn ping(&self) -> Result<PingResult, String> {
-        run_ping(&self.ping_setting, &self.tx)
+        run_ping(&self.ping_setting, &self.tx, &self.stop_handle)
     }
     /// Get progress receiver
     pub fn get_progress_receiver(&self) -> Arc<Mutex<Receiver<ProbeResult>>> {
         self.rx.clone()
     }
+    /// Get stop handle
+    pub fn get_stop_handle(&self) -> Arc<Mutex<bool>> {
+        self.stop_handle.clone()
+    }
+    /// Set stop handle. e.g. the global stop handle set by Ctrl-C
+    pub fn set_stop_handle(&mut self, stop_handle: Arc<Mutex<bool>>) {
+        self.stop_handle = stop_handle;
+    }
 }
 
 fn run_ping(
     setting: &PingSetting,
     msg_tx: &Arc<Mutex<Sender<ProbeResult>>>,
+    stop_handle: &Arc<Mutex<bool>>,
 ) -> Result<PingResult, String> {
     let interface: Interface = match crate::interface::get_interface_by_index(setting.if_index) {
         Some(interface) => interface,
@@ -73,7 +87,12 @@ fn run_ping(
     let config = nex::datalink::Config {
         write_buffer_size: 4096,
         read_buffer_size: 4096,
-        read_timeout: Some(setting.receive_timeout),
+        // Flood mode reads in short steps to keep the send pace
+        read_timeout: Some(if setting.flood {
+            Duration::from_millis(crate::config::FLOOD_PING_INTERVAL_MILLIS)
+        } else {
+            setting.receive_timeout
+        }),
         write_timeout: None,
         channel_type: nex::datalink::ChannelType::Layer2,
         bpf_fd_attempts: 1000,
@@ -88,15 +107,15 @@ fn run_ping(
     };
     match setting.protocol {
         crate::protocol::Protocol::ICMP => {
-            let result = icmp_ping(&mut tx, &mut rx, setting, msg_tx);
+            let result = icmp_ping(&mut tx, &mut rx, setting, msg_tx, stop_handle);
             return Ok(result);
         }
         crate::protocol::Protocol::TCP => {
-            let result = tcp_ping(&mut tx, &mut rx, setting, msg_tx);
+            let result = tcp_ping(&mut tx, &mut rx, setting, msg_tx, stop_handle);
             return Ok(result);
         }
         crate::protocol::Protocol::UDP => {
-            let result = udp_ping(&mut tx, &mut rx, setting, msg_tx);
+            let result = udp_ping(&mut tx, &mut rx, setting, msg_tx, stop_handle);
             return Ok(result);
         }
         _ => {
@@ -110,7 +129,11 @@ pub fn icmp_ping(
     rx: &mut Box<dyn RawReceiver>,
     setting: &PingSetting,
     msg_tx: &Arc<Mutex<Sender<ProbeResult>>>,
+    stop_handle: &Arc<Mutex<bool>>,
 ) -> PingResult {
+    if setting.flood {
+        return icmp_flood_ping(tx, rx, setting, msg_tx, stop_handle);
+    }
     let mut result = PingResult::new();
     result.protocol = Protocol::ICMP;
     let mut parse_option: ParseOption = ParseOption::default();
@@ -119,12 +142,24 @@ pub fn icmp_ping(
         parse_option.from_ip_packet = true;
         parse_option.offset = payload_offset;
     }
+    // Offset of IP header in received packet
+    let ip_offset: usize = if setting.tunnel {
+        parse_option.offset
+    } else {
+        14
+    };
     result.start_time = crate::sys::time::get_sysdate();
     let start_time = Instant::now();
     let mut responses: Vec<ProbeResult> = Vec::new();
     let packet_setting: PacketBuildSetting = PacketBuildSetting::from_ping_setting(setting);
     let icmp_packet: Vec<u8> = crate::packet::icmp::build_icmp_packet(packet_setting.clone());
-    for seq in 1..setting.count + 1 {
+    let mut seq: u32 = 0;
+    // Count 0 means ping until stopped
+    while setting.count == 0 || seq < setting.count {
+        if is_stopped(stop_handle) || is_deadline_exceeded(setting, start_time) {
+            break;
+        }
+        seq += 1;
         //let icmp_packet: Vec<u8> = crate::packet::icmp::build_icmp_packet(PacketBuildSetting::from_ping_setting(setting));
         let send_time = Instant::now();
         match tx.send(&icmp_packet) {
@@ -171,6 +206,12 @@ pub fn icmp_ping(
                                         node_type: NodeType::Destination,
                                         sent_packet_size: icmp_packet.len(),
                                         received_packet_size: packet.len(),
+                                        mpls_labels: Vec::new(),
+                                        ip_options: if setting.ipv4_option.is_some() {
+                                            parse_ipv4_options(&packet, ip_offset)
+                                        } else {
+                                            None
+                                        },
                                     };
                                     responses.push(probe_result.clone());
                                     match msg_tx.lock() {
@@ -210,6 +251,8 @@ pub fn icmp_ping(
                                         node_type: NodeType::Destination,
                                         sent_packet_size: icmp_packet.len(),
                                         received_packet_size: packet.len(),
+                                        mpls_labels: Vec::new(),
+                                        ip_options: None,
                                     };
                                     responses.push(probe_result.clone());
                                     match msg_tx.lock() {
@@ -264,8 +307,8 @@ pub fn icmp_ping(
                 break;
             }
         }
-        if seq < setting.count {
-            std::thread::sleep(setting.send_rate);
+        if setting.count == 0 || seq < setting.count {
+            wait_next(next_wait_time(setting, send_time, start_time), stop_handle);
         }
     }
     let probe_time = Instant::now().duration_since(start_time);
@@ -278,27 +321,184 @@ pub fn icmp_ping(
     if received_count == 0 {
         result.probe_status = ProbeStatus::with_error_message("No response".to_string());
     } else {
-        let ping_stat: PingStat = PingStat {
-            responses: responses.clone(),
-            probe_time: probe_time,
-            transmitted_count: setting.count as usize,
-            received_count: received_count,
-            min: responses
-                .iter()
-                .map(|r| r.rtt)
-                .min()
-                .unwrap_or(Duration::from_millis(0)),
-            avg: responses
-                .iter()
-                .fold(Duration::from_millis(0), |acc, r| acc + r.rtt)
-                / received_count as u32,
-            max: responses
-                .iter()
-                .map(|r| r.rtt)
-                .max()
-                .unwrap_or(Duration::from_millis(0)),
-        };
-        result.stat = ping_stat;
+        result.stat = PingStat::from_responses(responses, probe_time);
+        result.probe_status = ProbeStatus::new();
+    }
+    result
+}
+
+/// Flood ping. Sends ICMP echo requests every FLOOD_PING_INTERVAL_MILLIS without waiting
+/// for replies. Replies are matched to requests by identifier and sequence number,
+/// so a reply arriving after the next request is still counted for its own request.
+fn icmp_flood_ping(
+    tx: &mut Box<dyn RawSender>,
+    rx: &mut Box<dyn RawReceiver>,
+    setting: &PingSetting,
+    msg_tx: &Arc<Mutex<Sender<ProbeResult>>>,
+    stop_handle: &Arc<Mutex<bool>>,
+) -> PingResult {
+    let mut result = PingResult::new();
+    result.protocol = Protocol::ICMP;
+    let mut parse_option: ParseOption = ParseOption::default();
+    if setting.tunnel {
+        let payload_offset = if setting.loopback { 14 } else { 0 };
+        parse_option.from_ip_packet = true;
+        parse_option.offset = payload_offset;
+    }
+    // Offset of IP header in received packet
+    let ip_offset: usize = if setting.tunnel {
+        parse_option.offset
+    } else {
+        14
+    };
+    let send_interval: Duration = Duration::from_millis(crate::config::FLOOD_PING_INTERVAL_MILLIS);
+    result.start_time = crate::sys::time::get_sysdate();
+    let start_time = Instant::now();
+    let mut responses: Vec<ProbeResult> = Vec::new();
+    let packet_setting: PacketBuildSetting = PacketBuildSetting::from_ping_setting(setting);
+    let mut icmp_packet: Vec<u8> = crate::packet::icmp::build_icmp_packet(packet_setting.clone());
+    // Offset of IP header in sent packet
+    let send_ip_offset: usize = if packet_setting.ip_packet { 0 } else { 14 };
+    let echo_id: u16 = std::process::id() as u16;
+    // Requests waiting for reply. ICMP sequence number -> (seq, send time)
+    let mut in_flight: HashMap<u16, (u32, Instant)> = HashMap::new();
+    let mut seq: u32 = 0;
+    let mut next_send_time: Instant = start_time;
+    let send_progress = |probe_result: ProbeResult| match msg_tx.lock() {
+        Ok(lr) => match lr.send(probe_result) {
+            Ok(_) => {}
+            Err(_) => {}
+        },
+        Err(_) => {}
+    };
+    loop {
+        let sending: bool = (setting.count == 0 || seq < setting.count)
+            && !is_stopped(stop_handle)
+            && !is_deadline_exceeded(setting, start_time);
+        // Wait for the replies of requests already sent before finishing
+        if !sending && in_flight.is_empty() {
+            break;
+        }
+        if sending && Instant::now() >= next_send_time {
+            seq += 1;
+            crate::packet::icmp::set_echo_id_seq(
+                &mut icmp_packet,
+                send_ip_offset,
+                echo_id,
+                seq as u16,
+            );
+            match tx.send(&icmp_packet) {
+                Some(_) => {}
+                None => {}
+            }
+            let send_time: Instant = Instant::now();
+            in_flight.insert(seq as u16, (seq, send_time));
+            next_send_time = std::cmp::max(next_send_time + send_interval, send_time);
+        }
+        if let Ok(packet) = rx.next() {
+            let frame: Frame = Frame::from_bytes(&packet, parse_option.clone());
+            let mut mac_addr: MacAddr = MacAddr::zero();
+            if let Some(datalink_layer) = &frame.datalink {
+                if let Some(ethernet_header) = &datalink_layer.ethernet {
+                    mac_addr = ethernet_header.source;
+                }
+            }
+            // Source, destination and hop limit of echo reply
+            let reply: Option<(IpAddr, IpAddr, u8)> = match &frame.ip {
+                Some(ip_layer) => match (&ip_layer.ipv4, &ip_layer.ipv6) {
+                    (Some(ipv4_header), _)
+                        if ip_layer
+                            .icmp
+                            .as_ref()
+                            .map_or(false, |icmp| icmp.icmp_type == IcmpType::EchoReply) =>
+                    {
+                        Some((
+                            IpAddr::V4(ipv4_header.source),
+                            IpAddr::V4(ipv4_header.destination),
+                            ipv4_header.ttl,
+                        ))
+                    }
+                    (_, Some(ipv6_header))
+                        if ip_layer.icmpv6.as_ref().map_or(false, |icmpv6| {
+                            icmpv6.icmpv6_type == Icmpv6Type::EchoReply
+                        }) =>
+                    {
+                        Some((
+                            IpAddr::V6(ipv6_header.source),
+                            IpAddr::V6(ipv6_header.destination),
+                            ipv6_header.hop_limit,
+                        ))
+                    }
+                    _ => None,
+                },
+                None => None,
+            };
+            if let Some((source, destination, ttl)) = reply {
+                if source == setting.dst_ip && destination == packet_setting.src_ip {
+                    if let Some((id, icmp_seq)) =
+                        crate::packet::icmp::get_echo_id_seq(&packet, ip_offset)
+                    {
+                        if id == echo_id {
+                            if let Some((reply_seq, send_time)) = in_flight.remove(&icmp_seq) {
+                                let probe_result: ProbeResult = ProbeResult {
+                                    seq: reply_seq,
+                                    mac_addr: mac_addr,
+                                    ip_addr: setting.dst_ip,
+                                    host_name: setting.dst_hostname.clone(),
+                                    port_number: None,
+                                    port_status: None,
+                                    ttl: ttl,
+                                    hop: crate::ip::guess_initial_ttl(ttl) - ttl,
+                                    rtt: send_time.elapsed(),
+                                    probe_status: ProbeStatus::new(),
+                                    protocol: Protocol::ICMP,
+                                    node_type: NodeType::Destination,
+                                    sent_packet_size: icmp_packet.len(),
+                                    received_packet_size: packet.len(),
+                                    mpls_labels: Vec::new(),
+                                    ip_options: None,
+                                };
+                                responses.push(probe_result.clone());
+                                send_progress(probe_result);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // Requests without reply within receive_timeout are lost
+        let mut expired: Vec<(u16, u32)> = in_flight
+            .iter()
+            .filter(|(_, (_, send_time))| send_time.elapsed() > setting.receive_timeout)
+            .map(|(icmp_seq, (seq, _))| (*icmp_seq, *seq))
+            .collect();
+        expired.sort_by_key(|(_, seq)| *seq);
+        for (icmp_seq, expired_seq) in expired {
+            in_flight.remove(&icmp_seq);
+            let probe_result = ProbeResult::timeout(
+                expired_seq,
+                setting.dst_ip,
+                setting.dst_hostname.clone(),
+                Protocol::ICMP,
+                icmp_packet.len(),
+            );
+            responses.push(probe_result.clone());
+            send_progress(probe_result);
+        }
+    }
+    // Replies and timeouts are reported out of order
+    responses.sort_by_key(|r| r.seq);
+    let probe_time = Instant::now().duration_since(start_time);
+    result.end_time = crate::sys::time::get_sysdate();
+    result.elapsed_time = probe_time;
+    let received_count: usize = responses
+        .iter()
+        .filter(|r| r.probe_status.kind == ProbeStatusKind::Done)
+        .count();
+    if received_count == 0 {
+        result.probe_status = ProbeStatus::with_error_message("No response".to_string());
+    } else {
+        result.stat = PingStat::from_responses(responses, probe_time);
         result.probe_status = ProbeStatus::new();
     }
     result
@@ -309,6 +509,7 @@ pub fn tcp_ping(
     rx: &mut Box<dyn RawReceiver>,
     setting: &PingSetting,
     msg_tx: &Arc<Mutex<Sender<ProbeResult>>>,
+    stop_handle: &Arc<Mutex<bool>>,
 ) -> PingResult {
     let mut result = PingResult::new();
     result.protocol = Protocol::ICMP;
@@ -323,7 +524,13 @@ pub fn tcp_ping(
     let mut responses: Vec<ProbeResult> = Vec::new();
     let packet_setting: PacketBuildSetting = PacketBuildSetting::from_ping_setting(setting);
     let tcp_packet: Vec<u8> = crate::packet::tcp::build_tcp_syn_packet(packet_setting.clone());
-    for seq in 1..setting.count + 1 {
+    let mut seq: u32 = 0;
+    // Count 0 means ping until stopped
+    while setting.count == 0 || seq < setting.count {
+        if is_stopped(stop_handle) || is_deadline_exceeded(setting, start_time) {
+            break;
+        }
+        seq += 1;
         //let tcp_packet: Vec<u8> = crate::packet::tcp::build_tcp_packet(setting.clone(), None);
         let send_time = Instant::now();
         match tx.send(&tcp_packet) {
@@ -381,6 +588,8 @@ pub fn tcp_ping(
                                     node_type: NodeType::Destination,
                                     sent_packet_size: tcp_packet.len(),
                                     received_packet_size: packet.len(),
+                                    mpls_labels: Vec::new(),
+                                    ip_options: None,
                                 };
                                 if tcp_header.flags == TcpFlags::SYN | TcpFlags::ACK {
                                     probe_result.port_status = Some(PortStatus::Open);
@@ -482,8 +691,8 @@ pub fn tcp_ping(
                 break;
             }
         }
-        if seq < setting.count {
-            std::thread::sleep(setting.send_rate);
+        if setting.count == 0 || seq < setting.count {
+            wait_next(next_wait_time(setting, send_time, start_time), stop_handle);
         }
     }
     let probe_time = Instant::now().duration_since(start_time);
@@ -496,27 +705,7 @@ pub fn tcp_ping(
     if received_count == 0 {
         result.probe_status = ProbeStatus::with_error_message("No response".to_string());
     } else {
-        let ping_stat: PingStat = PingStat {
-            responses: responses.clone(),
-            probe_time: probe_time,
-            transmitted_count: setting.count as usize,
-            received_count: received_count,
-            min: responses
-                .iter()
-                .map(|r| r.rtt)
-                .min()
-                .unwrap_or(Duration::from_millis(0)),
-            avg: responses
-                .iter()
-                .fold(Duration::from_millis(0), |acc, r| acc + r.rtt)
-                / received_count as u32,
-            max: responses
-                .iter()
-                .map(|r| r.rtt)
-                .max()
-                .unwrap_or(Duration::from_millis(0)),
-        };
-        result.stat = ping_stat;
+        result.stat = PingStat::from_responses(responses, probe_time);
         result.probe_status = ProbeStatus::new();
     }
     result
@@ -527,6 +716,7 @@ pub fn udp_ping(
     rx: &mut Box<dyn RawReceiver>,
     setting: &PingSetting,
     msg_tx: &Arc<Mutex<Sender<ProbeResult>>>,
+    stop_handle: &Arc<Mutex<bool>>,
 ) -> PingResult {
     let mut result = PingResult::new();
     result.protocol = Protocol::UDP;
@@ -541,7 +731,13 @@ pub fn udp_ping(
     let mut responses: Vec<ProbeResult> = Vec::new();
     let packet_setting: PacketBuildSetting = PacketBuildSetting::from_ping_setting(setting);
     let udp_packet: Vec<u8> = crate::packet::udp::build_udp_packet(packet_setting.clone());
-    for seq in 1..setting.count + 1 {
+    let mut seq: u32 = 0;
+    // Count 0 means ping until stopped
+    while setting.count == 0 || seq < setting.count {
+        if is_stopped(stop_handle) || is_deadline_exceeded(setting, start_time) {
+            break;
+        }
+        seq += 1;
         //let udp_packet: Vec<u8> = crate::packet::udp::build_udp_packet(setting.clone(), None);
         let send_time = Instant::now();
         match tx.send(&udp_packet) {
@@ -588,6 +784,8 @@ pub fn udp_ping(
                                         node_type: NodeType::Destination,
                                         sent_packet_size: udp_packet.len(),
                                         received_packet_size: packet.len(),
+                                        mpls_labels: Vec::new(),
+                                        ip_options: None,
                                     };
                                     responses.push(probe_result.clone());
                                     match msg_tx.lock() {
@@ -625,6 +823,8 @@ pub fn udp_ping(
                                         node_type: NodeType::Destination,
                                         sent_packet_size: udp_packet.len(),
                                         received_packet_size: packet.len(),
+                                        mpls_labels: Vec::new(),
+                                        ip_options: None,
                                     };
                                     responses.push(probe_result.clone());
                                     match msg_tx.lock() {
@@ -679,8 +879,8 @@ pub fn udp_ping(
                 break;
             }
         }
-        if seq < setting.count {
-            std::thread::sleep(setting.send_rate);
+        if setting.count == 0 || seq < setting.count {
+            wait_next(next_wait_time(setting, send_time, start_time), stop_handle);
         }
     }
     let probe_time = Instant::now().duration_since(start_time);
@@ -693,28 +893,55 @@ pub fn udp_ping(
     if received_count == 0 {
         result.probe_status = ProbeStatus::with_error_message("No response".to_string());
     } else {
-        let ping_stat: PingStat = PingStat {
-            responses: responses.clone(),
-            probe_time: probe_time,
-            transmitted_count: setting.count as usize,
-            received_count: received_count,
-            min: responses
-                .iter()
-                .map(|r| r.rtt)
-                .min()
-                .unwrap_or(Duration::from_millis(0)),
-            avg: responses
-                .iter()
-                .fold(Duration::from_millis(0), |acc, r| acc + r.rtt)
-                / received_count as u32,
-            max: responses
-                .iter()
-                .map(|r| r.rtt)
-                .max()
-                .unwrap_or(Duration::from_millis(0)),
-        };
-        result.stat = ping_stat;
+        result.stat = PingStat::from_responses(responses, probe_time);
         result.probe_status = ProbeStatus::new();
     }
     result
 }
+
+pub(crate) fn is_stopped(stop_handle: &Arc<Mutex<bool>>) -> bool {
+    match stop_handle.lock() {
+        Ok(stop) => *stop,
+        Err(_) => false,
+    }
+}
+
+/// Time to wait before the next probe.
+///
+/// interval is measured from the send time, so the next probe is sent
+/// as soon as the reply arrives once the interval has elapsed.
+pub(crate) fn next_wait_time(
+    setting: &PingSetting,
+    send_time: Instant,
+    start_time: Instant,
+) -> Duration {
+    let wait_time: Duration = match setting.interval {
+        Some(interval) => interval.saturating_sub(send_time.elapsed()),
+        None => setting.send_rate,
+    };
+    match setting.deadline {
+        Some(deadline) => std::cmp::min(wait_time, deadline.saturating_sub(start_time.elapsed())),
+        None => wait_time,
+    }
+}
+
+pub(crate) fn is_deadline_exceeded(setting: &PingSetting, start_time: Instant) -> bool {
+    match setting.deadline {
+        Some(deadline) => start_time.elapsed() >= deadline,
+        None => false,
+    }
+}
+
+/// Sleep until the next probe. Returns early if stopped
+pub(crate) fn wait_next(send_rate: Duration, stop_handle: &Arc<Mutex<bool>>) {
+    let start_time = Instant::now();
+    while start_time.elapsed() < send_rate {
+        if is_stopped(stop_handle) {
+            return;
+        }
+        std::thread::sleep(std::cmp::min(
+            send_rate.saturating_sub(start_time.elapsed()),
+            Duration::from_millis(10),
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+use super::pinger::Pinger;
+use super::result::PmtuResult;
+use super::setting::PingSetting;
+use crate::probe::ProbeStatusKind;
+use std::net::IpAddr;
+use std::time::Instant;
+
+/// IPv4 header + ICMP echo header
+const IPV4_ICMP_HEADER_LEN: usize = 28;
+/// IPv6 header + ICMPv6 echo header
+const IPV6_ICMP_HEADER_LEN: usize = 48;
+
+/// Discover the path MTU to the destination.
+///
+/// Binary-searches the largest ICMP echo payload that reaches the destination without fragmentation.
+pub fn discover_pmtu(setting: &PingSetting, max_mtu: usize) -> Result<PmtuResult, String> {
+    let header_len: usize = match setting.dst_ip {
+        IpAddr::V4(_) => IPV4_ICMP_HEADER_LEN,
+        IpAddr::V6(_) => IPV6_ICMP_HEADER_LEN,
+    };
+    if max_mtu <= header_len {
+        return Err(format!("Invalid MTU: {}", max_mtu));
+    }
+    let start_time = Instant::now();
+    let mut result: PmtuResult = PmtuResult::new(setting.dst_ip);
+    // Check reachability with minimum payload
+    result.probes += 1;
+    if !probe_payload_size(setting, 0)? {
+        return Err(format!("{} is down or unreachable", setting.dst_ip));
+    }
+    let mut low: usize = 0;
+    let mut high: usize = max_mtu - header_len;
+    result.probes += 1;
+    if probe_payload_size(setting, high)? {
+        low = high;
+    } else {
+        // low: largest size known to pass, high: smallest size known to fail
+        while high - low > 1 {
+            let mid: usize = low + (high - low) / 2;
+            result.probes += 1;
+            if probe_payload_size(setting, mid)? {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+    }
+    result.payload_size = low;
+    result.mtu = low + header_len;
+    result.elapsed_time = start_time.elapsed();
+    Ok(result)
+}
+
+/// Send single echo request with Don't Fragment bit set. Returns true if echo reply received.
+fn probe_payload_size(setting: &PingSetting, payload_size: usize) -> Result<bool, String> {
+    let mut probe_setting: PingSetting = setting.clone();
+    probe_setting.count = 1;
+    probe_setting.payload_size = payload_size;
+    probe_setting.dont_fragment = true;
+    let pinger: Pinger = Pinger::new(probe_setting)?;
+    match pinger.ping() {
+        Ok(ping_result) => Ok(ping_result.probe_status.kind == ProbeStatusKind::Done),
+        Err(e) => Err(e),
+    }
+}
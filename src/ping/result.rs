@@ -1,6 +1,10 @@
-use crate::probe::{ProbeResult, ProbeStatus};
+use crate::db::geoip::GeoInfo;
+use crate::dns::asn::AsnInfo;
+use crate::probe::{ProbeResult, ProbeStatus, ProbeStatusKind};
 use crate::protocol::Protocol;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -19,6 +23,18 @@ pub struct PingStat {
     pub avg: Duration,
     /// Maximum RTT
     pub max: Duration,
+    /// Standard deviation of RTT
+    pub mdev: Duration,
+    /// Mean difference between consecutive RTTs
+    pub jitter: Duration,
+    /// 50th percentile (median) RTT
+    pub p50: Duration,
+    /// 95th percentile RTT
+    pub p95: Duration,
+    /// 99th percentile RTT
+    pub p99: Duration,
+    /// RTT of each probe in milliseconds, in sequence order. None if lost
+    pub rtt_series: Vec<Option<f64>>,
 }
 
 impl PingStat {
@@ -31,8 +47,70 @@ impl PingStat {
             min: Duration::from_millis(0),
             avg: Duration::from_millis(0),
             max: Duration::from_millis(0),
+            mdev: Duration::from_millis(0),
+            jitter: Duration::from_millis(0),
+            p50: Duration::from_millis(0),
+            p95: Duration::from_millis(0),
+            p99: Duration::from_millis(0),
+            rtt_series: Vec::new(),
         }
     }
+    /// Calculate statistics from responses. RTT statistics use received responses only
+    pub fn from_responses(responses: Vec<ProbeResult>, probe_time: Duration) -> PingStat {
+        let rtts: Vec<Duration> = responses
+            .iter()
+            .filter(|r| r.probe_status.kind == ProbeStatusKind::Done)
+            .map(|r| r.rtt)
+            .collect();
+        let mut stat: PingStat = PingStat::new();
+        stat.transmitted_count = responses.len();
+        stat.received_count = rtts.len();
+        stat.probe_time = probe_time;
+        stat.rtt_series = responses
+            .iter()
+            .map(|r| {
+                if r.probe_status.kind == ProbeStatusKind::Done {
+                    Some(r.rtt.as_secs_f64() * 1000.0)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        stat.responses = responses;
+        if rtts.is_empty() {
+            return stat;
+        }
+        let mut sorted: Vec<Duration> = rtts.clone();
+        sorted.sort();
+        stat.min = sorted[0];
+        stat.max = sorted[sorted.len() - 1];
+        let avg_secs: f64 =
+            rtts.iter().map(|rtt| rtt.as_secs_f64()).sum::<f64>() / rtts.len() as f64;
+        stat.avg = Duration::from_secs_f64(avg_secs);
+        let variance: f64 = rtts
+            .iter()
+            .map(|rtt| (rtt.as_secs_f64() - avg_secs).powi(2))
+            .sum::<f64>()
+            / rtts.len() as f64;
+        stat.mdev = Duration::from_secs_f64(variance.sqrt());
+        if rtts.len() > 1 {
+            let diff_sum: f64 = rtts
+                .windows(2)
+                .map(|w| (w[1].as_secs_f64() - w[0].as_secs_f64()).abs())
+                .sum();
+            stat.jitter = Duration::from_secs_f64(diff_sum / (rtts.len() - 1) as f64);
+        }
+        stat.p50 = percentile(&sorted, 50.0);
+        stat.p95 = percentile(&sorted, 95.0);
+        stat.p99 = percentile(&sorted, 99.0);
+        stat
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &Vec<Duration>, p: f64) -> Duration {
+    let rank: usize = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -61,6 +139,76 @@ impl PingResult {
     }
 }
 
+/// Ping result of a target in multi-target ping
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HostPingResult {
+    /// Target given by user
+    pub target: String,
+    /// Resolved IP address. None if resolution failed
+    pub ip_addr: Option<IpAddr>,
+    /// True if any reply received
+    pub alive: bool,
+    pub stat: PingStat,
+    /// Error message for unreachable target
+    pub message: String,
+}
+
+impl HostPingResult {
+    pub fn new(target: String, ip_addr: Option<IpAddr>) -> HostPingResult {
+        HostPingResult {
+            target: target,
+            ip_addr: ip_addr,
+            alive: false,
+            stat: PingStat::new(),
+            message: String::new(),
+        }
+    }
+}
+
+/// Result of multi-target ping
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiPingResult {
+    pub hosts: Vec<HostPingResult>,
+    /// Elapsed time
+    pub elapsed_time: Duration,
+}
+
+impl MultiPingResult {
+    pub fn new() -> MultiPingResult {
+        MultiPingResult {
+            hosts: Vec::new(),
+            elapsed_time: Duration::from_millis(0),
+        }
+    }
+}
+
+/// Result of path MTU discovery
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PmtuResult {
+    /// Destination IP address
+    pub dst_ip: IpAddr,
+    /// Path MTU in bytes
+    pub mtu: usize,
+    /// Largest ICMP echo payload sent without fragmentation
+    pub payload_size: usize,
+    /// Number of probes sent
+    pub probes: usize,
+    /// Elapsed time
+    pub elapsed_time: Duration,
+}
+
+impl PmtuResult {
+    pub fn new(dst_ip: IpAddr) -> PmtuResult {
+        PmtuResult {
+            dst_ip: dst_ip,
+            mtu: 0,
+            payload_size: 0,
+            probes: 0,
+            elapsed_time: Duration::from_millis(0),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TracerouteResult {
     pub nodes: Vec<ProbeResult>,
@@ -72,6 +220,15 @@ pub struct TracerouteResult {
     /// Elapsed time
     pub elapsed_time: Duration,
     pub protocol: Protocol,
+    /// Origin AS of each hop
+    #[serde(default)]
+    pub asns: HashMap<IpAddr, AsnInfo>,
+    /// Geolocation of each hop
+    #[serde(default)]
+    pub geo: HashMap<IpAddr, GeoInfo>,
+    /// Statistics of each hop
+    #[serde(default)]
+    pub hops: Vec<TraceHopStat>,
 }
 
 impl TracerouteResult {
@@ -83,6 +240,291 @@ impl TracerouteResult {
             end_time: String::new(),
             elapsed_time: Duration::from_millis(0),
             protocol: Protocol::UDP,
+            asns: HashMap::new(),
+            geo: HashMap::new(),
+            hops: Vec::new(),
+        }
+    }
+}
+
+/// Statistics of probes sent with the same TTL
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TraceHopStat {
+    /// TTL of the probes
+    pub ttl: u8,
+    /// Responding addresses
+    pub ip_addrs: Vec<IpAddr>,
+    /// Number of probes sent
+    pub sent: usize,
+    /// Number of replies received
+    pub received: usize,
+    /// Packet loss in percent
+    pub loss_percent: f64,
+    /// Minimum RTT
+    pub min_rtt: Duration,
+    /// Average RTT
+    pub avg_rtt: Duration,
+    /// Maximum RTT
+    pub max_rtt: Duration,
+}
+
+impl TraceHopStat {
+    /// Aggregate probe results by TTL
+    pub fn from_nodes(nodes: &[ProbeResult]) -> Vec<TraceHopStat> {
+        let mut hops: Vec<TraceHopStat> = Vec::new();
+        for node in nodes {
+            let ttl: u8 = node.seq as u8;
+            let index: usize = match hops.iter().position(|hop| hop.ttl == ttl) {
+                Some(index) => index,
+                None => {
+                    hops.push(TraceHopStat {
+                        ttl: ttl,
+                        ip_addrs: Vec::new(),
+                        sent: 0,
+                        received: 0,
+                        loss_percent: 0.0,
+                        min_rtt: Duration::from_millis(0),
+                        avg_rtt: Duration::from_millis(0),
+                        max_rtt: Duration::from_millis(0),
+                    });
+                    hops.len() - 1
+                }
+            };
+            let hop: &mut TraceHopStat = &mut hops[index];
+            hop.sent += 1;
+            if node.probe_status.kind != ProbeStatusKind::Done {
+                continue;
+            }
+            if !hop.ip_addrs.contains(&node.ip_addr) {
+                hop.ip_addrs.push(node.ip_addr);
+            }
+            if hop.received == 0 || node.rtt < hop.min_rtt {
+                hop.min_rtt = node.rtt;
+            }
+            if node.rtt > hop.max_rtt {
+                hop.max_rtt = node.rtt;
+            }
+            // Running sum. Divided below
+            hop.avg_rtt += node.rtt;
+            hop.received += 1;
+        }
+        for hop in hops.iter_mut() {
+            if hop.received > 0 {
+                hop.avg_rtt = hop.avg_rtt / hop.received as u32;
+            }
+            hop.loss_percent = (hop.sent - hop.received) as f64 / hop.sent as f64 * 100.0;
+        }
+        hops
+    }
+}
+
+/// Traceroute result of single flow
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FlowTraceResult {
+    /// Flow number
+    pub flow_id: usize,
+    /// Source port identifying the flow
+    pub src_port: u16,
+    pub result: TracerouteResult,
+}
+
+/// Result of multi-flow traceroute for enumerating ECMP paths
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiFlowTraceResult {
+    pub flows: Vec<FlowTraceResult>,
+    /// Distinct paths. None for hop without response
+    pub paths: Vec<Vec<Option<IpAddr>>>,
+    /// Elapsed time
+    pub elapsed_time: Duration,
+}
+
+impl MultiFlowTraceResult {
+    pub fn new() -> MultiFlowTraceResult {
+        MultiFlowTraceResult {
+            flows: Vec::new(),
+            paths: Vec::new(),
+            elapsed_time: Duration::from_millis(0),
+        }
+    }
+    /// Add flow result and record its path if not seen yet
+    pub fn add_flow(&mut self, flow: FlowTraceResult) {
+        let path: Vec<Option<IpAddr>> = flow
+            .result
+            .hops
+            .iter()
+            .map(|hop| hop.ip_addrs.first().cloned())
+            .collect();
+        if !self.paths.contains(&path) {
+            self.paths.push(path);
+        }
+        self.flows.push(flow);
+    }
+}
+
+/// Traceroute result of a target in multi-target traceroute
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TargetTraceResult {
+    /// Target as specified
+    pub target: String,
+    pub ip_addr: IpAddr,
+    pub result: TracerouteResult,
+}
+
+/// Node of the merged topology. Responding hops are shared between targets
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TopologyNode {
+    /// IP address for responding hop, `*<ttl>@<target>` for hop without response,
+    /// and `local` for the source host
+    pub id: String,
+    pub ip_addr: Option<IpAddr>,
+    pub host_name: String,
+    /// Targets whose path goes through this node
+    pub targets: Vec<String>,
+}
+
+/// Link between adjacent hops of the merged topology
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TopologyEdge {
+    /// Node ID of the nearer hop
+    pub src: String,
+    /// Node ID of the farther hop
+    pub dst: String,
+    /// Targets whose path uses this link
+    pub targets: Vec<String>,
+}
+
+/// Result of multi-target traceroute with the paths merged into one topology
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiTargetTraceResult {
+    pub targets: Vec<TargetTraceResult>,
+    pub nodes: Vec<TopologyNode>,
+    pub edges: Vec<TopologyEdge>,
+    /// Elapsed time
+    pub elapsed_time: Duration,
+}
+
+impl MultiTargetTraceResult {
+    /// Node ID of the source host
+    pub const LOCAL_NODE_ID: &'static str = "local";
+    pub fn new() -> MultiTargetTraceResult {
+        MultiTargetTraceResult {
+            targets: Vec::new(),
+            nodes: vec![TopologyNode {
+                id: Self::LOCAL_NODE_ID.to_string(),
+                ip_addr: None,
+                host_name: String::from("localhost"),
+                targets: Vec::new(),
+            }],
+            edges: Vec::new(),
+            elapsed_time: Duration::from_millis(0),
+        }
+    }
+    /// Add target result and merge its path into the topology
+    pub fn add_target(&mut self, target: TargetTraceResult) {
+        let mut prev_id: String = Self::LOCAL_NODE_ID.to_string();
+        self.add_node_target(&prev_id, &target.target);
+        for hop in &target.result.hops {
+            let (id, ip_addr) = match hop.ip_addrs.first() {
+                Some(ip_addr) => (ip_addr.to_string(), Some(*ip_addr)),
+                None => (format!("*{}@{}", hop.ttl, target.target), None),
+            };
+            if !self.nodes.iter().any(|node| node.id == id) {
+                let host_name: String = match ip_addr {
+                    Some(ip_addr) => target
+                        .result
+                        .nodes
+                        .iter()
+                        .find(|node| node.ip_addr == ip_addr && !node.host_name.is_empty())
+                        .map(|node| node.host_name.clone())
+                        .unwrap_or(ip_addr.to_string()),
+                    None => String::from("*"),
+                };
+                self.nodes.push(TopologyNode {
+                    id: id.clone(),
+                    ip_addr: ip_addr,
+                    host_name: host_name,
+                    targets: Vec::new(),
+                });
+            }
+            self.add_node_target(&id, &target.target);
+            match self
+                .edges
+                .iter_mut()
+                .find(|edge| edge.src == prev_id && edge.dst == id)
+            {
+                Some(edge) => {
+                    if !edge.targets.contains(&target.target) {
+                        edge.targets.push(target.target.clone());
+                    }
+                }
+                None => self.edges.push(TopologyEdge {
+                    src: prev_id.clone(),
+                    dst: id.clone(),
+                    targets: vec![target.target.clone()],
+                }),
+            }
+            prev_id = id;
+        }
+        self.targets.push(target);
+    }
+    fn add_node_target(&mut self, id: &str, target: &String) {
+        if let Some(node) = self.nodes.iter_mut().find(|node| node.id == id) {
+            if !node.targets.contains(target) {
+                node.targets.push(target.clone());
+            }
+        }
+    }
+}
+
+/// Network map built from traceroute topology
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TraceMap {
+    pub name: String,
+    pub nodes: Vec<TopologyNode>,
+    pub edges: Vec<TopologyEdge>,
+}
+
+impl TraceMap {
+    pub fn new(name: &str) -> TraceMap {
+        TraceMap {
+            name: name.to_string(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+    /// Merge nodes and edges of the topology. Existing nodes and edges are kept
+    /// and the targets of the new ones are added to them
+    pub fn merge(&mut self, topology: &MultiTargetTraceResult) {
+        for node in &topology.nodes {
+            match self.nodes.iter_mut().find(|n| n.id == node.id) {
+                Some(n) => {
+                    for target in &node.targets {
+                        if !n.targets.contains(target) {
+                            n.targets.push(target.clone());
+                        }
+                    }
+                    if n.ip_addr.map(|ip| ip.to_string()) == Some(n.host_name.clone()) {
+                        n.host_name = node.host_name.clone();
+                    }
+                }
+                None => self.nodes.push(node.clone()),
+            }
+        }
+        for edge in &topology.edges {
+            match self
+                .edges
+                .iter_mut()
+                .find(|e| e.src == edge.src && e.dst == edge.dst)
+            {
+                Some(e) => {
+                    for target in &edge.targets {
+                        if !e.targets.contains(target) {
+                            e.targets.push(target.clone());
+                        }
+                    }
+                }
+                None => self.edges.push(edge.clone()),
+            }
         }
     }
 }
@@ -1,10 +1,12 @@
 use std::net::Ipv4Addr;
 use std::{net::IpAddr, time::Duration};
 
+use netdev::mac::MacAddr;
 use netdev::Interface;
 use serde::{Deserialize, Serialize};
 
 use crate::config::{DEFAULT_HOP_LIMIT, DEFAULT_PING_COUNT};
+use crate::packet::ipv4::Ipv4OptionType;
 use crate::protocol::Protocol;
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -13,12 +15,29 @@ pub struct PingSetting {
     pub dst_hostname: String,
     pub dst_ip: IpAddr,
     pub dst_port: Option<u16>,
+    /// Destination MAC address for on-link target. Gateway MAC address is used if None
+    pub dst_mac: Option<MacAddr>,
     pub hop_limit: u8,
     pub protocol: Protocol,
     pub count: u32,
     pub receive_timeout: Duration,
     pub probe_timeout: Duration,
     pub send_rate: Duration,
+    /// Interval between the start of each probe. Used instead of send_rate if set
+    pub interval: Option<Duration>,
+    /// Flood mode. Send ICMP echo requests at a fixed pace without waiting for replies.
+    /// Replies are matched to requests by identifier and sequence number
+    pub flood: bool,
+    /// Stop the ping session after this time regardless of count
+    pub deadline: Option<Duration>,
+    /// ICMP echo payload size in bytes
+    pub payload_size: usize,
+    /// Set IPv4 Don't Fragment bit
+    pub dont_fragment: bool,
+    /// IPv4 option (Record Route or Timestamp) added to ICMP echo request
+    pub ipv4_option: Option<Ipv4OptionType>,
+    /// TOS (IPv4) or Traffic Class (IPv6) of probes
+    pub tos: u8,
     pub tunnel: bool,
     pub loopback: bool,
 }
@@ -30,12 +49,20 @@ impl Default for PingSetting {
             dst_hostname: "localhost".to_string(),
             dst_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
             dst_port: None,
+            dst_mac: None,
             hop_limit: DEFAULT_HOP_LIMIT,
             protocol: Protocol::ICMP,
             count: DEFAULT_PING_COUNT,
             receive_timeout: Duration::from_secs(1),
             probe_timeout: Duration::from_secs(30),
             send_rate: Duration::from_secs(1),
+            interval: None,
+            flood: false,
+            deadline: None,
+            payload_size: 0,
+            dont_fragment: false,
+            ipv4_option: None,
+            tos: 0,
             tunnel: false,
             loopback: false,
         }
@@ -56,12 +83,20 @@ impl PingSetting {
             dst_ip: dst_ip_addr,
             dst_hostname: dst_ip_addr.to_string(),
             dst_port: None,
+            dst_mac: None,
             hop_limit: 64,
             count: count,
             protocol: Protocol::ICMP,
             receive_timeout: Duration::from_secs(1),
             probe_timeout: Duration::from_secs(30),
             send_rate: Duration::from_secs(1),
+            interval: None,
+            flood: false,
+            deadline: None,
+            payload_size: 0,
+            dont_fragment: false,
+            ipv4_option: None,
+            tos: 0,
             tunnel: use_tun,
             loopback: loopback,
         };
@@ -81,12 +116,20 @@ impl PingSetting {
             dst_ip: dst_ip_addr,
             dst_hostname: dst_ip_addr.to_string(),
             dst_port: Some(dst_port),
+            dst_mac: None,
             hop_limit: 64,
             count: count,
             protocol: Protocol::TCP,
             receive_timeout: Duration::from_secs(1),
             probe_timeout: Duration::from_secs(30),
             send_rate: Duration::from_secs(1),
+            interval: None,
+            flood: false,
+            deadline: None,
+            payload_size: 0,
+            dont_fragment: false,
+            ipv4_option: None,
+            tos: 0,
             tunnel: use_tun,
             loopback: loopback,
         };
@@ -105,12 +148,20 @@ impl PingSetting {
             dst_ip: dst_ip_addr,
             dst_hostname: dst_ip_addr.to_string(),
             dst_port: Some(crate::config::DEFAULT_BASE_TARGET_UDP_PORT),
+            dst_mac: None,
             hop_limit: 64,
             count: count,
             protocol: Protocol::UDP,
             receive_timeout: Duration::from_secs(1),
             probe_timeout: Duration::from_secs(30),
             send_rate: Duration::from_secs(1),
+            interval: None,
+            flood: false,
+            deadline: None,
+            payload_size: 0,
+            dont_fragment: false,
+            ipv4_option: None,
+            tos: 0,
             tunnel: use_tun,
             loopback: loopback,
         };
@@ -1,4 +1,6 @@
 use crate::host::{NodeType, PortStatus};
+use crate::packet::ipv4::Ipv4OptionData;
+use crate::packet::mpls::MplsLabel;
 use crate::protocol::Protocol;
 use nex::net::mac::MacAddr;
 use serde::{Deserialize, Serialize};
@@ -83,6 +85,12 @@ pub struct ProbeResult {
     pub sent_packet_size: usize,
     /// Received packet size
     pub received_packet_size: usize,
+    /// MPLS label stack from ICMP extensions of the reply
+    #[serde(default)]
+    pub mpls_labels: Vec<MplsLabel>,
+    /// Decoded IPv4 options of the reply
+    #[serde(default)]
+    pub ip_options: Option<Ipv4OptionData>,
 }
 
 impl ProbeResult {
@@ -102,6 +110,8 @@ impl ProbeResult {
             node_type: NodeType::Destination,
             sent_packet_size: 0,
             received_packet_size: 0,
+            mpls_labels: Vec::new(),
+            ip_options: None,
         }
     }
     pub fn timeout(
@@ -129,6 +139,8 @@ impl ProbeResult {
             node_type: NodeType::Destination,
             sent_packet_size: sent_packet_size,
             received_packet_size: 0,
+            mpls_labels: Vec::new(),
+            ip_options: None,
         }
     }
     pub fn trace_timeout(
@@ -155,6 +167,8 @@ impl ProbeResult {
             node_type: node_type,
             sent_packet_size: sent_packet_size,
             received_packet_size: 0,
+            mpls_labels: Vec::new(),
+            ip_options: None,
         }
     }
 }
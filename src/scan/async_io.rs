@@ -15,11 +15,14 @@ use super::setting::{HostScanSetting, PortScanSetting};
 use crate::config::PCAP_WAIT_TIME_MILLIS;
 use crate::packet::frame::PacketFrame;
 use crate::pcap::PacketCaptureOptions;
+use nex::packet::ethernet::EtherType;
 use nex::packet::ip::IpNextLevelProtocol;
 use std::collections::HashSet;
 use std::thread;
 
-use super::result::{parse_hostscan_result, parse_portscan_result, ScanStatus};
+use super::result::{
+    parse_hostscan_result, parse_portscan_result, spawn_host_notifier, ScanStatus,
+};
 use super::setting::{HostScanType, PortScanType};
 
 pub(crate) async fn send_portscan_packets(
@@ -115,13 +118,26 @@ pub(crate) async fn send_hostscan_packets(
                     };
                     AsyncSocket::new(socket_option).unwrap()
                 }
+                // ARP can not be sent via raw IP socket
+                HostScanType::ArpScan => return,
             };
             let dst_socket_addr: SocketAddr = SocketAddr::new(dst.ip_addr, 0);
-            let packet_bytes =
-                build_hostscan_ip_next_packet(&interface, &dst, &scan_setting.scan_type);
-            match socket.send_to(&packet_bytes, dst_socket_addr).await {
-                Ok(_) => {}
-                Err(_) => {}
+            // TCP ping probes every specified port. Other types send a single probe per host.
+            let ports: Vec<u16> = match scan_setting.scan_type {
+                HostScanType::TcpPingScan if dst.ports.len() > 0 => dst.get_ports(),
+                _ => vec![dst.ports.first().map(|port| port.number).unwrap_or(0)],
+            };
+            for port in ports {
+                let packet_bytes = build_hostscan_ip_next_packet(
+                    &interface,
+                    dst.ip_addr,
+                    port,
+                    &scan_setting.scan_type,
+                );
+                match socket.send_to(&packet_bytes, dst_socket_addr).await {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
             }
             match ptx.lock() {
                 Ok(lr) => match lr.send(dst) {
@@ -177,6 +193,9 @@ pub async fn try_connect_ports(
                     status: PortStatus::Open,
                     service_name: String::new(),
                     service_version: String::new(),
+                    ssh: None,
+                    cpes: Vec::new(),
+                    cves: Vec::new(),
                 });
             }
             Err(_) => {
@@ -191,7 +210,12 @@ pub async fn try_connect_ports(
         mac_addr: target.mac_addr,
         vendor_name: target.vendor_name,
         os_family: String::new(),
+        os_hint: String::new(),
+        discovered_by: Vec::new(),
         ttl: target.ttl,
+        geo: None,
+        smb: None,
+        external: None,
     }
 }
 
@@ -235,6 +259,7 @@ pub fn run_connect_scan(
 pub(crate) async fn scan_hosts(
     scan_setting: HostScanSetting,
     ptx: &Arc<Mutex<Sender<Host>>>,
+    ftx: &Arc<Mutex<Sender<Host>>>,
 ) -> ScanResult {
     let interface = match crate::interface::get_interface_by_index(scan_setting.if_index) {
         Some(interface) => interface,
@@ -305,15 +330,25 @@ pub(crate) async fn scan_hosts(
                 .ip_protocols
                 .insert(IpNextLevelProtocol::Icmpv6);
         }
+        HostScanType::ArpScan => {
+            capture_options.ether_types.insert(EtherType::Arp);
+        }
     }
     let stop: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
     let stop_handle = Arc::clone(&stop);
     let packets: Arc<Mutex<Vec<PacketFrame>>> = Arc::new(Mutex::new(vec![]));
     let receive_packets: Arc<Mutex<Vec<PacketFrame>>> = Arc::clone(&packets);
+    // Report hosts as their responses are captured
+    let (frame_tx, frame_rx) = mpsc::channel();
+    let notify_handler = spawn_host_notifier(frame_rx, scan_setting.clone(), Arc::clone(ftx));
     // Spawn pcap thread
     let pcap_handler = thread::spawn(move || {
-        let packets: Vec<PacketFrame> =
-            crate::pcap::start_capture(&mut rx, capture_options, &stop_handle);
+        let packets: Vec<PacketFrame> = crate::pcap::start_capture_with_sender(
+            &mut rx,
+            capture_options,
+            &stop_handle,
+            frame_tx,
+        );
         match receive_packets.lock() {
             Ok(mut receive_packets) => {
                 for p in packets {
@@ -348,6 +383,12 @@ pub(crate) async fn scan_hosts(
             eprintln!("Failed to join pcap_handler: {:?}", e);
         }
     }
+    match notify_handler.join() {
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to join notify_handler: {:?}", e);
+        }
+    }
 
     let mut scan_result: ScanResult = ScanResult::new();
     match packets.lock() {
@@ -5,16 +5,19 @@ use crate::pcap::PacketCaptureOptions;
 use crate::scan::setting::{HostScanSetting, PortScanSetting};
 use netdev::Interface;
 use nex::datalink::RawSender;
+use nex::packet::ethernet::EtherType;
 use nex::packet::ip::IpNextLevelProtocol;
 use std::collections::HashSet;
 use std::net::SocketAddr;
-use std::sync::mpsc::Sender;
+use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
 use super::packet::{build_hostscan_packet, build_portscan_packet};
-use super::result::{parse_hostscan_result, parse_portscan_result, ScanResult, ScanStatus};
+use super::result::{
+    parse_hostscan_result, parse_portscan_result, spawn_host_notifier, ScanResult, ScanStatus,
+};
 use super::setting::{HostScanType, PortScanType};
 
 pub(crate) fn send_hostscan_packets(
@@ -23,6 +26,7 @@ pub(crate) fn send_hostscan_packets(
     targets: Vec<Host>,
     ptx: &Arc<Mutex<Sender<Host>>>,
     scan_type: HostScanType,
+    send_rate: Duration,
 ) {
     // Acquire message sender lock
     let ptx_lock = match ptx.lock() {
@@ -33,19 +37,28 @@ pub(crate) fn send_hostscan_packets(
         }
     };
     for target in targets {
-        let packet = build_hostscan_packet(&interface, &target, &scan_type, false);
-        match tx.send(&packet) {
-            Some(_) => {
-                // Notify packet sent
-                match ptx_lock.send(target) {
-                    Ok(_) => {}
-                    Err(e) => {
-                        eprintln!("Failed to send message: {}", e);
-                    }
+        // TCP ping probes every specified port. Other types send a single probe per host.
+        let ports: Vec<u16> = match scan_type {
+            HostScanType::TcpPingScan if target.ports.len() > 0 => target.get_ports(),
+            _ => vec![target.ports.first().map(|port| port.number).unwrap_or(0)],
+        };
+        for port in ports {
+            let packet = build_hostscan_packet(&interface, target.ip_addr, port, &scan_type, false);
+            match tx.send(&packet) {
+                Some(_) => {}
+                None => {
+                    eprintln!("Failed to send packet");
                 }
             }
-            None => {
-                eprintln!("Failed to send packet");
+            if !send_rate.is_zero() {
+                thread::sleep(send_rate);
+            }
+        }
+        // Notify packet sent
+        match ptx_lock.send(target) {
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Failed to send message: {}", e);
             }
         }
     }
@@ -102,6 +115,7 @@ pub(crate) fn send_portscan_packets(
 pub(crate) fn scan_hosts(
     scan_setting: HostScanSetting,
     ptx: &Arc<Mutex<Sender<Host>>>,
+    ftx: &Arc<Mutex<Sender<Host>>>,
 ) -> ScanResult {
     let interface = match crate::interface::get_interface_by_index(scan_setting.if_index) {
         Some(interface) => interface,
@@ -139,8 +153,15 @@ pub(crate) fn scan_hosts(
         tunnel: interface.is_tun(),
         loopback: interface.is_loopback(),
     };
-    for target in scan_setting.targets.clone() {
-        capture_options.src_ips.insert(target.ip_addr);
+    // Responses to multicast probes come from unknown sources. Do not filter by source in that case.
+    if !scan_setting
+        .targets
+        .iter()
+        .any(|target| target.ip_addr.is_multicast())
+    {
+        for target in scan_setting.targets.clone() {
+            capture_options.src_ips.insert(target.ip_addr);
+        }
     }
     match scan_setting.scan_type {
         HostScanType::IcmpPingScan => {
@@ -172,15 +193,30 @@ pub(crate) fn scan_hosts(
                 .ip_protocols
                 .insert(IpNextLevelProtocol::Icmpv6);
         }
+        HostScanType::ArpScan => {
+            capture_options.ether_types.insert(EtherType::Arp);
+            // NDP for IPv6 targets
+            capture_options.ether_types.insert(EtherType::Ipv6);
+            capture_options
+                .ip_protocols
+                .insert(IpNextLevelProtocol::Icmpv6);
+        }
     }
     let stop: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
     let stop_handle = Arc::clone(&stop);
     let packets: Arc<Mutex<Vec<PacketFrame>>> = Arc::new(Mutex::new(vec![]));
     let receive_packets: Arc<Mutex<Vec<PacketFrame>>> = Arc::clone(&packets);
+    // Report hosts as their responses are captured
+    let (frame_tx, frame_rx) = channel();
+    let notify_handler = spawn_host_notifier(frame_rx, scan_setting.clone(), Arc::clone(ftx));
     // Spawn pcap thread
     let pcap_handler = thread::spawn(move || {
-        let packets: Vec<PacketFrame> =
-            crate::pcap::start_capture(&mut rx, capture_options, &stop_handle);
+        let packets: Vec<PacketFrame> = crate::pcap::start_capture_with_sender(
+            &mut rx,
+            capture_options,
+            &stop_handle,
+            frame_tx,
+        );
         match receive_packets.lock() {
             Ok(mut receive_packets) => {
                 for p in packets {
@@ -202,6 +238,7 @@ pub(crate) fn scan_hosts(
         scan_setting.targets.clone(),
         ptx,
         scan_setting.scan_type.clone(),
+        scan_setting.send_rate,
     );
     thread::sleep(scan_setting.wait_time);
     // Stop pcap
@@ -220,6 +257,12 @@ pub(crate) fn scan_hosts(
             eprintln!("Failed to join pcap_handler: {:?}", e);
         }
     }
+    match notify_handler.join() {
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to join notify_handler: {:?}", e);
+        }
+    }
     let mut scan_result: ScanResult = ScanResult::new();
     match packets.lock() {
         Ok(packets) => {
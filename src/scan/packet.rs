@@ -1,14 +1,15 @@
 use super::setting::HostScanType;
-use crate::config::{DEFAULT_HOP_LIMIT, DEFAULT_LOCAL_TCP_PORT, DEFAULT_LOCAL_UDP_PORT};
-use crate::host::Host;
+use crate::config::{DEFAULT_HOP_LIMIT, DEFAULT_LOCAL_TCP_PORT};
 use crate::packet::setting::PacketBuildSetting;
 use netdev::Interface;
 use nex::net::ip::is_global_ipv6;
-use std::net::IpAddr;
+use rand::Rng;
+use std::net::{IpAddr, Ipv6Addr};
 
 pub(crate) fn build_hostscan_packet(
     interface: &Interface,
-    target_host: &Host,
+    target_ip_addr: IpAddr,
+    target_port: u16,
     scan_type: &HostScanType,
     ip_packet: bool,
 ) -> Vec<u8> {
@@ -19,7 +20,7 @@ pub(crate) fn build_hostscan_packet(
     if let Some(gateway) = &interface.gateway {
         build_setting.dst_mac = gateway.mac_addr;
     }
-    match target_host.ip_addr {
+    match target_ip_addr {
         IpAddr::V4(ipv4_addr) => {
             interface.ipv4.iter().for_each(|ipv4| {
                 build_setting.src_ip = IpAddr::V4(ipv4.addr);
@@ -27,7 +28,14 @@ pub(crate) fn build_hostscan_packet(
             build_setting.dst_ip = IpAddr::V4(ipv4_addr);
         }
         IpAddr::V6(ipv6_addr) => {
-            if is_global_ipv6(&ipv6_addr) {
+            if ipv6_addr.is_multicast() {
+                // Prefer global address so that responders reply from their global address.
+                // Fall back to link-local address.
+                build_setting.src_ip = crate::interface::get_interface_global_ipv6(interface)
+                    .or(crate::interface::get_interface_local_ipv6(interface))
+                    .unwrap_or(IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+                build_setting.dst_mac = crate::ip::ipv6_multicast_mac(&ipv6_addr);
+            } else if is_global_ipv6(&ipv6_addr) {
                 interface.ipv6.iter().for_each(|ipv6| {
                     if is_global_ipv6(&ipv6.addr) {
                         build_setting.src_ip = IpAddr::V6(ipv6.addr);
@@ -41,29 +49,34 @@ pub(crate) fn build_hostscan_packet(
             build_setting.dst_ip = IpAddr::V6(ipv6_addr);
         }
     }
-    if target_host.ports.len() > 0 {
-        build_setting.dst_port = target_host.ports[0].number;
-    }
+    build_setting.dst_port = target_port;
     build_setting.hop_limit = DEFAULT_HOP_LIMIT;
+    // Randomize IP identification per probe
+    build_setting.ip_id = Some(rand::random::<u16>());
     if ip_packet || interface.is_tun() || interface.is_loopback() {
         build_setting.ip_packet = true;
     }
     match scan_type {
         HostScanType::IcmpPingScan => crate::packet::icmp::build_icmp_packet(build_setting),
         HostScanType::TcpPingScan => {
-            build_setting.src_port = DEFAULT_LOCAL_TCP_PORT;
+            build_setting.src_port = random_source_port();
             crate::packet::tcp::build_tcp_syn_packet(build_setting)
         }
         HostScanType::UdpPingScan => {
-            build_setting.src_port = DEFAULT_LOCAL_UDP_PORT;
+            build_setting.src_port = random_source_port();
             crate::packet::udp::build_udp_packet(build_setting)
         }
+        HostScanType::ArpScan => match target_ip_addr {
+            IpAddr::V4(_) => crate::packet::arp::build_arp_packet(build_setting),
+            IpAddr::V6(_) => crate::packet::ndp::build_ndp_packet(build_setting),
+        },
     }
 }
 
 pub(crate) fn build_hostscan_ip_next_packet(
     interface: &Interface,
-    target_host: &Host,
+    target_ip_addr: IpAddr,
+    target_port: u16,
     scan_type: &HostScanType,
 ) -> Vec<u8> {
     let mut build_setting = PacketBuildSetting::new();
@@ -73,7 +86,7 @@ pub(crate) fn build_hostscan_ip_next_packet(
     if let Some(gateway) = &interface.gateway {
         build_setting.dst_mac = gateway.mac_addr;
     }
-    match target_host.ip_addr {
+    match target_ip_addr {
         IpAddr::V4(ipv4_addr) => {
             interface.ipv4.iter().for_each(|ipv4| {
                 build_setting.src_ip = IpAddr::V4(ipv4.addr);
@@ -81,7 +94,14 @@ pub(crate) fn build_hostscan_ip_next_packet(
             build_setting.dst_ip = IpAddr::V4(ipv4_addr);
         }
         IpAddr::V6(ipv6_addr) => {
-            if is_global_ipv6(&ipv6_addr) {
+            if ipv6_addr.is_multicast() {
+                // Prefer global address so that responders reply from their global address.
+                // Fall back to link-local address.
+                build_setting.src_ip = crate::interface::get_interface_global_ipv6(interface)
+                    .or(crate::interface::get_interface_local_ipv6(interface))
+                    .unwrap_or(IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+                build_setting.dst_mac = crate::ip::ipv6_multicast_mac(&ipv6_addr);
+            } else if is_global_ipv6(&ipv6_addr) {
                 interface.ipv6.iter().for_each(|ipv6| {
                     if is_global_ipv6(&ipv6.addr) {
                         build_setting.src_ip = IpAddr::V6(ipv6.addr);
@@ -95,9 +115,7 @@ pub(crate) fn build_hostscan_ip_next_packet(
             build_setting.dst_ip = IpAddr::V6(ipv6_addr);
         }
     }
-    if target_host.ports.len() > 0 {
-        build_setting.dst_port = target_host.ports[0].number;
-    }
+    build_setting.dst_port = target_port;
     build_setting.hop_limit = DEFAULT_HOP_LIMIT;
     if interface.is_tun() || interface.is_loopback() {
         build_setting.ip_packet = true;
@@ -105,13 +123,15 @@ pub(crate) fn build_hostscan_ip_next_packet(
     match scan_type {
         HostScanType::IcmpPingScan => crate::packet::icmp::build_ip_next_icmp_packet(build_setting),
         HostScanType::TcpPingScan => {
-            build_setting.src_port = DEFAULT_LOCAL_TCP_PORT;
+            build_setting.src_port = random_source_port();
             crate::packet::tcp::build_ip_next_tcp_syn_packet(build_setting)
         }
         HostScanType::UdpPingScan => {
-            build_setting.src_port = DEFAULT_LOCAL_UDP_PORT;
+            build_setting.src_port = random_source_port();
             crate::packet::udp::build_ip_next_udp_packet(build_setting)
         }
+        // ARP has no IP layer. ARP scan is always sent via the datalink channel.
+        HostScanType::ArpScan => Vec::new(),
     }
 }
 
@@ -201,3 +221,8 @@ pub(crate) fn build_portscan_ip_next_packet(
     build_setting.src_port = DEFAULT_LOCAL_TCP_PORT;
     crate::packet::tcp::build_ip_next_tcp_syn_packet(build_setting)
 }
+
+/// Get random source port from the dynamic (ephemeral) port range
+fn random_source_port() -> u16 {
+    rand::thread_rng().gen_range(49152..=65535)
+}
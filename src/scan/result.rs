@@ -1,11 +1,18 @@
 use netdev::mac::MacAddr;
 use netdev::Interface;
+use nex::packet::arp::ArpOperation;
+use nex::packet::icmp::IcmpType;
+use nex::packet::icmpv6::Icmpv6Type;
 use nex::packet::tcp::TcpFlags;
 
 use crate::host::{Host, Port, PortStatus};
 use crate::packet::frame::PacketFrame;
+use crate::protocol::Protocol;
 use std::collections::HashSet;
 use std::net::{IpAddr, SocketAddr};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
 use super::setting::{HostScanSetting, HostScanType, PortScanSetting};
@@ -31,6 +38,9 @@ pub struct ScanResult {
     pub scan_status: ScanStatus,
     /// Captured packet fingerprints
     pub fingerprints: Vec<PacketFrame>,
+    /// Every IP/MAC pair seen in ARP/NDP replies, including repeated ones
+    #[serde(default)]
+    pub address_bindings: Vec<(IpAddr, MacAddr)>,
 }
 
 impl ScanResult {
@@ -40,6 +50,7 @@ impl ScanResult {
             scan_time: Duration::from_millis(0),
             scan_status: ScanStatus::Done,
             fingerprints: vec![],
+            address_bindings: vec![],
         }
     }
     pub fn error(message: String) -> ScanResult {
@@ -48,6 +59,7 @@ impl ScanResult {
             scan_time: Duration::from_millis(0),
             scan_status: ScanStatus::Error(message),
             fingerprints: vec![],
+            address_bindings: vec![],
         }
     }
     /// Returns IP addresses from the scan result
@@ -103,6 +115,48 @@ impl ScanResult {
         }
         None
     }
+    /// Merge other scan result into this result.
+    ///
+    /// Hosts found by both are combined into one host.
+    pub fn merge(&mut self, other: ScanResult) {
+        for other_host in other.hosts {
+            match self
+                .hosts
+                .iter_mut()
+                .find(|host| host.ip_addr == other_host.ip_addr)
+            {
+                Some(host) => {
+                    for port in other_host.ports {
+                        if !host
+                            .ports
+                            .iter()
+                            .any(|port_info| port_info.number == port.number)
+                        {
+                            host.ports.push(port);
+                        }
+                    }
+                    if host.mac_addr == MacAddr::zero() {
+                        host.mac_addr = other_host.mac_addr;
+                    }
+                    if host.ttl == 0 {
+                        host.ttl = other_host.ttl;
+                    }
+                    for protocol in other_host.discovered_by {
+                        if !host.discovered_by.contains(&protocol) {
+                            host.discovered_by.push(protocol);
+                        }
+                    }
+                }
+                None => self.hosts.push(other_host),
+            }
+        }
+        self.fingerprints.extend(other.fingerprints);
+        self.address_bindings.extend(other.address_bindings);
+        self.scan_time += other.scan_time;
+        if other.scan_status != ScanStatus::Done {
+            self.scan_status = other.scan_status;
+        }
+    }
     pub fn get_host(&self, ip_addr: IpAddr) -> Option<Host> {
         for host in self.hosts.iter() {
             if host.ip_addr == ip_addr {
@@ -111,8 +165,9 @@ impl ScanResult {
         }
         None
     }
+    /// Sort hosts by IP address value. IPv4 hosts come first, followed by IPv6 hosts.
     pub fn sort_hosts(&mut self) {
-        self.hosts.sort_by(|a, b| a.ip_addr.cmp(&b.ip_addr));
+        self.hosts.sort_by_key(|host| host.ip_addr);
     }
     pub fn sort_ports(&mut self) {
         for host in self.hosts.iter_mut() {
@@ -179,6 +234,195 @@ pub enum ServiceProbeError {
     CustomError(String),
 }
 
+/// Parse captured frame into a host if it is a response to the host scan probe
+pub(crate) fn parse_hostscan_frame(
+    p: &PacketFrame,
+    scan_setting: &HostScanSetting,
+    iface: &Interface,
+    iface_ips: &HashSet<IpAddr>,
+) -> Option<Host> {
+    let mac_addr: MacAddr;
+    if let Some(ethernet_frame) = &p.ethernet_header {
+        if ethernet_frame.destination != iface.mac_addr.unwrap_or(MacAddr::zero()) {
+            return None;
+        }
+        mac_addr = ethernet_frame.source;
+    } else {
+        mac_addr = MacAddr::zero();
+    }
+    let mut ports: Vec<Port> = vec![];
+    match scan_setting.scan_type {
+        HostScanType::IcmpPingScan => {
+            // Only echo replies. Other ICMPv6 messages (e.g. NDP) may be captured
+            // when source filter is disabled for multicast discovery.
+            let is_echo_reply: bool = match (&p.icmp_header, &p.icmpv6_header) {
+                (Some(icmp_header), _) => icmp_header.icmp_type == IcmpType::EchoReply,
+                (_, Some(icmpv6_header)) => icmpv6_header.icmpv6_type == Icmpv6Type::EchoReply,
+                _ => false,
+            };
+            if !is_echo_reply {
+                return None;
+            }
+        }
+        HostScanType::TcpPingScan => {
+            if p.tcp_header.is_none() {
+                return None;
+            }
+            if let Some(tcp_packet) = &p.tcp_header {
+                if tcp_packet.flags == TcpFlags::SYN | TcpFlags::ACK {
+                    let port_info: Port = Port {
+                        number: tcp_packet.source,
+                        status: PortStatus::Open,
+                        service_name: String::new(),
+                        service_version: String::new(),
+                        ssh: None,
+                        cpes: Vec::new(),
+                        cves: Vec::new(),
+                    };
+                    ports.push(port_info);
+                } else if tcp_packet.flags == TcpFlags::RST | TcpFlags::ACK {
+                    let port_info: Port = Port {
+                        number: tcp_packet.source,
+                        status: PortStatus::Closed,
+                        service_name: String::new(),
+                        service_version: String::new(),
+                        ssh: None,
+                        cpes: Vec::new(),
+                        cves: Vec::new(),
+                    };
+                    ports.push(port_info);
+                } else {
+                    return None;
+                }
+            } else {
+                return None;
+            }
+        }
+        HostScanType::UdpPingScan => {
+            if p.icmp_header.is_none() && p.icmp_header.is_none() {
+                return None;
+            }
+        }
+        HostScanType::ArpScan => match (&p.arp_header, &p.icmpv6_header) {
+            (Some(arp_header), _) => {
+                if arp_header.operation != ArpOperation::Reply {
+                    return None;
+                }
+            }
+            (_, Some(icmpv6_header)) => {
+                if icmpv6_header.icmpv6_type != Icmpv6Type::NeighborAdvertisement {
+                    return None;
+                }
+            }
+            _ => return None,
+        },
+    }
+    let mut host_info: Host = if let Some(arp_header) = &p.arp_header {
+        let ip_addr: IpAddr = IpAddr::V4(arp_header.sender_proto_addr);
+        Host {
+            ip_addr: ip_addr,
+            hostname: scan_setting
+                .dns_map
+                .get(&ip_addr)
+                .unwrap_or(&String::new())
+                .clone(),
+            ports: ports,
+            mac_addr: arp_header.sender_hw_addr,
+            vendor_name: String::new(),
+            os_family: String::new(),
+            os_hint: String::new(),
+            discovered_by: Vec::new(),
+            ttl: 0,
+            geo: None,
+            smb: None,
+            external: None,
+        }
+    } else if let Some(ipv4_packet) = &p.ipv4_header {
+        Host {
+            ip_addr: IpAddr::V4(ipv4_packet.source),
+            hostname: scan_setting
+                .dns_map
+                .get(&IpAddr::V4(ipv4_packet.source))
+                .unwrap_or(&String::new())
+                .clone(),
+            ports: ports,
+            mac_addr: if iface_ips.contains(&IpAddr::V4(ipv4_packet.source)) {
+                iface.mac_addr.unwrap_or(MacAddr::zero())
+            } else {
+                mac_addr
+            },
+            vendor_name: String::new(),
+            os_family: String::new(),
+            os_hint: String::new(),
+            discovered_by: Vec::new(),
+            ttl: ipv4_packet.ttl,
+            geo: None,
+            smb: None,
+            external: None,
+        }
+    } else if let Some(ipv6_packet) = &p.ipv6_header {
+        Host {
+            ip_addr: IpAddr::V6(ipv6_packet.source),
+            hostname: scan_setting
+                .dns_map
+                .get(&IpAddr::V6(ipv6_packet.source))
+                .unwrap_or(&String::new())
+                .clone(),
+            ports: ports,
+            mac_addr: if iface_ips.contains(&IpAddr::V6(ipv6_packet.source)) {
+                iface.mac_addr.unwrap_or(MacAddr::zero())
+            } else {
+                mac_addr
+            },
+            vendor_name: String::new(),
+            os_family: String::new(),
+            os_hint: String::new(),
+            discovered_by: Vec::new(),
+            ttl: ipv6_packet.hop_limit,
+            geo: None,
+            smb: None,
+            external: None,
+        }
+    } else {
+        return None;
+    };
+    host_info.discovered_by = match scan_setting.scan_type {
+        HostScanType::ArpScan if p.arp_header.is_none() => vec![Protocol::NDP],
+        _ => vec![scan_setting.scan_type.protocol()],
+    };
+    Some(host_info)
+}
+
+/// Spawn a thread that sends each host to `ftx` as soon as its response is captured
+pub(crate) fn spawn_host_notifier(
+    frame_rx: Receiver<PacketFrame>,
+    scan_setting: HostScanSetting,
+    ftx: Arc<Mutex<Sender<Host>>>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let iface: Interface = match crate::interface::get_interface_by_index(scan_setting.if_index)
+        {
+            Some(iface) => iface,
+            None => return,
+        };
+        let iface_ips: HashSet<IpAddr> = crate::interface::get_local_ips(scan_setting.if_index);
+        let mut found: HashSet<IpAddr> = HashSet::new();
+        for p in frame_rx.iter() {
+            if let Some(host) = parse_hostscan_frame(&p, &scan_setting, &iface, &iface_ips) {
+                if found.insert(host.ip_addr) {
+                    match ftx.lock() {
+                        Ok(ftx) => match ftx.send(host) {
+                            Ok(_) => {}
+                            Err(_) => {}
+                        },
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+    })
+}
+
 pub(crate) fn parse_hostscan_result(
     packets: Vec<PacketFrame>,
     scan_setting: HostScanSetting,
@@ -190,98 +434,37 @@ pub(crate) fn parse_hostscan_result(
     };
     let iface_ips: HashSet<IpAddr> = crate::interface::get_local_ips(scan_setting.if_index);
     for p in packets {
-        let mac_addr: MacAddr;
-        if let Some(ethernet_frame) = &p.ethernet_header {
-            if ethernet_frame.destination != iface.mac_addr.unwrap_or(MacAddr::zero()) {
-                continue;
-            }
-            mac_addr = ethernet_frame.source;
-        } else {
-            mac_addr = MacAddr::zero();
+        let host_info: Host = match parse_hostscan_frame(&p, &scan_setting, &iface, &iface_ips) {
+            Some(host_info) => host_info,
+            None => continue,
+        };
+        // Keep every reply to detect conflicting bindings
+        if let HostScanType::ArpScan = scan_setting.scan_type {
+            result
+                .address_bindings
+                .push((host_info.ip_addr, host_info.mac_addr));
         }
-        let mut ports: Vec<Port> = vec![];
-        match scan_setting.scan_type {
-            HostScanType::IcmpPingScan => {
-                if p.icmp_header.is_none() && p.icmpv6_header.is_none() {
-                    continue;
-                }
-            }
-            HostScanType::TcpPingScan => {
-                if p.tcp_header.is_none() {
-                    continue;
-                }
-                if let Some(tcp_packet) = &p.tcp_header {
-                    if tcp_packet.flags == TcpFlags::SYN | TcpFlags::ACK {
-                        let port_info: Port = Port {
-                            number: tcp_packet.source,
-                            status: PortStatus::Open,
-                            service_name: String::new(),
-                            service_version: String::new(),
-                        };
-                        ports.push(port_info);
-                    } else if tcp_packet.flags == TcpFlags::RST | TcpFlags::ACK {
-                        let port_info: Port = Port {
-                            number: tcp_packet.source,
-                            status: PortStatus::Closed,
-                            service_name: String::new(),
-                            service_version: String::new(),
-                        };
-                        ports.push(port_info);
-                    } else {
-                        continue;
+        // A host is up on the first response. Responses on other ports are merged into it.
+        match result
+            .hosts
+            .iter_mut()
+            .find(|host| host.ip_addr == host_info.ip_addr)
+        {
+            Some(host) => {
+                for port in host_info.ports {
+                    if !host
+                        .ports
+                        .iter()
+                        .any(|port_info| port_info.number == port.number)
+                    {
+                        host.ports.push(port);
                     }
-                } else {
-                    continue;
-                }
-            }
-            HostScanType::UdpPingScan => {
-                if p.icmp_header.is_none() && p.icmp_header.is_none() {
-                    continue;
                 }
             }
-        }
-        let host_info: Host = if let Some(ipv4_packet) = &p.ipv4_header {
-            Host {
-                ip_addr: IpAddr::V4(ipv4_packet.source),
-                hostname: scan_setting
-                    .dns_map
-                    .get(&IpAddr::V4(ipv4_packet.source))
-                    .unwrap_or(&String::new())
-                    .clone(),
-                ports: ports,
-                mac_addr: if iface_ips.contains(&IpAddr::V4(ipv4_packet.source)) {
-                    iface.mac_addr.unwrap_or(MacAddr::zero())
-                } else {
-                    mac_addr
-                },
-                vendor_name: String::new(),
-                os_family: String::new(),
-                ttl: ipv4_packet.ttl,
-            }
-        } else if let Some(ipv6_packet) = &p.ipv6_header {
-            Host {
-                ip_addr: IpAddr::V6(ipv6_packet.source),
-                hostname: scan_setting
-                    .dns_map
-                    .get(&IpAddr::V6(ipv6_packet.source))
-                    .unwrap_or(&String::new())
-                    .clone(),
-                ports: ports,
-                mac_addr: if iface_ips.contains(&IpAddr::V6(ipv6_packet.source)) {
-                    iface.mac_addr.unwrap_or(MacAddr::zero())
-                } else {
-                    mac_addr
-                },
-                vendor_name: String::new(),
-                os_family: String::new(),
-                ttl: ipv6_packet.hop_limit,
+            None => {
+                result.hosts.push(host_info);
+                result.fingerprints.push(p.clone());
             }
-        } else {
-            continue;
-        };
-        if !result.hosts.contains(&host_info) {
-            result.hosts.push(host_info);
-            result.fingerprints.push(p.clone());
         }
     }
     return result;
@@ -353,6 +536,9 @@ pub(crate) fn parse_portscan_result(
                     status: PortStatus::Open,
                     service_name: String::new(),
                     service_version: String::new(),
+                    ssh: None,
+                    cpes: Vec::new(),
+                    cves: Vec::new(),
                 }
             } else if tcp_packet.flags == TcpFlags::RST | TcpFlags::ACK {
                 Port {
@@ -360,6 +546,9 @@ pub(crate) fn parse_portscan_result(
                     status: PortStatus::Closed,
                     service_name: String::new(),
                     service_version: String::new(),
+                    ssh: None,
+                    cpes: Vec::new(),
+                    cves: Vec::new(),
                 }
             } else {
                 continue;
@@ -386,7 +575,12 @@ pub(crate) fn parse_portscan_result(
                 mac_addr: mac_addr,
                 vendor_name: String::new(),
                 os_family: String::new(),
+                os_hint: String::new(),
+                discovered_by: Vec::new(),
                 ttl: ttl,
+                geo: None,
+                smb: None,
+                external: None,
             };
             result.hosts.push(host_info);
         }
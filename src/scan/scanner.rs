@@ -1,5 +1,5 @@
 use crate::host::Host;
-use crate::scan::setting::{HostScanSetting, PortScanSetting};
+use crate::scan::setting::{HostScanSetting, HostScanType, PortScanSetting};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::mpsc::{channel, Receiver, Sender};
@@ -19,29 +19,52 @@ pub struct HostScanner {
     pub tx: Arc<Mutex<Sender<Host>>>,
     /// Receiver for progress messaging
     pub rx: Arc<Mutex<Receiver<Host>>>,
+    /// Sender for hosts confirmed up during the scan
+    pub found_tx: Arc<Mutex<Sender<Host>>>,
+    /// Receiver for hosts confirmed up during the scan
+    pub found_rx: Arc<Mutex<Receiver<Host>>>,
 }
 
 impl HostScanner {
     /// Create new HostScanner
     pub fn new(scan_setting: HostScanSetting) -> Self {
         let (tx, rx) = channel();
+        let (found_tx, found_rx) = channel();
         Self {
             scan_setting,
             tx: Arc::new(Mutex::new(tx)),
             rx: Arc::new(Mutex::new(rx)),
+            found_tx: Arc::new(Mutex::new(found_tx)),
+            found_rx: Arc::new(Mutex::new(found_rx)),
         }
     }
     /// Get progress receiver
     pub fn get_progress_receiver(&self) -> Arc<Mutex<Receiver<Host>>> {
         self.rx.clone()
     }
+    /// Get receiver for hosts confirmed up. Hosts are sent as soon as their responses are captured
+    pub fn get_found_receiver(&self) -> Arc<Mutex<Receiver<Host>>> {
+        self.found_rx.clone()
+    }
     // Scan hosts
     pub fn scan(&self) -> ScanResult {
-        if self.scan_setting.async_scan {
+        // ARP/NDP scan and multicast discovery require datalink channel
+        if self.scan_setting.async_scan
+            && !matches!(self.scan_setting.scan_type, HostScanType::ArpScan)
+            && !self
+                .scan_setting
+                .targets
+                .iter()
+                .any(|target| target.ip_addr.is_multicast())
+        {
             let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(async_io::scan_hosts(self.scan_setting.clone(), &self.tx))
+            rt.block_on(async_io::scan_hosts(
+                self.scan_setting.clone(),
+                &self.tx,
+                &self.found_tx,
+            ))
         } else {
-            blocking::scan_hosts(self.scan_setting.clone(), &self.tx)
+            blocking::scan_hosts(self.scan_setting.clone(), &self.tx, &self.found_tx)
         }
     }
 }
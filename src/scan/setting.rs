@@ -164,14 +164,19 @@ pub enum HostScanType {
     /// Send UDP packets to a probably closed port and check response.
     /// This expects ICMP port unreachable message.
     UdpPingScan,
+    /// Send ARP requests to hosts on the local segment and check replies.
+    ///
+    /// Finds hosts that block ICMP and collects MAC addresses in one pass.
+    ArpScan,
 }
 
 impl HostScanType {
     pub fn from_str(scan_type: &str) -> HostScanType {
-        match scan_type {
+        match scan_type.to_uppercase().as_str() {
             "ICMP" | "ICMP-PING" | "ICMP_PING" => HostScanType::IcmpPingScan,
             "TCP" | "TCP-PING" | "TCP_PING" => HostScanType::TcpPingScan,
             "UDP" | "UDP-PING" | "UDP_PING" => HostScanType::UdpPingScan,
+            "ARP" | "ARP-SCAN" | "ARP_SCAN" => HostScanType::ArpScan,
             _ => HostScanType::IcmpPingScan,
         }
     }
@@ -180,6 +185,15 @@ impl HostScanType {
             HostScanType::IcmpPingScan => "ICMP-PING",
             HostScanType::TcpPingScan => "TCP-PING",
             HostScanType::UdpPingScan => "UDP-PING",
+            HostScanType::ArpScan => "ARP-SCAN",
+        }
+    }
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            HostScanType::IcmpPingScan => Protocol::ICMP,
+            HostScanType::TcpPingScan => Protocol::TCP,
+            HostScanType::UdpPingScan => Protocol::UDP,
+            HostScanType::ArpScan => Protocol::ARP,
         }
     }
 }
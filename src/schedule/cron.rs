@@ -0,0 +1,122 @@
+use chrono::{DateTime, Datelike, Duration, Local, Timelike};
+use std::collections::BTreeSet;
+
+/// Upper bound of the search for the next run
+const MAX_SEARCH_MINUTES: i64 = 366 * 24 * 60;
+
+/// Five-field cron expression: minute hour day-of-month month day-of-week.
+/// Fields accept `*`, numbers, ranges `a-b`, steps `*/n` `a-b/n` and lists `a,b`.
+/// Day of week is 0-7 (0 and 7 are Sunday)
+#[derive(Clone, Debug, PartialEq)]
+pub struct CronSchedule {
+    minutes: BTreeSet<u32>,
+    hours: BTreeSet<u32>,
+    days: BTreeSet<u32>,
+    months: BTreeSet<u32>,
+    weekdays: BTreeSet<u32>,
+    /// Day-of-month field is `*`
+    any_day: bool,
+    /// Day-of-week field is `*`
+    any_weekday: bool,
+}
+
+fn parse_number(s: &str, min: u32, max: u32) -> Result<u32, String> {
+    let n: u32 = s
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid number: {}", s))?;
+    if n < min || n > max {
+        return Err(format!("{} is out of range {}-{}", n, min, max));
+    }
+    Ok(n)
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<BTreeSet<u32>, String> {
+    let mut values: BTreeSet<u32> = BTreeSet::new();
+    for part in field.split(',') {
+        let (range, step): (&str, u32) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("Invalid step: {}", part)),
+            },
+            None => (part, 1),
+        };
+        let (start, end): (u32, u32) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_number(start, min, max)?, parse_number(end, min, max)?)
+        } else {
+            let start: u32 = parse_number(range, min, max)?;
+            // `5/15` means from 5 to the end
+            (start, if part.contains('/') { max } else { start })
+        };
+        if start > end {
+            return Err(format!("Invalid range: {}", range));
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    Ok(values)
+}
+
+impl CronSchedule {
+    /// Parse a cron expression. `@hourly`, `@daily`, `@weekly` and `@monthly` are also accepted
+    pub fn parse(expr: &str) -> Result<CronSchedule, String> {
+        let expr: &str = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            expr => expr,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Expected 5 fields (minute hour day month weekday): {}",
+                expr
+            ));
+        }
+        let mut weekdays: BTreeSet<u32> = parse_field(fields[4], 0, 7)?;
+        if weekdays.remove(&7) {
+            weekdays.insert(0);
+        }
+        Ok(CronSchedule {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays: weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+    pub fn matches(&self, time: &DateTime<Local>) -> bool {
+        if !self.minutes.contains(&time.minute())
+            || !self.hours.contains(&time.hour())
+            || !self.months.contains(&time.month())
+        {
+            return false;
+        }
+        let day: bool = self.days.contains(&time.day());
+        let weekday: bool = self
+            .weekdays
+            .contains(&time.weekday().num_days_from_sunday());
+        // Either day field matches if both are restricted, as in cron
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+    /// Next matching minute after `time`
+    pub fn next_after(&self, time: &DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut next: DateTime<Local> =
+            time.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        for _ in 0..MAX_SEARCH_MINUTES {
+            if self.matches(&next) {
+                return Some(next);
+            }
+            next = next + Duration::minutes(1);
+        }
+        None
+    }
+}
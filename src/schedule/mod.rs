@@ -0,0 +1,191 @@
+pub mod cron;
+
+use crate::output;
+use crate::serve::{JobRequest, JobStatus};
+use chrono::{DateTime, Local};
+use cron::CronSchedule;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Name of the run log in the history directory. One JSON record per line
+pub const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+/// Recurring scan. Stored in the schedule file as a JSON array
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    pub name: String,
+    pub cron: String,
+    #[serde(flatten)]
+    pub job: JobRequest,
+}
+
+impl ScheduleEntry {
+    /// Command line of the job. e.g. host 192.168.1.0/24 --ports 22,80
+    pub fn job_line(&self) -> String {
+        let mut parts: Vec<String> = vec![self.job.command.clone(), self.job.target.clone()];
+        parts.extend(self.job.args.iter().cloned());
+        parts.join(" ")
+    }
+}
+
+/// One scheduled run. Appended to the history file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub schedule: String,
+    pub command: String,
+    pub target: String,
+    pub started_at: DateTime<Local>,
+    pub finished_at: DateTime<Local>,
+    pub status: JobStatus,
+    /// JSON result of the run
+    pub result_file: Option<PathBuf>,
+    pub error: Option<String>,
+}
+
+/// Read the schedule file. A missing file has no schedules
+pub fn load_schedules(file_path: &Path) -> Result<Vec<ScheduleEntry>, String> {
+    match std::fs::read_to_string(file_path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse {}: {}", file_path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read {}: {}", file_path.display(), e)),
+    }
+}
+
+pub fn save_schedules(file_path: &Path, entries: &[ScheduleEntry]) -> Result<(), String> {
+    if let Some(dir) = file_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(file_path, serde_json::to_string_pretty(entries).unwrap())
+        .map_err(|e| format!("Failed to write {}: {}", file_path.display(), e))
+}
+
+/// Check the name, cron expression and job options
+/// Names are used as directory names in the history directory
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid name: {}. Use letters, digits, - and _",
+            name
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate(entry: &ScheduleEntry) -> Result<(), String> {
+    validate_name(&entry.name)?;
+    CronSchedule::parse(&entry.cron)?;
+    crate::serve::parse_job(&entry.job, &std::env::temp_dir().join("nrev-validate.json"))?;
+    Ok(())
+}
+
+/// Default history directory. ~/.nrev/history
+pub fn default_history_dir() -> Option<PathBuf> {
+    crate::fs::app_dir().map(|dir| dir.join("history"))
+}
+
+/// Result file of a run. <history_dir>/<name>/<time>.json
+pub fn history_result_path(history_dir: &Path, name: &str, time: &DateTime<Local>) -> PathBuf {
+    history_dir
+        .join(name)
+        .join(format!("{}.json", time.format("%Y%m%d-%H%M%S")))
+}
+
+pub fn append_history(history_dir: &Path, record: &HistoryRecord) -> Result<(), String> {
+    std::fs::create_dir_all(history_dir)
+        .map_err(|e| format!("Failed to create {}: {}", history_dir.display(), e))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_dir.join(HISTORY_FILE_NAME))
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", serde_json::to_string(record).unwrap()).map_err(|e| e.to_string())
+}
+
+/// Run the job. The result is saved to <history_dir>/<name>/<time>.json
+fn run_entry(entry: &ScheduleEntry, history_dir: &Path) -> HistoryRecord {
+    let started_at: DateTime<Local> = Local::now();
+    let result_dir: PathBuf = history_dir.join(&entry.name);
+    let result_path: PathBuf = history_result_path(history_dir, &entry.name, &started_at);
+    output::log_with_time(
+        &format!("Schedule {} started: {}", entry.name, entry.job_line()),
+        "INFO",
+    );
+    let result: Result<(), String> = std::fs::create_dir_all(&result_dir)
+        .map_err(|e| format!("Failed to create {}: {}", result_dir.display(), e))
+        .and_then(|_| crate::serve::parse_job(&entry.job, &result_path))
+        .and_then(|matches| {
+            if !crate::serve::run_job(&entry.job.command, &matches) {
+                Err("Job panicked".to_string())
+            } else if !result_path.exists() {
+                Err("Finished without a result".to_string())
+            } else {
+                Ok(())
+            }
+        });
+    output::log_with_time(
+        &format!(
+            "Schedule {} {}",
+            entry.name,
+            if result.is_ok() { "done" } else { "failed" }
+        ),
+        if result.is_ok() { "INFO" } else { "ERROR" },
+    );
+    HistoryRecord {
+        schedule: entry.name.clone(),
+        command: entry.job.command.clone(),
+        target: entry.job.target.clone(),
+        started_at: started_at,
+        finished_at: Local::now(),
+        status: if result.is_ok() {
+            JobStatus::Done
+        } else {
+            JobStatus::Failed
+        },
+        result_file: if result.is_ok() {
+            Some(result_path)
+        } else {
+            None
+        },
+        error: result.err(),
+    }
+}
+
+/// Run the schedules until the process is stopped. Jobs run one at a time.
+/// Runs missed while another job is running are skipped
+pub fn run_scheduler(entries: Vec<ScheduleEntry>, history_dir: PathBuf) -> Result<(), String> {
+    std::fs::create_dir_all(&history_dir)
+        .map_err(|e| format!("Failed to create {}: {}", history_dir.display(), e))?;
+    let mut schedules: Vec<(ScheduleEntry, CronSchedule, Option<DateTime<Local>>)> = Vec::new();
+    for entry in entries {
+        let cron: CronSchedule = CronSchedule::parse(&entry.cron)
+            .map_err(|e| format!("Schedule {}: {}", entry.name, e))?;
+        let next_run: Option<DateTime<Local>> = cron.next_after(&Local::now());
+        schedules.push((entry, cron, next_run));
+    }
+    loop {
+        for (entry, cron, next_run) in schedules.iter_mut() {
+            if next_run.map_or(false, |time| time <= Local::now()) {
+                let record: HistoryRecord = run_entry(entry, &history_dir);
+                if let Err(e) = append_history(&history_dir, &record) {
+                    output::log_with_time(&format!("Failed to write history: {}", e), "ERROR");
+                }
+                *next_run = cron.next_after(&Local::now());
+            }
+        }
+        // Wake up at least every minute to follow clock changes
+        let now: DateTime<Local> = Local::now();
+        let wait: Duration = match schedules.iter().filter_map(|(_, _, t)| *t).min() {
+            Some(time) => (time - now).to_std().unwrap_or(Duration::from_secs(0)),
+            None => Duration::from_secs(60),
+        };
+        thread::sleep(wait.min(Duration::from_secs(60)));
+    }
+}
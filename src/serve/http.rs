@@ -0,0 +1,149 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+/// Max size of a request body
+const MAX_BODY_SIZE: usize = 64 * 1024;
+const MAX_HEADER_LINES: usize = 100;
+
+/// Received HTTP request
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: String,
+    /// Path without query string
+    pub path: String,
+    /// Header fields. Names are lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name: String = name.to_lowercase();
+        self.headers
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json(status: u16, body: String) -> Response {
+        Response {
+            status: status,
+            content_type: "application/json",
+            body: body,
+        }
+    }
+    /// Plain text. Also the Prometheus text exposition format
+    pub fn text(status: u16, body: String) -> Response {
+        Response {
+            status: status,
+            content_type: "text/plain; version=0.0.4; charset=utf-8",
+            body: body,
+        }
+    }
+    /// {"error": message}
+    pub fn error(status: u16, message: &str) -> Response {
+        Response::json(status, serde_json::json!({ "error": message }).to_string())
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Read one request. The connection is closed after the response
+pub fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line: String = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|_| Response::error(400, "Failed to read request"))?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err(Response::error(400, "Malformed request line")),
+    };
+    let path: String = target.split('?').next().unwrap_or("/").to_string();
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for _ in 0..MAX_HEADER_LINES {
+        let mut line: String = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line: &str = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    let mut request = Request {
+        method: method,
+        path: path,
+        headers: headers,
+        body: Vec::new(),
+    };
+    let content_length: usize = request
+        .header("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_SIZE {
+        return Err(Response::error(413, "Request body too large"));
+    }
+    request.body = vec![0; content_length];
+    reader
+        .read_exact(&mut request.body)
+        .map_err(|_| Response::error(400, "Truncated request body"))?;
+    Ok(request)
+}
+
+pub fn write_response(stream: &mut TcpStream, response: &Response) {
+    let head: String = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    let _ = stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(response.body.as_bytes()))
+        .and_then(|_| stream.flush());
+}
+
+/// Write the head of a response whose body is streamed until the connection is closed
+pub fn write_stream_head(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+) -> std::io::Result<()> {
+    let head: String = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason_phrase(status),
+        content_type
+    );
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.flush())
+}
@@ -0,0 +1,498 @@
+pub mod http;
+
+use crate::output;
+use chrono::{DateTime, Local};
+use clap::ArgMatches;
+use http::{Request, Response};
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Subcommands that can be launched as jobs
+const JOB_COMMANDS: [&str; 4] = ["port", "host", "ping", "trace"];
+
+/// Options accepted in jobs. Options that read or write files on the server,
+/// connect to other hosts or use server credentials are left out
+fn job_options(command: &str) -> &'static [&'static str] {
+    match command {
+        "port" => &[
+            "--ports",
+            "-p",
+            "--range",
+            "-r",
+            "--scantype",
+            "-T",
+            "--service",
+            "-S",
+            "--ssh-audit",
+            "--smb",
+            "--random",
+            "-R",
+            "--wellknown",
+            "-W",
+            "--full",
+            "-F",
+            "--noping",
+            "--timeout",
+            "--waittime",
+            "-w",
+            "--rate",
+        ],
+        "host" => &[
+            "--protocol",
+            "-P",
+            "--port",
+            "-p",
+            "--random",
+            "-R",
+            "--resolve",
+            "--exclude",
+            "--timeout",
+            "--waittime",
+            "-w",
+            "--rate",
+            "--retries",
+            "--then-port",
+        ],
+        "ping" => &[
+            "--count",
+            "-c",
+            "--port",
+            "-p",
+            "--maxhop",
+            "--protocol",
+            "-P",
+            "--timeout",
+            "--waittime",
+            "-w",
+            "--rate",
+            "--interval",
+            "--deadline",
+            "--size",
+            "-s",
+            "--tos",
+            "--dscp",
+            "--df",
+            "--record-route",
+            "--timestamp",
+            "--pmtu",
+        ],
+        "trace" => &[
+            "--source-ip",
+            "--port",
+            "-p",
+            "--maxhop",
+            "--ipv6",
+            "-6",
+            "--firsthop",
+            "--timeout",
+            "--waittime",
+            "-w",
+            "--hop-timeout",
+            "--rate",
+            "--queries",
+            "--tos",
+            "--dscp",
+            "--parallel",
+            "--resolve",
+            "--no-resolve",
+            "-n",
+            "--dns-timeout",
+            "--asn",
+            "--flows",
+        ],
+        _ => &[],
+    }
+}
+
+/// Check every option of the job against job_options()
+fn check_job_args(job_request: &JobRequest) -> Result<(), String> {
+    if job_request.target.starts_with('-') {
+        return Err(format!("Invalid target: {}", job_request.target));
+    }
+    // host falls back to reading a host-list file named by the target
+    if job_request.command == "host" {
+        for target in job_request.target.split(',') {
+            let target: &str = target.trim();
+            if IpNet::from_str(target).is_err()
+                && IpAddr::from_str(target).is_err()
+                && crate::ip::parse_ip_range(target).is_none()
+            {
+                return Err(format!("Invalid target: {}", target));
+            }
+        }
+    }
+    let options: &[&str] = job_options(&job_request.command);
+    for arg in &job_request.args {
+        if !arg.starts_with('-') {
+            continue;
+        }
+        let name: &str = arg.split_once('=').map(|(name, _)| name).unwrap_or(arg);
+        if !options.contains(&name) {
+            return Err(format!(
+                "{} is not supported in {} jobs. Supported: {}",
+                name,
+                job_request.command,
+                options.join(" ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Body of POST /jobs. `args` are the options of the subcommand as on the command line
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobRequest {
+    pub command: String,
+    pub target: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl JobRequest {
+    /// Parse a command line such as "host 192.168.1.0/24 --ports 22,80"
+    pub fn from_line(line: &str) -> Option<JobRequest> {
+        let mut parts = line.split_whitespace().map(|s| s.to_string());
+        match (parts.next(), parts.next()) {
+            (Some(command), Some(target)) => Some(JobRequest {
+                command: command,
+                target: target,
+                args: parts.collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub command: String,
+    pub target: String,
+    pub args: Vec<String>,
+    pub status: JobStatus,
+    pub created_at: DateTime<Local>,
+    pub started_at: Option<DateTime<Local>>,
+    pub finished_at: Option<DateTime<Local>>,
+    pub error: Option<String>,
+    /// JSON result. Served by GET /jobs/{id}/result
+    #[serde(skip)]
+    pub result: Option<Value>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ServeSetting {
+    pub listen_addr: SocketAddr,
+    /// Required as `Authorization: Bearer <token>` if set
+    pub token: Option<String>,
+}
+
+type JobStore = Arc<Mutex<BTreeMap<String, Job>>>;
+
+/// Job waiting for the worker, with the parsed command line
+struct QueuedJob {
+    id: String,
+    command: String,
+    matches: ArgMatches,
+    result_path: PathBuf,
+}
+
+fn update_job(jobs: &JobStore, id: &str, f: impl FnOnce(&mut Job)) {
+    if let Ok(mut jobs) = jobs.lock() {
+        if let Some(job) = jobs.get_mut(id) {
+            f(job);
+        }
+    }
+}
+
+/// Set --count and --deadline of ping jobs within the job limits.
+/// Without them ping runs until Ctrl-C, which a job never receives
+fn bound_ping_args(args: &[String]) -> Result<Vec<String>, String> {
+    let mut bounded: Vec<String> = Vec::new();
+    let mut count: u32 = crate::config::DEFAULT_JOB_PING_COUNT;
+    let mut deadline: u64 = crate::config::MAX_JOB_PING_DEADLINE_SECS;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let (name, inline): (&str, Option<&str>) = match arg.split_once('=') {
+            Some((name, value)) if arg.starts_with('-') => (name, Some(value)),
+            _ => (arg.as_str(), None),
+        };
+        if !["--count", "-c", "--deadline"].contains(&name) {
+            bounded.push(arg.clone());
+            continue;
+        }
+        let value: &str = match inline.or(iter.next().map(|s| s.as_str())) {
+            Some(value) => value,
+            None => return Err(format!("{} requires a value", name)),
+        };
+        if name == "--deadline" {
+            let secs: u64 = value
+                .parse()
+                .map_err(|_| format!("Invalid deadline: {}", value))?;
+            deadline = secs.clamp(1, crate::config::MAX_JOB_PING_DEADLINE_SECS);
+        } else {
+            let n: u32 = value
+                .parse()
+                .map_err(|_| format!("Invalid count: {}", value))?;
+            count = n.clamp(1, crate::config::MAX_JOB_PING_COUNT);
+        }
+    }
+    bounded.extend([
+        "--count".to_string(),
+        count.to_string(),
+        "--deadline".to_string(),
+        deadline.to_string(),
+    ]);
+    Ok(bounded)
+}
+
+/// Parse the job with the CLI definition, so jobs accept the same options.
+/// The JSON result is written to `result_path`
+pub fn parse_job(job_request: &JobRequest, result_path: &PathBuf) -> Result<ArgMatches, String> {
+    if !JOB_COMMANDS.contains(&job_request.command.as_str()) {
+        return Err(format!(
+            "Unsupported command: {}. Supported: {}",
+            job_request.command,
+            JOB_COMMANDS.join(", ")
+        ));
+    }
+    check_job_args(job_request)?;
+    let mut argv: Vec<String> = vec![
+        crate::app::CRATE_BIN_NAME.to_string(),
+        "--save".to_string(),
+        result_path.to_string_lossy().to_string(),
+        job_request.command.clone(),
+        job_request.target.clone(),
+    ];
+    if job_request.command == "ping" {
+        argv.extend(bound_ping_args(&job_request.args)?);
+    } else {
+        argv.extend(job_request.args.iter().cloned());
+    }
+    let matches: ArgMatches = crate::get_app()
+        .try_get_matches_from(&argv)
+        .map_err(|e| e.to_string())?;
+    // --watch never returns and --webhook posts to any URL
+    for command in ["port", "host"] {
+        if let Some(sub_matches) = matches.subcommand_matches(command) {
+            if sub_matches.contains_id("watch") || sub_matches.contains_id("webhook") {
+                return Err("--watch and --webhook are not supported in jobs".to_string());
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// Run the subcommand handler. Its JSON result is written to the --save path.
+/// Returns false if the handler panicked
+pub fn run_job(command: &str, matches: &ArgMatches) -> bool {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| match command {
+        "port" => crate::handler::port::handle_portscan(matches),
+        "host" => crate::handler::host::handle_hostscan(matches),
+        "ping" => crate::handler::ping::handle_ping(matches),
+        "trace" => crate::handler::trace::handle_traceroute(matches),
+        _ => {}
+    }))
+    .is_ok()
+}
+
+/// Run jobs one at a time. Scans share the interface and packet capture
+fn run_worker(jobs: JobStore, rx: Receiver<QueuedJob>) {
+    for queued in rx.iter() {
+        update_job(&jobs, &queued.id, |job| {
+            job.status = JobStatus::Running;
+            job.started_at = Some(Local::now());
+        });
+        output::log_with_time(
+            &format!("Job {} started: {}", queued.id, queued.command),
+            "INFO",
+        );
+        let completed: bool = run_job(&queued.command, &queued.matches);
+        let result: Result<Value, String> = if !completed {
+            Err("Job panicked. See the server log".to_string())
+        } else {
+            match std::fs::read_to_string(&queued.result_path) {
+                Ok(text) => serde_json::from_str::<Value>(&text)
+                    .map_err(|e| format!("Failed to parse result: {}", e)),
+                Err(_) => Err("Finished without a result. See the server log".to_string()),
+            }
+        };
+        let _ = std::fs::remove_file(&queued.result_path);
+        output::log_with_time(
+            &format!(
+                "Job {} {}",
+                queued.id,
+                if result.is_ok() { "done" } else { "failed" }
+            ),
+            "INFO",
+        );
+        update_job(&jobs, &queued.id, |job| {
+            job.finished_at = Some(Local::now());
+            match result {
+                Ok(value) => {
+                    job.status = JobStatus::Done;
+                    job.result = Some(value);
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e);
+                }
+            }
+        });
+    }
+}
+
+fn create_job(jobs: &JobStore, job_tx: &Sender<QueuedJob>, body: &[u8]) -> Response {
+    let job_request: JobRequest = match serde_json::from_slice(body) {
+        Ok(job_request) => job_request,
+        Err(e) => return Response::error(400, &format!("Invalid job request: {}", e)),
+    };
+    let id: String = uuid::Uuid::new_v4().to_string();
+    let result_path: PathBuf = std::env::temp_dir().join(format!("nrev-job-{}.json", id));
+    let matches: ArgMatches = match parse_job(&job_request, &result_path) {
+        Ok(matches) => matches,
+        Err(e) => return Response::error(400, &e),
+    };
+    let job = Job {
+        id: id.clone(),
+        command: job_request.command.clone(),
+        target: job_request.target,
+        args: job_request.args,
+        status: JobStatus::Queued,
+        created_at: Local::now(),
+        started_at: None,
+        finished_at: None,
+        error: None,
+        result: None,
+    };
+    let body: String = serde_json::to_string_pretty(&job).unwrap();
+    if let Ok(mut jobs) = jobs.lock() {
+        jobs.insert(id.clone(), job);
+    }
+    let queued = QueuedJob {
+        id: id,
+        command: job_request.command,
+        matches: matches,
+        result_path: result_path,
+    };
+    if job_tx.send(queued).is_err() {
+        return Response::error(500, "Job worker stopped");
+    }
+    Response::json(202, body)
+}
+
+fn route(request: &Request, jobs: &JobStore, job_tx: &Sender<QueuedJob>) -> Response {
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["version"]) => Response::json(
+            200,
+            serde_json::json!({ "name": crate::app::CRATE_BIN_NAME, "version": clap::crate_version!() })
+                .to_string(),
+        ),
+        ("GET", ["jobs"]) => match jobs.lock() {
+            Ok(jobs) => {
+                let list: Vec<&Job> = jobs.values().collect();
+                Response::json(200, serde_json::to_string_pretty(&list).unwrap())
+            }
+            Err(_) => Response::error(500, "Job store unavailable"),
+        },
+        ("POST", ["jobs"]) => create_job(jobs, job_tx, &request.body),
+        ("GET", ["jobs", id]) => match jobs.lock() {
+            Ok(jobs) => match jobs.get(*id) {
+                Some(job) => Response::json(200, serde_json::to_string_pretty(job).unwrap()),
+                None => Response::error(404, "Job not found"),
+            },
+            Err(_) => Response::error(500, "Job store unavailable"),
+        },
+        ("GET", ["jobs", id, "result"]) => match jobs.lock() {
+            Ok(jobs) => match jobs.get(*id) {
+                Some(job) => match (&job.result, job.status) {
+                    (Some(result), _) => {
+                        Response::json(200, serde_json::to_string_pretty(result).unwrap())
+                    }
+                    (None, JobStatus::Failed) => {
+                        Response::error(409, job.error.as_deref().unwrap_or("Job failed"))
+                    }
+                    (None, _) => Response::error(409, "Job not finished"),
+                },
+                None => Response::error(404, "Job not found"),
+            },
+            Err(_) => Response::error(500, "Job store unavailable"),
+        },
+        (_, ["jobs"]) | (_, ["jobs", ..]) | (_, ["version"]) => {
+            Response::error(405, "Method not allowed")
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    setting: &ServeSetting,
+    jobs: &JobStore,
+    job_tx: &Sender<QueuedJob>,
+) {
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(
+        crate::config::DEFAULT_SERVE_READ_TIMEOUT_MILLIS,
+    )));
+    let response: Response = match http::read_request(&stream) {
+        Ok(request) => {
+            let authorized: bool = match &setting.token {
+                Some(token) => {
+                    request.header("authorization") == Some(format!("Bearer {}", token).as_str())
+                }
+                None => true,
+            };
+            if authorized {
+                route(&request, jobs, job_tx)
+            } else {
+                Response::error(401, "Unauthorized")
+            }
+        }
+        Err(response) => response,
+    };
+    http::write_response(&mut stream, &response);
+}
+
+/// Serve the REST API until the process is stopped
+pub fn serve(setting: ServeSetting) -> Result<(), String> {
+    let listener: TcpListener = TcpListener::bind(setting.listen_addr)
+        .map_err(|e| format!("Failed to listen on {}: {}", setting.listen_addr, e))?;
+    let jobs: JobStore = Arc::new(Mutex::new(BTreeMap::new()));
+    let (job_tx, job_rx): (Sender<QueuedJob>, Receiver<QueuedJob>) = channel();
+    let worker_jobs: JobStore = jobs.clone();
+    thread::spawn(move || run_worker(worker_jobs, job_rx));
+    for stream in listener.incoming() {
+        let stream: TcpStream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let setting: ServeSetting = setting.clone();
+        let jobs: JobStore = jobs.clone();
+        let job_tx: Sender<QueuedJob> = job_tx.clone();
+        thread::spawn(move || handle_connection(stream, &setting, &jobs, &job_tx));
+    }
+    Ok(())
+}
@@ -0,0 +1,342 @@
+use crate::util::ber;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::time::Duration;
+
+pub const SMB_PORT: u16 = 445;
+pub const NETBIOS_SESSION_PORT: u16 = 139;
+
+const SMB2_NEGOTIATE: u16 = 0x0000;
+const SMB2_SESSION_SETUP: u16 = 0x0001;
+const SMB2_HEADER_SIZE: usize = 64;
+/// SMB 2.0.2 to 3.0.2. 3.1.1 requires negotiate contexts
+const SMB2_DIALECTS: [u16; 4] = [0x0202, 0x0210, 0x0300, 0x0302];
+const SECURITY_MODE_SIGNING_ENABLED: u16 = 0x0001;
+const SECURITY_MODE_SIGNING_REQUIRED: u16 = 0x0002;
+const NTLMSSP_SIGNATURE: &[u8] = b"NTLMSSP\0";
+/// UNICODE, REQUEST_TARGET, NTLM, ALWAYS_SIGN, EXTENDED_SESSIONSECURITY, TARGET_INFO, VERSION, 128, 56
+const NTLMSSP_NEGOTIATE_FLAGS: u32 = 0xa288_8205;
+const OID_SPNEGO: &str = "1.3.6.1.5.5.2";
+const OID_NTLMSSP: &str = "1.3.6.1.4.1.311.2.2.10";
+/// NetBIOS session request/response
+const NBSS_SESSION_REQUEST: u8 = 0x81;
+const NBSS_POSITIVE_RESPONSE: u8 = 0x82;
+/// 100ns intervals between 1601-01-01 and 1970-01-01
+const FILETIME_UNIX_EPOCH: u64 = 116_444_736_000_000_000;
+
+/// Host information from SMB negotiation and the NTLM challenge
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SmbInfo {
+    /// Port negotiated on (445 or 139)
+    pub port: u16,
+    /// Dialect chosen by the server (e.g. 3.0.2)
+    pub dialect: String,
+    pub signing_enabled: bool,
+    pub signing_required: bool,
+    /// OS version from the NTLM challenge (e.g. Windows 10.0 Build 19041)
+    pub os_version: String,
+    pub netbios_name: String,
+    /// NetBIOS domain or workgroup
+    pub netbios_domain: String,
+    pub dns_name: String,
+    pub dns_domain: String,
+    pub dns_forest: String,
+    pub system_time: Option<DateTime<Utc>>,
+}
+
+impl SmbInfo {
+    fn new(port: u16) -> SmbInfo {
+        SmbInfo {
+            port: port,
+            dialect: String::new(),
+            signing_enabled: false,
+            signing_required: false,
+            os_version: String::new(),
+            netbios_name: String::new(),
+            netbios_domain: String::new(),
+            dns_name: String::new(),
+            dns_domain: String::new(),
+            dns_forest: String::new(),
+            system_time: None,
+        }
+    }
+    pub fn signing_label(&self) -> &'static str {
+        if self.signing_required {
+            "Required"
+        } else if self.signing_enabled {
+            "Enabled, not required"
+        } else {
+            "Disabled"
+        }
+    }
+}
+
+fn dialect_name(dialect: u16) -> String {
+    match dialect {
+        0x0202 => "2.0.2".to_string(),
+        0x0210 => "2.1".to_string(),
+        0x0300 => "3.0".to_string(),
+        0x0302 => "3.0.2".to_string(),
+        0x0311 => "3.1.1".to_string(),
+        _ => format!("0x{:04X}", dialect),
+    }
+}
+
+/// Windows release of the NTLM version. Samba reports a fixed version
+fn os_name(major: u8, minor: u8, build: u16) -> String {
+    let release: &str = match (major, minor) {
+        (5, 0) => "Windows 2000",
+        (5, 1) => "Windows XP",
+        (5, 2) => "Windows XP x64/Server 2003",
+        (6, 0) => "Windows Vista/Server 2008",
+        (6, 1) => "Windows 7/Server 2008 R2",
+        (6, 2) => "Windows 8/Server 2012",
+        (6, 3) => "Windows 8.1/Server 2012 R2",
+        (10, 0) if build >= 22000 => "Windows 11",
+        (10, 0) if build >= 20348 => "Windows Server 2022",
+        (10, 0) => "Windows 10/Server 2016+",
+        _ => "Windows",
+    };
+    format!("{} ({}.{} Build {})", release, major, minor, build)
+}
+
+fn filetime(value: u64) -> Option<DateTime<Utc>> {
+    let nanos: u64 = value.checked_sub(FILETIME_UNIX_EPOCH)?.checked_mul(100)?;
+    DateTime::<Utc>::from_timestamp(
+        (nanos / 1_000_000_000) as i64,
+        (nanos % 1_000_000_000) as u32,
+    )
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes([*data.get(pos)?, *data.get(pos + 1)?]))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], pos: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(pos..pos + 8)?.try_into().ok()?))
+}
+
+fn utf16_string(data: &[u8]) -> String {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// First-level encoded NetBIOS name with the suffix
+fn encode_netbios_name(name: &str, suffix: u8) -> Vec<u8> {
+    let mut padded: [u8; 16] = [b' '; 16];
+    for (i, b) in name.bytes().take(15).enumerate() {
+        padded[i] = b.to_ascii_uppercase();
+    }
+    padded[15] = suffix;
+    let mut encoded: Vec<u8> = vec![0x20];
+    for b in padded {
+        encoded.push(b'A' + (b >> 4));
+        encoded.push(b'A' + (b & 0x0f));
+    }
+    encoded.push(0);
+    encoded
+}
+
+/// Send message with the 4-byte session header
+fn send_message(stream: &mut TcpStream, message_type: u8, data: &[u8]) -> Result<(), String> {
+    let mut packet: Vec<u8> = vec![message_type];
+    packet.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+    packet.extend_from_slice(data);
+    stream
+        .write_all(&packet)
+        .map_err(|e| format!("Failed to send: {}", e))
+}
+
+/// Read one session message. Returns (type, data)
+fn recv_message(stream: &mut TcpStream) -> Result<(u8, Vec<u8>), String> {
+    let mut header: [u8; 4] = [0; 4];
+    stream
+        .read_exact(&mut header)
+        .map_err(|e| format!("No response: {}", e))?;
+    let len: usize = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+    let mut data: Vec<u8> = vec![0; len];
+    stream
+        .read_exact(&mut data)
+        .map_err(|e| format!("Truncated response: {}", e))?;
+    Ok((header[0], data))
+}
+
+/// NetBIOS session request to the generic server name. Required on 139
+fn netbios_session_request(stream: &mut TcpStream) -> Result<(), String> {
+    let mut data: Vec<u8> = encode_netbios_name("*SMBSERVER", 0x20);
+    data.extend(encode_netbios_name("NREV", 0x00));
+    send_message(stream, NBSS_SESSION_REQUEST, &data)?;
+    match recv_message(stream)? {
+        (NBSS_POSITIVE_RESPONSE, _) => Ok(()),
+        (message_type, _) => Err(format!("NetBIOS session refused (0x{:02X})", message_type)),
+    }
+}
+
+fn smb2_header(command: u16, message_id: u64) -> Vec<u8> {
+    let mut header: Vec<u8> = b"\xfeSMB".to_vec();
+    header.extend_from_slice(&(SMB2_HEADER_SIZE as u16).to_le_bytes());
+    // CreditCharge, Status
+    header.extend_from_slice(&[0; 6]);
+    header.extend_from_slice(&command.to_le_bytes());
+    // CreditRequest
+    header.extend_from_slice(&1u16.to_le_bytes());
+    // Flags, NextCommand
+    header.extend_from_slice(&[0; 8]);
+    header.extend_from_slice(&message_id.to_le_bytes());
+    // ProcessId, TreeId, SessionId, Signature
+    header.extend_from_slice(&[0; 32]);
+    header
+}
+
+fn build_negotiate() -> Vec<u8> {
+    let mut packet: Vec<u8> = smb2_header(SMB2_NEGOTIATE, 0);
+    packet.extend_from_slice(&36u16.to_le_bytes());
+    packet.extend_from_slice(&(SMB2_DIALECTS.len() as u16).to_le_bytes());
+    packet.extend_from_slice(&SECURITY_MODE_SIGNING_ENABLED.to_le_bytes());
+    // Reserved, Capabilities
+    packet.extend_from_slice(&[0; 6]);
+    packet.extend_from_slice(&rand::random::<[u8; 16]>());
+    // ClientStartTime
+    packet.extend_from_slice(&[0; 8]);
+    for dialect in SMB2_DIALECTS {
+        packet.extend_from_slice(&dialect.to_le_bytes());
+    }
+    packet
+}
+
+/// SPNEGO NegTokenInit carrying NTLMSSP NEGOTIATE
+fn build_session_setup() -> Vec<u8> {
+    let mut ntlmssp: Vec<u8> = NTLMSSP_SIGNATURE.to_vec();
+    ntlmssp.extend_from_slice(&1u32.to_le_bytes());
+    ntlmssp.extend_from_slice(&NTLMSSP_NEGOTIATE_FLAGS.to_le_bytes());
+    // DomainNameFields, WorkstationFields, Version
+    ntlmssp.extend_from_slice(&[0; 24]);
+    let mech_types: Vec<u8> = ber::encode_element(
+        0x30,
+        &ber::encode_element(0x06, &ber::encode_oid(OID_NTLMSSP).unwrap_or_default()),
+    );
+    let mut init: Vec<u8> = ber::encode_element(0xa0, &mech_types);
+    init.extend(ber::encode_element(
+        0xa2,
+        &ber::encode_element(0x04, &ntlmssp),
+    ));
+    let mut token: Vec<u8> =
+        ber::encode_element(0x06, &ber::encode_oid(OID_SPNEGO).unwrap_or_default());
+    token.extend(ber::encode_element(0xa0, &ber::encode_element(0x30, &init)));
+    let blob: Vec<u8> = ber::encode_element(0x60, &token);
+
+    let mut packet: Vec<u8> = smb2_header(SMB2_SESSION_SETUP, 1);
+    packet.extend_from_slice(&25u16.to_le_bytes());
+    // Flags, SecurityMode
+    packet.extend_from_slice(&[0, SECURITY_MODE_SIGNING_ENABLED as u8]);
+    // Capabilities, Channel
+    packet.extend_from_slice(&[0; 8]);
+    packet.extend_from_slice(&((SMB2_HEADER_SIZE + 24) as u16).to_le_bytes());
+    packet.extend_from_slice(&(blob.len() as u16).to_le_bytes());
+    // PreviousSessionId
+    packet.extend_from_slice(&[0; 8]);
+    packet.extend_from_slice(&blob);
+    packet
+}
+
+fn parse_negotiate(data: &[u8], info: &mut SmbInfo) -> Result<(), String> {
+    if data.get(0..4) != Some(b"\xfeSMB".as_slice()) {
+        return Err("Not an SMB2 response. SMB1-only servers are not supported".to_string());
+    }
+    let status: u32 = read_u32(data, 8).unwrap_or_default();
+    if status != 0 {
+        return Err(format!("Negotiate failed (0x{:08X})", status));
+    }
+    let body: &[u8] = data.get(SMB2_HEADER_SIZE..).unwrap_or_default();
+    let security_mode: u16 = read_u16(body, 2).ok_or("Malformed negotiate response")?;
+    info.signing_enabled = security_mode & SECURITY_MODE_SIGNING_ENABLED != 0;
+    info.signing_required = security_mode & SECURITY_MODE_SIGNING_REQUIRED != 0;
+    info.dialect = dialect_name(read_u16(body, 4).unwrap_or_default());
+    info.system_time = read_u64(body, 40).and_then(filetime);
+    Ok(())
+}
+
+/// Target info and version of the NTLMSSP CHALLENGE in the session setup response
+fn parse_challenge(data: &[u8], info: &mut SmbInfo) -> Result<(), String> {
+    let start: usize = data
+        .windows(NTLMSSP_SIGNATURE.len())
+        .position(|w| w == NTLMSSP_SIGNATURE)
+        .ok_or("No NTLM challenge in the response")?;
+    let challenge: &[u8] = &data[start..];
+    if read_u32(challenge, 8) != Some(2) {
+        return Err("Unexpected NTLM message".to_string());
+    }
+    let flags: u32 = read_u32(challenge, 20).unwrap_or_default();
+    // NEGOTIATE_VERSION
+    if flags & 0x0200_0000 != 0 {
+        if let (Some(major), Some(minor), Some(build)) = (
+            challenge.get(48),
+            challenge.get(49),
+            read_u16(challenge, 50),
+        ) {
+            info.os_version = os_name(*major, *minor, build);
+        }
+    }
+    let len: usize = read_u16(challenge, 40).unwrap_or_default() as usize;
+    let offset: usize = read_u32(challenge, 44).unwrap_or_default() as usize;
+    let mut target_info: &[u8] = challenge.get(offset..offset + len).unwrap_or_default();
+    // AV_PAIR list terminated by MsvAvEOL
+    while target_info.len() >= 4 {
+        let av_id: u16 = u16::from_le_bytes([target_info[0], target_info[1]]);
+        let av_len: usize = u16::from_le_bytes([target_info[2], target_info[3]]) as usize;
+        let value: &[u8] = match target_info.get(4..4 + av_len) {
+            Some(value) => value,
+            None => break,
+        };
+        match av_id {
+            0 => break,
+            1 => info.netbios_name = utf16_string(value),
+            2 => info.netbios_domain = utf16_string(value),
+            3 => info.dns_name = utf16_string(value),
+            4 => info.dns_domain = utf16_string(value),
+            5 => info.dns_forest = utf16_string(value),
+            _ => {}
+        }
+        target_info = &target_info[4 + av_len..];
+    }
+    Ok(())
+}
+
+/// Negotiate SMB2 and start NTLM authentication to read the host information.
+/// No credentials are sent
+pub fn enumerate(ip_addr: IpAddr, port: u16, timeout: Duration) -> Result<SmbInfo, String> {
+    let socket_addr: SocketAddr = SocketAddr::new(ip_addr, port);
+    let mut stream: TcpStream = TcpStream::connect_timeout(&socket_addr, timeout)
+        .map_err(|e| format!("Failed to connect to {}: {}", socket_addr, e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    if port == NETBIOS_SESSION_PORT {
+        netbios_session_request(&mut stream)?;
+    }
+    let mut info: SmbInfo = SmbInfo::new(port);
+    send_message(&mut stream, 0, &build_negotiate())?;
+    let (_, response) = recv_message(&mut stream)?;
+    parse_negotiate(&response, &mut info)?;
+    send_message(&mut stream, 0, &build_session_setup())?;
+    let (_, response) = recv_message(&mut stream)?;
+    // Negotiation alone is still useful if the challenge is missing
+    let _ = parse_challenge(&response, &mut info);
+    Ok(info)
+}
+
+/// Enumerate on 445, or 139 if 445 is not open
+pub fn enumerate_host(ip_addr: IpAddr, open_ports: &[u16], timeout: Duration) -> Option<SmbInfo> {
+    [SMB_PORT, NETBIOS_SESSION_PORT]
+        .iter()
+        .filter(|port| open_ports.contains(port))
+        .find_map(|port| enumerate(ip_addr, *port, timeout).ok())
+}
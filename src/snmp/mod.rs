@@ -0,0 +1,306 @@
+use crate::util::ber;
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const SNMP_PORT: u16 = 161;
+/// Community strings tried when none is specified
+pub const DEFAULT_COMMUNITIES: [&str; 6] =
+    ["public", "private", "community", "manager", "cisco", "snmp"];
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_TIMETICKS: u8 = 0x43;
+const PDU_GET_REQUEST: u8 = 0xa0;
+const PDU_GET_RESPONSE: u8 = 0xa2;
+
+const OID_SYS_DESCR: &str = "1.3.6.1.2.1.1.1.0";
+const OID_SYS_OBJECT_ID: &str = "1.3.6.1.2.1.1.2.0";
+const OID_SYS_UPTIME: &str = "1.3.6.1.2.1.1.3.0";
+const OID_SYS_CONTACT: &str = "1.3.6.1.2.1.1.4.0";
+const OID_SYS_NAME: &str = "1.3.6.1.2.1.1.5.0";
+const OID_SYS_LOCATION: &str = "1.3.6.1.2.1.1.6.0";
+const SYSTEM_OIDS: [&str; 6] = [
+    OID_SYS_DESCR,
+    OID_SYS_OBJECT_ID,
+    OID_SYS_UPTIME,
+    OID_SYS_CONTACT,
+    OID_SYS_NAME,
+    OID_SYS_LOCATION,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnmpVersion {
+    V1,
+    V2c,
+}
+
+impl SnmpVersion {
+    pub fn from_str(s: &str) -> Option<SnmpVersion> {
+        match s {
+            "1" | "v1" => Some(SnmpVersion::V1),
+            "2c" | "v2c" => Some(SnmpVersion::V2c),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            SnmpVersion::V1 => "v1",
+            SnmpVersion::V2c => "v2c",
+        }
+    }
+    /// Value of the version field
+    fn number(&self) -> i64 {
+        match self {
+            SnmpVersion::V1 => 0,
+            SnmpVersion::V2c => 1,
+        }
+    }
+}
+
+/// Settings of SNMP sweep
+#[derive(Clone, Debug)]
+pub struct SnmpSetting {
+    pub communities: Vec<String>,
+    pub version: SnmpVersion,
+    /// Wait time for each community
+    pub timeout: Duration,
+    pub concurrency: usize,
+}
+
+/// Device answering to a community, with the system group
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnmpDevice {
+    pub ip_addr: IpAddr,
+    pub community: String,
+    pub version: SnmpVersion,
+    pub sys_descr: String,
+    pub sys_object_id: String,
+    pub sys_uptime: Option<Duration>,
+    pub sys_contact: String,
+    pub sys_name: String,
+    pub sys_location: String,
+}
+
+/// Result of SNMP sweep
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnmpSweepResult {
+    /// Responding devices in the order of the targets
+    pub devices: Vec<SnmpDevice>,
+    pub scan_time: Duration,
+}
+
+/// Build GetRequest message for the OIDs
+fn build_get_request(
+    version: SnmpVersion,
+    community: &str,
+    request_id: i32,
+    oids: &[&str],
+) -> Vec<u8> {
+    let mut varbinds: Vec<u8> = Vec::new();
+    for oid in oids {
+        let mut varbind: Vec<u8> =
+            ber::encode_element(TAG_OID, &ber::encode_oid(oid).unwrap_or_default());
+        varbind.extend(ber::encode_element(TAG_NULL, &[]));
+        varbinds.extend(ber::encode_element(TAG_SEQUENCE, &varbind));
+    }
+    let mut pdu: Vec<u8> =
+        ber::encode_element(TAG_INTEGER, &ber::encode_integer(request_id as i64));
+    // error-status and error-index
+    pdu.extend(ber::encode_element(TAG_INTEGER, &[0]));
+    pdu.extend(ber::encode_element(TAG_INTEGER, &[0]));
+    pdu.extend(ber::encode_element(TAG_SEQUENCE, &varbinds));
+    let mut message: Vec<u8> =
+        ber::encode_element(TAG_INTEGER, &ber::encode_integer(version.number()));
+    message.extend(ber::encode_element(TAG_OCTET_STRING, community.as_bytes()));
+    message.extend(ber::encode_element(PDU_GET_REQUEST, &pdu));
+    ber::encode_element(TAG_SEQUENCE, &message)
+}
+
+/// Response PDU
+struct GetResponse {
+    request_id: i64,
+    error_status: i64,
+    /// (OID, tag, value)
+    varbinds: Vec<(String, u8, Vec<u8>)>,
+}
+
+fn parse_get_response(data: &[u8]) -> Option<GetResponse> {
+    let (_, message, _) = ber::read_element(data)?;
+    let fields = ber::read_elements(message);
+    let (tag, pdu) = fields.get(2)?;
+    if *tag != PDU_GET_RESPONSE {
+        return None;
+    }
+    let pdu_fields = ber::read_elements(pdu);
+    if pdu_fields.len() < 4 {
+        return None;
+    }
+    let mut response = GetResponse {
+        request_id: ber::decode_integer(pdu_fields[0].1)?,
+        error_status: ber::decode_integer(pdu_fields[1].1)?,
+        varbinds: vec![],
+    };
+    for (_, varbind) in ber::read_elements(pdu_fields[3].1) {
+        let parts = ber::read_elements(varbind);
+        if parts.len() < 2 || parts[0].0 != TAG_OID {
+            continue;
+        }
+        response
+            .varbinds
+            .push((ber::decode_oid(parts[0].1), parts[1].0, parts[1].1.to_vec()));
+    }
+    Some(response)
+}
+
+/// Send GetRequest and wait for the matching response
+fn get(
+    socket: &UdpSocket,
+    dst: &SocketAddr,
+    version: SnmpVersion,
+    community: &str,
+    oids: &[&str],
+    timeout: Duration,
+) -> Option<GetResponse> {
+    let request_id: i32 = rand::random::<i32>() & 0x7fffffff;
+    let request: Vec<u8> = build_get_request(version, community, request_id, oids);
+    socket.send_to(&request, dst).ok()?;
+    let start_time: Instant = Instant::now();
+    let mut buf: [u8; 65535] = [0; 65535];
+    while start_time.elapsed() < timeout {
+        let _ = socket.set_read_timeout(Some(timeout - start_time.elapsed()));
+        let (len, src) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(_) => return None,
+        };
+        if src.ip() != dst.ip() {
+            continue;
+        }
+        if let Some(response) = parse_get_response(&buf[..len]) {
+            if response.request_id == request_id as i64 {
+                return Some(response);
+            }
+        }
+    }
+    None
+}
+
+fn set_value(device: &mut SnmpDevice, oid: &str, tag: u8, value: &[u8]) {
+    let text = || String::from_utf8_lossy(value).trim().to_string();
+    match oid {
+        OID_SYS_DESCR => device.sys_descr = text(),
+        OID_SYS_OBJECT_ID if tag == TAG_OID => device.sys_object_id = ber::decode_oid(value),
+        // TimeTicks in hundredths of a second
+        OID_SYS_UPTIME if tag == TAG_TIMETICKS => {
+            device.sys_uptime = ber::decode_unsigned(value)
+                .map(|ticks| Duration::from_millis(ticks.saturating_mul(10)))
+        }
+        OID_SYS_CONTACT => device.sys_contact = text(),
+        OID_SYS_NAME => device.sys_name = text(),
+        OID_SYS_LOCATION => device.sys_location = text(),
+        _ => {}
+    }
+}
+
+/// Try the communities in order and fetch the system group with the first one answered
+pub fn probe(ip_addr: IpAddr, setting: &SnmpSetting) -> Option<SnmpDevice> {
+    let bind_addr: SocketAddr = match ip_addr {
+        IpAddr::V4(_) => "0.0.0.0:0".parse().unwrap(),
+        IpAddr::V6(_) => "[::]:0".parse().unwrap(),
+    };
+    let socket: UdpSocket = UdpSocket::bind(bind_addr).ok()?;
+    let dst: SocketAddr = SocketAddr::new(ip_addr, SNMP_PORT);
+    for community in &setting.communities {
+        // Agents drop requests with an unknown community silently
+        let response: GetResponse = match get(
+            &socket,
+            &dst,
+            setting.version,
+            community,
+            &SYSTEM_OIDS,
+            setting.timeout,
+        ) {
+            Some(response) => response,
+            None => continue,
+        };
+        let mut device = SnmpDevice {
+            ip_addr: ip_addr,
+            community: community.clone(),
+            version: setting.version,
+            sys_descr: String::new(),
+            sys_object_id: String::new(),
+            sys_uptime: None,
+            sys_contact: String::new(),
+            sys_name: String::new(),
+            sys_location: String::new(),
+        };
+        if response.error_status == 0 {
+            for (oid, tag, value) in &response.varbinds {
+                set_value(&mut device, oid, *tag, value);
+            }
+        } else {
+            // v1 fails the whole request if an object is missing. Ask one by one
+            for oid in SYSTEM_OIDS {
+                if let Some(response) = get(
+                    &socket,
+                    &dst,
+                    setting.version,
+                    community,
+                    &[oid],
+                    setting.timeout,
+                ) {
+                    if response.error_status != 0 {
+                        continue;
+                    }
+                    for (oid, tag, value) in &response.varbinds {
+                        set_value(&mut device, oid, *tag, value);
+                    }
+                }
+            }
+        }
+        return Some(device);
+    }
+    None
+}
+
+/// Probe the targets concurrently. Sends each target to ptx when done
+pub async fn sweep(
+    targets: Vec<IpAddr>,
+    setting: SnmpSetting,
+    ptx: Arc<Mutex<Sender<IpAddr>>>,
+) -> SnmpSweepResult {
+    let start_time: Instant = Instant::now();
+    let setting: &SnmpSetting = &setting;
+    let ptx: &Arc<Mutex<Sender<IpAddr>>> = &ptx;
+    let mut results: Vec<(usize, Option<SnmpDevice>)> =
+        stream::iter(targets.into_iter().enumerate())
+            .map(|(i, ip_addr)| async move {
+                let probe_setting: SnmpSetting = setting.clone();
+                // Sockets are blocking. Run each on the blocking pool
+                let device: Option<SnmpDevice> =
+                    tokio::task::spawn_blocking(move || probe(ip_addr, &probe_setting))
+                        .await
+                        .unwrap_or(None);
+                if let Ok(lr) = ptx.lock() {
+                    let _ = lr.send(ip_addr);
+                }
+                (i, device)
+            })
+            .buffer_unordered(setting.concurrency)
+            .collect()
+            .await;
+    results.sort_by_key(|(i, _)| *i);
+    SnmpSweepResult {
+        devices: results
+            .into_iter()
+            .filter_map(|(_, device)| device)
+            .collect(),
+        scan_time: start_time.elapsed(),
+    }
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::time::Duration;
+
+pub const SSH_PORT: u16 = 22;
+const SSH_MSG_KEXINIT: u8 = 20;
+/// Max size of the KEXINIT packet to read
+const MAX_PACKET_SIZE: usize = 35000;
+/// Lines other than the version string may precede it (RFC 4253 4.2)
+const MAX_PRE_BANNER_LINES: usize = 20;
+
+/// Deprecated algorithms by category. Variants with a -suffix or @domain also match
+const WEAK_KEX: [&str; 6] = [
+    "diffie-hellman-group1-sha1",
+    "diffie-hellman-group14-sha1",
+    "diffie-hellman-group-exchange-sha1",
+    "gss-group1-sha1",
+    "gss-gex-sha1",
+    "rsa1024-sha1",
+];
+const WEAK_HOST_KEY: [&str; 2] = ["ssh-dss", "ssh-rsa"];
+const WEAK_CIPHER: [&str; 12] = [
+    "none",
+    "des-cbc",
+    "3des-cbc",
+    "blowfish-cbc",
+    "cast128-cbc",
+    "arcfour",
+    "arcfour128",
+    "arcfour256",
+    "rijndael-cbc",
+    "aes128-cbc",
+    "aes192-cbc",
+    "aes256-cbc",
+];
+const WEAK_MAC: [&str; 5] = ["none", "hmac-md5", "hmac-sha1", "hmac-ripemd160", "umac-64"];
+
+/// Algorithms offered in the server's KEXINIT, with the banner
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SshAuditResult {
+    /// Version string (e.g. SSH-2.0-OpenSSH_9.6)
+    pub banner: String,
+    pub kex_algorithms: Vec<String>,
+    pub host_key_algorithms: Vec<String>,
+    /// Server to client. Listed in the server's order of preference
+    pub encryption_algorithms: Vec<String>,
+    pub mac_algorithms: Vec<String>,
+    pub compression_algorithms: Vec<String>,
+    /// Deprecated algorithms and protocol versions
+    pub issues: Vec<String>,
+}
+
+fn is_weak(list: &[&str], algorithm: &str) -> bool {
+    list.iter().any(|weak| match algorithm.strip_prefix(weak) {
+        Some(rest) => rest.is_empty() || rest.starts_with('-') || rest.starts_with('@'),
+        None => false,
+    })
+}
+
+fn find_issues(result: &SshAuditResult) -> Vec<String> {
+    let mut issues: Vec<String> = Vec::new();
+    if result.banner.starts_with("SSH-1.") && !result.banner.starts_with("SSH-1.99") {
+        issues.push("SSH protocol 1 only".to_string());
+    } else if result.banner.starts_with("SSH-1.99") {
+        issues.push("SSH protocol 1 is enabled".to_string());
+    }
+    let categories: [(&str, &Vec<String>, &[&str]); 4] = [
+        ("key exchange", &result.kex_algorithms, &WEAK_KEX),
+        ("host key", &result.host_key_algorithms, &WEAK_HOST_KEY),
+        ("cipher", &result.encryption_algorithms, &WEAK_CIPHER),
+        ("MAC", &result.mac_algorithms, &WEAK_MAC),
+    ];
+    for (category, algorithms, weak_list) in categories {
+        for algorithm in algorithms.iter().filter(|a| is_weak(weak_list, a)) {
+            issues.push(format!("Deprecated {} {}", category, algorithm));
+        }
+    }
+    issues
+}
+
+fn read_name_list(payload: &[u8], pos: &mut usize) -> Option<Vec<String>> {
+    let len: usize = u32::from_be_bytes(payload.get(*pos..*pos + 4)?.try_into().ok()?) as usize;
+    let list: &[u8] = payload.get(*pos + 4..*pos + 4 + len)?;
+    *pos += 4 + len;
+    Some(
+        String::from_utf8_lossy(list)
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string())
+            .collect(),
+    )
+}
+
+/// Read the version string and the server's KEXINIT. No key exchange is done
+pub fn audit(ip_addr: IpAddr, port: u16, timeout: Duration) -> Result<SshAuditResult, String> {
+    let socket_addr: SocketAddr = SocketAddr::new(ip_addr, port);
+    let mut stream: TcpStream = TcpStream::connect_timeout(&socket_addr, timeout)
+        .map_err(|e| format!("Failed to connect to {}: {}", socket_addr, e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    let client_banner: String = format!("SSH-2.0-nrev_{}\r\n", env!("CARGO_PKG_VERSION"));
+    stream
+        .write_all(client_banner.as_bytes())
+        .map_err(|e| format!("Failed to send version: {}", e))?;
+    let mut reader = BufReader::new(stream);
+    let mut banner: String = String::new();
+    for _ in 0..MAX_PRE_BANNER_LINES {
+        let mut line: String = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if line.starts_with("SSH-") {
+            banner = line.trim_end().to_string();
+            break;
+        }
+    }
+    if banner.is_empty() {
+        return Err("No SSH version string".to_string());
+    }
+    let mut result = SshAuditResult {
+        banner: banner,
+        kex_algorithms: vec![],
+        host_key_algorithms: vec![],
+        encryption_algorithms: vec![],
+        mac_algorithms: vec![],
+        compression_algorithms: vec![],
+        issues: vec![],
+    };
+    if result.banner.starts_with("SSH-2.0") || result.banner.starts_with("SSH-1.99") {
+        // Binary packet: length, padding length, payload, padding
+        let mut header: [u8; 5] = [0; 5];
+        reader
+            .read_exact(&mut header)
+            .map_err(|e| format!("No KEXINIT: {}", e))?;
+        let packet_len: usize =
+            u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let padding_len: usize = header[4] as usize;
+        if packet_len > MAX_PACKET_SIZE || packet_len < padding_len + 1 {
+            return Err("Malformed packet".to_string());
+        }
+        let mut body: Vec<u8> = vec![0; packet_len - 1];
+        reader
+            .read_exact(&mut body)
+            .map_err(|e| format!("Truncated KEXINIT: {}", e))?;
+        let payload: &[u8] = &body[..packet_len - 1 - padding_len];
+        if payload.first() != Some(&SSH_MSG_KEXINIT) {
+            return Err("Unexpected message instead of KEXINIT".to_string());
+        }
+        // Message type and cookie
+        let mut pos: usize = 17;
+        let mut lists: Vec<Vec<String>> = Vec::new();
+        // kex, host key, then client-to-server/server-to-client pairs of cipher, MAC, compression
+        for _ in 0..8 {
+            lists.push(read_name_list(payload, &mut pos).ok_or("Malformed KEXINIT")?);
+        }
+        result.kex_algorithms = lists[0].clone();
+        result.host_key_algorithms = lists[1].clone();
+        result.encryption_algorithms = lists[3].clone();
+        result.mac_algorithms = lists[5].clone();
+        result.compression_algorithms = lists[7].clone();
+    }
+    result.issues = find_issues(&result);
+    Ok(result)
+}
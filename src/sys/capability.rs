@@ -0,0 +1,249 @@
+use netdev::Interface;
+use nex::packet::ip::IpNextLevelProtocol;
+use nex::socket::{AsyncSocket, IpVersion, SocketOption, SocketType};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::time::Duration;
+
+/// Public address used to check the IPv6 route. No packet is sent
+const IPV6_PROBE_ADDR: Ipv6Addr = Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888);
+
+/// Capability required by some scan types
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Capability {
+    /// Npcap on Windows. Always available on other platforms
+    Driver,
+    RawSocket,
+    /// Capture and send on the datalink layer (AF_PACKET, BPF, Npcap)
+    PacketCapture,
+    /// ICMP datagram socket (unprivileged ping)
+    IcmpSocket,
+    Ipv6,
+}
+
+impl Capability {
+    pub fn name(&self) -> &str {
+        match self {
+            Capability::Driver => "Packet driver",
+            Capability::RawSocket => "Raw socket",
+            Capability::PacketCapture => "Packet capture",
+            Capability::IcmpSocket => "ICMP socket",
+            Capability::Ipv6 => "IPv6",
+        }
+    }
+}
+
+/// Result of testing one capability
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CapabilityCheck {
+    pub capability: Capability,
+    pub available: bool,
+    pub detail: String,
+}
+
+/// Whether a scan type works with the available capabilities
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanSupport {
+    pub scan_type: String,
+    pub supported: bool,
+    /// Missing capabilities
+    pub missing: Vec<Capability>,
+}
+
+/// Result of the self-test
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CapabilityReport {
+    pub os_type: String,
+    pub privileged: bool,
+    pub interface: Option<String>,
+    pub checks: Vec<CapabilityCheck>,
+    pub scans: Vec<ScanSupport>,
+}
+
+impl CapabilityReport {
+    pub fn is_available(&self, capability: Capability) -> bool {
+        self.checks
+            .iter()
+            .any(|check| check.capability == capability && check.available)
+    }
+}
+
+/// Scan types and the capabilities they need
+const SCAN_REQUIREMENTS: [(&str, &[Capability]); 9] = [
+    (
+        "Port scan (TCP-SYN)",
+        &[Capability::RawSocket, Capability::PacketCapture],
+    ),
+    ("Port scan (TCP-CONNECT)", &[]),
+    (
+        "Host scan (ICMP)",
+        &[Capability::RawSocket, Capability::PacketCapture],
+    ),
+    (
+        "Host scan (TCP/UDP)",
+        &[Capability::RawSocket, Capability::PacketCapture],
+    ),
+    ("Host scan (ARP)", &[Capability::PacketCapture]),
+    ("Ping / Traceroute", &[Capability::PacketCapture]),
+    ("Neighbor discovery (ARP/NDP)", &[Capability::PacketCapture]),
+    ("OS fingerprinting", &[Capability::PacketCapture]),
+    ("IPv6 targets", &[Capability::Ipv6]),
+];
+
+fn check_driver() -> CapabilityCheck {
+    let (available, detail) = match crate::dep::check_dependencies() {
+        Ok(_) => (
+            true,
+            if cfg!(target_os = "windows") {
+                "Npcap is installed".to_string()
+            } else {
+                "Not required".to_string()
+            },
+        ),
+        Err(e) => (false, e.message),
+    };
+    CapabilityCheck {
+        capability: Capability::Driver,
+        available: available,
+        detail: detail,
+    }
+}
+
+fn check_raw_socket() -> CapabilityCheck {
+    let socket_option = SocketOption {
+        ip_version: IpVersion::V4,
+        socket_type: SocketType::Raw,
+        protocol: Some(IpNextLevelProtocol::Tcp),
+        non_blocking: true,
+    };
+    let (available, detail) = match AsyncSocket::new(socket_option) {
+        Ok(_) => (true, "Raw IPv4 socket opened".to_string()),
+        Err(e) => (false, format!("Failed to open raw socket: {}", e)),
+    };
+    CapabilityCheck {
+        capability: Capability::RawSocket,
+        available: available,
+        detail: detail,
+    }
+}
+
+fn check_packet_capture(interface: Option<&Interface>) -> CapabilityCheck {
+    let (available, detail) = match interface {
+        Some(interface) => {
+            let config = nex::datalink::Config {
+                write_buffer_size: 4096,
+                read_buffer_size: 4096,
+                read_timeout: Some(Duration::from_millis(100)),
+                write_timeout: None,
+                channel_type: nex::datalink::ChannelType::Layer2,
+                bpf_fd_attempts: 1000,
+                linux_fanout: None,
+                promiscuous: false,
+            };
+            match nex::datalink::channel(interface, config) {
+                Ok(nex::datalink::Channel::Ethernet(_, _)) => {
+                    (true, format!("Channel opened on {}", interface.name))
+                }
+                Ok(_) => (false, "Unknown channel type".to_string()),
+                Err(e) => (
+                    false,
+                    format!("Failed to open channel on {}: {}", interface.name, e),
+                ),
+            }
+        }
+        None => (false, "No default interface".to_string()),
+    };
+    CapabilityCheck {
+        capability: Capability::PacketCapture,
+        available: available,
+        detail: detail,
+    }
+}
+
+fn check_icmp_socket() -> CapabilityCheck {
+    let socket_option = SocketOption {
+        ip_version: IpVersion::V4,
+        socket_type: SocketType::Dgram,
+        protocol: Some(IpNextLevelProtocol::Icmp),
+        non_blocking: true,
+    };
+    let (available, detail) = match AsyncSocket::new(socket_option) {
+        Ok(_) => (true, "ICMP datagram socket opened".to_string()),
+        // Linux allows it only within net.ipv4.ping_group_range
+        Err(e) => (false, format!("Failed to open ICMP socket: {}", e)),
+    };
+    CapabilityCheck {
+        capability: Capability::IcmpSocket,
+        available: available,
+        detail: detail,
+    }
+}
+
+fn check_ipv6(interface: Option<&Interface>) -> CapabilityCheck {
+    let global_addr: Option<Ipv6Addr> = interface.and_then(|interface| {
+        interface
+            .ipv6
+            .iter()
+            .map(|ipv6| ipv6.addr)
+            .find(|addr| crate::ip::is_global_addr(&IpAddr::V6(*addr)))
+    });
+    // Connecting a UDP socket only looks up the route
+    let route: Result<SocketAddr, String> = UdpSocket::bind("[::]:0")
+        .and_then(|socket| {
+            socket.connect(SocketAddr::new(IpAddr::V6(IPV6_PROBE_ADDR), 53))?;
+            socket.local_addr()
+        })
+        .map_err(|e| e.to_string());
+    let (available, detail) = match (global_addr, route) {
+        (Some(addr), Ok(_)) => (true, format!("Global address {}", addr)),
+        (None, Ok(local_addr)) => (true, format!("Route via {}", local_addr.ip())),
+        (Some(addr), Err(e)) => (
+            false,
+            format!("Global address {} but no route: {}", addr, e),
+        ),
+        (None, Err(e)) => (false, format!("No global address or route: {}", e)),
+    };
+    CapabilityCheck {
+        capability: Capability::Ipv6,
+        available: available,
+        detail: detail,
+    }
+}
+
+/// Test the capabilities used by the scans and map them to the scan types
+pub fn run_self_test() -> CapabilityReport {
+    let interface: Option<Interface> = netdev::get_default_interface().ok();
+    let driver = check_driver();
+    let mut checks: Vec<CapabilityCheck> = vec![driver.clone(), check_raw_socket()];
+    if driver.available {
+        checks.push(check_packet_capture(interface.as_ref()));
+    } else {
+        checks.push(CapabilityCheck {
+            capability: Capability::PacketCapture,
+            available: false,
+            detail: "Packet driver is not available".to_string(),
+        });
+    }
+    checks.push(check_icmp_socket());
+    checks.push(check_ipv6(interface.as_ref()));
+    let mut report = CapabilityReport {
+        os_type: super::os::get_os_type(),
+        privileged: super::os::is_privileged(),
+        interface: interface.map(|interface| interface.name),
+        checks: checks,
+        scans: vec![],
+    };
+    for (scan_type, requirements) in SCAN_REQUIREMENTS {
+        let missing: Vec<Capability> = requirements
+            .iter()
+            .filter(|capability| !report.is_available(**capability))
+            .copied()
+            .collect();
+        report.scans.push(ScanSupport {
+            scan_type: scan_type.to_string(),
+            supported: missing.is_empty(),
+            missing: missing,
+        });
+    }
+    report
+}
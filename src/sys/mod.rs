@@ -1,3 +1,4 @@
+pub mod capability;
 pub mod id;
 pub mod os;
 pub mod time;
@@ -1,3 +1,8 @@
+/// Check if running with administrator (root) privileges
+pub fn is_privileged() -> bool {
+    privilege::user::privileged()
+}
+
 #[cfg(target_os = "windows")]
 pub fn get_os_type() -> String {
     "windows".to_owned()
@@ -0,0 +1,180 @@
+use crate::util::ber::{read_element, read_elements};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_ORGANIZATION: &[u8] = &[0x55, 0x04, 0x0a];
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+
+/// Fields of an X.509 certificate
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// Serial number in hex
+    pub serial: String,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_after: Option<DateTime<Utc>>,
+    /// DNS names and IP addresses of subjectAltName
+    pub sans: Vec<String>,
+    pub self_signed: bool,
+}
+
+impl CertificateInfo {
+    /// Days until expiry. Negative if expired
+    pub fn days_remaining(&self) -> Option<i64> {
+        self.not_after
+            .map(|not_after| (not_after - Utc::now()).num_days())
+    }
+    /// True if a SAN (or the CN if no SAN) matches the host name. Wildcards cover one label
+    pub fn matches_host(&self, host: &str) -> bool {
+        let host: String = host.trim_end_matches('.').to_lowercase();
+        let names: Vec<String> = if self.sans.is_empty() {
+            common_name(&self.subject).into_iter().collect()
+        } else {
+            self.sans.clone()
+        };
+        names.iter().any(|name| {
+            let name: String = name.to_lowercase();
+            match name.strip_prefix("*.") {
+                Some(suffix) => match host.split_once('.') {
+                    Some((label, rest)) => !label.is_empty() && rest == suffix,
+                    None => false,
+                },
+                None => name == host,
+            }
+        })
+    }
+}
+
+/// CN from the distinguished name string
+fn common_name(dn: &str) -> Option<String> {
+    dn.split(", ")
+        .find_map(|rdn| rdn.strip_prefix("CN="))
+        .map(|cn| cn.to_string())
+}
+
+/// Distinguished name as CN=..., O=...
+fn parse_name(data: &[u8]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    // SEQUENCE OF SET OF SEQUENCE { OID, value }
+    for (_, set) in read_elements(data) {
+        for (_, attr) in read_elements(set) {
+            let fields = read_elements(attr);
+            if fields.len() < 2 {
+                continue;
+            }
+            let label: &str = match fields[0].1 {
+                OID_COMMON_NAME => "CN",
+                OID_ORGANIZATION => "O",
+                _ => continue,
+            };
+            parts.push(format!(
+                "{}={}",
+                label,
+                String::from_utf8_lossy(fields[1].1)
+            ));
+        }
+    }
+    parts.join(", ")
+}
+
+/// UTCTime (YYMMDDHHMMSSZ) or GeneralizedTime (YYYYMMDDHHMMSSZ)
+fn parse_time(tag: u8, data: &[u8]) -> Option<DateTime<Utc>> {
+    let s: String = String::from_utf8_lossy(data)
+        .trim_end_matches('Z')
+        .to_string();
+    let s: String = match tag {
+        0x17 => {
+            // RFC 5280: YY >= 50 is 19YY
+            let yy: u32 = s.get(0..2)?.parse().ok()?;
+            let century: &str = if yy >= 50 { "19" } else { "20" };
+            format!("{}{}", century, s)
+        }
+        0x18 => s,
+        _ => return None,
+    };
+    let naive: NaiveDateTime = NaiveDateTime::parse_from_str(&s, "%Y%m%d%H%M%S").ok()?;
+    Some(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
+}
+
+fn parse_sans(data: &[u8]) -> Vec<String> {
+    let mut sans: Vec<String> = Vec::new();
+    let names = match read_element(data) {
+        Some((_, content, _)) => read_elements(content),
+        None => return sans,
+    };
+    for (tag, value) in names {
+        match tag {
+            // dNSName [2]
+            0x82 => sans.push(String::from_utf8_lossy(value).to_string()),
+            // iPAddress [7]
+            0x87 => {
+                let ip_addr: Option<IpAddr> = match value.len() {
+                    4 => Some(IpAddr::V4(Ipv4Addr::new(
+                        value[0], value[1], value[2], value[3],
+                    ))),
+                    16 => {
+                        let mut octets: [u8; 16] = [0; 16];
+                        octets.copy_from_slice(value);
+                        Some(IpAddr::V6(Ipv6Addr::from(octets)))
+                    }
+                    _ => None,
+                };
+                sans.extend(ip_addr.map(|ip_addr| ip_addr.to_string()));
+            }
+            _ => {}
+        }
+    }
+    sans
+}
+
+/// Parse DER-encoded certificate
+pub fn parse_certificate(der: &[u8]) -> Option<CertificateInfo> {
+    // Certificate ::= SEQUENCE { tbsCertificate, signatureAlgorithm, signature }
+    let (_, cert, _) = read_element(der)?;
+    let (_, tbs, _) = read_element(cert)?;
+    let mut fields = read_elements(tbs);
+    // version [0] is optional
+    if fields.first().map(|(tag, _)| *tag) == Some(0xa0) {
+        fields.remove(0);
+    }
+    if fields.len() < 6 {
+        return None;
+    }
+    let serial: String = fields[0]
+        .1
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<String>>()
+        .join(":");
+    let issuer: String = parse_name(fields[2].1);
+    let validity = read_elements(fields[3].1);
+    let subject: String = parse_name(fields[4].1);
+    let mut sans: Vec<String> = Vec::new();
+    // extensions [3]
+    if let Some((_, extensions)) = fields.iter().find(|(tag, _)| *tag == 0xa3) {
+        if let Some((_, list, _)) = read_element(extensions) {
+            for (_, extension) in read_elements(list) {
+                let parts = read_elements(extension);
+                if parts.first().map(|(_, oid)| *oid) != Some(OID_SUBJECT_ALT_NAME) {
+                    continue;
+                }
+                // critical BOOLEAN is optional. extnValue is the last OCTET STRING
+                if let Some((_, value)) = parts.last() {
+                    sans = parse_sans(value);
+                }
+            }
+        }
+    }
+    Some(CertificateInfo {
+        self_signed: !subject.is_empty() && subject == issuer,
+        subject: subject,
+        issuer: issuer,
+        serial: serial,
+        not_before: validity.first().and_then(|(tag, v)| parse_time(*tag, v)),
+        not_after: validity.get(1).and_then(|(tag, v)| parse_time(*tag, v)),
+        sans: sans,
+    })
+}
@@ -0,0 +1,280 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+const CONTENT_TYPE_ALERT: u8 = 0x15;
+const CONTENT_TYPE_HANDSHAKE: u8 = 0x16;
+const HANDSHAKE_CLIENT_HELLO: u8 = 0x01;
+const HANDSHAKE_SERVER_HELLO: u8 = 0x02;
+const HANDSHAKE_CERTIFICATE: u8 = 0x0b;
+const HANDSHAKE_SERVER_HELLO_DONE: u8 = 0x0e;
+const EXT_SERVER_NAME: u16 = 0x0000;
+const EXT_SUPPORTED_GROUPS: u16 = 0x000a;
+const EXT_EC_POINT_FORMATS: u16 = 0x000b;
+const EXT_SIGNATURE_ALGORITHMS: u16 = 0x000d;
+const EXT_SUPPORTED_VERSIONS: u16 = 0x002b;
+const EXT_KEY_SHARE: u16 = 0x0033;
+/// x25519, secp256r1, secp384r1, secp521r1
+const SUPPORTED_GROUPS: [u16; 4] = [0x001d, 0x0017, 0x0018, 0x0019];
+const GROUP_X25519: u16 = 0x001d;
+/// ecdsa, rsa_pss, rsa_pkcs1 with sha256/384/512, then sha1
+const SIGNATURE_ALGORITHMS: [u16; 11] = [
+    0x0403, 0x0503, 0x0603, 0x0804, 0x0805, 0x0806, 0x0401, 0x0501, 0x0601, 0x0203, 0x0201,
+];
+/// Max size of the handshake messages to read
+const MAX_HANDSHAKE_SIZE: usize = 256 * 1024;
+
+/// Protocol versions in ascending order
+pub const SSL3: u16 = 0x0300;
+pub const TLS10: u16 = 0x0301;
+pub const TLS11: u16 = 0x0302;
+pub const TLS12: u16 = 0x0303;
+pub const TLS13: u16 = 0x0304;
+pub const PROTOCOL_VERSIONS: [u16; 5] = [SSL3, TLS10, TLS11, TLS12, TLS13];
+
+pub fn version_name(version: u16) -> &'static str {
+    match version {
+        SSL3 => "SSLv3",
+        TLS10 => "TLSv1.0",
+        TLS11 => "TLSv1.1",
+        TLS12 => "TLSv1.2",
+        TLS13 => "TLSv1.3",
+        _ => "Unknown",
+    }
+}
+
+/// Reply to a ClientHello
+#[derive(Clone, Debug)]
+pub struct ServerHello {
+    pub version: u16,
+    pub cipher_suite: u16,
+    /// DER certificates of the Certificate message (up to TLS 1.2)
+    pub certificates: Vec<Vec<u8>>,
+}
+
+fn push_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_extension(buf: &mut Vec<u8>, ext_type: u16, data: &[u8]) {
+    push_u16(buf, ext_type);
+    push_u16(buf, data.len() as u16);
+    buf.extend_from_slice(data);
+}
+
+fn u16_list(values: &[u16]) -> Vec<u8> {
+    let mut data: Vec<u8> = Vec::new();
+    push_u16(&mut data, (values.len() * 2) as u16);
+    for value in values {
+        push_u16(&mut data, *value);
+    }
+    data
+}
+
+/// Build ClientHello record offering the cipher suites at the version
+pub fn build_client_hello(
+    version: u16,
+    cipher_suites: &[u16],
+    server_name: Option<&str>,
+) -> Vec<u8> {
+    let mut extensions: Vec<u8> = Vec::new();
+    if version > SSL3 {
+        if let Some(server_name) = server_name {
+            let name: &[u8] = server_name.as_bytes();
+            let mut data: Vec<u8> = Vec::new();
+            push_u16(&mut data, (name.len() + 3) as u16);
+            data.push(0);
+            push_u16(&mut data, name.len() as u16);
+            data.extend_from_slice(name);
+            push_extension(&mut extensions, EXT_SERVER_NAME, &data);
+        }
+        push_extension(
+            &mut extensions,
+            EXT_SUPPORTED_GROUPS,
+            &u16_list(&SUPPORTED_GROUPS),
+        );
+        push_extension(&mut extensions, EXT_EC_POINT_FORMATS, &[1, 0]);
+    }
+    if version >= TLS12 {
+        push_extension(
+            &mut extensions,
+            EXT_SIGNATURE_ALGORITHMS,
+            &u16_list(&SIGNATURE_ALGORITHMS),
+        );
+    }
+    if version == TLS13 {
+        push_extension(&mut extensions, EXT_SUPPORTED_VERSIONS, &[2, 0x03, 0x04]);
+        // The handshake is not completed. Any x25519 public key will do
+        let public_key: [u8; 32] = rand::random::<[u8; 32]>();
+        let mut data: Vec<u8> = Vec::new();
+        push_u16(&mut data, 36);
+        push_u16(&mut data, GROUP_X25519);
+        push_u16(&mut data, 32);
+        data.extend_from_slice(&public_key);
+        push_extension(&mut extensions, EXT_KEY_SHARE, &data);
+    }
+    let mut body: Vec<u8> = Vec::new();
+    // TLS 1.3 is negotiated by supported_versions. legacy_version is TLS 1.2
+    push_u16(&mut body, std::cmp::min(version, TLS12));
+    body.extend_from_slice(&rand::random::<[u8; 32]>());
+    // Session ID. TLS 1.3 middlebox compatibility mode expects one
+    if version == TLS13 {
+        body.push(32);
+        body.extend_from_slice(&rand::random::<[u8; 32]>());
+    } else {
+        body.push(0);
+    }
+    body.extend_from_slice(&u16_list(cipher_suites));
+    // Null compression only
+    body.extend_from_slice(&[1, 0]);
+    if !extensions.is_empty() {
+        push_u16(&mut body, extensions.len() as u16);
+        body.extend_from_slice(&extensions);
+    }
+    let mut handshake: Vec<u8> = vec![HANDSHAKE_CLIENT_HELLO];
+    handshake.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&body);
+    let mut record: Vec<u8> = vec![CONTENT_TYPE_HANDSHAKE];
+    push_u16(&mut record, std::cmp::min(version, TLS10));
+    push_u16(&mut record, handshake.len() as u16);
+    record.extend_from_slice(&handshake);
+    record
+}
+
+/// Reads handshake messages across records
+struct HandshakeReader<'a> {
+    stream: &'a mut TcpStream,
+    buf: Vec<u8>,
+}
+
+impl<'a> HandshakeReader<'a> {
+    fn read_record(&mut self) -> Result<(), String> {
+        let mut header: [u8; 5] = [0; 5];
+        self.stream
+            .read_exact(&mut header)
+            .map_err(|e| format!("No handshake response: {}", e))?;
+        let len: usize = u16::from_be_bytes([header[3], header[4]]) as usize;
+        let mut fragment: Vec<u8> = vec![0; len];
+        self.stream
+            .read_exact(&mut fragment)
+            .map_err(|e| format!("Truncated record: {}", e))?;
+        match header[0] {
+            CONTENT_TYPE_HANDSHAKE => {
+                self.buf.extend_from_slice(&fragment);
+                if self.buf.len() > MAX_HANDSHAKE_SIZE {
+                    return Err("Handshake too large".to_string());
+                }
+                Ok(())
+            }
+            CONTENT_TYPE_ALERT => Err(format!(
+                "Alert {}",
+                fragment.get(1).copied().unwrap_or_default()
+            )),
+            content_type => Err(format!("Unexpected record type {}", content_type)),
+        }
+    }
+    /// Next message as (type, body)
+    fn next_message(&mut self) -> Result<(u8, Vec<u8>), String> {
+        loop {
+            if self.buf.len() >= 4 {
+                let len: usize =
+                    u32::from_be_bytes([0, self.buf[1], self.buf[2], self.buf[3]]) as usize;
+                if self.buf.len() >= 4 + len {
+                    let message: Vec<u8> = self.buf.drain(..4 + len).collect();
+                    return Ok((message[0], message[4..].to_vec()));
+                }
+            }
+            self.read_record()?;
+        }
+    }
+}
+
+/// (version, cipher suite) from ServerHello body
+fn parse_server_hello(body: &[u8]) -> Option<(u16, u16)> {
+    let mut version: u16 = u16::from_be_bytes([*body.first()?, *body.get(1)?]);
+    let session_id_len: usize = *body.get(34)? as usize;
+    let mut pos: usize = 35 + session_id_len;
+    let cipher_suite: u16 = u16::from_be_bytes([*body.get(pos)?, *body.get(pos + 1)?]);
+    // Compression method
+    pos += 3;
+    if pos + 2 <= body.len() {
+        let ext_end: usize = pos + 2 + u16::from_be_bytes([body[pos], body[pos + 1]]) as usize;
+        pos += 2;
+        while pos + 4 <= ext_end.min(body.len()) {
+            let ext_type: u16 = u16::from_be_bytes([body[pos], body[pos + 1]]);
+            let ext_len: usize = u16::from_be_bytes([body[pos + 2], body[pos + 3]]) as usize;
+            if ext_type == EXT_SUPPORTED_VERSIONS && ext_len == 2 {
+                version = u16::from_be_bytes([*body.get(pos + 4)?, *body.get(pos + 5)?]);
+            }
+            pos += 4 + ext_len;
+        }
+    }
+    Some((version, cipher_suite))
+}
+
+/// Certificates of the Certificate message body (TLS 1.2 and earlier)
+fn parse_certificates(body: &[u8]) -> Vec<Vec<u8>> {
+    let mut certificates: Vec<Vec<u8>> = Vec::new();
+    let mut pos: usize = 3;
+    while pos + 3 <= body.len() {
+        let len: usize = u32::from_be_bytes([0, body[pos], body[pos + 1], body[pos + 2]]) as usize;
+        pos += 3;
+        match body.get(pos..pos + len) {
+            Some(cert) => certificates.push(cert.to_vec()),
+            None => break,
+        }
+        pos += len;
+    }
+    certificates
+}
+
+/// Send ClientHello and read the server's choice.
+/// Err if the server refuses (alert or close) or answers another version
+pub fn client_hello(
+    socket_addr: &SocketAddr,
+    version: u16,
+    cipher_suites: &[u16],
+    server_name: Option<&str>,
+    read_certificates: bool,
+    timeout: Duration,
+) -> Result<ServerHello, String> {
+    let mut stream: TcpStream = TcpStream::connect_timeout(socket_addr, timeout)
+        .map_err(|e| format!("Failed to connect to {}: {}", socket_addr, e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    stream
+        .write_all(&build_client_hello(version, cipher_suites, server_name))
+        .map_err(|e| format!("Failed to send ClientHello: {}", e))?;
+    let mut reader = HandshakeReader {
+        stream: &mut stream,
+        buf: Vec::new(),
+    };
+    let (msg_type, body) = reader.next_message()?;
+    if msg_type != HANDSHAKE_SERVER_HELLO {
+        return Err(format!("Unexpected handshake message {}", msg_type));
+    }
+    let (server_version, cipher_suite) =
+        parse_server_hello(&body).ok_or("Malformed ServerHello".to_string())?;
+    if server_version != version {
+        return Err(format!("Server answered {}", version_name(server_version)));
+    }
+    let mut hello = ServerHello {
+        version: server_version,
+        cipher_suite: cipher_suite,
+        certificates: vec![],
+    };
+    // TLS 1.3 encrypts the rest
+    if read_certificates && version < TLS13 {
+        while let Ok((msg_type, body)) = reader.next_message() {
+            match msg_type {
+                HANDSHAKE_CERTIFICATE => hello.certificates = parse_certificates(&body),
+                HANDSHAKE_SERVER_HELLO_DONE => break,
+                _ => {}
+            }
+            if !hello.certificates.is_empty() {
+                break;
+            }
+        }
+    }
+    Ok(hello)
+}
@@ -0,0 +1,255 @@
+pub mod cert;
+pub mod cipher;
+pub mod handshake;
+
+use cert::CertificateInfo;
+use handshake::{PROTOCOL_VERSIONS, TLS13};
+use nex::socket::tls::rustls;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Certificates expiring within this many days are reported
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Cipher suite accepted by the server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CipherSuiteInfo {
+    pub code: u16,
+    pub name: String,
+    pub weak: bool,
+}
+
+/// Support of a protocol version
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TlsVersionSupport {
+    pub version: String,
+    pub supported: bool,
+    /// Accepted cipher suites in the server's order of preference
+    pub cipher_suites: Vec<CipherSuiteInfo>,
+}
+
+/// Result of TLS audit
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TlsAuditResult {
+    pub host: String,
+    pub ip_addr: IpAddr,
+    pub port: u16,
+    /// SNI sent and checked against the certificate
+    pub server_name: String,
+    pub versions: Vec<TlsVersionSupport>,
+    /// Certificate chain from the leaf
+    pub certificates: Vec<CertificateInfo>,
+    /// True if the chain is trusted by the system roots and covers the server name
+    pub chain_valid: bool,
+    pub chain_error: Option<String>,
+    /// Findings (deprecated protocols, weak ciphers, expiry, name mismatch)
+    pub issues: Vec<String>,
+    pub scan_time: Duration,
+}
+
+/// Cipher suites accepted at the version, by removing the chosen one and asking again
+fn enumerate_ciphers(
+    socket_addr: &SocketAddr,
+    version: u16,
+    server_name: Option<&str>,
+    timeout: Duration,
+) -> Vec<CipherSuiteInfo> {
+    let mut remaining: Vec<u16> = cipher::CIPHER_SUITES
+        .iter()
+        .map(|(code, _)| *code)
+        .filter(|code| cipher::is_tls13_cipher(*code) == (version == TLS13))
+        .collect();
+    let mut accepted: Vec<CipherSuiteInfo> = Vec::new();
+    while !remaining.is_empty() {
+        let hello = match handshake::client_hello(
+            socket_addr,
+            version,
+            &remaining,
+            server_name,
+            false,
+            timeout,
+        ) {
+            Ok(hello) => hello,
+            Err(_) => break,
+        };
+        // Servers must pick an offered suite
+        let i: usize = match remaining
+            .iter()
+            .position(|code| *code == hello.cipher_suite)
+        {
+            Some(i) => i,
+            None => break,
+        };
+        remaining.remove(i);
+        let name: String = cipher::cipher_name(hello.cipher_suite);
+        accepted.push(CipherSuiteInfo {
+            code: hello.cipher_suite,
+            weak: cipher::is_weak_cipher(&name),
+            name: name,
+        });
+    }
+    accepted
+}
+
+/// Complete a handshake verified against the system roots. Returns the peer certificates
+fn validate_chain(
+    socket_addr: &SocketAddr,
+    server_name: &str,
+    timeout: Duration,
+) -> Result<Vec<Vec<u8>>, String> {
+    let native_certs = nex::socket::tls::certs::get_native_certs()
+        .map_err(|e| format!("Failed to load root certificates: {}", e))?;
+    let config = rustls::ClientConfig::builder()
+        .with_root_certificates(native_certs)
+        .with_no_client_auth();
+    let name = rustls::pki_types::ServerName::try_from(server_name.to_string())
+        .map_err(|e| format!("Invalid server name {}: {}", server_name, e))?;
+    let mut conn = rustls::ClientConnection::new(Arc::new(config), name)
+        .map_err(|e| format!("Failed to create TLS client: {}", e))?;
+    let mut stream: TcpStream = TcpStream::connect_timeout(socket_addr, timeout)
+        .map_err(|e| format!("Failed to connect to {}: {}", socket_addr, e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    while conn.is_handshaking() {
+        conn.complete_io(&mut stream).map_err(|e| e.to_string())?;
+    }
+    Ok(conn
+        .peer_certificates()
+        .map(|certs| certs.iter().map(|cert| cert.as_ref().to_vec()).collect())
+        .unwrap_or_default())
+}
+
+fn find_issues(result: &TlsAuditResult) -> Vec<String> {
+    let mut issues: Vec<String> = Vec::new();
+    for version in &result.versions {
+        if !version.supported {
+            continue;
+        }
+        if matches!(version.version.as_str(), "SSLv3" | "TLSv1.0" | "TLSv1.1") {
+            issues.push(format!(
+                "Deprecated protocol {} is enabled",
+                version.version
+            ));
+        }
+        for cipher_suite in version.cipher_suites.iter().filter(|c| c.weak) {
+            issues.push(format!(
+                "Weak cipher suite {} ({})",
+                cipher_suite.name, version.version
+            ));
+        }
+    }
+    let modern: bool = result
+        .versions
+        .iter()
+        .any(|v| v.supported && matches!(v.version.as_str(), "TLSv1.2" | "TLSv1.3"));
+    if !modern {
+        issues.push("Neither TLSv1.2 nor TLSv1.3 is supported".to_string());
+    }
+    if let Some(e) = &result.chain_error {
+        issues.push(format!("Certificate validation failed: {}", e));
+    }
+    if let Some(leaf) = result.certificates.first() {
+        if !leaf.matches_host(&result.server_name) {
+            issues.push(format!(
+                "Certificate does not cover {} (SAN: {})",
+                result.server_name,
+                leaf.sans.join(", ")
+            ));
+        }
+        match leaf.days_remaining() {
+            Some(days) if days < 0 => {
+                issues.push(format!("Certificate expired {} days ago", -days))
+            }
+            Some(days) if days < EXPIRY_WARNING_DAYS => {
+                issues.push(format!("Certificate expires in {} days", days))
+            }
+            _ => {}
+        }
+        if leaf.self_signed {
+            issues.push("Certificate is self-signed".to_string());
+        }
+    }
+    issues
+}
+
+/// Enumerate protocol versions and cipher suites, and check the certificate chain
+pub fn audit(
+    host: &str,
+    ip_addr: IpAddr,
+    port: u16,
+    server_name: &str,
+    enum_ciphers: bool,
+    timeout: Duration,
+) -> TlsAuditResult {
+    let start_time: Instant = Instant::now();
+    let socket_addr: SocketAddr = SocketAddr::new(ip_addr, port);
+    // SNI must not be an IP address
+    let sni: Option<&str> = if server_name.parse::<IpAddr>().is_ok() {
+        None
+    } else {
+        Some(server_name)
+    };
+    let mut versions: Vec<TlsVersionSupport> = Vec::new();
+    let mut raw_certificates: Vec<Vec<u8>> = Vec::new();
+    for version in PROTOCOL_VERSIONS {
+        let mut support = TlsVersionSupport {
+            version: handshake::version_name(version).to_string(),
+            supported: false,
+            cipher_suites: vec![],
+        };
+        let all_ciphers: Vec<u16> = cipher::CIPHER_SUITES
+            .iter()
+            .map(|(code, _)| *code)
+            .filter(|code| cipher::is_tls13_cipher(*code) == (version == TLS13))
+            .collect();
+        if let Ok(hello) =
+            handshake::client_hello(&socket_addr, version, &all_ciphers, sni, true, timeout)
+        {
+            support.supported = true;
+            // Keep the chain of the highest version sent in clear
+            if !hello.certificates.is_empty() {
+                raw_certificates = hello.certificates;
+            }
+            support.cipher_suites = if enum_ciphers {
+                enumerate_ciphers(&socket_addr, version, sni, timeout)
+            } else {
+                let name: String = cipher::cipher_name(hello.cipher_suite);
+                vec![CipherSuiteInfo {
+                    code: hello.cipher_suite,
+                    weak: cipher::is_weak_cipher(&name),
+                    name: name,
+                }]
+            };
+        }
+        versions.push(support);
+    }
+    let (chain_valid, chain_error) = match validate_chain(&socket_addr, server_name, timeout) {
+        Ok(certificates) => {
+            if !certificates.is_empty() {
+                raw_certificates = certificates;
+            }
+            (true, None)
+        }
+        Err(e) => (false, Some(e)),
+    };
+    let mut result = TlsAuditResult {
+        host: host.to_string(),
+        ip_addr: ip_addr,
+        port: port,
+        server_name: server_name.to_string(),
+        versions: versions,
+        certificates: raw_certificates
+            .iter()
+            .filter_map(|der| cert::parse_certificate(der))
+            .collect(),
+        chain_valid: chain_valid,
+        chain_error: chain_error,
+        issues: vec![],
+        scan_time: Duration::ZERO,
+    };
+    result.issues = find_issues(&result);
+    result.scan_time = start_time.elapsed();
+    result
+}
@@ -0,0 +1,206 @@
+use crate::ping::result::{TraceHopStat, TracerouteResult};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Kind of difference of a hop against the baseline
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum HopChangeKind {
+    Unchanged,
+    /// Hop not in the baseline
+    Inserted,
+    /// Hop in the baseline but not in the current path
+    Removed,
+    /// Different router answered at the same position
+    Changed,
+}
+
+impl HopChangeKind {
+    pub fn name(&self) -> String {
+        match *self {
+            HopChangeKind::Unchanged => String::from("Unchanged"),
+            HopChangeKind::Inserted => String::from("Inserted"),
+            HopChangeKind::Removed => String::from("Removed"),
+            HopChangeKind::Changed => String::from("Changed"),
+        }
+    }
+}
+
+/// Difference of a hop against the baseline
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HopDiff {
+    pub kind: HopChangeKind,
+    /// TTL in the current path
+    pub ttl: Option<u8>,
+    /// TTL in the baseline path
+    pub baseline_ttl: Option<u8>,
+    /// Address in the current path. None for no response
+    pub ip_addr: Option<IpAddr>,
+    /// Address in the baseline path. None for no response
+    pub baseline_ip_addr: Option<IpAddr>,
+    /// Average RTT in the current path
+    pub rtt: Option<Duration>,
+    /// Average RTT in the baseline path
+    pub baseline_rtt: Option<Duration>,
+    /// RTT delta in milliseconds (current - baseline)
+    pub rtt_delta_ms: Option<f64>,
+    /// RTT delta exceeds the threshold
+    pub rtt_alert: bool,
+}
+
+/// Result of comparing a trace with the baseline
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PathDiff {
+    pub hops: Vec<HopDiff>,
+    /// Any hop inserted, removed or changed
+    pub path_changed: bool,
+    /// Number of hops whose RTT delta exceeds the threshold
+    pub rtt_alerts: usize,
+}
+
+/// Load traceroute result saved with --save
+pub fn load_baseline(file_path: &PathBuf) -> Result<TracerouteResult, String> {
+    let text: String = match std::fs::read_to_string(file_path) {
+        Ok(text) => text,
+        Err(e) => {
+            return Err(format!(
+                "Failed to read {}: {}",
+                file_path.to_string_lossy(),
+                e
+            ))
+        }
+    };
+    match serde_json::from_str::<TracerouteResult>(&text) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(format!(
+            "Failed to parse {} as traceroute result: {}",
+            file_path.to_string_lossy(),
+            e
+        )),
+    }
+}
+
+/// Compare the current path with the baseline.
+///
+/// Hops are aligned by the longest common subsequence of addresses,
+/// so an inserted hop does not mark every following hop as changed.
+pub fn compare_paths(
+    baseline: &TracerouteResult,
+    current: &TracerouteResult,
+    rtt_threshold: Duration,
+) -> PathDiff {
+    let base_hops: Vec<TraceHopStat> = TraceHopStat::from_nodes(&baseline.nodes);
+    let cur_hops: Vec<TraceHopStat> = TraceHopStat::from_nodes(&current.nodes);
+    let base_path: Vec<Option<IpAddr>> = base_hops
+        .iter()
+        .map(|h| h.ip_addrs.first().cloned())
+        .collect();
+    let cur_path: Vec<Option<IpAddr>> = cur_hops
+        .iter()
+        .map(|h| h.ip_addrs.first().cloned())
+        .collect();
+    // LCS table
+    let (n, m) = (base_path.len(), cur_path.len());
+    let mut lcs: Vec<Vec<usize>> = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if base_path[i] == cur_path[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                std::cmp::max(lcs[i + 1][j], lcs[i][j + 1])
+            };
+        }
+    }
+    let mut hops: Vec<HopDiff> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && base_path[i] == cur_path[j] {
+            hops.push(hop_diff(
+                HopChangeKind::Unchanged,
+                Some(&base_hops[i]),
+                Some(&cur_hops[j]),
+                rtt_threshold,
+            ));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            hops.push(hop_diff(
+                HopChangeKind::Removed,
+                Some(&base_hops[i]),
+                None,
+                rtt_threshold,
+            ));
+            i += 1;
+        } else {
+            // Removed hop followed by inserted hop at the same position is a change
+            match hops.last() {
+                Some(last) if last.kind == HopChangeKind::Removed => {
+                    let last: HopDiff = hops.pop().unwrap();
+                    let base_hop: Option<&TraceHopStat> =
+                        base_hops.iter().find(|h| Some(h.ttl) == last.baseline_ttl);
+                    hops.push(hop_diff(
+                        HopChangeKind::Changed,
+                        base_hop,
+                        Some(&cur_hops[j]),
+                        rtt_threshold,
+                    ));
+                }
+                _ => {
+                    hops.push(hop_diff(
+                        HopChangeKind::Inserted,
+                        None,
+                        Some(&cur_hops[j]),
+                        rtt_threshold,
+                    ));
+                }
+            }
+            j += 1;
+        }
+    }
+    let path_changed: bool = hops.iter().any(|h| h.kind != HopChangeKind::Unchanged);
+    let rtt_alerts: usize = hops.iter().filter(|h| h.rtt_alert).count();
+    PathDiff {
+        hops: hops,
+        path_changed: path_changed,
+        rtt_alerts: rtt_alerts,
+    }
+}
+
+fn hop_diff(
+    kind: HopChangeKind,
+    base_hop: Option<&TraceHopStat>,
+    cur_hop: Option<&TraceHopStat>,
+    rtt_threshold: Duration,
+) -> HopDiff {
+    let rtt_of = |hop: Option<&TraceHopStat>| -> Option<Duration> {
+        match hop {
+            Some(hop) if hop.received > 0 => Some(hop.avg_rtt),
+            _ => None,
+        }
+    };
+    let rtt: Option<Duration> = rtt_of(cur_hop);
+    let baseline_rtt: Option<Duration> = rtt_of(base_hop);
+    let rtt_delta_ms: Option<f64> = match (rtt, baseline_rtt) {
+        (Some(rtt), Some(baseline_rtt)) => {
+            Some((rtt.as_secs_f64() - baseline_rtt.as_secs_f64()) * 1000.0)
+        }
+        _ => None,
+    };
+    let rtt_alert: bool = kind == HopChangeKind::Unchanged
+        && match rtt_delta_ms {
+            Some(delta) => delta.abs() > rtt_threshold.as_secs_f64() * 1000.0,
+            None => false,
+        };
+    HopDiff {
+        kind: kind,
+        ttl: cur_hop.map(|h| h.ttl),
+        baseline_ttl: base_hop.map(|h| h.ttl),
+        ip_addr: cur_hop.and_then(|h| h.ip_addrs.first().cloned()),
+        baseline_ip_addr: base_hop.and_then(|h| h.ip_addrs.first().cloned()),
+        rtt: rtt,
+        baseline_rtt: baseline_rtt,
+        rtt_delta_ms: rtt_delta_ms,
+        rtt_alert: rtt_alert,
+    }
+}
@@ -1,2 +1,3 @@
+pub mod baseline;
 pub mod setting;
 pub mod tracer;
@@ -1,10 +1,14 @@
 use std::net::Ipv4Addr;
 use std::{net::IpAddr, time::Duration};
 
+use netdev::mac::MacAddr;
 use netdev::Interface;
 use serde::{Deserialize, Serialize};
 
-use crate::config::{DEFAULT_BASE_TARGET_UDP_PORT, DEFAULT_HOP_LIMIT};
+use crate::config::{
+    DEFAULT_BASE_TARGET_UDP_PORT, DEFAULT_HOP_LIMIT, DEFAULT_LOCAL_UDP_PORT,
+    DEFAULT_TRACE_DNS_TIMEOUT_MILLIS,
+};
 use crate::protocol::Protocol;
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -13,11 +17,34 @@ pub struct TraceSetting {
     pub dst_hostname: String,
     pub dst_ip: IpAddr,
     pub dst_port: u16,
+    /// Destination MAC address. Used for on-link (e.g. link-local) target instead of gateway
+    pub dst_mac: Option<MacAddr>,
+    /// Source IP address. Chosen from the interface if None
+    pub src_ip: Option<IpAddr>,
+    /// Source port. Kept constant across TTLs so that the flow identifier
+    /// (and UDP checksum) stays the same on load-balanced paths
+    pub src_port: u16,
     pub hop_limit: u8,
+    /// TTL of the first probe
+    pub first_hop: u8,
+    /// Number of TTLs probed concurrently. 1 for sequential trace
+    pub parallel: u8,
+    /// Number of probes per TTL
+    pub queries: u8,
+    /// TOS (IPv4) or Traffic Class (IPv6) of probes
+    pub tos: u8,
     pub protocol: Protocol,
+    /// Read timeout of the receive channel
     pub receive_timeout: Duration,
+    /// Time to wait for the reply of each probe before moving on to the next hop
+    pub hop_timeout: Duration,
+    /// Timeout of the whole trace
     pub probe_timeout: Duration,
     pub send_rate: Duration,
+    /// Resolve host names of hops
+    pub resolve: bool,
+    /// Timeout of each reverse DNS (PTR) lookup
+    pub dns_timeout: Duration,
     pub tunnel: bool,
     pub loopback: bool,
 }
@@ -29,11 +56,21 @@ impl Default for TraceSetting {
             dst_hostname: "localhost".to_string(),
             dst_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
             dst_port: DEFAULT_BASE_TARGET_UDP_PORT,
+            dst_mac: None,
+            src_ip: None,
+            src_port: DEFAULT_LOCAL_UDP_PORT,
             hop_limit: DEFAULT_HOP_LIMIT,
+            first_hop: 1,
+            parallel: 1,
+            queries: 1,
+            tos: 0,
             protocol: Protocol::UDP,
             receive_timeout: Duration::from_secs(1),
+            hop_timeout: Duration::from_secs(1),
             probe_timeout: Duration::from_secs(30),
             send_rate: Duration::from_secs(1),
+            resolve: true,
+            dns_timeout: Duration::from_millis(DEFAULT_TRACE_DNS_TIMEOUT_MILLIS),
             tunnel: false,
             loopback: false,
         }
@@ -50,11 +87,21 @@ impl TraceSetting {
             dst_ip: dst_ip_addr,
             dst_hostname: dst_ip_addr.to_string(),
             dst_port: DEFAULT_BASE_TARGET_UDP_PORT,
+            dst_mac: None,
+            src_ip: None,
+            src_port: DEFAULT_LOCAL_UDP_PORT,
             hop_limit: 64,
+            first_hop: 1,
+            parallel: 1,
+            queries: 1,
+            tos: 0,
             protocol: Protocol::UDP,
             receive_timeout: Duration::from_secs(1),
+            hop_timeout: Duration::from_secs(1),
             probe_timeout: Duration::from_secs(30),
             send_rate: Duration::from_secs(1),
+            resolve: true,
+            dns_timeout: Duration::from_millis(DEFAULT_TRACE_DNS_TIMEOUT_MILLIS),
             tunnel: use_tun,
             loopback: loopback,
         };
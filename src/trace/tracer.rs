@@ -1,6 +1,7 @@
 use crate::host::{NodeType, PortStatus};
+use crate::packet::mpls::parse_mpls_labels;
 use crate::packet::setting::PacketBuildSetting;
-use crate::ping::result::TracerouteResult;
+use crate::ping::result::{TraceHopStat, TracerouteResult};
 use crate::probe::{ProbeResult, ProbeStatus};
 use crate::protocol::Protocol;
 use netdev::Interface;
@@ -9,6 +10,7 @@ use nex::net::mac::MacAddr;
 use nex::packet::frame::{Frame, ParseOption};
 use nex::packet::icmp::IcmpType;
 use nex::packet::icmpv6::Icmpv6Type;
+use std::collections::HashMap;
 use std::net::IpAddr;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
@@ -73,7 +75,7 @@ fn run_traceroute(
     let config = nex::datalink::Config {
         write_buffer_size: 4096,
         read_buffer_size: 4096,
-        read_timeout: Some(setting.receive_timeout),
+        read_timeout: Some(std::cmp::min(setting.receive_timeout, setting.hop_timeout)),
         write_timeout: None,
         channel_type: nex::datalink::ChannelType::Layer2,
         bpf_fd_attempts: 1000,
@@ -90,7 +92,11 @@ fn run_traceroute(
         crate::protocol::Protocol::ICMP => Err("ICMP traceroute is not supported".to_string()),
         crate::protocol::Protocol::TCP => Err("TCP traceroute is not supported".to_string()),
         crate::protocol::Protocol::UDP => {
-            let result = udp_trace(&mut tx, &mut rx, setting, msg_tx);
+            let result = if setting.parallel > 1 {
+                udp_trace_parallel(&mut tx, &mut rx, setting, msg_tx)
+            } else {
+                udp_trace(&mut tx, &mut rx, setting, msg_tx)
+            };
             return Ok(result);
         }
         _ => {
@@ -113,185 +119,229 @@ pub fn udp_trace(
         parse_option.from_ip_packet = true;
         parse_option.offset = payload_offset;
     }
+    // Offset of IP header in received packet
+    let ip_offset: usize = if setting.tunnel {
+        parse_option.offset
+    } else {
+        14
+    };
     result.start_time = crate::sys::time::get_sysdate();
     let start_time = Instant::now();
     let mut responses: Vec<ProbeResult> = Vec::new();
     let mut dst_reached: bool = false;
-    for seq_ttl in 1..setting.hop_limit {
+    let mut timed_out: bool = false;
+    for seq_ttl in setting.first_hop..setting.hop_limit {
+        if start_time.elapsed() > setting.probe_timeout {
+            timed_out = true;
+            break;
+        }
         let packet_setting: PacketBuildSetting =
             PacketBuildSetting::from_trace_setting(setting, seq_ttl);
         let udp_packet: Vec<u8> = crate::packet::udp::build_udp_packet(packet_setting.clone());
         //let udp_packet: Vec<u8> = crate::packet::udp::build_udp_packet(setting.clone(), Some(seq_ttl));
-        let send_time = Instant::now();
-        match tx.send(&udp_packet) {
-            Some(_) => {}
-            None => {}
-        }
-        loop {
-            match rx.next() {
-                Ok(packet) => {
-                    let recv_time: Duration = Instant::now().duration_since(send_time);
-                    let frame: Frame = Frame::from_bytes(&packet, parse_option.clone());
-                    // Datalink
-                    let mut mac_addr: MacAddr = MacAddr::zero();
-                    if let Some(datalink_layer) = &frame.datalink {
-                        // Ethernet
-                        if let Some(ethernet_header) = &datalink_layer.ethernet {
-                            mac_addr = ethernet_header.source;
-                        }
-                    }
-                    if let Some(ip_layer) = &frame.ip {
-                        // IPv4
-                        if let Some(ipv4_header) = &ip_layer.ipv4 {
-                            if IpAddr::V4(ipv4_header.destination) != packet_setting.src_ip {
-                                continue;
+        // Send multiple queries per TTL
+        for _ in 0..setting.queries {
+            let send_time = Instant::now();
+            match tx.send(&udp_packet) {
+                Some(_) => {}
+                None => {}
+            }
+            loop {
+                match rx.next() {
+                    Ok(packet) => {
+                        let recv_time: Duration = Instant::now().duration_since(send_time);
+                        let frame: Frame = Frame::from_bytes(&packet, parse_option.clone());
+                        // Datalink
+                        let mut mac_addr: MacAddr = MacAddr::zero();
+                        if let Some(datalink_layer) = &frame.datalink {
+                            // Ethernet
+                            if let Some(ethernet_header) = &datalink_layer.ethernet {
+                                mac_addr = ethernet_header.source;
                             }
-                            // ICMP
-                            if let Some(icmp_header) = &ip_layer.icmp {
-                                match icmp_header.icmp_type {
-                                    IcmpType::TimeExceeded => {
-                                        let probe_result: ProbeResult = ProbeResult {
-                                            seq: seq_ttl as u32,
-                                            mac_addr: mac_addr,
-                                            ip_addr: IpAddr::V4(ipv4_header.source),
-                                            host_name: ipv4_header.source.to_string(),
-                                            port_number: None,
-                                            port_status: None,
-                                            ttl: ipv4_header.ttl,
-                                            hop: crate::ip::guess_initial_ttl(ipv4_header.ttl)
-                                                - ipv4_header.ttl,
-                                            rtt: recv_time,
-                                            probe_status: ProbeStatus::new(),
-                                            protocol: Protocol::UDP,
-                                            node_type: if seq_ttl == 1 {
-                                                NodeType::DefaultGateway
-                                            } else {
-                                                NodeType::Relay
-                                            },
-                                            sent_packet_size: udp_packet.len(),
-                                            received_packet_size: packet.len(),
-                                        };
-                                        responses.push(probe_result.clone());
-                                        match msg_tx.lock() {
-                                            Ok(lr) => match lr.send(probe_result) {
-                                                Ok(_) => {}
+                        }
+                        if let Some(ip_layer) = &frame.ip {
+                            // IPv4
+                            if let Some(ipv4_header) = &ip_layer.ipv4 {
+                                if IpAddr::V4(ipv4_header.destination) != packet_setting.src_ip {
+                                    continue;
+                                }
+                                // ICMP
+                                if let Some(icmp_header) = &ip_layer.icmp {
+                                    match icmp_header.icmp_type {
+                                        IcmpType::TimeExceeded => {
+                                            let probe_result: ProbeResult = ProbeResult {
+                                                seq: seq_ttl as u32,
+                                                mac_addr: mac_addr,
+                                                ip_addr: IpAddr::V4(ipv4_header.source),
+                                                host_name: ipv4_header.source.to_string(),
+                                                port_number: None,
+                                                port_status: None,
+                                                ttl: ipv4_header.ttl,
+                                                hop: crate::ip::guess_initial_ttl(ipv4_header.ttl)
+                                                    - ipv4_header.ttl,
+                                                rtt: recv_time,
+                                                probe_status: ProbeStatus::new(),
+                                                protocol: Protocol::UDP,
+                                                node_type: if seq_ttl == 1 {
+                                                    NodeType::DefaultGateway
+                                                } else {
+                                                    NodeType::Relay
+                                                },
+                                                sent_packet_size: udp_packet.len(),
+                                                received_packet_size: packet.len(),
+                                                mpls_labels: parse_mpls_labels(&packet, ip_offset),
+                                                ip_options: None,
+                                            };
+                                            responses.push(probe_result.clone());
+                                            match msg_tx.lock() {
+                                                Ok(lr) => match lr.send(probe_result) {
+                                                    Ok(_) => {}
+                                                    Err(_) => {}
+                                                },
                                                 Err(_) => {}
-                                            },
-                                            Err(_) => {}
+                                            }
+                                            break;
                                         }
-                                        break;
-                                    }
-                                    IcmpType::DestinationUnreachable => {
-                                        let probe_result: ProbeResult = ProbeResult {
-                                            seq: seq_ttl as u32,
-                                            mac_addr: mac_addr,
-                                            ip_addr: IpAddr::V4(ipv4_header.source),
-                                            host_name: ipv4_header.source.to_string(),
-                                            port_number: Some(setting.dst_port),
-                                            port_status: Some(PortStatus::Closed),
-                                            ttl: ipv4_header.ttl,
-                                            hop: crate::ip::guess_initial_ttl(ipv4_header.ttl)
-                                                - ipv4_header.ttl,
-                                            rtt: recv_time,
-                                            probe_status: ProbeStatus::new(),
-                                            protocol: Protocol::UDP,
-                                            node_type: NodeType::Destination,
-                                            sent_packet_size: udp_packet.len(),
-                                            received_packet_size: packet.len(),
-                                        };
-                                        responses.push(probe_result.clone());
-                                        match msg_tx.lock() {
-                                            Ok(lr) => match lr.send(probe_result) {
-                                                Ok(_) => {}
+                                        IcmpType::DestinationUnreachable => {
+                                            let probe_result: ProbeResult = ProbeResult {
+                                                seq: seq_ttl as u32,
+                                                mac_addr: mac_addr,
+                                                ip_addr: IpAddr::V4(ipv4_header.source),
+                                                host_name: ipv4_header.source.to_string(),
+                                                port_number: Some(setting.dst_port),
+                                                port_status: Some(PortStatus::Closed),
+                                                ttl: ipv4_header.ttl,
+                                                hop: crate::ip::guess_initial_ttl(ipv4_header.ttl)
+                                                    - ipv4_header.ttl,
+                                                rtt: recv_time,
+                                                probe_status: ProbeStatus::new(),
+                                                protocol: Protocol::UDP,
+                                                node_type: NodeType::Destination,
+                                                sent_packet_size: udp_packet.len(),
+                                                received_packet_size: packet.len(),
+                                                mpls_labels: Vec::new(),
+                                                ip_options: None,
+                                            };
+                                            responses.push(probe_result.clone());
+                                            match msg_tx.lock() {
+                                                Ok(lr) => match lr.send(probe_result) {
+                                                    Ok(_) => {}
+                                                    Err(_) => {}
+                                                },
                                                 Err(_) => {}
-                                            },
-                                            Err(_) => {}
+                                            }
+                                            dst_reached = true;
+                                            break;
                                         }
-                                        dst_reached = true;
-                                        break;
+                                        _ => {}
                                     }
-                                    _ => {}
                                 }
                             }
-                        }
-                        // IPv6
-                        if let Some(ipv6_header) = &ip_layer.ipv6 {
-                            if IpAddr::V6(ipv6_header.destination) != packet_setting.src_ip {
-                                continue;
-                            }
-                            // ICMPv6
-                            if let Some(icmpv6_header) = &ip_layer.icmpv6 {
-                                match icmpv6_header.icmpv6_type {
-                                    Icmpv6Type::TimeExceeded => {
-                                        let probe_result: ProbeResult = ProbeResult {
-                                            seq: seq_ttl as u32,
-                                            mac_addr: mac_addr,
-                                            ip_addr: IpAddr::V6(ipv6_header.source),
-                                            host_name: ipv6_header.source.to_string(),
-                                            port_number: None,
-                                            port_status: None,
-                                            ttl: ipv6_header.hop_limit,
-                                            hop: crate::ip::guess_initial_ttl(
-                                                ipv6_header.hop_limit,
-                                            ) - ipv6_header.hop_limit,
-                                            rtt: recv_time,
-                                            probe_status: ProbeStatus::new(),
-                                            protocol: Protocol::UDP,
-                                            node_type: if seq_ttl == 1 {
-                                                NodeType::DefaultGateway
-                                            } else {
-                                                NodeType::Relay
-                                            },
-                                            sent_packet_size: udp_packet.len(),
-                                            received_packet_size: packet.len(),
-                                        };
-                                        responses.push(probe_result.clone());
-                                        match msg_tx.lock() {
-                                            Ok(lr) => match lr.send(probe_result) {
-                                                Ok(_) => {}
+                            // IPv6
+                            if let Some(ipv6_header) = &ip_layer.ipv6 {
+                                if IpAddr::V6(ipv6_header.destination) != packet_setting.src_ip {
+                                    continue;
+                                }
+                                // ICMPv6
+                                if let Some(icmpv6_header) = &ip_layer.icmpv6 {
+                                    match icmpv6_header.icmpv6_type {
+                                        Icmpv6Type::TimeExceeded => {
+                                            let probe_result: ProbeResult = ProbeResult {
+                                                seq: seq_ttl as u32,
+                                                mac_addr: mac_addr,
+                                                ip_addr: IpAddr::V6(ipv6_header.source),
+                                                host_name: ipv6_header.source.to_string(),
+                                                port_number: None,
+                                                port_status: None,
+                                                ttl: ipv6_header.hop_limit,
+                                                hop: crate::ip::guess_initial_ttl(
+                                                    ipv6_header.hop_limit,
+                                                ) - ipv6_header.hop_limit,
+                                                rtt: recv_time,
+                                                probe_status: ProbeStatus::new(),
+                                                protocol: Protocol::UDP,
+                                                node_type: if seq_ttl == 1 {
+                                                    NodeType::DefaultGateway
+                                                } else {
+                                                    NodeType::Relay
+                                                },
+                                                sent_packet_size: udp_packet.len(),
+                                                received_packet_size: packet.len(),
+                                                mpls_labels: parse_mpls_labels(&packet, ip_offset),
+                                                ip_options: None,
+                                            };
+                                            responses.push(probe_result.clone());
+                                            match msg_tx.lock() {
+                                                Ok(lr) => match lr.send(probe_result) {
+                                                    Ok(_) => {}
+                                                    Err(_) => {}
+                                                },
                                                 Err(_) => {}
-                                            },
-                                            Err(_) => {}
+                                            }
+                                            break;
                                         }
-                                        break;
-                                    }
-                                    Icmpv6Type::DestinationUnreachable => {
-                                        let probe_result: ProbeResult = ProbeResult {
-                                            seq: seq_ttl as u32,
-                                            mac_addr: mac_addr,
-                                            ip_addr: IpAddr::V6(ipv6_header.source),
-                                            host_name: ipv6_header.source.to_string(),
-                                            port_number: Some(setting.dst_port),
-                                            port_status: Some(PortStatus::Closed),
-                                            ttl: ipv6_header.hop_limit,
-                                            hop: crate::ip::guess_initial_ttl(
-                                                ipv6_header.hop_limit,
-                                            ) - ipv6_header.hop_limit,
-                                            rtt: recv_time,
-                                            probe_status: ProbeStatus::new(),
-                                            protocol: Protocol::UDP,
-                                            node_type: NodeType::Destination,
-                                            sent_packet_size: udp_packet.len(),
-                                            received_packet_size: packet.len(),
-                                        };
-                                        responses.push(probe_result.clone());
-                                        match msg_tx.lock() {
-                                            Ok(lr) => match lr.send(probe_result) {
-                                                Ok(_) => {}
+                                        Icmpv6Type::DestinationUnreachable => {
+                                            let probe_result: ProbeResult = ProbeResult {
+                                                seq: seq_ttl as u32,
+                                                mac_addr: mac_addr,
+                                                ip_addr: IpAddr::V6(ipv6_header.source),
+                                                host_name: ipv6_header.source.to_string(),
+                                                port_number: Some(setting.dst_port),
+                                                port_status: Some(PortStatus::Closed),
+                                                ttl: ipv6_header.hop_limit,
+                                                hop: crate::ip::guess_initial_ttl(
+                                                    ipv6_header.hop_limit,
+                                                ) - ipv6_header.hop_limit,
+                                                rtt: recv_time,
+                                                probe_status: ProbeStatus::new(),
+                                                protocol: Protocol::UDP,
+                                                node_type: NodeType::Destination,
+                                                sent_packet_size: udp_packet.len(),
+                                                received_packet_size: packet.len(),
+                                                mpls_labels: Vec::new(),
+                                                ip_options: None,
+                                            };
+                                            responses.push(probe_result.clone());
+                                            match msg_tx.lock() {
+                                                Ok(lr) => match lr.send(probe_result) {
+                                                    Ok(_) => {}
+                                                    Err(_) => {}
+                                                },
                                                 Err(_) => {}
-                                            },
-                                            Err(_) => {}
+                                            }
+                                            dst_reached = true;
+                                            break;
                                         }
-                                        dst_reached = true;
-                                        break;
+                                        _ => {}
                                     }
-                                    _ => {}
                                 }
                             }
                         }
                     }
+                    Err(_e) => {
+                        // Read timeout only polls; keep waiting until the hop timeout
+                        if send_time.elapsed() < setting.hop_timeout {
+                            continue;
+                        }
+                        let probe_result = ProbeResult::trace_timeout(
+                            seq_ttl as u32,
+                            Protocol::UDP,
+                            udp_packet.len(),
+                            NodeType::Relay,
+                        );
+                        responses.push(probe_result.clone());
+                        match msg_tx.lock() {
+                            Ok(lr) => match lr.send(probe_result) {
+                                Ok(_) => {}
+                                Err(_) => {}
+                            },
+                            Err(_) => {}
+                        }
+                        break;
+                    }
                 }
-                Err(_e) => {
+                let wait_time: Duration = Instant::now().duration_since(send_time);
+                if wait_time > setting.hop_timeout {
                     let probe_result = ProbeResult::trace_timeout(
                         seq_ttl as u32,
                         Protocol::UDP,
@@ -309,14 +359,95 @@ pub fn udp_trace(
                     break;
                 }
             }
-            let wait_time: Duration = Instant::now().duration_since(send_time);
-            if wait_time > setting.receive_timeout {
-                let probe_result = ProbeResult::trace_timeout(
-                    seq_ttl as u32,
-                    Protocol::UDP,
-                    udp_packet.len(),
-                    NodeType::Relay,
-                );
+        }
+        if dst_reached {
+            break;
+        }
+        if seq_ttl < setting.hop_limit {
+            std::thread::sleep(setting.send_rate);
+        }
+    }
+    let probe_time = Instant::now().duration_since(start_time);
+    result.end_time = crate::sys::time::get_sysdate();
+    result.elapsed_time = probe_time;
+    result.hops = TraceHopStat::from_nodes(&responses);
+    result.nodes = responses;
+    result.probe_status = if timed_out {
+        ProbeStatus::with_timeout_message(format!(
+            "Trace timed out after {:?}",
+            setting.probe_timeout
+        ))
+    } else {
+        ProbeStatus::new()
+    };
+    result
+}
+
+/// Parallel UDP traceroute.
+///
+/// Probes `setting.parallel` TTLs at once. Replies are correlated by the IPv4 identification
+/// quoted in the ICMP error, or by the TTL carried in the payload for IPv6.
+/// Ports, UDP length and UDP checksum are unchanged, so the flow identifier stays the same across TTLs.
+pub fn udp_trace_parallel(
+    tx: &mut Box<dyn RawSender>,
+    rx: &mut Box<dyn RawReceiver>,
+    setting: &TraceSetting,
+    msg_tx: &Arc<Mutex<Sender<ProbeResult>>>,
+) -> TracerouteResult {
+    let mut result = TracerouteResult::new();
+    result.protocol = Protocol::UDP;
+    let mut parse_option: ParseOption = ParseOption::default();
+    if setting.tunnel {
+        let payload_offset = if setting.loopback { 14 } else { 0 };
+        parse_option.from_ip_packet = true;
+        parse_option.offset = payload_offset;
+    }
+    // Offset of IP header in received packet
+    let ip_offset: usize = if setting.tunnel {
+        parse_option.offset
+    } else {
+        14
+    };
+    result.start_time = crate::sys::time::get_sysdate();
+    let start_time = Instant::now();
+    let mut responses: Vec<ProbeResult> = Vec::new();
+    let mut dst_reached: bool = false;
+    let mut timed_out: bool = false;
+    let mut batch_start: u8 = setting.first_hop;
+    while batch_start < setting.hop_limit && !dst_reached {
+        if start_time.elapsed() > setting.probe_timeout {
+            timed_out = true;
+            break;
+        }
+        let batch_end: u8 = std::cmp::min(
+            batch_start.saturating_add(setting.parallel),
+            setting.hop_limit,
+        );
+        let mut dst_ttl: Option<u8> = None;
+        let mut rounds: Vec<(HashMap<u8, ProbeResult>, HashMap<u8, usize>)> = Vec::new();
+        for _ in 0..setting.queries {
+            rounds.push(probe_batch(
+                tx,
+                rx,
+                setting,
+                &parse_option,
+                ip_offset,
+                batch_start..batch_end,
+                &mut dst_ttl,
+            ));
+        }
+        // Report in TTL order, up to the destination
+        for seq_ttl in batch_start..batch_end {
+            for (replies, sent_sizes) in rounds.iter_mut() {
+                let probe_result: ProbeResult = match replies.remove(&seq_ttl) {
+                    Some(probe_result) => probe_result,
+                    None => ProbeResult::trace_timeout(
+                        seq_ttl as u32,
+                        Protocol::UDP,
+                        sent_sizes[&seq_ttl],
+                        NodeType::Relay,
+                    ),
+                };
                 responses.push(probe_result.clone());
                 match msg_tx.lock() {
                     Ok(lr) => match lr.send(probe_result) {
@@ -325,20 +456,219 @@ pub fn udp_trace(
                     },
                     Err(_) => {}
                 }
+            }
+            if dst_ttl == Some(seq_ttl) {
+                dst_reached = true;
                 break;
             }
         }
-        if dst_reached {
-            break;
-        }
-        if seq_ttl < setting.hop_limit {
+        batch_start = batch_end;
+        if !dst_reached && batch_start < setting.hop_limit {
             std::thread::sleep(setting.send_rate);
         }
     }
     let probe_time = Instant::now().duration_since(start_time);
     result.end_time = crate::sys::time::get_sysdate();
     result.elapsed_time = probe_time;
+    result.hops = TraceHopStat::from_nodes(&responses);
     result.nodes = responses;
-    result.probe_status = ProbeStatus::new();
+    result.probe_status = if timed_out {
+        ProbeStatus::with_timeout_message(format!(
+            "Trace timed out after {:?}",
+            setting.probe_timeout
+        ))
+    } else {
+        ProbeStatus::new()
+    };
     result
 }
+
+/// Send one probe for each TTL in the batch and collect the correlated replies
+fn probe_batch(
+    tx: &mut Box<dyn RawSender>,
+    rx: &mut Box<dyn RawReceiver>,
+    setting: &TraceSetting,
+    parse_option: &ParseOption,
+    ip_offset: usize,
+    batch: std::ops::Range<u8>,
+    dst_ttl: &mut Option<u8>,
+) -> (HashMap<u8, ProbeResult>, HashMap<u8, usize>) {
+    let (batch_start, batch_end): (u8, u8) = (batch.start, batch.end);
+    let mut send_times: HashMap<u8, Instant> = HashMap::new();
+    let mut sent_sizes: HashMap<u8, usize> = HashMap::new();
+    let mut src_ip: IpAddr = setting.dst_ip;
+    for seq_ttl in batch_start..batch_end {
+        let mut packet_setting: PacketBuildSetting =
+            PacketBuildSetting::from_trace_setting(setting, seq_ttl);
+        packet_setting.ip_id = Some(seq_ttl as u16);
+        packet_setting.payload = probe_payload(seq_ttl);
+        src_ip = packet_setting.src_ip;
+        let udp_packet: Vec<u8> = crate::packet::udp::build_udp_packet(packet_setting);
+        send_times.insert(seq_ttl, Instant::now());
+        sent_sizes.insert(seq_ttl, udp_packet.len());
+        match tx.send(&udp_packet) {
+            Some(_) => {}
+            None => {}
+        }
+    }
+    let last_send_time = Instant::now();
+    let mut replies: HashMap<u8, ProbeResult> = HashMap::new();
+    loop {
+        // Done if every probe up to the destination has been answered
+        let wanted_end: u8 = match *dst_ttl {
+            Some(ttl) => ttl + 1,
+            None => batch_end,
+        };
+        if (batch_start..wanted_end).all(|ttl| replies.contains_key(&ttl)) {
+            break;
+        }
+        if last_send_time.elapsed() > setting.hop_timeout {
+            break;
+        }
+        let packet = match rx.next() {
+            Ok(packet) => packet,
+            Err(_) => continue,
+        };
+        let recv_time: Instant = Instant::now();
+        let frame: Frame = Frame::from_bytes(&packet, parse_option.clone());
+        let mut mac_addr: MacAddr = MacAddr::zero();
+        if let Some(datalink_layer) = &frame.datalink {
+            if let Some(ethernet_header) = &datalink_layer.ethernet {
+                mac_addr = ethernet_header.source;
+            }
+        }
+        let ip_layer = match &frame.ip {
+            Some(ip_layer) => ip_layer,
+            None => continue,
+        };
+        // Source, TTL and whether the reply came from the destination
+        let (source, ttl, is_dst): (IpAddr, u8, bool) = if let Some(ipv4_header) = &ip_layer.ipv4 {
+            if IpAddr::V4(ipv4_header.destination) != src_ip {
+                continue;
+            }
+            match &ip_layer.icmp {
+                Some(icmp_header) => match icmp_header.icmp_type {
+                    IcmpType::TimeExceeded => {
+                        (IpAddr::V4(ipv4_header.source), ipv4_header.ttl, false)
+                    }
+                    IcmpType::DestinationUnreachable => {
+                        (IpAddr::V4(ipv4_header.source), ipv4_header.ttl, true)
+                    }
+                    _ => continue,
+                },
+                None => continue,
+            }
+        } else if let Some(ipv6_header) = &ip_layer.ipv6 {
+            if IpAddr::V6(ipv6_header.destination) != src_ip {
+                continue;
+            }
+            match &ip_layer.icmpv6 {
+                Some(icmpv6_header) => match icmpv6_header.icmpv6_type {
+                    Icmpv6Type::TimeExceeded => {
+                        (IpAddr::V6(ipv6_header.source), ipv6_header.hop_limit, false)
+                    }
+                    Icmpv6Type::DestinationUnreachable => {
+                        (IpAddr::V6(ipv6_header.source), ipv6_header.hop_limit, true)
+                    }
+                    _ => continue,
+                },
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+        let probe_ttl: u8 = match quoted_probe_ttl(&packet, ip_offset, setting.src_port) {
+            Some(ttl) if ttl >= batch_start && ttl < batch_end => ttl,
+            _ => continue,
+        };
+        if replies.contains_key(&probe_ttl) {
+            continue;
+        }
+        let send_time: Instant = send_times[&probe_ttl];
+        let probe_result: ProbeResult = ProbeResult {
+            seq: probe_ttl as u32,
+            mac_addr: mac_addr,
+            ip_addr: source,
+            host_name: source.to_string(),
+            port_number: if is_dst { Some(setting.dst_port) } else { None },
+            port_status: if is_dst {
+                Some(PortStatus::Closed)
+            } else {
+                None
+            },
+            ttl: ttl,
+            hop: crate::ip::guess_initial_ttl(ttl) - ttl,
+            rtt: recv_time.duration_since(send_time),
+            probe_status: ProbeStatus::new(),
+            protocol: Protocol::UDP,
+            node_type: if is_dst {
+                NodeType::Destination
+            } else if probe_ttl == 1 {
+                NodeType::DefaultGateway
+            } else {
+                NodeType::Relay
+            },
+            sent_packet_size: sent_sizes[&probe_ttl],
+            received_packet_size: packet.len(),
+            mpls_labels: parse_mpls_labels(&packet, ip_offset),
+            ip_options: None,
+        };
+        if is_dst {
+            *dst_ttl = Some(match *dst_ttl {
+                Some(ttl) => std::cmp::min(ttl, probe_ttl),
+                None => probe_ttl,
+            });
+        }
+        replies.insert(probe_ttl, probe_result);
+    }
+    (replies, sent_sizes)
+}
+
+/// Payload of the UDP probe. The TTL followed by its one's complement,
+/// so the UDP length and checksum are the same for every TTL
+fn probe_payload(ttl: u8) -> Vec<u8> {
+    let mut payload: Vec<u8> = Vec::with_capacity(4);
+    payload.extend_from_slice(&(ttl as u16).to_be_bytes());
+    payload.extend_from_slice(&(!(ttl as u16)).to_be_bytes());
+    payload
+}
+
+/// Get TTL of the UDP probe quoted in ICMP/ICMPv6 error message.
+/// IPv4 uses the quoted identification since routers may quote only 8 bytes of UDP.
+/// ICMPv6 errors quote the whole probe, so IPv6 uses the payload
+fn quoted_probe_ttl(packet: &[u8], ip_offset: usize, src_port: u16) -> Option<u8> {
+    const ICMP_HEADER_LEN: usize = 8;
+    const IPV6_HEADER_LEN: usize = 40;
+    const UDP_HEADER_LEN: usize = 8;
+    let version: u8 = packet.get(ip_offset)? >> 4;
+    let (udp_offset, ip_id): (usize, Option<u16>) = match version {
+        4 => {
+            let ihl: usize = ((packet.get(ip_offset)? & 0x0f) as usize) * 4;
+            let quoted_ip_offset: usize = ip_offset + ihl + ICMP_HEADER_LEN;
+            let quoted_ihl: usize = ((packet.get(quoted_ip_offset)? & 0x0f) as usize) * 4;
+            let ip_id: u16 = u16::from_be_bytes([
+                *packet.get(quoted_ip_offset + 4)?,
+                *packet.get(quoted_ip_offset + 5)?,
+            ]);
+            (quoted_ip_offset + quoted_ihl, Some(ip_id))
+        }
+        6 => (
+            ip_offset + IPV6_HEADER_LEN + ICMP_HEADER_LEN + IPV6_HEADER_LEN,
+            None,
+        ),
+        _ => return None,
+    };
+    let quoted_src_port: u16 =
+        u16::from_be_bytes([*packet.get(udp_offset)?, *packet.get(udp_offset + 1)?]);
+    if quoted_src_port != src_port {
+        return None;
+    }
+    let ttl: u16 = match ip_id {
+        Some(ip_id) => ip_id,
+        None => u16::from_be_bytes([
+            *packet.get(udp_offset + UDP_HEADER_LEN)?,
+            *packet.get(udp_offset + UDP_HEADER_LEN + 1)?,
+        ]),
+    };
+    u8::try_from(ttl).ok()
+}
@@ -0,0 +1,132 @@
+/// BER/DER element. Returns (tag, content, rest)
+pub fn read_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag: u8 = *data.first()?;
+    let first: u8 = *data.get(1)?;
+    let (len, header): (usize, usize) = if first & 0x80 == 0 {
+        (first as usize, 2)
+    } else {
+        let n: usize = (first & 0x7f) as usize;
+        if n == 0 || n > 4 {
+            return None;
+        }
+        let mut len: usize = 0;
+        for i in 0..n {
+            len = (len << 8) | *data.get(2 + i)? as usize;
+        }
+        (len, 2 + n)
+    };
+    let content: &[u8] = data.get(header..header + len)?;
+    Some((tag, content, &data[header + len..]))
+}
+
+/// Elements of a constructed content
+pub fn read_elements(mut data: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut elements: Vec<(u8, &[u8])> = Vec::new();
+    while let Some((tag, content, rest)) = read_element(data) {
+        elements.push((tag, content));
+        data = rest;
+    }
+    elements
+}
+
+/// Encode tag, definite length and content
+pub fn encode_element(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut buf: Vec<u8> = vec![tag];
+    let len: usize = content.len();
+    if len < 0x80 {
+        buf.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .iter()
+            .copied()
+            .skip_while(|b| *b == 0)
+            .collect();
+        buf.push(0x80 | bytes.len() as u8);
+        buf.extend_from_slice(&bytes);
+    }
+    buf.extend_from_slice(content);
+    buf
+}
+
+/// Minimal two's complement encoding of INTEGER content
+pub fn encode_integer(value: i64) -> Vec<u8> {
+    let bytes: [u8; 8] = value.to_be_bytes();
+    let mut start: usize = 0;
+    // Drop redundant sign bytes
+    while start < 7
+        && ((bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0))
+    {
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+/// INTEGER content as signed value
+pub fn decode_integer(data: &[u8]) -> Option<i64> {
+    if data.is_empty() || data.len() > 8 {
+        return None;
+    }
+    let mut value: i64 = if data[0] & 0x80 != 0 { -1 } else { 0 };
+    for b in data {
+        value = (value << 8) | *b as i64;
+    }
+    Some(value)
+}
+
+/// Unsigned value of INTEGER-like content (e.g. Counter32, TimeTicks)
+pub fn decode_unsigned(data: &[u8]) -> Option<u64> {
+    if data.is_empty() || data.len() > 9 {
+        return None;
+    }
+    Some(data.iter().fold(0u64, |value, b| (value << 8) | *b as u64))
+}
+
+/// Encode dotted OID (e.g. 1.3.6.1.2.1.1.1.0) as OBJECT IDENTIFIER content
+pub fn encode_oid(oid: &str) -> Option<Vec<u8>> {
+    let arcs: Vec<u64> = oid
+        .trim_start_matches('.')
+        .split('.')
+        .map(|arc| arc.parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    if arcs.len() < 2 {
+        return None;
+    }
+    let mut buf: Vec<u8> = Vec::new();
+    for arc in std::iter::once(arcs[0] * 40 + arcs[1]).chain(arcs[2..].iter().copied()) {
+        // Base 128, high bit set on all but the last byte
+        let mut bytes: Vec<u8> = vec![(arc & 0x7f) as u8];
+        let mut rest: u64 = arc >> 7;
+        while rest > 0 {
+            bytes.push(0x80 | (rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        bytes.reverse();
+        buf.extend_from_slice(&bytes);
+    }
+    Some(buf)
+}
+
+/// OBJECT IDENTIFIER content as dotted string
+pub fn decode_oid(data: &[u8]) -> String {
+    let mut arcs: Vec<u64> = Vec::new();
+    let mut value: u64 = 0;
+    for b in data {
+        value = (value << 7) | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first: u64 = std::cmp::min(value / 40, 2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+    arcs.iter()
+        .map(|arc| arc.to_string())
+        .collect::<Vec<String>>()
+        .join(".")
+}
@@ -1,2 +1,4 @@
+pub mod ber;
 pub mod setting;
+pub mod sparkline;
 pub mod tree;
@@ -1,3 +1,5 @@
+use netdev::interface::InterfaceType;
+use netdev::Interface;
 use std::time::Duration;
 
 const DEFAULT_WAIT_TIME_FACTOR: f64 = 2.0;
@@ -24,3 +26,19 @@ pub fn caluculate_wait_time(rtt: Duration) -> Duration {
     let wait_time = rtt.as_secs_f64() * factor;
     Duration::from_secs_f64(wait_time)
 }
+
+/// Calculate default send rate (interval between probes) from the link medium and speed
+pub fn calculate_send_rate(interface: &Interface) -> Duration {
+    // Wi-Fi drops bursts easily regardless of the advertised speed
+    if interface.if_type == InterfaceType::Wireless80211 {
+        return Duration::from_millis(1);
+    }
+    match interface.transmit_speed {
+        Some(speed) if speed >= 10_000_000_000 => Duration::from_micros(0),
+        Some(speed) if speed >= 1_000_000_000 => Duration::from_micros(50),
+        Some(speed) if speed >= 100_000_000 => Duration::from_micros(200),
+        Some(speed) if speed > 0 => Duration::from_millis(1),
+        // Unknown link speed
+        _ => Duration::from_micros(200),
+    }
+}
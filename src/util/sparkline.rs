@@ -0,0 +1,47 @@
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Shown for lost probes
+const LOST: char = '·';
+
+/// Render values as a sparkline. None is rendered as lost.
+///
+/// If there are more values than `width`, consecutive values are averaged into buckets.
+pub fn sparkline(values: &[Option<f64>], width: usize) -> String {
+    let points: Vec<Option<f64>> = if width > 0 && values.len() > width {
+        let bucket_size: usize = (values.len() + width - 1) / width;
+        values
+            .chunks(bucket_size)
+            .map(|chunk| {
+                let received: Vec<f64> = chunk.iter().filter_map(|v| *v).collect();
+                if received.is_empty() {
+                    None
+                } else {
+                    Some(received.iter().sum::<f64>() / received.len() as f64)
+                }
+            })
+            .collect()
+    } else {
+        values.to_vec()
+    };
+    let min: f64 = points
+        .iter()
+        .filter_map(|v| *v)
+        .fold(f64::INFINITY, f64::min);
+    let max: f64 = points
+        .iter()
+        .filter_map(|v| *v)
+        .fold(f64::NEG_INFINITY, f64::max);
+    points
+        .iter()
+        .map(|point| match point {
+            Some(value) => {
+                if max > min {
+                    let level = ((value - min) / (max - min) * (BARS.len() - 1) as f64).round();
+                    BARS[level as usize]
+                } else {
+                    BARS[0]
+                }
+            }
+            None => LOST,
+        })
+        .collect()
+}
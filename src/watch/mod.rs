@@ -0,0 +1,146 @@
+use crate::host::Host;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    NewHost,
+    HostGone,
+    PortOpened,
+    PortClosed,
+}
+
+/// Difference between two rounds of a watched scan
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchChange {
+    pub kind: ChangeKind,
+    pub ip_addr: IpAddr,
+    pub hostname: String,
+    pub port: Option<u16>,
+    pub service_name: String,
+    pub detected_at: DateTime<Local>,
+}
+
+impl WatchChange {
+    /// e.g. + 192.168.1.10 port 22 (ssh) opened
+    pub fn message(&self) -> String {
+        let host: String = if self.hostname.is_empty() || self.hostname == self.ip_addr.to_string()
+        {
+            self.ip_addr.to_string()
+        } else {
+            format!("{} ({})", self.ip_addr, self.hostname)
+        };
+        let port: String = match self.port {
+            Some(port) if self.service_name.is_empty() => format!("port {}", port),
+            Some(port) => format!("port {} ({})", port, self.service_name),
+            None => String::new(),
+        };
+        match self.kind {
+            ChangeKind::NewHost => format!("+ New host {}", host),
+            ChangeKind::HostGone => format!("- Host {} is gone", host),
+            ChangeKind::PortOpened => format!("+ {} {} opened", host, port),
+            ChangeKind::PortClosed => format!("- {} {} closed", host, port),
+        }
+    }
+}
+
+fn open_ports(host: &Host) -> BTreeMap<u16, String> {
+    host.get_open_ports()
+        .into_iter()
+        .map(|port| (port.number, port.service_name))
+        .collect()
+}
+
+/// Compare the hosts and open ports of two rounds
+pub fn diff_hosts(previous: &[Host], current: &[Host]) -> Vec<WatchChange> {
+    let now: DateTime<Local> = Local::now();
+    let previous: BTreeMap<IpAddr, &Host> = previous.iter().map(|h| (h.ip_addr, h)).collect();
+    let current: BTreeMap<IpAddr, &Host> = current.iter().map(|h| (h.ip_addr, h)).collect();
+    let ips: BTreeSet<IpAddr> = previous.keys().chain(current.keys()).cloned().collect();
+    let mut changes: Vec<WatchChange> = Vec::new();
+    let change = |kind: ChangeKind, host: &Host, port: Option<(u16, String)>| {
+        let (port, service_name) = match port {
+            Some((port, service_name)) => (Some(port), service_name),
+            None => (None, String::new()),
+        };
+        WatchChange {
+            kind: kind,
+            ip_addr: host.ip_addr,
+            hostname: host.hostname.clone(),
+            port: port,
+            service_name: service_name,
+            detected_at: now,
+        }
+    };
+    for ip in ips {
+        match (previous.get(&ip), current.get(&ip)) {
+            (None, Some(host)) => {
+                changes.push(change(ChangeKind::NewHost, host, None));
+                for port in open_ports(host) {
+                    changes.push(change(ChangeKind::PortOpened, host, Some(port)));
+                }
+            }
+            (Some(host), None) => changes.push(change(ChangeKind::HostGone, host, None)),
+            (Some(before), Some(after)) => {
+                let before_ports: BTreeMap<u16, String> = open_ports(before);
+                let after_ports: BTreeMap<u16, String> = open_ports(after);
+                for (port, service_name) in &after_ports {
+                    if !before_ports.contains_key(port) {
+                        changes.push(change(
+                            ChangeKind::PortOpened,
+                            after,
+                            Some((*port, service_name.clone())),
+                        ));
+                    }
+                }
+                for (port, service_name) in &before_ports {
+                    if !after_ports.contains_key(port) {
+                        changes.push(change(
+                            ChangeKind::PortClosed,
+                            after,
+                            Some((*port, service_name.clone())),
+                        ));
+                    }
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    changes
+}
+
+/// POST the changes as JSON. `text` is set for Slack-compatible incoming webhooks
+pub fn send_webhook(
+    url: &str,
+    command: &str,
+    changes: &[WatchChange],
+    timeout: Duration,
+) -> Result<(), String> {
+    let lines: Vec<String> = changes.iter().map(|change| change.message()).collect();
+    let body = serde_json::json!({
+        "text": format!(
+            "{} {} watch: {} change(s)\n{}",
+            crate::app::CRATE_BIN_NAME,
+            command,
+            changes.len(),
+            lines.join("\n")
+        ),
+        "command": command,
+        "changes": changes,
+    });
+    let response = crate::http::post(
+        url,
+        &[("Content-Type", "application/json")],
+        body.to_string().as_bytes(),
+        timeout,
+    )?;
+    if response.is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status))
+    }
+}
@@ -0,0 +1,257 @@
+pub mod rdap;
+
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// IANA knows the registry of every TLD and IP block
+pub const DEFAULT_WHOIS_SERVER: &str = "whois.iana.org";
+pub const WHOIS_PORT: u16 = 43;
+/// Max number of referrals to follow
+const MAX_REFERRALS: usize = 3;
+
+/// Response of one whois server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WhoisResponse {
+    pub server: String,
+    pub text: String,
+}
+
+/// Key fields parsed from the responses
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WhoisFields {
+    pub registrar: Option<String>,
+    pub creation_date: Option<String>,
+    pub updated_date: Option<String>,
+    pub expiry_date: Option<String>,
+    pub status: Vec<String>,
+    pub name_servers: Vec<String>,
+    pub netname: Option<String>,
+    /// Address range of the network (CIDR or inetnum)
+    pub net_range: Option<String>,
+    pub organization: Option<String>,
+    pub country: Option<String>,
+    pub abuse_contact: Option<String>,
+}
+
+/// Result of whois lookup
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WhoisResult {
+    pub query: String,
+    /// Responses in the order of the referrals
+    pub responses: Vec<WhoisResponse>,
+    pub fields: WhoisFields,
+    pub lookup_time: Duration,
+}
+
+/// Keys of each field. Registries name them differently
+const REGISTRAR_KEYS: [&str; 3] = ["registrar", "sponsoring registrar", "registrar name"];
+const CREATION_KEYS: [&str; 6] = [
+    "creation date",
+    "created",
+    "registered",
+    "regdate",
+    "registration time",
+    "domain registration date",
+];
+const UPDATED_KEYS: [&str; 4] = ["updated date", "last-modified", "changed", "updated"];
+const EXPIRY_KEYS: [&str; 6] = [
+    "registry expiry date",
+    "registrar registration expiration date",
+    "expiry date",
+    "expires",
+    "expiration time",
+    "paid-till",
+];
+const STATUS_KEYS: [&str; 2] = ["domain status", "status"];
+const NAME_SERVER_KEYS: [&str; 3] = ["name server", "nserver", "nameserver"];
+const NETNAME_KEYS: [&str; 2] = ["netname", "net-name"];
+const NET_RANGE_KEYS: [&str; 5] = ["cidr", "inetnum", "inet6num", "netrange", "ip-network"];
+const ORGANIZATION_KEYS: [&str; 5] = [
+    "orgname",
+    "org-name",
+    "organization",
+    "registrant organization",
+    "descr",
+];
+const COUNTRY_KEYS: [&str; 2] = ["country", "registrant country"];
+const ABUSE_KEYS: [&str; 4] = [
+    "orgabuseemail",
+    "abuse-mailbox",
+    "registrar abuse contact email",
+    "abuse contact",
+];
+
+/// Query string for the server. Some servers need flags for the expected output
+fn build_query(server: &str, query: &str, is_ip: bool) -> String {
+    match server {
+        // Network records only, not the related ASN/org listing
+        "whois.arin.net" if is_ip => format!("n + {}", query),
+        "whois.denic.de" => format!("-T dn {}", query),
+        _ => query.to_string(),
+    }
+}
+
+/// Send the query to the server and read until it closes the connection
+pub fn query_server(server: &str, query: &str, timeout: Duration) -> Result<String, String> {
+    let socket_addr: SocketAddr = (server, WHOIS_PORT)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", server, e))?
+        .next()
+        .ok_or(format!("Failed to resolve {}", server))?;
+    let mut stream: TcpStream = TcpStream::connect_timeout(&socket_addr, timeout)
+        .map_err(|e| format!("Failed to connect to {}: {}", server, e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    stream
+        .write_all(format!("{}\r\n", query).as_bytes())
+        .map_err(|e| format!("Failed to send query to {}: {}", server, e))?;
+    let mut buf: Vec<u8> = Vec::new();
+    stream
+        .read_to_end(&mut buf)
+        .map_err(|e| format!("Failed to read response from {}: {}", server, e))?;
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
+
+/// key: value pairs of the response. Comments are skipped
+fn parse_pairs(text: &str) -> Vec<(String, String)> {
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('%') || line.starts_with('#') {
+            // RIPE style abuse note: % Abuse contact for '...' is 'abuse@example.net'
+            if let Some(rest) = line.strip_prefix("% Abuse contact for") {
+                if let Some(email) = rest.rsplit(" is ").next() {
+                    pairs.push((
+                        "abuse contact".to_string(),
+                        email.trim_matches(|c| c == '\'' || c == ' ').to_string(),
+                    ));
+                }
+            }
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let value: &str = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            pairs.push((key.trim().to_lowercase(), value.to_string()));
+        }
+    }
+    pairs
+}
+
+/// Next whois server from the referral, if any
+fn find_referral(text: &str, current: &str) -> Option<String> {
+    for (key, value) in parse_pairs(text) {
+        let server: &str = match key.as_str() {
+            "refer" | "whois" | "registrar whois server" | "referralserver" => value.as_str(),
+            _ => continue,
+        };
+        // RWhois is another protocol
+        if server.starts_with("rwhois://") {
+            continue;
+        }
+        // ARIN: ReferralServer: whois://whois.ripe.net
+        let server: &str = server.trim_start_matches("whois://").trim_end_matches('/');
+        // Host only. Port is always 43
+        let server: &str = server.split(':').next().unwrap_or(server);
+        if server.is_empty() || server.eq_ignore_ascii_case(current) || server.contains('/') {
+            continue;
+        }
+        return Some(server.to_lowercase());
+    }
+    None
+}
+
+fn first_value(pairs: &[(String, String)], keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone()))
+}
+
+fn all_values(pairs: &[(String, String)], keys: &[&str]) -> Vec<String> {
+    let mut values: Vec<String> = Vec::new();
+    for (k, v) in pairs {
+        if keys.contains(&k.as_str()) && !values.iter().any(|value| value.eq_ignore_ascii_case(v)) {
+            values.push(v.clone());
+        }
+    }
+    values
+}
+
+/// Parse key fields. Later (more specific) responses take precedence
+pub fn parse_fields(responses: &[WhoisResponse]) -> WhoisFields {
+    let mut fields = WhoisFields::default();
+    for response in responses {
+        let pairs: Vec<(String, String)> = parse_pairs(&response.text);
+        let set = |field: &mut Option<String>, keys: &[&str]| {
+            if let Some(value) = first_value(&pairs, keys) {
+                *field = Some(value);
+            }
+        };
+        set(&mut fields.registrar, &REGISTRAR_KEYS);
+        set(&mut fields.creation_date, &CREATION_KEYS);
+        set(&mut fields.updated_date, &UPDATED_KEYS);
+        set(&mut fields.expiry_date, &EXPIRY_KEYS);
+        set(&mut fields.netname, &NETNAME_KEYS);
+        set(&mut fields.net_range, &NET_RANGE_KEYS);
+        set(&mut fields.organization, &ORGANIZATION_KEYS);
+        set(&mut fields.country, &COUNTRY_KEYS);
+        set(&mut fields.abuse_contact, &ABUSE_KEYS);
+        let status: Vec<String> = all_values(&pairs, &STATUS_KEYS);
+        if !status.is_empty() {
+            fields.status = status;
+        }
+        let name_servers: Vec<String> = all_values(&pairs, &NAME_SERVER_KEYS)
+            .into_iter()
+            .map(|ns| ns.to_lowercase())
+            .collect();
+        if !name_servers.is_empty() {
+            fields.name_servers = name_servers;
+        }
+    }
+    fields
+}
+
+/// Whois lookup of the domain or IP address, following referrals from the server
+pub fn lookup(query: &str, server: Option<&str>, timeout: Duration) -> Result<WhoisResult, String> {
+    let start_time: Instant = Instant::now();
+    let is_ip: bool = query.parse::<IpAddr>().is_ok();
+    let mut server: String = server.unwrap_or(DEFAULT_WHOIS_SERVER).to_string();
+    let mut responses: Vec<WhoisResponse> = Vec::new();
+    for _ in 0..=MAX_REFERRALS {
+        let text: String = match query_server(&server, &build_query(&server, query, is_ip), timeout)
+        {
+            Ok(text) => text,
+            // Keep the responses so far if a referral fails
+            Err(e) if !responses.is_empty() => {
+                crate::output::log_with_time(&e, "WARN");
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        let referral: Option<String> = find_referral(&text, &server);
+        responses.push(WhoisResponse {
+            server: server.clone(),
+            text: text,
+        });
+        match referral {
+            Some(next) if !responses.iter().any(|r| r.server == next) => server = next,
+            _ => break,
+        }
+    }
+    // IANA describes the TLD or the RIR, not the queried object
+    let parsed: &[WhoisResponse] =
+        if responses.len() > 1 && responses[0].server == DEFAULT_WHOIS_SERVER {
+            &responses[1..]
+        } else {
+            &responses
+        };
+    Ok(WhoisResult {
+        query: query.to_string(),
+        fields: parse_fields(parsed),
+        responses: responses,
+        lookup_time: start_time.elapsed(),
+    })
+}
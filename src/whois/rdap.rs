@@ -0,0 +1,339 @@
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// IANA bootstrap registries (RFC 9224)
+const BOOTSTRAP_BASE_URL: &str = "https://data.iana.org/rdap/";
+const RDAP_ACCEPT: &str = "application/rdap+json, application/json";
+const MAX_REDIRECTS: usize = 5;
+
+/// Object to look up
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RdapQuery {
+    Ip(IpAddr),
+    Asn(u32),
+    Domain(String),
+}
+
+impl RdapQuery {
+    /// IP address, AS number (AS15169 or 15169) or domain name
+    pub fn parse(s: &str) -> Option<RdapQuery> {
+        let s: &str = s.trim().trim_end_matches('.');
+        if let Ok(ip_addr) = s.parse::<IpAddr>() {
+            return Some(RdapQuery::Ip(ip_addr));
+        }
+        let digits: &str = if s.len() > 2 && s[..2].eq_ignore_ascii_case("as") {
+            &s[2..]
+        } else {
+            s
+        };
+        if let Ok(asn) = digits.parse::<u32>() {
+            return Some(RdapQuery::Asn(asn));
+        }
+        if s.contains('.') && !s.contains('/') {
+            return Some(RdapQuery::Domain(s.to_lowercase()));
+        }
+        None
+    }
+    fn bootstrap_file(&self) -> &str {
+        match self {
+            RdapQuery::Ip(IpAddr::V4(_)) => "ipv4.json",
+            RdapQuery::Ip(IpAddr::V6(_)) => "ipv6.json",
+            RdapQuery::Asn(_) => "asn.json",
+            RdapQuery::Domain(_) => "dns.json",
+        }
+    }
+    /// Path relative to the base URL of the server
+    fn path(&self) -> String {
+        match self {
+            RdapQuery::Ip(ip_addr) => format!("ip/{}", ip_addr),
+            RdapQuery::Asn(asn) => format!("autnum/{}", asn),
+            RdapQuery::Domain(name) => format!("domain/{}", name),
+        }
+    }
+    /// True if the entry of the bootstrap registry covers the query
+    fn matches(&self, entry: &str) -> bool {
+        match self {
+            RdapQuery::Ip(ip_addr) => match IpNet::from_str(entry) {
+                Ok(net) => net.contains(ip_addr),
+                Err(_) => false,
+            },
+            RdapQuery::Asn(asn) => {
+                let (start, end) = entry.split_once('-').unwrap_or((entry, entry));
+                match (start.parse::<u32>(), end.parse::<u32>()) {
+                    (Ok(start), Ok(end)) => start <= *asn && *asn <= end,
+                    _ => false,
+                }
+            }
+            RdapQuery::Domain(name) => {
+                let entry: String = entry.to_lowercase();
+                *name == entry || name.ends_with(&format!(".{}", entry))
+            }
+        }
+    }
+    /// Length of the match. The most specific entry wins
+    fn match_len(&self, entry: &str) -> usize {
+        match self {
+            RdapQuery::Ip(_) => IpNet::from_str(entry)
+                .map(|net| net.prefix_len() as usize)
+                .unwrap_or(0),
+            _ => entry.len(),
+        }
+    }
+}
+
+/// Registration event (e.g. registration, expiration, last changed)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RdapEvent {
+    pub action: String,
+    pub date: String,
+}
+
+/// Contact of the object
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RdapEntity {
+    pub handle: String,
+    /// e.g. registrant, registrar, abuse, technical
+    pub roles: Vec<String>,
+    pub name: String,
+    pub email: String,
+}
+
+/// Result of RDAP lookup
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RdapResult {
+    pub query: RdapQuery,
+    /// URL that answered, after redirects
+    pub url: String,
+    /// objectClassName (ip network, autnum, domain)
+    pub object_class: String,
+    pub handle: String,
+    pub name: String,
+    /// Address range (ip network) or AS number range (autnum)
+    pub range: String,
+    pub country: String,
+    pub status: Vec<String>,
+    pub events: Vec<RdapEvent>,
+    pub entities: Vec<RdapEntity>,
+    pub name_servers: Vec<String>,
+    /// Full response
+    pub raw: Value,
+    pub lookup_time: Duration,
+}
+
+impl RdapResult {
+    /// First entity with the role
+    pub fn contact_of(&self, role: &str) -> Option<&RdapEntity> {
+        self.entities
+            .iter()
+            .find(|entity| entity.roles.iter().any(|r| r == role))
+    }
+}
+
+fn get_json(url: &str, timeout: Duration) -> Result<(String, Value), String> {
+    let (url, response) =
+        crate::http::get_following(url, &[("Accept", RDAP_ACCEPT)], MAX_REDIRECTS, timeout)?;
+    if !response.is_success() {
+        // Servers describe the error in the body (e.g. 404 Not Found)
+        let description: String = serde_json::from_slice::<Value>(&response.body)
+            .ok()
+            .and_then(|v| v["title"].as_str().map(|s| s.to_string()))
+            .unwrap_or_default();
+        return Err(
+            format!("{} returned {} {}", url, response.status, description)
+                .trim_end()
+                .to_string(),
+        );
+    }
+    let value: Value = serde_json::from_slice(&response.body)
+        .map_err(|e| format!("Invalid JSON from {}: {}", url, e))?;
+    Ok((url, value))
+}
+
+/// Base URL of the RDAP server for the query from the IANA bootstrap registry
+pub fn find_server(query: &RdapQuery, timeout: Duration) -> Result<String, String> {
+    let (_, registry) = get_json(
+        &format!("{}{}", BOOTSTRAP_BASE_URL, query.bootstrap_file()),
+        timeout,
+    )?;
+    let services = registry["services"]
+        .as_array()
+        .ok_or("Invalid bootstrap registry".to_string())?;
+    // [[entries], [urls]]
+    let mut best: Option<(usize, &Vec<Value>)> = None;
+    for service in services {
+        let (entries, urls) = match (service[0].as_array(), service[1].as_array()) {
+            (Some(entries), Some(urls)) => (entries, urls),
+            _ => continue,
+        };
+        for entry in entries.iter().filter_map(|e| e.as_str()) {
+            if !query.matches(entry) {
+                continue;
+            }
+            let len: usize = query.match_len(entry);
+            if best.map_or(true, |(best_len, _)| len > best_len) {
+                best = Some((len, urls));
+            }
+        }
+    }
+    let urls: &Vec<Value> = match best {
+        Some((_, urls)) => urls,
+        None => return Err("No RDAP server in the bootstrap registry".to_string()),
+    };
+    let urls: Vec<&str> = urls.iter().filter_map(|u| u.as_str()).collect();
+    urls.iter()
+        .find(|u| u.starts_with("https://"))
+        .or(urls.first())
+        .map(|u| u.to_string())
+        .ok_or("No RDAP server in the bootstrap registry".to_string())
+}
+
+fn as_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// fn and email from jCard (RFC 7095)
+fn parse_vcard(vcard: &Value) -> (String, String) {
+    let mut name: String = String::new();
+    let mut email: String = String::new();
+    if let Some(properties) = vcard[1].as_array() {
+        for property in properties {
+            match property[0].as_str() {
+                Some("fn") if name.is_empty() => name = as_string(&property[3]),
+                Some("email") if email.is_empty() => email = as_string(&property[3]),
+                _ => {}
+            }
+        }
+    }
+    (name, email)
+}
+
+/// Entities including nested ones (e.g. abuse contact of the registrant)
+fn collect_entities(value: &Value, entities: &mut Vec<RdapEntity>) {
+    let list = match value["entities"].as_array() {
+        Some(list) => list,
+        None => return,
+    };
+    for entity in list {
+        let (name, email) = parse_vcard(&entity["vcardArray"]);
+        entities.push(RdapEntity {
+            handle: as_string(&entity["handle"]),
+            roles: entity["roles"]
+                .as_array()
+                .map(|roles| roles.iter().map(as_string).collect())
+                .unwrap_or_default(),
+            name: name,
+            email: email,
+        });
+        collect_entities(entity, entities);
+    }
+}
+
+fn parse_result(query: RdapQuery, url: String, value: Value, lookup_time: Duration) -> RdapResult {
+    let status: Vec<String> = value["status"]
+        .as_array()
+        .map(|list| list.iter().map(as_string).collect())
+        .unwrap_or_default();
+    let range: String = match value["objectClassName"].as_str() {
+        Some("ip network") => {
+            let cidrs: Vec<String> = value["cidr0_cidrs"]
+                .as_array()
+                .map(|list| {
+                    list.iter()
+                        .map(|c| {
+                            let prefix: String = if c["v4prefix"].is_string() {
+                                as_string(&c["v4prefix"])
+                            } else {
+                                as_string(&c["v6prefix"])
+                            };
+                            format!("{}/{}", prefix, as_string(&c["length"]))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            if cidrs.is_empty() {
+                format!(
+                    "{} - {}",
+                    as_string(&value["startAddress"]),
+                    as_string(&value["endAddress"])
+                )
+            } else {
+                cidrs.join(", ")
+            }
+        }
+        Some("autnum") => {
+            let start: String = as_string(&value["startAutnum"]);
+            let end: String = as_string(&value["endAutnum"]);
+            if start == end {
+                format!("AS{}", start)
+            } else {
+                format!("AS{} - AS{}", start, end)
+            }
+        }
+        _ => String::new(),
+    };
+    let events: Vec<RdapEvent> = value["events"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .map(|e| RdapEvent {
+                    action: as_string(&e["eventAction"]),
+                    date: as_string(&e["eventDate"]),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut entities: Vec<RdapEntity> = Vec::new();
+    collect_entities(&value, &mut entities);
+    let name_servers: Vec<String> = value["nameservers"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .map(|ns| as_string(&ns["ldhName"]).to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    // Domains carry the name in ldhName
+    let name: String = match value["name"].as_str() {
+        Some(name) => name.to_string(),
+        None => as_string(&value["ldhName"]).to_lowercase(),
+    };
+    RdapResult {
+        query: query,
+        url: url,
+        object_class: as_string(&value["objectClassName"]),
+        handle: as_string(&value["handle"]),
+        name: name,
+        range: range,
+        country: as_string(&value["country"]),
+        status: status,
+        events: events,
+        entities: entities,
+        name_servers: name_servers,
+        raw: value,
+        lookup_time: lookup_time,
+    }
+}
+
+/// RDAP lookup. The server is selected from the bootstrap registry unless specified
+pub fn lookup(
+    query: RdapQuery,
+    server: Option<&str>,
+    timeout: Duration,
+) -> Result<RdapResult, String> {
+    let start_time: Instant = Instant::now();
+    let base_url: String = match server {
+        Some(server) => server.to_string(),
+        None => find_server(&query, timeout)?,
+    };
+    let url: String = format!("{}/{}", base_url.trim_end_matches('/'), query.path());
+    let (url, value) = get_json(&url, timeout)?;
+    Ok(parse_result(query, url, value, start_time.elapsed()))
+}